@@ -5,6 +5,7 @@ This file contains all database migrations in chronological order.
 Each migration is versioned and idempotent.
 """
 
+import json
 import logging
 import os
 import sys
@@ -5602,3 +5603,1472 @@ def migration_058_create_recommendation_cache(conn, db_type: str) -> None:
         raise
     finally:
         cursor.close()
+
+
+@register_migration("059", "add_api_key_scopes_and_expiry", "Add Name, Scopes, ExpiresAt, LastUsed and Revoked columns to APIKeys for scoped, expiring keys", requires=["001"])
+def migration_059_add_api_key_scopes_and_expiry(conn, db_type: str) -> None:
+    """Scoped, expiring API keys.
+
+    Scopes is a comma-separated list of scope names (read, playback, feed, admin) checked
+    centrally by the API's key-policy middleware. NULL means an unrestricted key, which is
+    what every existing key (and every key issued at login) is, so no backfill is needed.
+    ExpiresAt NULL means the key never expires; Revoked keys are kept (rather than deleted)
+    so the key list still shows them with their LastUsed time."""
+    logger.info("Starting migration 059: add scope/expiry columns to APIKeys")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            for col_name, col_def in (
+                ("name", "VARCHAR(100)"),
+                ("scopes", "VARCHAR(255)"),
+                ("expiresat", "TIMESTAMP"),
+                ("lastused", "TIMESTAMP"),
+                ("revoked", "BOOLEAN NOT NULL DEFAULT FALSE"),
+            ):
+                cursor.execute(f'ALTER TABLE "APIKeys" ADD COLUMN IF NOT EXISTS {col_name} {col_def}')
+        else:  # MySQL / MariaDB
+            for col_name, col_def in (
+                ("Name", "VARCHAR(100)"),
+                ("Scopes", "VARCHAR(255)"),
+                ("ExpiresAt", "TIMESTAMP NULL DEFAULT NULL"),
+                ("LastUsed", "TIMESTAMP NULL DEFAULT NULL"),
+                ("Revoked", "BOOLEAN NOT NULL DEFAULT FALSE"),
+            ):
+                cursor.execute(
+                    """
+                    SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'APIKeys' AND COLUMN_NAME = %s
+                    """,
+                    (col_name,),
+                )
+                if not cursor.fetchone():
+                    cursor.execute(f"ALTER TABLE APIKeys ADD COLUMN {col_name} {col_def}")
+                    logger.info(f"Added column {col_name} to APIKeys (MySQL)")
+
+        logger.info("APIKeys scope/expiry migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in APIKeys scope/expiry migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("060", "add_full_text_search_indexes", "Add full-text search indexes over episode titles/descriptions and generated transcripts", requires=["001", "051"])
+def migration_060_add_full_text_search_indexes(conn, db_type: str) -> None:
+    """Native full-text search over episodes and transcripts.
+
+    PostgreSQL gets a stored generated tsvector column (title weighted above description) on
+    Episodes and one over TranscriptText on EpisodeTranscripts, each with a GIN index. The 'simple'
+    text-search configuration is used rather than 'english' because libraries are multilingual and
+    stemming for the wrong language hurts more than it helps. MySQL/MariaDB get FULLTEXT indexes on
+    the same columns; the MATCH() column lists in the API must stay identical to these indexes."""
+    logger.info("Starting migration 060: full-text search indexes")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                ALTER TABLE "Episodes" ADD COLUMN IF NOT EXISTS searchvector tsvector
+                GENERATED ALWAYS AS (
+                    setweight(to_tsvector('simple', coalesce(episodetitle, '')), 'A') ||
+                    setweight(to_tsvector('simple', coalesce(episodedescription, '')), 'B')
+                ) STORED
+            """)
+            cursor.execute("""
+                ALTER TABLE "EpisodeTranscripts" ADD COLUMN IF NOT EXISTS searchvector tsvector
+                GENERATED ALWAYS AS (to_tsvector('simple', coalesce(transcripttext, ''))) STORED
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_episodes_searchvector ON "Episodes" USING GIN (searchvector);
+                CREATE INDEX IF NOT EXISTS idx_episode_transcripts_searchvector ON "EpisodeTranscripts" USING GIN (searchvector);
+            """)
+        else:  # MySQL / MariaDB
+            for table, index_name, columns in (
+                ("Episodes", "ft_episodes_title_description", "EpisodeTitle, EpisodeDescription"),
+                ("EpisodeTranscripts", "ft_episode_transcripts_text", "TranscriptText"),
+            ):
+                cursor.execute(
+                    """
+                    SELECT COUNT(*) FROM INFORMATION_SCHEMA.STATISTICS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = %s AND INDEX_NAME = %s
+                    """,
+                    (table, index_name),
+                )
+                if cursor.fetchone()[0] == 0:
+                    cursor.execute(f"ALTER TABLE {table} ADD FULLTEXT INDEX {index_name} ({columns})")
+                    logger.info(f"Added FULLTEXT index {index_name} on {table} (MySQL)")
+
+        logger.info("Full-text search migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in full-text search migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("061", "create_podcasting_namespace_tables", "Persist parsed Podcasting 2.0 tags per episode and per podcast", requires=["001"])
+def migration_061_create_podcasting_namespace_tables(conn, db_type: str) -> None:
+    """Tables holding the Podcasting 2.0 (podcast: namespace) tags parsed at refresh time.
+
+    EpisodePodcastingData holds the per-item tags (chapters URL, transcripts, persons, soundbites,
+    season/episode, value) as a JSON document in Data, with season/episode and the chapters URL
+    also broken out as columns. ChaptersData caches the remote chapters JSON the first time an
+    episode is opened. PodcastPodcastingData holds the channel-level tags (podcast:guid, funding,
+    value, persons, podroll). Rows are upserted on refresh and never deleted by it, so the data
+    survives the feed dropping old items."""
+    logger.info("Starting migration 061: Podcasting 2.0 tag tables")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "EpisodePodcastingData" (
+                    EpisodeID INT PRIMARY KEY,
+                    SeasonNumber INT,
+                    EpisodeNumber DOUBLE PRECISION,
+                    ChaptersURL TEXT,
+                    ChaptersData TEXT,
+                    Data TEXT NOT NULL,
+                    UpdatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (EpisodeID) REFERENCES "Episodes"(EpisodeID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "PodcastPodcastingData" (
+                    PodcastID INT PRIMARY KEY,
+                    PodcastGUID VARCHAR(255),
+                    Data TEXT NOT NULL,
+                    UpdatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (PodcastID) REFERENCES "Podcasts"(PodcastID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_podcast_podcasting_data_guid ON "PodcastPodcastingData"(PodcastGUID);
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS EpisodePodcastingData (
+                    EpisodeID INT PRIMARY KEY,
+                    SeasonNumber INT,
+                    EpisodeNumber DOUBLE,
+                    ChaptersURL TEXT,
+                    ChaptersData LONGTEXT,
+                    Data LONGTEXT NOT NULL,
+                    UpdatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS PodcastPodcastingData (
+                    PodcastID INT PRIMARY KEY,
+                    PodcastGUID VARCHAR(255),
+                    Data LONGTEXT NOT NULL,
+                    UpdatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (PodcastID) REFERENCES Podcasts(PodcastID) ON DELETE CASCADE
+                )
+            """)
+            try:
+                cursor.execute("CREATE INDEX idx_podcast_podcasting_data_guid ON PodcastPodcastingData(PodcastGUID)")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("Podcasting 2.0 tag tables migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in Podcasting 2.0 tag tables migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("062", "create_websub_subscriptions", "Track WebSub (PubSubHubbub) push subscriptions for feeds that advertise a hub", requires=["001"])
+def migration_062_create_websub_subscriptions(conn, db_type: str) -> None:
+    """One row per feed URL with a WebSub hub, shared by every subscriber of that feed.
+
+    Status moves pending -> verified once the hub confirms the callback, and to denied, failed or
+    unsubscribing when the hub refuses us, cannot be reached or the feed stops advertising it.
+    Only verified rows with an unexpired lease exempt a feed from the scheduled poll, so every
+    other state falls back to polling. CallbackToken identifies the row in the callback URL and
+    Secret is the hub.secret used to verify the HMAC signature on pushed content."""
+    logger.info("Starting migration 062: WebSub subscriptions")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "WebSubSubscriptions" (
+                    SubscriptionID SERIAL PRIMARY KEY,
+                    FeedURL TEXT NOT NULL,
+                    HubURL TEXT NOT NULL,
+                    TopicURL TEXT NOT NULL,
+                    CallbackToken VARCHAR(64) NOT NULL UNIQUE,
+                    Secret VARCHAR(64) NOT NULL,
+                    Status VARCHAR(20) NOT NULL DEFAULT 'pending',
+                    LeaseSeconds INT,
+                    ExpiresAt TIMESTAMP,
+                    RequestedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    LastPushAt TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_websub_subscriptions_feedurl ON "WebSubSubscriptions"(FeedURL);
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS WebSubSubscriptions (
+                    SubscriptionID INT AUTO_INCREMENT PRIMARY KEY,
+                    FeedURL TEXT NOT NULL,
+                    HubURL TEXT NOT NULL,
+                    TopicURL TEXT NOT NULL,
+                    CallbackToken VARCHAR(64) NOT NULL UNIQUE,
+                    Secret VARCHAR(64) NOT NULL,
+                    Status VARCHAR(20) NOT NULL DEFAULT 'pending',
+                    LeaseSeconds INT,
+                    ExpiresAt TIMESTAMP NULL,
+                    RequestedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    LastPushAt TIMESTAMP NULL,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            try:
+                cursor.execute("CREATE INDEX idx_websub_subscriptions_feedurl ON WebSubSubscriptions(FeedURL(255))")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("WebSub subscriptions migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in WebSub subscriptions migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+def _consolidate_shared_content(cursor, db_type: str) -> None:
+    """Move generated transcripts and auto-detected skip segments onto each feed episode's content
+    episode (the lowest EpisodeID sharing a FeedEpisodeID), which is where the server now reads
+    and writes them. Only one sibling's rows are moved per source, so nothing is duplicated, and
+    the AdsDetected/SilenceDetected guards are carried over."""
+    def table(name):
+        return f'"{name}"' if db_type == "postgresql" else name
+
+    cursor.execute(f"SELECT EpisodeID, FeedEpisodeID FROM {table('Episodes')} WHERE FeedEpisodeID IS NOT NULL")
+    content_for_feed_episode = {}
+    feed_episode_of = {}
+    for episode_id, feed_episode_id in cursor.fetchall():
+        feed_episode_of[episode_id] = feed_episode_id
+        current = content_for_feed_episode.get(feed_episode_id)
+        if current is None or episode_id < current:
+            content_for_feed_episode[feed_episode_id] = episode_id
+    content_of = {ep: content_for_feed_episode[fe] for ep, fe in feed_episode_of.items()}
+
+    for content_table, sources in (
+        ("EpisodeTranscripts", ("generated",)),
+        ("EpisodeSkipSegments", ("auto-ad", "auto-silence")),
+    ):
+        placeholders = ", ".join(["%s"] * len(sources))
+        cursor.execute(
+            f"SELECT DISTINCT EpisodeID, Source FROM {table(content_table)} "
+            f"WHERE EpisodeID IS NOT NULL AND Source IN ({placeholders})",
+            sources,
+        )
+        present = {(row[0], row[1]) for row in cursor.fetchall()}
+        donors = {}
+        for episode_id, source in sorted(present):
+            content_id = content_of.get(episode_id)
+            if content_id is None or content_id == episode_id:
+                continue
+            if (content_id, source) in present or (content_id, source) in donors:
+                continue
+            donors[(content_id, source)] = episode_id
+        if donors:
+            cursor.executemany(
+                f"UPDATE {table(content_table)} SET EpisodeID = %s WHERE EpisodeID = %s AND Source = %s",
+                [(content_id, donor, source) for (content_id, source), donor in donors.items()],
+            )
+            logger.info(f"Moved {len(donors)} {content_table} result set(s) onto shared content episodes")
+
+    for flag in ("AdsDetected", "SilenceDetected"):
+        cursor.execute(
+            f"SELECT EpisodeID FROM {table('Episodes')} WHERE FeedEpisodeID IS NOT NULL AND {flag} = TRUE"
+        )
+        flagged = {row[0] for row in cursor.fetchall()}
+        to_flag = {content_of[ep] for ep in flagged if ep in content_of} - flagged
+        if to_flag:
+            cursor.executemany(
+                f"UPDATE {table('Episodes')} SET {flag} = TRUE WHERE EpisodeID = %s",
+                [(ep,) for ep in sorted(to_flag)],
+            )
+
+
+@register_migration("063", "create_shared_feed_layer", "Shared canonical Feeds/FeedEpisodes keyed by feed URL, linked from per-user Podcasts and Episodes", requires=["001", "045", "050", "051", "056"])
+def migration_063_create_shared_feed_layer(conn, db_type: str) -> None:
+    """One Feeds row per distinct feed (URL + credentials) and one FeedEpisodes row per distinct
+    episode within it, shared by every subscriber. Podcasts.FeedID and Episodes.FeedEpisodeID link
+    the per-user subscription/state rows to that shared identity, and existing data is linked here.
+
+    FeedKey is MD5(FeedURL + newline + Username). EpisodeKey is MD5 of 'g:' + GUID, else 'u:' + the
+    enclosure URL without its query string, else 't:' + title. Both must stay identical to the
+    expressions in rust-api/src/services/shared_feeds.rs, which links rows added after this runs."""
+    logger.info("Starting migration 063: shared feed layer")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "Feeds" (
+                    FeedID SERIAL PRIMARY KEY,
+                    FeedKey CHAR(32) NOT NULL UNIQUE,
+                    FeedURL TEXT NOT NULL,
+                    FeedUsername TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "FeedEpisodes" (
+                    FeedEpisodeID SERIAL PRIMARY KEY,
+                    FeedID INT NOT NULL,
+                    EpisodeKey CHAR(32) NOT NULL,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE (FeedID, EpisodeKey),
+                    FOREIGN KEY (FeedID) REFERENCES "Feeds"(FeedID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute('ALTER TABLE "Podcasts" ADD COLUMN IF NOT EXISTS feedid INT REFERENCES "Feeds"(feedid) ON DELETE SET NULL')
+            cursor.execute('ALTER TABLE "Episodes" ADD COLUMN IF NOT EXISTS feedepisodeid INT REFERENCES "FeedEpisodes"(feedepisodeid) ON DELETE SET NULL')
+            cursor.execute('CREATE INDEX IF NOT EXISTS idx_podcasts_feedid ON "Podcasts"(feedid)')
+            cursor.execute('CREATE INDEX IF NOT EXISTS idx_episodes_feedepisodeid ON "Episodes"(feedepisodeid)')
+
+            episode_key = """md5(CASE WHEN COALESCE(e.episodeguid, '') <> '' THEN 'g:' || e.episodeguid
+                WHEN COALESCE(e.episodeurl, '') <> '' THEN 'u:' || split_part(e.episodeurl, '?', 1)
+                ELSE 't:' || COALESCE(e.episodetitle, '') END)"""
+            cursor.execute("""
+                INSERT INTO "Feeds" (feedkey, feedurl, feedusername)
+                SELECT DISTINCT ON (md5(feedurl || chr(10) || COALESCE(username, '')))
+                       md5(feedurl || chr(10) || COALESCE(username, '')), feedurl, username
+                FROM "Podcasts" WHERE feedid IS NULL
+                ON CONFLICT (feedkey) DO NOTHING
+            """)
+            cursor.execute("""
+                UPDATE "Podcasts" p SET feedid = f.feedid
+                FROM "Feeds" f
+                WHERE p.feedid IS NULL AND f.feedkey = md5(p.feedurl || chr(10) || COALESCE(p.username, ''))
+            """)
+            cursor.execute(f"""
+                INSERT INTO "FeedEpisodes" (feedid, episodekey)
+                SELECT DISTINCT p.feedid, {episode_key}
+                FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+                WHERE e.feedepisodeid IS NULL AND p.feedid IS NOT NULL
+                ON CONFLICT (feedid, episodekey) DO NOTHING
+            """)
+            cursor.execute(f"""
+                UPDATE "Episodes" e SET feedepisodeid = fe.feedepisodeid
+                FROM "Podcasts" p, "FeedEpisodes" fe
+                WHERE e.feedepisodeid IS NULL AND p.podcastid = e.podcastid
+                  AND fe.feedid = p.feedid AND fe.episodekey = {episode_key}
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS Feeds (
+                    FeedID INT AUTO_INCREMENT PRIMARY KEY,
+                    FeedKey CHAR(32) NOT NULL UNIQUE,
+                    FeedURL TEXT NOT NULL,
+                    FeedUsername TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS FeedEpisodes (
+                    FeedEpisodeID INT AUTO_INCREMENT PRIMARY KEY,
+                    FeedID INT NOT NULL,
+                    EpisodeKey CHAR(32) NOT NULL,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE KEY uq_feed_episodes_key (FeedID, EpisodeKey),
+                    FOREIGN KEY (FeedID) REFERENCES Feeds(FeedID) ON DELETE CASCADE
+                )
+            """)
+            for table, col_name, ref_table, ref_col in (
+                ("Podcasts", "FeedID", "Feeds", "FeedID"),
+                ("Episodes", "FeedEpisodeID", "FeedEpisodes", "FeedEpisodeID"),
+            ):
+                cursor.execute(
+                    """
+                    SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = %s AND COLUMN_NAME = %s
+                    """,
+                    (table, col_name),
+                )
+                if not cursor.fetchone():
+                    cursor.execute(f"ALTER TABLE {table} ADD COLUMN {col_name} INT NULL")
+                    cursor.execute(
+                        f"ALTER TABLE {table} ADD CONSTRAINT fk_{table.lower()}_{col_name.lower()} "
+                        f"FOREIGN KEY ({col_name}) REFERENCES {ref_table}({ref_col}) ON DELETE SET NULL"
+                    )
+                    logger.info(f"Added column {col_name} to {table} (MySQL)")
+
+            episode_key = """MD5(CASE WHEN COALESCE(e.EpisodeGUID, '') <> '' THEN CONCAT('g:', e.EpisodeGUID)
+                WHEN COALESCE(e.EpisodeURL, '') <> '' THEN CONCAT('u:', SUBSTRING_INDEX(e.EpisodeURL, '?', 1))
+                ELSE CONCAT('t:', COALESCE(e.EpisodeTitle, '')) END)"""
+            cursor.execute("""
+                INSERT IGNORE INTO Feeds (FeedKey, FeedURL, FeedUsername)
+                SELECT MD5(CONCAT(FeedURL, CHAR(10 USING utf8mb4), COALESCE(Username, ''))), FeedURL, Username
+                FROM Podcasts WHERE FeedID IS NULL
+            """)
+            cursor.execute("""
+                UPDATE Podcasts p
+                JOIN Feeds f ON f.FeedKey = MD5(CONCAT(p.FeedURL, CHAR(10 USING utf8mb4), COALESCE(p.Username, '')))
+                SET p.FeedID = f.FeedID
+                WHERE p.FeedID IS NULL
+            """)
+            cursor.execute(f"""
+                INSERT IGNORE INTO FeedEpisodes (FeedID, EpisodeKey)
+                SELECT DISTINCT p.FeedID, {episode_key}
+                FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                WHERE e.FeedEpisodeID IS NULL AND p.FeedID IS NOT NULL
+            """)
+            cursor.execute(f"""
+                UPDATE Episodes e
+                JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                JOIN FeedEpisodes fe ON fe.FeedID = p.FeedID AND fe.EpisodeKey = {episode_key}
+                SET e.FeedEpisodeID = fe.FeedEpisodeID
+                WHERE e.FeedEpisodeID IS NULL
+            """)
+
+        _consolidate_shared_content(cursor, db_type)
+        logger.info("Shared feed layer migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in shared feed layer migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("064", "add_storage_quotas_and_retention", "Per-user download quotas and per-podcast keep-last-N / delete-after-played retention rules", requires=["001", "005"])
+def migration_064_add_storage_quotas_and_retention(conn, db_type: str) -> None:
+    """Users.DownloadQuotaBytes caps the bytes of server downloads a user may hold (NULL means
+    unlimited) and Users.QuotaPolicy says what happens when a download would exceed it: 'refuse'
+    fails the download, 'evict' deletes the user's oldest downloads (played ones first) to make
+    room. Podcasts.KeepLastEpisodes keeps only the newest N downloaded episodes of a podcast
+    (0 keeps all) and Podcasts.DeleteAfterPlayed removes a download once the episode is completed."""
+    logger.info("Starting migration 064: storage quotas and retention rules")
+    cursor = conn.cursor()
+
+    try:
+        columns = (
+            ("Users", "DownloadQuotaBytes", "BIGINT"),
+            ("Users", "QuotaPolicy", "VARCHAR(10) NOT NULL DEFAULT 'refuse'"),
+            ("Podcasts", "KeepLastEpisodes", "INT NOT NULL DEFAULT 0"),
+            ("Podcasts", "DeleteAfterPlayed", "BOOLEAN NOT NULL DEFAULT FALSE"),
+        )
+        if db_type == "postgresql":
+            for table, col_name, col_def in columns:
+                cursor.execute(f'ALTER TABLE "{table}" ADD COLUMN IF NOT EXISTS {col_name.lower()} {col_def}')
+        else:  # MySQL / MariaDB
+            for table, col_name, col_def in columns:
+                cursor.execute(
+                    """
+                    SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = %s AND COLUMN_NAME = %s
+                    """,
+                    (table, col_name),
+                )
+                if not cursor.fetchone():
+                    cursor.execute(f"ALTER TABLE {table} ADD COLUMN {col_name} {col_def}")
+                    logger.info(f"Added column {col_name} to {table} (MySQL)")
+
+        logger.info("Storage quotas and retention migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in storage quotas and retention migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("065", "add_serve_cut_audio_to_podcasts", "Per-podcast opt-in to serve downloads with ad and silence segments cut out", requires=["005", "056"])
+def migration_065_add_serve_cut_audio_to_podcasts(conn, db_type: str) -> None:
+    """Podcasts.ServeCutAudio makes the stream endpoint (and so the generated RSS feed) serve a
+    rendered copy of a downloaded episode with its skipped ad and silence segments removed,
+    instead of leaving clients to honour EpisodeSkipSegments themselves."""
+    logger.info("Starting migration 065: serve cut audio toggle")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute(
+                'ALTER TABLE "Podcasts" ADD COLUMN IF NOT EXISTS servecutaudio BOOLEAN NOT NULL DEFAULT FALSE'
+            )
+        else:  # MySQL / MariaDB
+            cursor.execute(
+                """
+                SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'Podcasts' AND COLUMN_NAME = 'ServeCutAudio'
+                """
+            )
+            if not cursor.fetchone():
+                cursor.execute("ALTER TABLE Podcasts ADD COLUMN ServeCutAudio BOOLEAN NOT NULL DEFAULT FALSE")
+                logger.info("Added column ServeCutAudio to Podcasts (MySQL)")
+
+        logger.info("Serve cut audio migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in serve cut audio migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("066", "create_scrobble_tables", "Per-user scrobble targets (ListenBrainz, webhook) and a delivery queue with retry state", requires=["001"])
+def migration_066_create_scrobble_tables(conn, db_type: str) -> None:
+    """ScrobbleTargets holds each user's external listen-tracking destinations. Kind is
+    'listenbrainz' or 'webhook'; Endpoint overrides the ListenBrainz API root or is the webhook
+    URL; Token is Fernet-encrypted like the gpodder passwords.
+
+    ScrobbleQueue holds one row per (target, episode) listen to deliver. Payload is the listen as
+    captured when it qualified, so later episode edits or deletions don't change what is sent.
+    Rows stay pending with an exponentially growing NextAttemptAt until they are sent or give up
+    (failed)."""
+    logger.info("Starting migration 066: scrobble targets and queue")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "ScrobbleTargets" (
+                    TargetID SERIAL PRIMARY KEY,
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    Kind VARCHAR(20) NOT NULL,
+                    Endpoint TEXT,
+                    Token TEXT,
+                    Enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                    LastSuccessAt TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_scrobble_targets_userid ON "ScrobbleTargets"(UserID);
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "ScrobbleQueue" (
+                    ScrobbleID SERIAL PRIMARY KEY,
+                    TargetID INT NOT NULL REFERENCES "ScrobbleTargets"(TargetID) ON DELETE CASCADE,
+                    EpisodeID INT NOT NULL,
+                    Payload TEXT NOT NULL,
+                    Status VARCHAR(10) NOT NULL DEFAULT 'pending',
+                    Attempts INT NOT NULL DEFAULT 0,
+                    NextAttemptAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    SentAt TIMESTAMP,
+                    UNIQUE (TargetID, EpisodeID)
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_scrobble_queue_due ON "ScrobbleQueue"(Status, NextAttemptAt);
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS ScrobbleTargets (
+                    TargetID INT AUTO_INCREMENT PRIMARY KEY,
+                    UserID INT NOT NULL,
+                    Kind VARCHAR(20) NOT NULL,
+                    Endpoint TEXT,
+                    Token TEXT,
+                    Enabled TINYINT(1) NOT NULL DEFAULT 1,
+                    LastSuccessAt TIMESTAMP NULL,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS ScrobbleQueue (
+                    ScrobbleID INT AUTO_INCREMENT PRIMARY KEY,
+                    TargetID INT NOT NULL,
+                    EpisodeID INT NOT NULL,
+                    Payload TEXT NOT NULL,
+                    Status VARCHAR(10) NOT NULL DEFAULT 'pending',
+                    Attempts INT NOT NULL DEFAULT 0,
+                    NextAttemptAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    SentAt TIMESTAMP NULL,
+                    UNIQUE KEY unique_target_episode (TargetID, EpisodeID),
+                    FOREIGN KEY (TargetID) REFERENCES ScrobbleTargets(TargetID) ON DELETE CASCADE
+                )
+            """)
+            try:
+                cursor.execute("CREATE INDEX idx_scrobble_queue_due ON ScrobbleQueue(Status, NextAttemptAt)")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("Scrobble tables migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in scrobble tables migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("067", "create_local_book_tables", "Audiobook-mode local podcasts: one episode per book backed by an ordered list of part files", requires=["001"])
+def migration_067_create_local_book_tables(conn, db_type: str) -> None:
+    """LocalBooks marks a local podcast as an audiobook and points at the single Episodes row that
+    represents the whole book, so the book has one resume position and one feed item.
+
+    LocalBookParts lists the book's files in playback order (disc, then track, then filename).
+    StartOffset is where each part begins on the joined timeline, in seconds; the stream endpoint
+    concatenates the parts in PartIndex order. Chapters is the part's own embedded chapters (JSON),
+    kept so a refresh can re-merge the book's chapters without re-reading every file."""
+    logger.info("Starting migration 067: local audiobook tables")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "LocalBooks" (
+                    PodcastID INT PRIMARY KEY REFERENCES "Podcasts"(PodcastID) ON DELETE CASCADE,
+                    EpisodeID INT NOT NULL REFERENCES "Episodes"(EpisodeID) ON DELETE CASCADE,
+                    UpdatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "LocalBookParts" (
+                    PodcastID INT NOT NULL REFERENCES "LocalBooks"(PodcastID) ON DELETE CASCADE,
+                    PartIndex INT NOT NULL,
+                    FilePath TEXT NOT NULL,
+                    Title TEXT NOT NULL,
+                    DiscNum INT,
+                    TrackNum INT,
+                    Duration INT NOT NULL DEFAULT 0,
+                    StartOffset INT NOT NULL DEFAULT 0,
+                    Chapters TEXT,
+                    PRIMARY KEY (PodcastID, PartIndex)
+                )
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS LocalBooks (
+                    PodcastID INT PRIMARY KEY,
+                    EpisodeID INT NOT NULL,
+                    UpdatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (PodcastID) REFERENCES Podcasts(PodcastID) ON DELETE CASCADE,
+                    FOREIGN KEY (EpisodeID) REFERENCES Episodes(EpisodeID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS LocalBookParts (
+                    PodcastID INT NOT NULL,
+                    PartIndex INT NOT NULL,
+                    FilePath TEXT NOT NULL,
+                    Title TEXT NOT NULL,
+                    DiscNum INT,
+                    TrackNum INT,
+                    Duration INT NOT NULL DEFAULT 0,
+                    StartOffset INT NOT NULL DEFAULT 0,
+                    Chapters TEXT,
+                    PRIMARY KEY (PodcastID, PartIndex),
+                    FOREIGN KEY (PodcastID) REFERENCES LocalBooks(PodcastID) ON DELETE CASCADE
+                )
+            """)
+
+        logger.info("Local audiobook tables migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in local audiobook tables migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("068", "create_sponsorblock_preferences", "SponsorBlock segments for YouTube videos: per-user category preferences + YouTubeVideos.SponsorBlockCheckedAt", requires=["001", "050", "056"])
+def migration_068_create_sponsorblock_preferences(conn, db_type: str) -> None:
+    """SponsorBlock community segments for YouTube subscriptions.
+
+    Segments are stored in EpisodeSkipSegments against the video (VideoID) with
+    Source='sponsorblock' and Kind set to the SponsorBlock category (sponsor, selfpromo, intro,
+    outro, interaction). Per-segment confirm/deny reuses EpisodeAdSkipReview.
+
+    UserSponsorBlockCategories holds each user's action per category: 'skip' (skip immediately),
+    'review' (held pending, like ads with AdSkipAutoActivate off) or 'off' (hidden). Categories
+    without a row use the built-in defaults.
+
+    YouTubeVideos.SponsorBlockCheckedAt records the last lookup so refreshes only re-query young
+    videos, whose segments are still being submitted."""
+    logger.info("Starting migration 068: SponsorBlock preferences")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "UserSponsorBlockCategories" (
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    Category VARCHAR(20) NOT NULL,
+                    Action VARCHAR(10) NOT NULL,
+                    PRIMARY KEY (UserID, Category)
+                )
+            """)
+            cursor.execute("""
+                SELECT column_name FROM information_schema.columns
+                WHERE table_name = 'YouTubeVideos' AND column_name = 'sponsorblockcheckedat'
+            """)
+            if not cursor.fetchone():
+                cursor.execute('ALTER TABLE "YouTubeVideos" ADD COLUMN sponsorblockcheckedat TIMESTAMP')
+                logger.info("Added sponsorblockcheckedat column to YouTubeVideos (PostgreSQL)")
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS UserSponsorBlockCategories (
+                    UserID INT NOT NULL,
+                    Category VARCHAR(20) NOT NULL,
+                    Action VARCHAR(10) NOT NULL,
+                    PRIMARY KEY (UserID, Category),
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                SELECT COLUMN_NAME FROM information_schema.COLUMNS
+                WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'YouTubeVideos' AND COLUMN_NAME = 'SponsorBlockCheckedAt'
+            """)
+            if not cursor.fetchone():
+                cursor.execute("ALTER TABLE YouTubeVideos ADD COLUMN SponsorBlockCheckedAt TIMESTAMP NULL")
+                logger.info("Added SponsorBlockCheckedAt column to YouTubeVideos (MySQL)")
+
+        logger.info("SponsorBlock preferences migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in SponsorBlock preferences migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("069", "add_source_kind_to_podcasts", "Podcasts.SourceKind says how a podcast is refreshed: 'rss', 'youtube' or 'ytdlp' (any other yt-dlp extractor)", requires=["005"])
+def migration_069_add_source_kind_to_podcasts(conn, db_type: str) -> None:
+    """Podcasts.SourceKind replaces IsYouTubeChannel as the way refreshes pick a source.
+
+    'rss' podcasts are fetched as feeds, 'youtube' ones from YouTube's Atom feeds and 'ytdlp'
+    ones by listing a playlist or channel page on any site yt-dlp has an extractor for (Vimeo,
+    SoundCloud, Bandcamp, Twitch VODs, PeerTube, ...). Both video kinds keep IsYouTubeChannel set,
+    since that flag is what routes their episodes through YouTubeVideos and the video
+    download/stream endpoints. Existing YouTube subscriptions are backfilled as 'youtube'."""
+    logger.info("Starting migration 069: podcast source kind")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                SELECT column_name FROM information_schema.columns
+                WHERE table_name = 'Podcasts' AND column_name = 'sourcekind'
+            """)
+            if not cursor.fetchone():
+                cursor.execute("""ALTER TABLE "Podcasts" ADD COLUMN sourcekind VARCHAR(20) NOT NULL DEFAULT 'rss'""")
+                cursor.execute("""UPDATE "Podcasts" SET sourcekind = 'youtube' WHERE isyoutubechannel = TRUE""")
+                logger.info("Added sourcekind column to Podcasts (PostgreSQL)")
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'Podcasts' AND COLUMN_NAME = 'SourceKind'
+            """)
+            if not cursor.fetchone():
+                cursor.execute("ALTER TABLE Podcasts ADD COLUMN SourceKind VARCHAR(20) NOT NULL DEFAULT 'rss'")
+                cursor.execute("UPDATE Podcasts SET SourceKind = 'youtube' WHERE IsYouTubeChannel = 1")
+                logger.info("Added SourceKind column to Podcasts (MySQL)")
+
+        logger.info("Podcast source kind migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in podcast source kind migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("070", "create_gpodder_sync_engine_tables", "Per-device episode action times, subscription tombstones and a per-run log for gpodder/Nextcloud sync", requires=["001"])
+def migration_070_create_gpodder_sync_engine_tables(conn, db_type: str) -> None:
+    """State the gpodder/Nextcloud sync engine keeps per (user, sync target).
+
+    GpodderDeviceEpisodeActions holds the newest episode action each remote device has reported,
+    so a device that comes back online with old positions can be recognised as stale.
+    GpodderSubscriptionTombstones records unsubscribes: local ones stay unpushed until the server
+    has accepted them (and win over a remote re-add until then), remote ones are kept for
+    reference. GpodderSyncLog has one row per sync run with its counts, the server cursors the next
+    incremental run asks for changes since, and LocalCursor, the time local episode actions
+    have been uploaded up to."""
+    logger.info("Starting migration 070: gpodder sync engine tables")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "GpodderDeviceEpisodeActions" (
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    SyncTarget TEXT NOT NULL,
+                    Device VARCHAR(255) NOT NULL,
+                    EpisodeURL TEXT NOT NULL,
+                    Action VARCHAR(20) NOT NULL,
+                    Position INT,
+                    ActionAt TIMESTAMP NOT NULL,
+                    PRIMARY KEY (UserID, SyncTarget, Device, EpisodeURL)
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "GpodderSubscriptionTombstones" (
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    SyncTarget TEXT NOT NULL,
+                    FeedURL TEXT NOT NULL,
+                    Origin VARCHAR(10) NOT NULL,
+                    Pushed BOOLEAN NOT NULL DEFAULT FALSE,
+                    RemovedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (UserID, SyncTarget, FeedURL)
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "GpodderSyncLog" (
+                    SyncLogID SERIAL PRIMARY KEY,
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    SyncTarget TEXT NOT NULL,
+                    Protocol VARCHAR(20) NOT NULL,
+                    FullSync BOOLEAN NOT NULL DEFAULT FALSE,
+                    Status VARCHAR(10) NOT NULL,
+                    StartedAt TIMESTAMP NOT NULL,
+                    FinishedAt TIMESTAMP NOT NULL,
+                    SubscriptionsAdded INT NOT NULL DEFAULT 0,
+                    SubscriptionsRemoved INT NOT NULL DEFAULT 0,
+                    SubscriptionsPushed INT NOT NULL DEFAULT 0,
+                    ActionsReceived INT NOT NULL DEFAULT 0,
+                    ActionsApplied INT NOT NULL DEFAULT 0,
+                    ActionsStale INT NOT NULL DEFAULT 0,
+                    ActionsUploaded INT NOT NULL DEFAULT 0,
+                    Conflicts INT NOT NULL DEFAULT 0,
+                    SubscriptionCursor BIGINT,
+                    ActionCursor BIGINT,
+                    LocalCursor TIMESTAMP,
+                    Error TEXT
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_gpodder_synclog_user_target ON "GpodderSyncLog"(UserID, SyncTarget, StartedAt)
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS GpodderDeviceEpisodeActions (
+                    ActionStateID INT AUTO_INCREMENT PRIMARY KEY,
+                    UserID INT NOT NULL,
+                    SyncTarget VARCHAR(512) NOT NULL,
+                    Device VARCHAR(255) NOT NULL,
+                    EpisodeURL VARCHAR(2048) NOT NULL,
+                    Action VARCHAR(20) NOT NULL,
+                    Position INT,
+                    ActionAt DATETIME NOT NULL,
+                    UNIQUE KEY unique_device_episode (UserID, SyncTarget(191), Device(64), EpisodeURL(255)),
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS GpodderSubscriptionTombstones (
+                    TombstoneID INT AUTO_INCREMENT PRIMARY KEY,
+                    UserID INT NOT NULL,
+                    SyncTarget VARCHAR(512) NOT NULL,
+                    FeedURL VARCHAR(2048) NOT NULL,
+                    Origin VARCHAR(10) NOT NULL,
+                    Pushed TINYINT(1) NOT NULL DEFAULT 0,
+                    RemovedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE KEY unique_tombstone (UserID, SyncTarget(191), FeedURL(512)),
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS GpodderSyncLog (
+                    SyncLogID INT AUTO_INCREMENT PRIMARY KEY,
+                    UserID INT NOT NULL,
+                    SyncTarget VARCHAR(512) NOT NULL,
+                    Protocol VARCHAR(20) NOT NULL,
+                    FullSync TINYINT(1) NOT NULL DEFAULT 0,
+                    Status VARCHAR(10) NOT NULL,
+                    StartedAt DATETIME NOT NULL,
+                    FinishedAt DATETIME NOT NULL,
+                    SubscriptionsAdded INT NOT NULL DEFAULT 0,
+                    SubscriptionsRemoved INT NOT NULL DEFAULT 0,
+                    SubscriptionsPushed INT NOT NULL DEFAULT 0,
+                    ActionsReceived INT NOT NULL DEFAULT 0,
+                    ActionsApplied INT NOT NULL DEFAULT 0,
+                    ActionsStale INT NOT NULL DEFAULT 0,
+                    ActionsUploaded INT NOT NULL DEFAULT 0,
+                    Conflicts INT NOT NULL DEFAULT 0,
+                    SubscriptionCursor BIGINT,
+                    ActionCursor BIGINT,
+                    LocalCursor DATETIME NULL,
+                    Error TEXT,
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            try:
+                cursor.execute("CREATE INDEX idx_gpodder_synclog_user_target ON GpodderSyncLog(UserID, SyncTarget(191), StartedAt)")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("Gpodder sync engine tables migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in gpodder sync engine tables migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("071", "create_background_jobs", "Persist background jobs (downloads, transcription, ad detection, feed refresh) with retry state so they survive restarts", requires=["001"])
+def migration_071_create_background_jobs(conn, db_type: str) -> None:
+    """BackgroundJobs holds one row per queued piece of background work. TaskID is the ID the task
+    list and websocket report it under; Kind and Payload describe the job, Pool is the worker pool
+    that runs it. Status is 'queued', 'running', 'completed', 'failed' or 'cancelled'; failed
+    attempts go back to 'queued' with a later NextAttemptAt. Jobs left 'running' by a restart are
+    queued again on startup."""
+    logger.info("Starting migration 071: background jobs")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "BackgroundJobs" (
+                    JobID SERIAL PRIMARY KEY,
+                    TaskID VARCHAR(36) NOT NULL UNIQUE,
+                    UserID INT NOT NULL,
+                    Kind VARCHAR(40) NOT NULL,
+                    Pool VARCHAR(20) NOT NULL,
+                    Payload TEXT NOT NULL,
+                    ItemID INT,
+                    Status VARCHAR(10) NOT NULL DEFAULT 'queued',
+                    Attempts INT NOT NULL DEFAULT 0,
+                    NextAttemptAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FinishedAt TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_background_jobs_due ON "BackgroundJobs"(Pool, Status, NextAttemptAt);
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS BackgroundJobs (
+                    JobID INT AUTO_INCREMENT PRIMARY KEY,
+                    TaskID VARCHAR(36) NOT NULL,
+                    UserID INT NOT NULL,
+                    Kind VARCHAR(40) NOT NULL,
+                    Pool VARCHAR(20) NOT NULL,
+                    Payload TEXT NOT NULL,
+                    ItemID INT NULL,
+                    Status VARCHAR(10) NOT NULL DEFAULT 'queued',
+                    Attempts INT NOT NULL DEFAULT 0,
+                    NextAttemptAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FinishedAt TIMESTAMP NULL,
+                    UNIQUE KEY unique_background_job_task (TaskID)
+                )
+            """)
+            try:
+                cursor.execute("CREATE INDEX idx_background_jobs_due ON BackgroundJobs(Pool, Status, NextAttemptAt)")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("Background jobs migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in background jobs migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("072", "background_jobs_claims", "Record which instance claimed a background job and when it last checked in, so replicas can share the queue", requires=["071"])
+def migration_072_background_jobs_claims(conn, db_type: str) -> None:
+    """ClaimedBy is the instance ID of the replica running a job; HeartbeatAt is refreshed while it
+    runs. A running job whose heartbeat goes stale belonged to a replica that died, and is queued
+    again by the others."""
+    logger.info("Starting migration 072: background job claims")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            for column, definition in (("claimedby", "VARCHAR(100)"), ("heartbeatat", "TIMESTAMP")):
+                cursor.execute("""
+                    SELECT column_name FROM information_schema.columns
+                    WHERE table_name = 'BackgroundJobs' AND column_name = %s
+                """, (column,))
+                if not cursor.fetchone():
+                    cursor.execute(f'ALTER TABLE "BackgroundJobs" ADD COLUMN {column} {definition}')
+                    logger.info(f"Added {column} column to BackgroundJobs (PostgreSQL)")
+        else:  # MySQL / MariaDB
+            for column, definition in (("ClaimedBy", "VARCHAR(100) NULL"), ("HeartbeatAt", "TIMESTAMP NULL")):
+                cursor.execute("""
+                    SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'BackgroundJobs' AND COLUMN_NAME = %s
+                """, (column,))
+                if not cursor.fetchone():
+                    cursor.execute(f"ALTER TABLE BackgroundJobs ADD COLUMN {column} {definition}")
+                    logger.info(f"Added {column} column to BackgroundJobs (MySQL)")
+
+        logger.info("Background job claims migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in background job claims migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("073", "create_webhook_tables", "Signed outbound webhooks for server events, per user and admin-wide, with a delivery log and retry state", requires=["001"])
+def migration_073_create_webhook_tables(conn, db_type: str) -> None:
+    """Webhooks holds the endpoints events are POSTed to. Scope 'user' endpoints receive their
+    owner's events; 'admin' endpoints, created by admins, receive every user's events plus
+    admin-only ones such as user.created. Events is a comma-separated list of event names, or '*'.
+    Secret signs each delivery (HMAC-SHA256) and is Fernet-encrypted like the gpodder passwords.
+
+    WebhookDeliveries is both the retry queue and the delivery log: one row per (endpoint, event),
+    with the JSON body captured when the event fired so retries send identical bytes. Rows stay
+    pending with an exponentially growing NextAttemptAt until they are delivered or give up
+    (failed); finished rows are pruned by the cleanup task."""
+    logger.info("Starting migration 073: webhooks and delivery log")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "Webhooks" (
+                    WebhookID SERIAL PRIMARY KEY,
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    Scope VARCHAR(10) NOT NULL DEFAULT 'user',
+                    Url TEXT NOT NULL,
+                    Secret TEXT NOT NULL,
+                    Events TEXT NOT NULL,
+                    Enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                    LastSuccessAt TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_webhooks_userid ON "Webhooks"(UserID);
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "WebhookDeliveries" (
+                    DeliveryID SERIAL PRIMARY KEY,
+                    WebhookID INT NOT NULL REFERENCES "Webhooks"(WebhookID) ON DELETE CASCADE,
+                    EventID VARCHAR(36) NOT NULL,
+                    Event VARCHAR(50) NOT NULL,
+                    Payload TEXT NOT NULL,
+                    Status VARCHAR(10) NOT NULL DEFAULT 'pending',
+                    Attempts INT NOT NULL DEFAULT 0,
+                    NextAttemptAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    ResponseStatus INT,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    DeliveredAt TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON "WebhookDeliveries"(Status, NextAttemptAt);
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON "WebhookDeliveries"(WebhookID, DeliveryID);
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS Webhooks (
+                    WebhookID INT AUTO_INCREMENT PRIMARY KEY,
+                    UserID INT NOT NULL,
+                    Scope VARCHAR(10) NOT NULL DEFAULT 'user',
+                    Url TEXT NOT NULL,
+                    Secret TEXT NOT NULL,
+                    Events TEXT NOT NULL,
+                    Enabled TINYINT(1) NOT NULL DEFAULT 1,
+                    LastSuccessAt TIMESTAMP NULL,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS WebhookDeliveries (
+                    DeliveryID INT AUTO_INCREMENT PRIMARY KEY,
+                    WebhookID INT NOT NULL,
+                    EventID VARCHAR(36) NOT NULL,
+                    Event VARCHAR(50) NOT NULL,
+                    Payload TEXT NOT NULL,
+                    Status VARCHAR(10) NOT NULL DEFAULT 'pending',
+                    Attempts INT NOT NULL DEFAULT 0,
+                    NextAttemptAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    ResponseStatus INT NULL,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    DeliveredAt TIMESTAMP NULL,
+                    INDEX idx_webhook_deliveries_webhook (WebhookID, DeliveryID),
+                    FOREIGN KEY (WebhookID) REFERENCES Webhooks(WebhookID) ON DELETE CASCADE
+                )
+            """)
+            try:
+                cursor.execute("CREATE INDEX idx_webhook_deliveries_due ON WebhookDeliveries(Status, NextAttemptAt)")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("Webhook tables migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in webhook tables migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("074", "create_notification_channels", "Multiple notification channels per user with message templates and per-podcast routing, replacing the one-row-per-platform UserNotificationSettings", requires=["001", "005", "011", "017", "033"])
+def migration_074_create_notification_channels(conn, db_type: str) -> None:
+    """NotificationChannels holds any number of channels per user (ntfy, Gotify, generic HTTP,
+    Matrix, Discord, Slack, Telegram, Pushover, email). Config is the platform's settings as JSON
+    (including a "platform" key), Fernet-encrypted because it carries tokens and webhook URLs.
+    TitleTemplate/MessageTemplate override the default new-episode text; NULL means the default.
+
+    PodcastNotificationChannels routes a podcast's new-episode notifications to a subset of its
+    owner's channels. A podcast with no rows notifies every enabled channel, so existing setups
+    keep working. Podcasts.NotificationsEnabled still switches a podcast's notifications on/off.
+
+    Existing UserNotificationSettings rows are copied into channels once; the old table is left in
+    place but no longer read."""
+    logger.info("Starting migration 074: notification channels")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "NotificationChannels" (
+                    ChannelID SERIAL PRIMARY KEY,
+                    UserID INT NOT NULL REFERENCES "Users"(UserID) ON DELETE CASCADE,
+                    Name VARCHAR(255) NOT NULL,
+                    Platform VARCHAR(20) NOT NULL,
+                    Enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                    Config TEXT NOT NULL,
+                    TitleTemplate TEXT,
+                    MessageTemplate TEXT,
+                    LastSentAt TIMESTAMP,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_notification_channels_userid ON "NotificationChannels"(UserID);
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS "PodcastNotificationChannels" (
+                    PodcastID INT NOT NULL REFERENCES "Podcasts"(PodcastID) ON DELETE CASCADE,
+                    ChannelID INT NOT NULL REFERENCES "NotificationChannels"(ChannelID) ON DELETE CASCADE,
+                    PRIMARY KEY (PodcastID, ChannelID)
+                )
+            """)
+            cursor.execute('SELECT COUNT(*) FROM "NotificationChannels"')
+            already_copied = cursor.fetchone()[0] > 0
+            cursor.execute("""
+                SELECT userid, platform, enabled, ntfytopic, ntfyserverurl, ntfyusername, ntfypassword,
+                       ntfyaccesstoken, gotifyurl, gotifytoken, httpurl, httptoken, httpmethod
+                FROM "UserNotificationSettings"
+            """)
+            legacy_rows = [] if already_copied else cursor.fetchall()
+            cursor.execute('SELECT encryptionkey FROM "AppSettings" WHERE appsettingsid = 1')
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS NotificationChannels (
+                    ChannelID INT AUTO_INCREMENT PRIMARY KEY,
+                    UserID INT NOT NULL,
+                    Name VARCHAR(255) NOT NULL,
+                    Platform VARCHAR(20) NOT NULL,
+                    Enabled TINYINT(1) NOT NULL DEFAULT 1,
+                    Config TEXT NOT NULL,
+                    TitleTemplate TEXT,
+                    MessageTemplate TEXT,
+                    LastSentAt TIMESTAMP NULL,
+                    LastError TEXT,
+                    CreatedAt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    INDEX idx_notification_channels_userid (UserID),
+                    FOREIGN KEY (UserID) REFERENCES Users(UserID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("""
+                CREATE TABLE IF NOT EXISTS PodcastNotificationChannels (
+                    PodcastID INT NOT NULL,
+                    ChannelID INT NOT NULL,
+                    PRIMARY KEY (PodcastID, ChannelID),
+                    FOREIGN KEY (PodcastID) REFERENCES Podcasts(PodcastID) ON DELETE CASCADE,
+                    FOREIGN KEY (ChannelID) REFERENCES NotificationChannels(ChannelID) ON DELETE CASCADE
+                )
+            """)
+            cursor.execute("SELECT COUNT(*) FROM NotificationChannels")
+            already_copied = cursor.fetchone()[0] > 0
+            cursor.execute("""
+                SELECT UserID, Platform, Enabled, NtfyTopic, NtfyServerUrl, NtfyUsername, NtfyPassword,
+                       NtfyAccessToken, GotifyUrl, GotifyToken, HttpUrl, HttpToken, HttpMethod
+                FROM UserNotificationSettings
+            """)
+            legacy_rows = [] if already_copied else cursor.fetchall()
+            cursor.execute("SELECT EncryptionKey FROM AppSettings WHERE AppSettingsID = 1")
+
+        key_row = cursor.fetchone()
+        copied = 0
+        if legacy_rows and key_row and key_row[0]:
+            key = key_row[0]
+            if isinstance(key, bytes):
+                key = key.rstrip(b'\x00').decode('utf-8')
+            fernet = Fernet(key.encode('utf-8'))
+
+            for (user_id, platform, enabled, ntfy_topic, ntfy_server_url, ntfy_username, ntfy_password,
+                 ntfy_access_token, gotify_url, gotify_token, http_url, http_token, http_method) in legacy_rows:
+                if platform == 'ntfy' and ntfy_topic:
+                    name = 'ntfy'
+                    config = {"platform": "ntfy", "topic": ntfy_topic, "server_url": ntfy_server_url,
+                              "username": ntfy_username, "password": ntfy_password,
+                              "access_token": ntfy_access_token}
+                elif platform == 'gotify' and gotify_url and gotify_token:
+                    name = 'Gotify'
+                    config = {"platform": "gotify", "url": gotify_url, "token": gotify_token}
+                elif platform == 'http' and http_url:
+                    name = 'HTTP'
+                    config = {"platform": "http", "url": http_url, "token": http_token,
+                              "method": http_method}
+                else:
+                    continue
+                encrypted = fernet.encrypt(json.dumps(config).encode('utf-8')).decode('utf-8')
+                if db_type == "postgresql":
+                    cursor.execute("""
+                        INSERT INTO "NotificationChannels" (UserID, Name, Platform, Enabled, Config)
+                        VALUES (%s, %s, %s, %s, %s)
+                    """, (user_id, name, platform, bool(enabled), encrypted))
+                else:
+                    cursor.execute("""
+                        INSERT INTO NotificationChannels (UserID, Name, Platform, Enabled, Config)
+                        VALUES (%s, %s, %s, %s, %s)
+                    """, (user_id, name, platform, 1 if enabled else 0, encrypted))
+                copied += 1
+
+        logger.info(f"Notification channels migration completed successfully ({copied} legacy settings copied)")
+
+    except Exception as e:
+        logger.error(f"Error in notification channels migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+def _move_content_to_feed_episodes(cursor, db_type: str) -> None:
+    """Re-key shared transcripts and skip segments from their content episode onto its
+    FeedEpisodeID. One sibling's rows per (feed episode, source) are moved, as in 063; copies left
+    on other siblings were never read and are removed."""
+    def table(name):
+        return f'"{name}"' if db_type == "postgresql" else name
+
+    for content_table in ("EpisodeTranscripts", "EpisodeSkipSegments"):
+        cursor.execute(
+            f"SELECT DISTINCT c.EpisodeID, c.Source, e.FeedEpisodeID FROM {table(content_table)} c "
+            f"JOIN {table('Episodes')} e ON e.EpisodeID = c.EpisodeID WHERE e.FeedEpisodeID IS NOT NULL"
+        )
+        donors = {}
+        extras = []
+        for episode_id, source, feed_episode_id in sorted(cursor.fetchall()):
+            if (feed_episode_id, source) in donors:
+                extras.append((episode_id, source))
+            else:
+                donors[(feed_episode_id, source)] = episode_id
+        if donors:
+            cursor.executemany(
+                f"UPDATE {table(content_table)} SET FeedEpisodeID = %s, EpisodeID = NULL "
+                f"WHERE EpisodeID = %s AND Source = %s",
+                [(feed_episode_id, donor, source) for (feed_episode_id, source), donor in donors.items()],
+            )
+        if extras:
+            cursor.executemany(
+                f"DELETE FROM {table(content_table)} WHERE EpisodeID = %s AND Source = %s",
+                extras,
+            )
+        logger.info(f"Re-keyed {len(donors)} {content_table} result set(s) onto feed episodes, dropped {len(extras)} duplicate(s)")
+
+
+@register_migration("075", "key_shared_content_by_feed_episode", "Store shared transcripts, skip segments and detection flags against FeedEpisodes instead of one subscriber's Episodes row", requires=["050", "051", "056", "063"])
+def migration_075_key_shared_content_by_feed_episode(conn, db_type: str) -> None:
+    """Since 063, shared transcripts and skip segments were stored against the lowest EpisodeID of
+    a feed episode. That row belongs to one subscriber and cascades on unsubscribe, taking the
+    shared results (and every user's ad reviews of those segments) with it.
+
+    EpisodeTranscripts and EpisodeSkipSegments gain a FeedEpisodeID owner, so a row now belongs to
+    exactly one of an episode (YouTube, local or not yet linked), a YouTube video, or a feed
+    episode. The AdsDetected/SilenceDetected guards move onto FeedEpisodes for the same reason."""
+    logger.info("Starting migration 075: key shared content by feed episode")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            for content_table, index in (
+                ("EpisodeTranscripts", "idx_episode_transcripts_feedepisodeid"),
+                ("EpisodeSkipSegments", "idx_skip_segments_feedepisodeid"),
+            ):
+                cursor.execute(
+                    f'ALTER TABLE "{content_table}" ADD COLUMN IF NOT EXISTS feedepisodeid INT '
+                    f'REFERENCES "FeedEpisodes"(feedepisodeid) ON DELETE CASCADE'
+                )
+                cursor.execute(f'CREATE INDEX IF NOT EXISTS {index} ON "{content_table}"(feedepisodeid)')
+                cursor.execute(
+                    "SELECT conname FROM pg_constraint WHERE conrelid = %s::regclass AND contype = 'c'",
+                    (f'"{content_table}"',),
+                )
+                for (name,) in cursor.fetchall():
+                    cursor.execute(f'ALTER TABLE "{content_table}" DROP CONSTRAINT "{name}"')
+                cursor.execute(f"""
+                    ALTER TABLE "{content_table}" ADD CONSTRAINT {content_table.lower()}_one_owner
+                    CHECK ((EpisodeID IS NOT NULL)::int + (VideoID IS NOT NULL)::int + (FeedEpisodeID IS NOT NULL)::int = 1)
+                """)
+            cursor.execute('ALTER TABLE "FeedEpisodes" ADD COLUMN IF NOT EXISTS adsdetected BOOLEAN DEFAULT FALSE')
+            cursor.execute('ALTER TABLE "FeedEpisodes" ADD COLUMN IF NOT EXISTS silencedetected BOOLEAN DEFAULT FALSE')
+            for flag in ("adsdetected", "silencedetected"):
+                cursor.execute(f"""
+                    UPDATE "FeedEpisodes" fe SET {flag} = TRUE
+                    WHERE EXISTS (SELECT 1 FROM "Episodes" e WHERE e.feedepisodeid = fe.feedepisodeid AND e.{flag} = TRUE)
+                """)
+        else:  # MySQL / MariaDB
+            for content_table in ("EpisodeTranscripts", "EpisodeSkipSegments"):
+                cursor.execute(
+                    """
+                    SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = %s AND COLUMN_NAME = 'FeedEpisodeID'
+                    """,
+                    (content_table,),
+                )
+                if not cursor.fetchone():
+                    cursor.execute(f"ALTER TABLE {content_table} ADD COLUMN FeedEpisodeID INT NULL")
+                    cursor.execute(
+                        f"ALTER TABLE {content_table} ADD CONSTRAINT fk_{content_table.lower()}_feedepisodeid "
+                        f"FOREIGN KEY (FeedEpisodeID) REFERENCES FeedEpisodes(FeedEpisodeID) ON DELETE CASCADE"
+                    )
+                    logger.info(f"Added column FeedEpisodeID to {content_table} (MySQL)")
+                # MySQL before 8.0.16 parses CHECK but never stores it, so there may be none to drop
+                cursor.execute(
+                    """
+                    SELECT CONSTRAINT_NAME FROM INFORMATION_SCHEMA.TABLE_CONSTRAINTS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = %s AND CONSTRAINT_TYPE = 'CHECK'
+                    """,
+                    (content_table,),
+                )
+                for (name,) in cursor.fetchall():
+                    cursor.execute(f"ALTER TABLE {content_table} DROP CONSTRAINT `{name}`")
+                cursor.execute(f"""
+                    ALTER TABLE {content_table} ADD CONSTRAINT {content_table.lower()}_one_owner
+                    CHECK ((EpisodeID IS NOT NULL) + (VideoID IS NOT NULL) + (FeedEpisodeID IS NOT NULL) = 1)
+                """)
+            for flag in ("AdsDetected", "SilenceDetected"):
+                cursor.execute(
+                    """
+                    SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                    WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'FeedEpisodes' AND COLUMN_NAME = %s
+                    """,
+                    (flag,),
+                )
+                if not cursor.fetchone():
+                    cursor.execute(f"ALTER TABLE FeedEpisodes ADD COLUMN {flag} BOOLEAN DEFAULT FALSE")
+                cursor.execute(f"""
+                    UPDATE FeedEpisodes fe JOIN Episodes e ON e.FeedEpisodeID = fe.FeedEpisodeID
+                    SET fe.{flag} = TRUE WHERE e.{flag} = TRUE
+                """)
+
+        _move_content_to_feed_episodes(cursor, db_type)
+        logger.info("Shared content re-key migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in shared content re-key migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("076", "add_podcast_name_search_index", "Add a full-text search index over podcast names so episode search also matches the show an episode belongs to", requires=["001", "060"])
+def migration_076_add_podcast_name_search_index(conn, db_type: str) -> None:
+    """Full-text index over Podcasts.PodcastName, alongside the episode and transcript indexes from 060.
+
+    PostgreSQL gets a stored generated tsvector column with a GIN index, using the same 'simple'
+    configuration as 060. MySQL/MariaDB get a FULLTEXT index on PodcastName; the MATCH() column
+    list in the API must stay identical to it."""
+    logger.info("Starting migration 076: podcast name search index")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                ALTER TABLE "Podcasts" ADD COLUMN IF NOT EXISTS searchvector tsvector
+                GENERATED ALWAYS AS (to_tsvector('simple', coalesce(podcastname, ''))) STORED
+            """)
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_podcasts_searchvector ON "Podcasts" USING GIN (searchvector)
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                SELECT COUNT(*) FROM INFORMATION_SCHEMA.STATISTICS
+                WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'Podcasts' AND INDEX_NAME = 'ft_podcasts_name'
+            """)
+            if cursor.fetchone()[0] == 0:
+                cursor.execute("ALTER TABLE Podcasts ADD FULLTEXT INDEX ft_podcasts_name (PodcastName)")
+                logger.info("Added FULLTEXT index ft_podcasts_name on Podcasts (MySQL)")
+
+        logger.info("Podcast name search index migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in podcast name search index migration: {e}")
+        raise
+    finally:
+        cursor.close()
+
+
+@register_migration("110", "gpodder_episode_actions_received_at", "Record when the built-in gpodder server received each episode action, so 'since' queries see late uploads", requires=["100"])
+def migration_110_gpodder_episode_actions_received_at(conn, db_type: str) -> None:
+    """GpodderSyncEpisodeActions.ReceivedAt is the Unix time the server stored an action.
+
+    Timestamp is when the action happened on the device, so a device that uploads after being
+    offline adds actions older than the 'since' other clients already hold. The built-in server
+    answers 'since' from ReceivedAt instead; rows from before this column fall back to Timestamp."""
+    logger.info("Starting migration 110: episode action received time")
+    cursor = conn.cursor()
+
+    try:
+        if db_type == "postgresql":
+            cursor.execute("""
+                SELECT column_name FROM information_schema.columns
+                WHERE table_name = 'GpodderSyncEpisodeActions' AND column_name = 'receivedat'
+            """)
+            if not cursor.fetchone():
+                cursor.execute("""ALTER TABLE "GpodderSyncEpisodeActions" ADD COLUMN receivedat BIGINT""")
+                logger.info("Added receivedat column to GpodderSyncEpisodeActions (PostgreSQL)")
+            cursor.execute("""
+                CREATE INDEX IF NOT EXISTS idx_episode_actions_user_received
+                ON "GpodderSyncEpisodeActions"(UserID, ReceivedAt)
+            """)
+        else:  # MySQL / MariaDB
+            cursor.execute("""
+                SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'GpodderSyncEpisodeActions' AND COLUMN_NAME = 'ReceivedAt'
+            """)
+            if not cursor.fetchone():
+                cursor.execute("ALTER TABLE GpodderSyncEpisodeActions ADD COLUMN ReceivedAt BIGINT NULL")
+                logger.info("Added ReceivedAt column to GpodderSyncEpisodeActions (MySQL)")
+            try:
+                cursor.execute("CREATE INDEX idx_episode_actions_user_received ON GpodderSyncEpisodeActions(UserID, ReceivedAt)")
+            except Exception:
+                pass  # Index may already exist
+
+        logger.info("Episode action received time migration completed successfully")
+
+    except Exception as e:
+        logger.error(f"Error in episode action received time migration: {e}")
+        raise
+    finally:
+        cursor.close()
@@ -102,6 +102,32 @@ class DatabaseMigrationManager:
             self._connection.close()
             self._connection = None
 
+    # Key of the lock that serialises migration runs across instances (advisory lock ID in
+    # PostgreSQL, lock name in MySQL)
+    MIGRATION_LOCK_ID = 7406731
+    MIGRATION_LOCK_NAME = "pinepods_migrations"
+    MIGRATION_LOCK_TIMEOUT_SECONDS = 600
+
+    def acquire_migration_lock(self):
+        """Wait until no other instance is migrating this database, so replicas starting together
+        apply each migration once. The lock is held by the connection and released when it closes."""
+        conn = self.get_connection()
+        cursor = conn.cursor()
+
+        try:
+            logger.info("Waiting for the migration lock")
+            if self.db_type == 'postgresql':
+                cursor.execute("SELECT pg_advisory_lock(%s)", (self.MIGRATION_LOCK_ID,))
+                cursor.fetchone()
+            else:
+                cursor.execute("SELECT GET_LOCK(%s, %s)", (self.MIGRATION_LOCK_NAME, self.MIGRATION_LOCK_TIMEOUT_SECONDS))
+                result = cursor.fetchone()
+                if not result or result[0] != 1:
+                    raise RuntimeError("Timed out waiting for another instance to finish migrating")
+            conn.commit()
+        finally:
+            cursor.close()
+
     def register_migration(self, migration: Migration):
         """Register a migration to be tracked"""
         self.migrations[migration.version] = migration
@@ -382,6 +408,9 @@ class DatabaseMigrationManager:
     def run_migrations(self, target_version: Optional[str] = None) -> bool:
         """Run all pending migrations up to target version"""
         try:
+            # Another instance may be migrating; what it applies shows up as applied below
+            self.acquire_migration_lock()
+
             # Create migration table
             self.create_migration_table()
             
@@ -1,112 +0,0 @@
-package main
-
-import (
-	"context"
-	"fmt"
-	"log"
-	"net/http"
-	"os"
-	"os/signal"
-	"syscall"
-	"time"
-
-	"pinepods/gpodder-api/config"
-	"pinepods/gpodder-api/internal/api"
-	"pinepods/gpodder-api/internal/db"
-
-	"github.com/gin-gonic/gin"
-	"github.com/joho/godotenv"
-)
-
-func main() {
-	// Load environment variables from .env file if it exists
-	_ = godotenv.Load()
-
-	debugMode := os.Getenv("DEBUG_MODE") == "true"
-
-	// Log resolved DB connection settings only in debug mode (never the password).
-	if debugMode {
-		fmt.Printf("Environment variables:\n")
-		fmt.Printf("DB_TYPE: %s\n", os.Getenv("DB_TYPE"))
-		fmt.Printf("DB_HOST: %s\n", os.Getenv("DB_HOST"))
-		fmt.Printf("DB_PORT: %s\n", os.Getenv("DB_PORT"))
-		fmt.Printf("DB_USER: %s\n", os.Getenv("DB_USER"))
-		fmt.Printf("DB_NAME: %s\n", os.Getenv("DB_NAME"))
-		fmt.Printf("DB_PASSWORD: [hidden]\n")
-	}
-
-	// Initialize configuration
-	cfg, err := config.Load()
-	if err != nil {
-		log.Fatalf("Failed to load configuration: %v", err)
-	}
-
-	fmt.Printf("Using database type: %s\n", cfg.Database.Type)
-
-	// Initialize database - use Database type instead of PostgresDB
-	var database *db.Database
-	database, err = db.NewDatabase(cfg.Database)
-	if err != nil {
-		log.Fatalf("Failed to connect to database: %v", err)
-	}
-	defer database.Close()
-
-	// Run Gin in release mode unless DEBUG_MODE is set: keeps the route table and
-	// per-request debug banners out of production logs.
-	if !debugMode {
-		gin.SetMode(gin.ReleaseMode)
-	}
-
-	// Initialize router. gin.Default() already attaches the Logger and Recovery
-	// middleware, so we must not add them again (doing so double-logs every request).
-	router := gin.Default()
-
-	// Add CORS middleware
-	router.Use(func(c *gin.Context) {
-		c.Writer.Header().Set("Access-Control-Allow-Origin", "*")
-		c.Writer.Header().Set("Access-Control-Allow-Credentials", "true")
-		c.Writer.Header().Set("Access-Control-Allow-Headers", "Content-Type, Content-Length, Accept-Encoding, X-CSRF-Token, Authorization, accept, origin, Cache-Control, X-Requested-With")
-		c.Writer.Header().Set("Access-Control-Allow-Methods", "POST, OPTIONS, GET, PUT, DELETE")
-		if c.Request.Method == "OPTIONS" {
-			c.AbortWithStatus(204)
-			return
-		}
-		c.Next()
-	})
-
-	// Register API routes
-	apiRoutes := router.Group("/api/2")
-	api.RegisterRoutes(apiRoutes, database)
-
-	// Register simple API routes (v1)
-	simpleAPIRoutes := router.Group("")
-	api.RegisterSimpleRoutes(simpleAPIRoutes, database)
-
-	// Start server
-	srv := &http.Server{
-		Addr:    fmt.Sprintf(":%d", cfg.Server.Port),
-		Handler: router,
-	}
-
-	// Graceful shutdown
-	go func() {
-		if err := srv.ListenAndServe(); err != nil && err != http.ErrServerClosed {
-			log.Fatalf("Failed to start server: %v", err)
-		}
-	}()
-
-	// Wait for interrupt signal
-	quit := make(chan os.Signal, 1)
-	signal.Notify(quit, syscall.SIGINT, syscall.SIGTERM)
-	<-quit
-	log.Println("Shutting down server...")
-
-	// Set timeout for shutdown
-	ctx, cancel := context.WithTimeout(context.Background(), 5*time.Second)
-	defer cancel()
-	if err := srv.Shutdown(ctx); err != nil {
-		log.Fatalf("Server forced to shutdown: %v", err)
-	}
-
-	log.Println("Server exiting")
-}
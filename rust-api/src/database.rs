@@ -5,14 +5,21 @@ use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
 use std::collections::HashMap;
 use bigdecimal::ToPrimitive;
-use std::sync::{Arc, Mutex};
-use lazy_static::lazy_static;
 use base64;
 use tracing::{debug, error, info, warn};
 
-// Global temporary MFA secrets storage (matches Python temp_mfa_secrets)
-lazy_static! {
-    static ref TEMP_MFA_SECRETS: Arc<Mutex<HashMap<String, String>>> = Arc::new(Mutex::new(HashMap::new()));
+// How long a generated MFA secret waits for the user to confirm it with a code
+const TEMP_MFA_SECRET_TTL_SECONDS: u64 = 600;
+
+// Scope/expiry state of a single API key, consumed by handlers::enforce_api_key_policy.
+// scopes: None = unrestricted key; otherwise a comma-separated services::api_scopes list.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ApiKeyPolicy {
+    pub api_key_id: i32,
+    pub user_id: i32,
+    pub scopes: Option<String>,
+    pub revoked: bool,
+    pub expired: bool,
 }
 
 #[derive(Clone)]
@@ -109,13 +116,31 @@ impl DatabasePool {
         }
     }
 
+    /// Current pool usage, sampled for `/api/metrics`.
+    pub fn pool_stats(&self) -> crate::services::metrics::PoolStats {
+        match self {
+            DatabasePool::Postgres(pool) => crate::services::metrics::PoolStats {
+                backend: "postgresql",
+                size: pool.size(),
+                idle: pool.num_idle() as u32,
+                max: pool.options().get_max_connections(),
+            },
+            DatabasePool::MySQL(pool) => crate::services::metrics::PoolStats {
+                backend: "mysql",
+                size: pool.size(),
+                idle: pool.num_idle() as u32,
+                max: pool.options().get_max_connections(),
+            },
+        }
+    }
+
     // Helper methods for database operations
 
     // Verify API key - matches Python verify_api_key function
     pub async fn verify_api_key(&self, api_key: &str) -> AppResult<bool> {
         match self {
             DatabasePool::Postgres(pool) => {
-                let row = sqlx::query(r#"SELECT * FROM "APIKeys" WHERE apikey = $1"#)
+                let row = sqlx::query(r#"SELECT apikeyid FROM "APIKeys" WHERE apikey = $1 AND revoked = FALSE AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP)"#)
                     .bind(api_key)
                     .fetch_optional(pool)
                     .await?;
@@ -123,7 +148,7 @@ impl DatabasePool {
                 Ok(row.is_some())
             }
             DatabasePool::MySQL(pool) => {
-                let row = sqlx::query("SELECT * FROM APIKeys WHERE APIKey = ?")
+                let row = sqlx::query("SELECT APIKeyID FROM APIKeys WHERE APIKey = ? AND Revoked = FALSE AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP)")
                     .bind(api_key)
                     .fetch_optional(pool)
                     .await?;
@@ -183,7 +208,7 @@ impl DatabasePool {
                 };
                 
                 // Then get API key
-                let api_row = sqlx::query(r#"SELECT apikey FROM "APIKeys" WHERE userid = $1 LIMIT 1"#)
+                let api_row = sqlx::query(r#"SELECT apikey FROM "APIKeys" WHERE userid = $1 AND scopes IS NULL AND revoked = FALSE AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP) LIMIT 1"#)
                     .bind(user_id)
                     .fetch_optional(pool)
                     .await?;
@@ -206,7 +231,7 @@ impl DatabasePool {
                 };
                 
                 // Then get API key
-                let api_row = sqlx::query("SELECT APIKey FROM APIKeys WHERE UserID = ? LIMIT 1")
+                let api_row = sqlx::query("SELECT APIKey FROM APIKeys WHERE UserID = ? AND Scopes IS NULL AND Revoked = FALSE AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP) LIMIT 1")
                     .bind(user_id)
                     .fetch_optional(pool)
                     .await?;
@@ -223,7 +248,7 @@ impl DatabasePool {
     pub async fn get_user_id_from_api_key(&self, api_key: &str) -> AppResult<i32> {
         match self {
             DatabasePool::Postgres(pool) => {
-                let row = sqlx::query(r#"SELECT userid FROM "APIKeys" WHERE apikey = $1 LIMIT 1"#)
+                let row = sqlx::query(r#"SELECT userid FROM "APIKeys" WHERE apikey = $1 AND revoked = FALSE AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP) LIMIT 1"#)
                     .bind(api_key)
                     .fetch_one(pool)
                     .await?;
@@ -231,7 +256,7 @@ impl DatabasePool {
                 Ok(row.try_get("userid")?)
             }
             DatabasePool::MySQL(pool) => {
-                let row = sqlx::query("SELECT UserID FROM APIKeys WHERE APIKey = ? LIMIT 1")
+                let row = sqlx::query("SELECT UserID FROM APIKeys WHERE APIKey = ? AND Revoked = FALSE AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP) LIMIT 1")
                     .bind(api_key)
                     .fetch_one(pool)
                     .await?;
@@ -405,7 +430,7 @@ impl DatabasePool {
     pub async fn get_api_user(&self, api_key: &str) -> AppResult<i32> {
         match self {
             DatabasePool::Postgres(pool) => {
-                let row = sqlx::query(r#"SELECT userid FROM "APIKeys" WHERE apikey = $1"#)
+                let row = sqlx::query(r#"SELECT userid FROM "APIKeys" WHERE apikey = $1 AND revoked = FALSE AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP)"#)
                     .bind(api_key)
                     .fetch_one(pool)
                     .await?;
@@ -413,7 +438,7 @@ impl DatabasePool {
                 Ok(row.try_get("userid")?)
             }
             DatabasePool::MySQL(pool) => {
-                let row = sqlx::query("SELECT UserID FROM APIKeys WHERE APIKey = ?")
+                let row = sqlx::query("SELECT UserID FROM APIKeys WHERE APIKey = ? AND Revoked = FALSE AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP)")
                     .bind(api_key)
                     .fetch_one(pool)
                     .await?;
@@ -1404,6 +1429,7 @@ impl DatabasePool {
                         feedurl as feed_url,
                         artworkurl as artwork_url,
                         isyoutubechannel as is_youtube,
+                        sourcekind as source_kind,
                         autodownload as auto_download,
                         autoqueue as auto_queue,
                         username as username,
@@ -1425,6 +1451,7 @@ impl DatabasePool {
                         feed_url: row.try_get("feed_url")?,
                         artwork_url: row.try_get("artwork_url").unwrap_or_default(),
                         is_youtube: row.try_get("is_youtube")?,
+                        source_kind: row.try_get("source_kind")?,
                         auto_download: row.try_get("auto_download")?,
                         auto_queue: row.try_get("auto_queue").unwrap_or(false),
                         username: row.try_get("username").ok(),
@@ -1443,6 +1470,7 @@ impl DatabasePool {
                         FeedURL as feed_url,
                         ArtworkURL as artwork_url,
                         IsYouTubeChannel as is_youtube,
+                        SourceKind as source_kind,
                         AutoDownload as auto_download,
                         AutoQueue as auto_queue,
                         Username as username,
@@ -1464,6 +1492,7 @@ impl DatabasePool {
                         feed_url: row.try_get("feed_url")?,
                         artwork_url: row.try_get("artwork_url").unwrap_or_default(),
                         is_youtube: row.try_get("is_youtube")?,
+                        source_kind: row.try_get("source_kind")?,
                         auto_download: row.try_get("auto_download")?,
                         auto_queue: row.try_get("auto_queue").unwrap_or(false),
                         username: row.try_get("username").ok(),
@@ -2806,13 +2835,15 @@ impl DatabasePool {
                         let duration: Option<i32> = row.try_get("episodeduration").ok();
                         
                         if let Some(duration) = duration {
-                            // Update completion status
-                            sqlx::query(
-                                r#"UPDATE "Episodes" SET completed = TRUE WHERE episodeid = $1"#
+                            // Update completion status; only the first completion fires the webhook
+                            let newly_completed = sqlx::query(
+                                r#"UPDATE "Episodes" SET completed = TRUE
+                                   WHERE episodeid = $1 AND COALESCE(completed, FALSE) = FALSE"#
                             )
                             .bind(episode_id)
                             .execute(pool)
-                            .await?;
+                            .await?
+                            .rows_affected();
 
                             // Update history
                             sqlx::query(
@@ -2827,6 +2858,15 @@ impl DatabasePool {
                             .bind(duration)
                             .execute(pool)
                             .await?;
+
+                            if newly_completed > 0 {
+                                crate::services::webhooks::emit_episode_event(
+                                    self,
+                                    episode_id,
+                                    crate::services::webhooks::WebhookEvent::EpisodeCompleted,
+                                    serde_json::json!({ "listen_duration_seconds": duration }),
+                                );
+                            }
                         }
                     }
                 }
@@ -2883,13 +2923,15 @@ impl DatabasePool {
                         let duration: Option<i32> = row.try_get("EpisodeDuration").ok();
                         
                         if let Some(duration) = duration {
-                            // Update completion status
-                            sqlx::query(
-                                "UPDATE Episodes SET Completed = 1 WHERE EpisodeID = ?"
+                            // Update completion status; only the first completion fires the webhook
+                            let newly_completed = sqlx::query(
+                                "UPDATE Episodes SET Completed = 1
+                                 WHERE EpisodeID = ? AND COALESCE(Completed, 0) = 0"
                             )
                             .bind(episode_id)
                             .execute(pool)
-                            .await?;
+                            .await?
+                            .rows_affected();
 
                             // Update history
                             sqlx::query(
@@ -2905,6 +2947,15 @@ impl DatabasePool {
                             .bind(duration)
                             .execute(pool)
                             .await?;
+
+                            if newly_completed > 0 {
+                                crate::services::webhooks::emit_episode_event(
+                                    self,
+                                    episode_id,
+                                    crate::services::webhooks::WebhookEvent::EpisodeCompleted,
+                                    serde_json::json!({ "listen_duration_seconds": duration }),
+                                );
+                            }
                         }
                     }
                 }
@@ -6772,8 +6823,9 @@ impl DatabasePool {
         // no-op UPDATE skipping, async duration estimation). Kept as a separate function from
         // add_episodes_with_new_list only because callers here want the first episode id rather
         // than the list of newly-inserted episodes.
-        let episodes = self.parse_rss_feed(&content, podcast_id, artwork_url).await?;
-        let _new = self.apply_parsed_episodes(podcast_id, &episodes, artwork_url, None, true).await?;
+        let parsed = self.parse_feed_body(&content, podcast_id, artwork_url).await?;
+        // A freshly added podcast's back catalogue isn't "new episodes"; don't notify for it
+        let _new = self.apply_parsed_episodes(podcast_id, &parsed, artwork_url, None, false).await?;
 
         // Get the actual first episode ID (earliest by pub date)
         let first_id = self.get_first_episode_id(podcast_id, false).await?;
@@ -6796,19 +6848,25 @@ impl DatabasePool {
         // parse_feed_episodes + apply_parsed_episodes directly so they can skip unchanged feeds,
         // apply the feed cutoff, and (for multi-user instances) parse a shared feed only once.
         let content = self.try_fetch_feed(feed_url, username, password).await?;
-        let episodes = self.parse_rss_feed(&content, podcast_id, artwork_url).await?;
-        self.apply_parsed_episodes(podcast_id, &episodes, artwork_url, None, true).await
+        let parsed = self.parse_feed_body(&content, podcast_id, artwork_url).await?;
+        self.apply_parsed_episodes(podcast_id, &parsed, artwork_url, None, false).await
     }
 
     /// Public wrapper around the RSS parser so the refresh layer can parse a feed body once and
-    /// apply it to multiple subscriber podcasts (cross-user fetch dedup).
+    /// apply it to multiple subscriber podcasts (cross-user fetch dedup). Also parses the
+    /// Podcasting 2.0 namespace so apply_parsed_episodes can persist it.
     pub async fn parse_feed_body(
         &self,
         content: &str,
         podcast_id: i32,
         artwork_url: &str,
-    ) -> AppResult<Vec<EpisodeData>> {
-        self.parse_rss_feed(content, podcast_id, artwork_url).await
+    ) -> AppResult<ParsedFeed> {
+        let mut episodes = self.parse_rss_feed(content, podcast_id, artwork_url).await?;
+        let tags = crate::services::podcast_namespace::parse_feed(content);
+        for ep in &mut episodes {
+            ep.podcasting = tags.item(ep.guid.as_deref(), &ep.url).cloned();
+        }
+        Ok(ParsedFeed { episodes, channel: tags.channel })
     }
 
     /// Apply a parsed episode list to a single podcast: dedup against existing rows, update
@@ -6821,10 +6879,12 @@ impl DatabasePool {
     /// write storm that re-UPDATEd the entire back-catalogue on every refresh cycle.
     ///
     /// `cutoff_days`: when Some(d) with d > 0, episodes older than `now - d days` are skipped.
+    ///
+    /// The feed's Podcasting 2.0 tags are persisted alongside (see store_podcasting_tags).
     pub async fn apply_parsed_episodes(
         &self,
         podcast_id: i32,
-        episodes: &[EpisodeData],
+        feed: &ParsedFeed,
         artwork_url: &str,
         cutoff_days: Option<i32>,
         notify: bool,
@@ -6834,7 +6894,8 @@ impl DatabasePool {
             Some(days) if days > 0 => Some(Utc::now() - chrono::Duration::days(days as i64)),
             _ => None,
         };
-        let candidates: Vec<&EpisodeData> = episodes
+        let candidates: Vec<&EpisodeData> = feed
+            .episodes
             .iter()
             .filter(|ep| cutoff_date.map_or(true, |cut| ep.pub_date >= cut))
             .collect();
@@ -6843,6 +6904,7 @@ impl DatabasePool {
         let mut new_episodes = Vec::new();
         let mut skipped_count: usize = 0;
         let mut updated_count: usize = 0;
+        let mut tagged: Vec<(i32, &crate::services::podcast_namespace::ItemTags)> = Vec::new();
 
         // Dedup key lists. url_bases/titles cover ALL candidates (not just guid-less ones) so a
         // new episode that now carries a GUID can still match a legacy row stored before GUIDs
@@ -6884,6 +6946,10 @@ impl DatabasePool {
             let artwork = if ep.artwork_url.is_empty() { artwork_url } else { ep.artwork_url.as_str() };
 
             if let Some(episode_id) = existing_id {
+                if let Some(tags) = &ep.podcasting {
+                    tagged.push((episode_id, tags));
+                }
+
                 // Compare against the stored row; only UPDATE when something actually changed or we
                 // can backfill a GUID. pubdate is intentionally excluded from change detection to
                 // avoid timestamp-precision false positives — it virtually never changes for an
@@ -7032,10 +7098,18 @@ impl DatabasePool {
                 }
             };
 
+            if let Some(tags) = &ep.podcasting {
+                tagged.push((episode_id, tags));
+            }
+
             if notify {
-                if let Err(e) = self.check_and_send_notification(podcast_id, &ep.title).await {
-                    tracing::warn!("Failed to send notification for episode '{}': {}", ep.title, e);
-                }
+                crate::services::notification_channels::notify_new_episode(self, podcast_id, episode_id);
+                crate::services::webhooks::emit_episode_event(
+                    self,
+                    episode_id,
+                    crate::services::webhooks::WebhookEvent::EpisodeNew,
+                    serde_json::json!({}),
+                );
             }
 
             new_episodes.push(crate::handlers::podcasts::Episode {
@@ -7066,204 +7140,168 @@ impl DatabasePool {
         // Only recount when something actually changed.
         if !new_episodes.is_empty() {
             self.update_episode_count(podcast_id).await?;
+            // Attach the new rows to the shared feed layer so content-level work (transcripts,
+            // ad/silence detection) done for another subscriber is reused. Not fatal: unlinked
+            // episodes simply keep their own results until the next refresh links them.
+            if let Err(e) = crate::services::shared_feeds::link_podcast_episodes(self, podcast_id).await {
+                tracing::warn!("Failed to link podcast {} to the shared feed layer: {}", podcast_id, e);
+            }
+        }
+
+        // Tag persistence is secondary to the episode list; a failure here must not fail the refresh.
+        if let Err(e) = self.store_podcasting_tags(podcast_id, &feed.channel, &tagged).await {
+            tracing::warn!("Failed to store Podcasting 2.0 tags for podcast {}: {}", podcast_id, e);
         }
 
         Ok(new_episodes)
     }
 
-    // Check and send notifications for new episodes - matches Python check_and_send_notification function
-    pub async fn check_and_send_notification(&self, podcast_id: i32, episode_title: &str) -> AppResult<bool> {
-        use std::time::Duration;
-        
-        let mut success = false;
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(2))
-            .build()
-            .map_err(|e| AppError::Http(e))?;
-        
+    /// Upsert the Podcasting 2.0 tags parsed from a feed. Stored JSON is compared first so an
+    /// unchanged feed costs one read per table and no writes. Rows are never deleted here, so
+    /// tags for items the feed has since dropped remain available. A changed chapters URL clears
+    /// the cached chapters JSON.
+    async fn store_podcasting_tags(
+        &self,
+        podcast_id: i32,
+        channel: &crate::services::podcast_namespace::ChannelTags,
+        items: &[(i32, &crate::services::podcast_namespace::ItemTags)],
+    ) -> AppResult<()> {
+        // The channel row is written even when the feed has no channel tags: its presence is what
+        // tells the episode/podcast endpoints this feed has been parsed and no scrape is needed.
+        let channel_json = serde_json::to_string(channel)?;
+
         match self {
             DatabasePool::Postgres(pool) => {
+                let current: Option<String> = sqlx::query_scalar(
+                    r#"SELECT data FROM "PodcastPodcastingData" WHERE podcastid = $1"#,
+                )
+                .bind(podcast_id)
+                .fetch_optional(pool)
+                .await?;
+                if current.as_deref() != Some(channel_json.as_str()) {
+                    sqlx::query(
+                        r#"INSERT INTO "PodcastPodcastingData" (podcastid, podcastguid, data, updatedat)
+                           VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+                           ON CONFLICT (podcastid) DO UPDATE
+                           SET podcastguid = EXCLUDED.podcastguid, data = EXCLUDED.data, updatedat = CURRENT_TIMESTAMP"#,
+                    )
+                    .bind(podcast_id)
+                    .bind(channel.guid.as_deref())
+                    .bind(&channel_json)
+                    .execute(pool)
+                    .await?;
+                }
+
+                if items.is_empty() {
+                    return Ok(());
+                }
                 let rows = sqlx::query(
-                    r#"SELECT p.notificationsenabled, p.userid, p.podcastname,
-                           uns.platform, uns.enabled, uns.ntfytopic, uns.ntfyserverurl,
-                           uns.ntfyusername, uns.ntfypassword, uns.ntfyaccesstoken,
-                           uns.gotifyurl, uns.gotifytoken
-                    FROM "Podcasts" p
-                    JOIN "UserNotificationSettings" uns ON p.userid = uns.userid
-                    WHERE p.podcastid = $1 AND p.notificationsenabled = true AND uns.enabled = true"#
+                    r#"SELECT d.episodeid, d.data FROM "EpisodePodcastingData" d
+                       JOIN "Episodes" e ON e.episodeid = d.episodeid
+                       WHERE e.podcastid = $1"#,
                 )
                 .bind(podcast_id)
                 .fetch_all(pool)
                 .await?;
-                
-                for result in rows {
-                    let podcast_name: String = result.try_get("podcastname")?;
-                    let platform: String = result.try_get("platform")?;
-                    
-                    match platform.as_str() {
-                        "ntfy" => {
-                            let topic: String = result.try_get("ntfytopic")?;
-                            let server_url: String = result.try_get("ntfyserverurl")?;
-                            let username: Option<String> = result.try_get("ntfyusername").ok();
-                            let password: Option<String> = result.try_get("ntfypassword").ok();
-                            let access_token: Option<String> = result.try_get("ntfyaccesstoken").ok();
-                            
-                            if let Ok(sent) = Self::send_ntfy_notification(&client, &topic, &server_url, username.as_deref(), password.as_deref(), access_token.as_deref(), &podcast_name, episode_title).await {
-                                if sent {
-                                    success = true;
-                                }
-                            }
-                        }
-                        "gotify" => {
-                            let url: String = result.try_get("gotifyurl")?;
-                            let token: String = result.try_get("gotifytoken")?;
-                            
-                            if let Ok(sent) = Self::send_gotify_notification(&client, &url, &token, &podcast_name, episode_title).await {
-                                if sent {
-                                    success = true;
-                                }
-                            }
-                        }
-                        _ => {
-                            tracing::warn!("Unknown notification platform: {}", platform);
-                        }
+                let mut current = HashMap::with_capacity(rows.len());
+                for row in rows {
+                    current.insert(row.try_get::<i32, _>("episodeid")?, row.try_get::<String, _>("data")?);
+                }
+
+                for (episode_id, tags) in items {
+                    let data = serde_json::to_string(tags)?;
+                    if current.get(episode_id) == Some(&data) {
+                        continue;
                     }
+                    sqlx::query(
+                        r#"INSERT INTO "EpisodePodcastingData"
+                           (episodeid, seasonnumber, episodenumber, chaptersurl, data, updatedat)
+                           VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP)
+                           ON CONFLICT (episodeid) DO UPDATE
+                           SET seasonnumber = EXCLUDED.seasonnumber,
+                               episodenumber = EXCLUDED.episodenumber,
+                               chaptersdata = CASE WHEN "EpisodePodcastingData".chaptersurl IS DISTINCT FROM EXCLUDED.chaptersurl
+                                                   THEN NULL ELSE "EpisodePodcastingData".chaptersdata END,
+                               chaptersurl = EXCLUDED.chaptersurl,
+                               data = EXCLUDED.data,
+                               updatedat = CURRENT_TIMESTAMP"#,
+                    )
+                    .bind(episode_id)
+                    .bind(tags.season)
+                    .bind(tags.episode)
+                    .bind(tags.chapters_url.as_deref())
+                    .bind(&data)
+                    .execute(pool)
+                    .await?;
                 }
             }
             DatabasePool::MySQL(pool) => {
+                let current: Option<String> = sqlx::query_scalar(
+                    "SELECT Data FROM PodcastPodcastingData WHERE PodcastID = ?",
+                )
+                .bind(podcast_id)
+                .fetch_optional(pool)
+                .await?;
+                if current.as_deref() != Some(channel_json.as_str()) {
+                    sqlx::query(
+                        "INSERT INTO PodcastPodcastingData (PodcastID, PodcastGUID, Data, UpdatedAt)
+                         VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                         ON DUPLICATE KEY UPDATE PodcastGUID = VALUES(PodcastGUID), Data = VALUES(Data), UpdatedAt = CURRENT_TIMESTAMP",
+                    )
+                    .bind(podcast_id)
+                    .bind(channel.guid.as_deref())
+                    .bind(&channel_json)
+                    .execute(pool)
+                    .await?;
+                }
+
+                if items.is_empty() {
+                    return Ok(());
+                }
                 let rows = sqlx::query(
-                    "SELECT p.NotificationsEnabled, p.UserID, p.PodcastName,
-                            uns.Platform, uns.Enabled, uns.NtfyTopic, uns.NtfyServerUrl,
-                            uns.NtfyUsername, uns.NtfyPassword, uns.NtfyAccessToken,
-                            uns.GotifyUrl, uns.GotifyToken
-                     FROM Podcasts p
-                     JOIN UserNotificationSettings uns ON p.UserID = uns.UserID
-                     WHERE p.PodcastID = ? AND p.NotificationsEnabled = true AND uns.Enabled = true"
+                    "SELECT d.EpisodeID, d.Data FROM EpisodePodcastingData d
+                     JOIN Episodes e ON e.EpisodeID = d.EpisodeID
+                     WHERE e.PodcastID = ?",
                 )
                 .bind(podcast_id)
                 .fetch_all(pool)
                 .await?;
-                
-                for result in rows {
-                    let podcast_name: String = result.try_get("PodcastName")?;
-                    let platform: String = result.try_get("Platform")?;
-                    
-                    match platform.as_str() {
-                        "ntfy" => {
-                            let topic: String = result.try_get("NtfyTopic")?;
-                            let server_url: String = result.try_get("NtfyServerUrl")?;
-                            let username: Option<String> = result.try_get("NtfyUsername").ok();
-                            let password: Option<String> = result.try_get("NtfyPassword").ok();
-                            let access_token: Option<String> = result.try_get("NtfyAccessToken").ok();
-                            
-                            if let Ok(sent) = Self::send_ntfy_notification(&client, &topic, &server_url, username.as_deref(), password.as_deref(), access_token.as_deref(), &podcast_name, episode_title).await {
-                                if sent {
-                                    success = true;
-                                }
-                            }
-                        }
-                        "gotify" => {
-                            let url: String = result.try_get("GotifyUrl")?;
-                            let token: String = result.try_get("GotifyToken")?;
-                            
-                            if let Ok(sent) = Self::send_gotify_notification(&client, &url, &token, &podcast_name, episode_title).await {
-                                if sent {
-                                    success = true;
-                                }
-                            }
-                        }
-                        _ => {
-                            tracing::warn!("Unknown notification platform: {}", platform);
-                        }
-                    }
+                let mut current = HashMap::with_capacity(rows.len());
+                for row in rows {
+                    current.insert(row.try_get::<i32, _>("EpisodeID")?, row.try_get::<String, _>("Data")?);
                 }
-            }
-        }
-        
-        Ok(success)
-    }
 
-    // Helper function to send NTFY notification - matches Python send_ntfy_notification function
-    async fn send_ntfy_notification(
-        client: &reqwest::Client,
-        topic: &str,
-        server_url: &str,
-        username: Option<&str>,
-        password: Option<&str>,
-        access_token: Option<&str>,
-        podcast_name: &str,
-        episode_title: &str,
-    ) -> AppResult<bool> {
-        let url = format!("{}/{}", server_url.trim_end_matches('/'), topic);
-        let message = format!("New episode available for {}: {}", podcast_name, episode_title);
-        
-        let mut request = client
-            .post(&url)
-            .header("Content-Type", "text/plain")
-            .body(message);
-        
-        // Add authentication if provided
-        if let Some(token) = access_token.filter(|t| !t.is_empty()) {
-            // Use access token (preferred method)
-            request = request.header("Authorization", format!("Bearer {}", token));
-        } else if let (Some(user), Some(pass)) = (username.filter(|u| !u.is_empty()), password.filter(|p| !p.is_empty())) {
-            // Use username/password basic auth
-            request = request.basic_auth(user, Some(pass));
-        }
-        
-        match request.send().await
-        {
-            Ok(response) => {
-                if response.status().is_success() {
-                    tracing::info!("Successfully sent NTFY notification to {}", url);
-                    Ok(true)
-                } else {
-                    tracing::warn!("NTFY notification failed with status: {}", response.status());
-                    Ok(false)
+                for (episode_id, tags) in items {
+                    let data = serde_json::to_string(tags)?;
+                    if current.get(episode_id) == Some(&data) {
+                        continue;
+                    }
+                    // ChaptersData is assigned before ChaptersURL: MySQL evaluates the update list
+                    // left to right, so the comparison still sees the old URL.
+                    sqlx::query(
+                        "INSERT INTO EpisodePodcastingData
+                         (EpisodeID, SeasonNumber, EpisodeNumber, ChaptersURL, Data, UpdatedAt)
+                         VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                         ON DUPLICATE KEY UPDATE
+                             SeasonNumber = VALUES(SeasonNumber),
+                             EpisodeNumber = VALUES(EpisodeNumber),
+                             ChaptersData = IF(ChaptersURL <=> VALUES(ChaptersURL), ChaptersData, NULL),
+                             ChaptersURL = VALUES(ChaptersURL),
+                             Data = VALUES(Data),
+                             UpdatedAt = CURRENT_TIMESTAMP",
+                    )
+                    .bind(episode_id)
+                    .bind(tags.season)
+                    .bind(tags.episode)
+                    .bind(tags.chapters_url.as_deref())
+                    .bind(&data)
+                    .execute(pool)
+                    .await?;
                 }
             }
-            Err(e) => {
-                tracing::warn!("Failed to send NTFY notification: {}", e);
-                Ok(false)
-            }
         }
-    }
 
-    // Helper function to send Gotify notification - matches Python send_gotify_notification function
-    async fn send_gotify_notification(
-        client: &reqwest::Client,
-        server_url: &str,
-        token: &str,
-        podcast_name: &str,
-        episode_title: &str,
-    ) -> AppResult<bool> {
-        let url = format!("{}/message?token={}", server_url.trim_end_matches('/'), token);
-        let message = format!("New episode available for {}: {}", podcast_name, episode_title);
-        
-        match client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "message": message,
-                "title": "New Podcast Episode"
-            }))
-            .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status().is_success() {
-                    tracing::info!("Successfully sent Gotify notification to {}", url);
-                    Ok(true)
-                } else {
-                    tracing::warn!("Gotify notification failed with status: {}", response.status());
-                    Ok(false)
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Failed to send Gotify notification: {}", e);
-                Ok(false)
-            }
-        }
+        Ok(())
     }
 
     /// Load existing episodes for a podcast that match any of the given GUIDs, URL bases, or
@@ -7510,6 +7548,7 @@ impl DatabasePool {
         if podcast_ids.is_empty() {
             return Ok(());
         }
+        crate::services::metrics::record_feed_refresh(true, podcast_ids.len());
         match self {
             DatabasePool::Postgres(pool) => {
                 sqlx::query(
@@ -7543,6 +7582,7 @@ impl DatabasePool {
         if podcast_ids.is_empty() {
             return Ok(());
         }
+        crate::services::metrics::record_feed_refresh(false, podcast_ids.len());
         // Truncate overly long errors so a giant message can't bloat the row.
         let error = if error.len() > 1000 { &error[..1000] } else { error };
         match self {
@@ -7572,6 +7612,7 @@ impl DatabasePool {
                 q.execute(pool).await?;
             }
         }
+        crate::services::webhooks::emit_refresh_failures(self, podcast_ids, error);
         Ok(())
     }
 
@@ -7816,6 +7857,7 @@ impl DatabasePool {
                 } else {
                     Some(entry.id.trim().to_string())
                 },
+                podcasting: None,
             };
 
             // Create data map to pass to Python-style parsing functions
@@ -9405,6 +9447,53 @@ impl DatabasePool {
         }
     }
 
+    // Record a user's video download so it shows as downloaded and counts toward their stats
+    pub async fn record_video_download(&self, user_id: i32, video_id: i32, size: i64, location: &str) -> AppResult<()> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                sqlx::query(r#"DELETE FROM "DownloadedVideos" WHERE userid = $1 AND videoid = $2"#)
+                    .bind(user_id)
+                    .bind(video_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(r#"INSERT INTO "DownloadedVideos" (userid, videoid, downloadedsize, downloadedlocation) VALUES ($1, $2, $3, $4)"#)
+                    .bind(user_id)
+                    .bind(video_id)
+                    .bind(size)
+                    .bind(location)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(r#"UPDATE "UserStats" SET episodesdownloaded = episodesdownloaded + 1 WHERE userid = $1"#)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                let mut tx = pool.begin().await?;
+                sqlx::query("DELETE FROM DownloadedVideos WHERE UserID = ? AND VideoID = ?")
+                    .bind(user_id)
+                    .bind(video_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("INSERT INTO DownloadedVideos (UserID, VideoID, DownloadedSize, DownloadedLocation) VALUES (?, ?, ?, ?)")
+                    .bind(user_id)
+                    .bind(video_id)
+                    .bind(size)
+                    .bind(location)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("UPDATE UserStats SET EpisodesDownloaded = EpisodesDownloaded + 1 WHERE UserID = ?")
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+
     // Delete downloaded episode
     pub async fn delete_episode(&self, user_id: i32, episode_id: i32, is_youtube: bool) -> AppResult<()> {
         match self {
@@ -10005,6 +10094,44 @@ impl DatabasePool {
         // Get authentication if available
         let username = podcast_details.get("username").and_then(|v| v.as_str());
         let password = podcast_details.get("password").and_then(|v| v.as_str());
+
+        // Serve the tags persisted at refresh time. Only podcasts not refreshed since tag
+        // persistence existed fall through to scraping the live feed below.
+        if let Some(channel) = self.get_podcast_podcasting_tags(podcast_id).await? {
+            let (item, cached_chapters) = self.get_episode_podcasting_tags(episode_id).await?.unwrap_or_default();
+
+            let chapters = match (cached_chapters, &item.chapters_url) {
+                (Some(cached), _) => serde_json::from_str(&cached).unwrap_or_else(|_| serde_json::json!([])),
+                (None, Some(url)) => match self.fetch_chapters_data(url, feed_url, username, password).await {
+                    Ok(chapters) => {
+                        if let Err(e) = self.cache_episode_chapters(episode_id, &chapters.to_string()).await {
+                            tracing::warn!("Failed to cache chapters for episode {}: {}", episode_id, e);
+                        }
+                        chapters
+                    }
+                    Err(_) => serde_json::json!([]),
+                },
+                (None, None) => serde_json::json!([]),
+            };
+
+            // Same shape as the scrape path: channel people followed by the episode's own.
+            let mut people = channel.persons;
+            for person in item.persons {
+                if !people.contains(&person) {
+                    people.push(person);
+                }
+            }
+
+            return Ok(serde_json::json!({
+                "chapters": chapters,
+                "transcripts": item.transcripts,
+                "people": people,
+                "soundbites": item.soundbites,
+                "season": item.season,
+                "episode": item.episode,
+                "value": item.value
+            }));
+        }
         
         // Fetch the RSS feed with authentication if needed
         let feed_content = self.try_fetch_feed(feed_url, username, password).await?;
@@ -10314,13 +10441,38 @@ impl DatabasePool {
         // Get authentication if available
         let username = podcast_details.get("username").and_then(|v| v.as_str());
         let password = podcast_details.get("password").and_then(|v| v.as_str());
+
+        // Get podcast index ID for PodPeople API fallback
+        let podcast_index_id = self.get_podcast_index_id(podcast_id).await.ok().flatten();
+
+        // Serve the tags persisted at refresh time; scrape only podcasts not yet refreshed since.
+        if let Some(channel) = self.get_podcast_podcasting_tags(podcast_id).await? {
+            let mut people: Vec<serde_json::Value> = channel
+                .persons
+                .into_iter()
+                .filter(|p| p.role.as_deref().is_none_or(|r| r.eq_ignore_ascii_case("host")))
+                .map(|p| serde_json::json!(p))
+                .collect();
+            if people.is_empty() {
+                people = self.fallback_podcast_hosts(podcast_index_id).await;
+            }
+            let podroll: Vec<serde_json::Value> = channel
+                .podroll
+                .into_iter()
+                .map(|feed_guid| serde_json::json!({ "feed_guid": feed_guid }))
+                .collect();
+
+            return Ok(serde_json::json!({
+                "people": people,
+                "podroll": podroll,
+                "funding": channel.funding,
+                "value": channel.value
+            }));
+        }
         
         // Fetch the RSS feed with authentication if needed
         let feed_content = self.try_fetch_feed(feed_url, username, password).await?;
         
-        // Get podcast index ID for PodPeople API fallback
-        let podcast_index_id = self.get_podcast_index_id(podcast_id).await.ok().flatten();
-        
         // Parse podcasting 2.0 features at the podcast level
         let people = self.parse_podcast_people(&feed_content, podcast_index_id).await?;
         let podroll = self.parse_podroll(&feed_content)?;
@@ -10432,67 +10584,133 @@ impl DatabasePool {
         
         // If no hosts found, try to get them from PodPeople API (like Python does)
         if people.is_empty() {
-            if let Some(index_id) = podcast_index_id {
-                if let Ok(podpeople_hosts) = self.get_podpeople_hosts(index_id).await {
-                    people = podpeople_hosts;
-                }
-            }
-            
-            // Final fallback if PodPeople API also fails
-            if people.is_empty() {
-                people.push(serde_json::json!({
-                    "name": "Unknown Host",
-                    "role": "Host",
-                    "group": null,
-                    "img": null,
-                    "href": null,
-                    "description": "No host information available."
-                }));
-            }
+            people = self.fallback_podcast_hosts(podcast_index_id).await;
         }
         
         Ok(serde_json::Value::Array(people))
     }
 
-    // Get podcast index ID - matches Python get_podcast_index_id function
-    pub async fn get_podcast_index_id(&self, podcast_id: i32) -> AppResult<Option<i32>> {
-        match self {
+    // Hosts for a feed that lists none: PodPeople API, then a placeholder entry
+    async fn fallback_podcast_hosts(&self, podcast_index_id: Option<i32>) -> Vec<serde_json::Value> {
+        let mut people = Vec::new();
+        if let Some(index_id) = podcast_index_id {
+            if let Ok(podpeople_hosts) = self.get_podpeople_hosts(index_id).await {
+                people = podpeople_hosts;
+            }
+        }
+
+        // Final fallback if PodPeople API also fails
+        if people.is_empty() {
+            people.push(serde_json::json!({
+                "name": "Unknown Host",
+                "role": "Host",
+                "group": null,
+                "img": null,
+                "href": null,
+                "description": "No host information available."
+            }));
+        }
+        people
+    }
+
+    // Channel-level Podcasting 2.0 tags stored at refresh time. None means the feed hasn't been
+    // parsed since tag persistence was added.
+    async fn get_podcast_podcasting_tags(&self, podcast_id: i32) -> AppResult<Option<crate::services::podcast_namespace::ChannelTags>> {
+        let data: Option<String> = match self {
             DatabasePool::Postgres(pool) => {
-                let row = sqlx::query(r#"SELECT podcastindexid FROM "Podcasts" WHERE podcastid = $1"#)
+                sqlx::query_scalar(r#"SELECT data FROM "PodcastPodcastingData" WHERE podcastid = $1"#)
                     .bind(podcast_id)
                     .fetch_optional(pool)
-                    .await?;
-                
-                if let Some(row) = row {
-                    Ok(row.try_get("podcastindexid").ok())
-                } else {
-                    Ok(None)
-                }
+                    .await?
             }
             DatabasePool::MySQL(pool) => {
-                let row = sqlx::query("SELECT PodcastIndexID FROM Podcasts WHERE PodcastID = ?")
+                sqlx::query_scalar("SELECT Data FROM PodcastPodcastingData WHERE PodcastID = ?")
                     .bind(podcast_id)
                     .fetch_optional(pool)
-                    .await?;
-                
-                if let Some(row) = row {
-                    Ok(row.try_get("PodcastIndexID").ok())
-                } else {
-                    Ok(None)
-                }
+                    .await?
             }
-        }
+        };
+        Ok(data.and_then(|d| serde_json::from_str(&d).ok()))
     }
 
-    // Get hosts from PodPeople API - matches Python get_podpeople_hosts function
-    async fn get_podpeople_hosts(&self, podcast_index_id: i32) -> AppResult<Vec<serde_json::Value>> {
-        use reqwest;
-        
-        // Get PodPeople API URL from environment, default to pinepods.online
-        let people_url = std::env::var("PEOPLE_API_URL")
-            .unwrap_or_else(|_| "https://people.pinepods.online".to_string());
-        
-        let client = reqwest::Client::new();
+    // Item-level Podcasting 2.0 tags for an episode, plus the cached chapters JSON if fetched yet
+    async fn get_episode_podcasting_tags(&self, episode_id: i32) -> AppResult<Option<(crate::services::podcast_namespace::ItemTags, Option<String>)>> {
+        let row: Option<(String, Option<String>)> = match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query_as(r#"SELECT data, chaptersdata FROM "EpisodePodcastingData" WHERE episodeid = $1"#)
+                    .bind(episode_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query_as("SELECT Data, ChaptersData FROM EpisodePodcastingData WHERE EpisodeID = ?")
+                    .bind(episode_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+        };
+        Ok(row.and_then(|(data, chapters)| serde_json::from_str(&data).ok().map(|tags| (tags, chapters))))
+    }
+
+    async fn cache_episode_chapters(&self, episode_id: i32, chapters: &str) -> AppResult<()> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"UPDATE "EpisodePodcastingData" SET chaptersdata = $1 WHERE episodeid = $2"#)
+                    .bind(chapters)
+                    .bind(episode_id)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("UPDATE EpisodePodcastingData SET ChaptersData = ? WHERE EpisodeID = ?")
+                    .bind(chapters)
+                    .bind(episode_id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Get podcast index ID - matches Python get_podcast_index_id function
+    pub async fn get_podcast_index_id(&self, podcast_id: i32) -> AppResult<Option<i32>> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let row = sqlx::query(r#"SELECT podcastindexid FROM "Podcasts" WHERE podcastid = $1"#)
+                    .bind(podcast_id)
+                    .fetch_optional(pool)
+                    .await?;
+                
+                if let Some(row) = row {
+                    Ok(row.try_get("podcastindexid").ok())
+                } else {
+                    Ok(None)
+                }
+            }
+            DatabasePool::MySQL(pool) => {
+                let row = sqlx::query("SELECT PodcastIndexID FROM Podcasts WHERE PodcastID = ?")
+                    .bind(podcast_id)
+                    .fetch_optional(pool)
+                    .await?;
+                
+                if let Some(row) = row {
+                    Ok(row.try_get("PodcastIndexID").ok())
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    // Get hosts from PodPeople API - matches Python get_podpeople_hosts function
+    async fn get_podpeople_hosts(&self, podcast_index_id: i32) -> AppResult<Vec<serde_json::Value>> {
+        use reqwest;
+        
+        // Get PodPeople API URL from environment, default to pinepods.online
+        let people_url = std::env::var("PEOPLE_API_URL")
+            .unwrap_or_else(|_| "https://people.pinepods.online".to_string());
+        
+        let client = reqwest::Client::new();
         let url = format!("{}/api/hosts/{}", people_url, podcast_index_id);
         
         // Make request with 5 second timeout (matches Python)
@@ -13635,13 +13853,15 @@ impl DatabasePool {
                 let query = if is_admin {
                     // Admin sees all API keys
                     r#"SELECT a.apikeyid, a.userid, u.username, RIGHT(a.apikey, 4) as lastfourdigits, 
-                       a.created::text as created
+                       a.created::text as created, a.name, a.scopes, a.expiresat::text as expiresat,
+                       a.lastused::text as lastused, a.revoked
                        FROM "APIKeys" a
                        JOIN "Users" u ON a.userid = u.userid"#
                 } else {
                     // Non-admin sees only their own API keys
                     r#"SELECT a.apikeyid, a.userid, u.username, RIGHT(a.apikey, 4) as lastfourdigits,
-                       a.created::text as created
+                       a.created::text as created, a.name, a.scopes, a.expiresat::text as expiresat,
+                       a.lastused::text as lastused, a.revoked
                        FROM "APIKeys" a
                        JOIN "Users" u ON a.userid = u.userid
                        WHERE a.userid = $1"#
@@ -13662,6 +13882,11 @@ impl DatabasePool {
                         lastfourdigits: row.try_get("lastfourdigits")?,
                         created: row.try_get("created")?,
                         podcastids: vec![], // Empty array as in Python
+                        name: row.try_get("name")?,
+                        scopes: row.try_get("scopes")?,
+                        expiresat: row.try_get("expiresat")?,
+                        lastused: row.try_get("lastused")?,
+                        revoked: row.try_get("revoked")?,
                     });
                 }
 
@@ -13683,13 +13908,15 @@ impl DatabasePool {
                 let query = if is_admin {
                     // Admin sees all API keys
                     "SELECT a.APIKeyID as apikeyid, a.UserID as userid, u.Username as username, RIGHT(a.APIKey, 4) as lastfourdigits,
-                     a.Created as created
+                     a.Created as created, a.Name as name, a.Scopes as scopes, a.ExpiresAt as expiresat,
+                     a.LastUsed as lastused, a.Revoked as revoked
                      FROM APIKeys a
                      JOIN Users u ON a.UserID = u.UserID"
                 } else {
                     // Non-admin sees only their own API keys  
                     "SELECT a.APIKeyID as apikeyid, a.UserID as userid, u.Username as username, RIGHT(a.APIKey, 4) as lastfourdigits,
-                     a.Created as created
+                     a.Created as created, a.Name as name, a.Scopes as scopes, a.ExpiresAt as expiresat,
+                     a.LastUsed as lastused, a.Revoked as revoked
                      FROM APIKeys a
                      JOIN Users u ON a.UserID = u.UserID
                      WHERE a.UserID = ?"
@@ -13710,6 +13937,11 @@ impl DatabasePool {
                         lastfourdigits: row.try_get("lastfourdigits")?,
                         created: row.try_get::<chrono::DateTime<chrono::Utc>, _>("created")?.to_string(),
                         podcastids: vec![], // Empty array as in Python
+                        name: row.try_get("name")?,
+                        scopes: row.try_get("scopes")?,
+                        expiresat: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("expiresat")?.map(|d| d.to_string()),
+                        lastused: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("lastused")?.map(|d| d.to_string()),
+                        revoked: row.try_get("revoked")?,
                     });
                 }
 
@@ -13724,6 +13956,18 @@ impl DatabasePool {
 
     // Create API key - matches Python create_api_key function exactly
     pub async fn create_api_key(&self, user_id: i32) -> AppResult<String> {
+        self.create_scoped_api_key(user_id, None, None, None).await
+    }
+
+    // Create an API key with an optional display name, scope list (comma-separated, see
+    // services::api_scopes) and lifetime in days. None for scopes = unrestricted key.
+    pub async fn create_scoped_api_key(
+        &self,
+        user_id: i32,
+        name: Option<&str>,
+        scopes: Option<&str>,
+        expires_in_days: Option<i32>,
+    ) -> AppResult<String> {
         use rand::RngExt;
         
         // Generate 64-character API key
@@ -13742,16 +13986,31 @@ impl DatabasePool {
 
         match self {
             DatabasePool::Postgres(pool) => {
-                sqlx::query(r#"INSERT INTO "APIKeys" (userid, apikey) VALUES ($1, $2)"#)
+                sqlx::query(r#"
+                    INSERT INTO "APIKeys" (userid, apikey, name, scopes, expiresat)
+                    VALUES ($1, $2, $3, $4,
+                            CASE WHEN $5::INT IS NULL THEN NULL
+                                 ELSE CURRENT_TIMESTAMP + ($5::INT * INTERVAL '1 day') END)
+                "#)
                     .bind(user_id)
                     .bind(&api_key)
+                    .bind(name)
+                    .bind(scopes)
+                    .bind(expires_in_days)
                     .execute(pool)
                     .await?;
             }
             DatabasePool::MySQL(pool) => {
-                sqlx::query("INSERT INTO APIKeys (UserID, APIKey) VALUES (?, ?)")
+                sqlx::query(
+                    "INSERT INTO APIKeys (UserID, APIKey, Name, Scopes, ExpiresAt)
+                     VALUES (?, ?, ?, ?, IF(? IS NULL, NULL, DATE_ADD(CURRENT_TIMESTAMP, INTERVAL ? DAY)))"
+                )
                     .bind(user_id)
                     .bind(&api_key)
+                    .bind(name)
+                    .bind(scopes)
+                    .bind(expires_in_days)
+                    .bind(expires_in_days)
                     .execute(pool)
                     .await?;
             }
@@ -13760,6 +14019,112 @@ impl DatabasePool {
         Ok(api_key)
     }
 
+    // Look up the scope/expiry policy of an API key for the central key-policy middleware.
+    // Returns None for strings that aren't API keys at all (e.g. RSS keys).
+    pub async fn get_api_key_policy(&self, api_key: &str) -> AppResult<Option<ApiKeyPolicy>> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let row = sqlx::query(r#"
+                    SELECT apikeyid, userid, scopes, revoked,
+                           (expiresat IS NOT NULL AND expiresat <= CURRENT_TIMESTAMP) AS expired
+                    FROM "APIKeys" WHERE apikey = $1
+                "#)
+                    .bind(api_key)
+                    .fetch_optional(pool)
+                    .await?;
+
+                match row {
+                    Some(row) => Ok(Some(ApiKeyPolicy {
+                        api_key_id: row.try_get("apikeyid")?,
+                        user_id: row.try_get("userid")?,
+                        scopes: row.try_get("scopes")?,
+                        revoked: row.try_get("revoked")?,
+                        expired: row.try_get("expired")?,
+                    })),
+                    None => Ok(None),
+                }
+            }
+            DatabasePool::MySQL(pool) => {
+                let row = sqlx::query(
+                    "SELECT APIKeyID, UserID, Scopes, Revoked,
+                            (ExpiresAt IS NOT NULL AND ExpiresAt <= CURRENT_TIMESTAMP) AS Expired
+                     FROM APIKeys WHERE APIKey = ?"
+                )
+                    .bind(api_key)
+                    .fetch_optional(pool)
+                    .await?;
+
+                match row {
+                    Some(row) => Ok(Some(ApiKeyPolicy {
+                        api_key_id: row.try_get("APIKeyID")?,
+                        user_id: row.try_get("UserID")?,
+                        scopes: row.try_get("Scopes")?,
+                        revoked: row.try_get("Revoked")?,
+                        expired: row.try_get::<i64, _>("Expired")? != 0,
+                    })),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    // Record that a key was used. Throttled in SQL to one write per key per minute so a
+    // chatty client (e.g. a progress-reporting player) doesn't turn every request into a write.
+    pub async fn touch_api_key_last_used(&self, api_key_id: i32) -> AppResult<()> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"
+                    UPDATE "APIKeys" SET lastused = CURRENT_TIMESTAMP
+                    WHERE apikeyid = $1
+                      AND (lastused IS NULL OR lastused < CURRENT_TIMESTAMP - INTERVAL '1 minute')
+                "#)
+                    .bind(api_key_id)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query(
+                    "UPDATE APIKeys SET LastUsed = CURRENT_TIMESTAMP
+                     WHERE APIKeyID = ?
+                       AND (LastUsed IS NULL OR LastUsed < DATE_SUB(CURRENT_TIMESTAMP, INTERVAL 1 MINUTE))"
+                )
+                    .bind(api_key_id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Revoke (but keep) an API key. Returns the key string so callers can drop cached
+    // validations for it, or None if no such key exists.
+    pub async fn revoke_api_key(&self, api_id: i32) -> AppResult<Option<String>> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let key: Option<String> = sqlx::query_scalar(
+                    r#"UPDATE "APIKeys" SET revoked = TRUE WHERE apikeyid = $1 RETURNING apikey"#
+                )
+                    .bind(api_id)
+                    .fetch_optional(pool)
+                    .await?;
+                Ok(key)
+            }
+            DatabasePool::MySQL(pool) => {
+                let key: Option<String> = sqlx::query_scalar("SELECT APIKey FROM APIKeys WHERE APIKeyID = ?")
+                    .bind(api_id)
+                    .fetch_optional(pool)
+                    .await?;
+                if key.is_some() {
+                    sqlx::query("UPDATE APIKeys SET Revoked = TRUE WHERE APIKeyID = ?")
+                        .bind(api_id)
+                        .execute(pool)
+                        .await?;
+                }
+                Ok(key)
+            }
+        }
+    }
+
     // Create RSS key - matches Python create_rss_key function exactly
     pub async fn create_rss_key(&self, user_id: i32, podcast_ids: Option<Vec<i32>>) -> AppResult<String> {
         use rand::RngExt;
@@ -13816,11 +14181,16 @@ impl DatabasePool {
         Ok(rss_key)
     }
 
-    // Count user API keys excluding a specific one - safety check for final API key
+    // Count user API keys excluding a specific one - safety check for final API key.
+    // Only active unrestricted keys count, since those are the ones login hands out.
     pub async fn count_user_api_keys_excluding(&self, user_id: i32, exclude_api_id: i32) -> AppResult<i32> {
         match self {
             DatabasePool::Postgres(pool) => {
-                let row = sqlx::query(r#"SELECT COUNT(*) as count FROM "APIKeys" WHERE userid = $1 AND apikeyid != $2"#)
+                let row = sqlx::query(r#"
+                    SELECT COUNT(*) as count FROM "APIKeys"
+                    WHERE userid = $1 AND apikeyid != $2 AND scopes IS NULL AND revoked = FALSE
+                      AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP)
+                "#)
                     .bind(user_id)
                     .bind(exclude_api_id)
                     .fetch_one(pool)
@@ -13828,7 +14198,11 @@ impl DatabasePool {
                 Ok(row.try_get::<i64, _>("count")? as i32)
             }
             DatabasePool::MySQL(pool) => {
-                let row = sqlx::query("SELECT COUNT(*) as count FROM APIKeys WHERE UserID = ? AND APIKeyID != ?")
+                let row = sqlx::query(
+                    "SELECT COUNT(*) as count FROM APIKeys
+                     WHERE UserID = ? AND APIKeyID != ? AND Scopes IS NULL AND Revoked = FALSE
+                       AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP)"
+                )
                     .bind(user_id)
                     .bind(exclude_api_id)
                     .fetch_one(pool)
@@ -13901,6 +14275,26 @@ impl DatabasePool {
         }
     }
 
+    // Get the key string for an API key ID - used to drop cached validations of a deleted key
+    pub async fn get_api_key_by_id(&self, api_id: i32) -> AppResult<Option<String>> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let result: Option<String> = sqlx::query_scalar(r#"SELECT apikey FROM "APIKeys" WHERE apikeyid = $1"#)
+                    .bind(api_id)
+                    .fetch_optional(pool)
+                    .await?;
+                Ok(result)
+            }
+            DatabasePool::MySQL(pool) => {
+                let result: Option<String> = sqlx::query_scalar("SELECT APIKey FROM APIKeys WHERE APIKeyID = ?")
+                    .bind(api_id)
+                    .fetch_optional(pool)
+                    .await?;
+                Ok(result)
+            }
+        }
+    }
+
     // Get the owner user ID of an API key by API key ID - for authorization checks
     pub async fn get_api_key_owner(&self, api_id: i32) -> AppResult<Option<i32>> {
         match self {
@@ -14015,6 +14409,15 @@ pub struct EpisodeData {
     /// guid (we deliberately do NOT use feed-rs's synthesized id, which can be a random UUID and
     /// would break dedup). Dedup falls back to URL/title when this is None.
     pub guid: Option<String>,
+    /// Podcasting 2.0 item tags, attached by parse_feed_body when the item carries any.
+    pub podcasting: Option<crate::services::podcast_namespace::ItemTags>,
+}
+
+/// A parsed feed body: the episode list plus the feed's channel-level Podcasting 2.0 tags.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFeed {
+    pub episodes: Vec<EpisodeData>,
+    pub channel: crate::services::podcast_namespace::ChannelTags,
 }
 
 /// Result of a conditional feed fetch (see `fetch_feed_conditional`).
@@ -14493,11 +14896,12 @@ impl DatabasePool {
         Ok((secret, qr_code_svg))
     }
     
-    // Store temporary MFA secret in memory (matches Python temp_mfa_secrets)
+    // Store temporary MFA secret in Redis (matches Python temp_mfa_secrets), so the confirming
+    // request may reach any replica
     async fn store_temp_mfa_secret(&self, key: &str, secret: &str) -> AppResult<()> {
-        let mut secrets = TEMP_MFA_SECRETS.lock().map_err(|e| AppError::internal(&format!("Failed to lock temp MFA secrets: {}", e)))?;
-        secrets.insert(key.to_string(), secret.to_string());
-        Ok(())
+        crate::services::cluster::redis()?
+            .set_ex(key, secret, TEMP_MFA_SECRET_TTL_SECONDS)
+            .await
     }
     
     // Verify temporary MFA code - matches Python verify_temp_mfa function exactly  
@@ -14534,16 +14938,14 @@ impl DatabasePool {
         Ok(verified)
     }
     
-    // Get temporary MFA secret from memory (matches Python temp_mfa_secrets lookup)
+    // Get temporary MFA secret (matches Python temp_mfa_secrets lookup)
     async fn get_temp_mfa_secret(&self, key: &str) -> AppResult<Option<String>> {
-        let secrets = TEMP_MFA_SECRETS.lock().map_err(|e| AppError::internal(&format!("Failed to lock temp MFA secrets: {}", e)))?;
-        Ok(secrets.get(key).cloned())
+        crate::services::cluster::redis()?.get(key).await
     }
     
-    // Remove temporary MFA secret from memory (matches Python temp_mfa_secrets cleanup)
+    // Remove temporary MFA secret (matches Python temp_mfa_secrets cleanup)
     async fn remove_temp_mfa_secret(&self, key: &str) -> AppResult<()> {
-        let mut secrets = TEMP_MFA_SECRETS.lock().map_err(|e| AppError::internal(&format!("Failed to lock temp MFA secrets: {}", e)))?;
-        secrets.remove(key);
+        crate::services::cluster::redis()?.delete(key).await?;
         Ok(())
     }
     
@@ -14822,7 +15224,7 @@ impl DatabasePool {
     async fn get_first_device_from_server(&self, gpodder_url: &str, username: &str, password: &str) -> AppResult<String> {
         let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
         
-        let devices_url = format!("{}/api/2/devices/{}.json", gpodder_url.trim_end_matches('/'), username);
+        let devices_url = format!("{}/api/2/devices/{}.json", crate::services::gpodder_sync::api_base(gpodder_url), username);
         
         let response = if session.authenticated {
             session.client.get(&devices_url).send().await
@@ -15202,7 +15604,7 @@ impl DatabasePool {
         };
         
         let devices_url = format!("{}/api/2/devices/{}.json", 
-            settings.url.trim_end_matches('/'), settings.username);
+            crate::services::gpodder_sync::api_base(&settings.url), settings.username);
         
         let mut request = client.get(&devices_url);
         
@@ -15263,7 +15665,7 @@ impl DatabasePool {
         
         // Try session-based authentication first - matches Python login flow
         let login_url = format!("{}/api/2/auth/{}/login.json", 
-            gpodder_url.trim_end_matches('/'), username);
+            crate::services::gpodder_sync::api_base(gpodder_url), username);
         
         let login_response = client
             .post(&login_url)
@@ -15357,894 +15759,76 @@ impl DatabasePool {
         })
     }
 
-    // Call gPodder service for sync - matches Python API calls exactly with enhanced error handling
+    // Incremental sync with a gPodder service (internal or external) through the sync engine. A
+    // regular sync that can't reach the server reports false; a forced one returns the error.
     async fn call_gpodder_service_sync(&self, user_id: i32, gpodder_url: &str, username: &str, password: &str, device_name: &str, force: bool) -> AppResult<bool> {
-        // Step 1: Get ALL devices first (critical for detecting changes from external devices like AntennaPod)
-        let devices_url = format!("{}/api/2/devices/{}.json", 
-            gpodder_url.trim_end_matches('/'), username);
-        
-        // Use correct authentication based on internal vs external
-        let devices_response = if gpodder_url == "http://localhost:8042" {
-            // Internal GPodder API - use X-GPodder-Token header
-            let client = reqwest::Client::new();
-            client.get(&devices_url)
-                .header("X-GPodder-Token", password)
-                .send()
-                .await
-        } else {
-            // External GPodder API - use session auth with basic fallback
-            let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-            if session.authenticated {
-                session.client
-                    .get(&devices_url)
-                    .send()
-                    .await
-            } else {
-                session.client
-                    .get(&devices_url)
-                    .basic_auth(username, Some(password))
-                    .send()
-                    .await
-            }
-        };
-        
-        let devices = match devices_response {
-            Ok(resp) if resp.status().is_success() => {
-                let devices_data: serde_json::Value = resp.json().await
-                    .map_err(|e| AppError::internal(&format!("Failed to parse devices: {}", e)))?;
-                
-                if let Some(devices_array) = devices_data.as_array() {
-                    let device_names: Vec<String> = devices_array.iter()
-                        .filter_map(|device| device.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                        .collect();
-                    
-                    tracing::info!("Found {} devices for user: {:?}", device_names.len(), device_names);
-                    device_names
-                } else {
-                    tracing::warn!("No devices found for user");
-                    vec![]
-                }
+        let remote = crate::services::gpodder_sync::Remote::gpodder(gpodder_url, username, password, device_name);
+        match crate::services::gpodder_sync::run(self, user_id, &remote, false).await {
+            Ok(_) => Ok(true),
+            Err(e) if !force => {
+                tracing::warn!("gPodder sync with {} failed for user {}: {}", gpodder_url, user_id, e);
+                Ok(false)
             }
-            Ok(resp) => {
-                tracing::warn!("Failed to get devices: {}", resp.status());
-                if force {
-                    return Err(AppError::internal(&format!("Force sync failed to get devices: {}", resp.status())));
-                } else {
-                    return Ok(false);
-                }
+            Err(e) => Err(e),
+        }
+    }
+
+    // Initial full sync for GPodder - reads everything the server has from ALL devices and pushes
+    // every local subscription and episode action
+    pub async fn call_gpodder_initial_full_sync(&self, user_id: i32, gpodder_url: &str, username: &str, password: &str, device_name: &str) -> AppResult<bool> {
+        tracing::info!("Starting initial full GPodder sync for user {} from {}", user_id, gpodder_url);
+        let remote = crate::services::gpodder_sync::Remote::gpodder(gpodder_url, username, password, device_name);
+        match crate::services::gpodder_sync::run(self, user_id, &remote, true).await {
+            Ok(_) => {
+                tracing::info!("Initial full GPodder sync completed for user {}", user_id);
+                Ok(true)
             }
             Err(e) => {
-                tracing::error!("Failed to connect to get devices: {}", e);
-                if force {
-                    return Err(AppError::internal(&format!("Force sync connection failed: {}", e)));
-                } else {
-                    return Ok(false);
+                tracing::error!("Initial full GPodder sync failed for user {}: {}", user_id, e);
+                Ok(false)
+            }
+        }
+    }
+    
+    // Initial full sync for Nextcloud - reads everything the server has and pushes every local
+    // subscription and episode action
+    pub async fn call_nextcloud_initial_full_sync(&self, user_id: i32, nextcloud_url: &str, username: &str, password: &str) -> AppResult<bool> {
+        tracing::info!("Starting initial full Nextcloud sync for user {} from {}", user_id, nextcloud_url);
+        let remote = crate::services::gpodder_sync::Remote::nextcloud(nextcloud_url, username, password);
+        crate::services::gpodder_sync::run(self, user_id, &remote, true).await?;
+        tracing::info!("Initial full Nextcloud sync completed for user {}", user_id);
+        Ok(true)
+    }
+    
+// Get user podcast feeds for sync
+    // Returns the user's syncable feed URLs. Excludes:
+    //  - private feeds (with credentials) - we don't push credentials to a sync server
+    //  - non-http(s) feeds (e.g. local:///opt/... local media, internal identifiers) - gpodder and
+    //    Nextcloud servers only accept http/https URLs and reject anything else, so including them
+    //    just produces doomed uploads and snapshot noise.
+    pub async fn get_user_podcast_feeds(&self, user_id: i32) -> AppResult<Vec<String>> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let rows = sqlx::query(r#"SELECT feedurl FROM "Podcasts" WHERE userid = $1 AND (username IS NULL OR username = '') AND (password IS NULL OR password = '') AND (feedurl LIKE 'http://%' OR feedurl LIKE 'https://%')"#)
+                    .bind(user_id)
+                    .fetch_all(pool)
+                    .await?;
+
+                let mut feeds = Vec::new();
+                for row in rows {
+                    feeds.push(row.try_get::<String, _>("feedurl")?);
                 }
+                Ok(feeds)
             }
-        };
-        
-        tracing::info!("Found {} devices to sync from: {:?}", devices.len(), devices);
-        
-        // Step 2: Get subscriptions from ALL devices with timestamps (like AntennaPod sync)
-        let since_timestamp = self.get_last_sync_timestamp(user_id).await?;
-        let mut all_subscriptions = std::collections::HashSet::new();
-        let mut all_removals = std::collections::HashSet::new();
-        
-        for device_id in &devices {
-            tracing::info!("Getting subscriptions from device: {}", device_id);
-            
-            let subscriptions_url = if let Some(since) = since_timestamp {
-                format!("{}/api/2/subscriptions/{}/{}.json?since={}", 
-                    gpodder_url.trim_end_matches('/'), username, device_id, since.timestamp())
-            } else {
-                format!("{}/api/2/subscriptions/{}/{}.json?since=0", 
-                    gpodder_url.trim_end_matches('/'), username, device_id)
-            };
-            
-            let device_response = if gpodder_url == "http://localhost:8042" {
-                let client = reqwest::Client::new();
-                client.get(&subscriptions_url)
-                    .header("X-GPodder-Token", password)
-                    .send()
-                    .await
-            } else {
-                let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-                if session.authenticated {
-                    session.client
-                        .get(&subscriptions_url)
-                        .send()
-                        .await
-                } else {
-                    session.client
-                        .get(&subscriptions_url)
-                        .basic_auth(username, Some(password))
-                        .send()
-                        .await
-                }
-            };
-            
-            match device_response {
-                Ok(resp) if resp.status().is_success() => {
-                    let response_text = resp.text().await
-                        .map_err(|e| AppError::internal(&format!("Failed to get response text: {}", e)))?;
-                    
-                    let sync_response: serde_json::Value = serde_json::from_str(&response_text)
-                        .map_err(|e| AppError::internal(&format!("Failed to parse gPodder sync response: {}", e)))?;
-                    
-                    let device_subscriptions = sync_response["add"].as_array()
-                        .unwrap_or(&vec![])
-                        .iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<String>>();
-                    
-                    let device_removals = sync_response["remove"].as_array()
-                        .unwrap_or(&vec![])
-                        .iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<String>>();
-                    
-                    tracing::info!("Device {} has {} subscriptions and {} removals", device_id, device_subscriptions.len(), device_removals.len());
-                    
-                    // Add to combined sets (HashSet automatically deduplicates)
-                    for subscription in device_subscriptions {
-                        all_subscriptions.insert(subscription);
-                    }
-                    
-                    for removal in device_removals {
-                        all_removals.insert(removal);
-                    }
-                }
-                Ok(resp) => {
-                    tracing::warn!("Device {} returned error: {}", device_id, resp.status());
-                    // Continue with other devices instead of failing
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get subscriptions from device {}: {}", device_id, e);
-                    // Continue with other devices instead of failing
-                }
-            }
-        }
-        
-        tracing::info!("Downloaded {} unique subscriptions and {} unique removals from ALL devices", all_subscriptions.len(), all_removals.len());
-        
-        // Step 3: Get episode actions with pagination support
-        // DON'T filter by device - this would exclude actions with NULL DeviceID
-        // The server limits responses to 25k actions, so we need to loop until we get all of them
-        let mut all_episode_actions = Vec::new();
-        const MAX_ACTIONS_PER_BATCH: usize = 25000;
-
-        tracing::info!("Getting ALL episode actions (no device filter to include NULL device actions)");
-
-        let initial_since = if let Some(since) = since_timestamp {
-            since.timestamp()
-        } else {
-            0
-        };
-
-        let mut current_since = initial_since;
-        let mut total_actions_fetched = 0;
-
-        loop {
-            // Fetch WITHOUT device parameter to get ALL actions including those with NULL DeviceID
-            let episode_actions_url = format!("{}/api/2/episodes/{}.json?since={}",
-                gpodder_url.trim_end_matches('/'), username, current_since);
-
-            let device_response = if gpodder_url == "http://localhost:8042" {
-                    let client = reqwest::Client::new();
-                    client.get(&episode_actions_url)
-                        .header("X-GPodder-Token", password)
-                        .send()
-                        .await
-                } else {
-                    let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-                    if session.authenticated {
-                        session.client
-                            .get(&episode_actions_url)
-                            .send()
-                            .await
-                    } else {
-                        session.client
-                            .get(&episode_actions_url)
-                            .basic_auth(username, Some(password))
-                            .send()
-                            .await
-                    }
-                };
-
-            match device_response {
-                Ok(resp) if resp.status().is_success() => {
-                    let response_text = resp.text().await
-                        .map_err(|e| AppError::internal(&format!("Failed to get episode actions response text: {}", e)))?;
-
-                    let episode_data: serde_json::Value = serde_json::from_str(&response_text)
-                        .map_err(|e| AppError::internal(&format!("Failed to parse episode actions response: {}", e)))?;
-
-                    let actions = episode_data.get("actions").and_then(|v| v.as_array()).cloned().unwrap_or_default();
-                    let batch_size = actions.len();
-                    let new_timestamp = episode_data.get("timestamp").and_then(|v| v.as_i64()).unwrap_or(current_since);
-
-                    tracing::info!("Fetched {} episode actions (since={})", batch_size, current_since);
-
-                    // Add actions from this batch
-                    for action in actions {
-                        all_episode_actions.push(action);
-                    }
-                    total_actions_fetched += batch_size;
-
-                    // If we got less than MAX_ACTIONS_PER_BATCH, we've reached the end
-                    if batch_size < MAX_ACTIONS_PER_BATCH {
-                        tracing::info!("Reached end of episode actions (got {} < {} limit)", batch_size, MAX_ACTIONS_PER_BATCH);
-                        break;
-                    }
-
-                    // Update since to the timestamp from the response for next iteration
-                    if new_timestamp > current_since {
-                        current_since = new_timestamp;
-                        tracing::info!("Updated since timestamp to {} for next batch", current_since);
-                    } else {
-                        tracing::warn!("Timestamp didn't advance, stopping pagination to avoid infinite loop");
-                        break;
-                    }
-                }
-                Ok(resp) => {
-                    tracing::warn!("Episode actions request returned error: {}", resp.status());
-                    break;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get episode actions: {}", e);
-                    break;
-                }
-            }
-        }
-
-        tracing::info!("Total: {} episode actions fetched from {} devices", total_actions_fetched, devices.len());
-
-        // Step 4: Process all subscriptions (additions)
-        let subscriptions_vec: Vec<String> = all_subscriptions.into_iter().collect();
-        self.process_gpodder_subscriptions(user_id, &subscriptions_vec).await?;
-
-        // Step 5: Process all subscription removals
-        let removals_vec: Vec<String> = all_removals.into_iter().collect();
-        if !removals_vec.is_empty() {
-            self.process_gpodder_subscription_removals(user_id, &removals_vec).await?;
-        }
-
-        // Step 6: Process all episode actions
-        if !all_episode_actions.is_empty() {
-            if let Err(e) = self.apply_remote_episode_actions(user_id, &all_episode_actions).await {
-                tracing::warn!("Episode actions processing failed but continuing: {}", e);
-            }
-        }
-        
-        // Step 7: Push genuine LOCAL subscription changes up to the gPodder service.
-        // We diff the current local feed set against the snapshot saved at the end of the last
-        // sync, so we only upload real local add/removes - never the full list (which would bloat
-        // the server change log) and never re-upload what the server just sent us (echo). This is
-        // also what propagates a local unsubscribe up to the server.
-        let local_subscriptions = self.get_user_podcast_feeds(user_id).await?;
-        let (mut to_add, mut to_remove) = self
-            .compute_subscription_delta(user_id, gpodder_url, &local_subscriptions)
-            .await?;
-        // Suppress echoes: don't push back changes that originated from the server this sync.
-        let server_added: std::collections::HashSet<&String> = subscriptions_vec.iter().collect();
-        let server_removed: std::collections::HashSet<&String> = removals_vec.iter().collect();
-        to_add.retain(|f| !server_added.contains(f));
-        to_remove.retain(|f| !server_removed.contains(f));
-        if let Err(e) = self
-            .upload_subscription_delta_to_gpodder(gpodder_url, username, password, device_name, &to_add, &to_remove)
-            .await
-        {
-            tracing::warn!("Subscription delta upload failed but continuing: {}", e);
-        }
-        // Record the reconciled local state as the new baseline for next time.
-        self.save_subscription_snapshot(user_id, gpodder_url, &local_subscriptions).await?;
-
-        // Step 8: Upload local episode actions since the last sync. Remote actions were already
-        // downloaded and applied in steps 3 and 6, so this is upload-only (no redundant re-fetch).
-        let local_actions = match since_timestamp {
-            Some(since) => self.get_user_episode_actions_since(user_id, since).await?,
-            None => self.get_user_episode_actions(user_id).await?,
-        };
-        if !local_actions.is_empty() {
-            if let Err(e) = self
-                .upload_episode_actions_to_gpodder(gpodder_url, username, password, &local_actions)
-                .await
-            {
-                tracing::warn!("Episode actions upload failed but continuing: {}", e);
-            }
-        }
-
-        // Step 9: Update last sync timestamp for next incremental sync
-        self.update_last_sync_timestamp(user_id).await?;
-
-        Ok(true)
-    }
-
-    // Initial full sync for GPodder - gets ALL user subscriptions from ALL devices
-    pub async fn call_gpodder_initial_full_sync(&self, user_id: i32, gpodder_url: &str, username: &str, password: &str, device_name: &str) -> AppResult<bool> {
-        tracing::info!("Starting initial full GPodder sync for user {} from {}", user_id, gpodder_url);
-        
-        // Step 1: Get ALL devices first (this is how AntennaPod and other apps do it)
-        let devices_url = format!("{}/api/2/devices/{}.json", 
-            gpodder_url.trim_end_matches('/'), username);
-        
-        let devices_response = if gpodder_url == "http://localhost:8042" {
-            let client = reqwest::Client::new();
-            client.get(&devices_url).header("X-GPodder-Token", password).send().await
-        } else {
-            let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-            if session.authenticated {
-                session.client.get(&devices_url).send().await
-            } else {
-                session.client.get(&devices_url).basic_auth(username, Some(password)).send().await
-            }
-        };
-        
-        let devices = match devices_response {
-            Ok(resp) if resp.status().is_success() => {
-                let devices_data: serde_json::Value = resp.json().await
-                    .map_err(|e| AppError::internal(&format!("Failed to parse devices: {}", e)))?;
-                
-                if let Some(devices_array) = devices_data.as_array() {
-                    let device_names: Vec<String> = devices_array.iter()
-                        .filter_map(|device| device.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                        .collect();
-                    
-                    tracing::info!("Found {} devices for user: {:?}", device_names.len(), device_names);
-                    device_names
-                } else {
-                    tracing::warn!("No devices found for user");
-                    vec![]
-                }
-            }
-            Ok(resp) => {
-                tracing::error!("Failed to get devices: {}", resp.status());
-                return Ok(false);
-            }
-            Err(e) => {
-                tracing::error!("Failed to connect for devices: {}", e);
-                return Ok(false);
-            }
-        };
-        
-        // Step 2: Get subscriptions from ALL devices (like AntennaPod does)
-        let mut all_subscriptions = std::collections::HashSet::new();
-        
-        for device_id in &devices {
-            tracing::info!("Getting subscriptions from device: {}", device_id);
-            
-            let subscriptions_url = format!("{}/api/2/subscriptions/{}/{}.json?since=0", 
-                gpodder_url.trim_end_matches('/'), username, device_id);
-            
-            let response = if gpodder_url == "http://localhost:8042" {
-                let client = reqwest::Client::new();
-                client.get(&subscriptions_url).header("X-GPodder-Token", password).send().await
-            } else {
-                let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-                if session.authenticated {
-                    session.client.get(&subscriptions_url).send().await
-                } else {
-                    session.client.get(&subscriptions_url).basic_auth(username, Some(password)).send().await
-                }
-            };
-            
-            match response {
-                Ok(resp) if resp.status().is_success() => {
-                    let response_text = resp.text().await
-                        .map_err(|e| AppError::internal(&format!("Failed to get response text: {}", e)))?;
-                    
-                    let sync_response: serde_json::Value = serde_json::from_str(&response_text)
-                        .map_err(|e| AppError::internal(&format!("Failed to parse gPodder sync response: {}", e)))?;
-                    
-                    let device_subscriptions = sync_response["add"].as_array()
-                        .unwrap_or(&vec![])
-                        .iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<String>>();
-                    
-                    tracing::info!("Device {} has {} subscriptions", device_id, device_subscriptions.len());
-                    
-                    // Add all subscriptions to our set (deduplicates automatically)
-                    for subscription in device_subscriptions {
-                        all_subscriptions.insert(subscription);
-                    }
-                }
-                Ok(resp) => {
-                    tracing::warn!("Failed to get subscriptions from device {}: {}", device_id, resp.status());
-                    // Continue with other devices
-                }
-                Err(e) => {
-                    tracing::warn!("Error getting subscriptions from device {}: {}", device_id, e);
-                    // Continue with other devices
-                }
-            }
-        }
-        
-        let all_subscriptions: Vec<String> = all_subscriptions.into_iter().collect();
-        tracing::info!("Total unique subscriptions from all devices: {}", all_subscriptions.len());
-        
-        // Step 2: Get episode actions from ALL devices with pagination support
-        // The server limits responses to 25k actions, so we need to loop until we get all of them
-        let mut all_episode_actions = Vec::new();
-        const MAX_ACTIONS_PER_BATCH: usize = 25000;
-
-        // DON'T filter by device for initial sync - this would exclude actions with NULL DeviceID
-        // The GPodder API filters WHERE DeviceID = X, which excludes NULL device actions
-        // For full sync, we want ALL actions regardless of device
-        tracing::info!("Getting ALL episode actions (not filtering by device to include NULL device actions)");
-
-        let mut current_since: i64 = 0; // Start from epoch
-        let mut total_actions_fetched = 0;
-
-        loop {
-            // Fetch WITHOUT device parameter to get ALL actions including those with NULL DeviceID
-            let episode_actions_url = format!("{}/api/2/episodes/{}.json?since={}",
-                gpodder_url.trim_end_matches('/'), username, current_since);
-
-                let response = if gpodder_url == "http://localhost:8042" {
-                    let client = reqwest::Client::new();
-                    client.get(&episode_actions_url).header("X-GPodder-Token", password).send().await
-                } else {
-                    let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-                    if session.authenticated {
-                        session.client.get(&episode_actions_url).send().await
-                    } else {
-                        session.client.get(&episode_actions_url).basic_auth(username, Some(password)).send().await
-                    }
-                };
-
-            match response {
-                Ok(resp) if resp.status().is_success() => {
-                    let episode_data: serde_json::Value = resp.json().await
-                        .map_err(|e| AppError::internal(&format!("Failed to parse episode actions: {}", e)))?;
-
-                    let actions = episode_data.get("actions").and_then(|v| v.as_array()).cloned().unwrap_or_default();
-                    let batch_size = actions.len();
-                    let new_timestamp = episode_data.get("timestamp").and_then(|v| v.as_i64()).unwrap_or(current_since);
-
-                    tracing::info!("Fetched {} episode actions (since={})", batch_size, current_since);
-
-                    // Add actions from this batch
-                    for action in actions {
-                        all_episode_actions.push(action);
-                    }
-                    total_actions_fetched += batch_size;
-
-                    // If we got less than MAX_ACTIONS_PER_BATCH, we've reached the end
-                    if batch_size < MAX_ACTIONS_PER_BATCH {
-                        tracing::info!("Reached end of episode actions (got {} < {} limit)", batch_size, MAX_ACTIONS_PER_BATCH);
-                        break;
-                    }
-
-                    // Update since to the timestamp from the response for next iteration
-                    if new_timestamp > current_since {
-                        current_since = new_timestamp;
-                        tracing::info!("Updated since timestamp to {} for next batch", current_since);
-                    } else {
-                        tracing::warn!("Timestamp didn't advance, stopping pagination to avoid infinite loop");
-                        break;
-                    }
-                }
-                Ok(resp) => {
-                    tracing::warn!("Failed to get episode actions: {}", resp.status());
-                    break;
-                }
-                Err(e) => {
-                    tracing::warn!("Error getting episode actions: {}", e);
-                    break;
-                }
-            }
-        }
-
-        tracing::info!("Total: {} episode actions fetched from {} devices", total_actions_fetched, devices.len());
-
-        // Step 3: Process all subscriptions and add missing podcasts FIRST
-        // This ensures podcasts and their episodes are in the database before applying episode actions
-        self.process_gpodder_subscriptions(user_id, &all_subscriptions).await?;
-
-        // Process all episode actions and apply them locally AFTER subscriptions are processed
-        if !all_episode_actions.is_empty() {
-            if let Err(e) = self.apply_remote_episode_actions(user_id, &all_episode_actions).await {
-                tracing::warn!("Failed to apply remote episode actions: {}", e);
-            }
-        }
-        
-        // Step 4: Upload local subscriptions to GPodder service (full list for the initial sync)
-        let local_subscriptions = self.get_user_podcast_feeds(user_id).await?;
-        self.upload_subscriptions_to_gpodder(gpodder_url, username, password, device_name, &local_subscriptions).await?;
-        // Save the snapshot baseline so later incremental syncs upload only genuine local deltas.
-        self.save_subscription_snapshot(user_id, gpodder_url, &local_subscriptions).await?;
-
-        // Step 5: Upload local episode actions to GPodder service
-        let local_episode_actions = self.get_user_episode_actions(user_id).await?;
-        if !local_episode_actions.is_empty() {
-            if let Err(e) = self.upload_episode_actions_to_gpodder(gpodder_url, username, password, &local_episode_actions).await {
-                tracing::warn!("Failed to upload local episode actions to GPodder: {}", e);
-                // Don't fail the sync if episode actions upload fails
-            }
-        }
-        
-        // Step 5: Clear any existing sync timestamp to start fresh for incremental syncs
-        self.clear_last_sync_timestamp(user_id).await?;
-        
-        tracing::info!("Initial full GPodder sync completed for user {}", user_id);
-        Ok(true)
-    }
-    
-    // Initial full sync for Nextcloud - gets ALL user subscriptions 
-    pub async fn call_nextcloud_initial_full_sync(&self, user_id: i32, nextcloud_url: &str, username: &str, password: &str) -> AppResult<bool> {
-        tracing::info!("Starting initial full Nextcloud sync for user {} from {}", user_id, nextcloud_url);
-        
-        let client = reqwest::Client::new();
-        
-        // Get ALL subscriptions from Nextcloud gPodder Sync app
-        let subscriptions_url = format!("{}/index.php/apps/gpoddersync/subscriptions", nextcloud_url.trim_end_matches('/'));
-        
-        let response = client
-            .get(&subscriptions_url)
-            .basic_auth(username, Some(password))
-            .send()
-            .await
-            .map_err(|e| AppError::internal(&format!("Failed to get Nextcloud subscriptions: {}", e)))?;
-        
-        if !response.status().is_success() {
-            tracing::error!("Failed to get Nextcloud subscriptions: {}", response.status());
-            return Ok(false);
-        }
-        
-        let subscriptions: serde_json::Value = response.json().await
-            .map_err(|e| AppError::internal(&format!("Failed to parse Nextcloud subscriptions: {}", e)))?;
-        
-        // The gPodder Sync app returns an object {"add": [...], "remove": [...], "timestamp": N}.
-        // (Older/other servers may return a bare array of feed URLs, so handle both.)
-        let feed_urls: Vec<String> = if let Some(add_list) = subscriptions.get("add").and_then(|v| v.as_array()) {
-            add_list.iter().filter_map(|f| f.as_str().map(|s| s.to_string())).collect()
-        } else if let Some(feeds) = subscriptions.as_array() {
-            feeds.iter().filter_map(|f| f.as_str().map(|s| s.to_string())).collect()
-        } else {
-            tracing::warn!("No subscriptions found in Nextcloud response: {:?}", subscriptions);
-            vec![]
-        };
-        tracing::info!("Downloaded {} subscriptions from Nextcloud", feed_urls.len());
-        
-        // Get ALL episode actions from Nextcloud
-        let episode_actions_url = format!("{}/index.php/apps/gpoddersync/episode_action", nextcloud_url.trim_end_matches('/'));
-        
-        let episode_response = client
-            .get(&episode_actions_url)
-            .basic_auth(username, Some(password))
-            .send()
-            .await;
-        
-        let mut all_episode_actions = Vec::new();
-        
-        match episode_response {
-            Ok(resp) if resp.status().is_success() => {
-                let episode_data: serde_json::Value = resp.json().await
-                    .map_err(|e| AppError::internal(&format!("Failed to parse Nextcloud episode actions: {}", e)))?;
-                
-                if let Some(actions) = episode_data.get("actions").and_then(|v| v.as_array()) {
-                    tracing::info!("Downloaded {} episode actions from Nextcloud", actions.len());
-                    
-                    // Add all episode actions
-                    for action in actions {
-                        all_episode_actions.push(action.clone());
-                    }
-                }
-            }
-            Ok(resp) => {
-                tracing::warn!("Failed to get Nextcloud episode actions: {}", resp.status());
-                // Continue even if episode actions fail
-            }
-            Err(e) => {
-                tracing::warn!("Error getting Nextcloud episode actions: {}", e);
-                // Continue even if episode actions fail
-            }
-        }
-        
-        // Process all episode actions and apply them locally
-        if !all_episode_actions.is_empty() {
-            if let Err(e) = self.apply_remote_episode_actions(user_id, &all_episode_actions).await {
-                tracing::warn!("Failed to apply remote episode actions from Nextcloud: {}", e);
-            }
-        }
-        
-        // Process all subscriptions and add missing podcasts
-        self.process_gpodder_subscriptions(user_id, &feed_urls).await?;
-        
-        // Upload local subscriptions to Nextcloud (full list for the initial sync)
-        let local_subscriptions = self.get_user_podcast_feeds(user_id).await?;
-        self.upload_subscriptions_to_nextcloud(nextcloud_url, username, password, &local_subscriptions).await?;
-        // Save the snapshot baseline (same target key as incremental sync: URL without trailing slash).
-        self.save_subscription_snapshot(user_id, nextcloud_url.trim_end_matches('/'), &local_subscriptions).await?;
-
-        // Upload local episode actions to Nextcloud
-        let local_episode_actions = self.get_user_episode_actions(user_id).await?;
-        if !local_episode_actions.is_empty() {
-            if let Err(e) = self.upload_episode_actions_to_nextcloud(nextcloud_url, username, password, &local_episode_actions).await {
-                tracing::warn!("Failed to upload local episode actions to Nextcloud: {}", e);
-                // Don't fail the sync if episode actions upload fails
-            }
-        }
-        
-        // Clear any existing sync timestamp to start fresh for incremental syncs
-        self.clear_last_sync_timestamp(user_id).await?;
-        
-        tracing::info!("Initial full Nextcloud sync completed for user {}", user_id);
-        Ok(true)
-    }
-    
-    // Upload episode actions to GPodder server for initial sync
-    async fn upload_episode_actions_to_gpodder(&self, gpodder_url: &str, username: &str, password: &str, episode_actions: &[serde_json::Value]) -> AppResult<()> {
-        let upload_url = format!("{}/api/2/episodes/{}.json", gpodder_url.trim_end_matches('/'), username);
-        
-        // Use correct authentication based on internal vs external
-        let response = if gpodder_url == "http://localhost:8042" {
-            // Internal GPodder API - use X-GPodder-Token header
-            let client = reqwest::Client::new();
-            client.post(&upload_url)
-                .header("X-GPodder-Token", password)
-                .json(episode_actions)
-                .send()
-                .await
-        } else {
-            // External GPodder API - use session auth with basic fallback
-            let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-            if session.authenticated {
-                // Use session-based authentication
-                session.client
-                    .post(&upload_url)
-                    .json(episode_actions)
-                    .send()
-                    .await
-            } else {
-                // Fallback to basic auth
-                session.client
-                    .post(&upload_url)
-                    .basic_auth(username, Some(password))
-                    .json(episode_actions)
-                    .send()
-                    .await
-            }
-        };
-        
-        match response {
-            Ok(resp) if resp.status().is_success() => {
-                tracing::info!("Successfully uploaded {} episode actions to GPodder", episode_actions.len());
-                Ok(())
-            }
-            Ok(resp) => {
-                tracing::warn!("Failed to upload episode actions to GPodder: {}", resp.status());
-                Ok(()) // Don't fail the whole sync if upload fails
-            }
-            Err(e) => {
-                Err(AppError::internal(&format!("Failed to upload episode actions to GPodder: {}", e)))
-            }
-        }
-    }
-    
-    // Upload subscriptions to Nextcloud using the gPodder Sync app endpoint
-    async fn upload_subscriptions_to_nextcloud(&self, nextcloud_url: &str, username: &str, password: &str, subscriptions: &[String]) -> AppResult<()> {
-        self.upload_subscription_changes_to_nextcloud(nextcloud_url, username, password, subscriptions, &[]).await
-    }
-
-    // Upload subscription add/remove changes using the gPodder Sync app's subscription_change/create
-    // endpoint. The app expects an object {"add": [...], "remove": [...]} (see AntennaPod's
-    // NextcloudSyncService.uploadSubscriptionChanges), not a bare array.
-    async fn upload_subscription_changes_to_nextcloud(&self, nextcloud_url: &str, username: &str, password: &str, add: &[String], remove: &[String]) -> AppResult<()> {
-        if add.is_empty() && remove.is_empty() {
-            return Ok(());
-        }
-
-        let client = reqwest::Client::new();
-        let upload_url = format!("{}/index.php/apps/gpoddersync/subscription_change/create", nextcloud_url.trim_end_matches('/'));
-
-        let body = serde_json::json!({
-            "add": add,
-            "remove": remove,
-        });
-
-        let response = client
-            .post(&upload_url)
-            .basic_auth(username, Some(password))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::internal(&format!("Failed to upload subscriptions to Nextcloud: {}", e)))?;
-
-        if response.status().is_success() {
-            tracing::info!("Successfully uploaded {} added / {} removed subscriptions to Nextcloud", add.len(), remove.len());
-            Ok(())
-        } else {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            tracing::warn!("Failed to upload subscriptions to Nextcloud: {} - {}", status, body);
-            Ok(()) // Don't fail the whole sync if upload fails
-        }
-    }
-    
-    // Upload episode actions to Nextcloud using the gPodder Sync app endpoint
-    async fn upload_episode_actions_to_nextcloud(&self, nextcloud_url: &str, username: &str, password: &str, episode_actions: &[serde_json::Value]) -> AppResult<()> {
-        let client = reqwest::Client::new();
-        // Nextcloud gPodder Sync app uses the episode_action endpoint  
-        let upload_url = format!("{}/index.php/apps/gpoddersync/episode_action/create", nextcloud_url.trim_end_matches('/'));
-        
-        let response = client
-            .post(&upload_url)
-            .basic_auth(username, Some(password))
-            .json(episode_actions)
-            .send()
-            .await
-            .map_err(|e| AppError::internal(&format!("Failed to upload episode actions to Nextcloud: {}", e)))?;
-        
-        if response.status().is_success() {
-            tracing::info!("Successfully uploaded {} episode actions to Nextcloud", episode_actions.len());
-            Ok(())
-        } else {
-            tracing::warn!("Failed to upload episode actions to Nextcloud: {}", response.status());
-            Ok(()) // Don't fail the whole sync if upload fails
-        }
-    }
-    
-
-    // Process gPodder subscriptions and add missing podcasts
-    async fn process_gpodder_subscriptions(&self, user_id: i32, subscriptions: &[String]) -> AppResult<()> {
-        let mut added = 0;
-        let mut skipped = 0;
-        let mut failed = 0;
-        
-        for feed_url in subscriptions {
-            tracing::info!("Processing podcast {} for user {}", feed_url, user_id);
-            
-            // Use the existing add_podcast_from_url function which handles duplicates and fetching
-            match self.add_podcast_from_url(user_id, feed_url, None).await {
-                Ok(_) => {
-                    added += 1;
-                    tracing::info!("Successfully added podcast: {}", feed_url);
-                }
-                Err(e) => {
-                    // Check if it failed because it already exists
-                    if self.podcast_exists_for_user(user_id, feed_url).await.unwrap_or(false) {
-                        skipped += 1;
-                        tracing::debug!("Podcast {} already exists for user {}", feed_url, user_id);
-                    } else {
-                        failed += 1;
-                        tracing::warn!("Failed to add podcast {}: {}", feed_url, e);
-                    }
-                }
-            }
-        }
-        
-        tracing::info!("GPodder subscription sync completed: {} added, {} skipped, {} failed", added, skipped, failed);
-        Ok(())
-    }
-
-    // Process subscription removals from gPodder service
-    async fn process_gpodder_subscription_removals(&self, user_id: i32, removals: &[String]) -> AppResult<()> {
-        let mut removed = 0;
-        let mut not_found = 0;
-        let mut failed = 0;
-        
-        for feed_url in removals {
-            tracing::info!("Processing podcast removal {} for user {}", feed_url, user_id);
-            
-            // Check if the podcast exists locally first
-            if self.podcast_exists_for_user(user_id, feed_url).await.unwrap_or(false) {
-                // Remove the podcast using existing function
-                match self.remove_podcast_by_url(user_id, feed_url).await {
-                    Ok(_) => {
-                        removed += 1;
-                        tracing::info!("Successfully removed podcast: {}", feed_url);
-                    }
-                    Err(e) => {
-                        failed += 1;
-                        tracing::warn!("Failed to remove podcast {}: {}", feed_url, e);
-                    }
-                }
-            } else {
-                not_found += 1;
-                tracing::debug!("Podcast {} not found locally for user {} (already removed)", feed_url, user_id);
-            }
-        }
-        
-        tracing::info!("GPodder subscription removal completed: {} removed, {} not found, {} failed", removed, not_found, failed);
-        Ok(())
-    }
-
-
-    // Upload local subscriptions to gPodder service - matches GPodder API spec POST /api/2/subscriptions/{username}/{device}.json
-    async fn upload_subscriptions_to_gpodder(&self, gpodder_url: &str, username: &str, password: &str, device_name: &str, subscriptions: &[String]) -> AppResult<()> {
-        self.upload_subscription_delta_to_gpodder(gpodder_url, username, password, device_name, subscriptions, &[]).await
-    }
-
-    // Upload subscription add/remove changes to a gPodder service (internal or external) following
-    // the GPodder API spec: POST /api/2/subscriptions/{user}/{device}.json with {"add":[...],"remove":[...]}.
-    async fn upload_subscription_delta_to_gpodder(&self, gpodder_url: &str, username: &str, password: &str, device_name: &str, add: &[String], remove: &[String]) -> AppResult<()> {
-        if add.is_empty() && remove.is_empty() {
-            return Ok(());
-        }
-
-        let upload_url = format!("{}/api/2/subscriptions/{}/{}.json", gpodder_url.trim_end_matches('/'), username, device_name);
-
-        // Format subscription changes according to GPodder API spec
-        let subscription_changes = serde_json::json!({
-            "add": add,
-            "remove": remove
-        });
-
-        // Use correct authentication based on internal vs external
-        let response = if gpodder_url == "http://localhost:8042" {
-            // Internal GPodder API - use X-GPodder-Token header
-            let client = reqwest::Client::new();
-            client.post(&upload_url)
-                .header("X-GPodder-Token", password)
-                .json(&subscription_changes)
-                .send()
-                .await
-        } else {
-            // External GPodder API - use session auth with basic fallback
-            let session = self.create_gpodder_session_with_password(gpodder_url, username, password).await?;
-            if session.authenticated {
-                session.client
-                    .post(&upload_url)
-                    .json(&subscription_changes)
-                    .send()
-                    .await
-            } else {
-                session.client
-                    .post(&upload_url)
-                    .basic_auth(username, Some(password))
-                    .json(&subscription_changes)
-                    .send()
-                    .await
-            }
-        };
-        
-        match response {
-            Ok(resp) if resp.status().is_success() => {
-                tracing::info!("Uploaded subscription delta to gPodder service: {} added, {} removed", add.len(), remove.len());
-                Ok(())
-            }
-            Ok(resp) => {
-                tracing::warn!("Failed to upload subscriptions to gPodder service: {}", resp.status());
-                Ok(()) // Don't fail sync for upload failures
-            }
-            Err(e) => {
-                tracing::warn!("Failed to upload subscriptions: {}", e);
-                Ok(()) // Don't fail sync for upload failures
-            }
-        }
-    }
-
-    // Get user podcast feeds for sync
-    // Returns the user's syncable feed URLs. Excludes:
-    //  - private feeds (with credentials) - we don't push credentials to a sync server
-    //  - non-http(s) feeds (e.g. local:///opt/... local media, internal identifiers) - gpodder and
-    //    Nextcloud servers only accept http/https URLs and reject anything else, so including them
-    //    just produces doomed uploads and snapshot noise.
-    async fn get_user_podcast_feeds(&self, user_id: i32) -> AppResult<Vec<String>> {
-        match self {
-            DatabasePool::Postgres(pool) => {
-                let rows = sqlx::query(r#"SELECT feedurl FROM "Podcasts" WHERE userid = $1 AND (username IS NULL OR username = '') AND (password IS NULL OR password = '') AND (feedurl LIKE 'http://%' OR feedurl LIKE 'https://%')"#)
-                    .bind(user_id)
-                    .fetch_all(pool)
-                    .await?;
-
-                let mut feeds = Vec::new();
-                for row in rows {
-                    feeds.push(row.try_get::<String, _>("feedurl")?);
-                }
-                Ok(feeds)
-            }
-            DatabasePool::MySQL(pool) => {
-                let rows = sqlx::query("SELECT FeedURL FROM Podcasts WHERE UserID = ? AND (Username IS NULL OR Username = '') AND (Password IS NULL OR Password = '') AND (FeedURL LIKE 'http://%' OR FeedURL LIKE 'https://%')")
-                    .bind(user_id)
-                    .fetch_all(pool)
-                    .await?;
-
-                let mut feeds = Vec::new();
-                for row in rows {
-                    feeds.push(row.try_get::<String, _>("FeedURL")?);
+            DatabasePool::MySQL(pool) => {
+                let rows = sqlx::query("SELECT FeedURL FROM Podcasts WHERE UserID = ? AND (Username IS NULL OR Username = '') AND (Password IS NULL OR Password = '') AND (FeedURL LIKE 'http://%' OR FeedURL LIKE 'https://%')")
+                    .bind(user_id)
+                    .fetch_all(pool)
+                    .await?;
+
+                let mut feeds = Vec::new();
+                for row in rows {
+                    feeds.push(row.try_get::<String, _>("FeedURL")?);
                 }
                 Ok(feeds)
             }
@@ -16253,7 +15837,7 @@ impl DatabasePool {
 
     // Get the set of feed URLs that were present locally at the end of the last sync to `target`.
     // Used to compute genuine local add/remove deltas to push up, without a per-change queue.
-    async fn get_subscription_snapshot(&self, user_id: i32, target: &str) -> AppResult<std::collections::HashSet<String>> {
+    pub async fn get_subscription_snapshot(&self, user_id: i32, target: &str) -> AppResult<std::collections::HashSet<String>> {
         let mut feeds = std::collections::HashSet::new();
         match self {
             DatabasePool::Postgres(pool) => {
@@ -16282,7 +15866,7 @@ impl DatabasePool {
 
     // Replace the stored snapshot for (user, target) with the given feed set. Called after a
     // successful sync so the next sync only uploads genuine local changes.
-    async fn save_subscription_snapshot(&self, user_id: i32, target: &str, feeds: &[String]) -> AppResult<()> {
+    pub async fn save_subscription_snapshot(&self, user_id: i32, target: &str, feeds: &[String]) -> AppResult<()> {
         match self {
             DatabasePool::Postgres(pool) => {
                 let mut tx = pool.begin().await?;
@@ -16322,143 +15906,7 @@ impl DatabasePool {
         Ok(())
     }
 
-    // Compute genuine local subscription changes since the last sync to `target` by diffing the
-    // current local feed set against the saved snapshot. Returns (to_add, to_remove):
-    //   to_add    = local now but not in snapshot  (subscribed locally since last sync)
-    //   to_remove = in snapshot but not local now   (unsubscribed locally since last sync)
-    // Private feeds (with credentials) are excluded by get_user_podcast_feeds, so they are never
-    // in the snapshot and never appear in either list - they are never pushed to a sync server.
-    async fn compute_subscription_delta(&self, user_id: i32, target: &str, local_feeds: &[String]) -> AppResult<(Vec<String>, Vec<String>)> {
-        let snapshot = self.get_subscription_snapshot(user_id, target).await?;
-        let local_set: std::collections::HashSet<String> = local_feeds.iter().cloned().collect();
-
-        let to_add: Vec<String> = local_feeds.iter().filter(|f| !snapshot.contains(*f)).cloned().collect();
-        let to_remove: Vec<String> = snapshot.iter().filter(|f| !local_set.contains(*f)).cloned().collect();
-        Ok((to_add, to_remove))
-    }
-
-    // Apply remote episode actions locally - matches Python apply_episode_actions function exactly
-    async fn apply_remote_episode_actions(&self, user_id: i32, actions: &[serde_json::Value]) -> AppResult<()> {
-        let total_actions = actions.len();
-        tracing::info!("Processing {} episode actions for user {}", total_actions, user_id);
-
-        let mut applied_count = 0;
-        let mut not_found_count = 0;
-        let mut not_found_urls: Vec<String> = Vec::new();
-
-        // Process with periodic progress logging
-        const LOG_INTERVAL: usize = 500; // Log progress every 500 actions
-
-        for (index, action) in actions.iter().enumerate() {
-            // Log progress periodically instead of for every action
-            if index > 0 && index % LOG_INTERVAL == 0 {
-                tracing::info!("Progress: {}/{} actions processed ({} applied, {} not found)",
-                              index, total_actions, applied_count, not_found_count);
-            }
-            if let (Some(episode_url), Some(action_type)) = (
-                action["episode"].as_str(),
-                action["action"].as_str()
-            ) {
-                match action_type {
-                    "play" => {
-                        // Handle both integer Unix timestamps and string timestamps
-                        let timestamp_opt = if let Some(timestamp_int) = action["timestamp"].as_i64() {
-                            // Unix timestamp as integer (gpodder standard format)
-                            Some(chrono::DateTime::from_timestamp(timestamp_int, 0).map(|dt| dt.naive_utc()).unwrap_or_else(|| chrono::Utc::now().naive_utc()))
-                        } else if let Some(timestamp_str) = action["timestamp"].as_str() {
-                            // String timestamp (alternative format)
-                            if let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(timestamp_str) {
-                                Some(parsed.naive_utc())
-                            } else if let Ok(parsed) = chrono::NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%dT%H:%M:%S") {
-                                Some(parsed)
-                            } else {
-                                tracing::warn!("Failed to parse timestamp string for episode action: {}", timestamp_str);
-                                None
-                            }
-                        } else {
-                            None
-                        };
-
-                        if let (Some(position), Some(timestamp)) = (
-                            action["position"].as_i64(),
-                            timestamp_opt
-                        ) {
-                            // Find local episode by URL
-                            if let Some(episode_id) = self.find_episode_by_url(user_id, episode_url).await? {
-                                
-                                // Get episode duration to check if it should be marked as complete
-                                if let Ok(episode_duration) = self.get_episode_duration(episode_id).await {
-                                    let position_sec = position as i32;
-                                    let remaining_time = episode_duration - position_sec;
-
-                                    // Mark complete if position is at/beyond the end OR within 60 seconds of completion
-                                    if episode_duration > 0 && (position_sec >= episode_duration || remaining_time <= 60) {
-                                        // At end or within 1 minute of completion - mark as complete
-                                        // GPodder sync only handles regular podcast episodes, never YouTube videos
-                                        match self.mark_episode_completed(episode_id, user_id, false).await {
-                                            Ok(_) => {
-                                                applied_count += 1;
-                                            }
-                                            Err(e) => {
-                                                tracing::debug!("Failed to mark episode as completed: {}", e);
-                                            }
-                                        }
-                                    } else {
-                                        // Update progress normally
-                                        match self.update_episode_progress(user_id, episode_id, position_sec, timestamp).await {
-                                            Ok(_) => {
-                                                applied_count += 1;
-                                            }
-                                            Err(e) => {
-                                                tracing::debug!("Failed to update episode progress: {}", e);
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    // Fallback to normal progress update if duration unavailable
-                                    match self.update_episode_progress(user_id, episode_id, position as i32, timestamp).await {
-                                        Ok(_) => {
-                                            applied_count += 1;
-                                        }
-                                        Err(e) => {
-                                            tracing::debug!("Failed to update episode progress (no duration): {}", e);
-                                        }
-                                    }
-                                }
-                            } else {
-                                not_found_count += 1;
-                                not_found_urls.push(episode_url.to_string());
-                            }
-                        }
-                    }
-                    "download" => {
-                        // Handle download actions if needed (currently not implemented)
-                        tracing::debug!("Download action for episode: {}", episode_url);
-                    }
-                    "delete" => {
-                        // Handle delete actions if needed (currently not implemented)
-                        tracing::debug!("Delete action for episode: {}", episode_url);
-                    }
-                    _ => {
-                        tracing::debug!("Unknown action type: {}", action_type);
-                    }
-                }
-            }
-        }
-        
-        tracing::info!("Episode actions processing complete: {}/{} applied, {} not found in local database",
-                      applied_count, total_actions, not_found_count);
-
-        // Log a sample of not-found URLs to help diagnose feed/episode URL mismatches
-        if !not_found_urls.is_empty() {
-            tracing::debug!("{} episode actions referenced episodes not in the local database (showing up to 20): {:?}",
-                          not_found_urls.len(), not_found_urls.iter().take(20).collect::<Vec<_>>());
-        }
-
-        Ok(())
-    }
-    
-    // Find episode ID by URL for user
+// Find episode ID by URL for user
     async fn find_episode_by_url(&self, user_id: i32, episode_url: &str) -> AppResult<Option<i32>> {
         match self {
             DatabasePool::Postgres(pool) => {
@@ -16495,50 +15943,12 @@ impl DatabasePool {
                     Ok(Some(row.try_get("EpisodeID")?))
                 } else {
                     Ok(None)
-                }
-            }
-        }
-    }
-    
-    // Update episode progress from remote sync
-    async fn update_episode_progress(&self, user_id: i32, episode_id: i32, position: i32, timestamp: chrono::NaiveDateTime) -> AppResult<()> {
-        match self {
-            DatabasePool::Postgres(pool) => {
-                // Insert or update episode history
-                sqlx::query(r#"
-                    INSERT INTO "UserEpisodeHistory" (userid, episodeid, listenduration, listendate)
-                    VALUES ($1, $2, $3, $4)
-                    ON CONFLICT (userid, episodeid)
-                    DO UPDATE SET listenduration = GREATEST("UserEpisodeHistory".listenduration, $3), listendate = $4
-                "#)
-                    .bind(user_id)
-                    .bind(episode_id)
-                    .bind(position)
-                    .bind(timestamp)
-                    .execute(pool)
-                    .await?;
-            }
-            DatabasePool::MySQL(pool) => {
-                // Insert or update episode history
-                sqlx::query("
-                    INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDuration, ListenDate)
-                    VALUES (?, ?, ?, ?)
-                    ON DUPLICATE KEY UPDATE 
-                        ListenDuration = GREATEST(ListenDuration, VALUES(ListenDuration)), 
-                        ListenDate = VALUES(ListenDate)
-                ")
-                    .bind(user_id)
-                    .bind(episode_id)
-                    .bind(position)
-                    .bind(timestamp)
-                    .execute(pool)
-                    .await?;
+                }
             }
         }
-        Ok(())
     }
-
-    // Get gPodder status - matches Python get_user_gpodder_status function exactly
+    
+// Get gPodder status - matches Python get_user_gpodder_status function exactly
     pub async fn gpodder_get_status(&self, user_id: i32) -> AppResult<GpodderStatus> {
         match self {
             DatabasePool::Postgres(pool) => {
@@ -16894,159 +16304,12 @@ impl DatabasePool {
     }
 
 
-    // Sync with Nextcloud - matches Python refresh_nextcloud_subscription function exactly
+    // Sync with Nextcloud - incremental, through the gpodder sync engine
     async fn sync_with_nextcloud(&self, user_id: i32, settings: &UserSyncSettings, _force: bool) -> AppResult<bool> {
-        let client = reqwest::Client::new();
-        let decrypted_password = self.decrypt_password(&settings.token).await?;
-        
-        // Step 1: Get last sync timestamp for incremental sync
-        let since_timestamp = self.get_last_sync_timestamp(user_id).await?;
-        
-        // Step 2: Get subscriptions from Nextcloud gPodder Sync app with timestamp
-        let subscriptions_url = if let Some(since) = since_timestamp {
-            format!("{}/index.php/apps/gpoddersync/subscription_changes/{}?since={}", 
-                settings.url.trim_end_matches('/'), since.timestamp(), since.timestamp())
-        } else {
-            format!("{}/index.php/apps/gpoddersync/subscriptions", settings.url.trim_end_matches('/'))
-        };
-        
-        tracing::info!("Getting Nextcloud subscriptions from: {}", subscriptions_url);
-        
-        let response = client
-            .get(&subscriptions_url)
-            .basic_auth(&settings.username, Some(&decrypted_password))
-            .send()
-            .await
-            .map_err(|e| AppError::internal(&format!("Failed to sync with Nextcloud: {}", e)))?;
-        
-        let mut subscriptions_processed = false;
-        
-        if response.status().is_success() {
-            let subscriptions_response: serde_json::Value = response.json().await
-                .map_err(|e| AppError::internal(&format!("Failed to parse Nextcloud subscriptions: {}", e)))?;
-            
-            // Handle both subscription change format and direct subscription list
-            let (subscriptions, removals) = if since_timestamp.is_some() {
-                // Incremental sync - expect {add: [], remove: [], timestamp: N} format
-                let adds = subscriptions_response["add"].as_array()
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .filter_map(|f| f.as_str().map(|s| s.to_string()))
-                    .collect::<Vec<String>>();
-                
-                let removes = subscriptions_response["remove"].as_array()
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .filter_map(|f| f.as_str().map(|s| s.to_string()))
-                    .collect::<Vec<String>>();
-                
-                (adds, removes)
-            } else {
-                // Full sync - expect direct array of subscription URLs, no removals in this format
-                let adds = subscriptions_response.as_array()
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .filter_map(|f| f.as_str().map(|s| s.to_string()))
-                    .collect::<Vec<String>>();
-                
-                (adds, Vec::new())
-            };
-            
-            tracing::info!("Downloaded {} subscriptions and {} removals from Nextcloud", subscriptions.len(), removals.len());
-            
-            // Process subscriptions and add missing podcasts
-            if !subscriptions.is_empty() {
-                self.process_gpodder_subscriptions(user_id, &subscriptions).await?;
-                subscriptions_processed = true;
-            }
-            
-            // Process subscription removals
-            if !removals.is_empty() {
-                self.process_gpodder_subscription_removals(user_id, &removals).await?;
-                subscriptions_processed = true; // Mark as processed since we did work
-            }
-        }
-        
-        // Step 3: Get episode actions from Nextcloud with timestamp
-        let episode_actions_url = if let Some(since) = since_timestamp {
-            format!("{}/index.php/apps/gpoddersync/episode_action?since={}", 
-                settings.url.trim_end_matches('/'), since.timestamp())
-        } else {
-            format!("{}/index.php/apps/gpoddersync/episode_action", settings.url.trim_end_matches('/'))
-        };
-        
-        tracing::info!("Getting Nextcloud episode actions from: {}", episode_actions_url);
-        
-        let episode_response = client
-            .get(&episode_actions_url)
-            .basic_auth(&settings.username, Some(&decrypted_password))
-            .send()
-            .await;
-        
-        let mut episode_actions_processed = false;
-        
-        if let Ok(resp) = episode_response {
-            if resp.status().is_success() {
-                let episode_actions: serde_json::Value = resp.json().await
-                    .map_err(|e| AppError::internal(&format!("Failed to parse Nextcloud episode actions: {}", e)))?;
-                
-                if let Some(actions_array) = episode_actions["actions"].as_array() {
-                    tracing::info!("Downloaded {} episode actions from Nextcloud", actions_array.len());
-                    
-                    if !actions_array.is_empty() {
-                        if let Err(e) = self.apply_remote_episode_actions(user_id, actions_array).await {
-                            tracing::warn!("Nextcloud episode actions processing failed but continuing: {}", e);
-                        } else {
-                            episode_actions_processed = true;
-                        }
-                    }
-                } else if let Some(actions_array) = episode_actions.as_array() {
-                    // Some Nextcloud implementations return direct array
-                    tracing::info!("Downloaded {} episode actions from Nextcloud (direct)", actions_array.len());
-                    
-                    if !actions_array.is_empty() {
-                        if let Err(e) = self.apply_remote_episode_actions(user_id, actions_array).await {
-                            tracing::warn!("Nextcloud episode actions processing failed but continuing: {}", e);
-                        } else {
-                            episode_actions_processed = true;
-                        }
-                    }
-                }
-            } else {
-                tracing::warn!("Nextcloud episode actions returned error: {}", resp.status());
-            }
-        } else {
-            tracing::warn!("Failed to get episode actions from Nextcloud");
-        }
-        
-        // Step 4: Upload local subscriptions to Nextcloud (if needed)
-        if since_timestamp.is_none() || subscriptions_processed {
-            let local_subscriptions = self.get_user_podcast_feeds(user_id).await?;
-            if let Err(e) = self.upload_subscriptions_to_nextcloud(&settings.url, &settings.username, &decrypted_password, &local_subscriptions).await {
-                tracing::warn!("Failed to upload subscriptions to Nextcloud: {}", e);
-            } else {
-                tracing::info!("Successfully uploaded {} subscriptions to Nextcloud", local_subscriptions.len());
-            }
-        } else {
-            tracing::info!("Skipping subscription upload to Nextcloud - no changes detected");
-        }
-        
-        // Step 5: Upload local episode actions to Nextcloud
-        let local_episode_actions = self.get_user_episode_actions(user_id).await?;
-        if !local_episode_actions.is_empty() {
-            if let Err(e) = self.upload_episode_actions_to_nextcloud(&settings.url, &settings.username, &decrypted_password, &local_episode_actions).await {
-                tracing::warn!("Failed to upload episode actions to Nextcloud: {}", e);
-            } else {
-                tracing::info!("Successfully uploaded {} episode actions to Nextcloud", local_episode_actions.len());
-            }
-        } else {
-            tracing::info!("No local episode actions to upload to Nextcloud");
-        }
-        
-        // Step 6: Update last sync timestamp for next incremental sync
-        self.update_last_sync_timestamp(user_id).await?;
-        
-        Ok(subscriptions_processed || episode_actions_processed)
+        let password = self.decrypt_password(&settings.token).await?;
+        let remote = crate::services::gpodder_sync::Remote::nextcloud(&settings.url, &settings.username, &password);
+        let report = crate::services::gpodder_sync::run(self, user_id, &remote, false).await?;
+        Ok(report.changed())
     }
 
     // Decrypt password using Fernet - matches Python encryption
@@ -17768,202 +17031,38 @@ impl DatabasePool {
     //     Ok(details)
     // }
     
-    // Get notification settings - matches Python get_notification_settings function exactly
+    // Get notification settings - the per-platform view over the user's notification channels
     pub async fn get_notification_settings(&self, user_id: i32) -> AppResult<Vec<serde_json::Value>> {
-        info!("Getting notification settings for user {}", user_id);
-        
-        let settings = match self {
-            DatabasePool::Postgres(pool) => {
-                let rows = sqlx::query(r#"
-                    SELECT platform, enabled, ntfytopic, ntfyserverurl, ntfyusername, ntfypassword, ntfyaccesstoken, gotifyurl, gotifytoken, httpurl, httptoken, httpmethod
-                    FROM "UserNotificationSettings" 
-                    WHERE userid = $1
-                "#)
-                    .bind(user_id)
-                    .fetch_all(pool)
-                    .await?;
-                
-                let mut settings = Vec::new();
-                for row in rows {
-                    let setting = serde_json::json!({
-                        "platform": row.try_get::<String, _>("platform")?,
-                        "enabled": row.try_get::<bool, _>("enabled")?,
-                        "ntfy_topic": row.try_get::<Option<String>, _>("ntfytopic")?,
-                        "ntfy_server_url": row.try_get::<Option<String>, _>("ntfyserverurl")?,
-                        "ntfy_username": row.try_get::<Option<String>, _>("ntfyusername")?,
-                        "ntfy_password": row.try_get::<Option<String>, _>("ntfypassword")?,
-                        "ntfy_access_token": row.try_get::<Option<String>, _>("ntfyaccesstoken")?,
-                        "gotify_url": row.try_get::<Option<String>, _>("gotifyurl")?,
-                        "gotify_token": row.try_get::<Option<String>, _>("gotifytoken")?,
-                        "http_url": row.try_get::<Option<String>, _>("httpurl")?,
-                        "http_token": row.try_get::<Option<String>, _>("httptoken")?,
-                        "http_method": row.try_get::<Option<String>, _>("httpmethod")?
-                    });
-                    settings.push(setting);
-                }
-                settings
-            }
-            DatabasePool::MySQL(pool) => {
-                let rows = sqlx::query("
-                    SELECT Platform, Enabled, NtfyTopic, NtfyServerURL, NtfyUsername, NtfyPassword, NtfyAccessToken, GotifyURL, GotifyToken, HttpUrl, HttpToken, HttpMethod
-                    FROM UserNotificationSettings 
-                    WHERE UserID = ?
-                    ORDER BY Platform
-                ")
-                    .bind(user_id)
-                    .fetch_all(pool)
-                    .await?;
-                
-                let mut settings = Vec::new();
-                for row in rows {
-                    let setting = serde_json::json!({
-                        "platform": row.try_get::<String, _>("Platform")?,
-                        "enabled": row.try_get::<bool, _>("Enabled")?,
-                        "ntfy_topic": row.try_get::<Option<String>, _>("NtfyTopic")?,
-                        "ntfy_server_url": row.try_get::<Option<String>, _>("NtfyServerURL")?,
-                        "ntfy_username": row.try_get::<Option<String>, _>("NtfyUsername")?,
-                        "ntfy_password": row.try_get::<Option<String>, _>("NtfyPassword")?,
-                        "ntfy_access_token": row.try_get::<Option<String>, _>("NtfyAccessToken")?,
-                        "gotify_url": row.try_get::<Option<String>, _>("GotifyURL")?,
-                        "gotify_token": row.try_get::<Option<String>, _>("GotifyToken")?,
-                        "http_url": row.try_get::<Option<String>, _>("HttpUrl")?,
-                        "http_token": row.try_get::<Option<String>, _>("HttpToken")?,
-                        "http_method": row.try_get::<Option<String>, _>("HttpMethod")?
-                    });
-                    settings.push(setting);
-                }
-                settings
-            }
-        };
-        
-        debug!("Found {} notification settings for user {}", settings.len(), user_id);
-        Ok(settings)
+        crate::services::notification_channels::legacy_settings(self, user_id).await
     }
     
-    // Update notification settings - matches Python update_notification_settings function exactly
+    // Update notification settings - saved into the user's first channel of the platform
     pub async fn update_notification_settings(&self, user_id: i32, platform: &str, enabled: bool, ntfy_topic: Option<&str>, ntfy_server_url: Option<&str>, ntfy_username: Option<&str>, ntfy_password: Option<&str>, ntfy_access_token: Option<&str>, gotify_url: Option<&str>, gotify_token: Option<&str>, http_url: Option<&str>, http_token: Option<&str>, http_method: Option<&str>) -> AppResult<bool> {
+        use crate::services::notification_channels::ChannelConfig;
+
         info!("Updating notification settings for user {} platform {}", user_id, platform);
-        
-        // Check if settings exist for this user/platform combination and perform update/insert
-        let success = match self {
-            DatabasePool::Postgres(pool) => {
-                let existing = sqlx::query(r#"SELECT COUNT(*) as count FROM "UserNotificationSettings" WHERE userid = $1 AND platform = $2"#)
-                    .bind(user_id)
-                    .bind(platform)
-                    .fetch_one(pool)
-                    .await?;
-                
-                let count: i64 = existing.try_get("count")?;
-                
-                if count > 0 {
-                    // Update existing record
-                    let result = sqlx::query(r#"
-                        UPDATE "UserNotificationSettings" 
-                        SET enabled = $3, ntfytopic = $4, ntfyserverurl = $5, ntfyusername = $6, ntfypassword = $7, ntfyaccesstoken = $8, gotifyurl = $9, gotifytoken = $10, httpurl = $11, httptoken = $12, httpmethod = $13
-                        WHERE userid = $1 AND platform = $2
-                    "#)
-                        .bind(user_id)
-                        .bind(platform)
-                        .bind(enabled)
-                        .bind(ntfy_topic)
-                        .bind(ntfy_server_url)
-                        .bind(ntfy_username)
-                        .bind(ntfy_password)
-                        .bind(ntfy_access_token)
-                        .bind(gotify_url)
-                        .bind(gotify_token)
-                        .bind(http_url)
-                        .bind(http_token)
-                        .bind(http_method)
-                        .execute(pool)
-                        .await?;
-                    result.rows_affected() > 0
-                } else {
-                    // Insert new record
-                    let result = sqlx::query(r#"
-                        INSERT INTO "UserNotificationSettings" 
-                        (userid, platform, enabled, ntfytopic, ntfyserverurl, ntfyusername, ntfypassword, ntfyaccesstoken, gotifyurl, gotifytoken, httpurl, httptoken, httpmethod)
-                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-                    "#)
-                        .bind(user_id)
-                        .bind(platform)
-                        .bind(enabled)
-                        .bind(ntfy_topic)
-                        .bind(ntfy_server_url)
-                        .bind(ntfy_username)
-                        .bind(ntfy_password)
-                        .bind(ntfy_access_token)
-                        .bind(gotify_url)
-                        .bind(gotify_token)
-                        .bind(http_url)
-                        .bind(http_token)
-                        .bind(http_method)
-                        .execute(pool)
-                        .await?;
-                    result.rows_affected() > 0
-                }
-            }
-            DatabasePool::MySQL(pool) => {
-                let existing = sqlx::query("SELECT COUNT(*) as count FROM UserNotificationSettings WHERE UserID = ? AND Platform = ?")
-                    .bind(user_id)
-                    .bind(platform)
-                    .fetch_one(pool)
-                    .await?;
-                
-                let count: i64 = existing.try_get("count")?;
-                
-                if count > 0 {
-                    // Update existing record
-                    let result = sqlx::query("
-                        UPDATE UserNotificationSettings 
-                        SET Enabled = ?, NtfyTopic = ?, NtfyServerURL = ?, NtfyUsername = ?, NtfyPassword = ?, NtfyAccessToken = ?, GotifyURL = ?, GotifyToken = ?, HttpUrl = ?, HttpToken = ?, HttpMethod = ?
-                        WHERE UserID = ? AND Platform = ?
-                    ")
-                        .bind(enabled)
-                        .bind(ntfy_topic)
-                        .bind(ntfy_server_url)
-                        .bind(ntfy_username)
-                        .bind(ntfy_password)
-                        .bind(ntfy_access_token)
-                        .bind(gotify_url)
-                        .bind(gotify_token)
-                        .bind(http_url)
-                        .bind(http_token)
-                        .bind(http_method)
-                        .bind(user_id)
-                        .bind(platform)
-                        .execute(pool)
-                        .await?;
-                    result.rows_affected() > 0
-                } else {
-                    // Insert new record
-                    let result = sqlx::query("
-                        INSERT INTO UserNotificationSettings 
-                        (UserID, Platform, Enabled, NtfyTopic, NtfyServerURL, NtfyUsername, NtfyPassword, NtfyAccessToken, GotifyURL, GotifyToken, HttpUrl, HttpToken, HttpMethod)
-                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                    ")
-                        .bind(user_id)
-                        .bind(platform)
-                        .bind(enabled)
-                        .bind(ntfy_topic)
-                        .bind(ntfy_server_url)
-                        .bind(ntfy_username)
-                        .bind(ntfy_password)
-                        .bind(ntfy_access_token)
-                        .bind(gotify_url)
-                        .bind(gotify_token)
-                        .bind(http_url)
-                        .bind(http_token)
-                        .bind(http_method)
-                        .execute(pool)
-                        .await?;
-                    result.rows_affected() > 0
-                }
-            }
+        let owned = |value: Option<&str>| value.map(str::to_string);
+        let config = match platform {
+            "ntfy" => ChannelConfig::Ntfy {
+                server_url: owned(ntfy_server_url),
+                topic: ntfy_topic.unwrap_or_default().to_string(),
+                username: owned(ntfy_username),
+                password: owned(ntfy_password),
+                access_token: owned(ntfy_access_token),
+            },
+            "gotify" => ChannelConfig::Gotify {
+                url: gotify_url.unwrap_or_default().to_string(),
+                token: gotify_token.unwrap_or_default().to_string(),
+            },
+            "http" => ChannelConfig::Http {
+                url: http_url.unwrap_or_default().to_string(),
+                token: owned(http_token),
+                method: owned(http_method),
+            },
+            other => return Err(AppError::bad_request(format!("Unsupported notification platform: {}", other))),
         };
-        
-        debug!("Successfully updated notification settings for user {} platform {}: {}", user_id, platform, success);
-        Ok(success)
+        crate::services::notification_channels::save_legacy_settings(self, user_id, enabled, &config).await?;
+        Ok(true)
     }
     
     // Add OIDC provider - matches Python add_oidc_provider function exactly
@@ -19808,13 +18907,13 @@ impl DatabasePool {
     }
 
     // Check existing YouTube channel subscription - matches Python check_existing_channel_subscription function exactly
-    pub async fn check_existing_channel_subscription(&self, channel_id: &str, user_id: i32) -> AppResult<Option<i32>> {
-        debug!("Checking existing channel subscription for {} and user {}", channel_id, user_id);
+    pub async fn check_existing_channel_subscription(&self, feed_url: &str, user_id: i32) -> AppResult<Option<i32>> {
+        debug!("Checking existing channel subscription for {} and user {}", feed_url, user_id);
         
         match self {
             DatabasePool::Postgres(pool) => {
                 let row = sqlx::query(r#"SELECT podcastid FROM "Podcasts" WHERE feedurl = $1 AND userid = $2"#)
-                    .bind(format!("https://www.youtube.com/channel/{}", channel_id))
+                    .bind(feed_url)
                     .bind(user_id)
                     .fetch_optional(pool)
                     .await?;
@@ -19830,7 +18929,7 @@ impl DatabasePool {
             }
             DatabasePool::MySQL(pool) => {
                 let row = sqlx::query("SELECT PodcastID FROM Podcasts WHERE FeedURL = ? AND UserID = ?")
-                    .bind(format!("https://www.youtube.com/channel/{}", channel_id))
+                    .bind(feed_url)
                     .bind(user_id)
                     .fetch_optional(pool)
                     .await?;
@@ -19856,7 +18955,13 @@ impl DatabasePool {
         let name = channel_info.get("name").unwrap_or(&empty_string);
         let description = channel_info.get("description").unwrap_or(&empty_string);
         let thumbnail_url = channel_info.get("thumbnail_url").unwrap_or(&empty_string);
-        let feed_url = format!("https://www.youtube.com/channel/{}", channel_id);
+        // Playlists carry their own feed URL; channels default to the channel page.
+        let feed_url = channel_info
+            .get("feed_url")
+            .cloned()
+            .unwrap_or_else(|| format!("https://www.youtube.com/channel/{}", channel_id));
+        // Other yt-dlp sites share the video tables and pipeline but refresh differently.
+        let source_kind = channel_info.get("source_kind").map(String::as_str).unwrap_or("youtube");
         
         // Insert new YouTube channel as podcast
         let podcast_id = match self {
@@ -19864,8 +18969,9 @@ impl DatabasePool {
                 let row = sqlx::query(r#"
                     INSERT INTO "Podcasts" (
                         userid, podcastname, artworkurl, description, episodecount,
-                        websiteurl, feedurl, author, categories, explicit, podcastindexid, feedcutoffdays, isyoutubechannel
-                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                        websiteurl, feedurl, author, categories, explicit, podcastindexid, feedcutoffdays, isyoutubechannel,
+                        sourcekind
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
                     RETURNING podcastid
                 "#)
                     .bind(user_id)
@@ -19881,6 +18987,7 @@ impl DatabasePool {
                     .bind(0) // No podcast index ID for YouTube
                     .bind(feed_cutoff)
                     .bind(true) // Is YouTube channel
+                    .bind(source_kind)
                     .fetch_one(pool)
                     .await?;
                 
@@ -19890,8 +18997,9 @@ impl DatabasePool {
                 let result = sqlx::query(r#"
                     INSERT INTO Podcasts (
                         UserID, PodcastName, ArtworkURL, Description, EpisodeCount,
-                        WebsiteURL, FeedURL, Author, Categories, Explicit, PodcastIndexID, FeedCutoffDays, IsYouTubeChannel
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        WebsiteURL, FeedURL, Author, Categories, Explicit, PodcastIndexID, FeedCutoffDays, IsYouTubeChannel,
+                        SourceKind
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#)
                     .bind(user_id)
                     .bind(name)
@@ -19906,6 +19014,7 @@ impl DatabasePool {
                     .bind(0) // No podcast index ID for YouTube
                     .bind(feed_cutoff)
                     .bind(true) // Is YouTube channel
+                    .bind(source_kind)
                     .execute(pool)
                     .await?;
                 
@@ -20689,7 +19798,7 @@ impl DatabasePool {
     }
 
     // Remove a downloaded file from disk if present (mirrors delete_episode handler behavior).
-    async fn remove_download_file(path: Option<&str>) {
+    pub(crate) async fn remove_download_file(path: Option<&str>) {
         if let Some(path) = path {
             if tokio::fs::metadata(path).await.is_ok() {
                 if let Err(e) = tokio::fs::remove_file(path).await {
@@ -22498,7 +21607,7 @@ impl DatabasePool {
                 // First try to get podcast for specific user
                 let mut podcast_row = sqlx::query(r#"
                     SELECT podcastid, podcastname, feedurl, description, author, artworkurl, 
-                           explicit, episodecount, categories, websiteurl, podcastindexid, isyoutubechannel,
+                           explicit, episodecount, categories, websiteurl, podcastindexid, isyoutubechannel, sourcekind,
                            userid, autodownload, startskip, endskip, username, password, notificationsenabled, feedcutoffdays,
                            playbackspeed, playbackspeedcustomized
                     FROM "Podcasts" 
@@ -22513,7 +21622,7 @@ impl DatabasePool {
                 if podcast_row.is_none() {
                     podcast_row = sqlx::query(r#"
                         SELECT podcastid, podcastname, feedurl, description, author, artworkurl, 
-                               explicit, episodecount, categories, websiteurl, podcastindexid, isyoutubechannel,
+                               explicit, episodecount, categories, websiteurl, podcastindexid, isyoutubechannel, sourcekind,
                                userid, autodownload, startskip, endskip, username, password, notificationsenabled, feedcutoffdays,
                                playbackspeed, playbackspeedcustomized
                         FROM "Podcasts" 
@@ -22562,6 +21671,7 @@ impl DatabasePool {
                     "username": row.try_get::<Option<String>, _>("username")?,
                     "password": row.try_get::<Option<String>, _>("password")?,
                     "isyoutubechannel": is_youtube,
+                    "sourcekind": row.try_get::<String, _>("sourcekind").unwrap_or_else(|_| "rss".to_string()),
                     "notificationsenabled": row.try_get::<bool, _>("notificationsenabled").unwrap_or(false),
                     "feedcutoffdays": row.try_get::<i32, _>("feedcutoffdays").unwrap_or(0),
                     "playbackspeedcustomized": row.try_get::<bool, _>("playbackspeedcustomized").unwrap_or(false),
@@ -22572,7 +21682,7 @@ impl DatabasePool {
                 // First try to get podcast for specific user
                 let mut podcast_row = sqlx::query(r#"
                     SELECT PodcastID, PodcastName, FeedURL, Description, Author, ArtworkURL, 
-                           Explicit, EpisodeCount, Categories, WebsiteURL, PodcastIndexID, IsYouTubeChannel,
+                           Explicit, EpisodeCount, Categories, WebsiteURL, PodcastIndexID, IsYouTubeChannel, SourceKind,
                            UserID, AutoDownload, StartSkip, EndSkip, Username, Password, NotificationsEnabled, FeedCutoffDays,
                            PlaybackSpeed, PlaybackSpeedCustomized
                     FROM Podcasts 
@@ -22587,7 +21697,7 @@ impl DatabasePool {
                 if podcast_row.is_none() {
                     podcast_row = sqlx::query(r#"
                         SELECT PodcastID, PodcastName, FeedURL, Description, Author, ArtworkURL, 
-                               Explicit, EpisodeCount, Categories, WebsiteURL, PodcastIndexID, IsYouTubeChannel,
+                               Explicit, EpisodeCount, Categories, WebsiteURL, PodcastIndexID, IsYouTubeChannel, SourceKind,
                                UserID, AutoDownload, StartSkip, EndSkip, Username, Password, NotificationsEnabled, FeedCutoffDays,
                                PlaybackSpeed, PlaybackSpeedCustomized
                         FROM Podcasts 
@@ -22636,6 +21746,7 @@ impl DatabasePool {
                     "username": row.try_get::<Option<String>, _>("Username")?,
                     "password": row.try_get::<Option<String>, _>("Password")?,
                     "isyoutubechannel": is_youtube,
+                    "sourcekind": row.try_get::<String, _>("SourceKind").unwrap_or_else(|_| "rss".to_string()),
                     "notificationsenabled": row.try_get::<i8, _>("NotificationsEnabled").unwrap_or(0) != 0,
                     "feedcutoffdays": row.try_get::<i32, _>("FeedCutoffDays").unwrap_or(0),
                     "playbackspeedcustomized": row.try_get::<i8, _>("PlaybackSpeedCustomized").unwrap_or(0) != 0,
@@ -22876,7 +21987,8 @@ impl DatabasePool {
             DatabasePool::Postgres(pool) => {
                 let result = sqlx::query(r#"
                     SELECT apikey FROM "APIKeys" 
-                    WHERE userid = $1 
+                    WHERE userid = $1 AND scopes IS NULL AND revoked = FALSE
+                      AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP)
                     ORDER BY created DESC 
                     LIMIT 1
                 "#)
@@ -22893,7 +22005,8 @@ impl DatabasePool {
             DatabasePool::MySQL(pool) => {
                 let result = sqlx::query(r#"
                     SELECT APIKey FROM APIKeys 
-                    WHERE UserID = ? 
+                    WHERE UserID = ? AND Scopes IS NULL AND Revoked = FALSE
+                      AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP)
                     ORDER BY Created DESC 
                     LIMIT 1
                 "#)
@@ -23010,7 +22123,7 @@ impl DatabasePool {
         match self {
             DatabasePool::Postgres(pool) => {
                 // Check for existing API key
-                let existing_key = sqlx::query(r#"SELECT apikey FROM "APIKeys" WHERE userid = $1 LIMIT 1"#)
+                let existing_key = sqlx::query(r#"SELECT apikey FROM "APIKeys" WHERE userid = $1 AND scopes IS NULL AND revoked = FALSE AND (expiresat IS NULL OR expiresat > CURRENT_TIMESTAMP) LIMIT 1"#)
                     .bind(user_id)
                     .fetch_optional(pool)
                     .await?;
@@ -23031,7 +22144,7 @@ impl DatabasePool {
                 Ok(api_key)
             }
             DatabasePool::MySQL(pool) => {
-                let existing_key = sqlx::query("SELECT APIKey FROM APIKeys WHERE UserID = ? LIMIT 1")
+                let existing_key = sqlx::query("SELECT APIKey FROM APIKeys WHERE UserID = ? AND Scopes IS NULL AND Revoked = FALSE AND (ExpiresAt IS NULL OR ExpiresAt > CURRENT_TIMESTAMP) LIMIT 1")
                     .bind(user_id)
                     .fetch_optional(pool)
                     .await?;
@@ -23728,20 +22841,15 @@ impl DatabasePool {
 
         debug!("Found YouTube ID: {}", youtube_id);
 
-        let file_path = format!("/opt/pinepods/downloads/youtube/{}.mp3", youtube_id);
-        let file_path_double = format!("/opt/pinepods/downloads/youtube/{}.mp3.mp3", youtube_id);
-
-        debug!("Checking paths: {} and {}", file_path, file_path_double);
-
-        if tokio::fs::metadata(&file_path).await.is_ok() {
-            debug!("Found file at {}", file_path);
-            Ok(Some(file_path))
-        } else if tokio::fs::metadata(&file_path_double).await.is_ok() {
-            debug!("Found file at {}", file_path_double);
-            Ok(Some(file_path_double))
-        } else {
-            debug!("No file found for YouTube ID: {}", youtube_id);
-            Ok(None)
+        match crate::services::youtube_source::audio_path(&youtube_id) {
+            Some(path) => {
+                debug!("Found file at {}", path.display());
+                Ok(Some(path.to_string_lossy().to_string()))
+            }
+            None => {
+                debug!("No file found for YouTube ID: {}", youtube_id);
+                Ok(None)
+            }
         }
     }
 
@@ -24278,7 +23386,8 @@ impl DatabasePool {
         let user_rss_key = self.get_or_create_user_rss_key(user_id).await?;
 
         // Get episodes (use the user's RSS key for stream URLs, not the requesting key)
-        let episodes = self.get_rss_episodes(user_id, limit, source_type, &effective_podcast_ids, podcast_filter, domain, &user_rss_key).await?;
+        let mut episodes = self.get_rss_episodes(user_id, limit, source_type, &effective_podcast_ids, podcast_filter, domain, &user_rss_key).await?;
+        self.describe_served_files(user_id, domain, &user_rss_key, &mut episodes).await;
 
         Self::write_rss_document(
             &format!("Pinepods - {}", podcast_name),
@@ -24311,6 +23420,7 @@ impl DatabasePool {
         let mut rss_elem = BytesStart::new("rss");
         rss_elem.push_attribute(("version", "2.0"));
         rss_elem.push_attribute(("xmlns:itunes", "http://www.itunes.com/dtds/podcast-1.0.dtd"));
+        rss_elem.push_attribute(("xmlns:podcast", "https://podcastindex.org/namespace/1.0"));
         writer.write_event(Event::Start(rss_elem))?;
 
         // Channel
@@ -24424,13 +23534,25 @@ impl DatabasePool {
                 writer.write_event(Event::End(BytesEnd::new("itunes:duration")))?;
             }
 
-            // Enclosure (length should be file size in bytes, using duration as placeholder)
+            // Enclosure (length is the file size in bytes when known, else duration as placeholder)
+            let length = match episode.length {
+                Some(bytes) => bytes.to_string(),
+                None => episode.duration.unwrap_or(0).to_string(),
+            };
+            let mime_type = episode.mime_type.unwrap_or_else(|| Self::enclosure_type(episode.url.split(['?', '#']).next().unwrap_or("")));
             let mut enclosure = BytesStart::new("enclosure");
             enclosure.push_attribute(("url", episode.url.as_str()));
-            enclosure.push_attribute(("length", episode.duration.unwrap_or(0).to_string().as_str()));
-            enclosure.push_attribute(("type", "audio/mpeg"));
+            enclosure.push_attribute(("length", length.as_str()));
+            enclosure.push_attribute(("type", mime_type));
             writer.write_event(Event::Empty(enclosure))?;
 
+            if let Some(ref chapters_url) = episode.chapters_url {
+                let mut chapters = BytesStart::new("podcast:chapters");
+                chapters.push_attribute(("url", chapters_url.as_str()));
+                chapters.push_attribute(("type", "application/json+chapters"));
+                writer.write_event(Event::Empty(chapters))?;
+            }
+
             writer.write_event(Event::End(BytesEnd::new("item")))?;
         }
 
@@ -24446,6 +23568,24 @@ impl DatabasePool {
         Ok(rss_content)
     }
 
+    /// Enclosure MIME type for a file path or URL path, by extension. MP3 when unknown, which is
+    /// what most publisher enclosures are.
+    fn enclosure_type(path: &str) -> &'static str {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("m4a") | Some("m4b") | Some("aac") => "audio/mp4",
+            Some("opus") | Some("ogg") | Some("oga") => "audio/ogg",
+            Some("webm") => "audio/webm",
+            Some("flac") => "audio/flac",
+            Some("wav") => "audio/wav",
+            Some("mp4") => "video/mp4",
+            _ => "audio/mpeg",
+        }
+    }
+
     /// Format a stored ISO timestamp (`%Y-%m-%dT%H:%M:%S`) as an RFC-822 pubDate.
     fn rss_pub_date(iso: &str) -> String {
         use chrono::{DateTime, Utc, NaiveDateTime};
@@ -24486,11 +23626,17 @@ impl DatabasePool {
         };
 
         RssEpisode {
+            episode_id: episodeid,
+            server_download: downloaded && !is_youtube,
+            is_youtube: downloaded && is_youtube,
             title,
             description,
             url,
             pub_date: Self::rss_pub_date(episodepubdate),
             duration: Some(episodeduration),
+            length: None,
+            mime_type: None,
+            chapters_url: None,
             author,
             artwork_url: episodeartwork.filter(|s| !s.is_empty()),
         }
@@ -24520,6 +23666,55 @@ impl DatabasePool {
         )
     }
 
+    /// Describe the files `stream_episode` serves for downloaded items: their MIME type and size,
+    /// and for podcasts with `ServeCutAudio` the cut copy, with a URL that changes with the removed
+    /// ranges (so apps re-download after a review), the cut duration and chapters remapped onto
+    /// the cut timeline. The cut size is estimated from the cut duration until the copy has been
+    /// rendered.
+    async fn describe_served_files(&self, user_id: i32, domain: &str, user_rss_key: &str, episodes: &mut [RssEpisode]) {
+        for episode in episodes.iter_mut().filter(|e| e.server_download || e.is_youtube) {
+            let location = if episode.is_youtube {
+                self.get_youtube_video_location(episode.episode_id, user_id).await
+            } else {
+                self.get_download_location(episode.episode_id, user_id).await
+            };
+            let Ok(Some(location)) = location else {
+                continue;
+            };
+            episode.mime_type = Some(Self::enclosure_type(&location));
+            if let Ok(meta) = tokio::fs::metadata(&location).await {
+                episode.length = Some(meta.len());
+            }
+            if episode.is_youtube {
+                continue;
+            }
+
+            let plan = match crate::services::audio_cut::cut_plan(self, user_id, episode.episode_id, std::path::Path::new(&location)).await {
+                Ok(Some(plan)) => plan,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Could not plan cut copy of episode {} for the feed: {}", episode.episode_id, e);
+                    continue;
+                }
+            };
+            let original = episode.duration.unwrap_or(0).max(0) as f64;
+            let cut = crate::services::audio_cut::map_time(original, &plan.ranges);
+            episode.length = match tokio::fs::metadata(&plan.dest).await {
+                Ok(meta) => Some(meta.len()),
+                Err(_) if original > 0.0 => Some((plan.source_len as f64 * cut / original) as u64),
+                Err(_) => Some(plan.source_len),
+            };
+            if original > 0.0 {
+                episode.duration = Some(cut.round() as i32);
+            }
+            episode.url = format!("{}&cut={}", episode.url, &plan.key[..12]);
+            episode.chapters_url = Some(format!(
+                "{}/api/data/stream/{}/chapters?api_key={}&user_id={}",
+                domain, episode.episode_id, user_rss_key, user_id
+            ));
+        }
+    }
+
     /// Generate an RSS feed for one of a user's episode collections
     /// (saved, queue, playlist, collection, downloads, history).
     pub async fn generate_collection_rss(
@@ -24570,7 +23765,7 @@ impl DatabasePool {
         let default_image = format!("{}/static/assets/favicon.png", domain);
 
         // Resolve the feed title/description and the episode list per source.
-        let (feed_name, feed_description, episodes): (String, String, Vec<RssEpisode>) = match source {
+        let (feed_name, feed_description, mut episodes): (String, String, Vec<RssEpisode>) = match source {
             FeedSource::Subscriptions => {
                 // Handled by generate_podcast_rss; guard just in case.
                 return Err(AppError::internal("Subscriptions feed must use generate_podcast_rss"));
@@ -24653,6 +23848,7 @@ impl DatabasePool {
                 (name, desc, items)
             }
         };
+        self.describe_served_files(user_id, domain, &user_rss_key, &mut episodes).await;
 
         Self::write_rss_document(
             &format!("Pinepods - {}", feed_name),
@@ -24772,7 +23968,9 @@ impl DatabasePool {
                         pp.podcastname,
                         pp.author,
                         pp.artworkurl,
-                        pp.description as podcastdescription
+                        pp.description as podcastdescription,
+                        de.episodeid IS NOT NULL as serverdownload,
+                        FALSE as isyoutube
                     FROM "Episodes" e
                     JOIN "Podcasts" pp ON e.podcastid = pp.podcastid
                     LEFT JOIN "DownloadedEpisodes" de ON e.episodeid = de.episodeid
@@ -24802,7 +24000,9 @@ impl DatabasePool {
                             pv.podcastname,
                             pv.author,
                             pv.artworkurl,
-                            pv.description as podcastdescription
+                            pv.description as podcastdescription,
+                            FALSE as serverdownload,
+                            TRUE as isyoutube
                         FROM "YouTubeVideos" y
                         JOIN "Podcasts" pv on y.podcastid = pv.podcastid
                         WHERE pv.userid = $3
@@ -24832,6 +24032,9 @@ impl DatabasePool {
                 
                 let mut episodes = Vec::new();
                 for row in rows {
+                    let episode_id: i32 = row.try_get("episodeid").unwrap_or(0);
+                    let server_download = row.try_get::<bool, _>("serverdownload").unwrap_or(false);
+                    let is_youtube = row.try_get::<bool, _>("isyoutube").unwrap_or(false);
                     let title: String = row.try_get("episodetitle").unwrap_or_else(|_| "Untitled Episode".to_string());
                     let description: String = row.try_get("episodedescription").unwrap_or_else(|_| String::new());
                     let url: String = row.try_get("episodeurl").unwrap_or_else(|_| String::new());
@@ -24850,11 +24053,17 @@ impl DatabasePool {
                     };
 
                     episodes.push(RssEpisode {
+                        episode_id,
+                        server_download,
+                        is_youtube,
                         title,
                         description,
                         url,
                         pub_date,
                         duration,
+                        length: None,
+                        mime_type: None,
+                        chapters_url: None,
                         author,
                         artwork_url,
                     });
@@ -24879,7 +24088,9 @@ impl DatabasePool {
                         pp.PodcastName COLLATE utf8mb4_unicode_ci as PodcastName,
                         pp.Author COLLATE utf8mb4_unicode_ci as Author,
                         pp.ArtworkURL COLLATE utf8mb4_unicode_ci as ArtworkURL,
-                        pp.Description COLLATE utf8mb4_unicode_ci as PodcastDescription
+                        pp.Description COLLATE utf8mb4_unicode_ci as PodcastDescription,
+                        CAST(de.EpisodeID IS NOT NULL AS SIGNED) as ServerDownload,
+                        CAST(0 AS SIGNED) as IsYouTube
                     FROM Episodes e
                     JOIN Podcasts pp ON e.PodcastID = pp.PodcastID
                     LEFT JOIN DownloadedEpisodes de ON e.EpisodeID = de.EpisodeID
@@ -24908,7 +24119,9 @@ impl DatabasePool {
                             pv.PodcastName COLLATE utf8mb4_unicode_ci as PodcastName,
                             pv.Author COLLATE utf8mb4_unicode_ci as Author,
                             pv.ArtworkURL COLLATE utf8mb4_unicode_ci as ArtworkURL,
-                            pv.Description COLLATE utf8mb4_unicode_ci as PodcastDescription
+                            pv.Description COLLATE utf8mb4_unicode_ci as PodcastDescription,
+                            CAST(0 AS SIGNED) as ServerDownload,
+                            CAST(1 AS SIGNED) as IsYouTube
                         FROM YouTubeVideos y
                         JOIN Podcasts pv on y.PodcastID = pv.PodcastID
                         WHERE pv.UserID = ?
@@ -24950,6 +24163,9 @@ impl DatabasePool {
                 
                 let mut episodes = Vec::new();
                 for row in rows {
+                    let episode_id: i32 = row.try_get("EpisodeID").unwrap_or(0);
+                    let server_download = row.try_get::<i64, _>("ServerDownload").unwrap_or(0) != 0;
+                    let is_youtube = row.try_get::<i64, _>("IsYouTube").unwrap_or(0) != 0;
                     let title: String = row.try_get("EpisodeTitle").unwrap_or_else(|_| "Untitled Episode".to_string());
                     let description: String = row.try_get("EpisodeDescription").unwrap_or_else(|_| String::new());
                     let url: String = row.try_get("EpisodeURL").unwrap_or_else(|_| String::new());
@@ -24968,11 +24184,17 @@ impl DatabasePool {
                     };
 
                     episodes.push(RssEpisode {
+                        episode_id,
+                        server_download,
+                        is_youtube,
                         title,
                         description,
                         url,
                         pub_date,
                         duration,
+                        length: None,
+                        mime_type: None,
+                        chapters_url: None,
                         author,
                         artwork_url,
                     });
@@ -25243,19 +24465,12 @@ impl DatabasePool {
             }
         };
 
-        // Delete the MP3 files for each video
+        // Delete the audio files for each video
         for video_id in &video_ids {
-            let file_paths = vec![
-                format!("/opt/pinepods/downloads/youtube/{}.mp3", video_id),
-                format!("/opt/pinepods/downloads/youtube/{}.mp3.mp3", video_id), // In case of double extension
-            ];
-
-            for file_path in file_paths {
-                if tokio::fs::metadata(&file_path).await.is_ok() {
-                    match tokio::fs::remove_file(&file_path).await {
-                        Ok(_) => info!("Deleted file: {}", file_path),
-                        Err(e) => warn!("Failed to delete file {}: {}", file_path, e),
-                    }
+            for file_path in crate::services::youtube_source::audio_files(video_id) {
+                match tokio::fs::remove_file(&file_path).await {
+                    Ok(_) => info!("Deleted file: {}", file_path.display()),
+                    Err(e) => warn!("Failed to delete file {}: {}", file_path.display(), e),
                 }
             }
         }
@@ -25385,6 +24600,7 @@ impl DatabasePool {
                     details.insert("websiteurl".to_string(), serde_json::Value::String(row.try_get::<String, _>("websiteurl").unwrap_or_default()));
                     details.insert("podcastindexid".to_string(), serde_json::Value::Number(serde_json::Number::from(row.try_get::<i32, _>("podcastindexid").unwrap_or(0))));
                     details.insert("isyoutubechannel".to_string(), serde_json::Value::Bool(row.try_get::<bool, _>("isyoutubechannel").unwrap_or(false)));
+                    details.insert("sourcekind".to_string(), serde_json::Value::String(row.try_get::<String, _>("sourcekind").unwrap_or_else(|_| "rss".to_string())));
 
                     Ok(Some(serde_json::Value::Object(details)))
                 } else {
@@ -25416,6 +24632,7 @@ impl DatabasePool {
                     details.insert("websiteurl".to_string(), serde_json::Value::String(row.try_get::<String, _>("WebsiteURL").unwrap_or_default()));
                     details.insert("podcastindexid".to_string(), serde_json::Value::Number(serde_json::Number::from(row.try_get::<i32, _>("PodcastIndexID").unwrap_or(0))));
                     details.insert("isyoutubechannel".to_string(), serde_json::Value::Bool(row.try_get::<bool, _>("IsYouTubeChannel").unwrap_or(false)));
+                    details.insert("sourcekind".to_string(), serde_json::Value::String(row.try_get::<String, _>("SourceKind").unwrap_or_else(|_| "rss".to_string())));
 
                     Ok(Some(serde_json::Value::Object(details)))
                 } else {
@@ -26367,7 +25584,7 @@ impl DatabasePool {
         };
         
         // First, check if device already exists
-        let device_list_url = format!("{}/api/2/devices/{}.json", gpodder_url.trim_end_matches('/'), username);
+        let device_list_url = format!("{}/api/2/devices/{}.json", crate::services::gpodder_sync::api_base(gpodder_url), username);
         
         let response = if auth_method == "internal" {
             client.get(&device_list_url)
@@ -26403,7 +25620,7 @@ impl DatabasePool {
         }
         
         // Device doesn't exist, create it
-        let device_url = format!("{}/api/2/devices/{}/{}.json", gpodder_url.trim_end_matches('/'), username, device_name);
+        let device_url = format!("{}/api/2/devices/{}/{}.json", crate::services::gpodder_sync::api_base(gpodder_url), username, device_name);
         let device_data = serde_json::json!({
             "caption": format!("PinePods Internal Device {}", device_name.split('-').last().unwrap_or("unknown")),
             "type": "server"
@@ -26513,7 +25730,7 @@ impl DatabasePool {
     // Decrypt GPodder token using existing encryption system - matches Python token decryption
     // Get comprehensive GPodder server statistics by calling actual GPodder API endpoints
     pub async fn get_gpodder_server_statistics(&self, user_id: i32) -> AppResult<crate::handlers::sync::GpodderStatistics> {
-        use crate::handlers::sync::{GpodderStatistics, ServerDevice, ServerSubscription, ServerEpisodeAction, EndpointTest};
+        use crate::handlers::sync::{GpodderStatistics, ServerDevice, ServerSubscription, ServerEpisodeAction, EndpointTest, SYNC_RUNS_SHOWN};
         use std::time::Instant;
         
         // Get user's sync settings using the same method as sync operations
@@ -26534,6 +25751,8 @@ impl DatabasePool {
                     connection_status: "Not configured".to_string(),
                     last_sync_timestamp: None,
                     api_endpoints_tested: vec![],
+                    recent_sync_runs: crate::services::gpodder_sync::recent_runs(self, user_id, SYNC_RUNS_SHOWN).await?,
+                    device_activity: vec![],
                 });
             }
         };
@@ -26568,6 +25787,8 @@ impl DatabasePool {
                     connection_status: "Unsupported sync type".to_string(),
                     last_sync_timestamp: None,
                     api_endpoints_tested: vec![],
+                    recent_sync_runs: crate::services::gpodder_sync::recent_runs(self, user_id, SYNC_RUNS_SHOWN).await?,
+                    device_activity: vec![],
                 });
             }
         };
@@ -26724,7 +25945,7 @@ impl DatabasePool {
             let session = self.create_gpodder_session_with_password(&gpodder_url, &username, &password).await?;
 
             // Test 1: Get devices from GPodder API
-            let devices_url = format!("{}/api/2/devices/{}.json", gpodder_url.trim_end_matches('/'), username);
+            let devices_url = format!("{}/api/2/devices/{}.json", crate::services::gpodder_sync::api_base(&gpodder_url), username);
             let start = Instant::now();
         
         let devices_response = if session.authenticated {
@@ -26785,7 +26006,7 @@ impl DatabasePool {
         // Test 2: Get subscriptions from GPodder API - use the user's actual default device
         let device_name = self.get_or_create_default_device(user_id).await?;
         let subscriptions_url = format!("{}/api/2/subscriptions/{}/{}.json?since=0", 
-            gpodder_url.trim_end_matches('/'), username, device_name);
+            crate::services::gpodder_sync::api_base(&gpodder_url), username, device_name);
         let start = Instant::now();
 
         let subscriptions_response = if session.authenticated {
@@ -26850,7 +26071,7 @@ impl DatabasePool {
         // device, because Pinepods uploads play actions with a NULL device, so a device filter
         // would hide all of them and only surface old device-tagged rows.
         let episodes_url = format!("{}/api/2/episodes/{}.json?since=0&order=desc&limit=10",
-            gpodder_url.trim_end_matches('/'), username);
+            crate::services::gpodder_sync::api_base(&gpodder_url), username);
         let start = Instant::now();
 
         let episodes_response = if session.authenticated {
@@ -26939,6 +26160,8 @@ impl DatabasePool {
             connection_status: connection_status.to_string(),
             last_sync_timestamp: last_sync.map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
             api_endpoints_tested,
+            recent_sync_runs: crate::services::gpodder_sync::recent_runs(self, user_id, SYNC_RUNS_SHOWN).await?,
+            device_activity: crate::services::gpodder_sync::device_activity(self, user_id).await?,
         })
     }
 
@@ -27027,7 +26250,7 @@ impl DatabasePool {
     }
 
     // Get last sync timestamp for incremental sync - PROPER GPodder spec implementation
-    async fn get_last_sync_timestamp(&self, user_id: i32) -> AppResult<Option<chrono::DateTime<chrono::Utc>>> {
+    pub async fn get_last_sync_timestamp(&self, user_id: i32) -> AppResult<Option<chrono::DateTime<chrono::Utc>>> {
         match self {
             DatabasePool::Postgres(pool) => {                
                 let row = sqlx::query(r#"SELECT lastsynctime FROM "Users" WHERE userid = $1"#)
@@ -27057,7 +26280,7 @@ impl DatabasePool {
     }
     
     // Update last sync timestamp - PROPER GPodder spec implementation for incremental sync
-    async fn update_last_sync_timestamp(&self, user_id: i32) -> AppResult<()> {
+    pub async fn update_last_sync_timestamp(&self, user_id: i32) -> AppResult<()> {
         let now = chrono::Utc::now();
         
         match self {
@@ -27080,28 +26303,8 @@ impl DatabasePool {
         Ok(())
     }
 
-    // Clear last sync timestamp - for initial full sync to start fresh
-    async fn clear_last_sync_timestamp(&self, user_id: i32) -> AppResult<()> {
-        match self {
-            DatabasePool::Postgres(pool) => {                
-                sqlx::query(r#"UPDATE "Users" SET lastsynctime = NULL WHERE userid = $1"#)
-                    .bind(user_id)
-                    .execute(pool)
-                    .await?;
-            }
-            DatabasePool::MySQL(pool) => {                
-                sqlx::query("UPDATE Users SET LastSyncTime = NULL WHERE UserID = ?")
-                    .bind(user_id)
-                    .execute(pool)
-                    .await?;
-            }
-        }
-        
-        Ok(())
-    }
-
-    // Get user episode actions since timestamp - CRITICAL for incremental sync performance
-    async fn get_user_episode_actions_since(&self, user_id: i32, since: chrono::DateTime<chrono::Utc>) -> AppResult<Vec<serde_json::Value>> {
+// Get user episode actions since timestamp - CRITICAL for incremental sync performance
+    pub async fn get_user_episode_actions_since(&self, user_id: i32, since: chrono::DateTime<chrono::Utc>) -> AppResult<Vec<serde_json::Value>> {
         match self {
             DatabasePool::Postgres(pool) => {
                 let rows = sqlx::query(r#"
@@ -27219,22 +26422,26 @@ impl DatabasePool {
             }
         }
     }
-
-    // Get all user episode actions - fallback for first sync
-    async fn get_user_episode_actions(&self, user_id: i32) -> AppResult<Vec<serde_json::Value>> {
-        // Use since timestamp of epoch (1970) to get all actions
-        let epoch = chrono::DateTime::from_timestamp(0, 0).unwrap_or_else(chrono::Utc::now);
-        self.get_user_episode_actions_since(user_id, epoch).await
-    }
 }
 
 #[derive(Debug)]
 struct RssEpisode {
+    episode_id: i32,
+    /// Streamed from the user's server download (not YouTube), so it may be served cut.
+    server_download: bool,
+    /// A YouTube video, streamed from its downloaded audio.
+    is_youtube: bool,
     title: String,
     description: String,
     url: String,
     pub_date: String,
     duration: Option<i32>,
+    /// Enclosure size in bytes, when known.
+    length: Option<u64>,
+    /// Enclosure MIME type of the served file, when known; otherwise guessed from the URL.
+    mime_type: Option<&'static str>,
+    /// Podcasting 2.0 chapters JSON for the item.
+    chapters_url: Option<String>,
     author: Option<String>,
     artwork_url: Option<String>,
 }
@@ -27262,7 +26469,7 @@ impl DatabasePool {
         }
     }
 
-    // Complete implementation of sync_with_nextcloud_for_user - matches Python nextcloud sync functionality  
+    // Nextcloud sync for a user with Nextcloud configured - incremental, through the gpodder sync engine
     pub async fn sync_with_nextcloud_for_user(&self, user_id: i32) -> AppResult<bool> {
         tracing::info!("Starting Nextcloud sync for user {}", user_id);
         
@@ -27305,197 +26512,31 @@ impl DatabasePool {
                     
                 if let Some(row) = row {
                     let url: Option<String> = row.try_get("GpodderUrl")?;
-                    let login: Option<String> = row.try_get("GpodderLoginName")?;
-                    let token: Option<String> = row.try_get("GpodderToken")?;
-                    
-                    (
-                        url.ok_or_else(|| AppError::internal("Nextcloud URL not configured"))?,
-                        login.ok_or_else(|| AppError::internal("Nextcloud username not configured"))?,
-                        token.ok_or_else(|| AppError::internal("Nextcloud token not configured"))?
-                    )
-                } else {
-                    return Err(AppError::not_found("User not found"));
-                }
-            }
-        };
-        
-        // Decrypt token using existing decrypt_password method
-        let password = self.decrypt_password(&encrypted_token).await?;
-        
-        // Get last sync timestamp for incremental sync (keep both the DateTime, for selecting
-        // local episode actions to upload, and the unix form, for the Nextcloud `since` query).
-        let last_sync_dt = self.get_last_sync_timestamp(user_id).await?;
-        let since_timestamp = last_sync_dt.map(|t| t.timestamp()).unwrap_or(0);
-        
-        // Build Nextcloud API endpoint URLs
-        let base_url = if gpodder_url.ends_with('/') {
-            gpodder_url.trim_end_matches('/').to_string()
-        } else {
-            gpodder_url.clone()
-        };
-        
-        let subscriptions_url = format!("{}/index.php/apps/gpoddersync/subscriptions", base_url);
-        let episode_action_url = format!("{}/index.php/apps/gpoddersync/episode_action", base_url);
-        
-        let client = reqwest::Client::new();
-        let mut has_changes = false;
-        
-        // Sync subscriptions from Nextcloud
-        let subscriptions_response = client
-            .get(&subscriptions_url)
-            .basic_auth(&username, Some(&password))
-            .query(&[("since", since_timestamp.to_string())])
-            .send()
-            .await
-            .map_err(|e| AppError::internal(&format!("Failed to fetch Nextcloud subscriptions: {}", e)))?;
-            
-        // Track changes the server sent us so we don't echo them back on upload.
-        let mut server_added: std::collections::HashSet<String> = std::collections::HashSet::new();
-        let mut server_removed: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-        if subscriptions_response.status().is_success() {
-            let subscription_data: serde_json::Value = subscriptions_response.json().await
-                .map_err(|e| AppError::internal(&format!("Failed to parse subscription response: {}", e)))?;
-
-            // Process subscription changes
-            if let Some(add_list) = subscription_data.get("add").and_then(|v| v.as_array()) {
-                for url in add_list {
-                    if let Some(podcast_url) = url.as_str() {
-                        tracing::info!("Adding Nextcloud subscription: {}", podcast_url);
-                        server_added.insert(podcast_url.to_string());
-                        if let Err(e) = self.add_podcast_from_url(user_id, podcast_url, None).await {
-                            tracing::error!("Failed to add podcast {}: {}", podcast_url, e);
-                        } else {
-                            has_changes = true;
-                        }
-                    }
-                }
-            }
-
-            if let Some(remove_list) = subscription_data.get("remove").and_then(|v| v.as_array()) {
-                for url in remove_list {
-                    if let Some(podcast_url) = url.as_str() {
-                        tracing::info!("Removing Nextcloud subscription: {}", podcast_url);
-                        server_removed.insert(podcast_url.to_string());
-                        if let Err(e) = self.remove_podcast_by_url(user_id, podcast_url).await {
-                            tracing::error!("Failed to remove podcast {}: {}", podcast_url, e);
-                        } else {
-                            has_changes = true;
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Sync episode actions from Nextcloud
-        let episode_actions_response = client
-            .get(&episode_action_url)
-            .basic_auth(&username, Some(&password))
-            .query(&[("since", since_timestamp.to_string())])
-            .send()
-            .await
-            .map_err(|e| AppError::internal(&format!("Failed to fetch Nextcloud episode actions: {}", e)))?;
-            
-        if episode_actions_response.status().is_success() {
-            let episode_actions_data: serde_json::Value = episode_actions_response.json().await
-                .map_err(|e| AppError::internal(&format!("Failed to parse episode actions response: {}", e)))?;
-            
-            if let Some(actions) = episode_actions_data.get("actions").and_then(|v| v.as_array()) {
-                for action in actions {
-                    if let Err(e) = self.process_nextcloud_episode_action(user_id, action).await {
-                        tracing::error!("Failed to process episode action: {}", e);
-                    } else {
-                        has_changes = true;
-                    }
+                    let login: Option<String> = row.try_get("GpodderLoginName")?;
+                    let token: Option<String> = row.try_get("GpodderToken")?;
+                    
+                    (
+                        url.ok_or_else(|| AppError::internal("Nextcloud URL not configured"))?,
+                        login.ok_or_else(|| AppError::internal("Nextcloud username not configured"))?,
+                        token.ok_or_else(|| AppError::internal("Nextcloud token not configured"))?
+                    )
+                } else {
+                    return Err(AppError::not_found("User not found"));
                 }
             }
-        }
-        
-        // Push genuine LOCAL subscription changes up to Nextcloud. Without an upload the sync is
-        // download-only, so podcasts added locally never reach the server. We diff the current
-        // local feed set against the snapshot from the last sync (rather than re-sending the full
-        // list) so we only push real local add/removes, propagate local unsubscribes, and avoid
-        // echoing back what the server just sent us.
-        let local_subscriptions = self.get_user_podcast_feeds(user_id).await?;
-        let (mut to_add, mut to_remove) = self
-            .compute_subscription_delta(user_id, &base_url, &local_subscriptions)
-            .await?;
-        to_add.retain(|f| !server_added.contains(f));
-        to_remove.retain(|f| !server_removed.contains(f));
-        if let Err(e) = self
-            .upload_subscription_changes_to_nextcloud(&base_url, &username, &password, &to_add, &to_remove)
-            .await
-        {
-            tracing::warn!("Failed to upload subscription delta to Nextcloud: {}", e);
-        }
-        self.save_subscription_snapshot(user_id, &base_url, &local_subscriptions).await?;
-
-        // Upload only episode actions created since the last sync (full history on the first sync),
-        // matching the gpodder path - avoids re-uploading the entire play history every sync.
-        let local_episode_actions = match last_sync_dt {
-            Some(since) => self.get_user_episode_actions_since(user_id, since).await?,
-            None => self.get_user_episode_actions(user_id).await?,
         };
-        if !local_episode_actions.is_empty() {
-            if let Err(e) = self.upload_episode_actions_to_nextcloud(&base_url, &username, &password, &local_episode_actions).await {
-                tracing::warn!("Failed to upload local episode actions to Nextcloud: {}", e);
-            }
-        }
-
-        // Update last sync timestamp
-        if let Err(e) = self.update_last_sync_timestamp(user_id).await {
-            tracing::error!("Failed to update sync timestamp for user {}: {}", user_id, e);
-        }
+        
+        // Decrypt token using existing decrypt_password method
+        let password = self.decrypt_password(&encrypted_token).await?;
 
-        tracing::info!("Nextcloud sync completed for user {} - changes: {}", user_id, has_changes);
-        Ok(has_changes)
-    }
+        let remote = crate::services::gpodder_sync::Remote::nextcloud(&gpodder_url, &username, &password);
+        let report = crate::services::gpodder_sync::run(self, user_id, &remote, false).await?;
 
-    // Process individual episode action from Nextcloud
-    async fn process_nextcloud_episode_action(&self, user_id: i32, action: &serde_json::Value) -> AppResult<()> {
-        let episode_url = action.get("episode") 
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AppError::internal("Missing episode URL in episode action"))?;
-            
-        let action_type = action.get("action")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AppError::internal("Missing action type in episode action"))?;
-        
-        // Find the episode by URL
-        let episode_id = match self.get_episode_id_by_url(episode_url).await {
-            Ok(Some(id)) => id,
-            Ok(None) => {
-                tracing::warn!("Episode not found for URL: {}", episode_url);
-                return Ok(());
-            }
-            Err(_) => {
-                tracing::warn!("Error finding episode for URL: {}", episode_url);
-                return Ok(());
-            }
-        };
-        
-        match action_type {
-            "play" => {
-                if let Some(position) = action.get("position").and_then(|v| v.as_i64()) {
-                    self.save_episode_history(user_id, episode_id, position as i32, 0).await?;
-                }
-            }
-            "download" => {
-                self.mark_episode_completed(episode_id, user_id, false).await?;
-            }  
-            "delete" => {
-                // Remove episode from user's history
-                self.remove_episode_from_history(user_id, episode_id).await?;
-            }
-            _ => {
-                tracing::debug!("Unknown action type: {}", action_type);
-            }
-        }
-        
-        Ok(())
+        tracing::info!("Nextcloud sync completed for user {} - changes: {}", user_id, report.changed());
+        Ok(report.changed())
     }
 
-    // Add podcast from URL - used by Nextcloud sync
+// Add podcast from URL - used by Nextcloud sync
     pub async fn add_podcast_from_url(&self, user_id: i32, feed_url: &str, _feed_cutoff: Option<i32>) -> AppResult<()> {
         // Check if podcast already exists for this user
         if self.podcast_exists_for_user(user_id, feed_url).await? {
@@ -27819,6 +26860,17 @@ impl DatabasePool {
                     .bind(user_id)
                     .execute(&mut *tx)
                     .await?;
+
+                // Tombstones and device actions belong to the server being disconnected
+                sqlx::query(r#"DELETE FROM "GpodderSubscriptionTombstones" WHERE userid = $1"#)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                sqlx::query(r#"DELETE FROM "GpodderDeviceEpisodeActions" WHERE userid = $1"#)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
                 
                 // Then clear GPodder settings from user record
                 sqlx::query(r#"
@@ -27846,6 +26898,17 @@ impl DatabasePool {
                     .bind(user_id)
                     .execute(&mut *tx)
                     .await?;
+
+                // Tombstones and device actions belong to the server being disconnected
+                sqlx::query("DELETE FROM GpodderSubscriptionTombstones WHERE UserID = ?")
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                sqlx::query("DELETE FROM GpodderDeviceEpisodeActions WHERE UserID = ?")
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
                 
                 // Then clear GPodder settings from user record
                 sqlx::query(r#"
@@ -30417,6 +29480,22 @@ impl DatabasePool {
     }
 }
 
+// FilePath, Title, DiscNum, TrackNum, Duration, StartOffset, Chapters
+type LocalBookPartRow = (String, String, Option<i32>, Option<i32>, i32, i32, Option<String>);
+
+fn local_book_part_from_row(row: LocalBookPartRow) -> crate::services::local_books::BookPart {
+    let (file_path, title, disc_num, track_num, duration, start_offset, chapters) = row;
+    crate::services::local_books::BookPart {
+        file_path,
+        title,
+        disc_num: disc_num.and_then(|d| u32::try_from(d).ok()),
+        track_num: track_num.and_then(|t| u32::try_from(t).ok()),
+        duration,
+        start_offset,
+        chapters: chapters.and_then(|c| serde_json::from_str(&c).ok()).unwrap_or_default(),
+    }
+}
+
 impl DatabasePool {
     // =========================================================
     // Local Podcast Functions
@@ -30518,6 +29597,7 @@ impl DatabasePool {
         user_id: i32,
         candidates: &[crate::handlers::local_podcast::LocalEpisodeCandidate],
     ) -> AppResult<()> {
+        let mut chaptered = Vec::new();
         for candidate in candidates {
             let episode_url = format!("local://{}", candidate.file_path);
             let artwork = candidate.artwork_url.as_deref().unwrap_or("");
@@ -30621,12 +29701,48 @@ impl DatabasePool {
                     }
                 }
             }
+
+            if !candidate.chapters.is_empty() {
+                chaptered.push((episode_id, crate::services::media_tags::chapters_json(&candidate.chapters)));
+            }
+        }
+
+        // Embedded chapters are served like fetched Podcasting 2.0 chapters: an item row whose
+        // cached chapters JSON is already filled in. The channel row is written for every local
+        // podcast so the episode endpoints use stored tags rather than trying to fetch local://.
+        let no_tags = crate::services::podcast_namespace::ItemTags::default();
+        let items: Vec<(i32, &crate::services::podcast_namespace::ItemTags)> =
+            chaptered.iter().map(|(episode_id, _)| (*episode_id, &no_tags)).collect();
+        self.store_podcasting_tags(podcast_id, &crate::services::podcast_namespace::ChannelTags::default(), &items)
+            .await?;
+        for (episode_id, chapters) in &chaptered {
+            self.cache_episode_chapters(*episode_id, &chapters.to_string()).await?;
         }
 
         self.update_episode_count(podcast_id).await?;
         Ok(())
     }
 
+    // Local podcasts (any user) backed by `dir`, for the local-media folder watcher.
+    pub async fn get_local_podcasts_for_directory(&self, dir: &str) -> AppResult<Vec<(i32, i32)>> {
+        let feed_url = format!("local://{}", dir);
+        let rows = match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query_as(r#"SELECT podcastid, userid FROM "Podcasts" WHERE feedurl = $1"#)
+                    .bind(&feed_url)
+                    .fetch_all(pool)
+                    .await?
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query_as("SELECT PodcastID, UserID FROM Podcasts WHERE FeedURL = ?")
+                    .bind(&feed_url)
+                    .fetch_all(pool)
+                    .await?
+            }
+        };
+        Ok(rows)
+    }
+
     pub async fn get_feed_url_for_podcast(
         &self,
         podcast_id: i32,
@@ -30718,6 +29834,320 @@ impl DatabasePool {
         }
     }
 
+    // Create the single episode that stands for an audiobook-mode local podcast, then record its
+    // parts. Returns the episode ID.
+    pub async fn create_local_book(
+        &self,
+        podcast_id: i32,
+        user_id: i32,
+        episode_url: &str,
+        title: &str,
+        details: &crate::services::local_books::BookDetails,
+        book: &crate::services::local_books::LocalBook,
+    ) -> AppResult<i32> {
+        let artwork = details.artwork_url.as_deref().unwrap_or("");
+        let episode_id = match self {
+            DatabasePool::Postgres(pool) => {
+                let row = sqlx::query(
+                    r#"INSERT INTO "Episodes"
+                       (podcastid, episodetitle, episodedescription, episodeurl, episodeartwork, episodepubdate, episodeduration)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7)
+                       RETURNING episodeid"#,
+                )
+                .bind(podcast_id)
+                .bind(title)
+                .bind(&details.description)
+                .bind(episode_url)
+                .bind(artwork)
+                .bind(details.pub_date)
+                .bind(book.duration)
+                .fetch_one(pool)
+                .await?;
+                let episode_id: i32 = row.try_get("episodeid")?;
+                sqlx::query(r#"INSERT INTO "LocalBooks" (podcastid, episodeid) VALUES ($1, $2)"#)
+                    .bind(podcast_id)
+                    .bind(episode_id)
+                    .execute(pool)
+                    .await?;
+                episode_id
+            }
+            DatabasePool::MySQL(pool) => {
+                let result = sqlx::query(
+                    "INSERT INTO Episodes
+                     (PodcastID, EpisodeTitle, EpisodeDescription, EpisodeURL, EpisodeArtwork, EpisodePubDate, EpisodeDuration)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(podcast_id)
+                .bind(title)
+                .bind(&details.description)
+                .bind(episode_url)
+                .bind(artwork)
+                .bind(details.pub_date)
+                .bind(book.duration)
+                .execute(pool)
+                .await?;
+                let episode_id = result.last_insert_id() as i32;
+                sqlx::query("INSERT INTO LocalBooks (PodcastID, EpisodeID) VALUES (?, ?)")
+                    .bind(podcast_id)
+                    .bind(episode_id)
+                    .execute(pool)
+                    .await?;
+                episode_id
+            }
+        };
+
+        self.replace_local_book_parts(podcast_id, user_id, episode_id, book).await?;
+        self.update_episode_count(podcast_id).await?;
+        Ok(episode_id)
+    }
+
+    // Swap in a re-assembled part list (files added or gone) for an existing book. The episode,
+    // and with it the listener's position and queue entry, is kept.
+    pub async fn update_local_book(
+        &self,
+        podcast_id: i32,
+        user_id: i32,
+        book: &crate::services::local_books::LocalBook,
+    ) -> AppResult<()> {
+        let episode_id: i32 = match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query_scalar(r#"SELECT episodeid FROM "LocalBooks" WHERE podcastid = $1"#)
+                    .bind(podcast_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query_scalar("SELECT EpisodeID FROM LocalBooks WHERE PodcastID = ?")
+                    .bind(podcast_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+        }
+        .ok_or_else(|| AppError::not_found("Local book not found"))?;
+
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"UPDATE "Episodes" SET episodeduration = $1 WHERE episodeid = $2"#)
+                    .bind(book.duration)
+                    .bind(episode_id)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("UPDATE Episodes SET EpisodeDuration = ? WHERE EpisodeID = ?")
+                    .bind(book.duration)
+                    .bind(episode_id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        self.replace_local_book_parts(podcast_id, user_id, episode_id, book).await
+    }
+
+    async fn replace_local_book_parts(
+        &self,
+        podcast_id: i32,
+        user_id: i32,
+        episode_id: i32,
+        book: &crate::services::local_books::LocalBook,
+    ) -> AppResult<()> {
+        // DownloadedEpisodes points at the first part so the episode reads as downloaded (and the
+        // RSS feed links it to /stream); stream_episode swaps in the joined audio.
+        let first_part = book.parts.first().map(|p| p.file_path.as_str()).unwrap_or_default();
+        let total_size: i64 = book
+            .parts
+            .iter()
+            .filter_map(|p| std::fs::metadata(&p.file_path).ok())
+            .map(|m| m.len() as i64)
+            .sum();
+
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"DELETE FROM "LocalBookParts" WHERE podcastid = $1"#)
+                    .bind(podcast_id)
+                    .execute(pool)
+                    .await?;
+                for (index, part) in book.parts.iter().enumerate() {
+                    sqlx::query(
+                        r#"INSERT INTO "LocalBookParts"
+                           (podcastid, partindex, filepath, title, discnum, tracknum, duration, startoffset, chapters)
+                           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+                    )
+                    .bind(podcast_id)
+                    .bind(index as i32)
+                    .bind(&part.file_path)
+                    .bind(&part.title)
+                    .bind(part.disc_num.map(|d| d as i32))
+                    .bind(part.track_num.map(|t| t as i32))
+                    .bind(part.duration)
+                    .bind(part.start_offset)
+                    .bind(serde_json::to_string(&part.chapters)?)
+                    .execute(pool)
+                    .await?;
+                }
+                sqlx::query(r#"UPDATE "LocalBooks" SET updatedat = CURRENT_TIMESTAMP WHERE podcastid = $1"#)
+                    .bind(podcast_id)
+                    .execute(pool)
+                    .await?;
+
+                let updated = sqlx::query(
+                    r#"UPDATE "DownloadedEpisodes" SET downloadedsize = $1, downloadedlocation = $2
+                       WHERE userid = $3 AND episodeid = $4"#,
+                )
+                .bind(total_size)
+                .bind(first_part)
+                .bind(user_id)
+                .bind(episode_id)
+                .execute(pool)
+                .await?;
+                if updated.rows_affected() == 0 {
+                    sqlx::query(r#"INSERT INTO "DownloadedEpisodes" (userid, episodeid, downloadedsize, downloadedlocation) VALUES ($1, $2, $3, $4)"#)
+                        .bind(user_id)
+                        .bind(episode_id)
+                        .bind(total_size)
+                        .bind(first_part)
+                        .execute(pool)
+                        .await?;
+                }
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("DELETE FROM LocalBookParts WHERE PodcastID = ?")
+                    .bind(podcast_id)
+                    .execute(pool)
+                    .await?;
+                for (index, part) in book.parts.iter().enumerate() {
+                    sqlx::query(
+                        "INSERT INTO LocalBookParts
+                         (PodcastID, PartIndex, FilePath, Title, DiscNum, TrackNum, Duration, StartOffset, Chapters)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(podcast_id)
+                    .bind(index as i32)
+                    .bind(&part.file_path)
+                    .bind(&part.title)
+                    .bind(part.disc_num.map(|d| d as i32))
+                    .bind(part.track_num.map(|t| t as i32))
+                    .bind(part.duration)
+                    .bind(part.start_offset)
+                    .bind(serde_json::to_string(&part.chapters)?)
+                    .execute(pool)
+                    .await?;
+                }
+                sqlx::query("UPDATE LocalBooks SET UpdatedAt = CURRENT_TIMESTAMP WHERE PodcastID = ?")
+                    .bind(podcast_id)
+                    .execute(pool)
+                    .await?;
+
+                let existing = sqlx::query("SELECT 1 FROM DownloadedEpisodes WHERE UserID = ? AND EpisodeID = ?")
+                    .bind(user_id)
+                    .bind(episode_id)
+                    .fetch_optional(pool)
+                    .await?;
+                if existing.is_some() {
+                    sqlx::query("UPDATE DownloadedEpisodes SET DownloadedSize = ?, DownloadedLocation = ? WHERE UserID = ? AND EpisodeID = ?")
+                        .bind(total_size)
+                        .bind(first_part)
+                        .bind(user_id)
+                        .bind(episode_id)
+                        .execute(pool)
+                        .await?;
+                } else {
+                    sqlx::query("INSERT INTO DownloadedEpisodes (UserID, EpisodeID, DownloadedSize, DownloadedLocation) VALUES (?, ?, ?, ?)")
+                        .bind(user_id)
+                        .bind(episode_id)
+                        .bind(total_size)
+                        .bind(first_part)
+                        .execute(pool)
+                        .await?;
+                }
+            }
+        }
+
+        // Merged chapters are served like a local episode's embedded chapters.
+        let no_tags = crate::services::podcast_namespace::ItemTags::default();
+        self.store_podcasting_tags(
+            podcast_id,
+            &crate::services::podcast_namespace::ChannelTags::default(),
+            &[(episode_id, &no_tags)],
+        )
+        .await?;
+        let chapters = crate::services::media_tags::chapters_json(&book.chapters);
+        self.cache_episode_chapters(episode_id, &chapters.to_string()).await
+    }
+
+    // The stored parts of an audiobook-mode local podcast in playback order, or None when the
+    // podcast is a regular (episodes) local podcast.
+    pub async fn get_local_book_parts(
+        &self,
+        podcast_id: i32,
+    ) -> AppResult<Option<Vec<crate::services::local_books::BookPart>>> {
+        let rows: Vec<LocalBookPartRow> = match self {
+            DatabasePool::Postgres(pool) => {
+                let is_book = sqlx::query(r#"SELECT 1 FROM "LocalBooks" WHERE podcastid = $1"#)
+                    .bind(podcast_id)
+                    .fetch_optional(pool)
+                    .await?;
+                if is_book.is_none() {
+                    return Ok(None);
+                }
+                sqlx::query_as(
+                    r#"SELECT filepath, title, discnum, tracknum, duration, startoffset, chapters
+                       FROM "LocalBookParts" WHERE podcastid = $1 ORDER BY partindex"#,
+                )
+                .bind(podcast_id)
+                .fetch_all(pool)
+                .await?
+            }
+            DatabasePool::MySQL(pool) => {
+                let is_book = sqlx::query("SELECT 1 FROM LocalBooks WHERE PodcastID = ?")
+                    .bind(podcast_id)
+                    .fetch_optional(pool)
+                    .await?;
+                if is_book.is_none() {
+                    return Ok(None);
+                }
+                sqlx::query_as(
+                    "SELECT FilePath, Title, DiscNum, TrackNum, Duration, StartOffset, Chapters
+                     FROM LocalBookParts WHERE PodcastID = ? ORDER BY PartIndex",
+                )
+                .bind(podcast_id)
+                .fetch_all(pool)
+                .await?
+            }
+        };
+        Ok(Some(rows.into_iter().map(local_book_part_from_row).collect()))
+    }
+
+    // The parts behind a book's episode, in playback order. Empty for any other episode.
+    pub async fn get_local_book_parts_for_episode(
+        &self,
+        episode_id: i32,
+    ) -> AppResult<Vec<crate::services::local_books::BookPart>> {
+        let rows: Vec<LocalBookPartRow> = match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query_as(
+                    r#"SELECT p.filepath, p.title, p.discnum, p.tracknum, p.duration, p.startoffset, p.chapters
+                       FROM "LocalBookParts" p JOIN "LocalBooks" b ON b.podcastid = p.podcastid
+                       WHERE b.episodeid = $1 ORDER BY p.partindex"#,
+                )
+                .bind(episode_id)
+                .fetch_all(pool)
+                .await?
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query_as(
+                    "SELECT p.FilePath, p.Title, p.DiscNum, p.TrackNum, p.Duration, p.StartOffset, p.Chapters
+                     FROM LocalBookParts p JOIN LocalBooks b ON b.PodcastID = p.PodcastID
+                     WHERE b.EpisodeID = ? ORDER BY p.PartIndex",
+                )
+                .bind(episode_id)
+                .fetch_all(pool)
+                .await?
+            }
+        };
+        Ok(rows.into_iter().map(local_book_part_from_row).collect())
+    }
+
     pub async fn update_podcast_artwork(
         &self,
         podcast_id: i32,
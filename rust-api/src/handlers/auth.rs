@@ -12,22 +12,20 @@ use crate::{
     handlers::{extract_api_key, check_user_or_admin_access},
     AppState,
 };
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, error};
 
 
-// Global storage for password-verified sessions pending MFA
-// Key: session_token, Value: (user_id, timestamp)
-lazy_static::lazy_static! {
-    static ref PENDING_MFA_SESSIONS: Arc<Mutex<HashMap<String, (i32, u64)>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+// Password-verified sessions pending MFA live in Redis (key: mfa_session:{token}, value: user_id)
+// so the MFA step can land on any replica. They expire after 5 minutes.
+const MFA_SESSION_TTL_SECONDS: u64 = 300;
+
+fn mfa_session_key(session_token: &str) -> String {
+    format!("mfa_session:{}", session_token)
 }
 
 /// Enforce a Redis-backed rate limit; returns 429 when the bucket is exceeded.
 /// Fails open if the cache is unavailable (availability over strictness for self-hosting).
-async fn enforce_rate_limit(
+pub(crate) async fn enforce_rate_limit(
     state: &AppState,
     bucket: &str,
     limit: u32,
@@ -191,7 +189,7 @@ pub struct ConfigResponse {
 }
 
 // Extract basic auth credentials from Authorization header
-fn extract_basic_auth(headers: &HeaderMap) -> AppResult<(String, String)> {
+pub(crate) fn extract_basic_auth(headers: &HeaderMap) -> AppResult<(String, String)> {
     let auth_header = headers
         .get("Authorization")
         .ok_or_else(|| AppError::unauthorized("Missing Authorization header"))?
@@ -260,25 +258,21 @@ pub async fn get_key(
         // Generate cryptographically secure session token
         use rand::RngExt;
         const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-        let mut rng = rand::rng();
-        let session_token: String = (0..32)
-            .map(|_| {
-                let idx = rng.random_range(0..CHARSET.len());
-                CHARSET[idx] as char
-            })
-            .collect();
+        let session_token: String = {
+            let mut rng = rand::rng();
+            (0..32)
+                .map(|_| {
+                    let idx = rng.random_range(0..CHARSET.len());
+                    CHARSET[idx] as char
+                })
+                .collect()
+        };
         
-        // Store session with timestamp (expires in 5 minutes)
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        {
-            let mut sessions = PENDING_MFA_SESSIONS.lock()
-                .map_err(|e| AppError::internal(&format!("Failed to lock MFA sessions: {}", e)))?;
-            sessions.insert(session_token.clone(), (user_id, timestamp));
-        }
+        // Store session (expires in 5 minutes)
+        state
+            .redis_client
+            .set_ex(&mfa_session_key(&session_token), user_id, MFA_SESSION_TTL_SECONDS)
+            .await?;
         
         // User must complete MFA verification first using this session token
         return Ok(Json(LoginResponse {
@@ -484,6 +478,7 @@ pub async fn create_first_admin(
         &request.email,
         &request.password, // Password should already be hashed by frontend
     ).await?;
+    crate::services::webhooks::emit_user_created(&state.db_pool, user_id, &request.username.to_lowercase(), &request.email, "setup");
     
     // Add PinePods news feed to admin users (matches Python startup tasks)
     if let Err(e) = state.db_pool.add_news_feed_if_not_added().await {
@@ -608,23 +603,6 @@ pub struct VerifyMfaLoginResponse {
     pub verified: bool,
 }
 
-// Helper function to clean expired MFA sessions
-fn cleanup_expired_mfa_sessions() -> Result<(), AppError> {
-    let current_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    
-    let mut sessions = PENDING_MFA_SESSIONS.lock()
-        .map_err(|e| AppError::internal(&format!("Failed to lock MFA sessions: {}", e)))?;
-    
-    sessions.retain(|_, (_, timestamp)| {
-        current_time - *timestamp < 300 // Keep sessions newer than 5 minutes
-    });
-    
-    Ok(())
-}
-
 // Verify MFA code during login and return API key - SECURE TWO-FACTOR AUTHENTICATION
 // CRITICAL: This endpoint REQUIRES a valid session token proving password was verified first
 #[utoipa::path(
@@ -641,42 +619,24 @@ pub async fn verify_mfa_and_get_key(
     State(state): State<AppState>,
     Json(request): Json<VerifyMfaLoginRequest>,
 ) -> Result<Json<VerifyMfaLoginResponse>, AppError> {
-    // Clean up expired sessions first
-    cleanup_expired_mfa_sessions()?;
-
     // Throttle MFA code attempts per pending-login session to prevent TOTP brute force.
     enforce_rate_limit(&state, &format!("mfa:{}", request.mfa_session_token), 5, 60).await?;
 
     // CRITICAL SECURITY CHECK: Validate session token from password authentication
-    let user_id = {
-        let mut sessions = PENDING_MFA_SESSIONS.lock()
-            .map_err(|e| AppError::internal(&format!("Failed to lock MFA sessions: {}", e)))?;
-        
-        match sessions.remove(&request.mfa_session_token) {
-            Some((user_id, timestamp)) => {
-                // Check if session is still valid (5 minutes)
-                let current_time = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                
-                if current_time - timestamp > 300 {
-                    return Ok(Json(VerifyMfaLoginResponse {
-                        status: "session_expired".to_string(),
-                        retrieved_key: None,
-                        verified: false,
-                    }));
-                }
-                
-                user_id
-            }
-            None => {
-                return Ok(Json(VerifyMfaLoginResponse {
-                    status: "invalid_session".to_string(),
-                    retrieved_key: None,
-                    verified: false,
-                }));
-            }
+    // Taken atomically so a session token is good for one attempt, on whichever replica
+    let user_id = match state
+        .redis_client
+        .get_del(&mfa_session_key(&request.mfa_session_token))
+        .await?
+        .and_then(|id| id.parse::<i32>().ok())
+    {
+        Some(user_id) => user_id,
+        None => {
+            return Ok(Json(VerifyMfaLoginResponse {
+                status: "invalid_session".to_string(),
+                retrieved_key: None,
+                verified: false,
+            }));
         }
     };
 
@@ -1538,6 +1498,7 @@ pub async fn oidc_callback(
         match state.db_pool.create_oidc_user(&email, &fullname, &final_username).await {
             Ok(user_id) => {
                 let _api_key = state.db_pool.create_api_key(user_id).await?;
+                crate::services::webhooks::emit_user_created(&state.db_pool, user_id, &final_username, &email, "oidc");
                 
                 // Set admin role for new user - EXACT match to Python
                 if let (Some(roles_claim), Some(admin_role)) = (roles_claim.as_ref().filter(|s| !s.is_empty()), admin_role.as_ref().filter(|s| !s.is_empty())) {
@@ -129,6 +129,9 @@ pub async fn bulk_queue_episodes(
     let (processed_count, failed_count) = state.db_pool
         .bulk_queue_episodes(request.episode_ids, request.user_id, is_youtube)
         .await?;
+    if processed_count > 0 {
+        state.playback_hub.queue_changed(request.user_id).await;
+    }
 
     let message = if failed_count > 0 {
         format!("Queued {} episodes, {} failed or already queued", processed_count, failed_count)
@@ -187,7 +190,7 @@ pub async fn bulk_download_episodes(
 
         if !is_downloaded {
             let result = if is_youtube {
-                state.task_spawner.spawn_download_youtube_video(episode_id, request.user_id).await
+                state.task_spawner.spawn_download_youtube_video(episode_id, request.user_id, false).await
             } else {
                 state.task_spawner.spawn_download_podcast_episode(episode_id, request.user_id).await
             };
@@ -335,10 +338,13 @@ pub async fn get_episode_by_url_key(
     params(
         ("episode_id" = i32, Path, description = "Episode ID to download"),
         ("api_key" = Option<String>, Query, description = "API key (alternative to the Api-Key header)"),
+        ("profile" = Option<String>, Query, description = "Transcode profile: opus48, aac64 or original (the default)"),
     ),
     security(("api_key" = [])),
     responses(
         (status = 200, description = "Episode media file stream", content_type = "application/octet-stream"),
+        (status = 202, description = "The requested transcode of the server's copy is being rendered; retry after the Retry-After delay"),
+        (status = 400, description = "Unknown transcode profile"),
         (status = 401, description = "API key is required or invalid"),
     ),
 )]
@@ -360,6 +366,8 @@ pub async fn download_episode_file(
     validate_api_key(&state, &api_key).await?;
     
     let user_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let profile = crate::services::transcode::Profile::parse(params.get("profile").map(String::as_str).unwrap_or(""))
+        .map_err(AppError::bad_request)?;
     
     // Get episode metadata
     let episode_info = match &state.db_pool {
@@ -427,83 +435,116 @@ pub async fn download_episode_file(
 
     let (episode_url, episode_title, podcast_name, pub_date, author, episode_artwork, artwork_url, description, feed_username, feed_password, feed_url, episode_guid, episode_duration) = episode_info;
 
-    // Download the episode file. Send a podcast-client User-Agent first; some hosts
-    // (e.g. Buzzsprout) reject requests without one with 403 Forbidden.
-    let client = reqwest::Client::new();
-    let build_request = |user_agent: &str| {
-        let mut request = client
-            .get(&episode_url)
-            .header("User-Agent", user_agent)
-            .header("Accept", "*/*");
-        if let (Some(ref username), Some(ref password)) = (&feed_username, &feed_password) {
-            if !username.is_empty() {
-                request = request.basic_auth(username, Some(password));
-            }
-        }
-        request
+    // A transcoded download of an episode already on the server reuses the stream cache, whose
+    // source was tagged when it was downloaded.
+    let server_copy = match profile {
+        Some(_) => state.db_pool.get_download_location(episode_id, user_id).await?,
+        None => None,
     };
 
-    let mut response = build_request("PinePods/1.0")
-        .send()
-        .await
-        .map_err(|e| AppError::internal(&format!("Failed to download episode: {}", e)))?;
+    let final_bytes = if let (Some(requested), Some(server_copy)) = (profile, server_copy) {
+        // The rendition is rendered in the background; the client retries rather than
+        // receiving the full-size original it didn't ask for.
+        let source = std::path::Path::new(&server_copy);
+        let Some(path) = crate::services::transcode::cached_or_queue(state.task_spawner.jobs(), user_id, source, requested).await? else {
+            return Ok((StatusCode::ACCEPTED, [(header::RETRY_AFTER, "30")], "Transcoding, retry shortly").into_response());
+        };
+        tokio::fs::read(&path)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to read episode file: {}", e)))?
+    } else {
+        // Download the episode file. Send a podcast-client User-Agent first; some hosts
+        // (e.g. Buzzsprout) reject requests without one with 403 Forbidden.
+        let client = reqwest::Client::new();
+        let build_request = |user_agent: &str| {
+            let mut request = client
+                .get(&episode_url)
+                .header("User-Agent", user_agent)
+                .header("Accept", "*/*");
+            if let (Some(ref username), Some(ref password)) = (&feed_username, &feed_password) {
+                if !username.is_empty() {
+                    request = request.basic_auth(username, Some(password));
+                }
+            }
+            request
+        };
 
-    // If we get a 403, retry with a browser User-Agent as a fallback.
-    if response.status() == reqwest::StatusCode::FORBIDDEN {
-        let browser_response = build_request("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+        let mut response = build_request("PinePods/1.0")
             .send()
             .await
-            .map_err(|e| AppError::internal(&format!("Failed to download episode: {}", e)))?;
-        if browser_response.status().is_success() {
-            response = browser_response;
+            .map_err(|e| AppError::internal(format!("Failed to download episode: {}", e)))?;
+
+        // If we get a 403, retry with a browser User-Agent as a fallback.
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            let browser_response = build_request("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+                .send()
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to download episode: {}", e)))?;
+            if browser_response.status().is_success() {
+                response = browser_response;
+            }
         }
-    }
 
-    if !response.status().is_success() {
-        return Err(AppError::internal(&format!("Server returned error: {}", response.status())));
-    }
+        if !response.status().is_success() {
+            return Err(AppError::internal(format!("Server returned error: {}", response.status())));
+        }
 
-    let audio_bytes = response.bytes()
-        .await
-        .map_err(|e| AppError::internal(&format!("Failed to download audio content: {}", e)))?;
+        let audio_bytes = response.bytes()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to download audio content: {}", e)))?;
     
-    // Create a temporary file for metadata processing
-    let temp_dir = std::env::temp_dir();
-    let temp_filename = format!("episode_{}_{}_{}.mp3", episode_id, user_id, chrono::Utc::now().timestamp());
-    let temp_path = temp_dir.join(&temp_filename);
+        // Create a temporary file for metadata processing
+        let temp_dir = std::env::temp_dir();
+        let temp_filename = format!("episode_{}_{}_{}.mp3", episode_id, user_id, chrono::Utc::now().timestamp());
+        let temp_path = temp_dir.join(&temp_filename);
     
-    // Write audio content to temp file
-    std::fs::write(&temp_path, &audio_bytes)
-        .map_err(|e| AppError::internal(&format!("Failed to write temp file: {}", e)))?;
+        // Write audio content to temp file
+        std::fs::write(&temp_path, &audio_bytes)
+            .map_err(|e| AppError::internal(format!("Failed to write temp file: {}", e)))?;
     
-    // Add metadata using the same shared tagging as server downloads. The client
-    // download streams a single mp3 with no folder, so sidecars do not apply here.
-    let episode_meta = crate::services::download_metadata::EpisodeMetadata {
-        title: episode_title.clone(),
-        artist: author.clone().unwrap_or_else(|| "Unknown".to_string()),
-        album: podcast_name.clone(),
-        date: pub_date,
-        description: description.clone(),
-        feed_url: feed_url.clone(),
-        episode_url: Some(episode_url.clone()),
-        guid: episode_guid.clone(),
-        duration: episode_duration,
-        episode_artwork: episode_artwork.clone(),
-        podcast_artwork: artwork_url.clone(),
-    };
-    if let Err(e) = crate::services::download_metadata::add_podcast_metadata(
-        &temp_path,
-        &episode_meta,
-    ).await {
-        tracing::warn!("Failed to add metadata to downloaded episode: {}", e);
-    }
+        // Add metadata using the same shared tagging as server downloads. The client
+        // download streams a single mp3 with no folder, so sidecars do not apply here.
+        let episode_meta = crate::services::download_metadata::EpisodeMetadata {
+            title: episode_title.clone(),
+            artist: author.clone().unwrap_or_else(|| "Unknown".to_string()),
+            album: podcast_name.clone(),
+            date: pub_date,
+            description: description.clone(),
+            feed_url: feed_url.clone(),
+            episode_url: Some(episode_url.clone()),
+            guid: episode_guid.clone(),
+            duration: episode_duration,
+            episode_artwork: episode_artwork.clone(),
+            podcast_artwork: artwork_url.clone(),
+        };
+        if let Err(e) = crate::services::download_metadata::add_podcast_metadata(
+            &temp_path,
+            &episode_meta,
+        ).await {
+            tracing::warn!("Failed to add metadata to downloaded episode: {}", e);
+        }
     
-    // Read the file with metadata back
-    let final_bytes = std::fs::read(&temp_path)
-        .map_err(|e| AppError::internal(&format!("Failed to read processed file: {}", e)))?;
+        // Read the file with metadata back, re-encoded first if a profile was requested
+        let final_bytes = match profile {
+            Some(profile) => {
+                let transcoded_path = temp_path.with_extension(profile.extension());
+                let transcoded = crate::services::transcode::transcode_file(&temp_path, &transcoded_path, profile).await;
+                let bytes = transcoded.and_then(|_| std::fs::read(&transcoded_path).map_err(|e| e.to_string()));
+                let _ = std::fs::remove_file(&transcoded_path);
+                bytes.map_err(|e| {
+                    let _ = std::fs::remove_file(&temp_path);
+                    AppError::internal(format!("Failed to transcode episode: {}", e))
+                })?
+            }
+            None => std::fs::read(&temp_path)
+                .map_err(|e| AppError::internal(format!("Failed to read processed file: {}", e)))?,
+        };
     
-    // Clean up temp file
-    let _ = std::fs::remove_file(&temp_path);
+        // Clean up temp file
+        let _ = std::fs::remove_file(&temp_path);
+
+        final_bytes
+    };
     
     // Create safe filename for download
     let safe_episode_title = episode_title.chars()
@@ -524,12 +565,16 @@ pub async fn download_episode_file(
         chrono::Utc::now().format("%Y-%m-%d").to_string()
     };
     
-    let filename = format!("{}_{}_-_{}.mp3", pub_date_str, safe_podcast_name, safe_episode_title);
+    let (extension, content_type) = match profile {
+        Some(profile) => (profile.extension(), profile.content_type()),
+        None => ("mp3", "audio/mpeg"),
+    };
+    let filename = format!("{}_{}_-_{}.{}", pub_date_str, safe_podcast_name, safe_episode_title, extension);
     
     // Return the file with appropriate headers
     let response = Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "audio/mpeg")
+        .header(header::CONTENT_TYPE, content_type)
         .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))
         .header(header::CONTENT_LENGTH, final_bytes.len())
         .body(axum::body::Body::from(final_bytes))
@@ -0,0 +1,569 @@
+//! The gpodder.net API v2 (plus the simple v1 subscription API) for AntennaPod and other gpodder
+//! clients, served from the PinePods tables by [`services::gpodder_server`].
+//!
+//! Clients authenticate as the user in the path with a login session cookie, HTTP basic auth
+//! (password, API key or the user's gpodder token) or the `X-GPodder-Token` header PinePods'
+//! own sync uses. The API is only served for users whose sync type is the built-in server.
+//!
+//! Path segments carry a format extension (`alice.json`); usernames and device IDs may contain
+//! dots, so only known extensions are stripped.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, Method, Uri},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::{
+    error::{AppError, AppResult},
+    handlers::{auth::{enforce_rate_limit, extract_basic_auth}, load_api_key_policy},
+    services::{
+        api_scopes::{parse_scopes, scopes_permit},
+        gpodder_server::{self, ActionQuery, GpodderUser, StoredAction, MAX_ACTIONS_PER_RESPONSE, SESSION_DAYS},
+        gpodder_sync::{self, SubscriptionChanges, INTERNAL_GPODDER_URL},
+    },
+    AppState,
+};
+
+const SESSION_COOKIE: &str = "sessionid";
+
+#[derive(Debug, Deserialize)]
+pub struct SinceQuery {
+    pub since: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EpisodeActionsQuery {
+    pub since: Option<i64>,
+    pub podcast: Option<String>,
+    pub device: Option<String>,
+    pub aggregated: Option<bool>,
+    /// `desc` for newest first.
+    pub order: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceUpdate {
+    pub caption: Option<String>,
+    #[serde(rename = "type")]
+    pub device_type: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SubscriptionUpload {
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncDevicesUpdate {
+    #[serde(default)]
+    pub synchronize: Vec<Vec<String>>,
+    #[serde(default, rename = "stop-synchronize")]
+    pub stop_synchronize: Vec<String>,
+}
+
+fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+fn session_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+fn header_text<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+// Whether the request carries credentials for this user.
+async fn authorized(state: &AppState, headers: &HeaderMap, method: &Method, uri: &Uri, user: &GpodderUser) -> AppResult<bool> {
+    let token = user.token.as_deref().filter(|t| !t.is_empty());
+    if let (Some(sent), Some(token)) = (header_text(headers, "X-GPodder-Token"), token) {
+        if sent == token {
+            return Ok(true);
+        }
+    }
+
+    if let Some(session) = session_cookie(headers) {
+        if gpodder_server::session_user(&state.db_pool, &session).await? == Some(user.user_id) {
+            return Ok(true);
+        }
+    }
+
+    let Ok((username, password)) = extract_basic_auth(headers) else {
+        return Ok(false);
+    };
+    if username != user.username.to_lowercase() {
+        return Ok(false);
+    }
+    if token == Some(password.as_str()) {
+        return Ok(true);
+    }
+
+    // An API key as the password, held to the same scope and expiry rules as in the Api-Key header
+    if let Some(policy) = load_api_key_policy(state, &password).await? {
+        if policy.user_id != user.user_id || policy.revoked || policy.expired {
+            return Ok(false);
+        }
+        return Ok(match &policy.scopes {
+            Some(raw) => parse_scopes(raw)
+                .map(|scopes| scopes_permit(&scopes, method, uri.path()))
+                .unwrap_or(false),
+            None => true,
+        });
+    }
+
+    // The account password, unless the account has MFA or only OIDC logins are allowed
+    if state.config.oidc.disable_standard_login || state.db_pool.check_mfa_enabled(user.user_id).await? {
+        return Ok(false);
+    }
+    // Clients send basic auth with every request, so this allows more than the login form does
+    enforce_rate_limit(state, &format!("gpodder-login:{}", username), 60, 60).await?;
+    state.db_pool.verify_password(&user.username, &password).await
+}
+
+// The user named in the path, if the request is authorized as them.
+async fn authenticate(state: &AppState, headers: &HeaderMap, method: &Method, uri: &Uri, username: &str) -> AppResult<GpodderUser> {
+    let user = gpodder_server::find_user(&state.db_pool, username)
+        .await?
+        .ok_or_else(|| AppError::unauthorized("Invalid gPodder credentials"))?;
+    if !authorized(state, headers, method, uri, &user).await? {
+        return Err(AppError::unauthorized("Invalid gPodder credentials"));
+    }
+    if !user.enabled() {
+        return Err(AppError::forbidden("The built-in gPodder server is not enabled for this user"));
+    }
+    Ok(user)
+}
+
+fn text_response(content_type: &'static str, body: String) -> Response {
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+// A subscription list in the requested format. JSON lists are podcast objects in API v2 and
+// plain URLs in v1.
+async fn subscription_list(state: &AppState, user_id: i32, format: Option<&str>, v2: bool) -> AppResult<Response> {
+    let feeds = state.db_pool.get_user_podcast_feeds(user_id).await?;
+    Ok(match format {
+        Some("txt") => text_response("text/plain; charset=utf-8", feeds.iter().map(|f| format!("{}\n", f)).collect()),
+        Some("opml") | Some("xml") => text_response("text/x-opml; charset=utf-8", gpodder_server::opml(&feeds)),
+        _ if v2 => Json(gpodder_server::podcast_details(&state.db_pool, user_id, &feeds).await?).into_response(),
+        _ => Json(feeds).into_response(),
+    })
+}
+
+// Clean uploaded feed URLs: returns the URLs to use and the `[sent, used]` rewrites, where a
+// rejected URL is rewritten to "".
+fn clean_urls(urls: &[String], rewrites: &mut Vec<(String, String)>) -> Vec<String> {
+    let mut cleaned = Vec::new();
+    for url in urls {
+        match gpodder_server::clean_url(url) {
+            Some(clean) => {
+                if &clean != url {
+                    rewrites.push((url.clone(), clean.clone()));
+                }
+                if !cleaned.contains(&clean) {
+                    cleaned.push(clean);
+                }
+            }
+            None => rewrites.push((url.clone(), String::new())),
+        }
+    }
+    cleaned
+}
+
+// Log a device's subscription changes and apply them to the user's podcasts. Removals happen
+// right away; new feeds are fetched in the background since a large upload can take minutes.
+async fn apply_subscription_changes(state: &AppState, user_id: i32, device: &str, changes: SubscriptionChanges, timestamp: i64) -> AppResult<()> {
+    let device_id = gpodder_server::device_id(&state.db_pool, user_id, device).await?;
+    gpodder_server::record_subscription_changes(&state.db_pool, user_id, device_id, &changes, timestamp).await?;
+    for url in &changes.remove {
+        state.db_pool.remove_podcast_by_url(user_id, url).await?;
+    }
+    if !changes.add.is_empty() {
+        let db_pool = state.db_pool.clone();
+        tokio::spawn(async move {
+            for url in changes.add {
+                if let Err(e) = db_pool.add_podcast_from_url(user_id, &url, None).await {
+                    tracing::warn!("gPodder client subscription to {} for user {} failed: {}", url, user_id, e);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+// POST /api/2/auth/{username}/login.json
+pub async fn login(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let client_ip = header_text(&headers, "X-Forwarded-For")
+        .and_then(|ips| ips.split(',').next())
+        .or_else(|| header_text(&headers, "X-Real-IP"))
+        .map(str::trim);
+    let (session, _) = gpodder_server::create_session(
+        &state.db_pool,
+        user.user_id,
+        header_text(&headers, "User-Agent"),
+        client_ip,
+    )
+    .await?;
+    let secure = if header_text(&headers, "X-Forwarded-Proto") == Some("https") { "; Secure" } else { "" };
+    let cookie = format!(
+        "{}={}; Max-Age={}; Path=/; HttpOnly; SameSite=Lax{}",
+        SESSION_COOKIE,
+        session,
+        SESSION_DAYS * 24 * 60 * 60,
+        secure
+    );
+    Ok(([(header::SET_COOKIE, cookie)], Json(serde_json::json!({}))).into_response())
+}
+
+// POST /api/2/auth/{username}/logout.json
+pub async fn logout(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    authenticate(&state, &headers, &method, &uri, &username).await?;
+    if let Some(session) = session_cookie(&headers) {
+        gpodder_server::delete_session(&state.db_pool, &session).await?;
+    }
+    let cookie = format!("{}=; Max-Age=0; Path=/; HttpOnly; SameSite=Lax", SESSION_COOKIE);
+    Ok(([(header::SET_COOKIE, cookie)], Json(serde_json::json!({}))).into_response())
+}
+
+// GET /api/2/devices/{username}.json
+pub async fn list_devices(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Json<Vec<gpodder_server::Device>>, AppError> {
+    let (username, _) = gpodder_server::split_format(&file);
+    let user = authenticate(&state, &headers, &method, &uri, username).await?;
+    Ok(Json(gpodder_server::devices(&state.db_pool, user.user_id).await?))
+}
+
+// POST /api/2/devices/{username}/{device}.json
+pub async fn update_device(
+    State(state): State<AppState>,
+    Path((username, file)): Path<(String, String)>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Json(update): Json<DeviceUpdate>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let (device, _) = gpodder_server::split_format(&file);
+    gpodder_server::update_device(
+        &state.db_pool,
+        user.user_id,
+        device,
+        update.caption.as_deref(),
+        update.device_type.as_deref(),
+    )
+    .await?;
+    Ok(Json(serde_json::json!({})))
+}
+
+// GET /api/2/updates/{username}/{device}.json?since=
+pub async fn device_updates(
+    State(state): State<AppState>,
+    Path((username, file)): Path<(String, String)>,
+    Query(query): Query<SinceQuery>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let (device, _) = gpodder_server::split_format(&file);
+    let timestamp = now();
+    let since = query.since.unwrap_or(0);
+    let device_id = gpodder_server::device_id(&state.db_pool, user.user_id, device).await?;
+    let changes = gpodder_server::subscription_changes(&state.db_pool, user.user_id, device_id, since).await?;
+    Ok(Json(serde_json::json!({
+        "add": gpodder_server::podcast_details(&state.db_pool, user.user_id, &changes.add).await?,
+        "remove": changes.remove,
+        "updates": gpodder_server::new_episodes(&state.db_pool, user.user_id, since).await?,
+        "timestamp": timestamp,
+    })))
+}
+
+// GET /api/2/subscriptions/{username}.json (and /subscriptions/{username}.{format} in v1)
+pub async fn all_subscriptions(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let (username, format) = gpodder_server::split_format(&file);
+    let user = authenticate(&state, &headers, &method, &uri, username).await?;
+    subscription_list(&state, user.user_id, format, uri.path().starts_with("/api/2/")).await
+}
+
+// GET /api/2/subscriptions/{username}/{device}.json?since=
+pub async fn subscription_changes(
+    State(state): State<AppState>,
+    Path((username, file)): Path<(String, String)>,
+    Query(query): Query<SinceQuery>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let (device, _) = gpodder_server::split_format(&file);
+    let timestamp = now();
+    let device_id = gpodder_server::device_id(&state.db_pool, user.user_id, device).await?;
+    // A device syncing for the first time gets the whole list
+    let changes = match query.since.unwrap_or(0) {
+        0 => SubscriptionChanges {
+            add: state.db_pool.get_user_podcast_feeds(user.user_id).await?,
+            remove: Vec::new(),
+        },
+        since => gpodder_server::subscription_changes(&state.db_pool, user.user_id, device_id, since).await?,
+    };
+    Ok(Json(serde_json::json!({
+        "add": changes.add,
+        "remove": changes.remove,
+        "timestamp": timestamp,
+    })))
+}
+
+// POST /api/2/subscriptions/{username}/{device}.json
+pub async fn upload_subscription_changes(
+    State(state): State<AppState>,
+    Path((username, file)): Path<(String, String)>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Json(upload): Json<SubscriptionUpload>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let (device, _) = gpodder_server::split_format(&file);
+    let added: HashSet<&str> = upload.add.iter().map(|u| u.trim()).collect();
+    if let Some(both) = upload.remove.iter().find(|u| added.contains(u.trim())) {
+        return Err(AppError::bad_request(format!("{} is both added and removed", both)));
+    }
+
+    let mut rewrites = Vec::new();
+    let changes = SubscriptionChanges {
+        add: clean_urls(&upload.add, &mut rewrites),
+        remove: clean_urls(&upload.remove, &mut rewrites),
+    };
+    let timestamp = now();
+    apply_subscription_changes(&state, user.user_id, device, changes, timestamp).await?;
+    Ok(Json(serde_json::json!({
+        "timestamp": timestamp,
+        "update_urls": rewrites.into_iter().map(|(sent, used)| [sent, used]).collect::<Vec<_>>(),
+    })))
+}
+
+// GET /subscriptions/{username}/{device}.{format} (API v1)
+pub async fn device_subscriptions(
+    State(state): State<AppState>,
+    Path((username, file)): Path<(String, String)>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let (device, format) = gpodder_server::split_format(&file);
+    gpodder_server::device_id(&state.db_pool, user.user_id, device).await?;
+    subscription_list(&state, user.user_id, format, false).await
+}
+
+// PUT /subscriptions/{username}/{device}.{format} (API v1): replace the subscription list
+pub async fn replace_subscriptions(
+    State(state): State<AppState>,
+    Path((username, file)): Path<(String, String)>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let user = authenticate(&state, &headers, &method, &uri, &username).await?;
+    let (device, format) = gpodder_server::split_format(&file);
+    let sent = gpodder_server::parse_subscription_list(&body, format).map_err(AppError::bad_request)?;
+    let mut rewrites = Vec::new();
+    let wanted = clean_urls(&sent, &mut rewrites);
+    let current = state.db_pool.get_user_podcast_feeds(user.user_id).await?;
+    let changes = SubscriptionChanges {
+        add: wanted.iter().filter(|url| !current.contains(url)).cloned().collect(),
+        remove: current.iter().filter(|url| !wanted.contains(url)).cloned().collect(),
+    };
+    apply_subscription_changes(&state, user.user_id, device, changes, now()).await?;
+    Ok(Json(serde_json::json!({})))
+}
+
+// GET /api/2/episodes/{username}.json
+pub async fn episode_actions(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    Query(query): Query<EpisodeActionsQuery>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (username, _) = gpodder_server::split_format(&file);
+    let user = authenticate(&state, &headers, &method, &uri, username).await?;
+    let timestamp = now();
+    let device_id = match &query.device {
+        Some(device) => match gpodder_server::existing_device_id(&state.db_pool, user.user_id, device).await? {
+            Some(id) => Some(id),
+            None => return Ok(Json(serde_json::json!({"actions": [], "timestamp": timestamp}))),
+        },
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(MAX_ACTIONS_PER_RESPONSE).clamp(1, MAX_ACTIONS_PER_RESPONSE);
+    let actions = gpodder_server::episode_actions(
+        &state.db_pool,
+        user.user_id,
+        &ActionQuery {
+            since: query.since.unwrap_or(0),
+            podcast: query.podcast.clone(),
+            device_id,
+            aggregated: query.aggregated.unwrap_or(false),
+            limit,
+            newest_first: query.order.as_deref() == Some("desc"),
+        },
+    )
+    .await?;
+    // A full page resumes from its newest action rather than now, so the rest isn't skipped
+    let timestamp = match actions.iter().map(|(_, received)| *received).max() {
+        Some(newest) if actions.len() as i64 == limit => newest,
+        _ => timestamp,
+    };
+    Ok(Json(serde_json::json!({
+        "actions": actions.iter().map(|(action, _)| action.to_json()).collect::<Vec<_>>(),
+        "timestamp": timestamp,
+    })))
+}
+
+// POST /api/2/episodes/{username}.json
+pub async fn upload_episode_actions(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Json(uploaded): Json<Vec<serde_json::Value>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (username, _) = gpodder_server::split_format(&file);
+    let user = authenticate(&state, &headers, &method, &uri, username).await?;
+    let timestamp = now();
+
+    let mut parsed = Vec::new();
+    let mut stored = Vec::new();
+    for value in &uploaded {
+        let Some(action) = gpodder_sync::parse_action(value) else {
+            continue;
+        };
+        let field = |key: &str| value.get(key).and_then(|v| v.as_i64()).map(|v| v as i32);
+        stored.push(StoredAction {
+            device: value.get("device").and_then(|d| d.as_str()).filter(|d| !d.is_empty()).map(str::to_string),
+            podcast: action.podcast.clone(),
+            episode: action.episode.clone(),
+            action: action.action.clone(),
+            timestamp: action.timestamp.timestamp(),
+            started: field("started"),
+            position: action.position,
+            total: field("total"),
+        });
+        parsed.push(action);
+    }
+    if parsed.len() < uploaded.len() {
+        tracing::debug!("Dropped {} malformed episode actions from user {}", uploaded.len() - parsed.len(), user.user_id);
+    }
+
+    let mut device_ids = HashMap::new();
+    for device in stored.iter().filter_map(|a| a.device.as_deref()) {
+        if !device_ids.contains_key(device) {
+            let id = gpodder_server::device_id(&state.db_pool, user.user_id, device).await?;
+            device_ids.insert(device.to_string(), id);
+        }
+    }
+    gpodder_server::record_episode_actions(&state.db_pool, user.user_id, &device_ids, &stored, timestamp).await?;
+    gpodder_sync::apply_actions(&state.db_pool, user.user_id, INTERNAL_GPODDER_URL, &parsed).await?;
+
+    Ok(Json(serde_json::json!({
+        "timestamp": timestamp,
+        "update_urls": [],
+    })))
+}
+
+async fn sync_status(state: &AppState, user_id: i32) -> AppResult<serde_json::Value> {
+    let (devices, pairs) = gpodder_server::device_pairs(&state.db_pool, user_id).await?;
+    let (groups, unsynced) = gpodder_server::sync_groups(&devices, &pairs);
+    Ok(serde_json::json!({
+        "synchronized": groups,
+        "not-synchronized": unsynced,
+    }))
+}
+
+// GET /api/2/sync-devices/{username}.json
+pub async fn sync_devices(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (username, _) = gpodder_server::split_format(&file);
+    let user = authenticate(&state, &headers, &method, &uri, username).await?;
+    Ok(Json(sync_status(&state, user.user_id).await?))
+}
+
+// POST /api/2/sync-devices/{username}.json. PinePods keeps one subscription list per user, so
+// every device already sees the same subscriptions; groups are stored for clients that show them.
+pub async fn update_sync_devices(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Json(update): Json<SyncDevicesUpdate>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (username, _) = gpodder_server::split_format(&file);
+    let user = authenticate(&state, &headers, &method, &uri, username).await?;
+    for device in &update.stop_synchronize {
+        if let Some(id) = gpodder_server::existing_device_id(&state.db_pool, user.user_id, device).await? {
+            gpodder_server::unpair_device(&state.db_pool, user.user_id, id).await?;
+        }
+    }
+    for group in &update.synchronize {
+        let mut ids = Vec::new();
+        for device in group {
+            ids.push(gpodder_server::device_id(&state.db_pool, user.user_id, device).await?);
+        }
+        for pair in ids.windows(2) {
+            if pair[0] != pair[1] {
+                gpodder_server::pair_devices(&state.db_pool, user.user_id, pair[0], pair[1]).await?;
+            }
+        }
+    }
+    Ok(Json(sync_status(&state, user.user_id).await?))
+}
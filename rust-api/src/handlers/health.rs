@@ -1,8 +1,13 @@
-use axum::{extract::State, response::Json};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Json, Response},
+};
 use chrono::Utc;
 use crate::{
-    error::AppResult,
+    error::{AppError, AppResult},
     models::{HealthResponse, PinepodsCheckResponse},
+    services::metrics,
     AppState,
 };
 
@@ -53,4 +58,46 @@ pub async fn health_check(State(state): State<AppState>) -> AppResult<Json<Healt
         redis: redis_healthy,
         timestamp: Utc::now(),
     }))
-}
\ No newline at end of file
+}
+/// Prometheus metrics endpoint
+/// GET /api/metrics
+#[utoipa::path(
+    get,
+    path = "/api/metrics",
+    tag = "health",
+    description = "Prometheus text-format metrics: per-route request latency and status, database pool usage, Redis latency, feed refresh outcomes and durations, download bytes, task queue depth and AI sidecar availability. Unauthenticated unless PINEPODS_METRICS_TOKEN is set, in which case it must be sent as a bearer token.",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus exposition format", content_type = "text/plain"),
+        (status = 401, description = "PINEPODS_METRICS_TOKEN is set and the bearer token does not match"),
+    ),
+)]
+pub async fn metrics(State(state): State<AppState>, headers: HeaderMap) -> AppResult<Response> {
+    if let Some(expected) = std::env::var("PINEPODS_METRICS_TOKEN").ok().filter(|t| !t.is_empty()) {
+        let provided = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if provided != Some(expected.as_str()) {
+            return Err(AppError::unauthorized("Invalid metrics token"));
+        }
+    }
+
+    let redis_started = std::time::Instant::now();
+    let redis_up = state.redis_client.health_check().await.unwrap_or(false);
+    let redis_latency_seconds = redis_up.then(|| redis_started.elapsed().as_secs_f64());
+
+    let snapshot = metrics::Snapshot {
+        pool: state.db_pool.pool_stats(),
+        redis_up,
+        redis_latency_seconds,
+        tasks: state.task_manager.status_counts().await.unwrap_or_default(),
+        ai_configured: crate::services::ai_client::ai_base_url().is_some(),
+        ai_available: state.ai_available.is_available(),
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics::render(&snapshot),
+    )
+        .into_response())
+}
@@ -6,6 +6,7 @@ use axum::{
 use chrono::{DateTime, NaiveDateTime, Utc};
 use id3::TagLike;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use tracing::{info};
@@ -13,12 +14,13 @@ use tracing::{info};
 use crate::{
     error::AppError,
     handlers::{extract_api_key, validate_api_key},
+    services::{local_books, media_tags},
     AppState,
 };
 
-const LOCAL_MEDIA_ROOT: &str = "/opt/pinepods/local-media";
+pub(crate) const LOCAL_MEDIA_ROOT: &str = "/opt/pinepods/local-media";
 const ARTWORK_DIR: &str = "_artwork";
-const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "ogg", "flac", "wav", "aac", "opus"];
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "m4b", "ogg", "flac", "wav", "aac", "opus"];
 
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct AddLocalPodcastRequest {
@@ -28,6 +30,9 @@ pub struct AddLocalPodcastRequest {
     pub description: Option<String>,
     pub author: Option<String>,
     pub explicit: Option<bool>,
+    /// "episodes" (default): one episode per file. "book": the files are parts of one audiobook,
+    /// exposed as a single episode with merged chapters.
+    pub kind: Option<String>,
 }
 
 #[derive(Deserialize, utoipa::ToSchema)]
@@ -57,7 +62,13 @@ pub struct LocalEpisodeCandidate {
     pub pub_date: NaiveDateTime,
     pub duration: i32,
     pub track_num: Option<u32>,
+    /// Disc number for multi-disc sets; only audiobook ordering uses it.
+    #[serde(default)]
+    pub disc_num: Option<u32>,
     pub artwork_url: Option<String>,
+    /// Chapters embedded in the file (ID3 CHAP, MP4 chapter track, Vorbis CHAPTERxxx).
+    #[serde(default)]
+    pub chapters: Vec<media_tags::Chapter>,
 }
 
 pub(crate) fn validate_local_media_path(input: &str) -> Result<PathBuf, AppError> {
@@ -73,6 +84,12 @@ pub(crate) fn validate_local_media_path(input: &str) -> Result<PathBuf, AppError
     Ok(canonical)
 }
 
+pub(crate) fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
 fn ensure_artwork_dir() -> Result<PathBuf, AppError> {
     let artwork_dir = PathBuf::from(LOCAL_MEDIA_ROOT).join(ARTWORK_DIR);
     std::fs::create_dir_all(&artwork_dir)
@@ -115,18 +132,109 @@ fn find_cover_art_in_dir(dir: &Path, artwork_dir: &Path) -> Option<String> {
     None
 }
 
-pub fn scan_local_directory(dir: &Path) -> Result<Vec<LocalEpisodeCandidate>, AppError> {
-    let artwork_dir = ensure_artwork_dir()?;
+fn file_modified_time(path: &Path) -> Option<NaiveDateTime> {
+    std::fs::metadata(path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| {
+            let secs = t
+                .duration_since(std::time::UNIX_EPOCH)
+                .ok()?
+                .as_secs() as i64;
+            DateTime::from_timestamp(secs, 0).map(|dt| dt.naive_utc())
+        })
+}
 
-    // Look for a cover art file in the directory once
-    let dir_artwork = find_cover_art_in_dir(dir, &artwork_dir);
+fn file_stem_title(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unknown")
+        .to_string()
+}
 
-    let mut candidates: Vec<LocalEpisodeCandidate> = Vec::new();
+// Read one audio file's tags: ID3 first (as before), then ffprobe for MP4 atoms and Vorbis
+// comments, falling back to the filename and modification time.
+fn read_local_file(path: &Path, artwork_dir: &Path) -> LocalEpisodeCandidate {
+    let path_str = path.to_string_lossy().to_string();
+
+    if let Ok(tag) = id3::Tag::read_from_path(path) {
+        let pub_date = tag
+            .date_recorded()
+            .and_then(|d| {
+                NaiveDateTime::parse_from_str(
+                    &format!(
+                        "{:04}-{:02}-{:02} 00:00:00",
+                        d.year,
+                        d.month.unwrap_or(1),
+                        d.day.unwrap_or(1)
+                    ),
+                    "%Y-%m-%d %H:%M:%S",
+                )
+                .ok()
+            })
+            .or_else(|| file_modified_time(path))
+            .unwrap_or_else(|| Utc::now().naive_utc());
+
+        // mp3-metadata only understands MPEG audio; ID3-tagged WAV/AAC need ffprobe.
+        let duration = crate::handlers::youtube::get_mp3_duration(&path_str)
+            .filter(|d| *d > 0)
+            .or_else(|| media_tags::probe(path).and_then(|t| t.duration))
+            .unwrap_or(0);
+
+        return LocalEpisodeCandidate {
+            file_path: path_str,
+            title: tag.title().map(|t| t.to_string()).unwrap_or_else(|| file_stem_title(path)),
+            description: tag
+                .comments()
+                .next()
+                .map(|c| c.text.clone())
+                .unwrap_or_default(),
+            pub_date,
+            duration,
+            track_num: tag.track(),
+            disc_num: tag.disc(),
+            artwork_url: extract_id3_artwork(&tag, artwork_dir),
+            chapters: media_tags::id3_chapters(&tag),
+        };
+    }
 
+    let tags = media_tags::probe(path).unwrap_or_default();
+    let artwork_url = if tags.has_artwork {
+        media_tags::extract_artwork(path).and_then(|data| save_artwork_bytes(&data, artwork_dir))
+    } else {
+        None
+    };
+    LocalEpisodeCandidate {
+        title: tags.title.unwrap_or_else(|| file_stem_title(path)),
+        description: tags.description.unwrap_or_default(),
+        pub_date: tags
+            .date
+            .or_else(|| file_modified_time(path))
+            .unwrap_or_else(|| Utc::now().naive_utc()),
+        duration: tags
+            .duration
+            .or_else(|| crate::handlers::youtube::get_mp3_duration(&path_str))
+            .unwrap_or(0),
+        track_num: tags.track,
+        disc_num: tags.disc,
+        artwork_url,
+        chapters: tags.chapters,
+        file_path: path_str,
+    }
+}
+
+pub fn scan_local_directory(dir: &Path) -> Result<Vec<LocalEpisodeCandidate>, AppError> {
+    scan_new_local_files(dir, &HashSet::new())
+}
+
+// Like scan_local_directory, but files in `known` (already imported) are skipped before any
+// tags are read, so refreshes and the folder watcher only touch new files.
+pub fn scan_new_local_files(dir: &Path, known: &HashSet<String>) -> Result<Vec<LocalEpisodeCandidate>, AppError> {
     let entries = std::fs::read_dir(dir).map_err(|e| {
         AppError::bad_request(format!("Cannot read directory: {}", e))
     })?;
 
+    let mut new_files = Vec::new();
     for entry in entries {
         let entry = match entry {
             Ok(e) => e,
@@ -134,117 +242,32 @@ pub fn scan_local_directory(dir: &Path) -> Result<Vec<LocalEpisodeCandidate>, Ap
         };
 
         let path = entry.path();
-        if !path.is_file() {
+        if !path.is_file() || !is_audio_file(&path) {
             continue;
         }
-
-        // Filter by audio extension
-        let ext = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .map(|e| e.to_lowercase())
-            .unwrap_or_default();
-        if !AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        if known.contains(path.to_string_lossy().as_ref()) {
             continue;
         }
-
-        let path_str = path.to_string_lossy().to_string();
-
-        // Try to read ID3 tags (works for MP3/M4A/most formats)
-        let (title, description, pub_date, duration, track_num, episode_artwork) =
-            if let Ok(tag) = id3::Tag::read_from_path(&path) {
-                let title = tag
-                    .title()
-                    .map(|t| t.to_string())
-                    .unwrap_or_else(|| {
-                        path.file_stem()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or("Unknown")
-                            .to_string()
-                    });
-
-                let description = tag
-                    .comments()
-                    .next()
-                    .map(|c| c.text.clone())
-                    .unwrap_or_default();
-
-                let pub_date = tag
-                    .date_recorded()
-                    .and_then(|d| {
-                        NaiveDateTime::parse_from_str(
-                            &format!(
-                                "{:04}-{:02}-{:02} 00:00:00",
-                                d.year,
-                                d.month.unwrap_or(1),
-                                d.day.unwrap_or(1)
-                            ),
-                            "%Y-%m-%d %H:%M:%S",
-                        )
-                        .ok()
-                    })
-                    .or_else(|| {
-                        // Fallback: file modification time
-                        std::fs::metadata(&path)
-                            .ok()
-                            .and_then(|m| m.modified().ok())
-                            .and_then(|t| {
-                                let secs = t
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .ok()?
-                                    .as_secs() as i64;
-                                DateTime::from_timestamp(secs, 0).map(|dt| dt.naive_utc())
-                            })
-                    })
-                    .unwrap_or_else(|| Utc::now().naive_utc());
-
-                let track_num = tag.track();
-
-                let duration = crate::handlers::youtube::get_mp3_duration(&path_str)
-                    .unwrap_or(0);
-
-                let episode_artwork = extract_id3_artwork(&tag, &artwork_dir);
-
-                (title, description, pub_date, duration, track_num, episode_artwork)
-            } else {
-                // No ID3 tags — use filename and file metadata
-                let title = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
-
-                let pub_date = std::fs::metadata(&path)
-                    .ok()
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|t| {
-                        let secs = t
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .ok()?
-                            .as_secs() as i64;
-                        DateTime::from_timestamp(secs, 0).map(|dt| dt.naive_utc())
-                    })
-                    .unwrap_or_else(|| Utc::now().naive_utc());
-
-                let duration = crate::handlers::youtube::get_mp3_duration(&path_str)
-                    .unwrap_or(0);
-
-                (title, String::new(), pub_date, duration, None, None)
-            };
-
-        let artwork_url = episode_artwork.or_else(|| dir_artwork.clone());
-
-        candidates.push(LocalEpisodeCandidate {
-            file_path: path_str,
-            title,
-            description,
-            pub_date,
-            duration,
-            track_num,
-            artwork_url,
-        });
+        new_files.push(path);
+    }
+    if new_files.is_empty() {
+        return Ok(Vec::new());
     }
 
+    let artwork_dir = ensure_artwork_dir()?;
+
+    // Look for a cover art file in the directory once
+    let dir_artwork = find_cover_art_in_dir(dir, &artwork_dir);
+
+    let mut candidates: Vec<LocalEpisodeCandidate> = new_files
+        .iter()
+        .map(|path| {
+            let mut candidate = read_local_file(path, &artwork_dir);
+            candidate.artwork_url = candidate.artwork_url.or_else(|| dir_artwork.clone());
+            candidate
+        })
+        .collect();
+
     // Sort: by track number first, then filename alphanumerically
     candidates.sort_by(|a, b| {
         match (a.track_num, b.track_num) {
@@ -294,6 +317,12 @@ pub async fn add_local_podcast(
         return Err(AppError::forbidden("You can only add podcasts for yourself"));
     }
 
+    let is_book = match request.kind.as_deref().unwrap_or("episodes") {
+        "episodes" => false,
+        "book" => true,
+        other => return Err(AppError::bad_request(format!("Unknown local podcast kind '{}' (expected episodes or book)", other))),
+    };
+
     let canonical_path = validate_local_media_path(&request.directory_path)?;
     let feed_url = format!("local://{}", canonical_path.display());
 
@@ -305,7 +334,7 @@ pub async fn add_local_podcast(
     let candidates = scan_local_directory(&canonical_path)?;
     if candidates.is_empty() {
         return Err(AppError::bad_request(
-            "No audio files found in the specified directory. Supported formats: mp3, m4a, ogg, flac, wav, aac, opus",
+            "No audio files found in the specified directory. Supported formats: mp3, m4a, m4b, ogg, flac, wav, aac, opus",
         ));
     }
 
@@ -327,10 +356,20 @@ pub async fn add_local_podcast(
         )
         .await?;
 
-    state
-        .db_pool
-        .add_local_episodes(podcast_id, request.user_id, &candidates)
-        .await?;
+    if is_book {
+        let details = local_books::BookDetails::from_candidates(&candidates)
+            .ok_or_else(|| AppError::internal("Audiobook has no parts"))?;
+        let book = local_books::assemble(candidates.iter().cloned().map(local_books::BookPart::from).collect());
+        state
+            .db_pool
+            .create_local_book(podcast_id, request.user_id, &feed_url, &request.podcast_name, &details, &book)
+            .await?;
+    } else {
+        state
+            .db_pool
+            .add_local_episodes(podcast_id, request.user_id, &candidates)
+            .await?;
+    }
 
     let podcast_details = state
         .db_pool
@@ -338,14 +377,75 @@ pub async fn add_local_podcast(
         .await?;
 
     info!(
-        "✅ Local podcast '{}' added with {} episodes",
+        "✅ Local {} '{}' added with {} {}",
+        if is_book { "audiobook" } else { "podcast" },
         request.podcast_name,
-        candidates.len()
+        candidates.len(),
+        if is_book { "parts" } else { "episodes" }
     );
 
     Ok(Json(serde_json::json!({ "data": podcast_details })))
 }
 
+// Add episodes for audio files in `dir` that the podcast doesn't have yet. Shared by manual
+// refresh and the local-media folder watcher. For an audiobook the new files become parts of
+// its one episode instead.
+pub(crate) async fn add_new_local_episodes(
+    db_pool: &crate::database::DatabasePool,
+    podcast_id: i32,
+    user_id: i32,
+    dir: &Path,
+) -> Result<usize, AppError> {
+    if let Some(parts) = db_pool.get_local_book_parts(podcast_id).await? {
+        return add_new_book_parts(db_pool, podcast_id, user_id, dir, parts).await;
+    }
+
+    let existing_paths: HashSet<String> = db_pool
+        .get_local_episode_paths(podcast_id)
+        .await?
+        .into_iter()
+        .collect();
+
+    // Tag reading shells out to ffprobe per file; keep it off the async workers.
+    let dir_owned = dir.to_path_buf();
+    let new_candidates = tokio::task::spawn_blocking(move || scan_new_local_files(&dir_owned, &existing_paths))
+        .await
+        .map_err(|e| AppError::internal(format!("Local media scan failed: {}", e)))??;
+
+    if !new_candidates.is_empty() {
+        db_pool
+            .add_local_episodes(podcast_id, user_id, &new_candidates)
+            .await?;
+    }
+    Ok(new_candidates.len())
+}
+
+// Re-assemble a book from its stored parts plus any new files in `dir`. Parts whose files are
+// gone are dropped, since the joined stream can't be built without them.
+async fn add_new_book_parts(
+    db_pool: &crate::database::DatabasePool,
+    podcast_id: i32,
+    user_id: i32,
+    dir: &Path,
+    parts: Vec<local_books::BookPart>,
+) -> Result<usize, AppError> {
+    let known: HashSet<String> = parts.iter().map(|p| p.file_path.clone()).collect();
+    let dir_owned = dir.to_path_buf();
+    let new_candidates = tokio::task::spawn_blocking(move || scan_new_local_files(&dir_owned, &known))
+        .await
+        .map_err(|e| AppError::internal(format!("Local media scan failed: {}", e)))??;
+
+    let stored = parts.len();
+    let mut parts: Vec<local_books::BookPart> = parts.into_iter().filter(|p| Path::new(&p.file_path).is_file()).collect();
+    if new_candidates.is_empty() && parts.len() == stored {
+        return Ok(0);
+    }
+    let added = new_candidates.len();
+    parts.extend(new_candidates.into_iter().map(local_books::BookPart::from));
+    db_pool.update_local_book(podcast_id, user_id, &local_books::assemble(parts)).await?;
+    Ok(added)
+}
+
 #[utoipa::path(
     post,
     path = "/refresh_local_podcast",
@@ -389,26 +489,7 @@ pub async fn refresh_local_podcast(
         return Err(AppError::bad_request("Local media directory no longer exists"));
     }
 
-    let candidates = scan_local_directory(canonical_path)?;
-
-    // Get existing episode file paths to avoid duplicates
-    let existing_paths = state
-        .db_pool
-        .get_local_episode_paths(request.podcast_id)
-        .await?;
-
-    let new_candidates: Vec<LocalEpisodeCandidate> = candidates
-        .into_iter()
-        .filter(|c| !existing_paths.contains(&c.file_path))
-        .collect();
-
-    let new_count = new_candidates.len();
-    if !new_candidates.is_empty() {
-        state
-            .db_pool
-            .add_local_episodes(request.podcast_id, request.user_id, &new_candidates)
-            .await?;
-    }
+    let new_count = add_new_local_episodes(&state.db_pool, request.podcast_id, request.user_id, canonical_path).await?;
 
     info!(
         "🔄 Refreshed local podcast {}: {} new episodes",
@@ -546,13 +627,7 @@ fn count_audio_files(dir: &Path) -> usize {
         .filter_map(|entry| entry.ok())
         .filter(|entry| {
             let path = entry.path();
-            if !path.is_file() {
-                return false;
-            }
-            path.extension()
-                .and_then(|e| e.to_str())
-                .map(|e| AUDIO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
-                .unwrap_or(false)
+            path.is_file() && is_audio_file(&path)
         })
         .count()
 }
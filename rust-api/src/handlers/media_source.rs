@@ -0,0 +1,161 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info, warn};
+
+use crate::{
+    error::AppError,
+    handlers::{extract_api_key, validate_api_key},
+    services::media_source,
+    AppState,
+};
+
+// Query struct for the media-site subscription endpoint
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct MediaSubscribeQuery {
+    /// Playlist, channel, album or user page on a site yt-dlp supports (not YouTube)
+    pub url: String,
+    pub user_id: i32,
+    pub feed_cutoff: Option<i32>,
+}
+
+// Subscribe to a playlist or channel on any yt-dlp supported site as a video podcast
+#[utoipa::path(
+    post,
+    path = "/media/subscribe",
+    tag = "youtube",
+    summary = "Subscribe to a media site playlist or channel",
+    description = "Follows a playlist, channel, album or user page on any site yt-dlp has an extractor for (Vimeo, SoundCloud, Bandcamp, Twitch VODs, PeerTube, ...). Entries are stored and downloaded like YouTube videos; YouTube URLs must use /youtube/subscribe.",
+    params(MediaSubscribeQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Not a public URL, a YouTube URL, or not a playlist/channel yt-dlp can list"),
+        (status = 401, description = "Invalid or missing API key"),
+    ),
+)]
+pub async fn subscribe_to_media_source(
+    State(state): State<AppState>,
+    Query(query): Query<MediaSubscribeQuery>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    // Check authorization - web key or user can only subscribe for themselves
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+
+    if key_id != query.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only subscribe for yourself!"));
+    }
+
+    let feed_url = media_source::validate_url(&query.url).await.map_err(AppError::bad_request)?;
+    let feed_cutoff = query.feed_cutoff.unwrap_or(30);
+
+    if let Some(podcast_id) = state.db_pool.check_existing_channel_subscription(&feed_url, query.user_id).await? {
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "podcast_id": podcast_id,
+            "message": "Already subscribed to this source"
+        })));
+    }
+
+    // The listing both proves yt-dlp can read the page and names the podcast.
+    let listing = media_source::fetch_listing(&feed_url).await.map_err(AppError::bad_request)?;
+    info!("Subscribing user {} to {} source {}", query.user_id, listing.extractor, feed_url);
+
+    let thumbnail_url = if listing.thumbnail_url.is_empty() {
+        listing.entries.first().map(|e| e.thumbnail.clone()).unwrap_or_default()
+    } else {
+        listing.thumbnail_url.clone()
+    };
+    let mut source_info = HashMap::new();
+    source_info.insert("channel_id".to_string(), feed_url.clone());
+    source_info.insert("feed_url".to_string(), feed_url.clone());
+    source_info.insert("name".to_string(), if listing.name.is_empty() { feed_url.clone() } else { listing.name.clone() });
+    source_info.insert("description".to_string(), listing.description.clone());
+    source_info.insert("thumbnail_url".to_string(), thumbnail_url);
+    source_info.insert("source_kind".to_string(), media_source::SOURCE_KIND.to_string());
+
+    let podcast_id = state.db_pool.add_youtube_channel(&source_info, query.user_id, feed_cutoff).await?;
+
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = process_media_source(podcast_id, &feed_url, feed_cutoff, &state_clone).await {
+            warn!("Error processing media source {}: {}", feed_url, e);
+        }
+    });
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "podcast_id": podcast_id,
+        "message": "Successfully subscribed"
+    })))
+}
+
+// List a media-site podcast's newest entries, store the new ones and download their audio
+pub async fn process_media_source(
+    podcast_id: i32,
+    feed_url: &str,
+    feed_cutoff: i32,
+    state: &AppState,
+) -> Result<(), AppError> {
+    debug!("Processing media source: podcast_id={} url={}", podcast_id, feed_url);
+
+    let cutoff_date = chrono::Utc::now() - chrono::Duration::days(feed_cutoff as i64);
+    state.db_pool.remove_old_youtube_videos(podcast_id, cutoff_date).await?;
+
+    let listing = media_source::fetch_listing(feed_url)
+        .await
+        .map_err(|e| AppError::external_error(&e))?;
+    let existing: HashSet<String> = state.db_pool.get_existing_youtube_videos(podcast_id).await?.into_iter().collect();
+
+    let mut new_videos = Vec::new();
+    let mut recent_videos = Vec::new();
+    for (index, mut entry) in listing.entries.into_iter().enumerate() {
+        // Entries already stored survived the cutoff cleanup, so only their files may be missing.
+        if existing.contains(&entry.url) {
+            recent_videos.push(serde_json::json!({"id": entry.id, "title": entry.title, "url": entry.url}));
+            continue;
+        }
+        if entry.published.is_none() {
+            if let Err(e) = media_source::fetch_details(&mut entry).await {
+                warn!("Could not look up {}: {}", entry.url, e);
+            }
+        }
+        // Undated entries keep the listing's (newest first) order.
+        let published = entry
+            .published
+            .unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::minutes(index as i64));
+        if published <= cutoff_date {
+            debug!("Entry {} from {} is too old, skipping", entry.id, published);
+            continue;
+        }
+
+        let video = serde_json::json!({
+            "id": entry.id,
+            "title": entry.title,
+            "description": entry.description,
+            "url": entry.url,
+            "thumbnail": entry.thumbnail,
+            "publish_date": published.to_rfc3339(),
+            "duration": entry.duration.unwrap_or(0)
+        });
+        recent_videos.push(video.clone());
+        new_videos.push(video);
+    }
+
+    if !new_videos.is_empty() {
+        state.db_pool.add_youtube_videos(podcast_id, &new_videos).await?;
+        info!("Added {} new entries to media source {}", new_videos.len(), podcast_id);
+    }
+    crate::handlers::youtube::download_recent_audio(state, &recent_videos).await;
+
+    state.db_pool.update_episode_count(podcast_id).await?;
+    Ok(())
+}
@@ -5,20 +5,34 @@ pub mod episodes;
 pub mod playlists;
 pub mod collections;
 pub mod websocket;
+pub mod playback;
 // pub mod async_tasks_examples;  // File was deleted
 pub mod refresh;
 pub mod proxy;
 pub mod settings;
 pub mod sync;
+pub mod gpodder_api;
 pub mod youtube;
 pub mod tasks;
 pub mod feed;
 pub mod local_podcast;
+pub mod websub;
+pub mod scrobble;
+pub mod notification_channels;
+pub mod webhooks;
+pub mod media_source;
 
 // Common handler utilities
-use axum::http::HeaderMap;
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 use crate::{
+    database::ApiKeyPolicy,
     error::{AppError, AppResult},
+    services::api_scopes::{parse_scopes, scopes_permit},
     AppState,
 };
 
@@ -33,6 +47,108 @@ pub fn extract_api_key(headers: &HeaderMap) -> AppResult<String> {
         .ok_or_else(|| AppError::unauthorized("Missing API key"))
 }
 
+// API key passed as a query parameter (RSS feeds, stream URLs and websockets use this form)
+fn extract_query_api_key(uri: &axum::http::Uri) -> Option<String> {
+    url::form_urlencoded::parse(uri.query()?.as_bytes())
+        .find(|(name, _)| name == "api_key")
+        .map(|(_, value)| value.into_owned())
+}
+
+// Load a key's scope/expiry policy, cached briefly in Redis. A cache miss is also when
+// the key's last-used time is refreshed, so that costs at most one write per key per TTL.
+async fn load_api_key_policy(state: &AppState, api_key: &str) -> AppResult<Option<ApiKeyPolicy>> {
+    if let Ok(Some(cached)) = state.redis_client.get_cached_api_key_policy(api_key).await {
+        if let Ok(policy) = serde_json::from_str::<Option<ApiKeyPolicy>>(&cached) {
+            return Ok(policy);
+        }
+    }
+
+    let policy = state.db_pool.get_api_key_policy(api_key).await?;
+    if let Some(policy) = &policy {
+        if !policy.revoked && !policy.expired {
+            state.db_pool.touch_api_key_last_used(policy.api_key_id).await?;
+        }
+    }
+
+    if let Err(e) = state.redis_client
+        .cache_api_key_policy(api_key, &serde_json::to_string(&policy)?, 60)
+        .await
+    {
+        tracing::warn!("Failed to cache API key policy: {}", e);
+    }
+
+    Ok(policy)
+}
+
+// Central API key policy check, applied to every route as middleware. Rejects revoked and
+// expired keys and enforces a scoped key's scope set against the request's method + path,
+// so handlers keep calling extract_api_key/validate_api_key without knowing about scopes.
+// Requests without a key, or whose key isn't an API key (e.g. an RSS key), pass through to
+// the handler's own checks.
+pub async fn enforce_api_key_policy(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let api_key = match extract_api_key(request.headers())
+        .ok()
+        .or_else(|| extract_query_api_key(request.uri()))
+    {
+        Some(key) => key,
+        None => return next.run(request).await,
+    };
+
+    let policy = match load_api_key_policy(&state, &api_key).await {
+        Ok(Some(policy)) => policy,
+        Ok(None) => return next.run(request).await,
+        Err(e) => return e.into_response(),
+    };
+
+    if policy.revoked {
+        return AppError::unauthorized("API key has been revoked").into_response();
+    }
+    if policy.expired {
+        return AppError::unauthorized("API key has expired").into_response();
+    }
+
+    if let Some(raw_scopes) = &policy.scopes {
+        let allowed = parse_scopes(raw_scopes)
+            .map(|scopes| scopes_permit(&scopes, request.method(), request.uri().path()))
+            .unwrap_or(false);
+        if !allowed {
+            return AppError::forbidden(format!(
+                "API key scope ({}) does not permit {} {}",
+                raw_scopes,
+                request.method(),
+                request.uri().path()
+            ))
+            .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+// Records every request's latency and status for /api/metrics, labelled by the matched route
+// template (e.g. /api/data/episode/{id}) rather than the raw path to keep label cardinality bounded.
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| crate::services::metrics::UNMATCHED_ROUTE.to_string());
+    let started = std::time::Instant::now();
+    let response = next.run(request).await;
+    crate::services::metrics::observe_http_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed().as_secs_f64(),
+    );
+    response
+}
+
 // Validate API key against database/cache
 pub async fn validate_api_key(state: &AppState, api_key: &str) -> AppResult<bool> {
     // First check Redis cache
@@ -70,4 +186,35 @@ pub async fn check_user_or_admin_access(state: &AppState, api_key: &str, target_
         // Check if user is admin
         state.db_pool.user_admin_check(requesting_user_id).await
     }
+}
+
+// Validate the request's API key and require it to belong to `user_id` (or be the web key).
+// Returns the key's user ID; `what` names the resource in the error, e.g. "webhooks".
+pub async fn check_owner(state: &AppState, headers: &HeaderMap, user_id: i32, what: &str) -> AppResult<i32> {
+    let api_key = extract_api_key(headers)?;
+    if !validate_api_key(state, &api_key).await? {
+        return Err(AppError::unauthorized("Invalid API key"));
+    }
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    if key_id != user_id && !state.db_pool.is_web_key(&api_key).await? {
+        return Err(AppError::forbidden(format!("You can only manage your own {}.", what)));
+    }
+    Ok(key_id)
+}
+
+// Self-hosted receivers (webhooks, ntfy/Gotify/Matrix servers) usually live on the LAN; only
+// admins may point outbound requests at private or loopback addresses.
+pub async fn ensure_urls_allowed(state: &AppState, key_id: i32, urls: &[&str]) -> AppResult<()> {
+    let is_admin = state.db_pool.user_admin_check(key_id).await?;
+    for url in urls.iter().map(|u| u.trim()).filter(|u| !u.is_empty()) {
+        if !is_admin {
+            crate::services::url_guard::ensure_safe_public_url_async(url)
+                .await
+                .map_err(|reason| AppError::bad_request(format!("URL not allowed: {}", reason)))?;
+        } else if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(AppError::bad_request("URLs must be http(s) URLs"));
+        }
+    }
+    Ok(())
 }
\ No newline at end of file
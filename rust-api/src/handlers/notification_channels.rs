@@ -0,0 +1,296 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AppError,
+    handlers::{check_owner, ensure_urls_allowed},
+    services::notification_channels::{self, ChannelConfig, ChannelSettings, NotificationChannel},
+    AppState,
+};
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct NotificationChannelsQuery {
+    pub user_id: i32,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct NotificationChannelsResponse {
+    pub channels: Vec<NotificationChannel>,
+    pub platforms: Vec<String>,
+    /// Placeholders usable in templates, written `{name}`.
+    pub template_variables: Vec<String>,
+    pub default_title_template: String,
+    pub default_message_template: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/notification_channels",
+    tag = "notifications",
+    summary = "List notification channels",
+    description = "Returns the user's new-episode notification channels, with tokens, passwords and webhook URLs blanked, plus the supported platforms and template placeholders.",
+    params(NotificationChannelsQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = NotificationChannelsResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these channels"),
+    ),
+)]
+pub async fn get_notification_channels(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<NotificationChannelsQuery>,
+) -> Result<Json<NotificationChannelsResponse>, AppError> {
+    check_owner(&state, &headers, query.user_id, "notification channels").await?;
+    let channels = notification_channels::list_channels(&state.db_pool, query.user_id).await?;
+    Ok(Json(NotificationChannelsResponse {
+        channels,
+        platforms: ChannelConfig::PLATFORMS.map(str::to_string).to_vec(),
+        template_variables: notification_channels::TEMPLATE_VARIABLES.map(str::to_string).to_vec(),
+        default_title_template: notification_channels::DEFAULT_TITLE_TEMPLATE.to_string(),
+        default_message_template: notification_channels::DEFAULT_MESSAGE_TEMPLATE.to_string(),
+    }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct NotificationChannelRequest {
+    pub user_id: i32,
+    pub name: String,
+    /// Defaults to true.
+    pub enabled: Option<bool>,
+    pub config: ChannelConfig,
+    /// Omit or leave empty for the default title.
+    pub title_template: Option<String>,
+    /// Omit or leave empty for the default message.
+    pub message_template: Option<String>,
+}
+
+async fn validate_channel(state: &AppState, key_id: i32, name: &str, config: &ChannelConfig) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::bad_request("A channel name is required"));
+    }
+    config.validate().map_err(AppError::bad_request)?;
+    ensure_urls_allowed(state, key_id, &config.urls()).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/add_notification_channel",
+    tag = "notifications",
+    summary = "Add a notification channel",
+    description = "Adds a channel that receives new-episode notifications for podcasts with notifications on. Settings are stored encrypted. Only admins may point a channel at a private or loopback address.",
+    request_body = NotificationChannelRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Missing settings or a disallowed URL"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these channels"),
+    ),
+)]
+pub async fn add_notification_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<NotificationChannelRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let key_id = check_owner(&state, &headers, request.user_id, "notification channels").await?;
+    validate_channel(&state, key_id, &request.name, &request.config).await?;
+
+    let settings = ChannelSettings {
+        name: request.name.trim(),
+        enabled: request.enabled.unwrap_or(true),
+        config: &request.config,
+        title_template: request.title_template.as_deref(),
+        message_template: request.message_template.as_deref(),
+    };
+    let channel_id = notification_channels::add_channel(&state.db_pool, request.user_id, &settings).await?;
+    Ok(Json(serde_json::json!({ "detail": "Notification channel added.", "channel_id": channel_id })))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct UpdateNotificationChannelRequest {
+    pub channel_id: i32,
+    #[serde(flatten)]
+    pub channel: NotificationChannelRequest,
+}
+
+#[utoipa::path(
+    post,
+    path = "/update_notification_channel",
+    tag = "notifications",
+    summary = "Update a notification channel",
+    description = "Replaces the channel's name, settings and templates. Secrets left blank keep their stored values, so a listed channel can be edited without re-entering them.",
+    request_body = UpdateNotificationChannelRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Missing settings or a disallowed URL"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these channels"),
+        (status = 404, description = "No such channel for this user"),
+    ),
+)]
+pub async fn update_notification_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateNotificationChannelRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let channel = request.channel;
+    let key_id = check_owner(&state, &headers, channel.user_id, "notification channels").await?;
+    let Some(stored) = notification_channels::channel_config(&state.db_pool, channel.user_id, request.channel_id).await? else {
+        return Err(AppError::not_found("Notification channel not found"));
+    };
+    let mut config = channel.config;
+    config.keep_secrets_from(&stored);
+    validate_channel(&state, key_id, &channel.name, &config).await?;
+
+    let settings = ChannelSettings {
+        name: channel.name.trim(),
+        enabled: channel.enabled.unwrap_or(true),
+        config: &config,
+        title_template: channel.title_template.as_deref(),
+        message_template: channel.message_template.as_deref(),
+    };
+    if !notification_channels::update_channel(&state.db_pool, channel.user_id, request.channel_id, &settings).await? {
+        return Err(AppError::not_found("Notification channel not found"));
+    }
+    Ok(Json(serde_json::json!({ "detail": "Notification channel updated." })))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ChannelIdRequest {
+    pub user_id: i32,
+    pub channel_id: i32,
+}
+
+#[utoipa::path(
+    post,
+    path = "/remove_notification_channel",
+    tag = "notifications",
+    summary = "Remove a notification channel",
+    description = "Deletes the channel and drops it from any podcast's routing.",
+    request_body = ChannelIdRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these channels"),
+        (status = 404, description = "No such channel for this user"),
+    ),
+)]
+pub async fn remove_notification_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ChannelIdRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_owner(&state, &headers, request.user_id, "notification channels").await?;
+    if !notification_channels::remove_channel(&state.db_pool, request.user_id, request.channel_id).await? {
+        return Err(AppError::not_found("Notification channel not found"));
+    }
+    Ok(Json(serde_json::json!({ "detail": "Notification channel removed." })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/test_notification_channel",
+    tag = "notifications",
+    summary = "Send a test notification",
+    description = "Sends the channel's templates rendered against the user's newest episode, so the test shows what a real notification looks like.",
+    request_body = ChannelIdRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Sent", body = serde_json::Value),
+        (status = 400, description = "The channel rejected the notification; the error is included"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these channels"),
+        (status = 404, description = "No such channel for this user"),
+    ),
+)]
+pub async fn test_notification_channel(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ChannelIdRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_owner(&state, &headers, request.user_id, "notification channels").await?;
+    match notification_channels::test_channel(&state.db_pool, request.user_id, request.channel_id).await? {
+        Some(Ok(())) => Ok(Json(serde_json::json!({ "detail": "Test notification sent." }))),
+        Some(Err(e)) => Err(AppError::bad_request(format!("Test notification failed: {}", e))),
+        None => Err(AppError::not_found("Notification channel not found")),
+    }
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct PodcastChannelsQuery {
+    pub user_id: i32,
+    pub podcast_id: i32,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PodcastChannelsResponse {
+    /// Channels this podcast notifies; empty means all enabled channels.
+    pub channel_ids: Vec<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/podcast_notification_channels",
+    tag = "notifications",
+    summary = "Get a podcast's notification routing",
+    params(PodcastChannelsQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = PodcastChannelsResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of this podcast"),
+    ),
+)]
+pub async fn get_podcast_notification_channels(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PodcastChannelsQuery>,
+) -> Result<Json<PodcastChannelsResponse>, AppError> {
+    check_owner(&state, &headers, query.user_id, "notification channels").await?;
+    let channel_ids = notification_channels::podcast_channels(&state.db_pool, query.user_id, query.podcast_id).await?;
+    Ok(Json(PodcastChannelsResponse { channel_ids }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SetPodcastChannelsRequest {
+    pub user_id: i32,
+    pub podcast_id: i32,
+    /// Channels to notify for this podcast; empty to notify all enabled channels.
+    pub channel_ids: Vec<i32>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/podcast_notification_channels",
+    tag = "notifications",
+    summary = "Route a podcast's notifications",
+    description = "Chooses which of the user's channels hear about new episodes of this podcast. The podcast's notification toggle still switches them on and off.",
+    request_body = SetPodcastChannelsRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Unknown channel"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of this podcast"),
+        (status = 404, description = "No such podcast for this user"),
+    ),
+)]
+pub async fn set_podcast_notification_channels(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<SetPodcastChannelsRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_owner(&state, &headers, request.user_id, "notification channels").await?;
+    if !notification_channels::set_podcast_channels(&state.db_pool, request.user_id, request.podcast_id, &request.channel_ids).await? {
+        return Err(AppError::not_found("Podcast not found"));
+    }
+    Ok(Json(serde_json::json!({ "detail": "Podcast notification channels updated." })))
+}
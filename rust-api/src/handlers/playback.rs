@@ -0,0 +1,239 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{sink::SinkExt, stream::StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    error::AppError,
+    handlers::{check_user_access, extract_api_key, validate_api_key},
+    services::playback_sync::{
+        ClientMessage, DeviceInfo, PlaybackState, RemoteAction, ServerMessage, PRESENCE_REFRESH_SECONDS,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct PlaybackStateQuery {
+    pub user_id: i32,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct PlaybackStateResponse {
+    pub now_playing: Option<PlaybackState>,
+    pub devices: Vec<DeviceInfo>,
+}
+
+// Same data as the websocket snapshot, for clients that only want to offer "continue listening"
+#[utoipa::path(
+    get,
+    path = "/playback_state",
+    tag = "podcasts",
+    summary = "Get cross-device playback state",
+    description = "Returns what the user's most recently active device is playing (episode, position, playing or paused) and which of their devices are connected to the playback websocket at /ws/api/playback/{user_id}.",
+    params(PlaybackStateQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = PlaybackStateResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "API key does not belong to the requested user"),
+    ),
+)]
+pub async fn get_playback_state(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PlaybackStateQuery>,
+) -> Result<Json<PlaybackStateResponse>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    if !validate_api_key(&state, &api_key).await? {
+        return Err(AppError::unauthorized("Invalid API key"));
+    }
+    if !check_user_access(&state, &api_key, query.user_id).await? {
+        return Err(AppError::forbidden("You can only view your own playback state"));
+    }
+
+    Ok(Json(PlaybackStateResponse {
+        now_playing: state.playback_hub.now_playing(query.user_id).await?,
+        devices: state.playback_hub.devices(query.user_id).await?,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct PlaybackSocketQuery {
+    api_key: String,
+    /// Stable per-install ID chosen by the client, so a reconnect is the same device.
+    device_id: String,
+    device_name: Option<String>,
+}
+
+pub async fn playback_websocket(
+    ws: WebSocketUpgrade,
+    Path(user_id): Path<i32>,
+    Query(query): Query<PlaybackSocketQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let authorized = match validate_api_key(&state, &query.api_key).await {
+        Ok(true) => check_user_access(&state, &query.api_key, user_id).await.unwrap_or(false),
+        _ => false,
+    };
+    if !authorized {
+        tracing::warn!("Playback WebSocket auth failed for user {}", user_id);
+        return (axum::http::StatusCode::FORBIDDEN, "Invalid API key").into_response();
+    }
+
+    let device_id = query.device_id.trim().to_string();
+    if device_id.is_empty() || device_id.len() > 100 {
+        return (axum::http::StatusCode::BAD_REQUEST, "A device_id of up to 100 characters is required")
+            .into_response();
+    }
+    let device = DeviceInfo {
+        device_name: query
+            .device_name
+            .map(|name| name.trim().chars().take(100).collect::<String>())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| device_id.clone()),
+        device_id,
+        last_seen: 0,
+    };
+
+    ws.on_upgrade(move |socket| handle_playback_socket(socket, user_id, device, state))
+}
+
+async fn send_json(sender: &mut futures::stream::SplitSink<WebSocket, Message>, message: &ServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(json) => sender.send(Message::Text(json.into())).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+async fn handle_playback_socket(socket: WebSocket, user_id: i32, mut device: DeviceInfo, state: AppState) {
+    let hub = state.playback_hub.clone();
+    let (mut sender, mut receiver) = socket.split();
+
+    // Subscribe before taking the snapshot so nothing published in between is missed
+    let mut updates = hub.subscribe();
+    if let Err(e) = hub.join(user_id, &mut device).await {
+        tracing::warn!("Failed to register playback device {} for user {}: {}", device.device_id, user_id, e);
+    }
+    match hub.snapshot(user_id).await {
+        Ok(snapshot) => {
+            if !send_json(&mut sender, &snapshot).await {
+                let _ = hub.leave(user_id, &device.device_id).await;
+                return;
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load playback state for user {}: {}", user_id, e),
+    }
+
+    let mut presence = tokio::time::interval(Duration::from_secs(PRESENCE_REFRESH_SECONDS));
+    presence.tick().await;
+
+    loop {
+        tokio::select! {
+            incoming = receiver.next() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                if text.as_str() == "ping" {
+                    continue;
+                }
+                let message = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        tracing::debug!("Ignoring malformed playback message from {}: {}", device.device_id, e);
+                        continue;
+                    }
+                };
+                if let Some(reply) = handle_client_message(&state, user_id, &device, message).await {
+                    if !send_json(&mut sender, &reply).await {
+                        break;
+                    }
+                }
+            }
+            update = updates.recv() => {
+                match update {
+                    Ok(envelope) => {
+                        if envelope.is_for(user_id, &device.device_id)
+                            && !send_json(&mut sender, &envelope.message).await
+                        {
+                            break;
+                        }
+                    }
+                    // Fell behind: replace whatever was dropped with the current picture
+                    Err(RecvError::Lagged(_)) => {
+                        if let Ok(snapshot) = hub.snapshot(user_id).await {
+                            if !send_json(&mut sender, &snapshot).await {
+                                break;
+                            }
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            _ = presence.tick() => {
+                if let Err(e) = hub.touch(user_id, &mut device).await {
+                    tracing::warn!("Failed to refresh playback device {}: {}", device.device_id, e);
+                }
+            }
+        }
+    }
+
+    if let Err(e) = hub.leave(user_id, &device.device_id).await {
+        tracing::warn!("Failed to unregister playback device {}: {}", device.device_id, e);
+    }
+}
+
+/// Act on one client message; returns a reply for the sender, if any.
+async fn handle_client_message(
+    state: &AppState,
+    user_id: i32,
+    device: &DeviceInfo,
+    message: ClientMessage,
+) -> Option<ServerMessage> {
+    let hub = &state.playback_hub;
+    match message {
+        ClientMessage::State(report) => {
+            match hub.report_state(user_id, device, report).await {
+                Ok(Some(stored)) => {
+                    // A pause is where another device picks up, so keep it even if the periodic
+                    // listen-duration report hasn't run yet
+                    if let (Some(episode_id), false) = (stored.episode_id, stored.playing) {
+                        let result = if stored.is_youtube {
+                            state.db_pool.record_youtube_listen_duration(episode_id, user_id, stored.position).await
+                        } else {
+                            state.db_pool.record_listen_duration(episode_id, user_id, stored.position).await
+                        };
+                        if let Err(e) = result {
+                            tracing::warn!("Failed to save paused position for episode {}: {}", episode_id, e);
+                        }
+                    }
+                    None
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::warn!("Failed to store playback state for user {}: {}", user_id, e);
+                    None
+                }
+            }
+        }
+        ClientMessage::Command { target, action, position } => {
+            if action == RemoteAction::Seek && position.is_none() {
+                return None;
+            }
+            hub.send_command(user_id, device, target, action, position).await;
+            None
+        }
+        ClientMessage::Resync => hub.snapshot(user_id).await.ok(),
+        ClientMessage::Ping => None,
+    }
+}
@@ -656,6 +656,7 @@ pub async fn queue_episode(
 
     // Queue the episode
     state.db_pool.queue_episode(request.episode_id, request.user_id, request.is_youtube).await?;
+    state.playback_hub.queue_changed(request.user_id).await;
     
     let message = if request.is_youtube {
         "Video queued successfully"
@@ -701,6 +702,7 @@ pub async fn remove_queued_episode(
 
     // Remove the episode from queue
     state.db_pool.remove_queued_episode(request.episode_id, request.user_id, request.is_youtube).await?;
+    state.playback_hub.queue_changed(request.user_id).await;
     
     Ok(Json(crate::models::QueueResponse {
         data: "Successfully Removed Episode From Queue".to_string(),
@@ -733,6 +735,7 @@ pub async fn clear_all_queue(
         return Err(AppError::forbidden("You can only clear your own queue!"));
     }
     state.db_pool.clear_queue(request.user_id).await?;
+    state.playback_hub.queue_changed(request.user_id).await;
     Ok(Json(crate::models::QueueResponse {
         data: "Queue cleared successfully".to_string(),
     }))
@@ -813,6 +816,7 @@ pub async fn reorder_queue(
 
     // Reorder the queue
     state.db_pool.reorder_queue(query.user_id, request.episode_ids).await?;
+    state.playback_hub.queue_changed(query.user_id).await;
     
     Ok(Json(crate::models::ReorderQueueResponse {
         message: "Queue reordered successfully".to_string(),
@@ -1159,6 +1163,8 @@ pub struct DownloadPodcastRequest {
     pub episode_id: i32,
     pub user_id: i32,
     pub is_youtube: Option<bool>,
+    /// For YouTube videos, also keep the video file rather than audio only.
+    pub video: Option<bool>,
 }
 
 #[derive(Deserialize, utoipa::ToSchema)]
@@ -1222,7 +1228,7 @@ pub async fn download_podcast(
 
     // Queue the download task using the task system
     let task_id = if is_youtube {
-        state.task_spawner.spawn_download_youtube_video(request.episode_id, request.user_id).await?
+        state.task_spawner.spawn_download_youtube_video(request.episode_id, request.user_id, request.video.unwrap_or(false)).await?
     } else {
         state.task_spawner.spawn_download_podcast_episode(request.episode_id, request.user_id).await?
     };
@@ -1638,7 +1644,15 @@ pub async fn fetch_podcasting_2_data(
     let user_id = query.user_id;
     
     // Call the database method to fetch podcasting 2.0 data
-    let data = state.db_pool.fetch_podcasting_2_data(episode_id, user_id).await?;
+    let mut data = state.db_pool.fetch_podcasting_2_data(episode_id, user_id).await?;
+
+    // Chapter times must match the cut copy the stream endpoint serves, if any.
+    let cut_ranges = crate::services::audio_cut::served_cut_ranges(&state.db_pool, user_id, episode_id).await;
+    if !cut_ranges.is_empty() {
+        if let Some(chapters) = data.get("chapters") {
+            data["chapters"] = crate::services::audio_cut::remap_chapters(chapters, &cut_ranges);
+        }
+    }
     
     Ok(Json(data))
 }
@@ -2235,6 +2249,9 @@ pub async fn mark_episode_completed(
             request.user_id,
             request.is_youtube.unwrap_or(false)
         ).await?;
+        if !request.is_youtube.unwrap_or(false) {
+            crate::services::scrobble::record_listen(&state, request.user_id, request.episode_id, true);
+        }
         
         Ok(Json(serde_json::json!({ "detail": "Episode marked as completed." })))
     } else {
@@ -2520,6 +2537,68 @@ pub async fn search_data(
     Ok(Json(SearchDataResponse { data: result, total }))
 }
 
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct FullTextSearchParams {
+    pub user_id: i32,
+    /// Search query. Supports "quoted phrases" and -excluded terms.
+    pub q: String,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct FullTextSearchResponse {
+    pub data: Vec<crate::services::search::SearchHit>,
+    pub total: i64,
+}
+
+// Relevance-ranked full-text search over episode titles, descriptions and generated
+// transcripts, with highlighted snippets and transcript timestamps to seek playback to
+#[utoipa::path(
+    get,
+    path = "/search_fulltext",
+    tag = "podcasts",
+    summary = "Full-text search episodes and transcripts",
+    params(FullTextSearchParams),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = FullTextSearchResponse),
+        (status = 400, description = "Empty search query"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "API key does not belong to the requested user"),
+    ),
+)]
+pub async fn full_text_search(
+    Query(params): Query<FullTextSearchParams>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<FullTextSearchResponse>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    if !check_user_access(&state, &api_key, params.user_id).await? {
+        return Err(AppError::forbidden("You can only search your own podcasts!"));
+    }
+
+    let query = params.q.trim();
+    if query.is_empty() {
+        return Err(AppError::bad_request("Search query must not be empty"));
+    }
+
+    let limit  = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let (data, total) = crate::services::search::search_episodes(
+        &state.db_pool,
+        params.user_id,
+        query,
+        limit,
+        offset,
+    ).await?;
+
+    Ok(Json(FullTextSearchResponse { data, total }))
+}
+
 // Request for fetch_transcript - proxy to avoid CORS issues
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct FetchTranscriptRequest {
@@ -2785,6 +2864,11 @@ pub async fn record_listen_duration(
         }
     }
 
+    // Queues a scrobble once the listen qualifies (long enough, or just auto-completed above)
+    if !data.is_youtube {
+        crate::services::scrobble::record_listen(&state, data.user_id, data.episode_id, false);
+    }
+
     Ok(Json(serde_json::json!({ "detail": "Listen duration recorded." })))
 }
 
@@ -3173,6 +3257,8 @@ pub struct StreamQuery {
     pub user_id: i32,
     #[serde(rename = "type")]
     pub source_type: Option<String>,
+    /// Transcode profile: `opus48`, `aac64` or `original` (the default).
+    pub profile: Option<String>,
 }
 
 // Stream episode - matches Python stream_episode function exactly
@@ -3185,6 +3271,9 @@ pub struct StreamQuery {
     security(("api_key" = [])),
     responses(
         (status = 200, description = "Audio/media stream", content_type = "application/octet-stream"),
+        (status = 206, description = "Requested byte range of the stream"),
+        (status = 202, description = "The requested transcode or joined audiobook is being rendered; retry after the Retry-After delay"),
+        (status = 400, description = "Unknown transcode profile"),
         (status = 401, description = "Invalid or missing API key"),
     ),
 )]
@@ -3192,9 +3281,12 @@ pub async fn stream_episode(
     State(state): State<crate::AppState>,
     Path(episode_id): Path<i32>,
     Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
 ) -> Result<axum::response::Response, AppError> {
     let api_key = &query.api_key;
     info!("Stream request for episode {} with api_key {} and user_id {}", episode_id, api_key, query.user_id);
+    let profile = crate::services::transcode::Profile::parse(query.profile.as_deref().unwrap_or(""))
+        .map_err(AppError::bad_request)?;
 
     // Try RSS key validation FIRST (RSS keys are used in RSS feeds for streaming)
     let mut is_valid = false;
@@ -3261,6 +3353,42 @@ pub async fn stream_episode(
         state.db_pool.get_download_location(episode_id, query.user_id).await?
     };
 
+    // Audiobook-mode local podcasts are one episode over several files: serve them joined.
+    if query.source_type.as_deref() != Some("youtube") {
+        use crate::services::local_books::{joined_media, JoinedMedia};
+        use axum::response::IntoResponse;
+        match joined_media(state.task_spawner.jobs(), &state.db_pool, query.user_id, episode_id).await? {
+            Some(JoinedMedia::Ready(joined)) => file_path = Some(joined.to_string_lossy().to_string()),
+            // Serving one part would record progress against the wrong timeline; ask to retry.
+            Some(JoinedMedia::Rendering) => {
+                return Ok((
+                    axum::http::StatusCode::ACCEPTED,
+                    [(axum::http::header::RETRY_AFTER, "30")],
+                    "Joining audiobook parts, retry shortly",
+                )
+                    .into_response());
+            }
+            None => {}
+        }
+    }
+
+    // Server downloads of podcasts with ServeCutAudio get their skipped segments cut out.
+    if query.source_type.as_deref() != Some("youtube") {
+        if let Some(path) = file_path.as_deref() {
+            use crate::services::audio_cut::{cut_or_queue, CutMedia};
+            let source = std::path::Path::new(path);
+            match cut_or_queue(state.task_spawner.jobs(), &state.db_pool, query.user_id, episode_id, source).await {
+                Ok(Some(CutMedia::Ready(cut))) => file_path = Some(cut.to_string_lossy().to_string()),
+                // Podcast apps save whatever they get as the episode, so serve the uncut original
+                // while the copy renders rather than a retry response.
+                Ok(Some(CutMedia::Rendering)) => debug!("Cut copy of episode {} is rendering, serving the original", episode_id),
+                Ok(None) => {}
+                // Serving the uncut original beats failing playback.
+                Err(e) => warn!("Failed to queue cut copy of episode {}: {}", episode_id, e),
+            }
+        }
+    }
+
     // Fall back to local-media episodes, which are not recorded in DownloadedEpisodes.
     // Their episode URL is a local:// pseudo-URL pointing under /opt/pinepods/local-media.
     if file_path.is_none() {
@@ -3276,15 +3404,55 @@ pub async fn stream_episode(
 
     if let Some(path) = file_path {
         debug!("Found file at: {}", path);
+
+        // Transcoded renditions are cached as whole files, so they are served (and seek) the
+        // same way as the original. A client that asked for a smaller rendition is told to retry
+        // while it renders rather than being handed the full-size original.
+        let (path, content_type) = match profile {
+            Some(profile) => {
+                match crate::services::transcode::cached_or_queue(
+                    state.task_spawner.jobs(),
+                    query.user_id,
+                    std::path::Path::new(&path),
+                    profile,
+                )
+                .await?
+                {
+                    Some(transcoded) => (transcoded.to_string_lossy().to_string(), Some(profile.content_type())),
+                    None => {
+                        use axum::response::IntoResponse;
+                        return Ok((
+                            axum::http::StatusCode::ACCEPTED,
+                            [(axum::http::header::RETRY_AFTER, "30")],
+                            "Transcoding, retry shortly",
+                        )
+                            .into_response());
+                    }
+                }
+            }
+            None => (path, None),
+        };
         
         // Use tower_http's ServeFile for proper file serving with range support
         use tower_http::services::ServeFile;
         use tower::ServiceExt;
         
         let service = ServeFile::new(&path);
-        let request = axum::http::Request::builder()
+        // Forward the client's range and conditional headers so players can seek and resume.
+        let mut request = axum::http::Request::builder()
             .method("GET")
-            .uri("/")
+            .uri("/");
+        for name in [
+            axum::http::header::RANGE,
+            axum::http::header::IF_RANGE,
+            axum::http::header::IF_MODIFIED_SINCE,
+            axum::http::header::IF_UNMODIFIED_SINCE,
+        ] {
+            if let Some(value) = headers.get(&name) {
+                request = request.header(name, value);
+            }
+        }
+        let request = request
             .body(axum::body::Body::empty())
             .map_err(|e| AppError::external_error(&format!("Failed to build request: {}", e)))?;
             
@@ -3292,7 +3460,10 @@ pub async fn stream_episode(
             .map_err(|e| AppError::external_error(&format!("Failed to serve file: {}", e)))?;
             
         // Convert the response body to the expected type
-        let (parts, body) = response.into_parts();
+        let (mut parts, body) = response.into_parts();
+        if let Some(content_type) = content_type {
+            parts.headers.insert(axum::http::header::CONTENT_TYPE, axum::http::HeaderValue::from_static(content_type));
+        }
         let body = axum::body::Body::new(body);
         let response = axum::response::Response::from_parts(parts, body);
             
@@ -3302,6 +3473,56 @@ pub async fn stream_episode(
     }
 }
 
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct StreamChaptersQuery {
+    pub api_key: String,
+    pub user_id: i32,
+}
+
+// Podcasting 2.0 chapters for what /stream serves, linked from the generated RSS feeds
+#[utoipa::path(
+    get,
+    path = "/stream/{episode_id}/chapters",
+    tag = "podcasts",
+    summary = "Stream chapters",
+    description = "Chapters JSON for the audio the stream endpoint serves this user, remapped onto the cut copy when the podcast serves cut audio. Accepts the user's RSS key.",
+    params(StreamChaptersQuery, ("episode_id" = i32, Path)),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Podcasting 2.0 chapters document", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Key does not belong to this user"),
+    ),
+)]
+pub async fn stream_chapters(
+    State(state): State<crate::AppState>,
+    Path(episode_id): Path<i32>,
+    Query(query): Query<StreamChaptersQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    match state.db_pool.get_rss_key_if_valid(&query.api_key, None).await? {
+        Some(rss_info) if rss_info.user_id == query.user_id => {}
+        Some(_) => return Err(AppError::forbidden("You do not have permission to access this episode")),
+        None => {
+            if !validate_api_key(&state, &query.api_key).await? {
+                return Err(AppError::unauthorized("Invalid API key or RSS key"));
+            }
+            let key_user_id = state.db_pool.get_user_id_from_api_key(&query.api_key).await?;
+            if key_user_id != query.user_id && !state.db_pool.is_web_key(&query.api_key).await? {
+                return Err(AppError::forbidden("You do not have permission to access this episode"));
+            }
+        }
+    }
+
+    let data = state.db_pool.fetch_podcasting_2_data(episode_id, query.user_id).await?;
+    let mut chapters = data.get("chapters").cloned().unwrap_or_else(|| serde_json::json!([]));
+    let cut_ranges = crate::services::audio_cut::served_cut_ranges(&state.db_pool, query.user_id, episode_id).await;
+    if !cut_ranges.is_empty() {
+        chapters = crate::services::audio_cut::remap_chapters(&chapters, &cut_ranges);
+    }
+
+    Ok(Json(serde_json::json!({ "version": "1.2.0", "chapters": chapters })))
+}
+
 // Get RSS key endpoint - get or create RSS key for user
 #[utoipa::path(
     get,
@@ -7,7 +7,7 @@ use futures::{sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::RwLock;
 use sqlx::Row;
 use tracing::{debug, info, warn};
 
@@ -48,15 +48,14 @@ pub enum RefreshMessage {
     Error { detail: String },
 }
 
-// Store locks per user to prevent concurrent refresh jobs
-type UserLocks = Arc<RwLock<HashMap<i32, Arc<Mutex<()>>>>>;
+// Lease on a user's refresh lock; renewed while the refresh runs
+const REFRESH_LOCK_TTL_SECONDS: u64 = 60;
 
 // Store active WebSocket connections
 type ActiveWebSockets = Arc<RwLock<HashMap<i32, Vec<tokio::sync::mpsc::Sender<RefreshMessage>>>>>;
 
 // Global state for refresh management
 lazy_static::lazy_static! {
-    static ref USER_LOCKS: UserLocks = Arc::new(RwLock::new(HashMap::new()));
     static ref ACTIVE_WEBSOCKETS: ActiveWebSockets = Arc::new(RwLock::new(HashMap::new()));
 }
 
@@ -75,18 +74,17 @@ pub async fn refresh_pods_admin(
     State(state): State<AppState>,
 ) -> Result<axum::Json<serde_json::Value>, AppError> {
     info!("Starting admin refresh process - background task (no WebSocket)");
-    
-    // This is the background task version - NO WebSocket, just direct refresh like Python
-    let state_clone = state.clone();
-    tokio::spawn(async move {
-        // This matches the Python refresh_pods function exactly
-        if let Err(e) = refresh_all_podcasts_background(&state_clone).await {
-            tracing::error!("Background refresh failed: {}", e);
-        }
-    });
+
+    // Queued on the feed refresh worker pool; joins the pending refresh if one is already queued
+    let task_id = state
+        .task_spawner
+        .jobs()
+        .enqueue(0, crate::services::job_queue::Job::RefreshFeeds)
+        .await?;
 
     Ok(axum::Json(serde_json::json!({
-        "detail": "Refresh initiated."
+        "detail": "Refresh initiated.",
+        "task_id": task_id
     })))
 }
 
@@ -192,6 +190,7 @@ struct PodcastRefreshItem {
     username: Option<String>,
     password: Option<String>,
     is_youtube: bool,
+    source_kind: String,
     user_id: i32,
     feed_cutoff: Option<i32>,
     etag: Option<String>,
@@ -216,7 +215,7 @@ async fn queue_auto_downloads(
     for episode in new_episodes {
         let is_yt = episode.episodeurl.contains("youtube.com") || episode.episodeurl.contains("youtu.be");
         let task_result = if is_yt {
-            state.task_spawner.spawn_download_youtube_video(episode.episodeid, user_id).await
+            state.task_spawner.spawn_download_youtube_video(episode.episodeid, user_id, false).await
         } else {
             state.task_spawner.spawn_download_podcast_episode(episode.episodeid, user_id).await
         };
@@ -243,16 +242,23 @@ async fn queue_auto_new_episodes(
     }
     let mut ordered: Vec<&crate::handlers::podcasts::Episode> = new_episodes.iter().collect();
     ordered.sort_by(|a, b| a.episodepubdate.cmp(&b.episodepubdate));
+    let mut queued = false;
     for episode in ordered {
         match state
             .db_pool
             .queue_episode(episode.episodeid, user_id, episode.is_youtube)
             .await
         {
-            Ok(()) => debug!("Auto-queued episode {} for user {}", episode.episodeid, user_id),
+            Ok(()) => {
+                debug!("Auto-queued episode {} for user {}", episode.episodeid, user_id);
+                queued = true;
+            }
             Err(e) => warn!("Failed to auto-queue episode {}: {}", episode.episodeid, e),
         }
     }
+    if queued {
+        state.playback_hub.queue_changed(user_id).await;
+    }
 }
 
 /// Refresh one feed that may be shared by several subscriber podcasts (cross-user dedup). The feed
@@ -262,6 +268,7 @@ async fn refresh_feed_group(state: &AppState, group: &[PodcastRefreshItem]) -> u
     let rep = &group[0];
     let ids: Vec<i32> = group.iter().map(|i| i.podcast_id).collect();
     let mut total_new = 0usize;
+    let started = std::time::Instant::now();
 
     // Reuse any stored validator from the group (they converge after the first unified cycle).
     let etag = group.iter().find_map(|i| i.etag.clone());
@@ -314,6 +321,12 @@ async fn refresh_feed_group(state: &AppState, group: &[PodcastRefreshItem]) -> u
                         .store_feed_cache_validators(&ids, etag.as_deref(), last_modified.as_deref())
                         .await;
                     let _ = state.db_pool.record_refresh_success(&ids).await;
+                    // Keep the feed's WebSub subscription in step with the hub it advertises.
+                    // Hubs can't fetch authenticated feeds, so those always stay on polling.
+                    if rep.username.is_none() {
+                        let links = crate::services::websub::discover(&body);
+                        crate::services::websub::sync_subscription(&state.db_pool, &rep.feed_url, links).await;
+                    }
                 }
                 Err(e) => {
                     warn!("Error parsing feed {}: {}", rep.feed_url, e);
@@ -327,30 +340,26 @@ async fn refresh_feed_group(state: &AppState, group: &[PodcastRefreshItem]) -> u
         }
     }
 
+    crate::services::metrics::observe_feed_refresh_duration(started.elapsed().as_secs_f64());
     total_new
 }
 
-/// Refresh a single YouTube channel podcast.
+/// Refresh a single video podcast: a YouTube channel/playlist, or a playlist or channel on
+/// another site yt-dlp can list.
 async fn refresh_youtube_item(state: &AppState, item: &PodcastRefreshItem) {
-    let channel_id = if item.feed_url.contains("channel/") {
-        item.feed_url
-            .split("channel/")
-            .nth(1)
-            .unwrap_or(&item.feed_url)
-            .split('/')
-            .next()
-            .unwrap_or(&item.feed_url)
-            .split('?')
-            .next()
-            .unwrap_or(&item.feed_url)
-            .to_string()
-    } else {
-        item.feed_url.clone()
+    if item.source_kind == crate::services::media_source::SOURCE_KIND {
+        refresh_media_source_item(state, item).await;
+        return;
+    }
+    let Some(source) = crate::services::youtube_source::YouTubeSource::parse(&item.feed_url) else {
+        warn!("Podcast {} has an unrecognised YouTube feed URL: {}", item.podcast_id, item.feed_url);
+        let _ = state.db_pool.record_refresh_failure(&[item.podcast_id], "Unrecognised YouTube feed URL").await;
+        return;
     };
     let ids = [item.podcast_id];
     match crate::handlers::youtube::process_youtube_channel(
         item.podcast_id,
-        &channel_id,
+        &source,
         item.feed_cutoff.unwrap_or(30),
         state,
     )
@@ -367,45 +376,73 @@ async fn refresh_youtube_item(state: &AppState, item: &PodcastRefreshItem) {
     }
 }
 
-// Background refresh function that matches Python refresh_pods exactly - NO WebSocket
-async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
-    info!("Running refresh");
-    
-    // Get ALL podcasts from ALL users - matches Python exactly
-    // Handle the different database types properly
-    let total_podcasts = match &state.db_pool {
-        crate::database::DatabasePool::Postgres(pool) => {
-            let count_row = sqlx::query(r#"SELECT COUNT(*) as total FROM "Podcasts""#)
-                .fetch_one(pool)
-                .await?;
-            count_row.try_get::<i64, _>("total")? as usize
+/// Refresh a single podcast that follows a non-YouTube site through yt-dlp.
+async fn refresh_media_source_item(state: &AppState, item: &PodcastRefreshItem) {
+    let ids = [item.podcast_id];
+    match crate::handlers::media_source::process_media_source(
+        item.podcast_id,
+        &item.feed_url,
+        item.feed_cutoff.unwrap_or(30),
+        state,
+    )
+    .await
+    {
+        Ok(_) => {
+            info!("Successfully refreshed media source {}", item.podcast_id);
+            let _ = state.db_pool.record_refresh_success(&ids).await;
         }
-        crate::database::DatabasePool::MySQL(pool) => {
-            let count_row = sqlx::query("SELECT COUNT(*) as total FROM Podcasts")
-                .fetch_one(pool)
-                .await?;
-            count_row.try_get::<i64, _>("total")? as usize
+        Err(e) => {
+            warn!("Error refreshing media source {}: {}", item.podcast_id, e);
+            let _ = state.db_pool.record_refresh_failure(&ids, &e.to_string()).await;
         }
-    };
-    
-    // Collect podcast rows into owned structs, skipping feeds that are in failure backoff. The
-    // backoff window grows with the consecutive-failure count (linear, capped at 24h) and is
+    }
+}
+
+/// Which podcast rows `load_refresh_items` returns.
+enum RefreshSelection<'a> {
+    /// The scheduled poll: every refreshable podcast, minus feeds in failure backoff and feeds
+    /// with a live WebSub lease (those still get a safety poll once a day).
+    Scheduled,
+    /// Every refreshable podcast subscribed to one feed URL (a WebSub push for that feed).
+    Feed(&'a str),
+}
+
+async fn load_refresh_items(
+    state: &AppState,
+    selection: RefreshSelection<'_>,
+    capacity: usize,
+) -> AppResult<Vec<PodcastRefreshItem>> {
+    // The backoff window grows with the consecutive-failure count (linear, capped at 24h) and is
     // computed in SQL so we don't have to read/normalize timestamps across Postgres/MySQL.
-    let mut refresh_items: Vec<PodcastRefreshItem> = Vec::with_capacity(total_podcasts);
+    let mut refresh_items: Vec<PodcastRefreshItem> = Vec::with_capacity(capacity);
 
     match &state.db_pool {
         crate::database::DatabasePool::Postgres(pool) => {
-            let rows = sqlx::query(
+            let filter = match selection {
+                RefreshSelection::Scheduled => {
+                    r#"AND (COALESCE(consecutivefailures, 0) = 0
+                            OR lastrefreshattempt IS NULL
+                            OR lastrefreshattempt < NOW() - (INTERVAL '1 minute' * (30 * LEAST(COALESCE(consecutivefailures, 0), 48))))
+                       AND NOT EXISTS (
+                            SELECT 1 FROM "WebSubSubscriptions" w
+                            WHERE w.feedurl = "Podcasts".feedurl AND w.status = 'verified' AND w.expiresat > NOW()
+                              AND "Podcasts".lastrefreshsuccess > NOW() - INTERVAL '1 day')"#
+                }
+                RefreshSelection::Feed(_) => "AND feedurl = $1",
+            };
+            let sql = format!(
                 r#"SELECT podcastid, feedurl, artworkurl, autodownload, autoqueue, username, password,
-                          isyoutubechannel, userid, feedcutoffdays, feedetag, feedlastmodified
+                          isyoutubechannel, sourcekind, userid, feedcutoffdays, feedetag, feedlastmodified
                    FROM "Podcasts"
                    WHERE COALESCE(refreshpodcast, TRUE) = TRUE
-                     AND (COALESCE(consecutivefailures, 0) = 0
-                          OR lastrefreshattempt IS NULL
-                          OR lastrefreshattempt < NOW() - (INTERVAL '1 minute' * (30 * LEAST(COALESCE(consecutivefailures, 0), 48))))"#
-            )
-            .fetch_all(pool)
-            .await?;
+                     {}"#,
+                filter
+            );
+            let mut query = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()));
+            if let RefreshSelection::Feed(feed_url) = selection {
+                query = query.bind(feed_url);
+            }
+            let rows = query.fetch_all(pool).await?;
 
             for result in rows {
                 let podcast_id: i32 = result.try_get("podcastid")?;
@@ -418,6 +455,7 @@ async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
                     username: result.try_get("username").ok(),
                     password: result.try_get("password").ok(),
                     is_youtube: result.try_get("isyoutubechannel")?,
+                    source_kind: result.try_get("sourcekind")?,
                     user_id: result.try_get("userid")?,
                     feed_cutoff: result.try_get("feedcutoffdays").ok(),
                     etag: result.try_get::<Option<String>, _>("feedetag").ok().flatten(),
@@ -426,17 +464,31 @@ async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
             }
         }
         crate::database::DatabasePool::MySQL(pool) => {
-            let rows = sqlx::query(
+            let filter = match selection {
+                RefreshSelection::Scheduled => {
+                    "AND (COALESCE(ConsecutiveFailures, 0) = 0
+                          OR LastRefreshAttempt IS NULL
+                          OR LastRefreshAttempt < NOW() - INTERVAL (30 * LEAST(COALESCE(ConsecutiveFailures, 0), 48)) MINUTE)
+                     AND NOT EXISTS (
+                          SELECT 1 FROM WebSubSubscriptions w
+                          WHERE w.FeedURL = Podcasts.FeedURL AND w.Status = 'verified' AND w.ExpiresAt > NOW()
+                            AND Podcasts.LastRefreshSuccess > NOW() - INTERVAL 1 DAY)"
+                }
+                RefreshSelection::Feed(_) => "AND FeedURL = ?",
+            };
+            let sql = format!(
                 "SELECT PodcastID, FeedURL, ArtworkURL, AutoDownload, AutoQueue, Username, Password,
-                        IsYouTubeChannel, UserID, FeedCutoffDays, FeedETag, FeedLastModified
+                        IsYouTubeChannel, SourceKind, UserID, FeedCutoffDays, FeedETag, FeedLastModified
                  FROM Podcasts
                  WHERE COALESCE(RefreshPodcast, 1) = 1
-                   AND (COALESCE(ConsecutiveFailures, 0) = 0
-                        OR LastRefreshAttempt IS NULL
-                        OR LastRefreshAttempt < NOW() - INTERVAL (30 * LEAST(COALESCE(ConsecutiveFailures, 0), 48)) MINUTE)"
-            )
-            .fetch_all(pool)
-            .await?;
+                   {}",
+                filter
+            );
+            let mut query = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()));
+            if let RefreshSelection::Feed(feed_url) = selection {
+                query = query.bind(feed_url);
+            }
+            let rows = query.fetch_all(pool).await?;
 
             for result in rows {
                 let podcast_id: i32 = result.try_get("PodcastID")?;
@@ -449,6 +501,7 @@ async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
                     username: result.try_get("Username").ok(),
                     password: result.try_get("Password").ok(),
                     is_youtube: result.try_get("IsYouTubeChannel")?,
+                    source_kind: result.try_get("SourceKind")?,
                     user_id: result.try_get("UserID")?,
                     feed_cutoff: result.try_get("FeedCutoffDays").ok(),
                     etag: result.try_get::<Option<String>, _>("FeedETag").ok().flatten(),
@@ -458,8 +511,56 @@ async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
         }
     }
 
-    // Partition into YouTube items (refreshed per-podcast) and RSS feeds grouped by
-    // (feed_url, username, password) so a feed subscribed by many users is fetched + parsed ONCE.
+    Ok(refresh_items)
+}
+
+/// Refresh every subscription to a single RSS feed (triggered by a WebSub push). Subscribers are
+/// grouped by credentials exactly like the scheduled refresh, and each group goes through
+/// `refresh_feed_group`. Returns the number of new episodes inserted.
+pub async fn refresh_feed_url(state: &AppState, feed_url: &str) -> AppResult<usize> {
+    let mut groups: HashMap<(Option<String>, Option<String>), Vec<PodcastRefreshItem>> = HashMap::new();
+    for item in load_refresh_items(state, RefreshSelection::Feed(feed_url), 8).await? {
+        if item.is_youtube {
+            continue;
+        }
+        groups.entry((item.username.clone(), item.password.clone())).or_default().push(item);
+    }
+
+    let mut total_new = 0;
+    for group in groups.into_values() {
+        total_new += refresh_feed_group(state, &group).await;
+    }
+    Ok(total_new)
+}
+
+// Background refresh function that matches Python refresh_pods exactly - NO WebSocket
+async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
+    info!("Running refresh");
+    
+    // Get ALL podcasts from ALL users - matches Python exactly
+    // Handle the different database types properly
+    let total_podcasts = match &state.db_pool {
+        crate::database::DatabasePool::Postgres(pool) => {
+            let count_row = sqlx::query(r#"SELECT COUNT(*) as total FROM "Podcasts""#)
+                .fetch_one(pool)
+                .await?;
+            count_row.try_get::<i64, _>("total")? as usize
+        }
+        crate::database::DatabasePool::MySQL(pool) => {
+            let count_row = sqlx::query("SELECT COUNT(*) as total FROM Podcasts")
+                .fetch_one(pool)
+                .await?;
+            count_row.try_get::<i64, _>("total")? as usize
+        }
+    };
+    
+    // Collect podcast rows into owned structs, skipping feeds that are in failure backoff and
+    // feeds kept current by WebSub push.
+    let refresh_items = load_refresh_items(state, RefreshSelection::Scheduled, total_podcasts).await?;
+
+    // Partition into video items (YouTube and other yt-dlp sources, refreshed per-podcast) and RSS
+    // feeds grouped by (feed_url, username, password) so a feed subscribed by many users is
+    // fetched + parsed ONCE.
     use std::collections::HashMap;
     let mut youtube_items: Vec<PodcastRefreshItem> = Vec::new();
     let mut feed_groups: HashMap<(String, Option<String>, Option<String>), Vec<PodcastRefreshItem>> = HashMap::new();
@@ -483,7 +584,7 @@ async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
     let groups: Vec<Vec<PodcastRefreshItem>> = feed_groups.into_values().collect();
     let subscription_count: usize = groups.iter().map(|g| g.len()).sum();
     info!(
-        "Running refresh: {} unique RSS feeds ({} subscriptions), {} YouTube channels (concurrency={})",
+        "Running refresh: {} unique RSS feeds ({} subscriptions), {} YouTube and yt-dlp sources (concurrency={})",
         groups.len(),
         subscription_count,
         youtube_items.len(),
@@ -495,7 +596,7 @@ async fn refresh_all_podcasts_background(state: &AppState) -> AppResult<()> {
         })
         .await;
 
-    // Refresh YouTube channels with bounded concurrency.
+    // Refresh YouTube channels and other yt-dlp sources with bounded concurrency.
     stream::iter(youtube_items)
         .for_each_concurrent(Some(REFRESH_CONCURRENCY), |item| async move {
             refresh_youtube_item(state, &item).await;
@@ -601,25 +702,25 @@ async fn handle_refresh_websocket(
     nextcloud_refresh: bool,
     state: AppState,
 ) {
-    // Check if refresh is already running for this user
+    // One refresh per user across all replicas; released when this handler returns
+    let _user_lock = match crate::services::cluster::Lock::try_acquire(
+        &state.redis_client,
+        &format!("refresh_user:{}", user_id),
+        REFRESH_LOCK_TTL_SECONDS,
+    )
+    .await
     {
-        let locks = USER_LOCKS.read().await;
-        if locks.contains_key(&user_id) {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
             let _ = send_error_and_close(socket, "Refresh job already running for this user.").await;
             return;
         }
-    }
-
-    // Create user lock
-    let user_lock = {
-        let mut locks = USER_LOCKS.write().await;
-        let lock = Arc::new(Mutex::new(()));
-        locks.insert(user_id, lock.clone());
-        lock
+        Err(e) => {
+            let _ = send_error_and_close(socket, &format!("Could not start refresh: {}", e)).await;
+            return;
+        }
     };
 
-    let _guard = user_lock.lock().await;
-
     let (mut sender, mut receiver) = socket.split();
     let (tx, mut rx) = tokio::sync::mpsc::channel::<RefreshMessage>(100);
 
@@ -680,11 +781,6 @@ async fn handle_refresh_websocket(
     }
 
     // Cleanup
-    {
-        let mut locks = USER_LOCKS.write().await;
-        locks.remove(&user_id);
-    }
-
     {
         let mut connections = ACTIVE_WEBSOCKETS.write().await;
         if let Some(user_connections) = connections.get_mut(&user_id) {
@@ -865,6 +961,7 @@ pub struct PodcastForRefresh {
     pub feed_url: String,
     pub artwork_url: Option<String>,
     pub is_youtube: bool,
+    pub source_kind: String,
     pub auto_download: bool,
     pub auto_queue: bool,
     pub username: Option<String>,
@@ -953,16 +1050,29 @@ async fn refresh_youtube_channel(
 ) -> AppResult<Vec<crate::handlers::podcasts::Episode>> {
     debug!("Refreshing YouTube channel: {}", podcast.name);
 
-    let channel_id = if podcast.feed_url.contains("channel/") {
-        podcast.feed_url.split("channel/").nth(1).unwrap_or(&podcast.feed_url).split('/').next().unwrap_or(&podcast.feed_url).split('?').next().unwrap_or(&podcast.feed_url)
-    } else {
-        &podcast.feed_url
-    };
-
     let ids = [podcast.id];
+    if podcast.source_kind == crate::services::media_source::SOURCE_KIND {
+        let cutoff = podcast.feed_cutoff_days.unwrap_or(30);
+        let result = crate::handlers::media_source::process_media_source(podcast.id, &podcast.feed_url, cutoff, state).await;
+        return match result {
+            Ok(_) => {
+                let _ = state.db_pool.record_refresh_success(&ids).await;
+                Ok(Vec::new())
+            }
+            Err(e) => {
+                warn!("Error refreshing media source {}: {}", podcast.name, e);
+                let _ = state.db_pool.record_refresh_failure(&ids, &e.to_string()).await;
+                Err(e)
+            }
+        };
+    }
+
+    let source = crate::services::youtube_source::YouTubeSource::parse(&podcast.feed_url)
+        .ok_or_else(|| AppError::bad_request(format!("Unrecognised YouTube feed URL: {}", podcast.feed_url)))?;
+
     match crate::handlers::youtube::process_youtube_channel(
         podcast.id,
-        channel_id,
+        &source,
         podcast.feed_cutoff_days.unwrap_or(30),
         state,
     ).await {
@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AppError,
+    handlers::{check_owner, ensure_urls_allowed},
+    services::scrobble::{self, ScrobbleTarget},
+    AppState,
+};
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ScrobbleTargetsQuery {
+    pub user_id: i32,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ScrobbleTargetsResponse {
+    pub targets: Vec<ScrobbleTarget>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/scrobble_targets",
+    tag = "scrobble",
+    summary = "List scrobble targets",
+    description = "Returns the user's scrobble targets with their delivery state and queued/failed listen counts. Tokens are never returned.",
+    params(ScrobbleTargetsQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = ScrobbleTargetsResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these targets"),
+    ),
+)]
+pub async fn get_scrobble_targets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ScrobbleTargetsQuery>,
+) -> Result<Json<ScrobbleTargetsResponse>, AppError> {
+    check_owner(&state, &headers, query.user_id, "scrobble targets").await?;
+    let targets = scrobble::list_targets(&state.db_pool, query.user_id).await?;
+    Ok(Json(ScrobbleTargetsResponse { targets }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct AddScrobbleTargetRequest {
+    pub user_id: i32,
+    /// `listenbrainz` or `webhook`.
+    pub kind: String,
+    /// ListenBrainz-compatible API root (defaults to api.listenbrainz.org), or the webhook URL.
+    pub endpoint: Option<String>,
+    /// ListenBrainz user token, or an optional bearer token for a webhook.
+    pub token: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/add_scrobble_target",
+    tag = "scrobble",
+    summary = "Add a scrobble target",
+    description = "Completed episodes, and listens past half the episode or four minutes, are submitted to ListenBrainz or POSTed to a webhook. The token is stored encrypted. Only admins may point a target at a private or loopback address.",
+    request_body = AddScrobbleTargetRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Unknown kind, missing token or URL, or a disallowed endpoint"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these targets"),
+    ),
+)]
+pub async fn add_scrobble_target(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AddScrobbleTargetRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let key_id = check_owner(&state, &headers, request.user_id, "scrobble targets").await?;
+
+    let endpoint = request.endpoint.as_deref().map(str::trim).filter(|e| !e.is_empty());
+    let token = request.token.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    match request.kind.as_str() {
+        scrobble::KIND_LISTENBRAINZ if token.is_none() => {
+            return Err(AppError::bad_request("A ListenBrainz user token is required"));
+        }
+        scrobble::KIND_LISTENBRAINZ => {}
+        scrobble::KIND_WEBHOOK if endpoint.is_none() => {
+            return Err(AppError::bad_request("A webhook URL is required"));
+        }
+        scrobble::KIND_WEBHOOK => {}
+        other => {
+            return Err(AppError::bad_request(format!(
+                "Unknown scrobble target kind '{}' (expected listenbrainz or webhook)",
+                other
+            )));
+        }
+    }
+
+    if let Some(url) = endpoint {
+        ensure_urls_allowed(&state, key_id, &[url]).await?;
+    }
+
+    let target_id = scrobble::add_target(&state.db_pool, request.user_id, &request.kind, endpoint, token).await?;
+    Ok(Json(serde_json::json!({ "detail": "Scrobble target added.", "target_id": target_id })))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RemoveScrobbleTargetRequest {
+    pub user_id: i32,
+    pub target_id: i32,
+}
+
+#[utoipa::path(
+    post,
+    path = "/remove_scrobble_target",
+    tag = "scrobble",
+    summary = "Remove a scrobble target",
+    description = "Deletes the target and any listens still queued for it.",
+    request_body = RemoveScrobbleTargetRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these targets"),
+        (status = 404, description = "No such target for this user"),
+    ),
+)]
+pub async fn remove_scrobble_target(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RemoveScrobbleTargetRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_owner(&state, &headers, request.user_id, "scrobble targets").await?;
+    if !scrobble::remove_target(&state.db_pool, request.user_id, request.target_id).await? {
+        return Err(AppError::not_found("Scrobble target not found"));
+    }
+    Ok(Json(serde_json::json!({ "detail": "Scrobble target removed." })))
+}
@@ -303,7 +303,10 @@ pub async fn add_user(
     }
 
     match state.db_pool.add_user(&user_values.fullname, &user_values.username.to_lowercase(), &user_values.email, &user_values.hash_pw).await {
-        Ok(user_id) => Ok(Json(serde_json::json!({ "detail": "Success", "user_id": user_id }))),
+        Ok(user_id) => {
+            crate::services::webhooks::emit_user_created(&state.db_pool, user_id, &user_values.username.to_lowercase(), &user_values.email, "admin");
+            Ok(Json(serde_json::json!({ "detail": "Success", "user_id": user_id })))
+        }
         Err(e) => {
             let error_msg = format!("{}", e);
             if error_msg.contains("username") && error_msg.contains("duplicate") {
@@ -342,7 +345,10 @@ pub async fn add_login_user(
     }
 
     match state.db_pool.add_user(&user_values.fullname, &user_values.username.to_lowercase(), &user_values.email, &user_values.hash_pw).await {
-        Ok(user_id) => Ok(Json(serde_json::json!({ "detail": "User added successfully", "user_id": user_id }))),
+        Ok(user_id) => {
+            crate::services::webhooks::emit_user_created(&state.db_pool, user_id, &user_values.username.to_lowercase(), &user_values.email, "self_service");
+            Ok(Json(serde_json::json!({ "detail": "User added successfully", "user_id": user_id })))
+        }
         Err(e) => {
             let error_msg = format!("{}", e);
             if error_msg.contains("username") && error_msg.contains("duplicate") {
@@ -1396,16 +1402,6 @@ pub async fn send_email_with_settings(
     settings: &EmailSettingsResponse,
     request: &SendEmailRequest,
 ) -> Result<String, AppError> {
-    use lettre::{
-        message::{header::ContentType, Message},
-        transport::smtp::{authentication::Credentials, client::Tls, client::TlsParameters},
-        AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
-    };
-    use tokio::time::{timeout, Duration};
-
-    // Read logo and create HTML content
-    let logo_base64 = read_logo_as_base64().await.unwrap_or_default();
-    
     // Check if this is a password reset email and format accordingly
     let (html_content, final_subject) = if request.subject.contains("Password Reset") {
         // Extract the reset code from the message
@@ -1434,16 +1430,36 @@ pub async fn send_email_with_settings(
         "#, request.subject, request.message.replace("\n", "<br>"));
         (content, request.subject.clone())
     };
-    
-    let html_body = create_html_email_template(&final_subject, &html_content, &logo_base64);
+
+    send_html_email(settings, &request.to_email, &final_subject, &html_content).await
+}
+
+/// Send already-formatted HTML content in the PinePods email layout. The subject is used as-is
+/// for the header and escaped for the page title.
+pub async fn send_html_email(
+    settings: &EmailSettingsResponse,
+    to_email: &str,
+    subject: &str,
+    html_content: &str,
+) -> Result<String, AppError> {
+    use lettre::{
+        message::{header::ContentType, Message},
+        transport::smtp::{authentication::Credentials, client::Tls, client::TlsParameters},
+        AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
+    };
+    use tokio::time::{timeout, Duration};
+
+    let logo_base64 = read_logo_as_base64().await.unwrap_or_default();
+    let title = crate::services::notification_channels::escape_html(subject);
+    let html_body = create_html_email_template(&title, html_content, &logo_base64);
 
     // Create email message with HTML
     let email = Message::builder()
         .from(settings.from_email.parse()
             .map_err(|_| AppError::bad_request("Invalid from email in settings"))?)
-        .to(request.to_email.parse()
+        .to(to_email.parse()
             .map_err(|_| AppError::bad_request("Invalid to email"))?)
-        .subject(&final_subject)
+        .subject(subject)
         .header(ContentType::TEXT_HTML)
         .body(html_body)
         .map_err(|e| AppError::internal(&format!("Failed to build email: {}", e)))?;
@@ -1547,6 +1563,12 @@ pub struct ApiInfo {
     pub lastfourdigits: String,
     pub created: String,
     pub podcastids: Vec<i32>,
+    pub name: Option<String>,
+    /// Comma-separated scope list; None means an unrestricted key.
+    pub scopes: Option<String>,
+    pub expiresat: Option<String>,
+    pub lastused: Option<String>,
+    pub revoked: bool,
 }
 
 // Get API info - matches Python api_get_api_info function exactly
@@ -1591,6 +1613,12 @@ pub struct CreateApiKeyRequest {
     pub user_id: i32,
     pub rssonly: bool,
     pub podcast_ids: Option<Vec<i32>>,
+    /// Display name for the key (e.g. "Home Assistant").
+    pub name: Option<String>,
+    /// Restrict the key to these scopes (read, playback, feed, admin). Omit for an unrestricted key.
+    pub scopes: Option<Vec<crate::services::api_scopes::ApiScope>>,
+    /// Key stops working this many days after creation. Omit for a key that never expires.
+    pub expires_in_days: Option<i32>,
 }
 
 // Create API key - matches Python api_create_api_key function exactly
@@ -1626,11 +1654,83 @@ pub async fn create_api_key(
         let new_key = state.db_pool.create_rss_key(request.user_id, request.podcast_ids).await?;
         Ok(Json(serde_json::json!({ "rss_key": new_key })))
     } else {
-        let new_key = state.db_pool.create_api_key(request.user_id).await?;
+        if let Some(days) = request.expires_in_days {
+            if days <= 0 {
+                return Err(AppError::bad_request("expires_in_days must be a positive number of days"));
+            }
+        }
+        let scopes = match &request.scopes {
+            Some(scopes) if scopes.is_empty() => {
+                return Err(AppError::bad_request("A scoped API key needs at least one scope"));
+            }
+            Some(scopes) => Some(crate::services::api_scopes::format_scopes(scopes)),
+            None => None,
+        };
+        let name = request.name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+        let new_key = state.db_pool
+            .create_scoped_api_key(request.user_id, name, scopes.as_deref(), request.expires_in_days)
+            .await?;
         Ok(Json(serde_json::json!({ "api_key": new_key })))
     }
 }
 
+// Request struct for revoke_api_key
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RevokeApiKeyRequest {
+    pub api_id: i32,
+}
+
+// Revoke an API key without deleting it, so it stays listed with its last-used time
+#[utoipa::path(
+    post,
+    path = "/revoke_api_key",
+    tag = "settings",
+    summary = "Revoke api key",
+    request_body = RevokeApiKeyRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not allowed to revoke this key"),
+        (status = 404, description = "API key not found"),
+    ),
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RevokeApiKeyRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    // Same ownership rules as delete_api_key
+    let requesting_user_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_requesting_user_admin = state.db_pool.user_admin_check(requesting_user_id).await?;
+    let api_key_owner = state.db_pool.get_api_key_owner(request.api_id).await?
+        .ok_or_else(|| AppError::not_found("API key not found"))?;
+
+    if !is_requesting_user_admin && requesting_user_id != api_key_owner {
+        return Err(AppError::forbidden("You are not authorized to access or remove other users api-keys."));
+    }
+    if state.db_pool.is_same_api_key(request.api_id, &api_key).await? {
+        return Err(AppError::forbidden("You cannot revoke the API key that is currently in use."));
+    }
+    if api_key_owner == 1 {
+        return Err(AppError::forbidden("Cannot revoke background task API key - would break refreshing."));
+    }
+    if state.db_pool.count_user_api_keys_excluding(api_key_owner, request.api_id).await? == 0 {
+        return Err(AppError::forbidden("Cannot revoke the final unrestricted API key - at least one is needed to maintain access."));
+    }
+
+    if let Some(revoked_key) = state.db_pool.revoke_api_key(request.api_id).await? {
+        if let Err(e) = state.redis_client.invalidate_api_key_cache(&revoked_key).await {
+            warn!("Failed to invalidate cache for revoked API key {}: {}", request.api_id, e);
+        }
+    }
+    Ok(Json(serde_json::json!({ "detail": "API key revoked." })))
+}
+
 // Request struct for delete_api_key
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct DeleteApiKeyRequest {
@@ -1706,8 +1806,14 @@ pub async fn delete_api_key(
         }
     }
 
-    // Proceed with deletion if the checks pass
+    // Proceed with deletion if the checks pass, dropping any cached validation of the key
+    let deleted_key = state.db_pool.get_api_key_by_id(api_id).await?;
     state.db_pool.delete_api_key(api_id).await?;
+    if let Some(deleted_key) = deleted_key {
+        if let Err(e) = state.redis_client.invalidate_api_key_cache(&deleted_key).await {
+            warn!("Failed to invalidate cache for deleted API key {}: {}", api_id, e);
+        }
+    }
     Ok(Json(serde_json::json!({ "detail": "API key deleted." })))
 }
 
@@ -1750,10 +1856,186 @@ pub async fn backup_user(
     Ok(opml_data)
 }
 
+// Versioned JSON export of everything the user owns, for moving to another server
+#[utoipa::path(
+    post,
+    path = "/export_user_data",
+    tag = "settings",
+    summary = "Export user data",
+    description = "Returns a versioned JSON document with the user's subscriptions (including feed credentials and per-podcast settings), listen history and positions, completed, saved and queued episodes, playlists and collections. Import it on another server with /import_user_data.",
+    request_body = BackupUserRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = crate::services::user_export::UserExport),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not your account"),
+    ),
+)]
+pub async fn export_user_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<BackupUserRequest>,
+) -> Result<Json<crate::services::user_export::UserExport>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let user_id_from_api_key = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if request.user_id != user_id_from_api_key && !is_web_key {
+        return Err(AppError::forbidden("You can only export your own data!"));
+    }
+
+    let export = crate::services::user_export::export_user(&state.db_pool, request.user_id).await?;
+    Ok(Json(export))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ImportUserDataRequest {
+    pub user_id: i32,
+    pub data: crate::services::user_export::UserExport,
+}
+
+#[utoipa::path(
+    post,
+    path = "/import_user_data",
+    tag = "settings",
+    summary = "Import user data",
+    description = "Starts a background task that replays a /export_user_data document onto this account: missing feeds are subscribed, episodes are matched by GUID and then enclosure URL, and history, queue, saved episodes, playlists and collections are merged in. Existing data is kept. Progress is reported through the task list.",
+    request_body = ImportUserDataRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Import started", body = serde_json::Value),
+        (status = 400, description = "Not a PinePods export, or a newer version than this server reads"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not your account"),
+    ),
+)]
+pub async fn import_user_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ImportUserDataRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let user_id_from_api_key = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if request.user_id != user_id_from_api_key && !is_web_key {
+        return Err(AppError::forbidden("You can only import into your own account!"));
+    }
+    crate::services::user_export::check_compatible(&request.data).map_err(AppError::bad_request)?;
+
+    let user_id = request.user_id;
+    let task_state = state.clone();
+    let task_id = state.task_spawner.spawn_progress_task(
+        "user_data_import".to_string(),
+        user_id,
+        move |reporter| async move {
+            let report = crate::services::user_export::import_user(&task_state, user_id, request.data, reporter).await?;
+            task_state.playback_hub.queue_changed(user_id).await;
+            Ok(serde_json::to_value(report)?)
+        },
+    ).await?;
+
+    Ok(Json(serde_json::json!({
+        "detail": "User data import started.",
+        "task_id": task_id
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/import_app_data",
+    tag = "settings",
+    summary = "Import from another podcast app",
+    description = "Starts a background import of subscriptions and listening state from another app. Multipart fields: `user_id`, `source` (`antennapod`, `pocketcasts` or `gpodder`), `file` (AntennaPod's database export, Pocket Casts' history JSON, or gpodder episode actions JSON) and, for Pocket Casts and gpodder, an optional `opml` with subscriptions. Played state, positions, favourites (as saved episodes) and queue order are merged into the account. Progress is reported through /import_progress.",
+    request_body(content = String, content_type = "multipart/form-data", description = "Export files"),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Import started", body = serde_json::Value),
+        (status = 400, description = "Unknown source, or a file that isn't that app's export"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not your account"),
+    ),
+)]
+pub async fn import_app_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, AppError> {
+    use crate::services::app_import::{self, Source};
+
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let mut user_id = None;
+    let mut source = None;
+    let mut file: Option<Vec<u8>> = None;
+    let mut opml: Option<String> = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| AppError::bad_request(format!("Multipart error: {}", e)))? {
+        let name = field.name().unwrap_or("").to_string();
+        let bytes = field.bytes().await.map_err(|e| AppError::bad_request(format!("Failed to read upload: {}", e)))?;
+        match name.as_str() {
+            "user_id" => user_id = String::from_utf8_lossy(&bytes).trim().parse::<i32>().ok(),
+            "source" => source = Source::parse(String::from_utf8_lossy(&bytes).trim()),
+            "file" => file = Some(bytes.to_vec()),
+            "opml" => opml = Some(String::from_utf8_lossy(&bytes).into_owned()),
+            _ => {}
+        }
+    }
+    let user_id = user_id.ok_or_else(|| AppError::bad_request("Missing user_id"))?;
+    let source = source.ok_or_else(|| AppError::bad_request("Missing or unknown source (expected antennapod, pocketcasts or gpodder)"))?;
+    let file = file.ok_or_else(|| AppError::bad_request("No export file uploaded"))?;
+
+    let user_id_from_api_key = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if user_id != user_id_from_api_key && !is_web_key {
+        return Err(AppError::forbidden("You can only import into your own account!"));
+    }
+
+    // Parse up front so a wrong file is reported to the caller rather than as a failed task.
+    let library = match source {
+        Source::AntennaPod => {
+            // SQLite needs a file to open.
+            let tmp_path = std::env::temp_dir().join(format!("antennapod_import_{}.db", uuid::Uuid::new_v4()));
+            tokio::fs::write(&tmp_path, &file).await?;
+            let library = app_import::parse_antennapod(&tmp_path).await;
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            library?
+        }
+        Source::PocketCasts => app_import::parse_pocketcasts(opml.as_deref(), &String::from_utf8_lossy(&file))?,
+        Source::Gpodder => app_import::parse_gpodder(opml.as_deref(), &String::from_utf8_lossy(&file))?,
+    };
+
+    let task_state = state.clone();
+    let task_id = state.task_spawner.spawn_simple_task(
+        "app_data_import".to_string(),
+        user_id,
+        move || async move {
+            let report = app_import::import_library(&task_state, user_id, source, library).await?;
+            if report.episodes_queued > 0 {
+                task_state.playback_hub.queue_changed(user_id).await;
+            }
+            Ok(serde_json::to_value(report)?)
+        },
+    ).await?;
+
+    Ok(Json(serde_json::json!({
+        "detail": format!("{} import started.", source.label()),
+        "task_id": task_id
+    })))
+}
+
 // Request struct for backup_server
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct BackupServerRequest {
+    /// Needed for `sql` dumps only.
+    #[serde(default)]
     pub database_pass: String,
+    /// `sql` (default): a pg_dump/mysqldump of this engine. `logical`: an engine-neutral
+    /// JSON-lines export that can be restored into either Postgres or MySQL.
+    #[serde(default)]
+    pub format: Option<String>,
 }
 
 // Backup server data - improved streaming approach for large databases
@@ -1762,6 +2044,7 @@ pub struct BackupServerRequest {
     path = "/backup_server",
     tag = "settings",
     summary = "Backup server",
+    description = "Streams a full data backup. With `format: \"logical\"` the backup is engine-neutral JSON lines (`.jsonl`) that restore_server can load into either Postgres or MySQL, e.g. to move an instance from MySQL to Postgres.",
     request_body = BackupServerRequest,
     security(("api_key" = [])),
     responses(
@@ -1785,6 +2068,12 @@ pub async fn backup_server(
         return Err(AppError::forbidden("Admin access required"));
     }
 
+    if request.format.as_deref() == Some("logical") {
+        return backup_server_logical(&state);
+    } else if request.format.as_deref().is_some_and(|f| f != "sql") {
+        return Err(AppError::bad_request("Unknown backup format (expected sql or logical)"));
+    }
+
     // For large databases, we'll implement streaming export instead of subprocess
     // This avoids loading the entire database into memory at once
     match backup_server_streaming(&state, &request.database_pass).await {
@@ -1912,6 +2201,50 @@ async fn backup_server_streaming(
         .map_err(|e| format!("Failed to build response: {}", e))?)
 }
 
+// Stream a logical backup straight from the pool; rows are written as they're read, so memory
+// stays bounded. An error mid-stream truncates the download, which restore detects by the
+// missing end marker.
+fn backup_server_logical(state: &AppState) -> Result<axum::response::Response, AppError> {
+    use axum::body::Body;
+    use tokio_util::io::ReaderStream;
+
+    let (writer, reader) = tokio::io::duplex(64 * 1024);
+    let db_pool = state.db_pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::services::logical_backup::write_backup(&db_pool, writer).await {
+            warn!("Logical backup failed: {}", e);
+        }
+    });
+
+    axum::response::Response::builder()
+        .status(200)
+        .header("content-type", "application/x-ndjson")
+        .header("content-disposition", "attachment; filename=\"pinepods_backup.jsonl\"")
+        .body(Body::from_stream(ReaderStream::new(reader)))
+        .map_err(|e| AppError::internal(format!("Failed to build response: {}", e)))
+}
+
+// Restore an uploaded or on-disk backup, picking the loader by file contents.
+async fn restore_backup_path(
+    db_pool: &crate::database::DatabasePool,
+    path: &std::path::Path,
+    reporter: std::sync::Arc<dyn crate::services::tasks::ProgressReporter>,
+) -> Result<(), AppError> {
+    use crate::services::logical_backup;
+
+    if logical_backup::is_logical_backup(path).await {
+        logical_backup::restore_from_path(db_pool, path, Some(reporter)).await?;
+    } else {
+        reporter.update_progress(50.0, Some("Restoring database...".to_string())).await?;
+        db_pool.restore_server_data_from_path(path).await?;
+    }
+    Ok(())
+}
+
+fn is_backup_filename(name: &str) -> bool {
+    name.ends_with(".sql") || name.ends_with(crate::services::logical_backup::EXTENSION)
+}
+
 /// RAII guard for the global "restore in progress" flag. Resets the flag on drop so a
 /// panic or early return in the restore task can't leave restores permanently blocked.
 pub struct RestoreGuard(std::sync::Arc<std::sync::atomic::AtomicBool>);
@@ -1967,7 +2300,7 @@ pub async fn restore_server(
 
     // Stream the uploaded file to a temp file on the backups volume so memory usage
     // stays bounded for large dumps (a real instance backup can be hundreds of MB).
-    // A ".tmp" extension keeps it out of list_backup_files (which only lists ".sql").
+    // A ".tmp" extension keeps it out of list_backup_files (which only lists backups).
     let backup_dir = std::path::Path::new("/opt/pinepods/backups");
     tokio::fs::create_dir_all(backup_dir).await
         .map_err(|e| AppError::internal(&format!("Failed to create backup directory: {}", e)))?;
@@ -1984,8 +2317,8 @@ pub async fn restore_server(
             let filename = field.file_name().unwrap_or("").to_string();
 
             // Validate file extension
-            if !filename.ends_with(".sql") {
-                return Err(AppError::bad_request("Only SQL files are allowed"));
+            if !is_backup_filename(&filename) {
+                return Err(AppError::bad_request("Only .sql or .jsonl backup files are allowed"));
             }
 
             let mut file = tokio::fs::File::create(&tmp_path).await
@@ -2011,7 +2344,7 @@ pub async fn restore_server(
 
     if !have_file {
         let _ = tokio::fs::remove_file(&tmp_path).await;
-        return Err(AppError::bad_request("No SQL file uploaded"));
+        return Err(AppError::bad_request("No backup file uploaded"));
     }
 
     // Run the restore as a tracked progress task so the UI sees real completion (the
@@ -2027,9 +2360,8 @@ pub async fn restore_server(
             let _restore_guard = restore_guard;
             async move {
                 reporter.update_progress(10.0, Some("Starting restore...".to_string())).await?;
-                reporter.update_progress(50.0, Some("Restoring database...".to_string())).await?;
 
-                let result = db_pool.restore_server_data_from_path(&tmp_path).await;
+                let result = restore_backup_path(&db_pool, &tmp_path, reporter.clone()).await;
 
                 // Always clean up the temp upload, success or failure.
                 if let Err(e) = tokio::fs::remove_file(&tmp_path).await {
@@ -2483,7 +2815,7 @@ pub async fn verify_gpodder_auth(
     // Direct HTTP call to match Python implementation exactly
     let client = reqwest::Client::new();
     let auth_url = format!("{}/api/2/auth/{}/login.json", 
-                          request.gpodder_url.trim_end_matches('/'), 
+                          crate::services::gpodder_sync::api_base(&request.gpodder_url), 
                           request.gpodder_username);
     
     match client
@@ -2764,12 +3096,14 @@ pub async fn add_custom_podcast(
 
     // Check if this is a YouTube channel request
     if request.youtube_channel.unwrap_or(false) {
-        // Extract channel ID from YouTube URL
-        let channel_id = extract_youtube_channel_id(&request.feed_url)?;
+        // Channel or playlist ID/URL; @handles are resolved to their channel by yt-dlp
+        let source = crate::services::youtube_source::resolve(&request.feed_url)
+            .await
+            .map_err(AppError::bad_request)?;
 
         // Check if channel already exists
         let existing_id = state.db_pool.check_existing_channel_subscription(
-            &channel_id,
+            &source.feed_url(),
             request.user_id,
         ).await?;
 
@@ -2780,7 +3114,7 @@ pub async fn add_custom_podcast(
         }
 
         // Get channel info using yt-dlp (bypasses Google API limits)
-        let channel_info = crate::handlers::youtube::get_youtube_channel_info(&channel_id).await?;
+        let channel_info = crate::handlers::youtube::get_youtube_channel_info(&source).await?;
 
         let feed_cutoff = request.feed_cutoff.unwrap_or(30);
 
@@ -2793,15 +3127,14 @@ pub async fn add_custom_podcast(
 
         // Spawn background task to process YouTube videos
         let state_clone = state.clone();
-        let channel_id_clone = channel_id.clone();
         tokio::spawn(async move {
             if let Err(e) = crate::handlers::youtube::process_youtube_channel(
                 podcast_id,
-                &channel_id_clone,
+                &source,
                 feed_cutoff,
                 &state_clone
             ).await {
-                warn!("Error processing YouTube channel {}: {}", channel_id_clone, e);
+                warn!("Error processing YouTube channel {}: {}", source.id(), e);
             }
         });
 
@@ -2835,44 +3168,6 @@ pub async fn add_custom_podcast(
     Ok(Json(serde_json::json!({ "data": podcast_details })))
 }
 
-// Helper function to extract YouTube channel ID from various URL formats
-fn extract_youtube_channel_id(url: &str) -> Result<String, AppError> {
-    // Support various YouTube URL formats:
-    // - https://www.youtube.com/channel/UC...
-    // - https://youtube.com/channel/UC...
-    // - https://www.youtube.com/@channelname
-    // - youtube.com/@channelname
-    // - Just the channel ID itself: UC...
-
-    let url_lower = url.to_lowercase();
-
-    // If it's already a channel ID (starts with UC)
-    if url.starts_with("UC") && !url.contains('/') && !url.contains('.') {
-        return Ok(url.to_string());
-    }
-
-    // Extract from /channel/ URLs
-    if url_lower.contains("/channel/") {
-        if let Some(channel_part) = url.split("/channel/").nth(1) {
-            let channel_id = channel_part.split(&['/', '?', '&'][..]).next().unwrap_or("");
-            if !channel_id.is_empty() {
-                return Ok(channel_id.to_string());
-            }
-        }
-    }
-
-    // For @handle URLs, we need to use yt-dlp to resolve the channel ID
-    // This will be handled by get_youtube_channel_info, so we return the URL as-is
-    if url_lower.contains("/@") || url.starts_with('@') {
-        return Ok(url.to_string());
-    }
-
-    Err(AppError::bad_request(&format!(
-        "Invalid YouTube channel URL. Expected format: https://www.youtube.com/channel/UC... or https://www.youtube.com/@channelname or just the channel ID. Got: {}",
-        url
-    )))
-}
-
 // Get notification settings - matches Python notification_settings GET function exactly
 #[utoipa::path(
     get,
@@ -2935,6 +3230,15 @@ pub async fn update_notification_settings(
         return Err(AppError::forbidden("You can only update your own notification settings!"));
     }
 
+    // Saved as a notification channel, so the same outbound URL check applies
+    let url = match request.platform.as_str() {
+        "ntfy" => request.ntfy_server_url.as_deref(),
+        "gotify" => request.gotify_url.as_deref(),
+        "http" => request.http_url.as_deref(),
+        _ => None,
+    };
+    crate::handlers::ensure_urls_allowed(&state, user_id_from_api_key, &url.into_iter().collect::<Vec<_>>()).await?;
+
     state.db_pool.update_notification_settings(
         request.user_id,
         &request.platform,
@@ -2953,7 +3257,7 @@ pub async fn update_notification_settings(
     Ok(Json(serde_json::json!({ "detail": "Notification settings updated successfully" })))
 }
 
-// Test notification - matches Python test_notification function exactly
+// Test notification - sends through the user's first channel of the platform
 #[utoipa::path(
     post,
     path = "/user/test_notification",
@@ -2982,20 +3286,10 @@ pub async fn test_notification(
         return Err(AppError::forbidden("You can only test your own notifications!"));
     }
 
-    // Get notification settings and send test notification
-    let settings = state.db_pool.get_notification_settings(request.user_id).await?;
-    
-    // Find settings for the specific platform
-    let platform_settings = settings.iter()
-        .find(|s| s.get("platform").and_then(|p| p.as_str()) == Some(&request.platform))
-        .ok_or_else(|| AppError::bad_request(&format!("No settings found for platform: {}", request.platform)))?;
-    
-    let success = state.notification_manager.send_test_notification(request.user_id, &request.platform, platform_settings).await?;
-    
-    if success {
-        Ok(Json(serde_json::json!({ "detail": "Test notification sent successfully" })))
-    } else {
-        Err(AppError::bad_request("Failed to send test notification - check your settings"))
+    match crate::services::notification_channels::test_legacy_platform(&state.db_pool, request.user_id, &request.platform).await? {
+        Some(Ok(())) => Ok(Json(serde_json::json!({ "detail": "Test notification sent successfully" }))),
+        Some(Err(e)) => Err(AppError::bad_request(format!("Failed to send test notification - check your settings ({})", e))),
+        None => Err(AppError::bad_request(format!("No settings found for platform: {}", request.platform))),
     }
 }
 
@@ -4289,6 +4583,9 @@ pub async fn get_silence_trim(
 pub struct SkipSegmentsQuery {
     pub episode_id: i32,
     pub user_id: i32,
+    /// Treat `episode_id` as a YouTube video id (SponsorBlock segments).
+    #[serde(default)]
+    pub is_youtube: bool,
 }
 
 #[utoipa::path(
@@ -4317,14 +4614,30 @@ pub async fn get_episode_skip_segments(
         return Err(AppError::forbidden("You can only view your own episodes."));
     }
 
+    if query.is_youtube {
+        let segments = crate::services::sponsorblock::get_video_skip_segments_for_user(
+            &state.db_pool, query.user_id, query.episode_id,
+        )
+        .await
+        .map_err(|e| AppError::internal(&e))?;
+        return Ok(Json(serde_json::json!({ "segments": segments })));
+    }
+
     // Per-user view: enriches each segment with its DB id and (for ads) the requesting user's
     // effective status, so the player and transcript review UI share one shape.
-    let segments = crate::services::ad_detection::get_episode_skip_segments_for_user(
+    let mut segments = crate::services::ad_detection::get_episode_skip_segments_for_user(
         &state.db_pool, query.user_id, query.episode_id,
     )
     .await
     .map_err(|e| AppError::internal(&e))?;
 
+    // When the stream is a cut copy, the removed segments are already gone from the audio and
+    // the rest sit earlier on its timeline.
+    let cut_ranges = crate::services::audio_cut::served_cut_ranges(&state.db_pool, query.user_id, query.episode_id).await;
+    if !cut_ranges.is_empty() {
+        segments = crate::services::audio_cut::remap_segments(segments, &cut_ranges);
+    }
+
     Ok(Json(serde_json::json!({ "segments": segments })))
 }
 
@@ -4420,7 +4733,7 @@ pub async fn detect_ads(
     Ok(Json(serde_json::json!({ "task_id": task_id, "detail": "Ad detection started." })))
 }
 
-// Per-user confirm/deny of a detected ad segment
+// Per-user confirm/deny of a detected ad segment (also used for SponsorBlock segments)
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct AdSegmentReviewRequest {
     pub segment_id: i32,
@@ -4629,6 +4942,170 @@ pub async fn get_ad_skip_auto_activate(
     Ok(Json(serde_json::json!({ "enabled": enabled })))
 }
 
+// Per-user SponsorBlock category actions (get + set)
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SponsorBlockCategoriesRequest {
+    pub user_id: i32,
+    /// Category (`sponsor`, `selfpromo`, `intro`, `outro`, `interaction`) to `skip`, `review` or `off`.
+    pub categories: std::collections::HashMap<String, String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/adjust_sponsorblock_categories",
+    tag = "settings",
+    summary = "Set per-user SponsorBlock category actions",
+    request_body = SponsorBlockCategoriesRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Unknown category or action"),
+        (status = 401, description = "Invalid or missing API key"),
+    ),
+)]
+pub async fn adjust_sponsorblock_categories(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<SponsorBlockCategoriesRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if key_id != request.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only modify your own settings."));
+    }
+
+    crate::services::sponsorblock::set_category_actions(&state.db_pool, request.user_id, &request.categories)
+        .await
+        .map_err(AppError::bad_request)?;
+
+    Ok(Json(serde_json::json!({ "detail": "SponsorBlock categories updated." })))
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SponsorBlockCategoriesQuery {
+    pub user_id: i32,
+}
+
+#[utoipa::path(
+    get,
+    path = "/get_sponsorblock_categories",
+    tag = "settings",
+    summary = "Get per-user SponsorBlock category actions",
+    params(SponsorBlockCategoriesQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+    ),
+)]
+pub async fn get_sponsorblock_categories(
+    State(state): State<AppState>,
+    Query(query): Query<SponsorBlockCategoriesQuery>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if key_id != query.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only view your own settings."));
+    }
+
+    let categories = crate::services::sponsorblock::get_category_actions(&state.db_pool, query.user_id)
+        .await
+        .map_err(|e| AppError::internal(&e))?;
+
+    Ok(Json(serde_json::json!({ "categories": categories })))
+}
+
+// Per-podcast serve-cut-audio opt-in: stream downloads with skipped segments removed
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ServeCutAudioRequest {
+    pub podcast_id: i32,
+    pub user_id: i32,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/adjust_serve_cut_audio",
+    tag = "settings",
+    summary = "Set per-podcast serve-cut-audio",
+    description = "When enabled, streaming a server-downloaded episode of this podcast (including from the generated RSS feed) serves a copy with silence and skipped ads cut out, with embedded chapters rewritten to match.",
+    request_body = ServeCutAudioRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+    ),
+)]
+pub async fn adjust_serve_cut_audio(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ServeCutAudioRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if key_id != request.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only modify your own podcasts."));
+    }
+
+    crate::services::audio_cut::set_serve_cut_audio(
+        &state.db_pool, request.podcast_id, request.user_id, request.enabled,
+    )
+    .await
+    .map_err(|e| AppError::internal(&e))?;
+
+    Ok(Json(serde_json::json!({ "detail": "Serve cut audio updated." })))
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ServeCutAudioQuery {
+    pub podcast_id: i32,
+    pub user_id: i32,
+}
+
+#[utoipa::path(
+    get,
+    path = "/get_serve_cut_audio",
+    tag = "settings",
+    summary = "Get per-podcast serve-cut-audio setting",
+    params(ServeCutAudioQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+    ),
+)]
+pub async fn get_serve_cut_audio(
+    State(state): State<AppState>,
+    Query(query): Query<ServeCutAudioQuery>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if key_id != query.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only view your own podcasts."));
+    }
+
+    let enabled = crate::services::audio_cut::get_serve_cut_audio(&state.db_pool, query.podcast_id)
+        .await
+        .map_err(|e| AppError::internal(&e))?;
+
+    Ok(Json(serde_json::json!({ "enabled": enabled })))
+}
+
 // ---- AI settings + model management (admin-only) ----
 
 #[utoipa::path(
@@ -5104,7 +5581,7 @@ pub async fn list_backup_files(
             for entry in entries {
                 if let Ok(entry) = entry {
                     let path = entry.path();
-                    if path.is_file() && path.extension().map_or(false, |ext| ext == "sql") {
+                    if path.is_file() && path.extension().is_some_and(|ext| ext == "sql" || ext == "jsonl") {
                         if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
                             let metadata = entry.metadata().ok();
                             let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
@@ -5171,7 +5648,7 @@ pub async fn restore_from_backup_file(
 
     // Validate filename to prevent path traversal
     let backup_filename = request.backup_filename.clone();
-    if backup_filename.contains("..") || backup_filename.contains("/") || !backup_filename.ends_with(".sql") {
+    if backup_filename.contains("..") || backup_filename.contains("/") || !is_backup_filename(&backup_filename) {
         return Err(AppError::bad_request("Invalid backup filename"));
     }
 
@@ -5202,10 +5679,10 @@ pub async fn restore_from_backup_file(
             let _restore_guard = restore_guard;
             async move {
                 reporter.update_progress(10.0, Some("Starting restoration from backup file...".to_string())).await?;
-                reporter.update_progress(50.0, Some("Restoring database...".to_string())).await?;
 
-                // Clears existing data, then streams the dump into psql/mysql.
-                db_pool.restore_server_data_from_path(std::path::Path::new(&backup_path)).await?;
+                // Clears existing data, then streams the dump into psql/mysql (or loads a
+                // logical backup through the pool).
+                restore_backup_path(&db_pool, std::path::Path::new(&backup_path), reporter.clone()).await?;
 
                 reporter.update_progress(100.0, Some("Restoration completed successfully".to_string())).await?;
 
@@ -5254,7 +5731,7 @@ pub async fn delete_backup_file(
 
     // Validate filename to prevent path traversal
     let backup_filename = request.backup_filename.clone();
-    if backup_filename.contains("..") || backup_filename.contains('/') || !backup_filename.ends_with(".sql") {
+    if backup_filename.contains("..") || backup_filename.contains('/') || !is_backup_filename(&backup_filename) {
         return Err(AppError::bad_request("Invalid backup filename"));
     }
 
@@ -5275,7 +5752,11 @@ pub async fn delete_backup_file(
 
 // Request struct for manual backup to directory
 #[derive(Deserialize, utoipa::ToSchema)]
-pub struct ManualBackupRequest {}
+pub struct ManualBackupRequest {
+    /// `sql` (default) or `logical`; see backup_server.
+    #[serde(default)]
+    pub format: Option<String>,
+}
 
 // Manual backup to directory - admin only
 #[utoipa::path(
@@ -5293,7 +5774,7 @@ pub struct ManualBackupRequest {}
 pub async fn manual_backup_to_directory(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(_request): Json<ManualBackupRequest>,
+    Json(request): Json<ManualBackupRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let api_key = extract_api_key(&headers)?;
     validate_api_key(&state, &api_key).await?;
@@ -5306,9 +5787,16 @@ pub async fn manual_backup_to_directory(
         return Err(AppError::forbidden("Admin access required"));
     }
 
+    let logical = match request.format.as_deref() {
+        None | Some("sql") => false,
+        Some("logical") => true,
+        Some(_) => return Err(AppError::bad_request("Unknown backup format (expected sql or logical)")),
+    };
+
     // Generate filename with timestamp
     let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-    let backup_filename = format!("manual_backup_{}.sql", timestamp);
+    let extension = if logical { crate::services::logical_backup::EXTENSION } else { ".sql" };
+    let backup_filename = format!("manual_backup_{}{}", timestamp, extension);
     let backup_path = format!("/opt/pinepods/backups/{}", backup_filename);
 
     // Ensure backup directory exists
@@ -5319,6 +5807,7 @@ pub async fn manual_backup_to_directory(
 
     // Clone for the async closure
     let backup_filename_for_closure = backup_filename.clone();
+    let db_pool = state.db_pool.clone();
 
     // Spawn backup task
     let task_id = state.task_spawner.spawn_progress_task(
@@ -5329,6 +5818,30 @@ pub async fn manual_backup_to_directory(
             let backup_filename = backup_filename_for_closure;
             async move {
                 reporter.update_progress(10.0, Some("Starting manual backup...".to_string())).await?;
+
+                if logical {
+                    reporter.update_progress(30.0, Some("Creating logical backup...".to_string())).await?;
+                    let file = tokio::fs::File::create(&backup_path).await?;
+                    let totals = match crate::services::logical_backup::write_backup(&db_pool, file).await {
+                        Ok(totals) => totals,
+                        Err(e) => {
+                            let _ = tokio::fs::remove_file(&backup_path).await;
+                            return Err(e);
+                        }
+                    };
+                    let size = tokio::fs::metadata(&backup_path).await.map(|m| m.len()).unwrap_or(0);
+                    reporter.update_progress(100.0, Some("Manual backup completed successfully".to_string())).await?;
+                    return Ok(serde_json::json!({
+                        "status": "Manual backup completed successfully",
+                        "backup_info": {
+                            "filename": backup_filename,
+                            "size": size,
+                            "path": backup_path,
+                            "tables": totals.tables,
+                            "rows": totals.rows
+                        }
+                    }));
+                }
                 
                 // Get database credentials from environment
                 let db_type = std::env::var("DB_TYPE").unwrap_or_else(|_| "postgresql".to_string());
@@ -6202,3 +6715,197 @@ pub async fn delete_custom_theme(
     Ok(Json(serde_json::json!({ "message": "Custom theme deleted successfully" })))
 }
 
+
+// ---- Download quotas and retention rules ----
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct StorageUsageQuery {
+    pub user_id: i32,
+}
+
+#[utoipa::path(
+    get,
+    path = "/storage_usage",
+    tag = "settings",
+    summary = "Get download storage usage",
+    description = "Bytes of server downloads held by the user against their quota, broken down by podcast. Users may read their own report; admins may read anyone's.",
+    params(StorageUsageQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = crate::services::storage::StorageReport),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not your report and not an admin"),
+    ),
+)]
+pub async fn get_storage_usage(
+    State(state): State<AppState>,
+    Query(query): Query<StorageUsageQuery>,
+    headers: HeaderMap,
+) -> Result<Json<crate::services::storage::StorageReport>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    if key_id != query.user_id && !state.db_pool.user_admin_check(key_id).await? {
+        return Err(AppError::forbidden("You can only view your own storage usage."));
+    }
+
+    let report = crate::services::storage::storage_report(&state.db_pool, query.user_id).await?;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    get,
+    path = "/storage_usage_all",
+    tag = "settings",
+    summary = "Get download storage usage for every user",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::services::storage::StorageReport>),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Admin access required"),
+    ),
+)]
+pub async fn get_storage_usage_all(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<crate::services::storage::StorageReport>>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    if !state.db_pool.user_admin_check(key_id).await? {
+        return Err(AppError::forbidden("Admin access required"));
+    }
+
+    let reports = crate::services::storage::storage_reports(&state.db_pool).await?;
+    Ok(Json(reports))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SetUserQuotaRequest {
+    pub user_id: i32,
+    /// Maximum bytes of server downloads; null or 0 removes the quota.
+    pub quota_bytes: Option<i64>,
+    /// `refuse` (default) or `evict`.
+    #[serde(default = "default_quota_policy")]
+    pub policy: String,
+}
+
+fn default_quota_policy() -> String { crate::services::storage::POLICY_REFUSE.to_string() }
+
+#[utoipa::path(
+    post,
+    path = "/set_user_quota",
+    tag = "settings",
+    summary = "Set a user's download quota (admin)",
+    description = "With the `refuse` policy a download that would exceed the quota fails; with `evict` the user's played downloads, then their oldest, are deleted to make room.",
+    request_body = SetUserQuotaRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 400, description = "Unknown policy"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Admin access required"),
+    ),
+)]
+pub async fn set_user_quota(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<SetUserQuotaRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    if !state.db_pool.user_admin_check(key_id).await? {
+        return Err(AppError::forbidden("Admin access required"));
+    }
+
+    crate::services::storage::set_quota(&state.db_pool, request.user_id, request.quota_bytes, &request.policy).await?;
+    Ok(Json(serde_json::json!({ "detail": "Download quota updated." })))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PodcastRetentionRequest {
+    pub podcast_id: i32,
+    pub user_id: i32,
+    #[serde(default)]
+    pub keep_last_episodes: i32,
+    #[serde(default)]
+    pub delete_after_played: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/adjust_podcast_retention",
+    tag = "settings",
+    summary = "Set per-podcast download retention rules",
+    description = "Keep only the newest `keep_last_episodes` downloads of the podcast (0 keeps all) and/or delete downloads once played. Applied by the scheduled cleanup.",
+    request_body = PodcastRetentionRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 404, description = "Podcast not found"),
+    ),
+)]
+pub async fn adjust_podcast_retention(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<PodcastRetentionRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if key_id != request.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only modify your own podcasts."));
+    }
+
+    let rules = crate::services::storage::RetentionRules {
+        keep_last_episodes: request.keep_last_episodes,
+        delete_after_played: request.delete_after_played,
+    };
+    crate::services::storage::set_retention(&state.db_pool, request.podcast_id, request.user_id, &rules).await?;
+
+    Ok(Json(serde_json::json!({ "detail": "Podcast retention rules updated." })))
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct PodcastRetentionQuery {
+    pub podcast_id: i32,
+    pub user_id: i32,
+}
+
+#[utoipa::path(
+    get,
+    path = "/get_podcast_retention",
+    tag = "settings",
+    summary = "Get per-podcast download retention rules",
+    params(PodcastRetentionQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = crate::services::storage::RetentionRules),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 404, description = "Podcast not found"),
+    ),
+)]
+pub async fn get_podcast_retention(
+    State(state): State<AppState>,
+    Query(query): Query<PodcastRetentionQuery>,
+    headers: HeaderMap,
+) -> Result<Json<crate::services::storage::RetentionRules>, AppError> {
+    let api_key = extract_api_key(&headers)?;
+    validate_api_key(&state, &api_key).await?;
+
+    let key_id = state.db_pool.get_user_id_from_api_key(&api_key).await?;
+    let is_web_key = state.db_pool.is_web_key(&api_key).await?;
+    if key_id != query.user_id && !is_web_key {
+        return Err(AppError::forbidden("You can only view your own podcasts."));
+    }
+
+    let rules = crate::services::storage::get_retention(&state.db_pool, query.podcast_id).await?;
+    Ok(Json(rules))
+}
@@ -418,7 +418,7 @@ pub async fn gpodder_test_connection(
     // Direct HTTP call to match Python implementation exactly
     let client = reqwest::Client::new();
     let auth_url = format!("{}/api/2/auth/{}/login.json", 
-                          gpodder_url.trim_end_matches('/'), 
+                          crate::services::gpodder_sync::api_base(gpodder_url), 
                           gpodder_username);
     
     let verified = match client
@@ -535,6 +535,10 @@ pub async fn gpodder_create_device(
 }
 
 // GPodder Statistics - real server-side stats from GPodder API
+
+/// How many sync log rows the statistics page shows
+pub const SYNC_RUNS_SHOWN: i64 = 20;
+
 #[derive(Serialize, utoipa::ToSchema)]
 pub struct GpodderStatistics {
     pub server_url: String,
@@ -549,6 +553,10 @@ pub struct GpodderStatistics {
     pub connection_status: String,
     pub last_sync_timestamp: Option<String>,
     pub api_endpoints_tested: Vec<EndpointTest>,
+    /// The most recent sync runs, newest first
+    pub recent_sync_runs: Vec<crate::services::gpodder_sync::SyncRun>,
+    /// Per remote device: how many episodes it reported actions for and its newest action
+    pub device_activity: Vec<crate::services::gpodder_sync::DeviceActivity>,
 }
 
 #[derive(Serialize, Clone, utoipa::ToSchema)]
@@ -589,6 +597,7 @@ pub struct EndpointTest {
     path = "/gpodder_statistics",
     tag = "sync",
     summary = "Gpodder get statistics",
+    description = "Server-side devices, subscriptions and episode actions, plus the user's recent sync runs and the last action time seen from each remote device.",
     security(("api_key" = [])),
     responses(
         (status = 200, description = "Success", body = GpodderStatistics),
@@ -621,6 +630,8 @@ pub async fn gpodder_get_statistics(
             connection_status: "Not configured".to_string(),
             last_sync_timestamp: None,
             api_endpoints_tested: vec![],
+            recent_sync_runs: crate::services::gpodder_sync::recent_runs(&state.db_pool, user_id, SYNC_RUNS_SHOWN).await?,
+            device_activity: vec![],
         }));
     }
 
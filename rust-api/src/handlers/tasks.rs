@@ -385,6 +385,30 @@ pub async fn cleanup_tasks_internal(state: &AppState) -> AppResult<()> {
         tracing::error!("Auto-delete old downloads failed during cleanup tasks: {}", e);
     }
 
+    // Per-podcast keep-last-N and delete-after-played rules
+    if let Err(e) = crate::services::storage::apply_retention_rules(&state.db_pool).await {
+        tracing::error!("Download retention rules failed during cleanup tasks: {}", e);
+    }
+
+    // Keep the transcoded-stream cache under its size limit
+    if let Err(e) = crate::services::transcode::prune_cache().await {
+        tracing::error!("Transcode cache prune failed during cleanup tasks: {}", e);
+    }
+    if let Err(e) = crate::services::audio_cut::prune_cache().await {
+        tracing::error!("Cut audio cache prune failed during cleanup tasks: {}", e);
+    }
+    if let Err(e) = crate::services::local_books::prune_cache().await {
+        tracing::error!("Joined audiobook cache prune failed during cleanup tasks: {}", e);
+    }
+
+    // Finished background jobs are kept only as long as their task entries
+    if let Err(e) = state.task_spawner.jobs().prune_finished().await {
+        tracing::error!("Background job prune failed during cleanup tasks: {}", e);
+    }
+    if let Err(e) = crate::services::webhooks::prune_deliveries(&state.db_pool).await {
+        tracing::error!("Webhook delivery log prune failed during cleanup tasks: {}", e);
+    }
+
     tracing::info!("Cleanup tasks completed successfully");
 
     Ok(())
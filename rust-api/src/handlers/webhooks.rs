@@ -0,0 +1,250 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AppError,
+    handlers::{check_owner, ensure_urls_allowed},
+    services::webhooks::{self, Webhook, WebhookDelivery},
+    AppState,
+};
+
+// User webhooks belong to their creator; admin-wide webhooks are shared by every admin
+async fn check_webhook_access(state: &AppState, user_id: i32, webhook_id: i32) -> Result<(), AppError> {
+    let Some((owner, scope)) = webhooks::webhook_owner(&state.db_pool, webhook_id).await? else {
+        return Err(AppError::not_found("Webhook not found"));
+    };
+    let allowed = if scope == webhooks::SCOPE_ADMIN {
+        state.db_pool.user_admin_check(user_id).await?
+    } else {
+        owner == user_id
+    };
+    if !allowed {
+        return Err(AppError::not_found("Webhook not found"));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct WebhooksQuery {
+    pub user_id: i32,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct WebhooksResponse {
+    pub webhooks: Vec<Webhook>,
+    /// Every event name a webhook can subscribe to.
+    pub available_events: Vec<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    summary = "List webhooks",
+    description = "Returns the user's webhooks with their last delivery state and pending/failed delivery counts, plus the admin-wide webhooks when the user is an admin. Secrets are never returned.",
+    params(WebhooksQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = WebhooksResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these webhooks"),
+    ),
+)]
+pub async fn get_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<WebhooksQuery>,
+) -> Result<Json<WebhooksResponse>, AppError> {
+    check_owner(&state, &headers, query.user_id, "webhooks").await?;
+    let is_admin = state.db_pool.user_admin_check(query.user_id).await?;
+    let webhooks = webhooks::list_webhooks(&state.db_pool, query.user_id, is_admin).await?;
+    let available_events = webhooks::WebhookEvent::SUBSCRIBABLE
+        .into_iter()
+        .filter(|event| is_admin || !event.admin_only())
+        .map(|event| event.name().to_string())
+        .collect();
+    Ok(Json(WebhooksResponse { webhooks, available_events }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct AddWebhookRequest {
+    pub user_id: i32,
+    pub url: String,
+    /// Event names such as `episode.new`, or `["*"]` for everything the scope can see.
+    pub events: Vec<String>,
+    /// `user` (default) for the user's own events, or `admin` for every user's events plus
+    /// server events such as `user.created`. Admin-wide webhooks need an admin.
+    pub scope: Option<String>,
+    /// Signing secret; generated when omitted.
+    pub secret: Option<String>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct AddWebhookResponse {
+    pub detail: String,
+    pub webhook_id: i32,
+    /// The signing secret. Only returned here, so store it now.
+    pub secret: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/add_webhook",
+    tag = "webhooks",
+    summary = "Add a webhook",
+    description = "Registers an endpoint that receives a signed JSON POST for each subscribed event. Deliveries carry X-PinePods-Event, X-PinePods-Delivery, X-PinePods-Timestamp and X-PinePods-Signature (sha256=HMAC-SHA256 of \"{timestamp}.{body}\" under the secret). The secret is stored encrypted. Only admins may point a webhook at a private or loopback address.",
+    request_body = AddWebhookRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = AddWebhookResponse),
+        (status = 400, description = "Unknown event or scope, or a disallowed URL"),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner, or an admin-wide webhook from a non-admin"),
+    ),
+)]
+pub async fn add_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AddWebhookRequest>,
+) -> Result<Json<AddWebhookResponse>, AppError> {
+    let key_id = check_owner(&state, &headers, request.user_id, "webhooks").await?;
+
+    let scope = request.scope.as_deref().unwrap_or(webhooks::SCOPE_USER);
+    match scope {
+        webhooks::SCOPE_USER => {}
+        webhooks::SCOPE_ADMIN => {
+            if !state.db_pool.user_admin_check(request.user_id).await? {
+                return Err(AppError::forbidden("Only admins can add admin-wide webhooks."));
+            }
+        }
+        other => {
+            return Err(AppError::bad_request(format!(
+                "Unknown webhook scope '{}' (expected user or admin)",
+                other
+            )));
+        }
+    }
+    let events = webhooks::parse_events(&request.events, scope).map_err(AppError::bad_request)?;
+
+    let url = request.url.trim();
+    ensure_urls_allowed(&state, key_id, &[url]).await?;
+
+    let secret = request
+        .secret
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(webhooks::new_secret);
+    let webhook_id = webhooks::add_webhook(&state.db_pool, request.user_id, scope, url, &events, &secret).await?;
+    Ok(Json(AddWebhookResponse { detail: "Webhook added.".to_string(), webhook_id, secret }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct WebhookRequest {
+    pub user_id: i32,
+    pub webhook_id: i32,
+}
+
+#[utoipa::path(
+    post,
+    path = "/remove_webhook",
+    tag = "webhooks",
+    summary = "Remove a webhook",
+    description = "Deletes the webhook along with its delivery log and any deliveries still pending.",
+    request_body = WebhookRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these webhooks"),
+        (status = 404, description = "No such webhook for this user"),
+    ),
+)]
+pub async fn remove_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<WebhookRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_owner(&state, &headers, request.user_id, "webhooks").await?;
+    check_webhook_access(&state, request.user_id, request.webhook_id).await?;
+    if !webhooks::remove_webhook(&state.db_pool, request.webhook_id).await? {
+        return Err(AppError::not_found("Webhook not found"));
+    }
+    Ok(Json(serde_json::json!({ "detail": "Webhook removed." })))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TestWebhookResponse {
+    pub delivery: WebhookDelivery,
+}
+
+#[utoipa::path(
+    post,
+    path = "/test_webhook",
+    tag = "webhooks",
+    summary = "Send a test event",
+    description = "Sends a signed `webhook.test` event to the webhook right away and returns the delivery log entry, including the response status. Test deliveries are not retried.",
+    request_body = WebhookRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "The attempt was made; check the delivery status", body = TestWebhookResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these webhooks"),
+        (status = 404, description = "No such webhook for this user"),
+    ),
+)]
+pub async fn test_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<WebhookRequest>,
+) -> Result<Json<TestWebhookResponse>, AppError> {
+    check_owner(&state, &headers, request.user_id, "webhooks").await?;
+    check_webhook_access(&state, request.user_id, request.webhook_id).await?;
+    let delivery = webhooks::test_fire(&state.db_pool, request.webhook_id, request.user_id).await?;
+    Ok(Json(TestWebhookResponse { delivery }))
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct WebhookDeliveriesQuery {
+    pub user_id: i32,
+    pub webhook_id: i32,
+    /// Defaults to 50, at most 200.
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct WebhookDeliveriesResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhook_deliveries",
+    tag = "webhooks",
+    summary = "Webhook delivery log",
+    description = "Returns the webhook's most recent deliveries, newest first, with their payloads, attempt counts and last errors. Finished deliveries are kept for 30 days.",
+    params(WebhookDeliveriesQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Success", body = WebhookDeliveriesResponse),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Not the owner of these webhooks"),
+        (status = 404, description = "No such webhook for this user"),
+    ),
+)]
+pub async fn get_webhook_deliveries(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<Json<WebhookDeliveriesResponse>, AppError> {
+    check_owner(&state, &headers, query.user_id, "webhooks").await?;
+    check_webhook_access(&state, query.user_id, query.webhook_id).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let deliveries = webhooks::list_deliveries(&state.db_pool, query.webhook_id, limit).await?;
+    Ok(Json(WebhookDeliveriesResponse { deliveries }))
+}
@@ -17,6 +17,8 @@ use crate::{
 
 type UserConnections = Arc<RwLock<HashMap<i32, Vec<broadcast::Sender<TaskUpdate>>>>>;
 
+/// Websockets connected to this replica. Other replicas hold their own; task updates reach all of
+/// them through the Redis fan-out in `TaskManager`, so a client may connect to any replica.
 pub struct WebSocketManager {
     connections: UserConnections,
 }
@@ -245,6 +247,47 @@ pub async fn get_task_status(
     Ok(axum::Json(task))
 }
 
+#[utoipa::path(
+    post,
+    path = "/{task_id}/cancel",
+    tag = "tasks",
+    summary = "Cancel a queued or running task",
+    description = "Cancels a download, transcription, ad detection or feed refresh job. A queued job never runs; a running one is stopped and any partly downloaded file removed.",
+    params(("task_id" = String, Path)),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Task cancelled", body = serde_json::Value),
+        (status = 401, description = "Invalid or missing API key"),
+        (status = 403, description = "Task does not belong to the requesting user"),
+        (status = 404, description = "No cancellable job with this task ID"),
+        (status = 409, description = "The task has already finished"),
+    ),
+)]
+pub async fn cancel_task(
+    headers: HeaderMap,
+    Path(task_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<axum::Json<serde_json::Value>, crate::error::AppError> {
+    let api_key = extract_api_key(&headers)?;
+    if !validate_api_key(&state, &api_key).await? {
+        return Err(crate::error::AppError::unauthorized("Invalid API key"));
+    }
+
+    let jobs = state.task_spawner.jobs();
+    let owner = jobs
+        .owner(&task_id)
+        .await?
+        .ok_or_else(|| crate::error::AppError::not_found("No cancellable job with this task ID"))?;
+    if !check_user_access(&state, &api_key, owner).await? {
+        return Err(crate::error::AppError::forbidden("You can only cancel your own tasks!"));
+    }
+
+    if !jobs.cancel(&task_id).await? {
+        return Err(crate::error::AppError::conflict("The task has already finished"));
+    }
+    Ok(axum::Json(serde_json::json!({ "detail": "Task cancelled", "task_id": task_id })))
+}
+
 #[utoipa::path(
     get,
     path = "/active",
@@ -0,0 +1,134 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::{
+    error::AppError,
+    services::websub,
+    AppState,
+};
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct WebSubVerifyQuery {
+    #[serde(rename = "hub.mode")]
+    pub mode: String,
+    #[serde(rename = "hub.topic")]
+    pub topic: Option<String>,
+    #[serde(rename = "hub.challenge")]
+    pub challenge: Option<String>,
+    #[serde(rename = "hub.lease_seconds")]
+    pub lease_seconds: Option<i64>,
+    #[serde(rename = "hub.reason")]
+    pub reason: Option<String>,
+}
+
+// Hub verification of a subscribe/unsubscribe request, or notice that it was denied
+#[utoipa::path(
+    get,
+    path = "/callback/{token}",
+    tag = "websub",
+    summary = "WebSub intent verification",
+    description = "Called by a WebSub hub to confirm a subscribe or unsubscribe request (the challenge is echoed back) or to report that a subscription was denied. Unauthenticated; the token identifies the subscription.",
+    params(("token" = String, Path), WebSubVerifyQuery),
+    responses(
+        (status = 200, description = "Challenge echoed (or denial recorded)", content_type = "text/plain"),
+        (status = 404, description = "Unknown subscription or a request we did not make"),
+    ),
+)]
+pub async fn websub_verify(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<WebSubVerifyQuery>,
+) -> Result<Response, AppError> {
+    let sub = websub::load_by_token(&state.db_pool, &token)
+        .await?
+        .ok_or_else(|| AppError::not_found("Unknown WebSub subscription"))?;
+
+    match query.mode.as_str() {
+        "denied" => {
+            info!("WebSub hub {} denied subscription to {}: {:?}", sub.hub_url, sub.topic_url, query.reason);
+            websub::mark_denied(&state.db_pool, sub.id, query.reason.as_deref()).await?;
+            Ok(StatusCode::OK.into_response())
+        }
+        "subscribe" | "unsubscribe" => {
+            if query.topic.as_deref() != Some(sub.topic_url.as_str()) {
+                return Err(AppError::not_found("Topic does not match this subscription"));
+            }
+            let challenge = query
+                .challenge
+                .ok_or_else(|| AppError::bad_request("hub.challenge is required"))?;
+
+            if query.mode == "subscribe" {
+                if sub.status == websub::STATUS_UNSUBSCRIBING {
+                    return Err(AppError::not_found("Subscription is being cancelled"));
+                }
+                let lease = query
+                    .lease_seconds
+                    .filter(|l| *l > 0)
+                    .unwrap_or(websub::REQUESTED_LEASE_SECONDS);
+                websub::mark_verified(&state.db_pool, sub.id, lease).await?;
+                info!("WebSub subscription verified for {} ({}s lease)", sub.topic_url, lease);
+            } else {
+                if sub.status != websub::STATUS_UNSUBSCRIBING {
+                    return Err(AppError::not_found("No unsubscribe was requested"));
+                }
+                websub::confirm_unsubscribed(&state.db_pool, sub.id).await?;
+                info!("WebSub unsubscribe confirmed for {}", sub.topic_url);
+            }
+
+            Ok((StatusCode::OK, [("content-type", "text/plain")], challenge).into_response())
+        }
+        other => Err(AppError::bad_request(format!("Unknown hub.mode: {}", other))),
+    }
+}
+
+// Content distribution from a hub: verify the signature and refresh the feed
+#[utoipa::path(
+    post,
+    path = "/callback/{token}",
+    tag = "websub",
+    summary = "WebSub content notification",
+    description = "Called by a WebSub hub when the feed changes. The X-Hub-Signature HMAC is checked against the subscription secret and, if valid, the feed is refreshed for every subscriber in the background. Unsigned or mis-signed notifications are acknowledged but ignored, as the WebSub spec requires.",
+    params(("token" = String, Path)),
+    request_body(content = String, content_type = "application/rss+xml"),
+    responses(
+        (status = 202, description = "Notification accepted"),
+        (status = 410, description = "Unknown subscription; the hub should stop delivering"),
+    ),
+)]
+pub async fn websub_push(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let Some(sub) = websub::load_by_token(&state.db_pool, &token).await? else {
+        return Ok(StatusCode::GONE.into_response());
+    };
+
+    let signature = headers.get("x-hub-signature").and_then(|v| v.to_str().ok());
+    if !websub::verify_signature(&sub.secret, signature, &body) {
+        warn!("Ignoring WebSub notification for {} with missing or invalid signature", sub.topic_url);
+        return Ok(StatusCode::ACCEPTED.into_response());
+    }
+    if sub.status != websub::STATUS_VERIFIED {
+        debug!("Ignoring WebSub notification for {} (subscription {})", sub.topic_url, sub.status);
+        return Ok(StatusCode::ACCEPTED.into_response());
+    }
+
+    websub::record_push(&state.db_pool, sub.id).await?;
+    let feed_url = sub.feed_url;
+    tokio::spawn(async move {
+        match crate::handlers::refresh::refresh_feed_url(&state, &feed_url).await {
+            Ok(new) => info!("WebSub push for {}: {} new episodes", feed_url, new),
+            Err(e) => warn!("WebSub push refresh of {} failed: {}", feed_url, e),
+        }
+    });
+
+    Ok(StatusCode::ACCEPTED.into_response())
+}
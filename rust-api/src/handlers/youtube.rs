@@ -11,6 +11,7 @@ use tracing::{debug, info, warn};
 use crate::{
     error::AppError,
     handlers::{extract_api_key, validate_api_key},
+    services::youtube_source::{self, YouTubeSource},
     AppState,
 };
 
@@ -47,6 +48,7 @@ pub struct YouTubeVideo {
 // Query struct for YouTube subscription endpoint
 #[derive(Deserialize, utoipa::IntoParams)]
 pub struct YouTubeSubscribeQuery {
+    /// Channel ID, playlist ID, or a channel, @handle or playlist URL
     pub channel_id: String,
     pub user_id: i32,
     pub feed_cutoff: Option<i32>,
@@ -244,9 +246,13 @@ pub async fn subscribe_to_youtube_channel(
 
     info!("Starting subscription for channel {}", query.channel_id);
 
+    let source = youtube_source::resolve(&query.channel_id)
+        .await
+        .map_err(AppError::bad_request)?;
+
     // Check if channel already exists
     let existing_id = state.db_pool.check_existing_channel_subscription(
-        &query.channel_id,
+        &source.feed_url(),
         query.user_id,
     ).await?;
 
@@ -260,7 +266,7 @@ pub async fn subscribe_to_youtube_channel(
     }
 
     info!("Getting channel info");
-    let channel_info = get_youtube_channel_info(&query.channel_id).await?;
+    let channel_info = get_youtube_channel_info(&source).await?;
 
     debug!("Adding channel to database");
     let podcast_id = state.db_pool.add_youtube_channel(
@@ -271,10 +277,9 @@ pub async fn subscribe_to_youtube_channel(
 
     // Spawn background task to process YouTube videos
     let state_clone = state.clone();
-    let channel_id_clone = query.channel_id.clone();
     tokio::spawn(async move {
-        if let Err(e) = process_youtube_channel(podcast_id, &channel_id_clone, feed_cutoff, &state_clone).await {
-            warn!("Error processing YouTube channel {}: {}", channel_id_clone, e);
+        if let Err(e) = process_youtube_channel(podcast_id, &source, feed_cutoff, &state_clone).await {
+            warn!("Error processing YouTube channel {}: {}", source.id(), e);
         }
     });
 
@@ -285,48 +290,30 @@ pub async fn subscribe_to_youtube_channel(
     })))
 }
 
-// Helper function to get YouTube channel info using Backend service
-pub async fn get_youtube_channel_info(channel_id: &str) -> Result<HashMap<String, String>, AppError> {
-    info!("Getting channel info for {} from Backend service", channel_id);
-    
-    // Get Backend URL from environment variable
-    let search_api_url = std::env::var("SEARCH_API_URL")
-        .map_err(|_| AppError::external_error("SEARCH_API_URL environment variable not set"))?;
-    
-    // Replace /api/search with /api/youtube/channel for the channel details endpoint
-    let backend_url = search_api_url.replace("/api/search", &format!("/api/youtube/channel?id={}", channel_id));
-    
-    let client = reqwest::Client::new();
-    let response = client.get(&backend_url)
-        .send()
-        .await
-        .map_err(|e| AppError::external_error(&format!("Failed to call Backend service: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::external_error(&format!("Backend service error: {}", response.status())));
-    }
+// Helper function to get YouTube channel or playlist info from yt-dlp, falling back to the
+// Atom feed's title when yt-dlp can't list it
+pub async fn get_youtube_channel_info(source: &YouTubeSource) -> Result<HashMap<String, String>, AppError> {
+    info!("Getting info for YouTube source {}", source.id());
 
-    let channel_data: serde_json::Value = response.json()
-        .await
-        .map_err(|e| AppError::external_error(&format!("Failed to parse Backend response: {}", e)))?;
+    let info = match youtube_source::fetch_info(source).await {
+        Ok(info) => info,
+        Err(e) => {
+            warn!("yt-dlp could not list {}: {}", source.id(), e);
+            youtube_source::SourceInfo::default()
+        }
+    };
+    let name = if info.name.is_empty() {
+        youtube_source::fetch_title(source).await.map_err(|e| AppError::external_error(&e))?
+    } else {
+        info.name
+    };
 
-    // Extract channel info from Backend service response
     let mut channel_info = HashMap::new();
-    
-    channel_info.insert("channel_id".to_string(), channel_id.to_string());
-    channel_info.insert("name".to_string(), 
-        channel_data.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string());
-    
-    let description = channel_data.get("description")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .chars()
-        .take(500)
-        .collect::<String>();
-    channel_info.insert("description".to_string(), description);
-
-    channel_info.insert("thumbnail_url".to_string(), 
-        channel_data.get("thumbnailUrl").and_then(|v| v.as_str()).unwrap_or("").to_string());
+    channel_info.insert("channel_id".to_string(), source.id().to_string());
+    channel_info.insert("feed_url".to_string(), source.feed_url());
+    channel_info.insert("name".to_string(), name);
+    channel_info.insert("description".to_string(), info.description);
+    channel_info.insert("thumbnail_url".to_string(), info.thumbnail_url);
 
     info!("Successfully extracted channel info for: {}", channel_info.get("name").unwrap_or(&"Unknown".to_string()));
     Ok(channel_info)
@@ -372,14 +359,14 @@ pub fn parse_youtube_duration(duration_str: &str) -> Option<i64> {
     Some(total_seconds)
 }
 
-// Process YouTube channel videos using Backend service
+// Process YouTube channel or playlist videos from the yt-dlp listing and the public Atom feed
 pub async fn process_youtube_channel(
     podcast_id: i32,
-    channel_id: &str,
+    source: &YouTubeSource,
     feed_cutoff: i32,
     state: &AppState,
 ) -> Result<(), AppError> {
-    debug!("Processing YouTube channel: podcast_id={} channel_id={}", podcast_id, channel_id);
+    debug!("Processing YouTube source: podcast_id={} source={}", podcast_id, source.id());
 
     let cutoff_date = chrono::Utc::now() - chrono::Duration::days(feed_cutoff as i64);
     debug!("Cutoff date set to: {}", cutoff_date);
@@ -388,32 +375,19 @@ pub async fn process_youtube_channel(
     debug!("Cleaning up videos older than cutoff date...");
     state.db_pool.remove_old_youtube_videos(podcast_id, cutoff_date).await?;
 
-    // Get Backend URL from environment variable
-    let search_api_url = std::env::var("SEARCH_API_URL")
-        .map_err(|_| AppError::external_error("SEARCH_API_URL environment variable not set"))?;
-    
-    // Replace /api/search with /api/youtube/channel for the channel details endpoint
-    let backend_url = search_api_url.replace("/api/search", &format!("/api/youtube/channel?id={}", channel_id));
-    debug!("Fetching channel data from Backend service: {}", backend_url);
-
-    // Get video list using Backend service
-    let client = reqwest::Client::new();
-    let response = client.get(&backend_url)
-        .send()
-        .await
-        .map_err(|e| AppError::external_error(&format!("Failed to call Backend service: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(AppError::external_error(&format!("Backend service error: {}", response.status())));
-    }
-
-    let channel_data: serde_json::Value = response.json()
+    // Entries, durations and the channel's name/artwork come from yt-dlp; without it only the
+    // Atom feed's newest 15 are seen, and videos get their duration once the audio is downloaded.
+    let info = youtube_source::fetch_info(source).await.unwrap_or_else(|e| {
+        warn!("yt-dlp could not list {}: {}", source.id(), e);
+        youtube_source::SourceInfo::default()
+    });
+    let uploads = youtube_source::fetch_uploads(source, &info)
         .await
-        .map_err(|e| AppError::external_error(&format!("Failed to parse Backend response: {}", e)))?;
+        .map_err(|e| AppError::external_error(&e))?;
 
     // Self-heal: update podcast name/artwork if they were empty from a broken initial subscription
-    let channel_name_update = channel_data.get("name").and_then(|v| v.as_str()).unwrap_or("");
-    let channel_thumb_update = channel_data.get("thumbnailUrl").and_then(|v| v.as_str()).unwrap_or("");
+    let channel_name_update = info.name.as_str();
+    let channel_thumb_update = info.thumbnail_url.as_str();
     if !channel_name_update.is_empty() || !channel_thumb_update.is_empty() {
         match &state.db_pool {
             crate::database::DatabasePool::Postgres(pool) => {
@@ -449,63 +423,28 @@ pub async fn process_youtube_channel(
         info!("Healed podcast {} metadata: name='{}', thumbnail='{}'", podcast_id, channel_name_update, channel_thumb_update);
     }
 
-    let empty_vec = vec![];
-    let recent_videos_data = channel_data.get("recentVideos")
-        .and_then(|v| v.as_array())
-        .unwrap_or(&empty_vec);
-
-    debug!("Found {} total videos from Backend service", recent_videos_data.len());
+    debug!("Found {} total videos in the feed", uploads.len());
 
     let mut recent_videos = Vec::new();
 
-    // Process each video from Backend service response
-    for video_entry in recent_videos_data {
-        let video_id = video_entry.get("id").and_then(|v| v.as_str()).unwrap_or("");
-        if video_id.is_empty() {
-            debug!("Skipping video with missing ID");
+    for upload in &uploads {
+        // Playlists aren't in upload order, so every entry is checked against the cutoff.
+        if upload.published <= cutoff_date {
+            debug!("Video {} from {} is too old, skipping", upload.id, upload.published);
             continue;
         }
 
-        debug!("Processing video ID: {}", video_id);
-
-        // Parse the publishedAt date from Backend service
-        let published_str = video_entry.get("publishedAt").and_then(|v| v.as_str()).unwrap_or("");
-        let published = chrono::DateTime::parse_from_rfc3339(published_str)
-            .map(|dt| dt.with_timezone(&chrono::Utc))
-            .unwrap_or_else(|_| {
-                warn!("Failed to parse date {}, using current time", published_str);
-                chrono::Utc::now()
-            });
-
-        info!("Video publish date: {}", published);
-
-        if published <= cutoff_date {
-            info!("Video {} from {} is too old, stopping processing", video_id, published);
-            break;
-        }
-
-        // Debug: print what we got from Backend for this video
-        info!("Backend video data for {}: {:?}", video_id, video_entry);
-        let duration_str = video_entry.get("duration").and_then(|v| v.as_str()).unwrap_or("");
-        info!("Duration string from Backend: '{}'", duration_str);
-        let parsed_duration = if !duration_str.is_empty() {
-            parse_youtube_duration(duration_str).unwrap_or(0)
-        } else {
-            0
-        };
-        debug!("Parsed duration: {}", parsed_duration);
-
         let video_data = serde_json::json!({
-            "id": video_id,
-            "title": video_entry.get("title").and_then(|v| v.as_str()).unwrap_or(""),
-            "description": video_entry.get("description").and_then(|v| v.as_str()).unwrap_or(""),
-            "url": format!("https://www.youtube.com/watch?v={}", video_id),
-            "thumbnail": video_entry.get("thumbnail").and_then(|v| v.as_str()).unwrap_or(""),
-            "publish_date": published.to_rfc3339(),
-            "duration": duration_str  // Store as string for proper parsing in database
+            "id": upload.id,
+            "title": upload.title,
+            "description": upload.description,
+            "url": youtube_source::watch_url(&upload.id),
+            "thumbnail": upload.thumbnail,
+            "publish_date": upload.published.to_rfc3339(),
+            "duration": info.durations.get(&upload.id).copied().unwrap_or(0)
         });
 
-        debug!("Successfully added video {} to processing queue", video_id);
+        debug!("Successfully added video {} to processing queue", upload.id);
         recent_videos.push(video_data);
     }
 
@@ -520,8 +459,7 @@ pub async fn process_youtube_channel(
         // Filter out videos that already exist
         let mut new_videos = Vec::new();
         for video in &recent_videos {
-            let video_url = format!("https://www.youtube.com/watch?v={}", 
-                video.get("id").and_then(|v| v.as_str()).unwrap_or(""));
+            let video_url = youtube_source::watch_url(video.get("id").and_then(|v| v.as_str()).unwrap_or(""));
             if !existing_videos.contains(&video_url) {
                 new_videos.push(video.clone());
             } else {
@@ -537,64 +475,10 @@ pub async fn process_youtube_channel(
             info!("No new videos to add");
         }
 
-        // Download audio for recent videos
-        info!("Starting audio downloads");
-        let mut successful_downloads = 0;
-        let mut failed_downloads = 0;
+        // Skip segments for new videos, and fresh ones for videos still collecting submissions
+        crate::services::sponsorblock::refresh_podcast_videos(&state.db_pool, podcast_id).await;
 
-        for video in &recent_videos {
-            let video_id = video.get("id").and_then(|v| v.as_str()).unwrap_or("");
-            let title = video.get("title").and_then(|v| v.as_str()).unwrap_or("");
-            
-            let output_path = format!("/opt/pinepods/downloads/youtube/{}.mp3", video_id);
-            let output_path_double = format!("{}.mp3", output_path);
-
-            debug!("Processing download for video: {}", video_id);
-            info!("Title: {}", title);
-            info!("Target path: {}", output_path);
-
-            // Check if file already exists
-            if tokio::fs::metadata(&output_path).await.is_ok() || 
-               tokio::fs::metadata(&output_path_double).await.is_ok() {
-                debug!("Audio file already exists, skipping download");
-                continue;
-            }
-
-            info!("Starting download...");
-            match download_youtube_audio(video_id, &output_path).await {
-                Ok(_) => {
-                    info!("Download completed successfully");
-                    successful_downloads += 1;
-                    
-                    // Get duration from the downloaded MP3 file and update database
-                    if let Some(duration) = get_mp3_duration(&output_path) {
-                        if let Err(e) = state.db_pool.update_youtube_video_duration(video_id, duration).await {
-                            warn!("Failed to update duration for video {}: {}", video_id, e);
-                        } else {
-                            debug!("Updated duration for video {} to {} seconds", video_id, duration);
-                        }
-                    } else {
-                        warn!("Could not read duration from MP3 file: {}", output_path);
-                    }
-                }
-                Err(e) => {
-                    failed_downloads += 1;
-                    let error_msg = e.to_string();
-                    if error_msg.to_lowercase().contains("members-only") {
-                        debug!("Skipping video {} - Members-only content: {}", video_id, title);
-                    } else if error_msg.to_lowercase().contains("private") {
-                        debug!("Skipping video {} - Private video: {}", video_id, title);
-                    } else if error_msg.to_lowercase().contains("unavailable") {
-                        debug!("Skipping video {} - Unavailable video: {}", video_id, title);
-                    } else {
-                        warn!("Failed to download video {}: {}", video_id, title);
-                        warn!("Error: {}", error_msg);
-                    }
-                }
-            }
-        }
-
-        warn!("Download summary: {} successful, {} failed", successful_downloads, failed_downloads);
+        download_recent_audio(state, &recent_videos).await;
     } else {
         info!("No new videos to process");
     }
@@ -610,37 +494,67 @@ pub async fn process_youtube_channel(
 }
 
 
-// Download YouTube audio using yt-dlp binary
-pub async fn download_youtube_audio(video_id: &str, output_path: &str) -> Result<(), AppError> {
-    // Remove .mp3 extension if present to prevent double extension
-    let base_path = if output_path.ends_with(".mp3") {
-        &output_path[..output_path.len() - 4]
-    } else {
-        output_path
-    };
+// Download the audio of newly listed videos that aren't on disk yet, filling in durations from
+// the files. Shared by YouTube and other yt-dlp sources; each video needs "id", "title" and "url".
+pub async fn download_recent_audio(state: &AppState, videos: &[serde_json::Value]) {
+    info!("Starting audio downloads");
+    let mut successful_downloads = 0;
+    let mut failed_downloads = 0;
 
-    let video_url = format!("https://www.youtube.com/watch?v={}", video_id);
+    for video in videos {
+        let video_id = video.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        let title = video.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let url = video.get("url").and_then(|v| v.as_str()).unwrap_or("");
 
-    let output = Command::new("yt-dlp")
-        .args(&[
-            "--format", "bestaudio/best",
-            "--extract-audio",
-            "--audio-format", "mp3",
-            "--output", base_path,
-            "--ignore-errors",
-            "--socket-timeout", "30",
-            &video_url
-        ])
-        .output()
-        .await
-        .map_err(|e| AppError::external_error(&format!("Failed to execute yt-dlp: {}", e)))?;
+        debug!("Processing download for video: {}", video_id);
+        info!("Title: {}", title);
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::external_error(&format!("yt-dlp download failed: {}", stderr)));
+        // Check if file already exists
+        if youtube_source::audio_path(video_id).is_some() {
+            debug!("Audio file already exists, skipping download");
+            continue;
+        }
+
+        info!("Starting download...");
+        match download_youtube_audio(video_id, url).await {
+            Ok(path) => {
+                info!("Download completed successfully");
+                successful_downloads += 1;
+                
+                // Get duration from the downloaded file and update database
+                if let Some(duration) = youtube_source::file_duration(&path).await {
+                    if let Err(e) = state.db_pool.update_youtube_video_duration(video_id, duration).await {
+                        warn!("Failed to update duration for video {}: {}", video_id, e);
+                    } else {
+                        debug!("Updated duration for video {} to {} seconds", video_id, duration);
+                    }
+                }
+            }
+            Err(e) => {
+                failed_downloads += 1;
+                let error_msg = e.to_string();
+                if error_msg.to_lowercase().contains("members-only") {
+                    debug!("Skipping video {} - Members-only content: {}", video_id, title);
+                } else if error_msg.to_lowercase().contains("private") {
+                    debug!("Skipping video {} - Private video: {}", video_id, title);
+                } else if error_msg.to_lowercase().contains("unavailable") {
+                    debug!("Skipping video {} - Unavailable video: {}", video_id, title);
+                } else {
+                    warn!("Failed to download video {}: {}", video_id, title);
+                    warn!("Error: {}", error_msg);
+                }
+            }
+        }
     }
 
-    Ok(())
+    warn!("Download summary: {} successful, {} failed", successful_downloads, failed_downloads);
+}
+
+// Download YouTube audio using yt-dlp binary, keeping the codec YouTube serves
+pub async fn download_youtube_audio(video_id: &str, page_url: &str) -> Result<std::path::PathBuf, AppError> {
+    youtube_source::download_audio(video_id, page_url)
+        .await
+        .map_err(|e| AppError::external_error(&e))
 }
 
 // Check if YouTube channel exists - matches Python api_check_youtube_channel function exactly
@@ -30,9 +30,12 @@ use config::Config;
 use database::DatabasePool;
 use error::AppResult;
 use redis_client::RedisClient;
-use services::{scheduler::BackgroundScheduler, task_manager::TaskManager, tasks::TaskSpawner};
+use services::{
+    playback_sync::PlaybackHub, scheduler::BackgroundScheduler, task_manager::TaskManager,
+    tasks::TaskSpawner,
+};
 use handlers::websocket::WebSocketManager;
-use redis_manager::{ImportProgressManager, NotificationManager};
+use redis_manager::ImportProgressManager;
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -43,8 +46,8 @@ pub struct AppState {
     pub task_manager: Arc<TaskManager>,
     pub task_spawner: Arc<TaskSpawner>,
     pub websocket_manager: Arc<WebSocketManager>,
+    pub playback_hub: Arc<PlaybackHub>,
     pub import_progress_manager: Arc<ImportProgressManager>,
-    pub notification_manager: Arc<NotificationManager>,
     /// Set while a full server restore is running. Used to reject concurrent restores
     /// and to block first-admin creation (which would otherwise race the restore and
     /// corrupt it). Shared across clones via Arc.
@@ -97,12 +100,21 @@ async fn main() -> AppResult<()> {
     let redis_client = RedisClient::new(&config).await?;
     info!("Redis/Valkey client initialized");
 
+    // Coordinate with other replicas sharing this database: locks, scheduler leadership and
+    // pub/sub fan-out all go through Redis
+    crate::services::cluster::init(redis_client.clone());
+    let instance_id = crate::services::cluster::claim_instance_id(&redis_client).await;
+    crate::services::cluster::spawn_leader_election(redis_client.clone());
+    info!("Cluster instance ID: {}", instance_id);
+
     // Initialize task management
     let task_manager = Arc::new(TaskManager::new(redis_client.clone()));
+    task_manager.spawn_fanout();
     let task_spawner = Arc::new(TaskSpawner::new(task_manager.clone(), db_pool.clone()));
     let websocket_manager = Arc::new(WebSocketManager::new());
+    let playback_hub = Arc::new(PlaybackHub::new(redis_client.clone()));
+    playback_hub.spawn_fanout();
     let import_progress_manager = Arc::new(ImportProgressManager::new(redis_client.clone()));
-    let notification_manager = Arc::new(NotificationManager::new());
     info!("Task management system initialized");
 
     // Create shared application state
@@ -113,8 +125,8 @@ async fn main() -> AppResult<()> {
         task_manager,
         task_spawner,
         websocket_manager,
+        playback_hub,
         import_progress_manager,
-        notification_manager,
         restore_in_progress: Arc::new(std::sync::atomic::AtomicBool::new(false)),
         ai_available: crate::services::ai_client::AiAvailability::new(),
     };
@@ -122,6 +134,9 @@ async fn main() -> AppResult<()> {
     // Start the AI sidecar health monitor (no-op if PINEPODS_AI_URL is unset).
     crate::services::ai_client::spawn_health_monitor(app_state.ai_available.clone());
 
+    // Pick up new files under the local-media root as they arrive.
+    crate::services::local_media_watch::spawn_watcher(app_state.db_pool.clone());
+
     // Build the application with routes
     let app = create_app(app_state.clone());
 
@@ -132,6 +147,10 @@ async fn main() -> AppResult<()> {
     
     // Start the scheduler with background tasks
     scheduler.start(scheduler_state.clone()).await?;
+
+    // Resume background jobs interrupted by the last shutdown (or abandoned by a replica that
+    // stopped) and start the worker pools
+    scheduler_state.task_spawner.jobs().start(scheduler_state.clone()).await?;
     
     // Run initial startup tasks immediately
     tokio::spawn({
@@ -170,6 +189,7 @@ fn openapi_router() -> OpenApiRouter<AppState> {
         // One routes!() call per distinct path; routes!() groups methods that share a path.
         .routes(routes!(handlers::health::pinepods_check))
         .routes(routes!(handlers::health::health_check))
+        .routes(routes!(handlers::health::metrics))
         // Partially-migrated groups (annotated handlers appear in the spec; the rest serve as plain routes)
         .nest("/api/data", create_data_routes())
         .nest("/api/episodes", create_episode_routes())
@@ -182,6 +202,7 @@ fn openapi_router() -> OpenApiRouter<AppState> {
         .nest("/api/proxy", create_proxy_routes())
         .nest("/api/gpodder", create_gpodder_routes())
         .nest("/api/feed", create_feed_routes())
+        .nest("/api/websub", create_websub_routes())
         .nest("/api/auth", create_auth_routes())
         .nest("/ws", OpenApiRouter::from(create_websocket_routes()))
 }
@@ -194,12 +215,18 @@ fn create_app(state: AppState) -> Router {
         // Routes/services that intentionally stay out of the API spec
         .route("/api/placeholder/{width}/{height}", get(handlers::proxy::placeholder_image))
         .nest_service("/api/local-media", ServeDir::new("/opt/pinepods/local-media"))
+        // gpodder.net API for AntennaPod and other gpodder clients
+        .merge(create_gpodder_api_routes())
         // Raw spec for tooling/docs builds, plus the interactive Scalar UI at /api/docs
         .route("/api/openapi.json", get(move || {
             let spec = spec.clone();
             async move { axum::Json(spec) }
         }))
         .merge(Scalar::with_url("/api/docs", api))
+        // Revoked/expired keys and API key scopes are enforced here for every route
+        .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::enforce_api_key_policy))
+        // Per-route latency/status histograms for /api/metrics
+        .layer(axum::middleware::from_fn(handlers::track_http_metrics))
         // Middleware stack
         .layer(
             ServiceBuilder::new()
@@ -306,6 +333,7 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::podcasts::get_extended_stats))
         .routes(routes!(handlers::podcasts::get_pinepods_version))
         .routes(routes!(handlers::podcasts::search_data))
+        .routes(routes!(handlers::podcasts::full_text_search))
         .routes(routes!(handlers::podcasts::proxy_search))
         .routes(routes!(handlers::podcasts::proxy_trending))
         .routes(routes!(handlers::podcasts::proxy_categories))
@@ -338,6 +366,7 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::podcasts::youtube_episodes))
         .routes(routes!(handlers::podcasts::remove_youtube_channel))
         .routes(routes!(handlers::podcasts::stream_episode))
+        .routes(routes!(handlers::podcasts::stream_chapters))
         .routes(routes!(handlers::podcasts::get_rss_key))
         .routes(routes!(handlers::podcasts::mark_episode_uncompleted))
         .routes(routes!(handlers::settings::set_theme))
@@ -375,7 +404,11 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::settings::get_api_info))
         .routes(routes!(handlers::settings::create_api_key))
         .routes(routes!(handlers::settings::delete_api_key))
+        .routes(routes!(handlers::settings::revoke_api_key))
         .routes(routes!(handlers::settings::backup_user))
+        .routes(routes!(handlers::settings::export_user_data))
+        .routes(routes!(handlers::settings::import_user_data))
+        .routes(routes!(handlers::settings::import_app_data))
         .routes(routes!(handlers::settings::backup_server))
         .routes(routes!(handlers::settings::restore_server))
         .routes(routes!(handlers::settings::restore_status))
@@ -426,6 +459,7 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::youtube::search_youtube_channels))
         .routes(routes!(handlers::youtube::subscribe_to_youtube_channel))
         .routes(routes!(handlers::youtube::check_youtube_channel))
+        .routes(routes!(handlers::media_source::subscribe_to_media_source))
         .routes(routes!(handlers::settings::enable_auto_download))
         .routes(routes!(handlers::settings::enable_auto_queue))
         .routes(routes!(handlers::settings::enable_auto_play_next))
@@ -445,6 +479,25 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::settings::get_auto_ad_detect))
         .routes(routes!(handlers::settings::adjust_ad_skip_auto_activate))
         .routes(routes!(handlers::settings::get_ad_skip_auto_activate))
+        .routes(routes!(handlers::settings::adjust_sponsorblock_categories))
+        .routes(routes!(handlers::settings::get_sponsorblock_categories))
+        .routes(routes!(handlers::settings::adjust_serve_cut_audio))
+        .routes(routes!(handlers::settings::get_serve_cut_audio))
+        .routes(routes!(handlers::playback::get_playback_state))
+        .routes(routes!(handlers::scrobble::get_scrobble_targets))
+        .routes(routes!(handlers::scrobble::add_scrobble_target))
+        .routes(routes!(handlers::scrobble::remove_scrobble_target))
+        .routes(routes!(handlers::webhooks::get_webhooks))
+        .routes(routes!(handlers::webhooks::add_webhook))
+        .routes(routes!(handlers::webhooks::remove_webhook))
+        .routes(routes!(handlers::webhooks::test_webhook))
+        .routes(routes!(handlers::webhooks::get_webhook_deliveries))
+        .routes(routes!(handlers::notification_channels::get_notification_channels))
+        .routes(routes!(handlers::notification_channels::add_notification_channel))
+        .routes(routes!(handlers::notification_channels::update_notification_channel))
+        .routes(routes!(handlers::notification_channels::remove_notification_channel))
+        .routes(routes!(handlers::notification_channels::test_notification_channel))
+        .routes(routes!(handlers::notification_channels::get_podcast_notification_channels, handlers::notification_channels::set_podcast_notification_channels))
         .routes(routes!(handlers::settings::get_ai_settings, handlers::settings::update_ai_settings))
         .routes(routes!(handlers::settings::get_ai_models))
         .routes(routes!(handlers::settings::ai_pull_model))
@@ -454,6 +507,11 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::settings::clear_podcast_playback_speed))
         .routes(routes!(handlers::settings::set_podcast_auto_download_delete_days))
         .routes(routes!(handlers::settings::clear_podcast_auto_download_delete_days))
+        .routes(routes!(handlers::settings::adjust_podcast_retention))
+        .routes(routes!(handlers::settings::get_podcast_retention))
+        .routes(routes!(handlers::settings::get_storage_usage))
+        .routes(routes!(handlers::settings::get_storage_usage_all))
+        .routes(routes!(handlers::settings::set_user_quota))
         .routes(routes!(handlers::settings::set_podcast_cover_preference))
         .routes(routes!(handlers::settings::clear_podcast_cover_preference))
         .routes(routes!(handlers::settings::toggle_podcast_notifications))
@@ -480,6 +538,22 @@ fn create_data_routes() -> OpenApiRouter<AppState> {
         // Add more data routes as needed
 }
 
+fn create_gpodder_api_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/2/auth/{username}/login.json", post(handlers::gpodder_api::login))
+        .route("/api/2/auth/{username}/logout.json", post(handlers::gpodder_api::logout))
+        .route("/api/2/devices/{file}", get(handlers::gpodder_api::list_devices))
+        .route("/api/2/devices/{username}/{file}", post(handlers::gpodder_api::update_device))
+        .route("/api/2/updates/{username}/{file}", get(handlers::gpodder_api::device_updates))
+        .route("/api/2/subscriptions/{file}", get(handlers::gpodder_api::all_subscriptions))
+        .route("/api/2/subscriptions/{username}/{file}", get(handlers::gpodder_api::subscription_changes).post(handlers::gpodder_api::upload_subscription_changes))
+        .route("/api/2/episodes/{file}", get(handlers::gpodder_api::episode_actions).post(handlers::gpodder_api::upload_episode_actions))
+        .route("/api/2/sync-devices/{file}", get(handlers::gpodder_api::sync_devices).post(handlers::gpodder_api::update_sync_devices))
+        // Simple API (v1)
+        .route("/subscriptions/{file}", get(handlers::gpodder_api::all_subscriptions))
+        .route("/subscriptions/{username}/{file}", get(handlers::gpodder_api::device_subscriptions).put(handlers::gpodder_api::replace_subscriptions))
+}
+
 fn create_podcast_routes() -> Router<AppState> {
     Router::new()
         .route("/notification_status", post(handlers::podcasts::get_notification_status))
@@ -500,6 +574,7 @@ fn create_task_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::websocket::get_user_tasks))
         .routes(routes!(handlers::websocket::get_active_tasks))
         .routes(routes!(handlers::websocket::get_task_status))
+        .routes(routes!(handlers::websocket::cancel_task))
 }
 
 fn create_async_routes() -> Router<AppState> {
@@ -537,10 +612,16 @@ fn create_feed_routes() -> OpenApiRouter<AppState> {
         .routes(routes!(handlers::feed::get_user_feed))
 }
 
+fn create_websub_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handlers::websub::websub_verify, handlers::websub::websub_push))
+}
+
 fn create_websocket_routes() -> Router<AppState> {
     Router::new()
         .route("/api/tasks/{user_id}", get(handlers::websocket::task_progress_websocket))
         .route("/api/data/episodes/{user_id}", get(handlers::refresh::websocket_refresh_episodes))
+        .route("/api/playback/{user_id}", get(handlers::playback::playback_websocket))
 }
 
 fn create_auth_routes() -> OpenApiRouter<AppState> {
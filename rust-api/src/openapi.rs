@@ -31,11 +31,15 @@ use utoipa::{Modify, OpenApi};
         (name = "playlists", description = "Smart and manual playlists"),
         (name = "settings", description = "User and server settings"),
         (name = "sync", description = "gpodder / Nextcloud synchronization"),
+        (name = "scrobble", description = "Scrobbling listens to ListenBrainz and webhooks"),
+        (name = "webhooks", description = "Signed outbound webhooks for server events"),
+        (name = "notifications", description = "New-episode notification channels and per-podcast routing"),
         (name = "tasks", description = "Background tasks and progress"),
         (name = "feed", description = "Public RSS feed generation"),
         (name = "proxy", description = "Media and image proxying"),
         (name = "local", description = "Local podcasts and media"),
-        (name = "youtube", description = "YouTube channel integration"),
+        (name = "youtube", description = "YouTube channels and other yt-dlp media sites"),
+        (name = "websub", description = "WebSub hub callbacks for push feed updates (no auth)"),
     ),
 )]
 pub struct ApiDoc;
@@ -3,6 +3,7 @@ use crate::{config::Config, error::AppResult};
 
 #[derive(Clone)]
 pub struct RedisClient {
+    client: Client,
     connection: ConnectionManager,
 }
 
@@ -11,11 +12,12 @@ impl RedisClient {
         let redis_url = config.redis_url();
         
         let client = Client::open(redis_url)?;
-        let connection = ConnectionManager::new(client).await?;
+        let connection = ConnectionManager::new(client.clone()).await?;
 
         tracing::info!("Successfully connected to Redis/Valkey");
         
         Ok(RedisClient {
+            client,
             connection,
         })
     }
@@ -110,6 +112,24 @@ impl RedisClient {
         self.get(&cache_key).await
     }
 
+    // Scope/expiry policy of an API key, cached as JSON for the key-policy middleware
+    pub async fn cache_api_key_policy(&self, api_key: &str, policy_json: &str, ttl_seconds: u64) -> AppResult<()> {
+        let cache_key = format!("api_key_policy:{}", api_key);
+        self.set_ex(&cache_key, policy_json, ttl_seconds).await
+    }
+
+    pub async fn get_cached_api_key_policy(&self, api_key: &str) -> AppResult<Option<String>> {
+        let cache_key = format!("api_key_policy:{}", api_key);
+        self.get(&cache_key).await
+    }
+
+    // Drop every cached fact about a key so a revocation takes effect immediately
+    pub async fn invalidate_api_key_cache(&self, api_key: &str) -> AppResult<()> {
+        self.delete(&format!("api_key:{}", api_key)).await?;
+        self.delete(&format!("api_key_policy:{}", api_key)).await?;
+        Ok(())
+    }
+
     // Rate limiting
     pub async fn check_rate_limit(&self, identifier: &str, limit: u32, window_seconds: u64) -> AppResult<bool> {
         let rate_key = format!("rate_limit:{}", identifier);
@@ -158,6 +178,57 @@ impl RedisClient {
         Ok(result)
     }
 
+    // Pub/sub, used to fan events out to every replica
+    pub async fn publish(&self, channel: &str, payload: &str) -> AppResult<()> {
+        let mut conn = self.connection.clone();
+        let _: i64 = conn.publish(channel, payload).await?;
+        Ok(())
+    }
+
+    // Subscriptions need a dedicated connection; the managed one is multiplexed
+    pub async fn pubsub(&self) -> AppResult<redis::aio::PubSub> {
+        Ok(self.client.get_async_pubsub().await?)
+    }
+
+    // Distributed locks: `owner` is a random token so only the holder can renew or release
+    pub async fn try_lock(&self, key: &str, owner: &str, ttl_seconds: u64) -> AppResult<bool> {
+        let mut conn = self.connection.clone();
+        let result: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(owner)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async(&mut conn)
+            .await?;
+        Ok(result.is_some())
+    }
+
+    pub async fn renew_lock(&self, key: &str, owner: &str, ttl_seconds: u64) -> AppResult<bool> {
+        let mut conn = self.connection.clone();
+        let renewed: i64 = redis::Script::new(
+            "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('EXPIRE', KEYS[1], ARGV[2]) else return 0 end",
+        )
+        .key(key)
+        .arg(owner)
+        .arg(ttl_seconds)
+        .invoke_async(&mut conn)
+        .await?;
+        Ok(renewed == 1)
+    }
+
+    pub async fn unlock(&self, key: &str, owner: &str) -> AppResult<bool> {
+        let mut conn = self.connection.clone();
+        let released: i64 = redis::Script::new(
+            "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('DEL', KEYS[1]) else return 0 end",
+        )
+        .key(key)
+        .arg(owner)
+        .invoke_async(&mut conn)
+        .await?;
+        Ok(released == 1)
+    }
+
     // Get a connection for direct Redis operations
     pub async fn get_connection(&self) -> AppResult<ConnectionManager> {
         Ok(self.connection.clone())
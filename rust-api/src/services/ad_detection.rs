@@ -2,35 +2,21 @@
 //! optional `pinepods-ai` sidecar's LLM, which labels ad spans, and stores those as
 //! `EpisodeSkipSegments` rows with `Kind='ad'`/`Source='auto-ad'`.
 //!
-//! Ad segments are content-level (one detection per episode, shared across subscribers). Because
+//! Ad segments are content-level (one detection per episode, shared across subscribers): they are
+//! stored against the episode's shared content key (see `shared_feeds::ContentKey`). Because
 //! this is a multi-user app, the *review/skip* decision is per-user: `EpisodeAdSkipReview` holds
 //! each user's per-segment override, falling back to the podcast's `AdSkipAutoActivate` default.
 
 use crate::database::DatabasePool;
 use crate::services::ai_client::AiSegment;
-use crate::services::{ai_client, ai_settings, transcription};
+use crate::services::shared_feeds::{self, ContentFlag, ContentKey};
+use crate::services::{ai_client, ai_settings, cluster, transcription};
 use serde::Serialize;
 use sqlx::Row;
-use std::collections::HashSet;
-use std::sync::{Mutex, OnceLock};
 use tracing::{debug, warn};
 
-/// Episodes currently being ad-scanned, so two triggers (e.g. the post-transcription chain and a
-/// manual request) don't run the LLM twice for the same episode.
-fn in_flight() -> &'static Mutex<HashSet<i32>> {
-    static S: OnceLock<Mutex<HashSet<i32>>> = OnceLock::new();
-    S.get_or_init(|| Mutex::new(HashSet::new()))
-}
-
-/// RAII marker removing the episode from the in-flight set on drop.
-struct InFlightGuard(i32);
-impl Drop for InFlightGuard {
-    fn drop(&mut self) {
-        if let Ok(mut set) = in_flight().lock() {
-            set.remove(&self.0);
-        }
-    }
-}
+/// Lease on an episode's ad-detection lock, renewed while the LLM pass runs.
+const IN_FLIGHT_TTL_SECONDS: u64 = 120;
 
 /// Source tag written to `EpisodeSkipSegments.Source` for AI-detected ads.
 pub const SOURCE_AD: &str = "auto-ad";
@@ -45,101 +31,71 @@ pub struct SkipSegmentView {
     pub start_time: f64,
     pub end_time: f64,
     pub source: String,
-    /// For `kind='ad'`: `"active"`/`"confirmed"` (skip), `"pending"`/`"rejected"` (don't skip).
-    /// `None` for non-ad kinds (e.g. silence).
+    /// For `kind='ad'` and SponsorBlock segments: `"active"`/`"confirmed"` (skip),
+    /// `"pending"`/`"rejected"` (don't skip). `None` for other kinds (e.g. silence).
     pub status: Option<String>,
 }
 
-/// Whether ad detection has already run for this episode (its own guard column).
-async fn already_detected(db_pool: &DatabasePool, episode_id: i32) -> bool {
-    match db_pool {
-        DatabasePool::Postgres(pool) => sqlx::query(
-            r#"SELECT COALESCE(adsdetected, FALSE) AS d FROM "Episodes" WHERE episodeid = $1"#,
-        )
-        .bind(episode_id)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .and_then(|r| r.try_get::<bool, _>("d").ok())
-        .unwrap_or(false),
-        DatabasePool::MySQL(pool) => sqlx::query(
-            "SELECT COALESCE(AdsDetected, 0) AS d FROM Episodes WHERE EpisodeID = ?",
-        )
-        .bind(episode_id)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .and_then(|r| r.try_get::<i8, _>("d").ok())
-        .map(|d| d != 0)
-        .unwrap_or(false),
-    }
-}
-
-/// Replace this episode's ad segments (scoped by Source so silence rows are untouched) and mark
-/// it analyzed. Returns the number of ad ranges written.
+/// Replace the content key's ad segments (scoped by Source so silence rows are untouched) and
+/// mark it analyzed. Returns the number of ad ranges written.
 async fn store_ad_segments(
     db_pool: &DatabasePool,
-    episode_id: i32,
+    key: ContentKey,
     segments: &[(f64, f64)],
 ) -> Result<usize, String> {
     match db_pool {
         DatabasePool::Postgres(pool) => {
-            sqlx::query(r#"DELETE FROM "EpisodeSkipSegments" WHERE episodeid = $1 AND source = $2"#)
-                .bind(episode_id)
+            let delete = format!(r#"DELETE FROM "EpisodeSkipSegments" WHERE {} = $1 AND source = $2"#, key.pg_column());
+            sqlx::query(sqlx::AssertSqlSafe(delete.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_AD)
                 .execute(pool)
                 .await
                 .map_err(|e| e.to_string())?;
+            let insert = format!(
+                r#"INSERT INTO "EpisodeSkipSegments" ({}, kind, starttime, endtime, source) VALUES ($1, $2, $3, $4, $5)"#,
+                key.pg_column()
+            );
             for (start, end) in segments {
-                sqlx::query(r#"
-                    INSERT INTO "EpisodeSkipSegments" (episodeid, kind, starttime, endtime, source)
-                    VALUES ($1, $2, $3, $4, $5)
-                "#)
-                .bind(episode_id)
-                .bind(KIND_AD)
-                .bind(*start)
-                .bind(*end)
-                .bind(SOURCE_AD)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(key.id())
+                    .bind(KIND_AD)
+                    .bind(*start)
+                    .bind(*end)
+                    .bind(SOURCE_AD)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
             }
-            sqlx::query(r#"UPDATE "Episodes" SET adsdetected = TRUE WHERE episodeid = $1"#)
-                .bind(episode_id)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
         }
         DatabasePool::MySQL(pool) => {
-            sqlx::query("DELETE FROM EpisodeSkipSegments WHERE EpisodeID = ? AND Source = ?")
-                .bind(episode_id)
+            let delete = format!("DELETE FROM EpisodeSkipSegments WHERE {} = ? AND Source = ?", key.mysql_column());
+            sqlx::query(sqlx::AssertSqlSafe(delete.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_AD)
                 .execute(pool)
                 .await
                 .map_err(|e| e.to_string())?;
+            let insert = format!(
+                "INSERT INTO EpisodeSkipSegments ({}, Kind, StartTime, EndTime, Source) VALUES (?, ?, ?, ?, ?)",
+                key.mysql_column()
+            );
             for (start, end) in segments {
-                sqlx::query("
-                    INSERT INTO EpisodeSkipSegments (EpisodeID, Kind, StartTime, EndTime, Source)
-                    VALUES (?, ?, ?, ?, ?)
-                ")
-                .bind(episode_id)
-                .bind(KIND_AD)
-                .bind(*start)
-                .bind(*end)
-                .bind(SOURCE_AD)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(key.id())
+                    .bind(KIND_AD)
+                    .bind(*start)
+                    .bind(*end)
+                    .bind(SOURCE_AD)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
             }
-            sqlx::query("UPDATE Episodes SET AdsDetected = TRUE WHERE EpisodeID = ?")
-                .bind(episode_id)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
         }
     }
+    shared_feeds::set_content_flag(db_pool, key, ContentFlag::AdsDetected)
+        .await
+        .map_err(|e| e.to_string())?;
     Ok(segments.len())
 }
 
@@ -181,28 +137,41 @@ pub async fn detect_episode_ads(
     if ai_client::ai_base_url().is_none() {
         return Err("AI service not configured".to_string());
     }
-    if !force && already_detected(db_pool, episode_id).await {
+    let content = shared_feeds::content_key(db_pool, episode_id).await;
+    if !force && shared_feeds::content_flag(db_pool, content, ContentFlag::AdsDetected).await {
         debug!("Episode {} already ad-scanned; skipping", episode_id);
         return Ok(0);
     }
 
-    // Skip if another detection for this episode is already running.
-    {
-        let mut set = in_flight().lock().map_err(|_| "in-flight lock poisoned".to_string())?;
-        if set.contains(&episode_id) {
-            debug!("Episode {} ad detection already in progress; skipping", episode_id);
-            return Ok(0);
-        }
-        set.insert(episode_id);
-    }
-    let _guard = InFlightGuard(episode_id);
+    // Skip if another detection for this feed episode is already running on any replica, so two
+    // triggers (e.g. the post-transcription chain and a manual request) don't run the LLM twice.
+    let lock_name = format!("ad_detection:{}", content);
+    let Some(_in_flight) = cluster::try_lock(&lock_name, IN_FLIGHT_TTL_SECONDS)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        debug!("Episode {} ad detection already in progress; skipping", episode_id);
+        return Ok(0);
+    };
 
     let segments = ensure_transcript_segments(db_pool, episode_id).await?;
     let llm = ai_settings::resolve_llm_spec(db_pool).await?;
     let result = ai_client::detect_ads(&segments, None, &llm, on_progress).await?;
     let spans: Vec<(f64, f64)> = result.segments.iter().map(|s| (s.start, s.end)).collect();
-    let n = store_ad_segments(db_pool, episode_id, &spans).await?;
+    let n = store_ad_segments(db_pool, content, &spans).await?;
     debug!("Ad detection stored {} ad span(s) for episode {}", n, episode_id);
+    if n > 0 {
+        crate::services::webhooks::emit_episode_event(
+            db_pool,
+            episode_id,
+            crate::services::webhooks::WebhookEvent::AdsDetected,
+            serde_json::json!({
+                "ad_count": n,
+                "ad_seconds": spans.iter().map(|(start, end)| end - start).sum::<f64>().round(),
+            }),
+        );
+    }
+    crate::services::audio_cut::maybe_prerender(db_pool.clone(), episode_id);
     Ok(n)
 }
 
@@ -218,6 +187,11 @@ pub fn maybe_detect_ads_after_transcript(db_pool: DatabasePool, episode_id: i32)
                 SELECT EXISTS(
                     SELECT 1 FROM "Episodes" e JOIN "Podcasts" p ON e.podcastid = p.podcastid
                     WHERE e.episodeid = $1 AND COALESCE(p.autoaddetect, FALSE) = TRUE
+                    UNION ALL
+                    SELECT 1 FROM "Episodes" c
+                    JOIN "Episodes" e ON e.feedepisodeid = c.feedepisodeid
+                    JOIN "Podcasts" p ON e.podcastid = p.podcastid
+                    WHERE c.episodeid = $1 AND COALESCE(p.autoaddetect, FALSE) = TRUE
                 ) AS any_on
             "#)
             .bind(episode_id)
@@ -231,9 +205,15 @@ pub fn maybe_detect_ads_after_transcript(db_pool: DatabasePool, episode_id: i32)
                 SELECT EXISTS(
                     SELECT 1 FROM Episodes e JOIN Podcasts p ON e.PodcastID = p.PodcastID
                     WHERE e.EpisodeID = ? AND COALESCE(p.AutoAdDetect, 0) = 1
+                    UNION ALL
+                    SELECT 1 FROM Episodes c
+                    JOIN Episodes e ON e.FeedEpisodeID = c.FeedEpisodeID
+                    JOIN Podcasts p ON e.PodcastID = p.PodcastID
+                    WHERE c.EpisodeID = ? AND COALESCE(p.AutoAdDetect, 0) = 1
                 ) AS any_on
             ")
             .bind(episode_id)
+            .bind(episode_id)
             .fetch_optional(pool)
             .await
             .ok()
@@ -244,6 +224,7 @@ pub fn maybe_detect_ads_after_transcript(db_pool: DatabasePool, episode_id: i32)
         };
 
         if any_opted_in {
+            let _permit = crate::services::job_queue::acquire(crate::services::job_queue::WorkerPool::AdDetection).await;
             if let Err(e) = detect_episode_ads(&db_pool, episode_id, false, |_| {}).await {
                 warn!("Auto ad-detection failed for episode {}: {}", episode_id, e);
             }
@@ -363,7 +344,8 @@ pub async fn set_ad_segment_review(
 }
 
 /// Read an episode's skip segments for a specific user, resolving each ad segment's effective
-/// status (per-user override, else the podcast's auto-activate default). Silence segments carry
+/// status (per-user override, else the user's own podcast's auto-activate default). Segments are
+/// read from the episode's shared content key. Silence segments carry
 /// `status = None`. Replaces the plain `audio_processing::get_episode_skip_segments` on the
 /// client-facing read path so the player and the transcript review UI share one shape.
 pub async fn get_episode_skip_segments_for_user(
@@ -371,19 +353,20 @@ pub async fn get_episode_skip_segments_for_user(
     user_id: i32,
     episode_id: i32,
 ) -> Result<Vec<SkipSegmentView>, String> {
+    let content = shared_feeds::content_key(db_pool, episode_id).await;
     let rows: Vec<(i32, String, f64, f64, String, Option<String>, bool)> = match db_pool {
-        DatabasePool::Postgres(pool) => sqlx::query(r#"
+        DatabasePool::Postgres(pool) => sqlx::query(sqlx::AssertSqlSafe(format!(r#"
             SELECT s.segmentid, s.kind, s.starttime, s.endtime, s.source,
                    r.status AS review_status,
                    COALESCE(p.adskipautoactivate, TRUE) AS auto_activate
             FROM "EpisodeSkipSegments" s
             LEFT JOIN "EpisodeAdSkipReview" r ON r.segmentid = s.segmentid AND r.userid = $2
-            LEFT JOIN "Episodes" e ON e.episodeid = s.episodeid
+            LEFT JOIN "Episodes" e ON e.episodeid = $3
             LEFT JOIN "Podcasts" p ON p.podcastid = e.podcastid AND p.userid = $2
-            WHERE s.episodeid = $1
+            WHERE s.{} = $1
             ORDER BY s.starttime
-        "#)
-        .bind(episode_id).bind(user_id)
+        "#, content.pg_column())))
+        .bind(content.id()).bind(user_id).bind(episode_id)
         .fetch_all(pool).await.map_err(|e| e.to_string())?
         .into_iter()
         .map(|r| (
@@ -396,19 +379,19 @@ pub async fn get_episode_skip_segments_for_user(
             r.try_get::<bool, _>("auto_activate").unwrap_or(true),
         ))
         .collect(),
-        DatabasePool::MySQL(pool) => sqlx::query(r#"
+        DatabasePool::MySQL(pool) => sqlx::query(sqlx::AssertSqlSafe(format!(r#"
             SELECT s.SegmentID AS segmentid, s.Kind AS kind, s.StartTime AS starttime,
                    s.EndTime AS endtime, s.Source AS source,
                    r.Status AS review_status,
                    COALESCE(p.AdSkipAutoActivate, 1) AS auto_activate
             FROM EpisodeSkipSegments s
             LEFT JOIN EpisodeAdSkipReview r ON r.SegmentID = s.SegmentID AND r.UserID = ?
-            LEFT JOIN Episodes e ON e.EpisodeID = s.EpisodeID
+            LEFT JOIN Episodes e ON e.EpisodeID = ?
             LEFT JOIN Podcasts p ON p.PodcastID = e.PodcastID AND p.UserID = ?
-            WHERE s.EpisodeID = ?
+            WHERE s.{} = ?
             ORDER BY s.StartTime
-        "#)
-        .bind(user_id).bind(user_id).bind(episode_id)
+        "#, content.mysql_column())))
+        .bind(user_id).bind(episode_id).bind(user_id).bind(content.id())
         .fetch_all(pool).await.map_err(|e| e.to_string())?
         .into_iter()
         .map(|r| (
@@ -0,0 +1,198 @@
+// Scope model for API keys. A key's `Scopes` column holds a comma-separated list of
+// scope names; NULL means the key is unrestricted (every key issued at login, and
+// every key created before scopes existed). Scoped keys are checked centrally by
+// `handlers::enforce_api_key_policy` against the request's method + path, so
+// individual handlers never need to know which kind of key called them.
+
+use axum::http::Method;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    /// Read-only access: GET requests plus the POST endpoints that only look data up.
+    Read,
+    /// Read access plus listen progress, completion, queue and saved-episode state.
+    Playback,
+    /// The user's RSS feed and subscription refresh only.
+    Feed,
+    /// Everything the owning user can do (same as an unscoped key).
+    Admin,
+}
+
+/// POST endpoints that only read data (the API predates a strict GET/POST split).
+const READ_ONLY_POSTS: &[&str] = &[
+    "/api/data/get_episode_metadata",
+    "/api/data/get_auto_download_status",
+    "/api/data/get_auto_queue_status",
+    "/api/data/get_auto_play_next_status",
+    "/api/data/get_next_podcast_episode",
+    "/api/data/get_next_playlist_episode",
+    "/api/data/podcast/notification_status",
+    "/api/data/podcast/favorite_status",
+    "/api/data/get_play_episode_details",
+    "/api/data/search_data",
+    "/api/data/fetch_transcript",
+    "/api/data/get_playback_speed",
+    "/api/data/get_auto_download_delete_days",
+    "/api/data/get_default_volume",
+    "/api/podcasts/notification_status",
+];
+
+/// GET endpoints that kick off work and so are not "read" for scope purposes.
+const MUTATING_GETS: &[&str] = &[
+    "/api/data/refresh_pods",
+    "/api/data/refresh_gpodder_subscriptions",
+    "/api/data/refresh_nextcloud_subscriptions",
+    "/api/data/cleanup_tasks",
+    "/api/data/update_playlists",
+    "/api/data/refresh_hosts",
+    "/api/data/auto_complete_episodes",
+    "/api/data/get_key",
+    // The upgrade is a GET, but the socket then sends playback state and commands.
+    "/ws/api/playback/",
+];
+
+/// Playback-state writes allowed for `playback` keys.
+const PLAYBACK_PATHS: &[&str] = &[
+    "/api/data/record_podcast_history",
+    "/api/data/record_listen_duration",
+    "/api/data/increment_listen_time/",
+    "/api/data/increment_played/",
+    "/api/data/update_episode_duration",
+    "/api/data/mark_episode_completed",
+    "/api/data/mark_episode_uncompleted",
+    "/api/data/bulk_mark_episodes_completed",
+    "/api/data/queue_pod",
+    "/api/data/remove_queued_pod",
+    "/api/data/reorder_queue",
+    "/api/data/clear_queue",
+    "/api/data/bulk_queue_episodes",
+    "/api/data/save_episode",
+    "/api/data/remove_saved_episode",
+    "/api/data/bulk_save_episodes",
+    "/api/2/episodes/",
+    "/ws/api/playback/",
+];
+
+/// Paths reachable by `feed` keys (any method).
+const FEED_PATHS: &[&str] = &[
+    "/api/feed/",
+    "/api/data/refresh_pods",
+    "/ws/api/data/episodes/",
+];
+
+/// Match either an exact path or, for entries ending in '/', a path prefix.
+fn matches(list: &[&str], path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    list.iter().any(|entry| {
+        if entry.ends_with('/') {
+            path.starts_with(entry)
+        } else {
+            path == *entry
+        }
+    })
+}
+
+impl ApiScope {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "read" => Some(ApiScope::Read),
+            "playback" => Some(ApiScope::Playback),
+            "feed" => Some(ApiScope::Feed),
+            "admin" => Some(ApiScope::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::Read => "read",
+            ApiScope::Playback => "playback",
+            ApiScope::Feed => "feed",
+            ApiScope::Admin => "admin",
+        }
+    }
+
+    /// Whether this single scope lets a key make `method` on `path`.
+    pub fn permits(&self, method: &Method, path: &str) -> bool {
+        let is_read = if *method == Method::GET || *method == Method::HEAD {
+            !matches(MUTATING_GETS, path)
+        } else {
+            *method == Method::POST && matches(READ_ONLY_POSTS, path)
+        };
+
+        match self {
+            ApiScope::Admin => true,
+            ApiScope::Read => is_read,
+            ApiScope::Playback => is_read || matches(PLAYBACK_PATHS, path),
+            ApiScope::Feed => matches(FEED_PATHS, path),
+        }
+    }
+}
+
+/// Parse the stored comma-separated scope list. Unknown names are rejected so a typo
+/// can never silently widen (or narrow) what a key is allowed to do.
+pub fn parse_scopes(raw: &str) -> Result<Vec<ApiScope>, String> {
+    let mut scopes = Vec::new();
+    for name in raw.split(',').filter(|s| !s.trim().is_empty()) {
+        let scope = ApiScope::parse(name).ok_or_else(|| format!("Unknown API key scope: {}", name.trim()))?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if scopes.is_empty() {
+        return Err("A scoped API key needs at least one scope".to_string());
+    }
+    Ok(scopes)
+}
+
+pub fn format_scopes(scopes: &[ApiScope]) -> String {
+    scopes.iter().map(ApiScope::as_str).collect::<Vec<_>>().join(",")
+}
+
+/// A request is allowed if any of the key's scopes permits it.
+pub fn scopes_permit(scopes: &[ApiScope], method: &Method, path: &str) -> bool {
+    scopes.iter().any(|scope| scope.permits(method, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_scope_allows_gets_and_lookup_posts_only() {
+        let read = [ApiScope::Read];
+        assert!(scopes_permit(&read, &Method::GET, "/api/data/return_pods/2"));
+        assert!(scopes_permit(&read, &Method::POST, "/api/data/search_data"));
+        assert!(!scopes_permit(&read, &Method::GET, "/api/data/refresh_pods"));
+        assert!(!scopes_permit(&read, &Method::POST, "/api/data/add_podcast"));
+        assert!(!scopes_permit(&read, &Method::POST, "/api/data/record_listen_duration"));
+        assert!(!scopes_permit(&read, &Method::GET, "/ws/api/playback/2"));
+    }
+
+    #[test]
+    fn playback_scope_allows_state_writes() {
+        let playback = [ApiScope::Playback];
+        assert!(scopes_permit(&playback, &Method::POST, "/api/data/record_listen_duration"));
+        assert!(scopes_permit(&playback, &Method::PUT, "/api/data/increment_listen_time/2"));
+        assert!(scopes_permit(&playback, &Method::GET, "/api/data/get_queued_episodes"));
+        assert!(scopes_permit(&playback, &Method::GET, "/ws/api/playback/2"));
+        assert!(!scopes_permit(&playback, &Method::DELETE, "/api/data/delete_api_key"));
+    }
+
+    #[test]
+    fn feed_scope_is_limited_to_feed_and_refresh() {
+        let feed = [ApiScope::Feed];
+        assert!(scopes_permit(&feed, &Method::GET, "/api/feed/2"));
+        assert!(scopes_permit(&feed, &Method::GET, "/api/data/refresh_pods"));
+        assert!(!scopes_permit(&feed, &Method::GET, "/api/data/return_pods/2"));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_dedupes() {
+        assert_eq!(parse_scopes("read, playback,read").unwrap(), vec![ApiScope::Read, ApiScope::Playback]);
+        assert!(parse_scopes("read,everything").is_err());
+        assert!(parse_scopes(" , ").is_err());
+        assert_eq!(format_scopes(&[ApiScope::Feed, ApiScope::Admin]), "feed,admin");
+    }
+}
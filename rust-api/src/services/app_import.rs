@@ -0,0 +1,493 @@
+//! Import subscriptions and listening state exported from other podcast apps.
+//!
+//! Each source is first parsed into a [`Library`]: the feeds to subscribe to and per-episode
+//! state (position, played, favourite, queued). Applying a library subscribes to missing feeds,
+//! then matches episodes by GUID and enclosure URL the same way the PinePods user import does,
+//! and replays state through the regular `save_episode_history` / `mark_episode_completed` /
+//! `save_episode` / `queue_episode` paths. Imports are additive; nothing is unplayed or removed.
+//!
+//! Supported sources:
+//! - **AntennaPod**: the database export (Settings → Import/Export → Database export), a SQLite
+//!   file with its feeds, played flags, positions, favourites and queue.
+//! - **Pocket Casts**: an OPML export for subscriptions plus the listening history JSON
+//!   (`{"episodes": [...]}` as returned by Pocket Casts' `/user/history`).
+//! - **gpodder**: episode actions JSON (a list, or `{"actions": [...]}` from
+//!   `/api/2/episodes`), with an optional OPML for subscriptions. Podcast Addict and most other
+//!   gpodder-syncing apps can produce this.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::user_export::{episode_index, find_podcast, EpisodeIndex};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use sqlx::{ConnectOptions, Row};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    AntennaPod,
+    PocketCasts,
+    Gpodder,
+}
+
+impl Source {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "antennapod" => Some(Source::AntennaPod),
+            "pocketcasts" | "pocket_casts" => Some(Source::PocketCasts),
+            "gpodder" => Some(Source::Gpodder),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::AntennaPod => "AntennaPod",
+            Source::PocketCasts => "Pocket Casts",
+            Source::Gpodder => "gpodder",
+        }
+    }
+}
+
+/// Listening state for one episode, as exported by the other app.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EpisodeState {
+    /// None when the source doesn't say which feed the episode belongs to (Pocket Casts).
+    pub feed_url: Option<String>,
+    pub guid: Option<String>,
+    pub url: Option<String>,
+    /// Resume position in seconds.
+    pub position: i32,
+    pub duration: i32,
+    pub completed: bool,
+    pub favourite: bool,
+    /// Position in the source app's queue.
+    pub queue_position: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Library {
+    pub feeds: Vec<String>,
+    pub episodes: Vec<EpisodeState>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AppImportReport {
+    pub podcasts_added: usize,
+    pub podcasts_existing: usize,
+    pub podcasts_failed: Vec<String>,
+    pub episodes_matched: usize,
+    pub episodes_unmatched: usize,
+    pub positions_restored: usize,
+    pub episodes_completed: usize,
+    pub favourites_restored: usize,
+    pub episodes_queued: usize,
+}
+
+/// Feed URLs from an OPML document.
+pub fn parse_opml(opml: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(opml);
+    let mut feeds = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name().as_ref() == b"outline" => {
+                for attr in e.attributes().with_checks(false).flatten() {
+                    if attr.key.as_ref().eq_ignore_ascii_case(b"xmlUrl") {
+                        let url = String::from_utf8_lossy(&attr.value).trim().replace("&amp;", "&");
+                        if !url.is_empty() && !feeds.contains(&url) {
+                            feeds.push(url);
+                        }
+                    }
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    feeds
+}
+
+// ---- AntennaPod ----
+
+/// Read an AntennaPod database export. Times in AntennaPod's DB are milliseconds.
+pub async fn parse_antennapod(path: &Path) -> AppResult<Library> {
+    let mut conn = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .map_err(|e| AppError::bad_request(format!("Not an AntennaPod database export: {}", e)))?;
+
+    // Feeds gained a subscription state (kept-but-unsubscribed feeds) in AntennaPod 3.
+    let has_state = sqlx::query("SELECT 1 FROM pragma_table_info('Feeds') WHERE name = 'state'")
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|e| AppError::bad_request(format!("Not an AntennaPod database export: {}", e)))?
+        .is_some();
+    let sql = format!(
+        "SELECT id, download_url FROM Feeds WHERE download_url IS NOT NULL{}",
+        if has_state { " AND state = 0" } else { "" }
+    );
+    let mut feeds = Vec::new();
+    let mut feed_urls = HashMap::new();
+    for row in sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).fetch_all(&mut conn).await? {
+        let url: String = row.try_get("download_url")?;
+        // Local folders ("antennapod_local:content://...") only exist on the phone.
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            continue;
+        }
+        feed_urls.insert(row.try_get::<i64, _>("id")?, url.clone());
+        feeds.push(url);
+    }
+
+    let rows = sqlx::query(
+        "SELECT i.id, i.feed, i.item_identifier, i.read, m.download_url, m.position, m.duration,
+                EXISTS (SELECT 1 FROM Favorites f WHERE f.feeditem = i.id) AS favourite,
+                (SELECT q.id FROM Queue q WHERE q.feeditem = i.id) AS queue_position
+         FROM FeedItems i LEFT JOIN FeedMedia m ON m.feeditem = i.id
+         WHERE i.read = 1 OR m.position > 0
+            OR EXISTS (SELECT 1 FROM Favorites f WHERE f.feeditem = i.id)
+            OR EXISTS (SELECT 1 FROM Queue q WHERE q.feeditem = i.id)",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+    let mut episodes = Vec::with_capacity(rows.len());
+    for row in rows {
+        let Some(feed_url) = feed_urls.get(&row.try_get::<i64, _>("feed")?) else { continue };
+        episodes.push(EpisodeState {
+            feed_url: Some(feed_url.clone()),
+            guid: row.try_get("item_identifier")?,
+            url: row.try_get("download_url")?,
+            position: (row.try_get::<Option<i64>, _>("position")?.unwrap_or(0) / 1000) as i32,
+            duration: (row.try_get::<Option<i64>, _>("duration")?.unwrap_or(0) / 1000) as i32,
+            completed: row.try_get::<i64, _>("read")? == 1,
+            favourite: row.try_get::<i64, _>("favourite")? != 0,
+            queue_position: row.try_get("queue_position")?,
+        });
+    }
+    Ok(Library { feeds, episodes })
+}
+
+// ---- Pocket Casts ----
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PocketCastsEpisode {
+    url: Option<String>,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    played_up_to: f64,
+    /// 1 unplayed, 2 in progress, 3 played.
+    #[serde(default)]
+    playing_status: i32,
+    #[serde(default)]
+    starred: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PocketCastsHistory {
+    episodes: Vec<PocketCastsEpisode>,
+}
+
+pub fn parse_pocketcasts(opml: Option<&str>, history: &str) -> AppResult<Library> {
+    let history: PocketCastsHistory = serde_json::from_str(history)
+        .map_err(|e| AppError::bad_request(format!("Not a Pocket Casts history export: {}", e)))?;
+    let episodes = history
+        .episodes
+        .into_iter()
+        .filter(|e| e.url.is_some())
+        .map(|e| EpisodeState {
+            feed_url: None,
+            guid: None,
+            url: e.url,
+            position: e.played_up_to as i32,
+            duration: e.duration as i32,
+            completed: e.playing_status == 3,
+            favourite: e.starred,
+            queue_position: None,
+        })
+        .collect();
+    Ok(Library { feeds: opml.map(parse_opml).unwrap_or_default(), episodes })
+}
+
+// ---- gpodder episode actions ----
+
+#[derive(Debug, Deserialize)]
+struct EpisodeAction {
+    podcast: String,
+    episode: String,
+    guid: Option<String>,
+    action: String,
+    timestamp: Option<String>,
+    position: Option<i64>,
+    total: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EpisodeActions {
+    List(Vec<EpisodeAction>),
+    Response { actions: Vec<EpisodeAction> },
+}
+
+/// Clients report a finished episode as a play action whose position reaches the total.
+const COMPLETION_SLACK_SECS: i64 = 5;
+
+pub fn parse_gpodder(opml: Option<&str>, actions: &str) -> AppResult<Library> {
+    let actions = match serde_json::from_str(actions)
+        .map_err(|e| AppError::bad_request(format!("Not a gpodder episode actions export: {}", e)))?
+    {
+        EpisodeActions::List(actions) => actions,
+        EpisodeActions::Response { actions } => actions,
+    };
+
+    // Only the latest play (or "new", which resets it) per episode counts.
+    let mut latest: HashMap<(String, String), EpisodeAction> = HashMap::new();
+    for action in actions {
+        let action_type = action.action.to_ascii_lowercase();
+        if action_type != "play" && action_type != "new" {
+            continue;
+        }
+        let key = (action.podcast.clone(), action.episode.clone());
+        let newer = latest.get(&key).is_none_or(|seen| action.timestamp >= seen.timestamp);
+        if newer {
+            latest.insert(key, action);
+        }
+    }
+
+    // With an OPML, that's the subscription list; otherwise subscribe to every played feed.
+    let mut feeds = opml.map(parse_opml).unwrap_or_default();
+    let feeds_from_actions = opml.is_none();
+    let mut episodes = Vec::new();
+    for action in latest.into_values() {
+        if !action.action.eq_ignore_ascii_case("play") {
+            continue;
+        }
+        let position = action.position.unwrap_or(0);
+        let total = action.total.unwrap_or(0);
+        if feeds_from_actions && !feeds.contains(&action.podcast) {
+            feeds.push(action.podcast.clone());
+        }
+        episodes.push(EpisodeState {
+            feed_url: Some(action.podcast),
+            guid: action.guid,
+            url: Some(action.episode),
+            position: position as i32,
+            duration: total as i32,
+            completed: total > 0 && position + COMPLETION_SLACK_SECS >= total,
+            favourite: false,
+            queue_position: None,
+        });
+    }
+    Ok(Library { feeds, episodes })
+}
+
+// ---- Applying ----
+
+/// Every episode of the user's podcasts, for sources that don't say which feed an episode is in.
+async fn user_episode_index(db_pool: &DatabasePool, user_id: i32) -> AppResult<EpisodeIndex> {
+    let mut index = EpisodeIndex::default();
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            for r in sqlx::query(
+                r#"SELECT e.episodeid, e.episodeguid, e.episodeurl FROM "Episodes" e
+                   JOIN "Podcasts" p ON p.podcastid = e.podcastid WHERE p.userid = $1"#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            {
+                index.insert(r.try_get("episodeid")?, r.try_get("episodeguid")?, r.try_get("episodeurl")?);
+            }
+        }
+        DatabasePool::MySQL(pool) => {
+            for r in sqlx::query(
+                "SELECT e.EpisodeID, e.EpisodeGUID, e.EpisodeURL FROM Episodes e
+                 JOIN Podcasts p ON p.PodcastID = e.PodcastID WHERE p.UserID = ?",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            {
+                index.insert(r.try_get("EpisodeID")?, r.try_get("EpisodeGUID")?, r.try_get("EpisodeURL")?);
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Apply `library` for `user_id`, publishing progress through the import progress manager
+/// (the same `import_progress` the OPML import reports to).
+pub async fn import_library(
+    state: &crate::AppState,
+    user_id: i32,
+    source: Source,
+    library: Library,
+) -> AppResult<AppImportReport> {
+    let db_pool = &state.db_pool;
+    let progress = &state.import_progress_manager;
+    let mut report = AppImportReport::default();
+    // One step per feed, plus one for replaying episode state.
+    progress.start_import(user_id, library.feeds.len() as i32 + 1).await?;
+
+    let mut indexes: HashMap<String, EpisodeIndex> = HashMap::new();
+    for (i, feed_url) in library.feeds.iter().enumerate() {
+        progress.update_progress(user_id, i as i32 + 1, feed_url).await?;
+        let podcast_id = match find_podcast(db_pool, user_id, feed_url).await? {
+            Some(podcast_id) => {
+                report.podcasts_existing += 1;
+                podcast_id
+            }
+            None => {
+                let added = match db_pool.get_podcast_values(feed_url, user_id, None, None).await {
+                    Ok(values) => db_pool.add_podcast_from_values(&values, user_id, 30, None, None).await,
+                    Err(e) => Err(e),
+                };
+                match added {
+                    Ok((podcast_id, _)) => {
+                        report.podcasts_added += 1;
+                        podcast_id
+                    }
+                    Err(e) => {
+                        warn!("{} import: could not subscribe user {} to {}: {}", source.label(), user_id, feed_url, e);
+                        report.podcasts_failed.push(feed_url.clone());
+                        continue;
+                    }
+                }
+            }
+        };
+        indexes.insert(feed_url.clone(), episode_index(db_pool, podcast_id).await?);
+    }
+
+    progress
+        .update_progress(user_id, library.feeds.len() as i32 + 1, &format!("{} listening history", source.label()))
+        .await?;
+    let needs_user_index = library.episodes.iter().any(|e| e.feed_url.is_none());
+    let user_index = if needs_user_index { Some(user_episode_index(db_pool, user_id).await?) } else { None };
+
+    let mut queue = Vec::new();
+    for episode in &library.episodes {
+        if let Some(feed_url) = episode.feed_url.as_ref().filter(|url| !indexes.contains_key(*url)) {
+            // A feed the user already follows that wasn't in the export's subscription list.
+            if let Some(podcast_id) = find_podcast(db_pool, user_id, feed_url).await? {
+                indexes.insert(feed_url.clone(), episode_index(db_pool, podcast_id).await?);
+            }
+        }
+        let index = match &episode.feed_url {
+            Some(feed_url) => indexes.get(feed_url),
+            None => user_index.as_ref(),
+        };
+        let Some(episode_id) = index.and_then(|index| index.find(episode.guid.as_deref(), episode.url.as_deref())) else {
+            report.episodes_unmatched += 1;
+            continue;
+        };
+        report.episodes_matched += 1;
+
+        if episode.completed {
+            db_pool.mark_episode_completed(episode_id, user_id, false).await?;
+            report.episodes_completed += 1;
+        } else if episode.position > 0 {
+            db_pool.save_episode_history(user_id, episode_id, episode.position, episode.duration).await?;
+            report.positions_restored += 1;
+        }
+        if episode.favourite {
+            db_pool.save_episode(episode_id, user_id, false).await?;
+            report.favourites_restored += 1;
+        }
+        if let Some(position) = episode.queue_position {
+            queue.push((position, episode_id));
+        }
+    }
+    queue.sort();
+    for (_, episode_id) in queue {
+        db_pool.queue_episode(episode_id, user_id, false).await?;
+        report.episodes_queued += 1;
+    }
+
+    progress.clear_progress(user_id).await?;
+    info!(
+        "{} import for user {}: {} podcasts added, {} existing, {} failed; {} episodes matched, {} unmatched",
+        source.label(),
+        user_id,
+        report.podcasts_added,
+        report.podcasts_existing,
+        report.podcasts_failed.len(),
+        report.episodes_matched,
+        report.episodes_unmatched
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_antennapod_database_export() {
+        let path = std::env::temp_dir().join(format!("antennapod_{}.db", uuid::Uuid::new_v4()));
+        let mut conn = sqlx::sqlite::SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .connect()
+            .await
+            .unwrap();
+        for sql in [
+            "CREATE TABLE Feeds (id INTEGER PRIMARY KEY, title TEXT, download_url TEXT, state INTEGER)",
+            "CREATE TABLE FeedItems (id INTEGER PRIMARY KEY, feed INTEGER, item_identifier TEXT, read INTEGER)",
+            "CREATE TABLE FeedMedia (id INTEGER PRIMARY KEY, feeditem INTEGER, download_url TEXT, position INTEGER, duration INTEGER)",
+            "CREATE TABLE Favorites (id INTEGER PRIMARY KEY, feeditem INTEGER, feed INTEGER)",
+            "CREATE TABLE Queue (id INTEGER PRIMARY KEY, feeditem INTEGER, feed INTEGER)",
+            "INSERT INTO Feeds VALUES (1, 'Show', 'https://example.com/feed.xml', 0),
+                (2, 'Gone', 'https://example.com/old.xml', 1), (3, 'Local', 'antennapod_local:content://x', 0)",
+            "INSERT INTO FeedItems VALUES (10, 1, 'guid-played', 1), (11, 1, 'guid-partial', 0),
+                (12, 1, 'guid-untouched', -1), (13, 2, 'guid-unsubscribed', 1)",
+            "INSERT INTO FeedMedia VALUES (1, 10, 'https://example.com/a.mp3', 0, 1800000),
+                (2, 11, 'https://example.com/b.mp3', 754000, 3600000), (3, 12, 'https://example.com/c.mp3', 0, 60000)",
+            "INSERT INTO Favorites VALUES (1, 10, 1)",
+            "INSERT INTO Queue VALUES (5, 11, 1)",
+        ] {
+            sqlx::query(sql).execute(&mut conn).await.unwrap();
+        }
+        drop(conn);
+
+        let library = parse_antennapod(&path).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(library.feeds, vec!["https://example.com/feed.xml".to_string()]);
+
+        let mut episodes = library.episodes;
+        episodes.sort_by(|a, b| a.guid.cmp(&b.guid));
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(episodes[0].guid.as_deref(), Some("guid-partial"));
+        assert_eq!((episodes[0].position, episodes[0].duration, episodes[0].completed), (754, 3600, false));
+        assert_eq!(episodes[0].queue_position, Some(5));
+        assert_eq!(episodes[1].guid.as_deref(), Some("guid-played"));
+        assert!(episodes[1].completed && episodes[1].favourite);
+    }
+
+    #[test]
+    fn keeps_the_latest_gpodder_play_per_episode() {
+        let opml = r#"<opml><body><outline text="A" xmlUrl="https://a.example/feed?x=1&amp;y=2"/></body></opml>"#;
+        let actions = r#"{"actions": [
+            {"podcast": "https://a.example/feed?x=1&y=2", "episode": "https://a.example/1.mp3", "action": "play",
+             "timestamp": "2024-01-01T10:00:00", "position": 100, "total": 1000},
+            {"podcast": "https://a.example/feed?x=1&y=2", "episode": "https://a.example/1.mp3", "action": "PLAY",
+             "timestamp": "2024-01-02T10:00:00", "position": 998, "total": 1000},
+            {"podcast": "https://b.example/feed", "episode": "https://b.example/1.mp3", "action": "play",
+             "timestamp": "2024-01-01T10:00:00", "position": 50, "total": 1000},
+            {"podcast": "https://b.example/feed", "episode": "https://b.example/1.mp3", "action": "new",
+             "timestamp": "2024-01-03T10:00:00"},
+            {"podcast": "https://b.example/feed", "episode": "https://b.example/2.mp3", "action": "download",
+             "timestamp": "2024-01-03T10:00:00"}
+        ]}"#;
+        let library = parse_gpodder(Some(opml), actions).unwrap();
+        assert_eq!(library.feeds, vec!["https://a.example/feed?x=1&y=2".to_string()]);
+        assert_eq!(library.episodes.len(), 1);
+        assert_eq!(parse_gpodder(None, actions).unwrap().feeds, vec!["https://a.example/feed?x=1&y=2".to_string()]);
+        assert!(library.episodes[0].completed);
+        assert_eq!(library.episodes[0].position, 998);
+    }
+}
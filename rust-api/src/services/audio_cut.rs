@@ -0,0 +1,582 @@
+//! Server-side cut copies of downloaded episodes, with skipped segments removed from the audio.
+//!
+//! Skip segments (`audio_processing` silence, `ad_detection` ads) normally only tell our own
+//! player what to jump over, so third-party apps reading the generated RSS feed, which points
+//! downloaded episodes at `/api/data/stream`, play everything. With a podcast's
+//! `ServeCutAudio` opt-in, `stream_episode` serves a rendered copy of the download instead, with
+//! every silence range and every ad the user has confirmed cut out by ffmpeg. Auto-activated
+//! (`active`) ads are only skipped by the player, since a false positive cut from the audio
+//! can't be undone by the listener. Embedded chapters are rewritten onto the shortened timeline;
+//! the Podcasting 2.0 chapter JSON, the RSS enclosure and the remaining skip segments the API
+//! returns are remapped the same way via [`served_cut_ranges`].
+//!
+//! Copies live in [`CACHE_DIR`], keyed by source file and the exact ranges removed, so a new
+//! review decision or a re-run detection renders a fresh copy and the stale one ages out of the
+//! LRU prune (`PINEPODS_CUT_CACHE_MB`, default 4096).
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::ad_detection::{self, SkipSegmentView, KIND_AD};
+use crate::services::cluster;
+use crate::services::job_queue::{Job, JobQueue};
+use crate::services::transcode;
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{debug, info, warn};
+
+/// Under the downloads mount, next to the transcode cache.
+pub const CACHE_DIR: &str = "/opt/pinepods/downloads/.cut-cache";
+
+const DEFAULT_CACHE_MB: u64 = 4096;
+
+/// Read a podcast's serve-cut-audio opt-in.
+pub async fn get_serve_cut_audio(db_pool: &DatabasePool, podcast_id: i32) -> Result<bool, String> {
+    let enabled = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT COALESCE(servecutaudio, FALSE) AS c FROM "Podcasts" WHERE podcastid = $1"#,
+        )
+        .bind(podcast_id).fetch_optional(pool).await.map_err(|e| e.to_string())?
+        .and_then(|r| r.try_get::<bool, _>("c").ok()).unwrap_or(false),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT COALESCE(ServeCutAudio, 0) AS c FROM Podcasts WHERE PodcastID = ?",
+        )
+        .bind(podcast_id).fetch_optional(pool).await.map_err(|e| e.to_string())?
+        .and_then(|r| r.try_get::<i8, _>("c").ok()).map(|c| c != 0).unwrap_or(false),
+    };
+    Ok(enabled)
+}
+
+/// Update a podcast's serve-cut-audio opt-in (owner-scoped).
+pub async fn set_serve_cut_audio(
+    db_pool: &DatabasePool,
+    podcast_id: i32,
+    user_id: i32,
+    enabled: bool,
+) -> Result<(), String> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"UPDATE "Podcasts" SET servecutaudio = $1 WHERE podcastid = $2 AND userid = $3"#)
+                .bind(enabled).bind(podcast_id).bind(user_id)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE Podcasts SET ServeCutAudio = ? WHERE PodcastID = ? AND UserID = ?")
+                .bind(enabled).bind(podcast_id).bind(user_id)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the user's own podcast for `episode_id` has the opt-in set.
+async fn enabled_for_episode(db_pool: &DatabasePool, user_id: i32, episode_id: i32) -> bool {
+    match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT COALESCE(p.servecutaudio, FALSE) AS c
+               FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               WHERE e.episodeid = $1 AND p.userid = $2"#,
+        )
+        .bind(episode_id).bind(user_id).fetch_optional(pool).await.ok().flatten()
+        .and_then(|r| r.try_get::<bool, _>("c").ok()).unwrap_or(false),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT COALESCE(p.ServeCutAudio, 0) AS c
+             FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             WHERE e.EpisodeID = ? AND p.UserID = ?",
+        )
+        .bind(episode_id).bind(user_id).fetch_optional(pool).await.ok().flatten()
+        .and_then(|r| r.try_get::<i8, _>("c").ok()).map(|c| c != 0).unwrap_or(false),
+    }
+}
+
+/// Whether a segment is removed from the cut copy: all silence, and ads the user confirmed.
+fn is_cut(segment: &SkipSegmentView) -> bool {
+    if segment.kind == KIND_AD {
+        segment.status.as_deref() == Some("confirmed")
+    } else {
+        true
+    }
+}
+
+/// Sort, drop empty and merge overlapping or touching ranges.
+fn merge_ranges(mut ranges: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    ranges.retain(|(start, end)| end > start && *start >= 0.0);
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut merged: Vec<(f64, f64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// The ranges cut out of `episode_id` for this user, or empty when the opt-in is off.
+pub async fn removed_ranges(db_pool: &DatabasePool, user_id: i32, episode_id: i32) -> Result<Vec<(f64, f64)>, String> {
+    if !enabled_for_episode(db_pool, user_id, episode_id).await {
+        return Ok(Vec::new());
+    }
+    let segments = ad_detection::get_episode_skip_segments_for_user(db_pool, user_id, episode_id).await?;
+    Ok(merge_ranges(
+        segments.iter().filter(|s| is_cut(s)).map(|s| (s.start_time, s.end_time)).collect(),
+    ))
+}
+
+/// The ranges removed from what `stream_episode` serves this user for `episode_id`: the same as
+/// [`removed_ranges`], but only when the user has a server download (local-media and YouTube
+/// streams, and episodes played from the publisher's URL, are never cut). Timestamps shown for
+/// that episode need [`map_time`] applied.
+pub async fn served_cut_ranges(db_pool: &DatabasePool, user_id: i32, episode_id: i32) -> Vec<(f64, f64)> {
+    match db_pool.get_download_location(episode_id, user_id).await {
+        Ok(Some(_)) => removed_ranges(db_pool, user_id, episode_id).await.unwrap_or_else(|e| {
+            warn!("Could not read cut ranges for episode {}: {}", episode_id, e);
+            Vec::new()
+        }),
+        _ => Vec::new(),
+    }
+}
+
+/// Map a timestamp in the original audio onto the cut copy. A time inside a removed range lands
+/// on the cut point.
+pub fn map_time(t: f64, ranges: &[(f64, f64)]) -> f64 {
+    let mut removed = 0.0;
+    for &(start, end) in ranges {
+        if t >= end {
+            removed += end - start;
+        } else if t > start {
+            return start - removed;
+        } else {
+            break;
+        }
+    }
+    t - removed
+}
+
+/// Remap Podcasting 2.0 JSON chapters (`startTime`/`endTime` in seconds) onto the cut copy.
+/// Chapters that fall entirely inside removed audio are dropped.
+pub fn remap_chapters(chapters: &serde_json::Value, ranges: &[(f64, f64)]) -> serde_json::Value {
+    let Some(list) = chapters.as_array() else {
+        return chapters.clone();
+    };
+    let mut out: Vec<serde_json::Value> = Vec::with_capacity(list.len());
+    for chapter in list {
+        let mut chapter = chapter.clone();
+        let Some(start) = chapter.get("startTime").and_then(|v| v.as_f64()) else {
+            out.push(chapter);
+            continue;
+        };
+        let mapped_start = map_time(start, ranges);
+        if let Some(end) = chapter.get("endTime").and_then(|v| v.as_f64()) {
+            let mapped_end = map_time(end, ranges);
+            if mapped_end <= mapped_start {
+                continue;
+            }
+            chapter["endTime"] = serde_json::json!(mapped_end);
+        }
+        chapter["startTime"] = serde_json::json!(mapped_start);
+        // An earlier chapter now starting at the same instant had all of its audio removed.
+        if out.last().and_then(|c| c.get("startTime")).and_then(|v| v.as_f64()) == Some(mapped_start) {
+            out.pop();
+        }
+        out.push(chapter);
+    }
+    serde_json::Value::Array(out)
+}
+
+/// Drop the segments that were cut out of the served audio and shift the rest (e.g. pending or
+/// rejected ads) onto the cut timeline.
+pub fn remap_segments(segments: Vec<SkipSegmentView>, ranges: &[(f64, f64)]) -> Vec<SkipSegmentView> {
+    segments
+        .into_iter()
+        .filter(|s| !is_cut(s))
+        .filter_map(|mut s| {
+            s.start_time = map_time(s.start_time, ranges);
+            s.end_time = map_time(s.end_time, ranges);
+            (s.end_time > s.start_time).then_some(s)
+        })
+        .collect()
+}
+
+fn cache_key(source: &Path, meta: &std::fs::Metadata, ranges: &[(f64, f64)]) -> String {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = Sha256::new();
+    hasher.update(source.to_string_lossy().as_bytes());
+    hasher.update(meta.len().to_le_bytes());
+    hasher.update(mtime.to_le_bytes());
+    for (start, end) in ranges {
+        hasher.update(start.to_le_bytes());
+        hasher.update(end.to_le_bytes());
+    }
+    hex::encode(&hasher.finalize()[..16])
+}
+
+/// A user's cut copy of a download, whether or not it has been rendered yet.
+pub struct CutPlan {
+    /// The merged ranges removed from the source.
+    pub ranges: Vec<(f64, f64)>,
+    /// Changes whenever the source or the removed ranges do.
+    pub key: String,
+    /// Where the rendered copy lives in [`CACHE_DIR`].
+    pub dest: PathBuf,
+    /// Size of the source file, in bytes.
+    pub source_len: u64,
+}
+
+/// The cut copy `stream_episode` serves this user for `source`, without rendering it. `None`
+/// when nothing is cut (opt-in off, or no segments to remove).
+pub async fn cut_plan(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    episode_id: i32,
+    source: &Path,
+) -> Result<Option<CutPlan>, String> {
+    let ranges = removed_ranges(db_pool, user_id, episode_id).await?;
+    if ranges.is_empty() {
+        return Ok(None);
+    }
+
+    let meta = tokio::fs::metadata(source)
+        .await
+        .map_err(|e| format!("cannot read {}: {}", source.display(), e))?;
+    let key = cache_key(source, &meta, &ranges);
+    let extension = source.extension().and_then(|e| e.to_str()).unwrap_or("mp3").to_ascii_lowercase();
+    let dest = Path::new(CACHE_DIR).join(format!("{}.{}", key, extension));
+    Ok(Some(CutPlan { ranges, key, dest, source_len: meta.len() }))
+}
+
+/// A cut copy, as far as `stream_episode` is concerned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CutMedia {
+    /// Serve this file.
+    Ready(PathBuf),
+    /// The copy is being rendered by the job queue.
+    Rendering,
+}
+
+/// The cut copy of `source` `stream_episode` should serve this user. A copy that isn't cached
+/// yet queues a render job on `user_id`'s behalf and comes back as [`CutMedia::Rendering`].
+/// `None` when nothing is cut (opt-in off, or no segments to remove).
+pub async fn cut_or_queue(
+    jobs: &JobQueue,
+    db_pool: &DatabasePool,
+    user_id: i32,
+    episode_id: i32,
+    source: &Path,
+) -> AppResult<Option<CutMedia>> {
+    let Some(plan) = cut_plan(db_pool, user_id, episode_id, source).await.map_err(AppError::internal)? else {
+        return Ok(None);
+    };
+    if tokio::fs::metadata(&plan.dest).await.is_ok() {
+        transcode::touch(&plan.dest);
+        return Ok(Some(CutMedia::Ready(plan.dest)));
+    }
+    let job = Job::CutAudio { user_id, episode_id, source: source.to_string_lossy().into_owned() };
+    jobs.enqueue(user_id, job).await?;
+    Ok(Some(CutMedia::Rendering))
+}
+
+/// Render this user's cut copy of `source` into the cache unless it is already there. `None`
+/// when nothing is cut. Run by the job queue and [`maybe_prerender`]; fails while another
+/// replica is rendering the same copy, so the job's retry finds the finished file.
+pub async fn render_cut(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    episode_id: i32,
+    source: &Path,
+) -> Result<Option<PathBuf>, String> {
+    let Some(CutPlan { ranges, key, dest, .. }) = cut_plan(db_pool, user_id, episode_id, source).await? else {
+        return Ok(None);
+    };
+    let extension = dest.extension().and_then(|e| e.to_str()).unwrap_or("mp3").to_string();
+
+    let lock = transcode::key_lock(&key);
+    let _guard = lock.lock().await;
+
+    if tokio::fs::metadata(&dest).await.is_ok() {
+        transcode::touch(&dest);
+        return Ok(Some(dest));
+    }
+    let Some(_render_lock) = cluster::try_lock(&format!("render:{}", key), transcode::RENDER_LOCK_TTL_SECONDS)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err(format!("{} is being rendered by another instance", dest.display()));
+    };
+
+    tokio::fs::create_dir_all(CACHE_DIR)
+        .await
+        .map_err(|e| format!("cannot create {}: {}", CACHE_DIR, e))?;
+    let partial = Path::new(CACHE_DIR).join(format!("{}.partial.{}", key, extension));
+    let started = std::time::Instant::now();
+    if let Err(e) = render(source, &partial, &ranges).await {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&partial, &dest)
+        .await
+        .map_err(|e| format!("cannot move cut copy into cache: {}", e))?;
+    info!(
+        "Rendered cut copy of episode {} ({} range(s), {:.0}s removed) in {:.1}s",
+        episode_id,
+        ranges.len(),
+        ranges.iter().map(|(s, e)| e - s).sum::<f64>(),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(Some(dest))
+}
+
+/// A chapter embedded in the source file, in seconds.
+struct EmbeddedChapter {
+    start: f64,
+    end: f64,
+    title: String,
+}
+
+/// Read embedded chapters (ID3 CHAP, MP4 chapter track, ...) with ffprobe.
+async fn probe_chapters(source: &Path) -> Vec<EmbeddedChapter> {
+    let output = match tokio::process::Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_chapters"])
+        .arg(source)
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => output,
+        Ok(_) | Err(_) => return Vec::new(),
+    };
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    parsed
+        .get("chapters")
+        .and_then(|c| c.as_array())
+        .map(|chapters| {
+            chapters
+                .iter()
+                .filter_map(|c| {
+                    Some(EmbeddedChapter {
+                        start: c.get("start_time")?.as_str()?.parse().ok()?,
+                        end: c.get("end_time")?.as_str()?.parse().ok()?,
+                        title: c
+                            .get("tags")
+                            .and_then(|t| t.get("title"))
+                            .and_then(|t| t.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Escape a value for ffmpeg's FFMETADATA format.
+pub(crate) fn ffmetadata_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// FFMETADATA with the embedded chapters moved onto the cut timeline.
+fn chapters_ffmetadata(chapters: &[EmbeddedChapter], ranges: &[(f64, f64)]) -> String {
+    let mut out = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        let start = map_time(chapter.start, ranges);
+        let end = map_time(chapter.end, ranges);
+        if end <= start {
+            continue;
+        }
+        let _ = write!(
+            out,
+            "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (start * 1000.0).round() as i64,
+            (end * 1000.0).round() as i64,
+            ffmetadata_escape(&chapter.title)
+        );
+    }
+    out
+}
+
+/// Re-encode `source` into `dest` without the audio inside `ranges`. Tags are carried over and
+/// embedded chapters are rewritten; artwork is dropped (feeds carry it separately).
+async fn render(source: &Path, dest: &Path, ranges: &[(f64, f64)]) -> Result<(), String> {
+    let between = ranges
+        .iter()
+        .map(|(start, end)| format!("between(t,{:.3},{:.3})", start, end))
+        .collect::<Vec<_>>()
+        .join("+");
+    let filter = format!("aselect='not({})',asetpts=N/SR/TB", between);
+
+    let chapters = probe_chapters(source).await;
+    let metadata_path = dest.with_extension("ffmeta");
+    if !chapters.is_empty() {
+        tokio::fs::write(&metadata_path, chapters_ffmetadata(&chapters, ranges))
+            .await
+            .map_err(|e| format!("cannot write chapter metadata: {}", e))?;
+    }
+
+    debug!("Cutting {} into {} ({})", source.display(), dest.display(), filter);
+    let mut command = tokio::process::Command::new("ffmpeg");
+    command.args(["-hide_banner", "-nostats", "-y", "-i"]).arg(source);
+    if chapters.is_empty() {
+        command.args(["-map_chapters", "-1"]);
+    } else {
+        command.arg("-i").arg(&metadata_path).args(["-map_chapters", "1"]);
+    }
+    command
+        .args(["-map", "0:a:0", "-map_metadata", "0", "-vn", "-af"])
+        .arg(&filter);
+    if dest.extension().and_then(|e| e.to_str()) == Some("mp3") {
+        // VBR ~190 kbps: a cut copy of a typical podcast download should not sound worse than it.
+        command.args(["-c:a", "libmp3lame", "-q:a", "2"]);
+    }
+    let output = command
+        .arg(dest)
+        .output()
+        .await
+        .map_err(|e| format!("failed to spawn ffmpeg: {}", e));
+    if !chapters.is_empty() {
+        let _ = tokio::fs::remove_file(&metadata_path).await;
+    }
+    let output = output?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or("")
+        ));
+    }
+    Ok(())
+}
+
+/// Fire-and-forget hook run once an episode's segments or download change: render cut copies for
+/// every subscriber with the opt-in and a server download of this feed episode, so the first
+/// play from a podcast app doesn't wait on ffmpeg.
+pub fn maybe_prerender(db_pool: DatabasePool, episode_id: i32) {
+    tokio::spawn(async move {
+        let targets: Vec<(i32, i32, String)> = match db_pool {
+            DatabasePool::Postgres(ref pool) => sqlx::query(r#"
+                SELECT p.userid, s.episodeid, d.downloadedlocation
+                FROM (
+                    SELECT episodeid FROM "Episodes" WHERE episodeid = $1
+                    UNION
+                    SELECT s.episodeid FROM "Episodes" e JOIN "Episodes" s ON s.feedepisodeid = e.feedepisodeid
+                    WHERE e.episodeid = $1
+                ) siblings
+                JOIN "Episodes" s ON s.episodeid = siblings.episodeid
+                JOIN "Podcasts" p ON p.podcastid = s.podcastid AND COALESCE(p.servecutaudio, FALSE) = TRUE
+                JOIN "DownloadedEpisodes" d ON d.episodeid = s.episodeid AND d.userid = p.userid
+            "#)
+            .bind(episode_id)
+            .fetch_all(pool)
+            .await
+            .map(|rows| rows.into_iter().filter_map(|r| Some((
+                r.try_get::<i32, _>("userid").ok()?,
+                r.try_get::<i32, _>("episodeid").ok()?,
+                r.try_get::<String, _>("downloadedlocation").ok()?,
+            ))).collect())
+            .unwrap_or_default(),
+            DatabasePool::MySQL(ref pool) => sqlx::query("
+                SELECT p.UserID, s.EpisodeID, d.DownloadedLocation
+                FROM (
+                    SELECT EpisodeID FROM Episodes WHERE EpisodeID = ?
+                    UNION
+                    SELECT s.EpisodeID FROM Episodes e JOIN Episodes s ON s.FeedEpisodeID = e.FeedEpisodeID
+                    WHERE e.EpisodeID = ?
+                ) siblings
+                JOIN Episodes s ON s.EpisodeID = siblings.EpisodeID
+                JOIN Podcasts p ON p.PodcastID = s.PodcastID AND COALESCE(p.ServeCutAudio, 0) = 1
+                JOIN DownloadedEpisodes d ON d.EpisodeID = s.EpisodeID AND d.UserID = p.UserID
+            ")
+            .bind(episode_id)
+            .bind(episode_id)
+            .fetch_all(pool)
+            .await
+            .map(|rows| rows.into_iter().filter_map(|r| Some((
+                r.try_get::<i32, _>("UserID").ok()?,
+                r.try_get::<i32, _>("EpisodeID").ok()?,
+                r.try_get::<String, _>("DownloadedLocation").ok()?,
+            ))).collect())
+            .unwrap_or_default(),
+        };
+
+        // Sequential on purpose: one ffmpeg at a time is plenty for background work, and users
+        // sharing a download location with identical review decisions hit the cache.
+        for (user_id, sibling_id, location) in targets {
+            if let Err(e) = render_cut(&db_pool, user_id, sibling_id, Path::new(&location)).await {
+                warn!("Pre-rendering cut copy of episode {} for user {} failed: {}", sibling_id, user_id, e);
+            }
+        }
+    });
+}
+
+fn cache_limit_bytes() -> u64 {
+    std::env::var("PINEPODS_CUT_CACHE_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CACHE_MB)
+        * 1024
+        * 1024
+}
+
+/// Trim the cut-copy cache back under its size limit, least recently used first.
+pub async fn prune_cache() -> Result<(), String> {
+    transcode::prune_dir(CACHE_DIR, cache_limit_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_times_across_removed_ranges() {
+        let ranges = merge_ranges(vec![(30.0, 40.0), (10.0, 20.0), (15.0, 25.0)]);
+        assert_eq!(ranges, vec![(10.0, 25.0), (30.0, 40.0)]);
+        assert_eq!(map_time(5.0, &ranges), 5.0);
+        assert_eq!(map_time(12.0, &ranges), 10.0);
+        assert_eq!(map_time(27.0, &ranges), 12.0);
+        assert_eq!(map_time(50.0, &ranges), 25.0);
+    }
+
+    #[test]
+    fn drops_chapters_inside_removed_audio() {
+        let chapters = serde_json::json!([
+            {"startTime": 0, "title": "Intro"},
+            {"startTime": 60, "title": "Sponsor"},
+            {"startTime": 90, "title": "Main"},
+        ]);
+        let remapped = remap_chapters(&chapters, &[(60.0, 90.0)]);
+        let titles: Vec<_> = remapped.as_array().unwrap().iter().map(|c| c["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, vec!["Intro", "Main"]);
+        assert_eq!(remapped[1]["startTime"], serde_json::json!(60.0));
+    }
+
+    #[test]
+    fn cuts_silence_and_confirmed_ads_only() {
+        let segment = |kind: &str, status: Option<&str>| SkipSegmentView {
+            segment_id: 1,
+            kind: kind.to_string(),
+            start_time: 0.0,
+            end_time: 10.0,
+            source: "test".to_string(),
+            status: status.map(str::to_string),
+        };
+        assert!(is_cut(&segment("silence", None)));
+        assert!(is_cut(&segment(KIND_AD, Some("confirmed"))));
+        assert!(!is_cut(&segment(KIND_AD, Some("active"))));
+        assert!(!is_cut(&segment(KIND_AD, Some("pending"))));
+        assert!(!is_cut(&segment(KIND_AD, Some("rejected"))));
+    }
+}
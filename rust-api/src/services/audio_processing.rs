@@ -6,10 +6,12 @@
 //! silent ranges into `EpisodeSkipSegments` as `Kind='silence'` rows.
 //!
 //! Segments are content-level (per episode, not per user): the audio is identical for every
-//! subscriber, so detection runs once and `Episodes.SilenceDetected` guards re-analysis. This is
+//! subscriber, so detection runs once per shared content key (see `shared_feeds::ContentKey`) and
+//! its `SilenceDetected` flag guards re-analysis. This is
 //! the same skip-segment substrate a future ad-detector (#790) would write into.
 
 use crate::database::DatabasePool;
+use crate::services::shared_feeds::{self, ContentFlag, ContentKey};
 use sqlx::Row;
 use tracing::{debug, warn};
 
@@ -156,97 +158,67 @@ async fn podcast_silence_settings(db_pool: &DatabasePool, episode_id: i32) -> Re
     Ok(row.unwrap_or((false, 2)))
 }
 
-/// Persist detected silence segments for an episode: clear prior auto-silence rows, insert the new
-/// ranges, and mark the episode analyzed. Returns the number of segments written.
+/// Persist detected silence segments for a content key: clear prior auto-silence rows, insert the
+/// new ranges, and mark it analyzed. Returns the number of segments written.
 async fn store_silence_segments(
     db_pool: &DatabasePool,
-    episode_id: i32,
+    key: ContentKey,
     segments: &[(f64, f64)],
 ) -> Result<usize, String> {
     match db_pool {
         DatabasePool::Postgres(pool) => {
-            sqlx::query(r#"DELETE FROM "EpisodeSkipSegments" WHERE episodeid = $1 AND source = $2"#)
-                .bind(episode_id)
+            let delete = format!(r#"DELETE FROM "EpisodeSkipSegments" WHERE {} = $1 AND source = $2"#, key.pg_column());
+            sqlx::query(sqlx::AssertSqlSafe(delete.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_SILENCE)
                 .execute(pool)
                 .await
                 .map_err(|e| e.to_string())?;
+            let insert = format!(
+                r#"INSERT INTO "EpisodeSkipSegments" ({}, kind, starttime, endtime, source) VALUES ($1, $2, $3, $4, $5)"#,
+                key.pg_column()
+            );
             for (start, end) in segments {
-                sqlx::query(r#"
-                    INSERT INTO "EpisodeSkipSegments" (episodeid, kind, starttime, endtime, source)
-                    VALUES ($1, $2, $3, $4, $5)
-                "#)
-                .bind(episode_id)
-                .bind(KIND_SILENCE)
-                .bind(*start)
-                .bind(*end)
-                .bind(SOURCE_SILENCE)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(key.id())
+                    .bind(KIND_SILENCE)
+                    .bind(*start)
+                    .bind(*end)
+                    .bind(SOURCE_SILENCE)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
             }
-            sqlx::query(r#"UPDATE "Episodes" SET silencedetected = TRUE WHERE episodeid = $1"#)
-                .bind(episode_id)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
         }
         DatabasePool::MySQL(pool) => {
-            sqlx::query("DELETE FROM EpisodeSkipSegments WHERE EpisodeID = ? AND Source = ?")
-                .bind(episode_id)
+            let delete = format!("DELETE FROM EpisodeSkipSegments WHERE {} = ? AND Source = ?", key.mysql_column());
+            sqlx::query(sqlx::AssertSqlSafe(delete.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_SILENCE)
                 .execute(pool)
                 .await
                 .map_err(|e| e.to_string())?;
+            let insert = format!(
+                "INSERT INTO EpisodeSkipSegments ({}, Kind, StartTime, EndTime, Source) VALUES (?, ?, ?, ?, ?)",
+                key.mysql_column()
+            );
             for (start, end) in segments {
-                sqlx::query("
-                    INSERT INTO EpisodeSkipSegments (EpisodeID, Kind, StartTime, EndTime, Source)
-                    VALUES (?, ?, ?, ?, ?)
-                ")
-                .bind(episode_id)
-                .bind(KIND_SILENCE)
-                .bind(*start)
-                .bind(*end)
-                .bind(SOURCE_SILENCE)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(key.id())
+                    .bind(KIND_SILENCE)
+                    .bind(*start)
+                    .bind(*end)
+                    .bind(SOURCE_SILENCE)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
             }
-            sqlx::query("UPDATE Episodes SET SilenceDetected = TRUE WHERE EpisodeID = ?")
-                .bind(episode_id)
-                .execute(pool)
-                .await
-                .map_err(|e| e.to_string())?;
         }
     }
-    Ok(segments.len())
-}
-
-/// Whether an episode has already been analyzed for silence.
-async fn already_detected(db_pool: &DatabasePool, episode_id: i32) -> bool {
-    match db_pool {
-        DatabasePool::Postgres(pool) => sqlx::query(
-            r#"SELECT COALESCE(silencedetected, FALSE) AS d FROM "Episodes" WHERE episodeid = $1"#,
-        )
-        .bind(episode_id)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .and_then(|r| r.try_get::<bool, _>("d").ok())
-        .unwrap_or(false),
-        DatabasePool::MySQL(pool) => sqlx::query(
-            "SELECT COALESCE(SilenceDetected, 0) AS d FROM Episodes WHERE EpisodeID = ?",
-        )
-        .bind(episode_id)
-        .fetch_optional(pool)
+    shared_feeds::set_content_flag(db_pool, key, ContentFlag::SilenceDetected)
         .await
-        .ok()
-        .flatten()
-        .and_then(|r| r.try_get::<i8, _>("d").ok())
-        .map(|d| d != 0)
-        .unwrap_or(false),
-    }
+        .map_err(|e| e.to_string())?;
+    Ok(segments.len())
 }
 
 /// Analyze one episode's downloaded file for silence and persist the result.
@@ -259,7 +231,8 @@ pub async fn analyze_episode_silence(
     force: bool,
     threshold_override: Option<i32>,
 ) -> Result<usize, String> {
-    if !force && already_detected(db_pool, episode_id).await {
+    let content = shared_feeds::content_key(db_pool, episode_id).await;
+    if !force && shared_feeds::content_flag(db_pool, content, ContentFlag::SilenceDetected).await {
         debug!("Episode {} already analyzed for silence; skipping", episode_id);
         return Ok(0);
     }
@@ -278,7 +251,7 @@ pub async fn analyze_episode_silence(
     };
 
     let segments = detect_silence(&file_path, level).await?;
-    let count = store_silence_segments(db_pool, episode_id, &segments).await?;
+    let count = store_silence_segments(db_pool, content, &segments).await?;
     debug!("Stored {} silence segment(s) for episode {}", count, episode_id);
     Ok(count)
 }
@@ -363,5 +336,8 @@ pub fn maybe_detect_silence_after_download(db_pool: DatabasePool, episode_id: i3
             Ok((false, _)) => {} // trim-silence not enabled for this podcast
             Err(e) => warn!("Could not read silence settings for episode {}: {}", episode_id, e),
         }
+        // Runs after detection so the copy includes fresh silence ranges; ads found earlier for
+        // another subscriber apply to this download too.
+        crate::services::audio_cut::maybe_prerender(db_pool, episode_id);
     });
 }
@@ -0,0 +1,243 @@
+//! Coordination between rust-api replicas that share one database and one Redis/Valkey.
+//!
+//! Every replica serves HTTP and websockets and runs job queue workers. What must happen once per
+//! cluster goes through Redis:
+//! - task progress is published on [`TASK_UPDATES_CHANNEL`] and re-broadcast by every replica, so
+//!   a websocket held by one replica sees jobs running on another;
+//! - a named [`Lock`] (`SET NX` with a TTL renewed while held) keeps a critical section, such as
+//!   ad detection for one episode, to one replica at a time and frees itself if the holder dies;
+//! - one replica holds the scheduler leadership lease ([`is_leader`]), so cron jobs fire once.
+//!
+//! A single replica behaves exactly as before: it wins every lock and the lease.
+//!
+//! Each replica needs a name that is unique in the cluster and the same across its restarts
+//! ([`instance_id`]), so a restarted replica takes back the jobs it was running straight away
+//! instead of waiting for their heartbeat to go stale. The hostname serves in Docker Compose and
+//! Kubernetes, and `PINEPODS_INSTANCE_ID` overrides it. The name is leased in Redis while the
+//! replica runs ([`claim_instance_id`]): a replica that finds its name held by a live one (two
+//! replicas sharing a hostname) runs under a random suffix instead, so neither takes the other's
+//! running jobs for its own.
+
+use crate::error::{AppError, AppResult};
+use crate::redis_client::RedisClient;
+use futures::StreamExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Task progress updates, as JSON [`TaskUpdate`](crate::services::task_manager::TaskUpdate)s.
+pub const TASK_UPDATES_CHANNEL: &str = "pinepods:task_updates";
+/// Task IDs of cancelled jobs, for the replica running them.
+pub const JOB_CANCEL_CHANNEL: &str = "pinepods:job_cancel";
+/// Worker pool names with newly queued jobs.
+pub const JOB_WAKE_CHANNEL: &str = "pinepods:job_wake";
+/// Playback handoff messages, as JSON [`Envelope`](crate::services::playback_sync::Envelope)s.
+pub const PLAYBACK_CHANNEL: &str = "pinepods:playback";
+
+const LOCK_PREFIX: &str = "lock:";
+const LEADER_LOCK: &str = "scheduler_leader";
+const INSTANCE_LOCK_PREFIX: &str = "instance:";
+/// A leader that stops renewing (crashed, partitioned) is replaced after this long.
+const LEADER_TTL_SECONDS: u64 = 30;
+/// A crashed replica's name is free again after this long.
+const INSTANCE_TTL_SECONDS: u64 = 30;
+const RESUBSCRIBE_SECONDS: u64 = 5;
+
+static REDIS: OnceLock<RedisClient> = OnceLock::new();
+static LEADER: AtomicBool = AtomicBool::new(false);
+static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+/// Held for the life of the process; see [`claim_instance_id`].
+static INSTANCE_LEASE: OnceLock<Lock> = OnceLock::new();
+
+/// `PINEPODS_INSTANCE_ID`, or the hostname.
+fn configured_instance_id() -> String {
+    [
+        std::env::var("PINEPODS_INSTANCE_ID").ok(),
+        std::env::var("HOSTNAME").ok(),
+        std::fs::read_to_string("/etc/hostname").ok(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|id| id.trim().to_string())
+    .find(|id| !id.is_empty())
+    .unwrap_or_else(|| "pinepods".to_string())
+}
+
+/// Make `redis` available to services that only hold a database pool (e.g. ad detection's
+/// in-flight guard). Called once at startup.
+pub fn init(redis: RedisClient) {
+    let _ = REDIS.set(redis);
+}
+
+pub(crate) fn redis() -> AppResult<&'static RedisClient> {
+    REDIS
+        .get()
+        .ok_or_else(|| AppError::Config("Cluster coordination is not initialised".to_string()))
+}
+
+/// This replica's name in logs, job claims and lock owners: `PINEPODS_INSTANCE_ID`, or the
+/// hostname. Stable across restarts, so startup can requeue this replica's own claims.
+pub fn instance_id() -> &'static str {
+    INSTANCE_ID.get_or_init(configured_instance_id).as_str()
+}
+
+/// Lease this replica's name in Redis for as long as it runs. If a live replica already holds it,
+/// run under the name with a random suffix, and warn: requeueing "our" claims at startup would
+/// otherwise take over that replica's running jobs. A replica restarted within
+/// [`INSTANCE_TTL_SECONDS`] of a crash also gets a suffix; its old jobs are then requeued once
+/// their heartbeat goes stale. Called once at startup, before anything reads [`instance_id`].
+pub async fn claim_instance_id(redis: &RedisClient) -> &'static str {
+    let configured = configured_instance_id();
+    let owner = uuid::Uuid::new_v4().simple().to_string();
+    let name = format!("{}{}", INSTANCE_LOCK_PREFIX, configured);
+    let id = match Lock::try_acquire_as(redis, &name, owner, INSTANCE_TTL_SECONDS).await {
+        Ok(Some(lease)) => {
+            let _ = INSTANCE_LEASE.set(lease);
+            configured
+        }
+        Ok(None) => {
+            let suffixed = format!("{}-{}", configured, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+            warn!(
+                "Instance ID {} is in use by another live replica; running as {}. Set PINEPODS_INSTANCE_ID per replica.",
+                configured, suffixed
+            );
+            suffixed
+        }
+        Err(e) => {
+            warn!("Could not lease instance ID {}: {}", configured, e);
+            configured
+        }
+    };
+    INSTANCE_ID.get_or_init(|| id).as_str()
+}
+
+/// Whether this replica currently holds the scheduler leadership lease.
+pub fn is_leader() -> bool {
+    LEADER.load(Ordering::Relaxed)
+}
+
+/// A named lock held by this replica until dropped.
+pub struct Lock {
+    redis: RedisClient,
+    key: String,
+    owner: String,
+    renewal: tokio::task::JoinHandle<()>,
+}
+
+impl Lock {
+    /// Take the lock `name` unless another holder has it. The TTL is renewed while the guard
+    /// lives, so it only bounds how long a crashed holder blocks others.
+    pub async fn try_acquire(redis: &RedisClient, name: &str, ttl_seconds: u64) -> AppResult<Option<Lock>> {
+        let owner = format!("{}:{}", instance_id(), uuid::Uuid::new_v4().simple());
+        Self::try_acquire_as(redis, name, owner, ttl_seconds).await
+    }
+
+    async fn try_acquire_as(redis: &RedisClient, name: &str, owner: String, ttl_seconds: u64) -> AppResult<Option<Lock>> {
+        let key = format!("{}{}", LOCK_PREFIX, name);
+        if !redis.try_lock(&key, &owner, ttl_seconds).await? {
+            return Ok(None);
+        }
+
+        let renewal = tokio::spawn({
+            let redis = redis.clone();
+            let key = key.clone();
+            let owner = owner.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs((ttl_seconds / 3).max(1))).await;
+                    match redis.renew_lock(&key, &owner, ttl_seconds).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            warn!("Lock {} expired before it could be renewed", key);
+                            break;
+                        }
+                        Err(e) => warn!("Failed to renew lock {}: {}", key, e),
+                    }
+                }
+            }
+        });
+
+        Ok(Some(Lock { redis: redis.clone(), key, owner, renewal }))
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        self.renewal.abort();
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let redis = self.redis.clone();
+            let key = std::mem::take(&mut self.key);
+            let owner = std::mem::take(&mut self.owner);
+            runtime.spawn(async move {
+                if let Err(e) = redis.unlock(&key, &owner).await {
+                    warn!("Failed to release lock {}: {}", key, e);
+                }
+            });
+        }
+    }
+}
+
+/// Take a lock through the client registered with [`init`].
+pub async fn try_lock(name: &str, ttl_seconds: u64) -> AppResult<Option<Lock>> {
+    Lock::try_acquire(redis()?, name, ttl_seconds).await
+}
+
+/// Campaign for the scheduler leadership lease and renew it while held. A replica that can't
+/// reach Redis steps down rather than risk two leaders.
+pub fn spawn_leader_election(redis: RedisClient) {
+    tokio::spawn(async move {
+        let key = format!("{}{}", LOCK_PREFIX, LEADER_LOCK);
+        loop {
+            let held = if is_leader() {
+                redis.renew_lock(&key, instance_id(), LEADER_TTL_SECONDS).await
+            } else {
+                redis.try_lock(&key, instance_id(), LEADER_TTL_SECONDS).await
+            };
+            let leader = match held {
+                Ok(leader) => leader,
+                Err(e) => {
+                    warn!("Scheduler leadership check failed: {}", e);
+                    false
+                }
+            };
+            if LEADER.swap(leader, Ordering::Relaxed) != leader {
+                if leader {
+                    info!("👑 {} is now the scheduler leader", instance_id());
+                } else {
+                    warn!("{} is no longer the scheduler leader", instance_id());
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(LEADER_TTL_SECONDS / 3)).await;
+        }
+    });
+}
+
+/// Pass every message published on `channel` to `handler`, resubscribing after a lost
+/// connection.
+pub fn spawn_subscriber<F>(redis: RedisClient, channel: &'static str, handler: F)
+where
+    F: Fn(String) + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            match redis.pubsub().await {
+                Ok(mut pubsub) => match pubsub.subscribe(channel).await {
+                    Ok(()) => {
+                        let mut messages = pubsub.on_message();
+                        while let Some(message) = messages.next().await {
+                            match message.get_payload::<String>() {
+                                Ok(payload) => handler(payload),
+                                Err(e) => warn!("Unreadable message on {}: {}", channel, e),
+                            }
+                        }
+                        warn!("Subscription to {} dropped; resubscribing", channel);
+                    }
+                    Err(e) => warn!("Failed to subscribe to {}: {}", channel, e),
+                },
+                Err(e) => warn!("Failed to open a subscription to {}: {}", channel, e),
+            }
+            tokio::time::sleep(Duration::from_secs(RESUBSCRIBE_SECONDS)).await;
+        }
+    });
+}
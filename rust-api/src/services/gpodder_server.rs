@@ -0,0 +1,998 @@
+//! Storage and protocol helpers for the gpodder.net API v2 served by
+//! [`handlers::gpodder_api`](crate::handlers::gpodder_api).
+//!
+//! Everything lives in the PinePods tables. A user's subscriptions are their `Podcasts` feeds,
+//! which every device shares; what each device changed is logged in `GpodderSyncSubscriptions`
+//! so other devices can ask for changes `since` a timestamp. Uploaded episode actions are logged
+//! in `GpodderSyncEpisodeActions` for other devices and merged into the user's listen history by
+//! the [`gpodder_sync`](super::gpodder_sync) engine's rules. Devices are `GpodderDevices` rows,
+//! created the first time a client names them, and sync groups are stored as device pairs in
+//! `GpodderSyncDevicePairs`. Login sessions are `GpodderSessions` rows.
+//!
+//! Changes made in PinePods itself reach the logs through the user's internal sync, which talks to
+//! this server as the `pinepods` device.
+//!
+//! Timestamps are Unix seconds of server time. `since` queries include the given second, so a
+//! change stored in the same second as a response is returned again rather than missed.
+
+use crate::database::DatabasePool;
+use crate::error::AppResult;
+use crate::services::app_import::parse_opml;
+use crate::services::gpodder_sync::SubscriptionChanges;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+
+/// Sessions from `/auth/{username}/login.json` last this long.
+pub const SESSION_DAYS: i64 = 30;
+
+/// Episode action responses are capped at this many actions; clients page with `since`.
+pub const MAX_ACTIONS_PER_RESPONSE: i64 = 25000;
+
+/// A user as the gpodder API authenticates them.
+#[derive(Debug, Clone)]
+pub struct GpodderUser {
+    pub user_id: i32,
+    pub username: String,
+    pub sync_type: Option<String>,
+    /// The token PinePods' own sync uses against this server.
+    pub token: Option<String>,
+}
+
+impl GpodderUser {
+    /// The API is served for users who sync through the built-in server.
+    pub fn enabled(&self) -> bool {
+        matches!(self.sync_type.as_deref(), Some("gpodder") | Some("both"))
+    }
+}
+
+/// A device as listed by `/devices/{username}.json`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Device {
+    pub id: String,
+    pub caption: String,
+    #[serde(rename = "type")]
+    pub device_type: String,
+    pub subscriptions: i64,
+}
+
+/// A row of `GpodderSyncEpisodeActions`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredAction {
+    pub device: Option<String>,
+    pub podcast: String,
+    pub episode: String,
+    pub action: String,
+    /// When the action happened on the device.
+    pub timestamp: i64,
+    pub started: Option<i32>,
+    pub position: Option<i32>,
+    pub total: Option<i32>,
+}
+
+impl StoredAction {
+    /// An action as sent to clients, with the timestamp in the `yyyy-MM-ddTHH:mm:ss` form
+    /// AntennaPod expects.
+    pub fn to_json(&self) -> serde_json::Value {
+        let timestamp = DateTime::<Utc>::from_timestamp(self.timestamp, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+        let mut action = serde_json::json!({
+            "podcast": self.podcast,
+            "episode": self.episode,
+            "action": self.action,
+            "timestamp": timestamp,
+        });
+        if let Some(device) = self.device.as_deref().filter(|d| !d.is_empty()) {
+            action["device"] = serde_json::json!(device);
+        }
+        for (key, value) in [("started", self.started), ("position", self.position), ("total", self.total)] {
+            if let Some(value) = value {
+                action[key] = serde_json::json!(value);
+            }
+        }
+        action
+    }
+}
+
+/// Which episode actions `/episodes/{username}.json` returns.
+#[derive(Debug, Clone, Default)]
+pub struct ActionQuery {
+    pub since: i64,
+    pub podcast: Option<String>,
+    /// Only actions uploaded by this device.
+    pub device_id: Option<i32>,
+    /// Only the newest action per episode.
+    pub aggregated: bool,
+    pub limit: i64,
+    /// Newest first, for the statistics view; sync clients page oldest first.
+    pub newest_first: bool,
+}
+
+/// Split a path segment such as `alice.json` into its name and format. Names may contain dots,
+/// so only a known format extension is split off.
+pub fn split_format(segment: &str) -> (&str, Option<&str>) {
+    match segment.rsplit_once('.') {
+        Some((name, format)) if matches!(format, "json" | "txt" | "opml" | "jsonp" | "xml") && !name.is_empty() => {
+            (name, Some(format))
+        }
+        _ => (segment, None),
+    }
+}
+
+/// A feed URL from a client, trimmed, or `None` if it isn't an http(s) URL.
+pub fn clean_url(url: &str) -> Option<String> {
+    let trimmed = url.trim();
+    let parsed = url::Url::parse(trimmed).ok()?;
+    matches!(parsed.scheme(), "http" | "https").then(|| trimmed.to_string())
+}
+
+/// An OPML document listing `feeds`.
+pub fn opml(feeds: &[String]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<opml version=\"2.0\">\n  <head><title>PinePods subscriptions</title></head>\n  <body>\n");
+    for feed in feeds {
+        let url = quick_xml::escape::escape(feed.as_str());
+        out.push_str(&format!("    <outline type=\"rss\" text=\"{}\" xmlUrl=\"{}\"/>\n", url, url));
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+/// Feed URLs from a subscription list uploaded in `format`: a JSON array, one URL per line, or
+/// OPML.
+pub fn parse_subscription_list(body: &str, format: Option<&str>) -> Result<Vec<String>, String> {
+    match format {
+        Some("txt") => Ok(body.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect()),
+        Some("opml") | Some("xml") => Ok(parse_opml(body)),
+        _ => serde_json::from_str(body).map_err(|e| format!("Invalid subscription list: {}", e)),
+    }
+}
+
+/// The net change per feed from log rows ordered oldest first: a feed's last action wins.
+pub fn latest_changes(rows: &[(String, String)]) -> SubscriptionChanges {
+    let mut last: BTreeMap<&str, &str> = BTreeMap::new();
+    for (url, action) in rows {
+        last.insert(url, action);
+    }
+    let mut changes = SubscriptionChanges::default();
+    for (url, action) in last {
+        match action {
+            "add" => changes.add.push(url.to_string()),
+            "remove" => changes.remove.push(url.to_string()),
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// Sync groups from device pairs: devices linked by any chain of pairs form one group. Returns
+/// the groups and the devices in none, each sorted.
+pub fn sync_groups(devices: &[String], pairs: &[(String, String)]) -> (Vec<Vec<String>>, Vec<String>) {
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    fn root<'a>(parent: &mut HashMap<&'a str, &'a str>, device: &'a str) -> &'a str {
+        let mut current = device;
+        while let Some(&next) = parent.get(current) {
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        parent.insert(device, current);
+        current
+    }
+    for (a, b) in pairs {
+        parent.entry(a).or_insert(a);
+        parent.entry(b).or_insert(b);
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        if ra != rb {
+            parent.insert(ra.max(rb), ra.min(rb));
+        }
+    }
+    let paired: Vec<&str> = parent.keys().copied().collect();
+    let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for device in paired {
+        let group = root(&mut parent, device);
+        groups.entry(group).or_default().push(device.to_string());
+    }
+    let mut groups: Vec<Vec<String>> = groups.into_values().collect();
+    for group in &mut groups {
+        group.sort();
+    }
+    let mut unsynced: Vec<String> = devices.iter().filter(|d| !parent.contains_key(d.as_str())).cloned().collect();
+    unsynced.sort();
+    unsynced.dedup();
+    (groups, unsynced)
+}
+
+pub async fn find_user(db_pool: &DatabasePool, username: &str) -> AppResult<Option<GpodderUser>> {
+    let user = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT userid, username, pod_sync_type, gpoddertoken FROM "Users" WHERE LOWER(username) = LOWER($1)"#,
+        )
+        .bind(username)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| -> Result<GpodderUser, sqlx::Error> {
+            Ok(GpodderUser {
+                user_id: row.try_get("userid")?,
+                username: row.try_get("username")?,
+                sync_type: row.try_get("pod_sync_type")?,
+                token: row.try_get("gpoddertoken")?,
+            })
+        })
+        .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT UserID, Username, Pod_Sync_Type, GpodderToken FROM Users WHERE LOWER(Username) = LOWER(?)",
+        )
+        .bind(username)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| -> Result<GpodderUser, sqlx::Error> {
+            Ok(GpodderUser {
+                user_id: row.try_get("UserID")?,
+                username: row.try_get("Username")?,
+                sync_type: row.try_get("Pod_Sync_Type")?,
+                token: row.try_get("GpodderToken")?,
+            })
+        })
+        .transpose()?,
+    };
+    Ok(user)
+}
+
+fn new_session_token() -> String {
+    use rand::distr::Alphanumeric;
+    use rand::RngExt;
+    rand::rng().sample_iter(&Alphanumeric).take(48).map(char::from).collect()
+}
+
+/// Start a login session; returns its token and expiry.
+pub async fn create_session(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    user_agent: Option<&str>,
+    client_ip: Option<&str>,
+) -> AppResult<(String, NaiveDateTime)> {
+    let token = new_session_token();
+    let expires = (Utc::now() + chrono::Duration::days(SESSION_DAYS)).naive_utc();
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"INSERT INTO "GpodderSessions" (userid, sessiontoken, expiresat, useragent, clientip) VALUES ($1, $2, $3, $4, $5)"#)
+                .bind(user_id)
+                .bind(&token)
+                .bind(expires)
+                .bind(user_agent)
+                .bind(client_ip)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("INSERT INTO GpodderSessions (UserID, SessionToken, ExpiresAt, UserAgent, ClientIP) VALUES (?, ?, ?, ?, ?)")
+                .bind(user_id)
+                .bind(&token)
+                .bind(expires)
+                .bind(user_agent)
+                .bind(client_ip)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok((token, expires))
+}
+
+/// The user a live session belongs to. Expired sessions are deleted on sight.
+pub async fn session_user(db_pool: &DatabasePool, token: &str) -> AppResult<Option<i32>> {
+    let now = Utc::now().naive_utc();
+    let session: Option<(i32, NaiveDateTime)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT userid, expiresat FROM "GpodderSessions" WHERE sessiontoken = $1"#)
+            .bind(token)
+            .fetch_optional(pool)
+            .await?
+            .map(|row| Ok::<_, sqlx::Error>((row.try_get("userid")?, row.try_get("expiresat")?)))
+            .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT UserID, ExpiresAt FROM GpodderSessions WHERE SessionToken = ?")
+            .bind(token)
+            .fetch_optional(pool)
+            .await?
+            .map(|row| Ok::<_, sqlx::Error>((row.try_get("UserID")?, row.try_get("ExpiresAt")?)))
+            .transpose()?,
+    };
+    let Some((user_id, expires)) = session else {
+        return Ok(None);
+    };
+    if expires <= now {
+        delete_session(db_pool, token).await?;
+        return Ok(None);
+    }
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"UPDATE "GpodderSessions" SET lastactive = CURRENT_TIMESTAMP WHERE sessiontoken = $1"#)
+                .bind(token)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE GpodderSessions SET LastActive = CURRENT_TIMESTAMP WHERE SessionToken = ?")
+                .bind(token)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(Some(user_id))
+}
+
+pub async fn delete_session(db_pool: &DatabasePool, token: &str) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"DELETE FROM "GpodderSessions" WHERE sessiontoken = $1"#)
+                .bind(token)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("DELETE FROM GpodderSessions WHERE SessionToken = ?")
+                .bind(token)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The ID of a user's device, registering it the first time a client names it. Also marks the
+/// device as seen now.
+pub async fn device_id(db_pool: &DatabasePool, user_id: i32, name: &str) -> AppResult<i32> {
+    let id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"INSERT INTO "GpodderDevices" (userid, devicename, devicetype, devicecaption, isactive, lastsync)
+               VALUES ($1, $2, 'other', $2, TRUE, CURRENT_TIMESTAMP)
+               ON CONFLICT (userid, devicename) DO UPDATE SET lastsync = CURRENT_TIMESTAMP, isactive = TRUE
+               RETURNING deviceid"#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_one(pool)
+        .await?
+        .try_get("deviceid")?,
+        DatabasePool::MySQL(pool) => {
+            // LAST_INSERT_ID(expr) makes an existing row's ID the insert ID too.
+            let result = sqlx::query(
+                "INSERT INTO GpodderDevices (UserID, DeviceName, DeviceType, DeviceCaption, IsActive, LastSync)
+                 VALUES (?, ?, 'other', ?, TRUE, CURRENT_TIMESTAMP)
+                 ON DUPLICATE KEY UPDATE LastSync = CURRENT_TIMESTAMP, IsActive = TRUE, DeviceID = LAST_INSERT_ID(DeviceID)",
+            )
+            .bind(user_id)
+            .bind(name)
+            .bind(name)
+            .execute(pool)
+            .await?;
+            result.last_insert_id() as i32
+        }
+    };
+    Ok(id)
+}
+
+/// The ID of an existing device, without registering it.
+pub async fn existing_device_id(db_pool: &DatabasePool, user_id: i32, name: &str) -> AppResult<Option<i32>> {
+    let id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT deviceid FROM "GpodderDevices" WHERE userid = $1 AND devicename = $2"#)
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .map(|row| row.try_get("deviceid"))
+            .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT DeviceID FROM GpodderDevices WHERE UserID = ? AND DeviceName = ?")
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .map(|row| row.try_get("DeviceID"))
+            .transpose()?,
+    };
+    Ok(id)
+}
+
+/// The user's active devices. Every device sees all of the user's subscriptions.
+pub async fn devices(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<Device>> {
+    let subscriptions = db_pool.get_user_podcast_feeds(user_id).await?.len() as i64;
+    let rows: Vec<(String, Option<String>, Option<String>)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT devicename, devicecaption, devicetype FROM "GpodderDevices" WHERE userid = $1 AND isactive = TRUE ORDER BY devicename"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok::<_, sqlx::Error>((row.try_get("devicename")?, row.try_get("devicecaption")?, row.try_get("devicetype")?)))
+        .collect::<Result<_, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT DeviceName, DeviceCaption, DeviceType FROM GpodderDevices WHERE UserID = ? AND IsActive = TRUE ORDER BY DeviceName",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok::<_, sqlx::Error>((row.try_get("DeviceName")?, row.try_get("DeviceCaption")?, row.try_get("DeviceType")?)))
+        .collect::<Result<_, _>>()?,
+    };
+    Ok(rows
+        .into_iter()
+        .map(|(id, caption, device_type)| Device {
+            caption: caption.filter(|c| !c.is_empty()).unwrap_or_else(|| id.clone()),
+            id,
+            device_type: device_type.unwrap_or_else(|| "other".to_string()),
+            subscriptions,
+        })
+        .collect())
+}
+
+/// Set a device's caption and type, registering it if needed. `None` leaves a field as it is.
+pub async fn update_device(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    name: &str,
+    caption: Option<&str>,
+    device_type: Option<&str>,
+) -> AppResult<()> {
+    let device_id = device_id(db_pool, user_id, name).await?;
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"UPDATE "GpodderDevices" SET devicecaption = COALESCE($1, devicecaption), devicetype = COALESCE($2, devicetype) WHERE deviceid = $3"#,
+            )
+            .bind(caption)
+            .bind(device_type)
+            .bind(device_id)
+            .execute(pool)
+            .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE GpodderDevices SET DeviceCaption = COALESCE(?, DeviceCaption), DeviceType = COALESCE(?, DeviceType) WHERE DeviceID = ?")
+                .bind(caption)
+                .bind(device_type)
+                .bind(device_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// What other devices changed in the user's subscriptions since `since`.
+pub async fn subscription_changes(db_pool: &DatabasePool, user_id: i32, device_id: i32, since: i64) -> AppResult<SubscriptionChanges> {
+    let rows: Vec<(String, String)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT podcasturl, action FROM "GpodderSyncSubscriptions"
+               WHERE userid = $1 AND deviceid != $2 AND timestamp >= $3
+               ORDER BY timestamp, subscriptionid"#,
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok::<_, sqlx::Error>((row.try_get("podcasturl")?, row.try_get("action")?)))
+        .collect::<Result<_, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT PodcastURL, Action FROM GpodderSyncSubscriptions
+             WHERE UserID = ? AND DeviceID != ? AND Timestamp >= ?
+             ORDER BY Timestamp, SubscriptionID",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok::<_, sqlx::Error>((row.try_get("PodcastURL")?, row.try_get("Action")?)))
+        .collect::<Result<_, _>>()?,
+    };
+    Ok(latest_changes(&rows))
+}
+
+/// Log a device's subscription changes for the other devices.
+pub async fn record_subscription_changes(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    device_id: i32,
+    changes: &SubscriptionChanges,
+    timestamp: i64,
+) -> AppResult<()> {
+    let rows = changes.add.iter().map(|url| (url, "add")).chain(changes.remove.iter().map(|url| (url, "remove")));
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let mut tx = pool.begin().await?;
+            for (url, action) in rows {
+                sqlx::query(r#"INSERT INTO "GpodderSyncSubscriptions" (userid, deviceid, podcasturl, action, timestamp) VALUES ($1, $2, $3, $4, $5)"#)
+                    .bind(user_id)
+                    .bind(device_id)
+                    .bind(url)
+                    .bind(action)
+                    .bind(timestamp)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            let mut tx = pool.begin().await?;
+            for (url, action) in rows {
+                sqlx::query("INSERT INTO GpodderSyncSubscriptions (UserID, DeviceID, PodcastURL, Action, Timestamp) VALUES (?, ?, ?, ?, ?)")
+                    .bind(user_id)
+                    .bind(device_id)
+                    .bind(url)
+                    .bind(action)
+                    .bind(timestamp)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Log uploaded episode actions, received at `received_at`, for the other devices.
+pub async fn record_episode_actions(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    device_ids: &HashMap<String, i32>,
+    actions: &[StoredAction],
+    received_at: i64,
+) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let mut tx = pool.begin().await?;
+            for action in actions {
+                sqlx::query(
+                    r#"INSERT INTO "GpodderSyncEpisodeActions"
+                       (userid, deviceid, podcasturl, episodeurl, action, timestamp, started, position, total, receivedat)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#,
+                )
+                .bind(user_id)
+                .bind(action.device.as_ref().and_then(|d| device_ids.get(d)))
+                .bind(&action.podcast)
+                .bind(&action.episode)
+                .bind(&action.action)
+                .bind(action.timestamp)
+                .bind(action.started)
+                .bind(action.position)
+                .bind(action.total)
+                .bind(received_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            let mut tx = pool.begin().await?;
+            for action in actions {
+                sqlx::query(
+                    "INSERT INTO GpodderSyncEpisodeActions
+                     (UserID, DeviceID, PodcastURL, EpisodeURL, Action, Timestamp, Started, Position, Total, ReceivedAt)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(user_id)
+                .bind(action.device.as_ref().and_then(|d| device_ids.get(d)))
+                .bind(&action.podcast)
+                .bind(&action.episode)
+                .bind(&action.action)
+                .bind(action.timestamp)
+                .bind(action.started)
+                .bind(action.position)
+                .bind(action.total)
+                .bind(received_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Logged episode actions matching `query`, each with the time the server received it.
+pub async fn episode_actions(db_pool: &DatabasePool, user_id: i32, query: &ActionQuery) -> AppResult<Vec<(StoredAction, i64)>> {
+    let order = if query.newest_first { "DESC" } else { "ASC" };
+    // Only static SQL is formatted in. Aggregated keeps the newest row per episode: no later
+    // row for the same episode exists.
+    let rows = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let aggregated = if query.aggregated {
+                r#" AND NOT EXISTS (SELECT 1 FROM "GpodderSyncEpisodeActions" n
+                     WHERE n.userid = e.userid AND n.episodeurl = e.episodeurl
+                     AND (n.timestamp > e.timestamp OR (n.timestamp = e.timestamp AND n.actionid > e.actionid)))"#
+            } else {
+                ""
+            };
+            let sql = format!(
+                r#"SELECT d.devicename, e.podcasturl, e.episodeurl, e.action, e.timestamp, e.started, e.position, e.total,
+                          COALESCE(e.receivedat, e.timestamp) AS received
+                   FROM "GpodderSyncEpisodeActions" e
+                   LEFT JOIN "GpodderDevices" d ON d.deviceid = e.deviceid
+                   WHERE e.userid = $1 AND COALESCE(e.receivedat, e.timestamp) >= $2
+                   AND ($3::TEXT IS NULL OR e.podcasturl = $3) AND ($4::INT IS NULL OR e.deviceid = $4){}
+                   ORDER BY received {}, e.actionid {}
+                   LIMIT $5"#,
+                aggregated, order, order
+            );
+            sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                .bind(user_id)
+                .bind(query.since)
+                .bind(&query.podcast)
+                .bind(query.device_id)
+                .bind(query.limit)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    Ok::<_, sqlx::Error>((
+                        StoredAction {
+                            device: row.try_get("devicename")?,
+                            podcast: row.try_get("podcasturl")?,
+                            episode: row.try_get("episodeurl")?,
+                            action: row.try_get("action")?,
+                            timestamp: row.try_get("timestamp")?,
+                            started: row.try_get("started")?,
+                            position: row.try_get("position")?,
+                            total: row.try_get("total")?,
+                        },
+                        row.try_get("received")?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        DatabasePool::MySQL(pool) => {
+            let aggregated = if query.aggregated {
+                " AND NOT EXISTS (SELECT 1 FROM GpodderSyncEpisodeActions n
+                   WHERE n.UserID = e.UserID AND n.EpisodeURL = e.EpisodeURL
+                   AND (n.Timestamp > e.Timestamp OR (n.Timestamp = e.Timestamp AND n.ActionID > e.ActionID)))"
+            } else {
+                ""
+            };
+            let sql = format!(
+                "SELECT d.DeviceName, e.PodcastURL, e.EpisodeURL, e.Action, e.Timestamp, e.Started, e.Position, e.Total,
+                        COALESCE(e.ReceivedAt, e.Timestamp) AS Received
+                 FROM GpodderSyncEpisodeActions e
+                 LEFT JOIN GpodderDevices d ON d.DeviceID = e.DeviceID
+                 WHERE e.UserID = ? AND COALESCE(e.ReceivedAt, e.Timestamp) >= ?
+                 AND (? IS NULL OR e.PodcastURL = ?) AND (? IS NULL OR e.DeviceID = ?){}
+                 ORDER BY Received {}, e.ActionID {}
+                 LIMIT ?",
+                aggregated, order, order
+            );
+            sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                .bind(user_id)
+                .bind(query.since)
+                .bind(&query.podcast)
+                .bind(&query.podcast)
+                .bind(query.device_id)
+                .bind(query.device_id)
+                .bind(query.limit)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    Ok::<_, sqlx::Error>((
+                        StoredAction {
+                            device: row.try_get("DeviceName")?,
+                            podcast: row.try_get("PodcastURL")?,
+                            episode: row.try_get("EpisodeURL")?,
+                            action: row.try_get("Action")?,
+                            timestamp: row.try_get("Timestamp")?,
+                            started: row.try_get("Started")?,
+                            position: row.try_get("Position")?,
+                            total: row.try_get("Total")?,
+                        },
+                        row.try_get("Received")?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+    Ok(rows)
+}
+
+/// The user's device names and stored sync pairs.
+pub async fn device_pairs(db_pool: &DatabasePool, user_id: i32) -> AppResult<(Vec<String>, Vec<(String, String)>)> {
+    let devices = devices(db_pool, user_id).await?.into_iter().map(|d| d.id).collect();
+    let pairs = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT d1.devicename AS first, d2.devicename AS second
+               FROM "GpodderSyncDevicePairs" p
+               JOIN "GpodderDevices" d1 ON d1.deviceid = p.deviceid1
+               JOIN "GpodderDevices" d2 ON d2.deviceid = p.deviceid2
+               WHERE p.userid = $1"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok::<_, sqlx::Error>((row.try_get("first")?, row.try_get("second")?)))
+        .collect::<Result<_, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT d1.DeviceName AS First, d2.DeviceName AS Second
+             FROM GpodderSyncDevicePairs p
+             JOIN GpodderDevices d1 ON d1.DeviceID = p.DeviceID1
+             JOIN GpodderDevices d2 ON d2.DeviceID = p.DeviceID2
+             WHERE p.UserID = ?",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok::<_, sqlx::Error>((row.try_get("First")?, row.try_get("Second")?)))
+        .collect::<Result<_, _>>()?,
+    };
+    Ok((devices, pairs))
+}
+
+/// Put two devices in the same sync group.
+pub async fn pair_devices(db_pool: &DatabasePool, user_id: i32, first: i32, second: i32) -> AppResult<()> {
+    let (low, high) = (first.min(second), first.max(second));
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"INSERT INTO "GpodderSyncDevicePairs" (userid, deviceid1, deviceid2) VALUES ($1, $2, $3)
+                   ON CONFLICT (userid, deviceid1, deviceid2) DO NOTHING"#,
+            )
+            .bind(user_id)
+            .bind(low)
+            .bind(high)
+            .execute(pool)
+            .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("INSERT IGNORE INTO GpodderSyncDevicePairs (UserID, DeviceID1, DeviceID2) VALUES (?, ?, ?)")
+                .bind(user_id)
+                .bind(low)
+                .bind(high)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Take a device out of its sync group.
+pub async fn unpair_device(db_pool: &DatabasePool, user_id: i32, device_id: i32) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"DELETE FROM "GpodderSyncDevicePairs" WHERE userid = $1 AND (deviceid1 = $2 OR deviceid2 = $2)"#)
+                .bind(user_id)
+                .bind(device_id)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("DELETE FROM GpodderSyncDevicePairs WHERE UserID = ? AND (DeviceID1 = ? OR DeviceID2 = ?)")
+                .bind(user_id)
+                .bind(device_id)
+                .bind(device_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// gpodder podcast objects for the user's podcasts among `feeds`, in the order given. Feeds the
+/// user isn't subscribed to (yet) are returned with just their URL.
+pub async fn podcast_details(db_pool: &DatabasePool, user_id: i32, feeds: &[String]) -> AppResult<Vec<serde_json::Value>> {
+    type PodcastRow = (String, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>);
+    let rows: Vec<PodcastRow> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT feedurl, podcastname, description, websiteurl, artworkurl, author FROM "Podcasts" WHERE userid = $1"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok::<_, sqlx::Error>((
+                row.try_get("feedurl")?,
+                row.try_get("podcastname")?,
+                row.try_get("description")?,
+                row.try_get("websiteurl")?,
+                row.try_get("artworkurl")?,
+                row.try_get("author")?,
+            ))
+        })
+        .collect::<Result<_, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT FeedURL, PodcastName, Description, WebsiteURL, ArtworkURL, Author FROM Podcasts WHERE UserID = ?",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok::<_, sqlx::Error>((
+                row.try_get("FeedURL")?,
+                row.try_get("PodcastName")?,
+                row.try_get("Description")?,
+                row.try_get("WebsiteURL")?,
+                row.try_get("ArtworkURL")?,
+                row.try_get("Author")?,
+            ))
+        })
+        .collect::<Result<_, _>>()?,
+    };
+    let by_url: HashMap<&str, _> = rows.iter().map(|r| (r.0.as_str(), r)).collect();
+    Ok(feeds
+        .iter()
+        .map(|feed| match by_url.get(feed.as_str()) {
+            Some((url, title, description, website, logo, author)) => serde_json::json!({
+                "url": url,
+                "title": title.clone().unwrap_or_default(),
+                "description": description.clone().unwrap_or_default(),
+                "website": website.clone().unwrap_or_default(),
+                "logo_url": logo.clone().unwrap_or_default(),
+                "author": author.clone().unwrap_or_default(),
+                "subscribers": 1,
+            }),
+            None => serde_json::json!({"url": feed}),
+        })
+        .collect())
+}
+
+/// gpodder episode objects for episodes of the user's podcasts published since `since`.
+pub async fn new_episodes(db_pool: &DatabasePool, user_id: i32, since: i64) -> AppResult<Vec<serde_json::Value>> {
+    let since = DateTime::<Utc>::from_timestamp(since, 0).unwrap_or_default().naive_utc();
+    type EpisodeRow = (Option<String>, Option<String>, Option<String>, String, Option<String>, Option<String>, Option<NaiveDateTime>);
+    let rows: Vec<EpisodeRow> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT e.episodetitle, e.episodeurl, p.podcastname, p.feedurl, e.episodedescription, p.websiteurl, e.episodepubdate
+               FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               WHERE p.userid = $1 AND e.episodepubdate >= $2
+               ORDER BY e.episodepubdate
+               LIMIT 1000"#,
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok::<_, sqlx::Error>((
+                row.try_get("episodetitle")?,
+                row.try_get("episodeurl")?,
+                row.try_get("podcastname")?,
+                row.try_get("feedurl")?,
+                row.try_get("episodedescription")?,
+                row.try_get("websiteurl")?,
+                row.try_get("episodepubdate")?,
+            ))
+        })
+        .collect::<Result<_, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT e.EpisodeTitle, e.EpisodeURL, p.PodcastName, p.FeedURL, e.EpisodeDescription, p.WebsiteURL, e.EpisodePubDate
+             FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             WHERE p.UserID = ? AND e.EpisodePubDate >= ?
+             ORDER BY e.EpisodePubDate
+             LIMIT 1000",
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok::<_, sqlx::Error>((
+                row.try_get("EpisodeTitle")?,
+                row.try_get("EpisodeURL")?,
+                row.try_get("PodcastName")?,
+                row.try_get("FeedURL")?,
+                row.try_get("EpisodeDescription")?,
+                row.try_get("WebsiteURL")?,
+                row.try_get("EpisodePubDate")?,
+            ))
+        })
+        .collect::<Result<_, _>>()?,
+    };
+    Ok(rows
+        .into_iter()
+        .filter_map(|(title, url, podcast_title, podcast_url, description, website, released)| {
+            Some(serde_json::json!({
+                "title": title.unwrap_or_default(),
+                "url": url.filter(|u| !u.is_empty())?,
+                "podcast_title": podcast_title.unwrap_or_default(),
+                "podcast_url": podcast_url,
+                "description": description.unwrap_or_default(),
+                "website": website.unwrap_or_default(),
+                "released": released?.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                "status": "new",
+            }))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_formats_and_cleans_urls() {
+        assert_eq!(split_format("alice.json"), ("alice", Some("json")));
+        assert_eq!(split_format("j.doe.opml"), ("j.doe", Some("opml")));
+        assert_eq!(split_format("phone"), ("phone", None));
+        assert_eq!(split_format("my.phone"), ("my.phone", None));
+        assert_eq!(clean_url("  https://example.com/feed.xml\n").as_deref(), Some("https://example.com/feed.xml"));
+        assert_eq!(clean_url("ftp://example.com/feed"), None);
+        assert_eq!(clean_url("not a url"), None);
+    }
+
+    #[test]
+    fn subscription_lists_round_trip() {
+        let feeds = strings(&["https://example.com/feed?a=1&b=2", "https://example.org/rss"]);
+        assert_eq!(parse_subscription_list(&opml(&feeds), Some("opml")).unwrap(), feeds);
+        assert_eq!(parse_subscription_list("https://a\n\n  https://b \n", Some("txt")).unwrap(), strings(&["https://a", "https://b"]));
+        assert_eq!(parse_subscription_list(r#"["https://a"]"#, Some("json")).unwrap(), strings(&["https://a"]));
+        assert!(parse_subscription_list("https://a", Some("json")).is_err());
+    }
+
+    #[test]
+    fn last_logged_change_per_feed_wins() {
+        let rows = vec![
+            ("https://a".to_string(), "add".to_string()),
+            ("https://b".to_string(), "add".to_string()),
+            ("https://a".to_string(), "remove".to_string()),
+            ("https://c".to_string(), "remove".to_string()),
+            ("https://c".to_string(), "add".to_string()),
+        ];
+        let changes = latest_changes(&rows);
+        assert_eq!(changes.add, strings(&["https://b", "https://c"]));
+        assert_eq!(changes.remove, strings(&["https://a"]));
+    }
+
+    #[test]
+    fn chained_pairs_form_one_group() {
+        let devices = strings(&["tablet", "phone", "laptop", "desktop", "car"]);
+        let pairs = vec![
+            ("phone".to_string(), "tablet".to_string()),
+            ("laptop".to_string(), "desktop".to_string()),
+            ("tablet".to_string(), "laptop".to_string()),
+        ];
+        let (groups, unsynced) = sync_groups(&devices, &pairs);
+        assert_eq!(groups, vec![strings(&["desktop", "laptop", "phone", "tablet"])]);
+        assert_eq!(unsynced, strings(&["car"]));
+
+        let (groups, unsynced) = sync_groups(&devices, &[("car".to_string(), "phone".to_string())]);
+        assert_eq!(groups, vec![strings(&["car", "phone"])]);
+        assert_eq!(unsynced, strings(&["desktop", "laptop", "tablet"]));
+    }
+
+    #[test]
+    fn actions_serialize_in_the_antennapod_format() {
+        let action = StoredAction {
+            device: Some("phone".to_string()),
+            podcast: "https://example.com/feed".to_string(),
+            episode: "https://example.com/1.mp3".to_string(),
+            action: "play".to_string(),
+            timestamp: 1714557600,
+            started: Some(0),
+            position: Some(120),
+            total: None,
+        };
+        assert_eq!(
+            action.to_json(),
+            serde_json::json!({
+                "podcast": "https://example.com/feed",
+                "episode": "https://example.com/1.mp3",
+                "action": "play",
+                "timestamp": "2024-05-01T10:00:00",
+                "device": "phone",
+                "started": 0,
+                "position": 120,
+            })
+        );
+    }
+}
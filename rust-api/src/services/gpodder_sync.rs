@@ -0,0 +1,1581 @@
+//! Two-way sync of subscriptions and episode actions with a gpodder server (the built-in one or an
+//! external gpodder.net-compatible service) or a Nextcloud instance running the gPodder Sync app.
+//!
+//! A run against a target (the server URL) asks only for what changed on the server since the
+//! cursors the previous run logged, then reconciles:
+//!
+//! * **Subscriptions** three ways: the remote changes, the local changes since the last run (the
+//!   current feeds diffed against the target's `GpodderSubscriptionSnapshot`) and unsubscribe
+//!   tombstones. When both sides changed the same feed, the local change wins and is pushed
+//!   back ([`plan_subscriptions`]). A local unsubscribe stays an unpushed tombstone until the
+//!   server accepts it, so a failed upload is retried on the next run and a re-add from another
+//!   device can't bring the feed back in the meantime.
+//! * **Episode actions** by time. The newest action each device reported per episode is kept in
+//!   `GpodderDeviceEpisodeActions`; of the play actions received, only the newest per episode
+//!   is considered, and it is applied only if it is newer than the local listen date. A later
+//!   rewind on another device moves the position back, while a device that was offline for a
+//!   while can't drag positions back to where it last was.
+//!
+//! Every run appends a row to `GpodderSyncLog`, which `/gpodder_statistics` shows alongside the
+//! per-device activity. The local side is behind [`SyncStore`] so the engine can be exercised
+//! against a fake server without a database.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// The URL stored for users syncing with the gpodder server built into this API, which takes
+/// the user's token in a header. Requests for it go to [`INTERNAL_API_BASE`].
+pub const INTERNAL_GPODDER_URL: &str = "http://localhost:8042";
+
+/// Where this API listens, for requests to the built-in gpodder server.
+const INTERNAL_API_BASE: &str = "http://127.0.0.1:8032";
+
+/// gpodder servers cap each episode action response at this many actions.
+const MAX_ACTIONS_PER_BATCH: usize = 25000;
+/// A synced position this close to the end marks the episode completed.
+const COMPLETED_WITHIN_SECONDS: i32 = 60;
+/// Sync log rows kept per user and target.
+const LOG_ROWS_KEPT: i64 = 50;
+/// Pushed tombstones are forgotten after this many days.
+const TOMBSTONE_DAYS: i64 = 90;
+/// Actions from clients that don't name a device are grouped under this one.
+const UNKNOWN_DEVICE: &str = "unknown";
+
+const ORIGIN_LOCAL: &str = "local";
+const ORIGIN_REMOTE: &str = "remote";
+
+const STATUS_SUCCESS: &str = "success";
+const STATUS_PARTIAL: &str = "partial";
+const STATUS_FAILED: &str = "failed";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Protocol {
+    #[default]
+    Gpodder,
+    Nextcloud,
+}
+
+impl Protocol {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Gpodder => "gpodder",
+            Protocol::Nextcloud => "nextcloud",
+        }
+    }
+}
+
+/// A server to sync with and the credentials to use.
+#[derive(Debug, Clone)]
+pub struct Remote {
+    pub protocol: Protocol,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// Device local subscription changes and actions are uploaded as (gpodder only).
+    pub device: String,
+}
+
+impl Remote {
+    pub fn gpodder(url: &str, username: &str, password: &str, device: &str) -> Self {
+        Remote {
+            protocol: Protocol::Gpodder,
+            url: url.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            device: device.to_string(),
+        }
+    }
+
+    pub fn nextcloud(url: &str, username: &str, password: &str) -> Self {
+        Remote {
+            protocol: Protocol::Nextcloud,
+            url: url.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            device: String::new(),
+        }
+    }
+
+    /// The key snapshots, tombstones and log rows are stored under.
+    pub fn target(&self) -> &str {
+        self.url.trim_end_matches('/')
+    }
+
+    fn is_internal(&self) -> bool {
+        self.protocol == Protocol::Gpodder && self.target() == INTERNAL_GPODDER_URL
+    }
+}
+
+/// The base URL to send gpodder API requests for a stored server URL to.
+pub fn api_base(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    if url == INTERNAL_GPODDER_URL {
+        INTERNAL_API_BASE
+    } else {
+        url
+    }
+}
+
+/// One episode action as reported by a server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeAction {
+    pub podcast: String,
+    pub episode: String,
+    pub action: String,
+    pub device: String,
+    pub timestamp: DateTime<Utc>,
+    pub position: Option<i32>,
+}
+
+/// Subscription adds and removes, from either side.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubscriptionChanges {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+/// What a run does with subscriptions.
+#[derive(Debug, Default, PartialEq)]
+pub struct SubscriptionPlan {
+    /// Feeds to subscribe to locally.
+    pub subscribe: Vec<String>,
+    /// Feeds to unsubscribe from locally.
+    pub unsubscribe: Vec<String>,
+    pub push_add: Vec<String>,
+    pub push_remove: Vec<String>,
+    /// Feeds both sides changed since the last run. The local change won and is pushed.
+    pub conflicts: Vec<String>,
+}
+
+/// Where the previous run left off.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cursor {
+    /// Server timestamp to ask for subscription changes since.
+    pub subscriptions: i64,
+    /// Server timestamp to ask for episode actions since.
+    pub actions: i64,
+    /// Local episode actions up to this time have been uploaded; `None` uploads them all.
+    pub local: Option<DateTime<Utc>>,
+}
+
+/// A local episode a remote action refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalEpisode {
+    pub episode_id: i32,
+    pub duration: i32,
+    pub listened_at: Option<DateTime<Utc>>,
+}
+
+/// The outcome of one run, as written to `GpodderSyncLog`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    pub protocol: Protocol,
+    pub full: bool,
+    pub status: &'static str,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub subscriptions_added: i32,
+    pub subscriptions_removed: i32,
+    pub subscriptions_pushed: i32,
+    pub actions_received: i32,
+    pub actions_applied: i32,
+    pub actions_stale: i32,
+    pub actions_uploaded: i32,
+    pub conflicts: i32,
+    pub cursor: Cursor,
+    pub error: Option<String>,
+}
+
+impl SyncReport {
+    /// Whether the run changed anything locally.
+    pub fn changed(&self) -> bool {
+        self.subscriptions_added + self.subscriptions_removed + self.actions_applied > 0
+    }
+}
+
+/// A logged run as shown by `/gpodder_statistics`.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SyncRun {
+    pub target: String,
+    pub protocol: String,
+    pub full_sync: bool,
+    /// "success", "partial" (an upload failed and will be retried) or "failed"
+    pub status: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub subscriptions_added: i32,
+    pub subscriptions_removed: i32,
+    pub subscriptions_pushed: i32,
+    pub actions_received: i32,
+    pub actions_applied: i32,
+    /// Play actions skipped because a newer one (remote or local) already set the position
+    pub actions_stale: i32,
+    pub actions_uploaded: i32,
+    pub conflicts: i32,
+    pub error: Option<String>,
+}
+
+/// The newest action a remote device has reported, summed up per device.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DeviceActivity {
+    pub target: String,
+    pub device: String,
+    pub episodes: i64,
+    pub last_action_at: String,
+}
+
+/// Read a gpodder timestamp: Unix seconds, RFC 3339, or the spec's zoneless UTC
+/// `2009-12-12T09:00:00`.
+pub fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    if let Some(seconds) = value.as_i64() {
+        return Utc.timestamp_opt(seconds, 0).single();
+    }
+    let text = value.as_str()?;
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|t| t.and_utc())
+}
+
+/// Read an episode action. Actions without an episode, action type or timestamp can't be merged
+/// and are dropped.
+pub fn parse_action(value: &serde_json::Value) -> Option<EpisodeAction> {
+    let text = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let episode = text("episode");
+    let action = text("action").to_ascii_lowercase();
+    if episode.is_empty() || action.is_empty() {
+        return None;
+    }
+    let device = Some(text("device")).filter(|d| !d.is_empty()).unwrap_or_else(|| UNKNOWN_DEVICE.to_string());
+    Some(EpisodeAction {
+        podcast: text("podcast"),
+        episode,
+        action,
+        device,
+        timestamp: parse_timestamp(value.get("timestamp")?)?,
+        position: value.get("position").and_then(|p| p.as_i64()).map(|p| p as i32),
+    })
+}
+
+/// The newest play action with a position per episode, oldest first. On equal timestamps the
+/// one the server listed last wins.
+pub fn newest_plays(actions: &[EpisodeAction]) -> Vec<&EpisodeAction> {
+    let mut newest: HashMap<&str, &EpisodeAction> = HashMap::new();
+    for action in actions.iter().filter(|a| a.action == "play" && a.position.is_some()) {
+        match newest.get(action.episode.as_str()) {
+            Some(current) if current.timestamp > action.timestamp => {}
+            _ => {
+                newest.insert(&action.episode, action);
+            }
+        }
+    }
+    let mut plays: Vec<_> = newest.into_values().collect();
+    plays.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.episode.cmp(&b.episode)));
+    plays
+}
+
+/// Whether a remote action should replace the local position.
+pub fn is_newer(action_at: DateTime<Utc>, listened_at: Option<DateTime<Utc>>) -> bool {
+    listened_at.is_none_or(|local| action_at > local)
+}
+
+/// Whether a position finishes an episode.
+pub fn completes(position: i32, duration: i32) -> bool {
+    duration > 0 && position >= duration - COMPLETED_WITHIN_SECONDS
+}
+
+fn unique(feeds: &[String]) -> Vec<&String> {
+    let mut seen = HashSet::new();
+    feeds.iter().filter(|f| seen.insert(*f)).collect()
+}
+
+/// Reconcile remote and local subscription changes since the last run.
+///
+/// `local` is the current local feed set and `local_changes` what changed locally since the last
+/// run, with unpushed tombstones counted as removals. A feed the server reports as both added
+/// and removed (different devices disagreeing) is left as it is locally. When a feed was changed
+/// on both sides, the local change wins: it is kept and pushed again.
+pub fn plan_subscriptions(
+    remote: &SubscriptionChanges,
+    local: &HashSet<String>,
+    local_changes: &SubscriptionChanges,
+) -> SubscriptionPlan {
+    let ambiguous: HashSet<&String> = remote.add.iter().filter(|f| remote.remove.contains(f)).collect();
+    let remote_add: HashSet<&String> = remote.add.iter().filter(|f| !ambiguous.contains(f)).collect();
+    let remote_remove: HashSet<&String> = remote.remove.iter().filter(|f| !ambiguous.contains(f)).collect();
+    let local_add: HashSet<&String> = local_changes.add.iter().collect();
+    let local_remove: HashSet<&String> = local_changes.remove.iter().collect();
+
+    let mut plan = SubscriptionPlan::default();
+    for feed in unique(&remote.add).into_iter().filter(|f| remote_add.contains(f)) {
+        if local_remove.contains(feed) {
+            plan.conflicts.push(feed.clone());
+        } else if !local.contains(feed) {
+            plan.subscribe.push(feed.clone());
+        }
+    }
+    for feed in unique(&remote.remove).into_iter().filter(|f| remote_remove.contains(f)) {
+        if local_add.contains(feed) {
+            plan.conflicts.push(feed.clone());
+        } else if local.contains(feed) {
+            plan.unsubscribe.push(feed.clone());
+        }
+    }
+    // Anything the server already has is not pushed again; conflicting changes always are.
+    plan.push_add = unique(&local_changes.add).into_iter().filter(|f| !remote_add.contains(f)).cloned().collect();
+    plan.push_remove = unique(&local_changes.remove)
+        .into_iter()
+        .filter(|f| !remote_remove.contains(f))
+        .cloned()
+        .collect();
+    plan
+}
+
+/// The local side of a sync.
+#[async_trait::async_trait]
+pub trait SyncStore: Send + Sync {
+    /// The user's syncable (public http/https) feed URLs.
+    async fn feeds(&self, user_id: i32) -> AppResult<Vec<String>>;
+    async fn snapshot(&self, user_id: i32, target: &str) -> AppResult<HashSet<String>>;
+    async fn save_snapshot(&self, user_id: i32, target: &str, feeds: &[String]) -> AppResult<()>;
+    async fn unpushed_tombstones(&self, user_id: i32, target: &str) -> AppResult<Vec<String>>;
+    /// Insert or update tombstones, keeping the original removal time of existing ones.
+    async fn record_tombstones(&self, user_id: i32, target: &str, feeds: &[String], origin: &str, pushed: bool) -> AppResult<()>;
+    async fn clear_tombstones(&self, user_id: i32, target: &str, feeds: &[String]) -> AppResult<()>;
+    async fn subscribe(&self, user_id: i32, feed_url: &str) -> AppResult<()>;
+    async fn unsubscribe(&self, user_id: i32, feed_url: &str) -> AppResult<()>;
+    /// Keep the newest action per device and episode.
+    async fn record_device_actions(&self, user_id: i32, target: &str, actions: &[EpisodeAction]) -> AppResult<()>;
+    async fn local_episode(&self, user_id: i32, episode_url: &str) -> AppResult<Option<LocalEpisode>>;
+    /// Set the listen position and date from a remote action, and mark the episode completed.
+    async fn set_position(&self, user_id: i32, episode: &LocalEpisode, position: i32, at: DateTime<Utc>, completed: bool) -> AppResult<()>;
+    /// Local episode actions in gpodder's upload format, changed after `since`.
+    async fn local_actions_since(&self, user_id: i32, since: Option<DateTime<Utc>>) -> AppResult<Vec<serde_json::Value>>;
+    async fn cursor(&self, user_id: i32, target: &str) -> AppResult<Cursor>;
+    async fn finish_run(&self, user_id: i32, target: &str, report: &SyncReport) -> AppResult<()>;
+}
+
+/// An authenticated connection to a [`Remote`].
+struct Client<'a> {
+    remote: &'a Remote,
+    http: reqwest::Client,
+    /// External gpodder servers prefer a login session; basic auth is the fallback.
+    session: bool,
+}
+
+impl<'a> Client<'a> {
+    async fn connect(remote: &'a Remote) -> Result<Client<'a>, String> {
+        let http = reqwest::Client::builder()
+            .cookie_provider(std::sync::Arc::new(reqwest::cookie::Jar::default()))
+            .timeout(Duration::from_secs(300))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let mut client = Client { remote, http, session: false };
+        if remote.protocol == Protocol::Gpodder && !remote.is_internal() {
+            let login = format!("{}/api/2/auth/{}/login.json", remote.target(), remote.username);
+            let response = client.http.post(&login).basic_auth(&remote.username, Some(&remote.password)).send().await;
+            client.session = matches!(response, Ok(r) if r.status().is_success());
+            if !client.session {
+                debug!("gPodder session login to {} failed, using basic auth", remote.target());
+            }
+        }
+        Ok(client)
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.remote.is_internal() {
+            request.header("X-GPodder-Token", &self.remote.password)
+        } else if self.session {
+            request
+        } else {
+            request.basic_auth(&self.remote.username, Some(&self.remote.password))
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", api_base(self.remote.target()), path)
+    }
+
+    async fn get(&self, path: &str, since: i64) -> Result<serde_json::Value, String> {
+        let response = self
+            .authorize(self.http.get(self.url(path)).query(&[("since", since)]))
+            .send()
+            .await
+            .map_err(|e| format!("GET {} failed: {}", path, e))?;
+        if !response.status().is_success() {
+            return Err(format!("GET {} returned {}", path, response.status()));
+        }
+        response.json().await.map_err(|e| format!("GET {} returned unreadable JSON: {}", path, e))
+    }
+
+    async fn post(&self, path: &str, body: &serde_json::Value) -> Result<(), String> {
+        let response = self
+            .authorize(self.http.post(self.url(path)).json(body))
+            .send()
+            .await
+            .map_err(|e| format!("POST {} failed: {}", path, e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("POST {} returned {}: {}", path, status, text));
+        }
+        Ok(())
+    }
+
+    /// Subscription changes since `since` and the server's new timestamp. For gpodder these are
+    /// the union over all of the user's devices, so changes made in other apps are seen.
+    async fn subscription_changes(&self, since: i64) -> Result<(SubscriptionChanges, i64), String> {
+        let urls = |value: &serde_json::Value| -> Vec<String> {
+            value.as_array().map(|a| a.iter().filter_map(|u| u.as_str().map(str::to_string)).collect()).unwrap_or_default()
+        };
+        match self.remote.protocol {
+            Protocol::Nextcloud => {
+                let body = self.get("/index.php/apps/gpoddersync/subscriptions", since).await?;
+                // Older servers answer with a bare array of subscribed feeds.
+                if body.is_array() {
+                    return Ok((SubscriptionChanges { add: urls(&body), remove: Vec::new() }, since));
+                }
+                let timestamp = body.get("timestamp").and_then(|t| t.as_i64()).unwrap_or(since);
+                Ok((SubscriptionChanges { add: urls(&body["add"]), remove: urls(&body["remove"]) }, timestamp))
+            }
+            Protocol::Gpodder => {
+                let devices = self.get(&format!("/api/2/devices/{}.json", self.remote.username), 0).await?;
+                let devices: Vec<String> = devices
+                    .as_array()
+                    .map(|d| d.iter().filter_map(|d| d.get("id")?.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default();
+                debug!("Reading subscription changes from {} devices: {:?}", devices.len(), devices);
+
+                let mut changes = SubscriptionChanges::default();
+                let mut timestamp = since;
+                for device in &devices {
+                    let path = format!("/api/2/subscriptions/{}/{}.json", self.remote.username, device);
+                    match self.get(&path, since).await {
+                        Ok(body) => {
+                            changes.add.extend(urls(&body["add"]));
+                            changes.remove.extend(urls(&body["remove"]));
+                            timestamp = timestamp.max(body.get("timestamp").and_then(|t| t.as_i64()).unwrap_or(since));
+                        }
+                        Err(e) => warn!("Skipping subscriptions of device {}: {}", device, e),
+                    }
+                }
+                Ok((changes, timestamp))
+            }
+        }
+    }
+
+    /// Episode actions since `since` from all devices, and the server's new timestamp.
+    async fn episode_actions(&self, since: i64) -> Result<(Vec<serde_json::Value>, i64), String> {
+        let path = match self.remote.protocol {
+            Protocol::Nextcloud => "/index.php/apps/gpoddersync/episode_action".to_string(),
+            // No device filter: it would leave out actions uploaded without a device.
+            Protocol::Gpodder => format!("/api/2/episodes/{}.json", self.remote.username),
+        };
+        let mut actions = Vec::new();
+        let mut current = since;
+        loop {
+            let body = self.get(&path, current).await?;
+            let batch = match body.get("actions").or(Some(&body)).and_then(|a| a.as_array()) {
+                Some(batch) => batch.clone(),
+                None => Vec::new(),
+            };
+            let timestamp = body.get("timestamp").and_then(|t| t.as_i64()).unwrap_or(current);
+            let full_batch = batch.len() >= MAX_ACTIONS_PER_BATCH;
+            actions.extend(batch);
+            if self.remote.protocol == Protocol::Nextcloud || !full_batch || timestamp <= current {
+                return Ok((actions, timestamp.max(current)));
+            }
+            current = timestamp;
+        }
+    }
+
+    async fn upload_subscription_changes(&self, add: &[String], remove: &[String]) -> Result<(), String> {
+        let path = match self.remote.protocol {
+            Protocol::Nextcloud => "/index.php/apps/gpoddersync/subscription_change/create".to_string(),
+            Protocol::Gpodder => format!("/api/2/subscriptions/{}/{}.json", self.remote.username, self.remote.device),
+        };
+        self.post(&path, &serde_json::json!({ "add": add, "remove": remove })).await
+    }
+
+    async fn upload_episode_actions(&self, actions: &[serde_json::Value]) -> Result<(), String> {
+        let path = match self.remote.protocol {
+            Protocol::Nextcloud => "/index.php/apps/gpoddersync/episode_action/create".to_string(),
+            Protocol::Gpodder => format!("/api/2/episodes/{}.json", self.remote.username),
+        };
+        self.post(&path, &serde_json::json!(actions)).await
+    }
+}
+
+/// Sync one user with one server and log the run. A `full` run ignores the previous cursors and
+/// snapshot: it reads everything the server has and pushes every local subscription and action,
+/// as the first sync after setting up a server does.
+pub async fn run(store: &dyn SyncStore, user_id: i32, remote: &Remote, full: bool) -> AppResult<SyncReport> {
+    let target = remote.target().to_string();
+    let mut report = SyncReport {
+        protocol: remote.protocol,
+        full,
+        status: STATUS_SUCCESS,
+        started_at: Utc::now(),
+        ..Default::default()
+    };
+    info!("Starting {} sync for user {} with {}", remote.protocol.as_str(), user_id, target);
+
+    let result = sync(store, user_id, remote, &target, &mut report).await;
+    if let Err(e) = &result {
+        report.status = STATUS_FAILED;
+        report.error = Some(e.to_string());
+    }
+    report.finished_at = Utc::now();
+    if let Err(e) = store.finish_run(user_id, &target, &report).await {
+        warn!("Failed to log sync run for user {}: {}", user_id, e);
+    }
+    info!(
+        "Sync for user {} with {} {}: {} subscribed, {} unsubscribed, {} pushed, {}/{} actions applied ({} stale), {} uploaded, {} conflicts",
+        user_id, target, report.status, report.subscriptions_added, report.subscriptions_removed, report.subscriptions_pushed,
+        report.actions_applied, report.actions_received, report.actions_stale, report.actions_uploaded, report.conflicts
+    );
+    result.map(|_| report)
+}
+
+async fn sync(store: &dyn SyncStore, user_id: i32, remote: &Remote, target: &str, report: &mut SyncReport) -> AppResult<()> {
+    let previous = if report.full { Cursor::default() } else { store.cursor(user_id, target).await? };
+    let client = Client::connect(remote).await.map_err(AppError::external_error)?;
+
+    // Read the server first: a run that can't see the server's subscriptions changes nothing.
+    let (remote_changes, subscription_cursor) =
+        client.subscription_changes(previous.subscriptions).await.map_err(AppError::external_error)?;
+    let (remote_actions, action_cursor) = match client.episode_actions(previous.actions).await {
+        Ok(actions) => actions,
+        Err(e) => {
+            warn!("Could not read episode actions from {}: {}", target, e);
+            report.status = STATUS_PARTIAL;
+            report.error = Some(e);
+            (Vec::new(), previous.actions)
+        }
+    };
+    report.cursor = Cursor { subscriptions: subscription_cursor, actions: action_cursor, local: previous.local };
+
+    // Local changes since the last run, plus removals the server hasn't accepted yet.
+    let feeds = store.feeds(user_id).await?;
+    let local: HashSet<String> = feeds.iter().cloned().collect();
+    let snapshot = if report.full { HashSet::new() } else { store.snapshot(user_id, target).await? };
+    let mut local_changes = SubscriptionChanges {
+        add: feeds.iter().filter(|f| !snapshot.contains(*f)).cloned().collect(),
+        remove: snapshot.iter().filter(|f| !local.contains(*f)).cloned().collect(),
+    };
+    store.record_tombstones(user_id, target, &local_changes.remove, ORIGIN_LOCAL, false).await?;
+    for feed in store.unpushed_tombstones(user_id, target).await? {
+        if !local.contains(&feed) && !local_changes.remove.contains(&feed) {
+            local_changes.remove.push(feed);
+        }
+    }
+    // Subscribing again voids an earlier unsubscribe.
+    store.clear_tombstones(user_id, target, &local_changes.add).await?;
+
+    let plan = plan_subscriptions(&remote_changes, &local, &local_changes);
+    report.conflicts = plan.conflicts.len() as i32;
+    for feed in &plan.conflicts {
+        info!("Subscription to {} changed on both sides since the last sync, keeping the local change", feed);
+    }
+    for feed in &plan.subscribe {
+        match store.subscribe(user_id, feed).await {
+            Ok(()) => report.subscriptions_added += 1,
+            Err(e) => warn!("Failed to subscribe user {} to {}: {}", user_id, feed, e),
+        }
+    }
+    store.clear_tombstones(user_id, target, &plan.subscribe).await?;
+    let mut unsubscribed = Vec::new();
+    for feed in &plan.unsubscribe {
+        match store.unsubscribe(user_id, feed).await {
+            Ok(()) => unsubscribed.push(feed.clone()),
+            Err(e) => warn!("Failed to unsubscribe user {} from {}: {}", user_id, feed, e),
+        }
+    }
+    report.subscriptions_removed = unsubscribed.len() as i32;
+    store.record_tombstones(user_id, target, &unsubscribed, ORIGIN_REMOTE, true).await?;
+
+    // Our own uploads come back from gpodder servers under our device; they are already applied.
+    let actions: Vec<EpisodeAction> = remote_actions
+        .iter()
+        .filter_map(parse_action)
+        .filter(|a| remote.protocol != Protocol::Gpodder || a.device != remote.device)
+        .collect();
+    report.actions_received = actions.len() as i32;
+    let outcome = apply_actions(store, user_id, target, &actions).await?;
+    report.actions_applied = outcome.applied.len() as i32;
+    report.actions_stale = outcome.stale;
+    let applied = outcome.applied;
+
+    // Push local subscription changes. Adds that fail to upload stay out of the snapshot and
+    // removes stay unpushed tombstones, so the next run tries them again.
+    let mut pending_adds: HashSet<&String> = HashSet::new();
+    if !plan.push_add.is_empty() || !plan.push_remove.is_empty() {
+        match client.upload_subscription_changes(&plan.push_add, &plan.push_remove).await {
+            Ok(()) => {
+                report.subscriptions_pushed = (plan.push_add.len() + plan.push_remove.len()) as i32;
+                store.record_tombstones(user_id, target, &plan.push_remove, ORIGIN_LOCAL, true).await?;
+            }
+            Err(e) => {
+                warn!("Subscription upload to {} failed: {}", target, e);
+                report.status = STATUS_PARTIAL;
+                report.error = Some(e);
+                pending_adds.extend(plan.push_add.iter());
+            }
+        }
+    }
+    let snapshot: Vec<String> = store.feeds(user_id).await?.into_iter().filter(|f| !pending_adds.contains(f)).collect();
+    store.save_snapshot(user_id, target, &snapshot).await?;
+
+    // Upload local actions since the last successful upload, except those just taken from the server.
+    let upload_started = Utc::now();
+    let mut uploads: Vec<serde_json::Value> = store
+        .local_actions_since(user_id, previous.local)
+        .await?
+        .into_iter()
+        .filter(|a| !applied.contains(a.get("episode").and_then(|e| e.as_str()).unwrap_or_default()))
+        .collect();
+    if remote.protocol == Protocol::Gpodder {
+        for action in &mut uploads {
+            action["device"] = serde_json::json!(remote.device);
+        }
+    }
+    if uploads.is_empty() {
+        report.cursor.local = Some(upload_started);
+    } else {
+        match client.upload_episode_actions(&uploads).await {
+            Ok(()) => {
+                report.actions_uploaded = uploads.len() as i32;
+                report.cursor.local = Some(upload_started);
+            }
+            Err(e) => {
+                warn!("Episode action upload to {} failed: {}", target, e);
+                report.status = STATUS_PARTIAL;
+                report.error = Some(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What [`apply_actions`] did with a batch of episode actions.
+#[derive(Debug, Default)]
+pub struct AppliedActions {
+    /// Episode URLs whose local position was set.
+    pub applied: HashSet<String>,
+    /// Plays superseded by a newer play in the batch or older than the local listen date.
+    pub stale: i32,
+}
+
+/// Record each device's newest action per episode and apply the newest play of each episode
+/// that is newer than the local listen date. Used by sync runs and by the built-in server when
+/// a client uploads actions.
+pub async fn apply_actions(
+    store: &dyn SyncStore,
+    user_id: i32,
+    target: &str,
+    actions: &[EpisodeAction],
+) -> AppResult<AppliedActions> {
+    store.record_device_actions(user_id, target, actions).await?;
+    let plays = newest_plays(actions);
+    let mut outcome = AppliedActions {
+        stale: actions.iter().filter(|a| a.action == "play" && a.position.is_some()).count() as i32 - plays.len() as i32,
+        ..Default::default()
+    };
+    let mut not_found = 0;
+    for play in plays {
+        let Some(episode) = store.local_episode(user_id, &play.episode).await? else {
+            not_found += 1;
+            continue;
+        };
+        if !is_newer(play.timestamp, episode.listened_at) {
+            debug!("Ignoring stale position for {} from {} ({})", play.episode, play.device, play.timestamp);
+            outcome.stale += 1;
+            continue;
+        }
+        let position = play.position.unwrap_or(0).max(0);
+        store
+            .set_position(user_id, &episode, position, play.timestamp, completes(position, episode.duration))
+            .await?;
+        outcome.applied.insert(play.episode.clone());
+    }
+    if not_found > 0 {
+        debug!("{} episode actions refer to episodes user {} doesn't have", not_found, user_id);
+    }
+    Ok(outcome)
+}
+
+fn format_time(time: Option<NaiveDateTime>) -> String {
+    time.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()).unwrap_or_default()
+}
+
+/// The user's most recent sync runs across all targets, newest first.
+pub async fn recent_runs(db_pool: &DatabasePool, user_id: i32, limit: i64) -> AppResult<Vec<SyncRun>> {
+    let runs = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT synctarget, protocol, fullsync, status, startedat, finishedat, subscriptionsadded,
+                      subscriptionsremoved, subscriptionspushed, actionsreceived, actionsapplied, actionsstale,
+                      actionsuploaded, conflicts, error
+               FROM "GpodderSyncLog" WHERE userid = $1 ORDER BY synclogid DESC LIMIT $2"#,
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<SyncRun> {
+            Ok(SyncRun {
+                target: r.try_get("synctarget")?,
+                protocol: r.try_get("protocol")?,
+                full_sync: r.try_get("fullsync")?,
+                status: r.try_get("status")?,
+                started_at: format_time(r.try_get("startedat")?),
+                finished_at: format_time(r.try_get("finishedat")?),
+                subscriptions_added: r.try_get("subscriptionsadded")?,
+                subscriptions_removed: r.try_get("subscriptionsremoved")?,
+                subscriptions_pushed: r.try_get("subscriptionspushed")?,
+                actions_received: r.try_get("actionsreceived")?,
+                actions_applied: r.try_get("actionsapplied")?,
+                actions_stale: r.try_get("actionsstale")?,
+                actions_uploaded: r.try_get("actionsuploaded")?,
+                conflicts: r.try_get("conflicts")?,
+                error: r.try_get("error")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT SyncTarget, Protocol, CAST(FullSync AS SIGNED) AS FullSync, Status, StartedAt, FinishedAt,
+                    SubscriptionsAdded, SubscriptionsRemoved, SubscriptionsPushed, ActionsReceived, ActionsApplied,
+                    ActionsStale, ActionsUploaded, Conflicts, Error
+             FROM GpodderSyncLog WHERE UserID = ? ORDER BY SyncLogID DESC LIMIT ?",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<SyncRun> {
+            Ok(SyncRun {
+                target: r.try_get("SyncTarget")?,
+                protocol: r.try_get("Protocol")?,
+                full_sync: r.try_get::<i64, _>("FullSync")? != 0,
+                status: r.try_get("Status")?,
+                started_at: format_time(r.try_get("StartedAt")?),
+                finished_at: format_time(r.try_get("FinishedAt")?),
+                subscriptions_added: r.try_get("SubscriptionsAdded")?,
+                subscriptions_removed: r.try_get("SubscriptionsRemoved")?,
+                subscriptions_pushed: r.try_get("SubscriptionsPushed")?,
+                actions_received: r.try_get("ActionsReceived")?,
+                actions_applied: r.try_get("ActionsApplied")?,
+                actions_stale: r.try_get("ActionsStale")?,
+                actions_uploaded: r.try_get("ActionsUploaded")?,
+                conflicts: r.try_get("Conflicts")?,
+                error: r.try_get("Error")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(runs)
+}
+
+/// Per target and device: how many episodes the device has reported actions for, and when its
+/// newest action happened.
+pub async fn device_activity(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<DeviceActivity>> {
+    let activity = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT synctarget, device, COUNT(*) AS episodes, MAX(actionat) AS last_action_at
+               FROM "GpodderDeviceEpisodeActions" WHERE userid = $1
+               GROUP BY synctarget, device ORDER BY last_action_at DESC"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<DeviceActivity> {
+            Ok(DeviceActivity {
+                target: r.try_get("synctarget")?,
+                device: r.try_get("device")?,
+                episodes: r.try_get("episodes")?,
+                last_action_at: format_time(r.try_get("last_action_at")?),
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT SyncTarget, Device, COUNT(*) AS episodes, MAX(ActionAt) AS last_action_at
+             FROM GpodderDeviceEpisodeActions WHERE UserID = ?
+             GROUP BY SyncTarget, Device ORDER BY last_action_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<DeviceActivity> {
+            Ok(DeviceActivity {
+                target: r.try_get("SyncTarget")?,
+                device: r.try_get("Device")?,
+                episodes: r.try_get("episodes")?,
+                last_action_at: format_time(r.try_get("last_action_at")?),
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(activity)
+}
+
+#[async_trait::async_trait]
+impl SyncStore for DatabasePool {
+    async fn feeds(&self, user_id: i32) -> AppResult<Vec<String>> {
+        self.get_user_podcast_feeds(user_id).await
+    }
+
+    async fn snapshot(&self, user_id: i32, target: &str) -> AppResult<HashSet<String>> {
+        self.get_subscription_snapshot(user_id, target).await
+    }
+
+    async fn save_snapshot(&self, user_id: i32, target: &str, feeds: &[String]) -> AppResult<()> {
+        self.save_subscription_snapshot(user_id, target, feeds).await
+    }
+
+    async fn unpushed_tombstones(&self, user_id: i32, target: &str) -> AppResult<Vec<String>> {
+        let feeds = match self {
+            DatabasePool::Postgres(pool) => sqlx::query_scalar(
+                r#"SELECT feedurl FROM "GpodderSubscriptionTombstones"
+                   WHERE userid = $1 AND synctarget = $2 AND origin = $3 AND pushed = FALSE"#,
+            )
+            .bind(user_id)
+            .bind(target)
+            .bind(ORIGIN_LOCAL)
+            .fetch_all(pool)
+            .await?,
+            DatabasePool::MySQL(pool) => sqlx::query_scalar(
+                "SELECT FeedURL FROM GpodderSubscriptionTombstones
+                 WHERE UserID = ? AND SyncTarget = ? AND Origin = ? AND Pushed = 0",
+            )
+            .bind(user_id)
+            .bind(target)
+            .bind(ORIGIN_LOCAL)
+            .fetch_all(pool)
+            .await?,
+        };
+        Ok(feeds)
+    }
+
+    async fn record_tombstones(&self, user_id: i32, target: &str, feeds: &[String], origin: &str, pushed: bool) -> AppResult<()> {
+        if feeds.is_empty() {
+            return Ok(());
+        }
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                for feed in feeds {
+                    sqlx::query(
+                        r#"INSERT INTO "GpodderSubscriptionTombstones" (userid, synctarget, feedurl, origin, pushed)
+                           VALUES ($1, $2, $3, $4, $5)
+                           ON CONFLICT (userid, synctarget, feedurl) DO UPDATE SET origin = EXCLUDED.origin, pushed = EXCLUDED.pushed"#,
+                    )
+                    .bind(user_id)
+                    .bind(target)
+                    .bind(feed)
+                    .bind(origin)
+                    .bind(pushed)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                let mut tx = pool.begin().await?;
+                for feed in feeds {
+                    sqlx::query(
+                        "INSERT INTO GpodderSubscriptionTombstones (UserID, SyncTarget, FeedURL, Origin, Pushed)
+                         VALUES (?, ?, ?, ?, ?)
+                         ON DUPLICATE KEY UPDATE Origin = VALUES(Origin), Pushed = VALUES(Pushed)",
+                    )
+                    .bind(user_id)
+                    .bind(target)
+                    .bind(feed)
+                    .bind(origin)
+                    .bind(pushed)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn clear_tombstones(&self, user_id: i32, target: &str, feeds: &[String]) -> AppResult<()> {
+        if feeds.is_empty() {
+            return Ok(());
+        }
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"DELETE FROM "GpodderSubscriptionTombstones" WHERE userid = $1 AND synctarget = $2 AND feedurl = ANY($3)"#)
+                    .bind(user_id)
+                    .bind(target)
+                    .bind(feeds)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                let mut tx = pool.begin().await?;
+                for feed in feeds {
+                    sqlx::query("DELETE FROM GpodderSubscriptionTombstones WHERE UserID = ? AND SyncTarget = ? AND FeedURL = ?")
+                        .bind(user_id)
+                        .bind(target)
+                        .bind(feed)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&self, user_id: i32, feed_url: &str) -> AppResult<()> {
+        self.add_podcast_from_url(user_id, feed_url, None).await
+    }
+
+    async fn unsubscribe(&self, user_id: i32, feed_url: &str) -> AppResult<()> {
+        self.remove_podcast_by_url(user_id, feed_url).await
+    }
+
+    async fn record_device_actions(&self, user_id: i32, target: &str, actions: &[EpisodeAction]) -> AppResult<()> {
+        // Only the newest action per device and episode is stored, so collapse the batch first.
+        let mut newest: HashMap<(&str, &str), &EpisodeAction> = HashMap::new();
+        for action in actions {
+            let key = (action.device.as_str(), action.episode.as_str());
+            if newest.get(&key).is_none_or(|current| action.timestamp >= current.timestamp) {
+                newest.insert(key, action);
+            }
+        }
+        if newest.is_empty() {
+            return Ok(());
+        }
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                for action in newest.values() {
+                    sqlx::query(
+                        r#"INSERT INTO "GpodderDeviceEpisodeActions" (userid, synctarget, device, episodeurl, action, position, actionat)
+                           VALUES ($1, $2, $3, $4, $5, $6, $7)
+                           ON CONFLICT (userid, synctarget, device, episodeurl) DO UPDATE
+                           SET action = EXCLUDED.action, position = EXCLUDED.position, actionat = EXCLUDED.actionat
+                           WHERE "GpodderDeviceEpisodeActions".actionat < EXCLUDED.actionat"#,
+                    )
+                    .bind(user_id)
+                    .bind(target)
+                    .bind(&action.device)
+                    .bind(&action.episode)
+                    .bind(&action.action)
+                    .bind(action.position)
+                    .bind(action.timestamp.naive_utc())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                let mut tx = pool.begin().await?;
+                for action in newest.values() {
+                    // ActionAt is assigned last so the comparisons above it see the stored value.
+                    sqlx::query(
+                        "INSERT INTO GpodderDeviceEpisodeActions (UserID, SyncTarget, Device, EpisodeURL, Action, Position, ActionAt)
+                         VALUES (?, ?, ?, ?, ?, ?, ?)
+                         ON DUPLICATE KEY UPDATE
+                             Action = IF(ActionAt < VALUES(ActionAt), VALUES(Action), Action),
+                             Position = IF(ActionAt < VALUES(ActionAt), VALUES(Position), Position),
+                             ActionAt = GREATEST(ActionAt, VALUES(ActionAt))",
+                    )
+                    .bind(user_id)
+                    .bind(target)
+                    .bind(&action.device)
+                    .bind(&action.episode)
+                    .bind(&action.action)
+                    .bind(action.position)
+                    .bind(action.timestamp.naive_utc())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn local_episode(&self, user_id: i32, episode_url: &str) -> AppResult<Option<LocalEpisode>> {
+        let episode = match self {
+            DatabasePool::Postgres(pool) => sqlx::query(
+                r#"SELECT e.episodeid, e.episodeduration, h.listendate
+                   FROM "Episodes" e
+                   JOIN "Podcasts" p ON e.podcastid = p.podcastid
+                   LEFT JOIN "UserEpisodeHistory" h ON h.episodeid = e.episodeid AND h.userid = $2
+                   WHERE e.episodeurl = $1 AND p.userid = $2
+                   LIMIT 1"#,
+            )
+            .bind(episode_url)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| -> AppResult<LocalEpisode> {
+                Ok(LocalEpisode {
+                    episode_id: r.try_get("episodeid")?,
+                    duration: r.try_get::<Option<i32>, _>("episodeduration")?.unwrap_or(0),
+                    listened_at: r.try_get::<Option<NaiveDateTime>, _>("listendate")?.map(|t| t.and_utc()),
+                })
+            })
+            .transpose()?,
+            DatabasePool::MySQL(pool) => sqlx::query(
+                "SELECT e.EpisodeID, e.EpisodeDuration, h.ListenDate
+                 FROM Episodes e
+                 JOIN Podcasts p ON e.PodcastID = p.PodcastID
+                 LEFT JOIN UserEpisodeHistory h ON h.EpisodeID = e.EpisodeID AND h.UserID = ?
+                 WHERE e.EpisodeURL = ? AND p.UserID = ?
+                 LIMIT 1",
+            )
+            .bind(user_id)
+            .bind(episode_url)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| -> AppResult<LocalEpisode> {
+                Ok(LocalEpisode {
+                    episode_id: r.try_get("EpisodeID")?,
+                    duration: r.try_get::<Option<i32>, _>("EpisodeDuration")?.unwrap_or(0),
+                    listened_at: r.try_get::<Option<NaiveDateTime>, _>("ListenDate")?.map(|t| t.and_utc()),
+                })
+            })
+            .transpose()?,
+        };
+        Ok(episode)
+    }
+
+    async fn set_position(&self, user_id: i32, episode: &LocalEpisode, position: i32, at: DateTime<Utc>, completed: bool) -> AppResult<()> {
+        // Completing stamps the history with the current time, so the position and the action's
+        // own time are written after it.
+        if completed {
+            self.mark_episode_completed(episode.episode_id, user_id, false).await?;
+        }
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"INSERT INTO "UserEpisodeHistory" (userid, episodeid, listenduration, listendate)
+                       VALUES ($1, $2, $3, $4)
+                       ON CONFLICT (userid, episodeid) DO UPDATE SET listenduration = $3, listendate = $4"#,
+                )
+                .bind(user_id)
+                .bind(episode.episode_id)
+                .bind(position)
+                .bind(at.naive_utc())
+                .execute(pool)
+                .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query(
+                    "INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDuration, ListenDate)
+                     VALUES (?, ?, ?, ?)
+                     ON DUPLICATE KEY UPDATE ListenDuration = VALUES(ListenDuration), ListenDate = VALUES(ListenDate)",
+                )
+                .bind(user_id)
+                .bind(episode.episode_id)
+                .bind(position)
+                .bind(at.naive_utc())
+                .execute(pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn local_actions_since(&self, user_id: i32, since: Option<DateTime<Utc>>) -> AppResult<Vec<serde_json::Value>> {
+        let since = since.unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap());
+        self.get_user_episode_actions_since(user_id, since).await
+    }
+
+    async fn cursor(&self, user_id: i32, target: &str) -> AppResult<Cursor> {
+        let logged = match self {
+            DatabasePool::Postgres(pool) => sqlx::query(
+                r#"SELECT subscriptioncursor, actioncursor, localcursor FROM "GpodderSyncLog"
+                   WHERE userid = $1 AND synctarget = $2 AND status <> $3
+                   ORDER BY synclogid DESC LIMIT 1"#,
+            )
+            .bind(user_id)
+            .bind(target)
+            .bind(STATUS_FAILED)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| -> AppResult<(Option<i64>, Option<i64>, Option<NaiveDateTime>)> {
+                Ok((r.try_get("subscriptioncursor")?, r.try_get("actioncursor")?, r.try_get("localcursor")?))
+            })
+            .transpose()?,
+            DatabasePool::MySQL(pool) => sqlx::query(
+                "SELECT SubscriptionCursor, ActionCursor, LocalCursor FROM GpodderSyncLog
+                 WHERE UserID = ? AND SyncTarget = ? AND Status <> ?
+                 ORDER BY SyncLogID DESC LIMIT 1",
+            )
+            .bind(user_id)
+            .bind(target)
+            .bind(STATUS_FAILED)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| -> AppResult<(Option<i64>, Option<i64>, Option<NaiveDateTime>)> {
+                Ok((r.try_get("SubscriptionCursor")?, r.try_get("ActionCursor")?, r.try_get("LocalCursor")?))
+            })
+            .transpose()?,
+        };
+        if let Some((subscriptions, actions, local)) = logged {
+            return Ok(Cursor {
+                subscriptions: subscriptions.unwrap_or(0),
+                actions: actions.unwrap_or(0),
+                local: local.map(|t| t.and_utc()),
+            });
+        }
+        // Targets synced before runs were logged continue from the user's last sync time.
+        let last_sync = self.get_last_sync_timestamp(user_id).await?;
+        Ok(Cursor {
+            subscriptions: last_sync.map_or(0, |t| t.timestamp()),
+            actions: last_sync.map_or(0, |t| t.timestamp()),
+            local: last_sync,
+        })
+    }
+
+    async fn finish_run(&self, user_id: i32, target: &str, report: &SyncReport) -> AppResult<()> {
+        let tombstone_cutoff = (Utc::now() - chrono::Duration::days(TOMBSTONE_DAYS)).naive_utc();
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"INSERT INTO "GpodderSyncLog" (userid, synctarget, protocol, fullsync, status, startedat, finishedat,
+                           subscriptionsadded, subscriptionsremoved, subscriptionspushed, actionsreceived, actionsapplied,
+                           actionsstale, actionsuploaded, conflicts, subscriptioncursor, actioncursor, localcursor, error)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)"#,
+                )
+                .bind(user_id)
+                .bind(target)
+                .bind(report.protocol.as_str())
+                .bind(report.full)
+                .bind(report.status)
+                .bind(report.started_at.naive_utc())
+                .bind(report.finished_at.naive_utc())
+                .bind(report.subscriptions_added)
+                .bind(report.subscriptions_removed)
+                .bind(report.subscriptions_pushed)
+                .bind(report.actions_received)
+                .bind(report.actions_applied)
+                .bind(report.actions_stale)
+                .bind(report.actions_uploaded)
+                .bind(report.conflicts)
+                .bind(report.cursor.subscriptions)
+                .bind(report.cursor.actions)
+                .bind(report.cursor.local.map(|t| t.naive_utc()))
+                .bind(&report.error)
+                .execute(pool)
+                .await?;
+                sqlx::query(
+                    r#"DELETE FROM "GpodderSyncLog" WHERE userid = $1 AND synctarget = $2 AND synclogid < (
+                           SELECT MIN(synclogid) FROM (
+                               SELECT synclogid FROM "GpodderSyncLog" WHERE userid = $1 AND synctarget = $2
+                               ORDER BY synclogid DESC LIMIT $3
+                           ) kept
+                       )"#,
+                )
+                .bind(user_id)
+                .bind(target)
+                .bind(LOG_ROWS_KEPT)
+                .execute(pool)
+                .await?;
+                sqlx::query(r#"DELETE FROM "GpodderSubscriptionTombstones" WHERE userid = $1 AND pushed = TRUE AND removedat < $2"#)
+                    .bind(user_id)
+                    .bind(tombstone_cutoff)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query(
+                    "INSERT INTO GpodderSyncLog (UserID, SyncTarget, Protocol, FullSync, Status, StartedAt, FinishedAt,
+                         SubscriptionsAdded, SubscriptionsRemoved, SubscriptionsPushed, ActionsReceived, ActionsApplied,
+                         ActionsStale, ActionsUploaded, Conflicts, SubscriptionCursor, ActionCursor, LocalCursor, Error)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(user_id)
+                .bind(target)
+                .bind(report.protocol.as_str())
+                .bind(report.full)
+                .bind(report.status)
+                .bind(report.started_at.naive_utc())
+                .bind(report.finished_at.naive_utc())
+                .bind(report.subscriptions_added)
+                .bind(report.subscriptions_removed)
+                .bind(report.subscriptions_pushed)
+                .bind(report.actions_received)
+                .bind(report.actions_applied)
+                .bind(report.actions_stale)
+                .bind(report.actions_uploaded)
+                .bind(report.conflicts)
+                .bind(report.cursor.subscriptions)
+                .bind(report.cursor.actions)
+                .bind(report.cursor.local.map(|t| t.naive_utc()))
+                .bind(&report.error)
+                .execute(pool)
+                .await?;
+                sqlx::query(
+                    "DELETE FROM GpodderSyncLog WHERE UserID = ? AND SyncTarget = ? AND SyncLogID < (
+                         SELECT MIN(SyncLogID) FROM (
+                             SELECT SyncLogID FROM GpodderSyncLog WHERE UserID = ? AND SyncTarget = ?
+                             ORDER BY SyncLogID DESC LIMIT ?
+                         ) kept
+                     )",
+                )
+                .bind(user_id)
+                .bind(target)
+                .bind(user_id)
+                .bind(target)
+                .bind(LOG_ROWS_KEPT)
+                .execute(pool)
+                .await?;
+                sqlx::query("DELETE FROM GpodderSubscriptionTombstones WHERE UserID = ? AND Pushed = 1 AND RemovedAt < ?")
+                    .bind(user_id)
+                    .bind(tombstone_cutoff)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        if report.status != STATUS_FAILED {
+            self.update_last_sync_timestamp(user_id).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_server;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, Method, StatusCode, Uri},
+        response::{IntoResponse, Response},
+        Json, Router,
+    };
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// A stand-in gpodder / Nextcloud gPodder Sync server. It serves canned subscription changes
+    /// (per device; Nextcloud's are under "") and episode actions, and records every upload, query
+    /// string and credential it sees.
+    #[derive(Default)]
+    struct Server {
+        subscriptions: HashMap<String, SubscriptionChanges>,
+        actions: Vec<serde_json::Value>,
+        timestamp: i64,
+        reject_uploads: bool,
+        uploaded_subscriptions: Vec<serde_json::Value>,
+        uploaded_actions: Vec<serde_json::Value>,
+        queries: Vec<(String, String)>,
+        credentials: Vec<String>,
+    }
+
+    type Shared = Arc<Mutex<Server>>;
+
+    async fn handle(State(server): State<Shared>, method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> Response {
+        let mut s = server.lock().unwrap();
+        let path = uri.path().to_string();
+        if let Some(auth) = headers.get("x-gpodder-token").or(headers.get("authorization")) {
+            s.credentials.push(auth.to_str().unwrap().to_string());
+        }
+        if let Some(query) = uri.query() {
+            s.queries.push((path.clone(), query.to_string()));
+        }
+        let changes = |c: Option<&SubscriptionChanges>, ts: i64| {
+            let c = c.cloned().unwrap_or_default();
+            Json(json!({"add": c.add, "remove": c.remove, "timestamp": ts})).into_response()
+        };
+        if method == Method::POST {
+            if path.ends_with("/login.json") {
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+            if s.reject_uploads {
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            if path.contains("/episode") {
+                s.uploaded_actions.extend(body.as_array().cloned().unwrap_or_default());
+            } else {
+                s.uploaded_subscriptions.push(body);
+            }
+            return Json(json!({"timestamp": s.timestamp, "update_urls": []})).into_response();
+        }
+        if path.starts_with("/api/2/devices/") {
+            let devices: Vec<_> = s.subscriptions.keys().map(|id| json!({"id": id, "caption": id, "type": "mobile"})).collect();
+            return Json(json!(devices)).into_response();
+        }
+        if let Some(rest) = path.strip_prefix("/api/2/subscriptions/") {
+            let device = rest.split('/').nth(1).unwrap_or_default().trim_end_matches(".json");
+            return changes(s.subscriptions.get(device), s.timestamp);
+        }
+        if path == "/index.php/apps/gpoddersync/subscriptions" {
+            return changes(s.subscriptions.get(""), s.timestamp);
+        }
+        if path.starts_with("/api/2/episodes/") || path == "/index.php/apps/gpoddersync/episode_action" {
+            return Json(json!({"actions": s.actions, "timestamp": s.timestamp})).into_response();
+        }
+        StatusCode::NOT_FOUND.into_response()
+    }
+
+    async fn fake_server(server: Server) -> (String, Shared) {
+        let shared: Shared = Arc::new(Mutex::new(server));
+        let app = Router::new().fallback(handle).with_state(shared.clone());
+        (test_server::serve(app).await, shared)
+    }
+
+    /// The local side in memory.
+    #[derive(Default)]
+    struct Local {
+        feeds: Vec<String>,
+        snapshot: HashSet<String>,
+        /// feed -> (origin, pushed)
+        tombstones: HashMap<String, (String, bool)>,
+        episodes: HashMap<String, LocalEpisode>,
+        /// episode id -> (position, completed)
+        positions: HashMap<i32, (i32, bool)>,
+        device_actions: HashMap<(String, String), DateTime<Utc>>,
+        local_actions: Vec<serde_json::Value>,
+        cursor: Cursor,
+        runs: Vec<SyncReport>,
+    }
+
+    struct MemoryStore(Mutex<Local>);
+
+    #[async_trait::async_trait]
+    impl SyncStore for MemoryStore {
+        async fn feeds(&self, _: i32) -> AppResult<Vec<String>> {
+            Ok(self.0.lock().unwrap().feeds.clone())
+        }
+        async fn snapshot(&self, _: i32, _: &str) -> AppResult<HashSet<String>> {
+            Ok(self.0.lock().unwrap().snapshot.clone())
+        }
+        async fn save_snapshot(&self, _: i32, _: &str, feeds: &[String]) -> AppResult<()> {
+            self.0.lock().unwrap().snapshot = feeds.iter().cloned().collect();
+            Ok(())
+        }
+        async fn unpushed_tombstones(&self, _: i32, _: &str) -> AppResult<Vec<String>> {
+            let local = self.0.lock().unwrap();
+            Ok(local.tombstones.iter().filter(|(_, (o, p))| o == ORIGIN_LOCAL && !p).map(|(f, _)| f.clone()).collect())
+        }
+        async fn record_tombstones(&self, _: i32, _: &str, feeds: &[String], origin: &str, pushed: bool) -> AppResult<()> {
+            let mut local = self.0.lock().unwrap();
+            for feed in feeds {
+                local.tombstones.insert(feed.clone(), (origin.to_string(), pushed));
+            }
+            Ok(())
+        }
+        async fn clear_tombstones(&self, _: i32, _: &str, feeds: &[String]) -> AppResult<()> {
+            let mut local = self.0.lock().unwrap();
+            for feed in feeds {
+                local.tombstones.remove(feed);
+            }
+            Ok(())
+        }
+        async fn subscribe(&self, _: i32, feed_url: &str) -> AppResult<()> {
+            self.0.lock().unwrap().feeds.push(feed_url.to_string());
+            Ok(())
+        }
+        async fn unsubscribe(&self, _: i32, feed_url: &str) -> AppResult<()> {
+            self.0.lock().unwrap().feeds.retain(|f| f != feed_url);
+            Ok(())
+        }
+        async fn record_device_actions(&self, _: i32, _: &str, actions: &[EpisodeAction]) -> AppResult<()> {
+            let mut local = self.0.lock().unwrap();
+            for action in actions {
+                let at = local.device_actions.entry((action.device.clone(), action.episode.clone())).or_insert(action.timestamp);
+                *at = (*at).max(action.timestamp);
+            }
+            Ok(())
+        }
+        async fn local_episode(&self, _: i32, episode_url: &str) -> AppResult<Option<LocalEpisode>> {
+            Ok(self.0.lock().unwrap().episodes.get(episode_url).cloned())
+        }
+        async fn set_position(&self, _: i32, episode: &LocalEpisode, position: i32, at: DateTime<Utc>, completed: bool) -> AppResult<()> {
+            let mut local = self.0.lock().unwrap();
+            local.positions.insert(episode.episode_id, (position, completed));
+            for e in local.episodes.values_mut().filter(|e| e.episode_id == episode.episode_id) {
+                e.listened_at = Some(at);
+            }
+            Ok(())
+        }
+        async fn local_actions_since(&self, _: i32, _: Option<DateTime<Utc>>) -> AppResult<Vec<serde_json::Value>> {
+            Ok(self.0.lock().unwrap().local_actions.clone())
+        }
+        async fn cursor(&self, _: i32, _: &str) -> AppResult<Cursor> {
+            Ok(self.0.lock().unwrap().cursor.clone())
+        }
+        async fn finish_run(&self, _: i32, _: &str, report: &SyncReport) -> AppResult<()> {
+            let mut local = self.0.lock().unwrap();
+            if report.status != STATUS_FAILED {
+                local.cursor = report.cursor.clone();
+            }
+            local.runs.push(report.clone());
+            Ok(())
+        }
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn episode(id: i32, duration: i32, listened_at: Option<i64>) -> LocalEpisode {
+        LocalEpisode { episode_id: id, duration, listened_at: listened_at.map(at) }
+    }
+
+    fn play(episode: &str, device: &str, position: i32, timestamp: i64) -> serde_json::Value {
+        json!({"podcast": "https://example.com/feed.xml", "episode": episode, "device": device,
+               "action": "play", "position": position, "timestamp": timestamp})
+    }
+
+    fn feeds(list: &[&str]) -> Vec<String> {
+        list.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_gpodder_timestamps_and_actions() {
+        assert_eq!(parse_timestamp(&json!(1_700_000_000)), Some(at(1_700_000_000)));
+        assert_eq!(parse_timestamp(&json!("2023-11-14T22:13:20")), Some(at(1_700_000_000)));
+        assert_eq!(parse_timestamp(&json!("2023-11-14T22:13:20.250")).map(|t| t.timestamp()), Some(1_700_000_000));
+        assert_eq!(parse_timestamp(&json!("2023-11-15T00:13:20+02:00")), Some(at(1_700_000_000)));
+        assert_eq!(parse_timestamp(&json!("yesterday")), None);
+
+        let action = parse_action(&json!({"episode": "https://e/1.mp3", "action": "PLAY", "timestamp": 5, "position": 42})).unwrap();
+        assert_eq!((action.action.as_str(), action.device.as_str(), action.position), ("play", UNKNOWN_DEVICE, Some(42)));
+        assert!(parse_action(&json!({"episode": "https://e/1.mp3", "action": "play"})).is_none());
+        assert!(completes(3550, 3600) && !completes(3500, 3600) && !completes(10, 0));
+    }
+
+    #[tokio::test]
+    async fn newest_action_wins_and_stale_devices_cannot_rewind() {
+        let mut server = Server { timestamp: 2000, ..Default::default() };
+        server.subscriptions.insert("phone".into(), SubscriptionChanges::default());
+        server.actions = vec![
+            // Older than the local listen: a phone that was offline for a while.
+            play("https://e/a.mp3", "phone", 1200, 900),
+            // A deliberate rewind on the tablet, newer than both the phone and the local listen.
+            play("https://e/b.mp3", "phone", 2000, 1050),
+            play("https://e/b.mp3", "tablet", 300, 1100),
+            play("https://e/c.mp3", "tablet", 580, 1200),
+            play("https://e/unknown.mp3", "tablet", 10, 1200),
+            // Our own earlier upload, echoed back.
+            play("https://e/a.mp3", "pinepods", 5, 1900),
+        ];
+        let (url, server) = fake_server(server).await;
+
+        let mut local = Local::default();
+        local.episodes.insert("https://e/a.mp3".into(), episode(1, 3600, Some(1000)));
+        local.episodes.insert("https://e/b.mp3".into(), episode(2, 3600, Some(1000)));
+        local.episodes.insert("https://e/c.mp3".into(), episode(3, 600, None));
+        local.local_actions = vec![play("https://e/b.mp3", "", 900, 1000), play("https://e/e.mp3", "", 60, 1500)];
+        let store = MemoryStore(Mutex::new(local));
+
+        let remote = Remote::gpodder(&format!("{}/", url), "alice", "secret", "pinepods");
+        let report = run(&store, 1, &remote, false).await.unwrap();
+        assert_eq!(report.status, STATUS_SUCCESS);
+        assert_eq!((report.actions_received, report.actions_applied, report.actions_stale), (5, 2, 2));
+
+        let local = store.0.lock().unwrap();
+        assert!(!local.positions.contains_key(&1));
+        assert_eq!(local.positions[&2], (300, false));
+        assert_eq!(local.positions[&3], (580, true));
+        assert_eq!(local.device_actions[&("phone".to_string(), "https://e/b.mp3".to_string())], at(1050));
+        assert_eq!(local.device_actions[&("tablet".to_string(), "https://e/b.mp3".to_string())], at(1100));
+        assert!(!local.device_actions.contains_key(&("pinepods".to_string(), "https://e/a.mp3".to_string())));
+        assert_eq!(local.cursor.actions, 2000);
+
+        // Only the local action the server didn't just overrule goes up, tagged with our device.
+        let server = server.lock().unwrap();
+        assert_eq!(server.uploaded_actions.len(), 1);
+        assert_eq!(server.uploaded_actions[0]["episode"], "https://e/e.mp3");
+        assert_eq!(server.uploaded_actions[0]["device"], "pinepods");
+        // The login is refused, so every request falls back to basic auth.
+        assert!(server.credentials.iter().all(|c| c.starts_with("Basic ")));
+    }
+
+    #[tokio::test]
+    async fn conflicting_subscription_changes_keep_the_local_side() {
+        let mut server = Server { timestamp: 800, ..Default::default() };
+        server.subscriptions.insert(
+            String::new(),
+            SubscriptionChanges { add: feeds(&["https://b", "https://e"]), remove: feeds(&["https://c", "https://d"]) },
+        );
+        let (url, server) = fake_server(server).await;
+
+        let local = Local {
+            feeds: feeds(&["https://a", "https://c", "https://d"]),
+            snapshot: feeds(&["https://a", "https://b", "https://c"]).into_iter().collect(),
+            cursor: Cursor { subscriptions: 500, actions: 500, local: None },
+            ..Default::default()
+        };
+        let store = MemoryStore(Mutex::new(local));
+
+        let report = run(&store, 1, &Remote::nextcloud(&url, "alice", "app-password"), false).await.unwrap();
+        assert_eq!((report.subscriptions_added, report.subscriptions_removed, report.conflicts), (1, 1, 2));
+
+        let server = server.lock().unwrap();
+        // b was unsubscribed here and d subscribed here since the last run: both are re-asserted.
+        assert_eq!(server.uploaded_subscriptions, vec![json!({"add": ["https://d"], "remove": ["https://b"]})]);
+        assert!(server.queries.contains(&("/index.php/apps/gpoddersync/subscriptions".into(), "since=500".into())));
+
+        let local = store.0.lock().unwrap();
+        let mut now: Vec<_> = local.feeds.clone();
+        now.sort();
+        assert_eq!(now, feeds(&["https://a", "https://d", "https://e"]));
+        assert_eq!(local.snapshot, now.into_iter().collect());
+        assert_eq!(local.tombstones["https://b"], (ORIGIN_LOCAL.to_string(), true));
+        assert_eq!(local.tombstones["https://c"], (ORIGIN_REMOTE.to_string(), true));
+        assert_eq!(local.cursor.subscriptions, 800);
+    }
+
+    #[tokio::test]
+    async fn failed_uploads_are_retried_on_the_next_run() {
+        let mut server = Server { timestamp: 100, reject_uploads: true, ..Default::default() };
+        server.subscriptions.insert("pinepods".into(), SubscriptionChanges::default());
+        let (url, server) = fake_server(server).await;
+
+        let local = Local {
+            feeds: feeds(&["https://a", "https://new"]),
+            snapshot: feeds(&["https://a", "https://gone"]).into_iter().collect(),
+            local_actions: vec![play("https://e/a.mp3", "", 60, 50)],
+            ..Default::default()
+        };
+        let store = MemoryStore(Mutex::new(local));
+        let remote = Remote::gpodder(&url, "alice", "secret", "pinepods");
+
+        let report = run(&store, 1, &remote, false).await.unwrap();
+        assert_eq!(report.status, STATUS_PARTIAL);
+        {
+            let local = store.0.lock().unwrap();
+            assert_eq!(local.tombstones["https://gone"], (ORIGIN_LOCAL.to_string(), false));
+            assert_eq!(local.snapshot, feeds(&["https://a"]).into_iter().collect());
+            assert_eq!(local.cursor.local, None);
+        }
+
+        server.lock().unwrap().reject_uploads = false;
+        let report = run(&store, 1, &remote, false).await.unwrap();
+        assert_eq!((report.status, report.subscriptions_pushed, report.actions_uploaded), (STATUS_SUCCESS, 2, 1));
+        assert_eq!(
+            server.lock().unwrap().uploaded_subscriptions,
+            vec![json!({"add": ["https://new"], "remove": ["https://gone"]})]
+        );
+        let local = store.0.lock().unwrap();
+        assert_eq!(local.tombstones["https://gone"], (ORIGIN_LOCAL.to_string(), true));
+        assert!(local.cursor.local.is_some());
+        assert_eq!(local.runs.len(), 2);
+    }
+}
@@ -0,0 +1,862 @@
+//! Durable background job queue for downloads, transcription, ad detection, feed refreshes and
+//! audio renders (transcodes and joined audiobooks).
+//!
+//! Each job is a row in `BackgroundJobs` keyed by the task ID it reports progress under, so the
+//! task list and websocket show queued jobs like any other task. Jobs run on per-kind
+//! [`WorkerPool`]s, each limited to its configured number of concurrent jobs. A failed attempt
+//! goes back to the queue with exponential backoff until the job's attempt limit or a permanent
+//! error (missing episode, full quota); a queued or running job can be cancelled.
+//!
+//! Several replicas can share the queue. A claim records the claiming instance, which keeps the
+//! job's heartbeat fresh while it runs; a running job whose heartbeat goes stale (its replica died
+//! or restarted) is queued again by whichever replica notices. Wake-ups and cancellations are
+//! published through Redis so they reach the replica concerned.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::cluster;
+use crate::services::task_manager::TaskManager;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::AbortHandle;
+use tracing::{error, info, warn};
+
+const STATUS_QUEUED: &str = "queued";
+const STATUS_RUNNING: &str = "running";
+const STATUS_COMPLETED: &str = "completed";
+const STATUS_FAILED: &str = "failed";
+const STATUS_CANCELLED: &str = "cancelled";
+
+const FIRST_RETRY_SECONDS: i64 = 30;
+const MAX_RETRY_SECONDS: i64 = 60 * 60;
+/// How often an idle pool looks for jobs whose retry delay has passed.
+const POLL_SECONDS: u64 = 15;
+/// How often a replica refreshes the heartbeat of the jobs it runs and looks for stale ones.
+const HEARTBEAT_SECONDS: u64 = 15;
+/// A running job whose heartbeat is older than this is presumed orphaned.
+const STALE_AFTER_SECONDS: i64 = 90;
+/// Finished jobs are kept as long as their task entries.
+const KEEP_FINISHED_DAYS: i64 = 7;
+
+/// A set of workers sharing one concurrency limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkerPool {
+    Downloads,
+    Transcription,
+    AdDetection,
+    FeedRefresh,
+    Renders,
+}
+
+impl WorkerPool {
+    pub const ALL: [WorkerPool; 5] = [
+        WorkerPool::Downloads,
+        WorkerPool::Transcription,
+        WorkerPool::AdDetection,
+        WorkerPool::FeedRefresh,
+        WorkerPool::Renders,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WorkerPool::Downloads => "downloads",
+            WorkerPool::Transcription => "transcription",
+            WorkerPool::AdDetection => "ad_detection",
+            WorkerPool::FeedRefresh => "feed_refresh",
+            WorkerPool::Renders => "renders",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<WorkerPool> {
+        WorkerPool::ALL.into_iter().find(|pool| pool.name() == name)
+    }
+
+    fn env_var(self) -> &'static str {
+        match self {
+            WorkerPool::Downloads => "PINEPODS_DOWNLOAD_WORKERS",
+            WorkerPool::Transcription => "PINEPODS_TRANSCRIBE_WORKERS",
+            WorkerPool::AdDetection => "PINEPODS_AD_DETECT_WORKERS",
+            WorkerPool::FeedRefresh => "PINEPODS_REFRESH_WORKERS",
+            WorkerPool::Renders => "PINEPODS_RENDER_WORKERS",
+        }
+    }
+
+    /// Jobs the pool runs at once: its `PINEPODS_*_WORKERS` variable, or three parallel
+    /// downloads and one of everything else. The AI sidecar handles one request at a time well.
+    pub fn workers(self) -> usize {
+        let default = match self {
+            WorkerPool::Downloads => 3,
+            _ => 1,
+        };
+        std::env::var(self.env_var())
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(default)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PERMITS: HashMap<WorkerPool, Arc<Semaphore>> = WorkerPool::ALL
+        .iter()
+        .map(|pool| (*pool, Arc::new(Semaphore::new(pool.workers()))))
+        .collect();
+}
+
+/// Wait for a free worker in `pool`. Work started outside the queue, such as auto-transcription
+/// after a download, takes a permit too so it counts against the same limit.
+pub async fn acquire(pool: WorkerPool) -> OwnedSemaphorePermit {
+    PERMITS[&pool]
+        .clone()
+        .acquire_owned()
+        .await
+        .expect("worker pool semaphores are never closed")
+}
+
+/// A unit of background work, stored as the job's JSON payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Job {
+    DownloadEpisode { episode_id: i32 },
+    DownloadVideo { video_id: i32, with_video: bool },
+    TranscribeEpisode { episode_id: i32, force: bool },
+    DetectAds { episode_id: i32, force: bool },
+    RefreshFeeds,
+    /// Render the cached `profile` rendition of a server file (see `transcode`).
+    Transcode { source: String, profile: String },
+    /// Join an audiobook episode's part files into its cached single file (see `local_books`).
+    JoinBook { episode_id: i32 },
+    /// Render a user's cut copy of a server download (see `audio_cut`). The user is part of the
+    /// payload because the ranges cut are theirs.
+    CutAudio { user_id: i32, episode_id: i32, source: String },
+}
+
+impl Job {
+    pub fn pool(&self) -> WorkerPool {
+        match self {
+            Job::DownloadEpisode { .. } | Job::DownloadVideo { .. } => WorkerPool::Downloads,
+            Job::TranscribeEpisode { .. } => WorkerPool::Transcription,
+            Job::DetectAds { .. } => WorkerPool::AdDetection,
+            Job::RefreshFeeds => WorkerPool::FeedRefresh,
+            Job::Transcode { .. } | Job::JoinBook { .. } | Job::CutAudio { .. } => WorkerPool::Renders,
+        }
+    }
+
+    /// The task type the job is listed under; the web client keys its icons and labels on it.
+    pub fn task_type(&self) -> &'static str {
+        match self {
+            Job::DownloadEpisode { .. } => "download_episode",
+            Job::DownloadVideo { .. } => "download_video",
+            Job::TranscribeEpisode { .. } => "transcribe_episode",
+            Job::DetectAds { .. } => "detect_ads",
+            Job::RefreshFeeds => "refresh_feeds",
+            Job::Transcode { .. } => "transcode_audio",
+            Job::JoinBook { .. } => "join_audiobook",
+            Job::CutAudio { .. } => "cut_audio",
+        }
+    }
+
+    /// Jobs whose result is shared by everyone asking for it. Queueing one while an identical
+    /// job is queued or running returns that job's task ID instead of a second copy.
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Job::RefreshFeeds | Job::Transcode { .. } | Job::JoinBook { .. } | Job::CutAudio { .. })
+    }
+
+    pub fn item_id(&self) -> Option<i32> {
+        match self {
+            Job::DownloadEpisode { episode_id }
+            | Job::TranscribeEpisode { episode_id, .. }
+            | Job::DetectAds { episode_id, .. }
+            | Job::JoinBook { episode_id }
+            | Job::CutAudio { episode_id, .. } => Some(*episode_id),
+            Job::DownloadVideo { video_id, .. } => Some(*video_id),
+            Job::RefreshFeeds | Job::Transcode { .. } => None,
+        }
+    }
+
+    /// Attempts before the job is marked failed. A refresh isn't retried: the next scheduled
+    /// run does the same work.
+    pub fn max_attempts(&self) -> i32 {
+        match self {
+            Job::DownloadEpisode { .. } | Job::DownloadVideo { .. } => 5,
+            Job::TranscribeEpisode { .. } | Job::DetectAds { .. } => 3,
+            Job::Transcode { .. } | Job::JoinBook { .. } | Job::CutAudio { .. } => 2,
+            Job::RefreshFeeds => 1,
+        }
+    }
+}
+
+/// Delay before retry number `attempts` (1-based): thirty seconds, doubling, capped at an hour.
+fn retry_delay_seconds(attempts: i32) -> i64 {
+    crate::services::outbox::backoff_seconds(FIRST_RETRY_SECONDS, MAX_RETRY_SECONDS, attempts)
+}
+
+/// Whether another attempt could succeed. Errors about the request itself (a deleted episode,
+/// a full quota) would fail the same way again.
+fn is_retryable(error: &AppError) -> bool {
+    !matches!(
+        error,
+        AppError::Database(sqlx::Error::RowNotFound)
+            | AppError::NotFound(_)
+            | AppError::BadRequest(_)
+            | AppError::Validation(_)
+            | AppError::Auth(_)
+            | AppError::Authorization(_)
+            | AppError::Conflict(_)
+            | AppError::FeedParsing(_)
+    )
+}
+
+/// A job claimed by a worker. `attempts` includes the one about to run.
+#[derive(Debug)]
+struct ClaimedJob {
+    task_id: String,
+    user_id: i32,
+    job: Job,
+    attempts: i32,
+}
+
+pub struct JobQueue {
+    db_pool: DatabasePool,
+    task_manager: Arc<TaskManager>,
+    wake: HashMap<WorkerPool, Arc<Notify>>,
+    running: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl JobQueue {
+    pub fn new(db_pool: DatabasePool, task_manager: Arc<TaskManager>) -> Self {
+        Self {
+            db_pool,
+            task_manager,
+            wake: WorkerPool::ALL.iter().map(|pool| (*pool, Arc::new(Notify::new()))).collect(),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Queue a job for `user_id` and return the ID of the task it reports under. A shared job
+    /// ([`Job::is_shared`]) that is already queued or running is not queued twice; its task ID is
+    /// returned instead.
+    pub async fn enqueue(&self, user_id: i32, job: Job) -> AppResult<String> {
+        let payload = serde_json::to_string(&job)?;
+        if job.is_shared() {
+            if let Some(task_id) = self.active_task(job.task_type(), &payload).await? {
+                return Ok(task_id);
+            }
+        }
+
+        let task_id = self
+            .task_manager
+            .create_task_with_item_id(job.task_type().to_string(), user_id, job.item_id())
+            .await?;
+
+        match &self.db_pool {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"INSERT INTO "BackgroundJobs" (taskid, userid, kind, pool, payload, itemid)
+                       VALUES ($1, $2, $3, $4, $5, $6)"#,
+                )
+                .bind(&task_id)
+                .bind(user_id)
+                .bind(job.task_type())
+                .bind(job.pool().name())
+                .bind(&payload)
+                .bind(job.item_id())
+                .execute(pool)
+                .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query(
+                    "INSERT INTO BackgroundJobs (TaskID, UserID, Kind, Pool, Payload, ItemID)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&task_id)
+                .bind(user_id)
+                .bind(job.task_type())
+                .bind(job.pool().name())
+                .bind(&payload)
+                .bind(job.item_id())
+                .execute(pool)
+                .await?;
+            }
+        }
+
+        self.wake[&job.pool()].notify_one();
+        // Let idle workers on other replicas pick it up without waiting for their next poll
+        if let Ok(redis) = cluster::redis() {
+            if let Err(e) = redis.publish(cluster::JOB_WAKE_CHANNEL, job.pool().name()).await {
+                warn!("Failed to announce queued job {}: {}", task_id, e);
+            }
+        }
+        Ok(task_id)
+    }
+
+    /// The user a queued job belongs to, or None if `task_id` isn't a job.
+    pub async fn owner(&self, task_id: &str) -> AppResult<Option<i32>> {
+        let owner = match &self.db_pool {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query_scalar(r#"SELECT userid FROM "BackgroundJobs" WHERE taskid = $1"#)
+                    .bind(task_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query_scalar("SELECT UserID FROM BackgroundJobs WHERE TaskID = ?")
+                    .bind(task_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+        };
+        Ok(owner)
+    }
+
+    /// Cancel a queued or running job. A running job is stopped where it is; a partly written
+    /// download is removed. Returns false if the job had already finished.
+    pub async fn cancel(&self, task_id: &str) -> AppResult<bool> {
+        let cancelled = match &self.db_pool {
+            DatabasePool::Postgres(pool) => sqlx::query(
+                r#"UPDATE "BackgroundJobs" SET status = $2, finishedat = NOW()
+                   WHERE taskid = $1 AND status IN ($3, $4)"#,
+            )
+            .bind(task_id)
+            .bind(STATUS_CANCELLED)
+            .bind(STATUS_QUEUED)
+            .bind(STATUS_RUNNING)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+            DatabasePool::MySQL(pool) => sqlx::query(
+                "UPDATE BackgroundJobs SET Status = ?, FinishedAt = NOW()
+                 WHERE TaskID = ? AND Status IN (?, ?)",
+            )
+            .bind(STATUS_CANCELLED)
+            .bind(task_id)
+            .bind(STATUS_QUEUED)
+            .bind(STATUS_RUNNING)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        };
+        if cancelled == 0 {
+            return Ok(false);
+        }
+
+        if !self.abort_local(task_id) {
+            // Running on another replica, or still queued
+            if let Ok(redis) = cluster::redis() {
+                if let Err(e) = redis.publish(cluster::JOB_CANCEL_CHANNEL, task_id).await {
+                    warn!("Failed to announce cancellation of job {}: {}", task_id, e);
+                }
+            }
+        }
+        if let Err(e) = self.task_manager.cancel_task(task_id).await {
+            warn!("Cancelled job {} but couldn't update its task: {}", task_id, e);
+        }
+        info!("Cancelled background job {}", task_id);
+        Ok(true)
+    }
+
+    /// Queue again the jobs a restart of this instance interrupted, then start one dispatcher per
+    /// worker pool plus the heartbeat that keeps this replica's claims alive.
+    pub async fn start(self: &Arc<Self>, state: Arc<AppState>) -> AppResult<()> {
+        let resumed = self.requeue_orphaned(true).await?;
+        if resumed > 0 {
+            info!("Resuming {} background jobs interrupted by a restart", resumed);
+        }
+
+        if let Ok(redis) = cluster::redis() {
+            let queue = self.clone();
+            cluster::spawn_subscriber(redis.clone(), cluster::JOB_WAKE_CHANNEL, move |name| {
+                if let Some(pool) = WorkerPool::from_name(&name) {
+                    queue.wake[&pool].notify_one();
+                }
+            });
+            let queue = self.clone();
+            cluster::spawn_subscriber(redis.clone(), cluster::JOB_CANCEL_CHANNEL, move |task_id| {
+                if queue.abort_local(&task_id) {
+                    info!("Stopped background job {} cancelled on another instance", task_id);
+                }
+            });
+        }
+        tokio::spawn(self.clone().heartbeat());
+
+        for pool in WorkerPool::ALL {
+            info!("Starting {} worker pool with {} workers", pool.name(), pool.workers());
+            tokio::spawn(self.clone().dispatch(pool, state.clone()));
+        }
+        Ok(())
+    }
+
+    /// Delete finished jobs older than their task entries.
+    pub async fn prune_finished(&self) -> AppResult<u64> {
+        let deleted = match &self.db_pool {
+            DatabasePool::Postgres(pool) => sqlx::query(
+                r#"DELETE FROM "BackgroundJobs"
+                   WHERE status IN ($1, $2, $3) AND finishedat < NOW() - (INTERVAL '1 day' * $4)"#,
+            )
+            .bind(STATUS_COMPLETED)
+            .bind(STATUS_FAILED)
+            .bind(STATUS_CANCELLED)
+            .bind(KEEP_FINISHED_DAYS as f64)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+            DatabasePool::MySQL(pool) => sqlx::query(
+                "DELETE FROM BackgroundJobs
+                 WHERE Status IN (?, ?, ?) AND FinishedAt < NOW() - INTERVAL ? DAY",
+            )
+            .bind(STATUS_COMPLETED)
+            .bind(STATUS_FAILED)
+            .bind(STATUS_CANCELLED)
+            .bind(KEEP_FINISHED_DAYS)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        };
+        Ok(deleted)
+    }
+
+    /// Stop the job if it runs on this replica. Returns whether it did.
+    fn abort_local(&self, task_id: &str) -> bool {
+        match self.running.lock().unwrap().remove(task_id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Keep this replica's claims fresh and take back jobs whose replica stopped checking in.
+    async fn heartbeat(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(Duration::from_secs(HEARTBEAT_SECONDS)).await;
+            if let Err(e) = self.touch_claims().await {
+                warn!("Failed to refresh background job heartbeats: {}", e);
+            }
+            match self.requeue_orphaned(false).await {
+                Ok(0) => {}
+                Ok(n) => {
+                    info!("Queued again {} background jobs abandoned by another instance", n);
+                    for wake in self.wake.values() {
+                        wake.notify_one();
+                    }
+                }
+                Err(e) => warn!("Failed to look for abandoned background jobs: {}", e),
+            }
+        }
+    }
+
+    async fn touch_claims(&self) -> AppResult<()> {
+        match &self.db_pool {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(
+                    r#"UPDATE "BackgroundJobs" SET heartbeatat = NOW() WHERE claimedby = $1 AND status = $2"#,
+                )
+                .bind(cluster::instance_id())
+                .bind(STATUS_RUNNING)
+                .execute(pool)
+                .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("UPDATE BackgroundJobs SET HeartbeatAt = NOW() WHERE ClaimedBy = ? AND Status = ?")
+                    .bind(cluster::instance_id())
+                    .bind(STATUS_RUNNING)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue again running jobs whose heartbeat went stale, plus (at startup) any this instance
+    /// held before it restarted. The attempt stays counted.
+    async fn requeue_orphaned(&self, including_own: bool) -> AppResult<u64> {
+        let own = if including_own { Some(cluster::instance_id()) } else { None };
+        let requeued = match &self.db_pool {
+            DatabasePool::Postgres(pool) => sqlx::query(
+                r#"UPDATE "BackgroundJobs" SET status = $1, nextattemptat = NOW(), claimedby = NULL
+                   WHERE status = $2
+                     AND (heartbeatat IS NULL
+                          OR heartbeatat < NOW() - (INTERVAL '1 second' * $3)
+                          OR claimedby = $4)"#,
+            )
+            .bind(STATUS_QUEUED)
+            .bind(STATUS_RUNNING)
+            .bind(STALE_AFTER_SECONDS as f64)
+            .bind(own)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+            DatabasePool::MySQL(pool) => sqlx::query(
+                "UPDATE BackgroundJobs SET Status = ?, NextAttemptAt = NOW(), ClaimedBy = NULL
+                 WHERE Status = ?
+                   AND (HeartbeatAt IS NULL
+                        OR HeartbeatAt < NOW() - INTERVAL ? SECOND
+                        OR ClaimedBy = ?)",
+            )
+            .bind(STATUS_QUEUED)
+            .bind(STATUS_RUNNING)
+            .bind(STALE_AFTER_SECONDS)
+            .bind(own)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        };
+        Ok(requeued)
+    }
+
+    /// Run `pool`'s jobs forever: wait for a free worker, then claim and start the next due job.
+    async fn dispatch(self: Arc<Self>, pool: WorkerPool, state: Arc<AppState>) {
+        let wake = self.wake[&pool].clone();
+        loop {
+            let permit = acquire(pool).await;
+            match self.claim(pool).await {
+                Ok(Some(job)) => self.run(job, permit, state.clone()),
+                Ok(None) => {
+                    drop(permit);
+                    tokio::select! {
+                        _ = wake.notified() => {}
+                        _ = tokio::time::sleep(Duration::from_secs(POLL_SECONDS)) => {}
+                    }
+                }
+                Err(e) => {
+                    drop(permit);
+                    warn!("Failed to claim a {} job: {}", pool.name(), e);
+                    tokio::time::sleep(Duration::from_secs(POLL_SECONDS)).await;
+                }
+            }
+        }
+    }
+
+    /// Mark the oldest due job of `pool` running. The conditional update makes the claim
+    /// exclusive, so a job never runs twice at once.
+    async fn claim(&self, pool: WorkerPool) -> AppResult<Option<ClaimedJob>> {
+        loop {
+            let candidate = match &self.db_pool {
+                DatabasePool::Postgres(db) => sqlx::query(
+                    r#"SELECT taskid, userid, payload, attempts FROM "BackgroundJobs"
+                       WHERE pool = $1 AND status = $2 AND nextattemptat <= NOW()
+                       ORDER BY nextattemptat, jobid
+                       LIMIT 1"#,
+                )
+                .bind(pool.name())
+                .bind(STATUS_QUEUED)
+                .fetch_optional(db)
+                .await?
+                .map(|r| -> AppResult<(String, i32, String, i32)> {
+                    Ok((r.try_get("taskid")?, r.try_get("userid")?, r.try_get("payload")?, r.try_get("attempts")?))
+                })
+                .transpose()?,
+                DatabasePool::MySQL(db) => sqlx::query(
+                    "SELECT TaskID, UserID, Payload, Attempts FROM BackgroundJobs
+                     WHERE Pool = ? AND Status = ? AND NextAttemptAt <= NOW()
+                     ORDER BY NextAttemptAt, JobID
+                     LIMIT 1",
+                )
+                .bind(pool.name())
+                .bind(STATUS_QUEUED)
+                .fetch_optional(db)
+                .await?
+                .map(|r| -> AppResult<(String, i32, String, i32)> {
+                    Ok((r.try_get("TaskID")?, r.try_get("UserID")?, r.try_get("Payload")?, r.try_get("Attempts")?))
+                })
+                .transpose()?,
+            };
+            let Some((task_id, user_id, payload, attempts)) = candidate else {
+                return Ok(None);
+            };
+
+            let claimed = match &self.db_pool {
+                DatabasePool::Postgres(db) => sqlx::query(
+                    r#"UPDATE "BackgroundJobs"
+                       SET status = $2, attempts = attempts + 1, claimedby = $4, heartbeatat = NOW()
+                       WHERE taskid = $1 AND status = $3"#,
+                )
+                .bind(&task_id)
+                .bind(STATUS_RUNNING)
+                .bind(STATUS_QUEUED)
+                .bind(cluster::instance_id())
+                .execute(db)
+                .await?
+                .rows_affected(),
+                DatabasePool::MySQL(db) => sqlx::query(
+                    "UPDATE BackgroundJobs
+                     SET Status = ?, Attempts = Attempts + 1, ClaimedBy = ?, HeartbeatAt = NOW()
+                     WHERE TaskID = ? AND Status = ?",
+                )
+                .bind(STATUS_RUNNING)
+                .bind(cluster::instance_id())
+                .bind(&task_id)
+                .bind(STATUS_QUEUED)
+                .execute(db)
+                .await?
+                .rows_affected(),
+            };
+            if claimed != 1 {
+                continue;
+            }
+
+            let job = match serde_json::from_str::<Job>(&payload) {
+                Ok(job) => job,
+                Err(e) => {
+                    error!("Background job {} has an unreadable payload: {}", task_id, e);
+                    self.record(&task_id, attempts + 1, STATUS_FAILED, Some(&e.to_string()), 0).await?;
+                    continue;
+                }
+            };
+            // The task entry may have expired or been lost while the job waited.
+            self.task_manager.ensure_task(&task_id, job.task_type(), user_id).await?;
+            return Ok(Some(ClaimedJob { task_id, user_id, job, attempts: attempts + 1 }));
+        }
+    }
+
+    /// Run a claimed job on its own task, holding the pool permit until it finishes.
+    fn run(self: &Arc<Self>, claimed: ClaimedJob, permit: OwnedSemaphorePermit, state: Arc<AppState>) {
+        let queue = self.clone();
+        let task_id = claimed.task_id.clone();
+
+        // Registered under the lock before the job can finish and deregister itself.
+        let mut running = self.running.lock().unwrap();
+        let handle = tokio::spawn(async move {
+            let _permit = permit;
+            let result = execute(&state, &claimed).await;
+            queue.running.lock().unwrap().remove(&claimed.task_id);
+            if let Err(e) = queue.finish(&claimed, result).await {
+                error!("Failed to record the outcome of job {}: {}", claimed.task_id, e);
+            }
+        });
+        running.insert(task_id, handle.abort_handle());
+    }
+
+    /// Record a finished attempt: done, queued again after a backoff, or failed for good.
+    async fn finish(&self, claimed: &ClaimedJob, result: AppResult<Value>) -> AppResult<()> {
+        let task_id = claimed.task_id.as_str();
+        match result {
+            Ok(value) => {
+                if self.record(task_id, claimed.attempts, STATUS_COMPLETED, None, 0).await? {
+                    self.task_manager.complete_task(task_id, Some(value), None).await?;
+                }
+            }
+            Err(e) if is_retryable(&e) && claimed.attempts < claimed.job.max_attempts() => {
+                let delay = retry_delay_seconds(claimed.attempts);
+                warn!(
+                    "Job {} ({}) attempt {}/{} failed, retrying in {}s: {}",
+                    task_id, claimed.job.task_type(), claimed.attempts, claimed.job.max_attempts(), delay, e
+                );
+                if self.record(task_id, claimed.attempts, STATUS_QUEUED, Some(&e.to_string()), delay).await? {
+                    let message = format!(
+                        "Attempt {} of {} failed, retrying in {}s: {}",
+                        claimed.attempts, claimed.job.max_attempts(), delay, e
+                    );
+                    self.task_manager.requeue_task(task_id, message).await?;
+                }
+            }
+            Err(e) => {
+                error!("Job {} ({}) for user {} failed: {}", task_id, claimed.job.task_type(), claimed.user_id, e);
+                if self.record(task_id, claimed.attempts, STATUS_FAILED, Some(&e.to_string()), 0).await? {
+                    self.task_manager.fail_task(task_id, e.to_string()).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move this replica's attempt `attempts` of a running job to `status`. Returns false if the
+    /// job was cancelled meanwhile, or presumed abandoned and claimed again.
+    async fn record(&self, task_id: &str, attempts: i32, status: &str, error: Option<&str>, delay: i64) -> AppResult<bool> {
+        let updated = match &self.db_pool {
+            DatabasePool::Postgres(pool) => sqlx::query(
+                r#"UPDATE "BackgroundJobs"
+                   SET status = $2, lasterror = $3,
+                       nextattemptat = NOW() + (INTERVAL '1 second' * $4),
+                       finishedat = CASE WHEN $2 = 'queued' THEN NULL ELSE NOW() END,
+                       claimedby = NULL
+                   WHERE taskid = $1 AND status = $5 AND claimedby = $6 AND attempts = $7"#,
+            )
+            .bind(task_id)
+            .bind(status)
+            .bind(error)
+            .bind(delay as f64)
+            .bind(STATUS_RUNNING)
+            .bind(cluster::instance_id())
+            .bind(attempts)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+            DatabasePool::MySQL(pool) => sqlx::query(
+                "UPDATE BackgroundJobs
+                 SET Status = ?, LastError = ?,
+                     NextAttemptAt = NOW() + INTERVAL ? SECOND,
+                     FinishedAt = IF(? = 'queued', NULL, NOW()),
+                     ClaimedBy = NULL
+                 WHERE TaskID = ? AND Status = ? AND ClaimedBy = ? AND Attempts = ?",
+            )
+            .bind(status)
+            .bind(error)
+            .bind(delay)
+            .bind(status)
+            .bind(task_id)
+            .bind(STATUS_RUNNING)
+            .bind(cluster::instance_id())
+            .bind(attempts)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        };
+        Ok(updated == 1)
+    }
+
+    /// The task ID of a queued or running job with this kind and payload, if any.
+    async fn active_task(&self, kind: &str, payload: &str) -> AppResult<Option<String>> {
+        let task_id = match &self.db_pool {
+            DatabasePool::Postgres(pool) => sqlx::query_scalar(
+                r#"SELECT taskid FROM "BackgroundJobs" WHERE kind = $1 AND payload = $2 AND status IN ($3, $4) LIMIT 1"#,
+            )
+            .bind(kind)
+            .bind(payload)
+            .bind(STATUS_QUEUED)
+            .bind(STATUS_RUNNING)
+            .fetch_optional(pool)
+            .await?,
+            DatabasePool::MySQL(pool) => sqlx::query_scalar(
+                "SELECT TaskID FROM BackgroundJobs WHERE Kind = ? AND Payload = ? AND Status IN (?, ?) LIMIT 1",
+            )
+            .bind(kind)
+            .bind(payload)
+            .bind(STATUS_QUEUED)
+            .bind(STATUS_RUNNING)
+            .fetch_optional(pool)
+            .await?,
+        };
+        Ok(task_id)
+    }
+}
+
+async fn execute(state: &Arc<AppState>, claimed: &ClaimedJob) -> AppResult<Value> {
+    use crate::services::tasks;
+
+    let task_manager = &state.task_manager;
+    let db_pool = &state.db_pool;
+    let task_id = claimed.task_id.as_str();
+    match claimed.job {
+        Job::DownloadEpisode { episode_id } => {
+            tasks::download_podcast_episode(task_manager, db_pool, task_id, episode_id, claimed.user_id).await
+        }
+        Job::DownloadVideo { video_id, with_video } => {
+            tasks::download_youtube_video(task_manager, db_pool, task_id, video_id, claimed.user_id, with_video).await
+        }
+        Job::TranscribeEpisode { episode_id, force } => {
+            tasks::transcribe_episode(task_manager, db_pool, task_id, episode_id, force)
+                .await
+                .map_err(AppError::internal)
+        }
+        Job::DetectAds { episode_id, force } => {
+            tasks::detect_ads(task_manager, db_pool, task_id, episode_id, force)
+                .await
+                .map_err(AppError::internal)
+        }
+        Job::RefreshFeeds => {
+            crate::services::scheduler::BackgroundScheduler::run_refresh_pods(state.clone()).await?;
+            Ok(serde_json::json!({ "status": "refreshed" }))
+        }
+        Job::Transcode { ref source, ref profile } => {
+            let profile = crate::services::transcode::Profile::parse(profile)
+                .map_err(AppError::bad_request)?
+                .ok_or_else(|| AppError::bad_request("No transcode profile given"))?;
+            let source = std::path::Path::new(source);
+            if !source.exists() {
+                return Err(AppError::not_found(format!("{} no longer exists", source.display())));
+            }
+            crate::services::transcode::render(source, profile).await.map_err(AppError::internal)?;
+            Ok(serde_json::json!({ "profile": profile.name() }))
+        }
+        Job::JoinBook { episode_id } => {
+            crate::services::local_books::render_joined(db_pool, episode_id)
+                .await
+                .map_err(AppError::internal)?;
+            Ok(serde_json::json!({ "episode_id": episode_id }))
+        }
+        Job::CutAudio { user_id, episode_id, ref source } => {
+            let source = std::path::Path::new(source);
+            if !source.exists() {
+                return Err(AppError::not_found(format!("{} no longer exists", source.display())));
+            }
+            crate::services::audio_cut::render_cut(db_pool, user_id, episode_id, source)
+                .await
+                .map_err(AppError::internal)?;
+            Ok(serde_json::json!({ "episode_id": episode_id }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_backoff_doubles_up_to_an_hour() {
+        assert_eq!(retry_delay_seconds(1), 30);
+        assert_eq!(retry_delay_seconds(2), 60);
+        assert_eq!(retry_delay_seconds(4), 240);
+        assert_eq!(retry_delay_seconds(20), MAX_RETRY_SECONDS);
+    }
+
+    #[test]
+    fn jobs_round_trip_through_their_payload() {
+        let jobs = [
+            Job::DownloadEpisode { episode_id: 7 },
+            Job::DownloadVideo { video_id: 3, with_video: true },
+            Job::TranscribeEpisode { episode_id: 7, force: false },
+            Job::DetectAds { episode_id: 7, force: true },
+            Job::RefreshFeeds,
+            Job::Transcode { source: "/opt/pinepods/downloads/a.mp3".to_string(), profile: "opus48".to_string() },
+            Job::JoinBook { episode_id: 7 },
+            Job::CutAudio { user_id: 2, episode_id: 7, source: "/opt/pinepods/downloads/a.mp3".to_string() },
+        ];
+        for job in jobs {
+            let payload = serde_json::to_string(&job).unwrap();
+            assert_eq!(serde_json::from_str::<Job>(&payload).unwrap(), job);
+        }
+        assert_eq!(
+            serde_json::to_value(Job::DownloadEpisode { episode_id: 7 }).unwrap(),
+            serde_json::json!({ "kind": "download_episode", "episode_id": 7 })
+        );
+    }
+
+    #[test]
+    fn jobs_run_on_their_kind_of_pool() {
+        assert_eq!(Job::DownloadVideo { video_id: 1, with_video: false }.pool(), WorkerPool::Downloads);
+        assert_eq!(Job::TranscribeEpisode { episode_id: 1, force: false }.pool(), WorkerPool::Transcription);
+        assert_eq!(Job::DetectAds { episode_id: 1, force: false }.pool(), WorkerPool::AdDetection);
+        assert_eq!(Job::RefreshFeeds.pool(), WorkerPool::FeedRefresh);
+        assert_eq!(Job::Transcode { source: "a.mp3".to_string(), profile: "aac64".to_string() }.pool(), WorkerPool::Renders);
+        assert_eq!(Job::RefreshFeeds.item_id(), None);
+        assert_eq!(Job::JoinBook { episode_id: 1 }.pool(), WorkerPool::Renders);
+        assert!(Job::RefreshFeeds.is_shared());
+        assert!(Job::JoinBook { episode_id: 1 }.is_shared());
+        assert!(!Job::DownloadEpisode { episode_id: 1 }.is_shared());
+    }
+
+    #[test]
+    fn pools_are_found_by_their_wake_up_name() {
+        for pool in WorkerPool::ALL {
+            assert_eq!(WorkerPool::from_name(pool.name()), Some(pool));
+        }
+        assert_eq!(WorkerPool::from_name("nope"), None);
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        assert!(is_retryable(&AppError::internal("connection reset")));
+        assert!(is_retryable(&AppError::external_error("yt-dlp exited with 1")));
+        assert!(!is_retryable(&AppError::not_found("Episode not found")));
+        assert!(!is_retryable(&AppError::Database(sqlx::Error::RowNotFound)));
+        assert!(!is_retryable(&AppError::conflict("Download quota reached")));
+    }
+}
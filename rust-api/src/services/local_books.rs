@@ -0,0 +1,461 @@
+//! Audiobook mode for local podcasts.
+//!
+//! A local podcast added with `kind: "book"` is one book rather than a set of episodes. Its files
+//! are parts of a single timeline, ordered by disc, then track, then filename (numbers in names
+//! compare numerically, so "Part 2" comes before "Part 10"). The book is a single Episodes row, so
+//! it has one resume position, one queue entry and one item in the generated RSS feed. Embedded
+//! chapters from every part are shifted onto the joined timeline; a part without chapters becomes
+//! one chapter named after the part.
+//!
+//! `stream_episode` serves the joined audio through [`joined_media`]: a single-file book (one
+//! M4B) is served as is, and a multi-file book is concatenated by ffmpeg into [`CACHE_DIR`] once
+//! and reused until a part changes. Joining a long book takes minutes, so the request that finds
+//! no cached file queues a render job ([`render_joined`]) and is told to retry instead of
+//! waiting. The cache is trimmed with the other render caches (`PINEPODS_BOOK_CACHE_MB`,
+//! default 8192).
+
+use crate::database::DatabasePool;
+use crate::error::AppResult;
+use crate::handlers::local_podcast::LocalEpisodeCandidate;
+use crate::services::audio_cut::ffmetadata_escape;
+use crate::services::cluster;
+use crate::services::job_queue::{Job, JobQueue};
+use crate::services::media_tags::Chapter;
+use crate::services::transcode;
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{debug, info, warn};
+
+/// Under the downloads mount, next to the transcode and cut-copy caches.
+pub const CACHE_DIR: &str = "/opt/pinepods/downloads/.book-cache";
+
+const DEFAULT_CACHE_MB: u64 = 8192;
+
+/// One file of a book, as stored in `LocalBookParts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookPart {
+    pub file_path: String,
+    pub title: String,
+    pub disc_num: Option<u32>,
+    pub track_num: Option<u32>,
+    pub duration: i32,
+    /// Where the part starts on the joined timeline, in seconds. Set by [`assemble`].
+    pub start_offset: i32,
+    /// The part's own embedded chapters, relative to the start of the file.
+    pub chapters: Vec<Chapter>,
+}
+
+impl From<LocalEpisodeCandidate> for BookPart {
+    fn from(candidate: LocalEpisodeCandidate) -> Self {
+        BookPart {
+            file_path: candidate.file_path,
+            title: candidate.title,
+            disc_num: candidate.disc_num,
+            track_num: candidate.track_num,
+            duration: candidate.duration,
+            start_offset: 0,
+            chapters: candidate.chapters,
+        }
+    }
+}
+
+/// A book's parts in playback order with its merged chapters.
+#[derive(Debug, Clone)]
+pub struct LocalBook {
+    pub parts: Vec<BookPart>,
+    pub chapters: Vec<Chapter>,
+    pub duration: i32,
+}
+
+/// What a new book's episode row is created with, taken from its files.
+#[derive(Debug, Clone)]
+pub struct BookDetails {
+    pub description: String,
+    pub pub_date: NaiveDateTime,
+    pub artwork_url: Option<String>,
+}
+
+impl BookDetails {
+    pub fn from_candidates(candidates: &[LocalEpisodeCandidate]) -> Option<Self> {
+        Some(BookDetails {
+            description: candidates
+                .iter()
+                .map(|c| c.description.trim())
+                .find(|d| !d.is_empty())
+                .unwrap_or_default()
+                .to_string(),
+            pub_date: candidates.iter().map(|c| c.pub_date).min()?,
+            artwork_url: candidates.iter().find_map(|c| c.artwork_url.clone()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NameChunk {
+    Number(u64),
+    Text(String),
+}
+
+/// Split a filename into text and number runs so numbers sort by value.
+fn natural_key(name: &str) -> Vec<NameChunk> {
+    let mut chunks = Vec::new();
+    let mut rest = name;
+    while let Some(first) = rest.chars().next() {
+        let is_digit = first.is_ascii_digit();
+        let end = rest.find(|c: char| c.is_ascii_digit() != is_digit).unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        chunks.push(match run.parse() {
+            Ok(n) if is_digit => NameChunk::Number(n),
+            _ => NameChunk::Text(run.to_lowercase()),
+        });
+        rest = tail;
+    }
+    chunks
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or(path)
+}
+
+/// Disc (untagged counts as disc 1), then track (untagged after tagged), then filename.
+fn part_order(a: &BookPart, b: &BookPart) -> Ordering {
+    a.disc_num
+        .unwrap_or(1)
+        .cmp(&b.disc_num.unwrap_or(1))
+        .then_with(|| match (a.track_num, b.track_num) {
+            (Some(ta), Some(tb)) => ta.cmp(&tb),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        .then_with(|| natural_key(file_name(&a.file_path)).cmp(&natural_key(file_name(&b.file_path))))
+}
+
+/// Every part's chapters shifted onto the joined timeline.
+fn merge_chapters(parts: &[BookPart]) -> Vec<Chapter> {
+    let mut merged = Vec::new();
+    for part in parts {
+        let offset = part.start_offset as f64;
+        if part.chapters.is_empty() {
+            merged.push(Chapter {
+                start: offset,
+                end: (part.duration > 0).then_some(offset + part.duration as f64),
+                title: part.title.clone(),
+            });
+            continue;
+        }
+        merged.extend(part.chapters.iter().map(|c| Chapter {
+            start: offset + c.start,
+            end: c.end.map(|end| offset + end),
+            title: c.title.clone(),
+        }));
+    }
+    merged
+}
+
+/// Order the parts and lay them out on one timeline.
+pub fn assemble(mut parts: Vec<BookPart>) -> LocalBook {
+    parts.sort_by(part_order);
+    let mut offset = 0;
+    for part in &mut parts {
+        part.start_offset = offset;
+        offset += part.duration.max(0);
+    }
+    LocalBook { chapters: merge_chapters(&parts), duration: offset, parts }
+}
+
+fn cache_key(parts: &[BookPart]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        let meta = std::fs::metadata(&part.file_path).ok();
+        let mtime = meta
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        hasher.update(part.file_path.as_bytes());
+        hasher.update(meta.map(|m| m.len()).unwrap_or(0).to_le_bytes());
+        hasher.update(mtime.to_le_bytes());
+    }
+    hex::encode(&hasher.finalize()[..16])
+}
+
+/// Output extension: the parts' own container when they all share one (stream copy), otherwise
+/// AAC in MP4.
+fn joined_extension(parts: &[BookPart]) -> (&'static str, bool) {
+    let extension = |part: &BookPart| {
+        Path::new(&part.file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+    };
+    let first = parts.first().and_then(extension);
+    if parts.iter().any(|p| extension(p) != first) {
+        return ("m4a", false);
+    }
+    match first.as_deref() {
+        Some("mp3") => ("mp3", true),
+        Some("m4a") | Some("m4b") => ("m4a", true),
+        Some("ogg") => ("ogg", true),
+        Some("opus") => ("opus", true),
+        Some("flac") => ("flac", true),
+        _ => ("m4a", false),
+    }
+}
+
+/// A book episode's audio, as far as `stream_episode` is concerned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinedMedia {
+    /// Serve this file.
+    Ready(PathBuf),
+    /// The joined file is being rendered by the job queue; ask the client to retry.
+    Rendering,
+}
+
+fn cache_path(parts: &[BookPart]) -> (String, PathBuf, bool) {
+    let key = cache_key(parts);
+    let (extension, copy) = joined_extension(parts);
+    let dest = Path::new(CACHE_DIR).join(format!("{}.{}", key, extension));
+    (key, dest, copy)
+}
+
+/// The audio `stream_episode` should serve for a book episode: the only part of a single-file
+/// book, or the cached concatenation of a multi-file one. A multi-file book that isn't cached yet
+/// queues a join job on `user_id`'s behalf and comes back as [`JoinedMedia::Rendering`]. `None`
+/// when the episode isn't a book.
+pub async fn joined_media(
+    jobs: &JobQueue,
+    db_pool: &DatabasePool,
+    user_id: i32,
+    episode_id: i32,
+) -> AppResult<Option<JoinedMedia>> {
+    let parts = db_pool.get_local_book_parts_for_episode(episode_id).await?;
+    match parts.as_slice() {
+        [] => Ok(None),
+        [only] => Ok(Some(JoinedMedia::Ready(PathBuf::from(&only.file_path)))),
+        _ => {
+            let (_, dest, _) = cache_path(&parts);
+            if tokio::fs::metadata(&dest).await.is_ok() {
+                transcode::touch(&dest);
+                return Ok(Some(JoinedMedia::Ready(dest)));
+            }
+            jobs.enqueue(user_id, Job::JoinBook { episode_id }).await?;
+            Ok(Some(JoinedMedia::Rendering))
+        }
+    }
+}
+
+/// Join a multi-file book episode's parts into the cache unless they already are. Run by the job
+/// queue; fails while another replica is joining the same book, so the job's retry finds the
+/// finished file.
+pub async fn render_joined(db_pool: &DatabasePool, episode_id: i32) -> Result<PathBuf, String> {
+    let parts = db_pool
+        .get_local_book_parts_for_episode(episode_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    match parts.as_slice() {
+        [] => Err(format!("episode {} is not an audiobook", episode_id)),
+        [only] => Ok(PathBuf::from(&only.file_path)),
+        _ => cached_join(&parts).await,
+    }
+}
+
+async fn cached_join(parts: &[BookPart]) -> Result<PathBuf, String> {
+    let (key, dest, copy) = cache_path(parts);
+    let extension = dest.extension().and_then(|e| e.to_str()).unwrap_or("m4a").to_string();
+
+    let lock = transcode::key_lock(&key);
+    let _guard = lock.lock().await;
+
+    if tokio::fs::metadata(&dest).await.is_ok() {
+        transcode::touch(&dest);
+        return Ok(dest);
+    }
+    let Some(_render_lock) = cluster::try_lock(&format!("render:{}", key), transcode::RENDER_LOCK_TTL_SECONDS)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err(format!("{} is being joined by another instance", dest.display()));
+    };
+
+    tokio::fs::create_dir_all(CACHE_DIR)
+        .await
+        .map_err(|e| format!("cannot create {}: {}", CACHE_DIR, e))?;
+    let partial = Path::new(CACHE_DIR).join(format!("{}.partial.{}", key, extension));
+    let started = std::time::Instant::now();
+    let result = match render(parts, &key, &partial, copy).await {
+        // Parts in one container can still disagree on codec parameters; re-encode instead.
+        Err(e) if copy => {
+            warn!("Stream-copy join of {} book parts failed, re-encoding: {}", parts.len(), e);
+            render(parts, &key, &partial, false).await
+        }
+        result => result,
+    };
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&partial, &dest)
+        .await
+        .map_err(|e| format!("cannot move joined book into cache: {}", e))?;
+    info!(
+        "Joined {} book parts into {} in {:.1}s",
+        parts.len(),
+        dest.display(),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(dest)
+}
+
+/// ffmpeg concat demuxer list. Paths are quoted with `'` escaped as the demuxer expects.
+fn concat_list(parts: &[BookPart]) -> String {
+    let mut out = String::from("ffconcat version 1.0\n");
+    for part in parts {
+        let _ = writeln!(out, "file '{}'", part.file_path.replace('\'', r"'\''"));
+    }
+    out
+}
+
+/// FFMETADATA with the merged chapters, so the joined file carries them too.
+fn chapters_ffmetadata(chapters: &[Chapter], total: i32) -> String {
+    let mut out = String::from(";FFMETADATA1\n");
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapter
+            .end
+            .or_else(|| chapters.get(i + 1).map(|next| next.start))
+            .unwrap_or(total as f64);
+        if end <= chapter.start {
+            continue;
+        }
+        let _ = write!(
+            out,
+            "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (chapter.start * 1000.0).round() as i64,
+            (end * 1000.0).round() as i64,
+            ffmetadata_escape(&chapter.title)
+        );
+    }
+    out
+}
+
+async fn render(parts: &[BookPart], key: &str, dest: &Path, copy: bool) -> Result<(), String> {
+    let list_path = Path::new(CACHE_DIR).join(format!("{}.partial.ffconcat", key));
+    let metadata_path = Path::new(CACHE_DIR).join(format!("{}.partial.ffmeta", key));
+    tokio::fs::write(&list_path, concat_list(parts))
+        .await
+        .map_err(|e| format!("cannot write concat list: {}", e))?;
+    tokio::fs::write(&metadata_path, chapters_ffmetadata(&merge_chapters(parts), parts.iter().map(|p| p.duration.max(0)).sum()))
+        .await
+        .map_err(|e| format!("cannot write chapter metadata: {}", e))?;
+
+    debug!("Joining {} parts into {} (copy: {})", parts.len(), dest.display(), copy);
+    let mut command = tokio::process::Command::new("ffmpeg");
+    command
+        .args(["-hide_banner", "-nostats", "-y", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .arg("-i")
+        .arg(&metadata_path)
+        .args(["-map", "0:a:0", "-map_metadata", "0", "-map_chapters", "1", "-vn"]);
+    if copy {
+        command.args(["-c:a", "copy"]);
+    } else {
+        command.args(["-c:a", "aac", "-b:a", "96k"]);
+    }
+    if dest.extension().and_then(|e| e.to_str()) == Some("m4a") {
+        command.args(["-f", "mp4"]);
+    }
+    let output = command.arg(dest).output().await.map_err(|e| format!("failed to spawn ffmpeg: {}", e));
+    let _ = tokio::fs::remove_file(&list_path).await;
+    let _ = tokio::fs::remove_file(&metadata_path).await;
+    let output = output?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or("")
+        ));
+    }
+    Ok(())
+}
+
+fn cache_limit_bytes() -> u64 {
+    std::env::var("PINEPODS_BOOK_CACHE_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CACHE_MB)
+        * 1024
+        * 1024
+}
+
+/// Trim the joined-book cache back under its size limit, least recently used first.
+pub async fn prune_cache() -> Result<(), String> {
+    transcode::prune_dir(CACHE_DIR, cache_limit_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(file: &str, disc: Option<u32>, track: Option<u32>, duration: i32, chapters: Vec<Chapter>) -> BookPart {
+        BookPart {
+            file_path: format!("/opt/pinepods/local-media/book/{}", file),
+            title: file.trim_end_matches(".mp3").to_string(),
+            disc_num: disc,
+            track_num: track,
+            duration,
+            start_offset: 0,
+            chapters,
+        }
+    }
+
+    #[test]
+    fn orders_parts_by_disc_track_and_natural_filename() {
+        let book = assemble(vec![
+            part("Part 10.mp3", None, None, 10, vec![]),
+            part("d2t1.mp3", Some(2), Some(1), 10, vec![]),
+            part("d1t2.mp3", Some(1), Some(2), 10, vec![]),
+            part("Part 2.mp3", None, None, 10, vec![]),
+            part("d1t1.mp3", None, Some(1), 10, vec![]),
+        ]);
+        let titles: Vec<_> = book.parts.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["d1t1", "d1t2", "Part 2", "Part 10", "d2t1"]);
+        assert_eq!(book.parts[4].start_offset, 40);
+        assert_eq!(book.duration, 50);
+    }
+
+    #[test]
+    fn merges_chapters_onto_one_timeline() {
+        let book = assemble(vec![
+            part(
+                "1.mp3",
+                None,
+                Some(1),
+                600,
+                vec![
+                    Chapter { start: 0.0, end: Some(300.0), title: "Opening".into() },
+                    Chapter { start: 300.0, end: None, title: "Chapter 1".into() },
+                ],
+            ),
+            part("2.mp3", None, Some(2), 400, vec![]),
+            part("3.mp3", None, Some(3), 200, vec![Chapter { start: 20.0, end: Some(200.0), title: "Epilogue".into() }]),
+        ]);
+        assert_eq!(
+            book.chapters,
+            vec![
+                Chapter { start: 0.0, end: Some(300.0), title: "Opening".into() },
+                Chapter { start: 300.0, end: None, title: "Chapter 1".into() },
+                Chapter { start: 600.0, end: Some(1000.0), title: "2".into() },
+                Chapter { start: 1020.0, end: Some(1200.0), title: "Epilogue".into() },
+            ]
+        );
+        let metadata = chapters_ffmetadata(&book.chapters, book.duration);
+        assert!(metadata.contains("START=300000\nEND=600000\ntitle=Chapter 1\n"));
+    }
+}
@@ -0,0 +1,144 @@
+//! Watch the local-media root so new audio files become episodes without a manual refresh.
+//!
+//! The root is watched recursively with inotify. An audio file being created, written or moved
+//! in marks its directory dirty; once a directory has been quiet for [`SETTLE`] (so large copies
+//! have finished) every local podcast backed by that directory picks up its new files the same
+//! way `/refresh_local_podcast` does. Deletions are left alone: episodes and their history stay
+//! until the user removes them.
+
+use crate::database::DatabasePool;
+use crate::handlers::local_podcast::{add_new_local_episodes, is_audio_file, LOCAL_MEDIA_ROOT};
+use notify::event::{AccessKind, AccessMode, EventKind, ModifyKind};
+use notify::{Event, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How long a directory must see no further writes before it is scanned.
+const SETTLE: Duration = Duration::from_secs(10);
+const TICK: Duration = Duration::from_secs(2);
+
+/// Disable with `LOCAL_MEDIA_WATCH=false`, e.g. on network mounts where inotify sees nothing.
+fn watch_enabled() -> bool {
+    !matches!(
+        std::env::var("LOCAL_MEDIA_WATCH").map(|v| v.to_ascii_lowercase()).as_deref(),
+        Ok("false") | Ok("0") | Ok("no")
+    )
+}
+
+/// The directory whose contents `event` changed, if it concerns an audio file.
+fn dirty_directory(event: &Event) -> Option<PathBuf> {
+    let relevant = matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Data(_))
+            | EventKind::Modify(ModifyKind::Name(_))
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    );
+    if !relevant {
+        return None;
+    }
+    // For renames the destination is the last path.
+    let path = event.paths.last()?;
+    if !is_audio_file(path) || path.components().any(|c| c.as_os_str() == "_artwork") {
+        return None;
+    }
+    path.parent().map(Path::to_path_buf)
+}
+
+async fn scan_directory(db_pool: &DatabasePool, dir: &Path) {
+    let Ok(dir) = dir.canonicalize() else { return };
+    let podcasts = match db_pool.get_local_podcasts_for_directory(&dir.display().to_string()).await {
+        Ok(podcasts) => podcasts,
+        Err(e) => {
+            warn!("Local media watch: lookup for {} failed: {}", dir.display(), e);
+            return;
+        }
+    };
+    for (podcast_id, user_id) in podcasts {
+        match add_new_local_episodes(db_pool, podcast_id, user_id, &dir).await {
+            Ok(0) => {}
+            Ok(added) => info!("📂 Local media watch: {} new episode(s) in {} for podcast {}", added, dir.display(), podcast_id),
+            Err(e) => warn!("Local media watch: scanning {} for podcast {} failed: {}", dir.display(), podcast_id, e),
+        }
+    }
+}
+
+/// Start watching the local-media root. No-op if it doesn't exist or watching is disabled.
+pub fn spawn_watcher(db_pool: DatabasePool) {
+    let root = Path::new(LOCAL_MEDIA_ROOT);
+    if !watch_enabled() || !root.is_dir() {
+        info!("Local media watch disabled ({} not present or LOCAL_MEDIA_WATCH=false)", LOCAL_MEDIA_ROOT);
+        return;
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = match notify::recommended_watcher(move |result: notify::Result<Event>| {
+        if let Ok(event) = result {
+            let _ = tx.send(event);
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("Local media watch unavailable: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(root, RecursiveMode::Recursive) {
+        warn!("Local media watch could not watch {}: {}", LOCAL_MEDIA_ROOT, e);
+        return;
+    }
+    info!("📂 Watching {} for new local media", LOCAL_MEDIA_ROOT);
+
+    tokio::spawn(async move {
+        // Dropping the watcher stops the inotify watches; it lives as long as this loop.
+        let _watcher = watcher;
+        let mut dirty: HashMap<PathBuf, Instant> = HashMap::new();
+        let mut tick = tokio::time::interval(TICK);
+        loop {
+            tokio::select! {
+                event = rx.recv() => {
+                    let Some(event) = event else { break };
+                    if let Some(dir) = dirty_directory(&event) {
+                        dirty.insert(dir, Instant::now());
+                    }
+                }
+                _ = tick.tick() => {
+                    let settled: Vec<PathBuf> = dirty
+                        .iter()
+                        .filter(|(_, touched)| touched.elapsed() >= SETTLE)
+                        .map(|(dir, _)| dir.clone())
+                        .collect();
+                    for dir in settled {
+                        dirty.remove(&dir);
+                        scan_directory(&db_pool, &dir).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, RemoveKind, RenameMode};
+
+    #[test]
+    fn only_new_or_written_audio_files_dirty_their_directory() {
+        let event = |kind, paths: &[&str]| Event { kind, paths: paths.iter().map(PathBuf::from).collect(), attrs: Default::default() };
+        let dir = Some(PathBuf::from("/opt/pinepods/local-media/show"));
+
+        assert_eq!(dirty_directory(&event(EventKind::Create(CreateKind::File), &["/opt/pinepods/local-media/show/1.opus"])), dir);
+        assert_eq!(
+            dirty_directory(&event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/tmp/partial", "/opt/pinepods/local-media/show/2.M4A"]
+            )),
+            dir
+        );
+        assert_eq!(dirty_directory(&event(EventKind::Create(CreateKind::File), &["/opt/pinepods/local-media/show/cover.jpg"])), None);
+        assert_eq!(dirty_directory(&event(EventKind::Remove(RemoveKind::File), &["/opt/pinepods/local-media/show/1.mp3"])), None);
+    }
+}
@@ -0,0 +1,692 @@
+//! Database-engine-neutral server backups.
+//!
+//! `pg_dump`/`mysqldump` output can only be loaded back into the engine that produced it and
+//! needs those binaries in the container. A logical backup is written from [`DatabasePool`]
+//! itself as JSON lines, which either engine can load:
+//!
+//! ```text
+//! {"format":"pinepods-logical-backup","version":1,"schema_version":"066","source_engine":"mysql",...}
+//! {"table":"Podcasts","columns":["PodcastID","PodcastName",...]}
+//! [1,"Some Show",...]
+//! ...
+//! {"end":{"tables":57,"rows":123456}}
+//! ```
+//!
+//! Rows are read inside one snapshot transaction, so the backup is consistent. On restore,
+//! tables and columns are matched case-insensitively (Postgres folds our unquoted column names
+//! to lowercase, MySQL keeps them CamelCase) and every value is cast to the target column's
+//! type, so booleans stored as `TINYINT`, `JSON` podcast-id lists and `INTEGER[]` arrays, and
+//! both engines' timestamp spellings all land correctly. The load runs in a single transaction:
+//! a truncated file or a failing row leaves the existing data untouched. On Postgres the load
+//! skips FK triggers, which needs a superuser (or a granted `SET` on `session_replication_role`);
+//! other roles fall back to deferred constraints, which only cover constraints declared
+//! `DEFERRABLE`.
+//!
+//! `schema_migrations` is never copied. The target must already be migrated to at least the
+//! backup's schema version; columns the target has dropped are ignored and columns it has
+//! since added take their defaults.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::tasks::ProgressReporter;
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{info, warn};
+
+pub const FORMAT: &str = "pinepods-logical-backup";
+pub const FORMAT_VERSION: u32 = 1;
+/// File extension for logical backups, next to `.sql` dumps in the backups directory.
+pub const EXTENSION: &str = ".jsonl";
+
+/// Not copied: login tokens are disposable (and excluded from SQL dumps for the same reason),
+/// and migration state belongs to the target's own schema.
+const SKIP_TABLES: &[&str] = &["schema_migrations", "sessions", "gpoddersessions"];
+
+/// Bind parameters per multi-row INSERT, under both engines' 65535-placeholder limit.
+const MAX_PARAMS_PER_INSERT: usize = 20_000;
+const MAX_ROWS_PER_INSERT: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    schema_version: Option<String>,
+    source_engine: String,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TableStart {
+    table: String,
+    columns: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BackupTotals {
+    pub tables: usize,
+    pub rows: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Trailer {
+    end: BackupTotals,
+}
+
+fn engine_name(db_pool: &DatabasePool) -> &'static str {
+    match db_pool {
+        DatabasePool::Postgres(_) => "postgresql",
+        DatabasePool::MySQL(_) => "mysql",
+    }
+}
+
+fn skipped(table: &str) -> bool {
+    SKIP_TABLES.contains(&table.to_ascii_lowercase().as_str())
+}
+
+fn pg_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn mysql_ident(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// A column of a table on this server.
+struct Column {
+    name: String,
+    /// Postgres: `format_type()` output, used as the cast target. MySQL: `DATA_TYPE`.
+    sql_type: String,
+}
+
+async fn list_tables(db_pool: &DatabasePool) -> AppResult<Vec<String>> {
+    let tables = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            "SELECT tablename::text AS name FROM pg_tables WHERE schemaname = 'public' ORDER BY tablename",
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<String, _>("name"))
+        .collect::<Result<Vec<_>, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT CAST(TABLE_NAME AS CHAR) AS name FROM information_schema.TABLES
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_NAME",
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<String, _>("name"))
+        .collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(tables)
+}
+
+/// Writable (non-generated) columns of `table`, in table order.
+async fn list_columns(db_pool: &DatabasePool, table: &str) -> AppResult<Vec<Column>> {
+    let columns = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            "SELECT a.attname::text AS name, format_type(a.atttypid, a.atttypmod) AS sql_type
+             FROM pg_attribute a
+             JOIN pg_class c ON c.oid = a.attrelid
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE n.nspname = 'public' AND c.relname = $1 AND a.attnum > 0
+               AND NOT a.attisdropped AND a.attgenerated = ''
+             ORDER BY a.attnum",
+        )
+        .bind(table)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| Ok(Column { name: r.try_get("name")?, sql_type: r.try_get("sql_type")? }))
+        .collect::<Result<Vec<_>, sqlx::Error>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT CAST(COLUMN_NAME AS CHAR) AS name, CAST(DATA_TYPE AS CHAR) AS sql_type
+             FROM information_schema.COLUMNS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND EXTRA NOT LIKE '%GENERATED%'
+             ORDER BY ORDINAL_POSITION",
+        )
+        .bind(table)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| Ok(Column { name: r.try_get("name")?, sql_type: r.try_get("sql_type")? }))
+        .collect::<Result<Vec<_>, sqlx::Error>>()?,
+    };
+    Ok(columns)
+}
+
+/// Migrations from this number up are the gpodder sync series, applied on every install
+/// regardless of the core schema's age.
+const GPODDER_MIGRATIONS_FROM: u32 = 100;
+
+/// Highest applied core migration, e.g. `"066"`.
+async fn schema_version(db_pool: &DatabasePool) -> AppResult<Option<String>> {
+    let versions: Vec<String> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT version::text AS v FROM "schema_migrations""#)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|r| r.try_get("v"))
+            .collect::<Result<_, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT CAST(version AS CHAR) AS v FROM schema_migrations")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|r| r.try_get("v"))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(core_version(versions.iter().map(String::as_str)).map(|v| format!("{:03}", v)))
+}
+
+/// The highest core migration number among `versions`. Versions are stored as text, so they are
+/// compared as numbers here rather than with SQL `MAX`.
+fn core_version<'a>(versions: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+    versions
+        .into_iter()
+        .filter_map(|v| v.trim().parse::<u32>().ok())
+        .filter(|v| *v < GPODDER_MIGRATIONS_FROM)
+        .max()
+}
+
+async fn write_line<W: AsyncWrite + Unpin, T: Serialize>(out: &mut W, value: &T) -> AppResult<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    out.write_all(&line).await?;
+    Ok(())
+}
+
+/// Stream a logical backup of every table into `out`.
+pub async fn write_backup<W: AsyncWrite + Unpin + Send>(db_pool: &DatabasePool, out: W) -> AppResult<BackupTotals> {
+    let mut out = tokio::io::BufWriter::new(out);
+    write_line(
+        &mut out,
+        &Header {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            schema_version: schema_version(db_pool).await?,
+            source_engine: engine_name(db_pool).to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+    .await?;
+
+    let mut totals = BackupTotals::default();
+    let tables: Vec<String> = list_tables(db_pool).await?.into_iter().filter(|t| !skipped(t)).collect();
+    let mut table_columns = Vec::with_capacity(tables.len());
+    for table in tables {
+        let columns: Vec<String> = list_columns(db_pool, &table).await?.into_iter().map(|c| c.name).collect();
+        table_columns.push((table, columns));
+    }
+
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let mut tx = pool.begin().await?;
+            // One snapshot for every table, so rows that reference each other stay consistent.
+            sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY").execute(&mut *tx).await?;
+            for (table, columns) in &table_columns {
+                write_line(&mut out, &TableStart { table: table.clone(), columns: columns.clone() }).await?;
+                // row_to_json renders every type (arrays, timestamps, numerics) as portable JSON.
+                let sql = format!("SELECT row_to_json(t)::text AS r FROM {} t", pg_ident(table));
+                let mut rows = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).fetch(&mut *tx);
+                while let Some(row) = rows.try_next().await? {
+                    let object: serde_json::Map<String, Value> = serde_json::from_str(&row.try_get::<String, _>("r")?)?;
+                    let values: Vec<&Value> = columns.iter().map(|c| object.get(c).unwrap_or(&Value::Null)).collect();
+                    write_line(&mut out, &values).await?;
+                    totals.rows += 1;
+                }
+                totals.tables += 1;
+            }
+            tx.commit().await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            // InnoDB's default REPEATABLE READ gives the transaction a single consistent snapshot.
+            let mut tx = pool.begin().await?;
+            for (table, columns) in &table_columns {
+                write_line(&mut out, &TableStart { table: table.clone(), columns: columns.clone() }).await?;
+                if columns.is_empty() {
+                    totals.tables += 1;
+                    continue;
+                }
+                let sql = format!(
+                    "SELECT CAST(JSON_ARRAY({}) AS CHAR) AS r FROM {}",
+                    columns.iter().map(|c| mysql_ident(c)).collect::<Vec<_>>().join(", "),
+                    mysql_ident(table)
+                );
+                let mut rows = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).fetch(&mut *tx);
+                while let Some(row) = rows.try_next().await? {
+                    let mut line = row.try_get::<String, _>("r")?.into_bytes();
+                    line.push(b'\n');
+                    out.write_all(&line).await?;
+                    totals.rows += 1;
+                }
+                totals.tables += 1;
+            }
+            tx.commit().await?;
+        }
+    }
+
+    write_line(&mut out, &Trailer { end: BackupTotals { tables: totals.tables, rows: totals.rows } }).await?;
+    out.flush().await?;
+    info!("Logical backup written: {} tables, {} rows", totals.tables, totals.rows);
+    Ok(totals)
+}
+
+/// Whether `path` holds a logical backup (as opposed to a SQL dump).
+pub async fn is_logical_backup(path: &Path) -> bool {
+    let Ok(file) = tokio::fs::File::open(path).await else { return false };
+    let mut first = String::new();
+    let mut reader = tokio::io::BufReader::new(file);
+    if reader.read_line(&mut first).await.is_err() {
+        return false;
+    }
+    serde_json::from_str::<Header>(&first).is_ok_and(|h| h.format == FORMAT)
+}
+
+// ---- Restore ----
+
+/// Render a JSON array as a Postgres array literal (`{1,2,"a b"}`).
+fn pg_array_literal(items: &[Value]) -> String {
+    let elements: Vec<String> = items
+        .iter()
+        .map(|item| match item {
+            Value::Null => "NULL".to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(n) => n.to_string(),
+            Value::Array(inner) => pg_array_literal(inner),
+            Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Value::Object(_) => format!("\"{}\"", item.to_string().replace('\\', "\\\\").replace('"', "\\\"")),
+        })
+        .collect();
+    format!("{{{}}}", elements.join(","))
+}
+
+/// Timestamps as MySQL accepts them: Postgres writes `2024-05-01T10:00:00.123` (and an offset
+/// for `timestamptz`), which MariaDB rejects.
+fn mysql_datetime(value: &str) -> Option<String> {
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(t.naive_utc().format("%Y-%m-%d %H:%M:%S%.f").to_string());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(t) = chrono::NaiveDateTime::parse_from_str(value, format) {
+            return Some(t.format("%Y-%m-%d %H:%M:%S%.f").to_string());
+        }
+    }
+    Some(value.to_string())
+}
+
+/// Text form of `value` for a column of `sql_type` on the target engine. Every parameter is
+/// bound as text and cast by the database, which handles widening and narrowing for us.
+fn to_param(value: &Value, sql_type: &str, postgres: bool) -> Option<String> {
+    let sql_type = sql_type.to_ascii_lowercase();
+    match value {
+        Value::Null => None,
+        Value::Bool(b) if postgres => Some(b.to_string()),
+        Value::Bool(b) => Some(if *b { "1" } else { "0" }.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if s.starts_with("0000-00-00") => None,
+        Value::String(s) if !postgres && (sql_type == "datetime" || sql_type == "timestamp") => mysql_datetime(s),
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) if postgres && sql_type.ends_with("[]") => Some(pg_array_literal(items)),
+        other => Some(other.to_string()),
+    }
+}
+
+/// One backup table mapped onto a target table.
+struct TargetTable {
+    name: String,
+    /// For each backup column: the target column index, or None if the target dropped it.
+    mapping: Vec<Option<usize>>,
+    columns: Vec<Column>,
+    used: Vec<usize>,
+}
+
+impl TargetTable {
+    fn new(name: String, target_columns: Vec<Column>, backup_columns: &[String]) -> Self {
+        let by_name: HashMap<String, usize> = target_columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.name.to_ascii_lowercase(), i))
+            .collect();
+        let mapping: Vec<Option<usize>> = backup_columns.iter().map(|c| by_name.get(&c.to_ascii_lowercase()).copied()).collect();
+        let used = mapping.iter().flatten().copied().collect();
+        Self { name, mapping, columns: target_columns, used }
+    }
+
+    fn batch_size(&self) -> usize {
+        (MAX_PARAMS_PER_INSERT / self.used.len().max(1)).clamp(1, MAX_ROWS_PER_INSERT)
+    }
+}
+
+fn insert_sql(table: &TargetTable, rows: usize, postgres: bool) -> String {
+    let mut sql = if postgres {
+        format!(
+            "INSERT INTO {} ({}) VALUES ",
+            pg_ident(&table.name),
+            table.used.iter().map(|&i| pg_ident(&table.columns[i].name)).collect::<Vec<_>>().join(", ")
+        )
+    } else {
+        format!(
+            "INSERT INTO {} ({}) VALUES ",
+            mysql_ident(&table.name),
+            table.used.iter().map(|&i| mysql_ident(&table.columns[i].name)).collect::<Vec<_>>().join(", ")
+        )
+    };
+    let mut param = 0;
+    for row in 0..rows {
+        if row > 0 {
+            sql.push_str(", ");
+        }
+        let values: Vec<String> = table
+            .used
+            .iter()
+            .map(|&i| {
+                param += 1;
+                if postgres {
+                    format!("CAST(${} AS {})", param, table.columns[i].sql_type)
+                } else {
+                    "?".to_string()
+                }
+            })
+            .collect();
+        sql.push('(');
+        sql.push_str(&values.join(", "));
+        sql.push(')');
+    }
+    sql
+}
+
+/// Restore progress: backup rows are streamed, so progress is measured in bytes read.
+struct Progress {
+    reporter: Option<Arc<dyn ProgressReporter>>,
+    total_bytes: u64,
+    read_bytes: u64,
+    last_percent: u64,
+}
+
+impl Progress {
+    async fn advance(&mut self, bytes: usize, table: &str) -> AppResult<()> {
+        self.read_bytes += bytes as u64;
+        let percent = (self.read_bytes * 100).checked_div(self.total_bytes).unwrap_or(0).min(99);
+        if percent >= self.last_percent + 5 {
+            self.last_percent = percent;
+            if let Some(reporter) = &self.reporter {
+                reporter.update_progress(percent as f64, Some(format!("Restoring {}", table))).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! restore_into {
+    ($tx:expr, $db_pool:expr, $lines:expr, $progress:expr, $postgres:expr) => {{
+        let mut totals = BackupTotals::default();
+        let mut current: Option<TargetTable> = None;
+        let mut pending: Vec<Vec<Value>> = Vec::new();
+        let mut trailer: Option<BackupTotals> = None;
+        let mut skipped_tables = Vec::new();
+        let target_tables: HashMap<String, String> = list_tables($db_pool)
+            .await?
+            .into_iter()
+            .map(|t| (t.to_ascii_lowercase(), t))
+            .collect();
+
+        while let Some(line) = $lines.next_line().await? {
+            $progress.advance(line.len() + 1, current.as_ref().map_or("", |t| t.name.as_str())).await?;
+            if trailer.is_some() {
+                return Err(AppError::bad_request("Backup has data after its end marker"));
+            }
+            if line.starts_with('[') {
+                let row: Vec<Value> = serde_json::from_str(&line)?;
+                totals.rows += 1;
+                if let Some(table) = &current {
+                    pending.push(row);
+                    if pending.len() >= table.batch_size() {
+                        flush!($tx, table, pending, $postgres);
+                    }
+                }
+                continue;
+            }
+
+            let value: Value = serde_json::from_str(&line)?;
+            if let Some(table) = &current {
+                flush!($tx, table, pending, $postgres);
+            }
+            if let Some(end) = value.get("end") {
+                trailer = Some(serde_json::from_value(end.clone())?);
+                current = None;
+                continue;
+            }
+            let start: TableStart = serde_json::from_value(value)?;
+            totals.tables += 1;
+            current = match target_tables.get(&start.table.to_ascii_lowercase()) {
+                Some(name) if !skipped(name) => {
+                    let table = TargetTable::new(name.clone(), list_columns($db_pool, name).await?, &start.columns);
+                    if table.used.is_empty() {
+                        None
+                    } else {
+                        Some(table)
+                    }
+                }
+                _ => {
+                    skipped_tables.push(start.table);
+                    None
+                }
+            };
+        }
+
+        let Some(trailer) = trailer else {
+            return Err(AppError::bad_request("Backup is truncated (no end marker); nothing was restored"));
+        };
+        if trailer.rows != totals.rows || trailer.tables != totals.tables {
+            return Err(AppError::bad_request(format!(
+                "Backup is incomplete: expected {} tables / {} rows, found {} / {}",
+                trailer.tables, trailer.rows, totals.tables, totals.rows
+            )));
+        }
+        if !skipped_tables.is_empty() {
+            warn!("Logical restore skipped tables this server doesn't have: {}", skipped_tables.join(", "));
+        }
+        totals
+    }};
+}
+
+macro_rules! flush {
+    ($tx:expr, $table:expr, $pending:expr, $postgres:expr) => {{
+        if !$pending.is_empty() {
+            let sql = insert_sql($table, $pending.len(), $postgres);
+            let mut query = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()));
+            for row in $pending.drain(..) {
+                for (value, target) in row.iter().zip(&$table.mapping) {
+                    if let Some(i) = target {
+                        query = query.bind(to_param(value, &$table.columns[*i].sql_type, $postgres));
+                    }
+                }
+            }
+            query.execute(&mut *$tx).await?;
+        }
+    }};
+}
+
+/// Replace all data on this server with the contents of the logical backup at `path`.
+pub async fn restore_from_path(
+    db_pool: &DatabasePool,
+    path: &Path,
+    reporter: Option<Arc<dyn ProgressReporter>>,
+) -> AppResult<BackupTotals> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to open backup file: {}", e)))?;
+    let total_bytes = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+    let mut lines = tokio::io::BufReader::new(file).lines();
+
+    let header: Header = match lines.next_line().await? {
+        Some(line) => serde_json::from_str(&line).map_err(|_| AppError::bad_request("Not a PinePods logical backup"))?,
+        None => return Err(AppError::bad_request("Backup file is empty")),
+    };
+    if header.format != FORMAT || header.version > FORMAT_VERSION {
+        return Err(AppError::bad_request(format!(
+            "Unsupported backup format {} v{} (this server reads {} v{})",
+            header.format, header.version, FORMAT, FORMAT_VERSION
+        )));
+    }
+    let target_version = schema_version(db_pool).await?;
+    // Older backups may carry a gpodder migration number, which says nothing about the core
+    // schema; only core numbers are compared.
+    let backup_version = header.schema_version.as_deref().and_then(|v| core_version([v]));
+    let target_core = target_version.as_deref().and_then(|v| core_version([v]));
+    if let (Some(backup), Some(target)) = (backup_version, target_core) {
+        if backup > target {
+            return Err(AppError::bad_request(format!(
+                "Backup is from schema {:03} but this server is at {:03}; upgrade this server before restoring",
+                backup, target
+            )));
+        }
+    }
+    info!(
+        "Restoring logical backup from {} (schema {:?}) into {} (schema {:?})",
+        header.source_engine,
+        header.schema_version,
+        engine_name(db_pool),
+        target_version
+    );
+
+    let mut progress = Progress { reporter, total_bytes, read_bytes: 0, last_percent: 0 };
+    let clear: Vec<String> = list_tables(db_pool).await?.into_iter().filter(|t| t != "schema_migrations").collect();
+
+    let totals = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let mut tx = pool.begin().await?;
+            // Skip FK triggers during the load; rows arrive in table-name order, not FK order.
+            // Switching the replication role needs a superuser (or, from Postgres 15, a granted
+            // `SET` on it). Without one, fall back to deferring the constraints that allow it.
+            let mut role = sqlx::Connection::begin(&mut *tx).await?;
+            match sqlx::query("SET LOCAL session_replication_role = replica").execute(&mut *role).await {
+                Ok(_) => role.commit().await?,
+                Err(e) => {
+                    role.rollback().await?;
+                    warn!("Cannot disable FK triggers for the restore ({}); deferring constraints instead", e);
+                    sqlx::query("SET CONSTRAINTS ALL DEFERRED").execute(&mut *tx).await?;
+                }
+            }
+            if !clear.is_empty() {
+                let sql = format!(
+                    "TRUNCATE TABLE {} RESTART IDENTITY CASCADE",
+                    clear.iter().map(|t| pg_ident(t)).collect::<Vec<_>>().join(", ")
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).execute(&mut *tx).await?;
+            }
+            let totals = restore_into!(tx, db_pool, lines, progress, true);
+
+            // Explicit ids were inserted, so move every serial sequence past them.
+            let serials = sqlx::query(
+                "SELECT c.table_name::text AS t, c.column_name::text AS c
+                 FROM information_schema.columns c
+                 WHERE c.table_schema = 'public' AND c.column_default LIKE 'nextval(%'",
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            for row in serials {
+                let table: String = row.try_get("t")?;
+                let column: String = row.try_get("c")?;
+                let sql = format!(
+                    "SELECT setval(pg_get_serial_sequence($1, $2), COALESCE(MAX({col}), 1), MAX({col}) IS NOT NULL) FROM {table}",
+                    col = pg_ident(&column),
+                    table = pg_ident(&table)
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(pg_ident(&table))
+                    .bind(&column)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+            totals
+        }
+        DatabasePool::MySQL(pool) => {
+            // FOREIGN_KEY_CHECKS is a session setting and outlives the transaction, so it is
+            // turned back on however the load ends before the connection returns to the pool.
+            let mut conn = pool.acquire().await?;
+            sqlx::query("SET FOREIGN_KEY_CHECKS = 0").execute(&mut *conn).await?;
+            let result: AppResult<BackupTotals> = async {
+                // DELETE (not TRUNCATE, which auto-commits) keeps the clear inside the transaction.
+                let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+                for table in &clear {
+                    let sql = format!("DELETE FROM {}", mysql_ident(table));
+                    sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).execute(&mut *tx).await?;
+                }
+                let totals = restore_into!(tx, db_pool, lines, progress, false);
+                tx.commit().await?;
+                Ok(totals)
+            }
+            .await;
+            if let Err(e) = sqlx::query("SET FOREIGN_KEY_CHECKS = 1").execute(&mut *conn).await {
+                warn!("Could not re-enable foreign key checks after the restore, dropping the connection: {}", e);
+                conn.close_on_drop();
+            }
+            result?
+        }
+    };
+
+    info!("Logical restore completed: {} tables, {} rows", totals.tables, totals.rows);
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn schema_version_ignores_the_gpodder_series() {
+        assert_eq!(core_version(["001", "066", "009", "100", "110"]), Some(66));
+        assert_eq!(core_version(["100", "not-a-number"]), None);
+        assert!(core_version(["070"]) > core_version(["066"]));
+    }
+
+    #[test]
+    fn converts_values_for_the_target_engine() {
+        // MySQL JSON podcast-id lists become Postgres arrays, and back to JSON text.
+        assert_eq!(to_param(&json!([1, 2, 3]), "integer[]", true).as_deref(), Some("{1,2,3}"));
+        assert_eq!(to_param(&json!([1, 2, 3]), "json", false).as_deref(), Some("[1,2,3]"));
+        assert_eq!(pg_array_literal(&[json!("a \"b\""), Value::Null]), r#"{"a \"b\"",NULL}"#);
+
+        // Postgres booleans into TINYINT; MySQL 0/1 is accepted by Postgres' boolean cast.
+        assert_eq!(to_param(&json!(true), "tinyint", false).as_deref(), Some("1"));
+        assert_eq!(to_param(&json!(1), "boolean", true).as_deref(), Some("1"));
+
+        assert_eq!(
+            to_param(&json!("2024-05-01T10:00:00.5+02:00"), "datetime", false).as_deref(),
+            Some("2024-05-01 08:00:00.500")
+        );
+        assert_eq!(
+            to_param(&json!("2024-05-01T10:00:00"), "timestamp", false).as_deref(),
+            Some("2024-05-01 10:00:00")
+        );
+        assert_eq!(to_param(&json!("0000-00-00 00:00:00"), "timestamp without time zone", true), None);
+    }
+
+    #[test]
+    fn maps_backup_columns_case_insensitively() {
+        let target = vec![
+            Column { name: "podcastid".into(), sql_type: "integer".into() },
+            Column { name: "podcastname".into(), sql_type: "text".into() },
+            Column { name: "newcolumn".into(), sql_type: "boolean".into() },
+        ];
+        let table = TargetTable::new(
+            "Podcasts".into(),
+            target,
+            &["PodcastID".into(), "DroppedColumn".into(), "PodcastName".into()],
+        );
+        assert_eq!(table.mapping, vec![Some(0), None, Some(1)]);
+        assert_eq!(
+            insert_sql(&table, 2, true),
+            r#"INSERT INTO "Podcasts" ("podcastid", "podcastname") VALUES (CAST($1 AS integer), CAST($2 AS text)), (CAST($3 AS integer), CAST($4 AS text))"#
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Playlists and channels on any site yt-dlp has an extractor for (Vimeo, SoundCloud, Bandcamp,
+//! Twitch VODs, PeerTube, ...), followed as podcasts with `SourceKind = 'ytdlp'`.
+//!
+//! The podcast's feed URL is the page the user subscribed to. Each refresh lists its newest
+//! entries with one `yt-dlp --flat-playlist` call; entries the listing gives no date for are
+//! looked up individually the first time they're seen. Entries are stored in `YouTubeVideos`
+//! like YouTube uploads, under an ID namespaced by extractor (`vimeo-123456`) so they can't
+//! collide with YouTube video IDs or each other, and their audio goes through the same
+//! [`youtube_source`](super::youtube_source) download and stream path.
+//!
+//! yt-dlp's generic extractor is disabled: it would fetch any page the user names and follow
+//! whatever media URLs it finds there, so only URLs a site extractor claims are accepted.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use tokio::process::Command;
+
+/// `Podcasts.SourceKind` for podcasts refreshed through this module.
+pub const SOURCE_KIND: &str = "ytdlp";
+
+/// How many of the newest entries each listing reads.
+const LISTING_ITEMS: usize = 30;
+
+/// Keeps yt-dlp to site extractors; see the module docs.
+const EXTRACTORS: [&str; 2] = ["--use-extractors", "default,-generic"];
+
+/// One entry of a listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// `<extractor>-<id>`, stored as `YouTubeVideos.YouTubeVideoID` and used as the file name.
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// The entry's own page, which is what yt-dlp downloads.
+    pub url: String,
+    pub thumbnail: String,
+    pub published: Option<DateTime<Utc>>,
+    pub duration: Option<i64>,
+}
+
+/// A playlist or channel and its newest entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Listing {
+    pub name: String,
+    pub description: String,
+    pub thumbnail_url: String,
+    /// yt-dlp's extractor key for the listing, e.g. `VimeoUser` or `BandcampAlbum`.
+    pub extractor: String,
+    pub entries: Vec<Entry>,
+}
+
+/// Whether a URL belongs to YouTube, which has its own native source.
+pub fn is_youtube_url(url: &str) -> bool {
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        .unwrap_or_default();
+    host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com")
+}
+
+/// Check that user input is a public http(s) URL this module should handle.
+pub async fn validate_url(input: &str) -> Result<String, String> {
+    let url = input.trim();
+    if is_youtube_url(url) {
+        return Err("YouTube channels and playlists are subscribed through the YouTube source".to_string());
+    }
+    crate::services::url_guard::ensure_safe_public_url_async(url)
+        .await
+        .map_err(|e| format!("Cannot subscribe to {}: {}", url, e))?;
+    Ok(url.to_string())
+}
+
+/// `<extractor>-<id>` with anything but ASCII alphanumerics, `_` and `-` replaced, so it is
+/// safe as a file name.
+fn namespaced_id(extractor: &str, id: &str) -> String {
+    let raw = format!("{}-{}", extractor.to_ascii_lowercase(), id);
+    raw.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+fn text(value: &serde_json::Value, key: &str) -> String {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+}
+
+/// The best thumbnail yt-dlp reports: `thumbnail`, else the last (largest) of `thumbnails`.
+fn thumbnail(value: &serde_json::Value) -> String {
+    let single = text(value, "thumbnail");
+    if !single.is_empty() {
+        return single;
+    }
+    value
+        .get("thumbnails")
+        .and_then(|t| t.as_array())
+        .and_then(|t| t.iter().rev().find_map(|t| t.get("url")?.as_str()))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// When an entry was published, from `timestamp`, `release_timestamp` or `upload_date`.
+pub fn published(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let timestamp = ["timestamp", "release_timestamp"]
+        .iter()
+        .find_map(|key| value.get(*key)?.as_f64())
+        .and_then(|ts| Utc.timestamp_opt(ts as i64, 0).single());
+    timestamp.or_else(|| {
+        let date = value.get("upload_date")?.as_str()?;
+        let day = NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+        Some(Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0)?))
+    })
+}
+
+fn entry(value: &serde_json::Value, extractor: &str) -> Option<Entry> {
+    let id = value.get("id")?.as_str().filter(|id| !id.is_empty())?;
+    let url = [text(value, "webpage_url"), text(value, "url")]
+        .into_iter()
+        .find(|u| u.starts_with("http://") || u.starts_with("https://"))?;
+    let ie_key = value.get("ie_key").and_then(|k| k.as_str()).unwrap_or(extractor);
+    Some(Entry {
+        id: namespaced_id(ie_key, id),
+        title: text(value, "title"),
+        description: text(value, "description"),
+        url,
+        thumbnail: thumbnail(value),
+        published: published(value),
+        duration: value.get("duration").and_then(|d| d.as_f64()).map(|d| d.round() as i64),
+    })
+}
+
+/// Map `yt-dlp --flat-playlist --dump-single-json` output onto a [`Listing`]. A URL that yt-dlp
+/// reads as a single video rather than a playlist or channel is an error.
+pub fn parse_listing(listing: &serde_json::Value) -> Result<Listing, String> {
+    if listing.get("_type").and_then(|t| t.as_str()) != Some("playlist") {
+        return Err("This URL is a single item, not a playlist or channel".to_string());
+    }
+    let extractor = text(listing, "extractor_key");
+    let entries = listing
+        .get("entries")
+        .and_then(|e| e.as_array())
+        .map(|entries| entries.iter().filter_map(|e| entry(e, &extractor)).collect())
+        .unwrap_or_default();
+    let name = [text(listing, "title"), text(listing, "channel"), text(listing, "uploader")]
+        .into_iter()
+        .find(|n| !n.is_empty())
+        .unwrap_or_default();
+    Ok(Listing {
+        name,
+        description: text(listing, "description").chars().take(500).collect(),
+        thumbnail_url: thumbnail(listing),
+        extractor,
+        entries,
+    })
+}
+
+/// List a playlist or channel's newest entries.
+pub async fn fetch_listing(url: &str) -> Result<Listing, String> {
+    let items = format!("1-{}", LISTING_ITEMS);
+    let output = Command::new("yt-dlp")
+        .args(EXTRACTORS)
+        .args(["--flat-playlist", "--dump-single-json", "--playlist-items", &items, "--socket-timeout", "30"])
+        .arg(url)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("yt-dlp listing failed: {}", stderr.lines().last().unwrap_or("")));
+    }
+    let listing: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Unreadable yt-dlp listing: {}", e))?;
+    parse_listing(&listing)
+}
+
+/// Fill in an entry's date, description and duration from its own page, for listings whose
+/// flat entries leave them out.
+pub async fn fetch_details(entry: &mut Entry) -> Result<(), String> {
+    let output = Command::new("yt-dlp")
+        .args(EXTRACTORS)
+        .args(["--dump-json", "--skip-download", "--no-playlist", "--socket-timeout", "30"])
+        .arg(&entry.url)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("yt-dlp lookup failed: {}", stderr.lines().last().unwrap_or("")));
+    }
+    let details: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Unreadable yt-dlp output: {}", e))?;
+    entry.published = entry.published.or_else(|| published(&details));
+    if entry.description.is_empty() {
+        entry.description = text(&details, "description");
+    }
+    if entry.thumbnail.is_empty() {
+        entry.thumbnail = thumbnail(&details);
+    }
+    if entry.duration.is_none() {
+        entry.duration = details.get("duration").and_then(|d| d.as_f64()).map(|d| d.round() as i64);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_flat_listing() {
+        let listing = serde_json::json!({
+            "_type": "playlist", "extractor_key": "VimeoUser", "title": "Studio Talks",
+            "thumbnails": [{"url": "https://i.vimeocdn.com/small"}, {"url": "https://i.vimeocdn.com/large"}],
+            "entries": [
+                {"_type": "url", "ie_key": "Vimeo", "id": "76979871", "url": "https://vimeo.com/76979871",
+                 "title": "Part 1", "duration": 62.4, "timestamp": 1714557600},
+                {"_type": "url", "id": "5", "url": "https://vimeo.com/5", "upload_date": "20240502"},
+                {"_type": "url", "id": "6", "url": "/relative/6"}
+            ]
+        });
+        let listing = parse_listing(&listing).unwrap();
+        assert_eq!(listing.name, "Studio Talks");
+        assert_eq!(listing.thumbnail_url, "https://i.vimeocdn.com/large");
+        assert_eq!(listing.entries.len(), 2);
+        assert_eq!(listing.entries[0].id, "vimeo-76979871");
+        assert_eq!(listing.entries[0].duration, Some(62));
+        assert_eq!(listing.entries[0].published.unwrap().to_rfc3339(), "2024-05-01T10:00:00+00:00");
+        assert_eq!(listing.entries[1].id, "vimeouser-5");
+        assert_eq!(listing.entries[1].published.unwrap().to_rfc3339(), "2024-05-02T00:00:00+00:00");
+    }
+
+    #[test]
+    fn rejects_single_items_and_youtube() {
+        let single = serde_json::json!({"_type": "video", "id": "1", "title": "One"});
+        assert!(parse_listing(&single).is_err());
+        assert!(is_youtube_url("https://www.youtube.com/@somehandle"));
+        assert!(is_youtube_url("https://youtu.be/dQw4w9WgXcQ"));
+        assert!(!is_youtube_url("https://soundcloud.com/artist/sets/album"));
+        assert_eq!(namespaced_id("PeerTube", "a/b:c"), "peertube-a_b_c");
+    }
+}
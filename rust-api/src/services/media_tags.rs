@@ -0,0 +1,242 @@
+//! Tags and embedded chapters from local audio files.
+//!
+//! ID3 (MP3, and the occasional ID3-tagged WAV/AAC) is read with the `id3` crate, including
+//! CHAP/CTOC chapter frames. Everything else — MP4/M4A `ilst` atoms, Vorbis comments in
+//! Ogg/Opus/FLAC, MP4 chapter tracks and Nero chapters, embedded cover art — comes from ffprobe,
+//! which every PinePods image ships for transcoding. Without ffprobe those files fall back to
+//! the filename and modification time as before.
+
+use chrono::NaiveDateTime;
+use id3::TagLike;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// One embedded chapter, in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Chapter {
+    pub start: f64,
+    pub end: Option<f64>,
+    pub title: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MediaTags {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub date: Option<NaiveDateTime>,
+    pub track: Option<u32>,
+    pub disc: Option<u32>,
+    pub duration: Option<i32>,
+    pub chapters: Vec<Chapter>,
+    pub has_artwork: bool,
+}
+
+/// Chapters in the Podcasting 2.0 JSON chapters shape, which is what the episode chapters
+/// endpoint serves.
+pub fn chapters_json(chapters: &[Chapter]) -> serde_json::Value {
+    serde_json::Value::Array(
+        chapters
+            .iter()
+            .map(|c| {
+                let mut chapter = serde_json::json!({ "startTime": c.start, "title": c.title });
+                if let Some(end) = c.end {
+                    chapter["endTime"] = serde_json::json!(end);
+                }
+                chapter
+            })
+            .collect(),
+    )
+}
+
+/// Chapters from ID3 CHAP frames, in table-of-contents order when the file has one.
+pub fn id3_chapters(tag: &id3::Tag) -> Vec<Chapter> {
+    let by_id: std::collections::HashMap<&str, &id3::frame::Chapter> =
+        tag.chapters().map(|c| (c.element_id.as_str(), c)).collect();
+    let ordered: Vec<&id3::frame::Chapter> = match tag.tables_of_contents().find(|t| t.top_level) {
+        Some(toc) => toc.elements.iter().filter_map(|id| by_id.get(id.as_str()).copied()).collect(),
+        None => {
+            let mut chapters: Vec<_> = tag.chapters().collect();
+            chapters.sort_by_key(|c| c.start_time);
+            chapters
+        }
+    };
+    ordered
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| Chapter {
+            start: c.start_time as f64 / 1000.0,
+            end: (c.end_time > c.start_time).then(|| c.end_time as f64 / 1000.0),
+            title: c.title().map(str::to_string).unwrap_or_else(|| format!("Chapter {}", i + 1)),
+        })
+        .collect()
+}
+
+fn tag_value<'a>(tags: &'a serde_json::Map<String, serde_json::Value>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|key| {
+        tags.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .and_then(|(_, v)| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    })
+}
+
+/// Dates as taggers write them: `2024-05-01`, `2024-05-01T10:00:00Z`, or a bare year.
+fn parse_tag_date(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(t.naive_utc());
+    }
+    let date = value.get(..10).unwrap_or(value);
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return d.and_hms_opt(0, 0, 0);
+    }
+    let year: i32 = value.get(..4)?.parse().ok()?;
+    chrono::NaiveDate::from_ymd_opt(year, 1, 1)?.and_hms_opt(0, 0, 0)
+}
+
+/// Map `ffprobe -show_format -show_chapters -show_streams` JSON onto [`MediaTags`].
+pub fn parse_ffprobe(probe: &serde_json::Value) -> MediaTags {
+    let empty = serde_json::Map::new();
+    let format = probe.get("format");
+    let tags = format.and_then(|f| f.get("tags")).and_then(|t| t.as_object()).unwrap_or(&empty);
+
+    let chapters = probe
+        .get("chapters")
+        .and_then(|c| c.as_array())
+        .map(|chapters| {
+            chapters
+                .iter()
+                .enumerate()
+                .filter_map(|(i, c)| {
+                    let start: f64 = c.get("start_time")?.as_str()?.parse().ok()?;
+                    let end = c.get("end_time").and_then(|e| e.as_str()).and_then(|e| e.parse().ok());
+                    let title = c
+                        .get("tags")
+                        .and_then(|t| t.as_object())
+                        .and_then(|t| tag_value(t, &["title"]))
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("Chapter {}", i + 1));
+                    Some(Chapter { start, end, title })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let has_artwork = probe
+        .get("streams")
+        .and_then(|s| s.as_array())
+        .is_some_and(|streams| {
+            streams.iter().any(|s| {
+                s.get("disposition").and_then(|d| d.get("attached_pic")).and_then(|v| v.as_i64()) == Some(1)
+            })
+        });
+
+    MediaTags {
+        title: tag_value(tags, &["title"]).map(str::to_string),
+        description: tag_value(tags, &["description", "synopsis", "comment"]).map(str::to_string),
+        artist: tag_value(tags, &["artist", "album_artist"]).map(str::to_string),
+        album: tag_value(tags, &["album"]).map(str::to_string),
+        date: tag_value(tags, &["date", "creation_time", "year"]).and_then(parse_tag_date),
+        // "3/12" in both ID3-style and Vorbis TRACKNUMBER tags.
+        track: tag_value(tags, &["track", "tracknumber"]).and_then(|t| t.split('/').next()?.trim().parse().ok()),
+        disc: tag_value(tags, &["disc", "discnumber"]).and_then(|t| t.split('/').next()?.trim().parse().ok()),
+        duration: format
+            .and_then(|f| f.get("duration"))
+            .and_then(|d| d.as_str())
+            .and_then(|d| d.parse::<f64>().ok())
+            .map(|d| d.round() as i32),
+        chapters,
+        has_artwork,
+    }
+}
+
+/// Probe a file with ffprobe. None if ffprobe is missing or can't read the file.
+pub fn probe(path: &Path) -> Option<MediaTags> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_chapters", "-show_streams"])
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    Some(parse_ffprobe(&parsed))
+}
+
+/// The embedded cover image (MP4 `covr`, FLAC/Vorbis picture blocks) as encoded bytes.
+pub fn extract_artwork(path: &Path) -> Option<Vec<u8>> {
+    let output = Command::new("ffmpeg")
+        .args(["-v", "quiet", "-i"])
+        .arg(path)
+        .args(["-an", "-map", "0:v:0", "-c", "copy", "-frames:v", "1", "-f", "image2pipe", "-"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    (!output.stdout.is_empty()).then_some(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_mp4_and_vorbis_tags_and_chapters() {
+        let probe = serde_json::json!({
+            "streams": [
+                {"codec_type": "audio", "disposition": {"attached_pic": 0}},
+                {"codec_type": "video", "disposition": {"attached_pic": 1}}
+            ],
+            "chapters": [
+                {"start_time": "0.000000", "end_time": "61.500000", "tags": {"title": "Intro"}},
+                {"start_time": "61.500000", "end_time": "1800.000000", "tags": {}}
+            ],
+            "format": {
+                "duration": "1800.480000",
+                "tags": {"TITLE": "Episode 3", "ARTIST": "Host", "DATE": "2023", "TRACKNUMBER": "3/12", "DISCNUMBER": "2", "comment": "Notes"}
+            }
+        });
+        let tags = parse_ffprobe(&probe);
+        assert_eq!(tags.title.as_deref(), Some("Episode 3"));
+        assert_eq!(tags.description.as_deref(), Some("Notes"));
+        assert_eq!(tags.track, Some(3));
+        assert_eq!(tags.disc, Some(2));
+        assert_eq!(tags.duration, Some(1800));
+        assert_eq!(tags.date.map(|d| d.to_string()).as_deref(), Some("2023-01-01 00:00:00"));
+        assert!(tags.has_artwork);
+        assert_eq!(tags.chapters[1], Chapter { start: 61.5, end: Some(1800.0), title: "Chapter 2".into() });
+        assert_eq!(
+            chapters_json(&tags.chapters)[0],
+            serde_json::json!({"startTime": 0.0, "endTime": 61.5, "title": "Intro"})
+        );
+    }
+
+    #[test]
+    fn orders_id3_chapters_by_table_of_contents() {
+        let mut tag = id3::Tag::new();
+        for (id, start, end, title) in [("b", 60_000, 120_000, "Second"), ("a", 0, 60_000, "First")] {
+            let mut frames = id3::Tag::new();
+            frames.set_title(title);
+            tag.add_frame(id3::frame::Chapter {
+                element_id: id.into(),
+                start_time: start,
+                end_time: end,
+                start_offset: u32::MAX,
+                end_offset: u32::MAX,
+                frames: frames.frames().cloned().collect(),
+            });
+        }
+        assert_eq!(id3_chapters(&tag).iter().map(|c| c.title.as_str()).collect::<Vec<_>>(), ["First", "Second"]);
+
+        tag.add_frame(id3::frame::TableOfContents {
+            element_id: "toc".into(),
+            top_level: true,
+            ordered: true,
+            elements: vec!["b".into(), "a".into()],
+            frames: Vec::new(),
+        });
+        let chapters = id3_chapters(&tag);
+        assert_eq!(chapters[0], Chapter { start: 60.0, end: Some(120.0), title: "Second".into() });
+    }
+}
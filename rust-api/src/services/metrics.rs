@@ -0,0 +1,219 @@
+//! Prometheus metrics for the API server, served as text at `GET /api/metrics`.
+//!
+//! Counters and histograms live in a process-wide registry that the hot paths update (the HTTP
+//! middleware, feed refresh, episode downloads). Values that are cheap to read on demand — DB pool
+//! usage, Redis latency, task queue depth and AI sidecar availability — are sampled per scrape
+//! into a [`Snapshot`] instead. The exposition format is small enough to render by hand.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Request latency buckets, in seconds.
+const HTTP_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+/// Feed fetch + parse + apply buckets, in seconds. Large feeds with many subscribers take a while.
+const REFRESH_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Route label used for requests that matched no route, so probes for random paths can't create
+/// unbounded label values.
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self { bounds, counts: vec![0; bounds.len()], sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(self.counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Write the `_bucket`/`_sum`/`_count` series. `labels` is an already-rendered label list
+    /// without braces (may be empty).
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(out, "{}_bucket{{{}{}le=\"{}\"}} {}", name, labels, sep, bound, count);
+        }
+        let _ = writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", name, labels, sep, self.count);
+        let braces = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        let _ = writeln!(out, "{}_sum{} {}", name, braces, self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, braces, self.count);
+    }
+}
+
+struct Registry {
+    /// Keyed by (method, route template, status code).
+    http: Mutex<BTreeMap<(String, String, u16), Histogram>>,
+    refresh_success: AtomicU64,
+    refresh_failure: AtomicU64,
+    refresh_duration: Mutex<Histogram>,
+    download_bytes: AtomicU64,
+}
+
+fn registry() -> &'static Registry {
+    static R: OnceLock<Registry> = OnceLock::new();
+    R.get_or_init(|| Registry {
+        http: Mutex::new(BTreeMap::new()),
+        refresh_success: AtomicU64::new(0),
+        refresh_failure: AtomicU64::new(0),
+        refresh_duration: Mutex::new(Histogram::new(REFRESH_BUCKETS)),
+        download_bytes: AtomicU64::new(0),
+    })
+}
+
+/// Record one completed HTTP request. `route` is the matched route template, not the raw path.
+pub fn observe_http_request(method: &str, route: &str, status: u16, seconds: f64) {
+    if let Ok(mut http) = registry().http.lock() {
+        http.entry((method.to_string(), route.to_string(), status))
+            .or_insert_with(|| Histogram::new(HTTP_BUCKETS))
+            .observe(seconds);
+    }
+}
+
+/// Count refresh outcomes, one per podcast row (a shared feed counts once per subscriber).
+pub fn record_feed_refresh(success: bool, podcasts: usize) {
+    let counter = if success { &registry().refresh_success } else { &registry().refresh_failure };
+    counter.fetch_add(podcasts as u64, Ordering::Relaxed);
+}
+
+/// Record how long one feed took to fetch, parse and apply to all of its subscribers.
+pub fn observe_feed_refresh_duration(seconds: f64) {
+    if let Ok(mut h) = registry().refresh_duration.lock() {
+        h.observe(seconds);
+    }
+}
+
+/// Add bytes written by an episode download.
+pub fn add_download_bytes(bytes: u64) {
+    registry().download_bytes.fetch_add(bytes, Ordering::Relaxed);
+}
+
+/// Connection pool usage for the configured database.
+pub struct PoolStats {
+    pub backend: &'static str,
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
+/// Values sampled at scrape time by the metrics handler.
+pub struct Snapshot {
+    pub pool: PoolStats,
+    pub redis_up: bool,
+    /// Round-trip of a Redis `PING`, when it succeeded.
+    pub redis_latency_seconds: Option<f64>,
+    /// Task counts by status (`pending`, `running`, ...).
+    pub tasks: BTreeMap<String, u64>,
+    pub ai_configured: bool,
+    pub ai_available: bool,
+}
+
+/// Escape a label value per the exposition format.
+fn label_value(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Render every metric in the Prometheus text exposition format (version 0.0.4).
+pub fn render(snapshot: &Snapshot) -> String {
+    let r = registry();
+    let mut out = String::new();
+
+    family(&mut out, "pinepods_http_request_duration_seconds", "histogram", "HTTP request latency by route and status.");
+    if let Ok(http) = r.http.lock() {
+        for ((method, route, status), h) in http.iter() {
+            let labels = format!(
+                "method=\"{}\",route=\"{}\",status=\"{}\"",
+                label_value(method),
+                label_value(route),
+                status
+            );
+            h.render(&mut out, "pinepods_http_request_duration_seconds", &labels);
+        }
+    }
+
+    family(&mut out, "pinepods_feed_refreshes_total", "counter", "Podcast refresh outcomes, counted per subscriber podcast.");
+    let _ = writeln!(out, "pinepods_feed_refreshes_total{{result=\"success\"}} {}", r.refresh_success.load(Ordering::Relaxed));
+    let _ = writeln!(out, "pinepods_feed_refreshes_total{{result=\"failure\"}} {}", r.refresh_failure.load(Ordering::Relaxed));
+
+    family(&mut out, "pinepods_feed_refresh_duration_seconds", "histogram", "Time to fetch, parse and apply one feed for all of its subscribers.");
+    if let Ok(h) = r.refresh_duration.lock() {
+        h.render(&mut out, "pinepods_feed_refresh_duration_seconds", "");
+    }
+
+    family(&mut out, "pinepods_download_bytes_total", "counter", "Bytes written by episode downloads.");
+    let _ = writeln!(out, "pinepods_download_bytes_total {}", r.download_bytes.load(Ordering::Relaxed));
+
+    let backend = format!("backend=\"{}\"", snapshot.pool.backend);
+    family(&mut out, "pinepods_db_pool_connections", "gauge", "Database pool connections by state.");
+    let _ = writeln!(out, "pinepods_db_pool_connections{{{},state=\"idle\"}} {}", backend, snapshot.pool.idle);
+    let _ = writeln!(
+        out,
+        "pinepods_db_pool_connections{{{},state=\"in_use\"}} {}",
+        backend,
+        snapshot.pool.size.saturating_sub(snapshot.pool.idle)
+    );
+    family(&mut out, "pinepods_db_pool_max_connections", "gauge", "Configured maximum database pool size.");
+    let _ = writeln!(out, "pinepods_db_pool_max_connections{{{}}} {}", backend, snapshot.pool.max);
+
+    family(&mut out, "pinepods_redis_up", "gauge", "Whether Redis answered PING.");
+    let _ = writeln!(out, "pinepods_redis_up {}", snapshot.redis_up as u8);
+    if let Some(latency) = snapshot.redis_latency_seconds {
+        family(&mut out, "pinepods_redis_ping_seconds", "gauge", "Round-trip time of a Redis PING during this scrape.");
+        let _ = writeln!(out, "pinepods_redis_ping_seconds {}", latency);
+    }
+
+    family(&mut out, "pinepods_tasks", "gauge", "Background tasks in the task manager by status.");
+    for (status, count) in &snapshot.tasks {
+        let _ = writeln!(out, "pinepods_tasks{{status=\"{}\"}} {}", label_value(status), count);
+    }
+
+    family(&mut out, "pinepods_ai_sidecar_configured", "gauge", "Whether PINEPODS_AI_URL is set.");
+    let _ = writeln!(out, "pinepods_ai_sidecar_configured {}", snapshot.ai_configured as u8);
+    family(&mut out, "pinepods_ai_sidecar_available", "gauge", "Whether the AI sidecar passed its last health check.");
+    let _ = writeln!(out, "pinepods_ai_sidecar_available {}", snapshot.ai_available as u8);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut h = Histogram::new(&[0.1, 1.0]);
+        h.observe(0.05);
+        h.observe(0.5);
+        h.observe(3.0);
+        let mut out = String::new();
+        h.render(&mut out, "x", "route=\"/a\"");
+        assert!(out.contains("x_bucket{route=\"/a\",le=\"0.1\"} 1\n"));
+        assert!(out.contains("x_bucket{route=\"/a\",le=\"1\"} 2\n"));
+        assert!(out.contains("x_bucket{route=\"/a\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("x_count{route=\"/a\"} 3\n"));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
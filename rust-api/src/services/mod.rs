@@ -1,14 +1,43 @@
 pub mod ad_detection;
 pub mod ai_client;
 pub mod ai_settings;
+pub mod api_scopes;
+pub mod app_import;
+pub mod audio_cut;
 pub mod audio_processing;
 pub mod auth;
+pub mod cluster;
 pub mod download_metadata;
+pub mod gpodder_server;
+pub mod gpodder_sync;
+pub mod job_queue;
+pub mod local_books;
+pub mod local_media_watch;
+pub mod logical_backup;
+pub mod media_source;
+pub mod media_tags;
+pub mod metrics;
+pub mod notification_channels;
+pub mod outbox;
+pub mod playback_sync;
+pub mod podcast_namespace;
 pub mod recommendations;
 pub mod scheduler;
+pub mod scrobble;
+pub mod search;
+pub mod shared_feeds;
+pub mod sponsorblock;
+pub mod storage;
 pub mod task_manager;
 pub mod tasks;
+#[cfg(test)]
+pub mod test_server;
+pub mod transcode;
 pub mod transcription;
 pub mod url_guard;
+pub mod user_export;
+pub mod webhooks;
+pub mod websub;
+pub mod youtube_source;
 
 // Common service utilities and shared functionality
\ No newline at end of file
@@ -0,0 +1,1357 @@
+//! New-episode notification channels.
+//!
+//! Each user can have any number of channels (ntfy, Gotify, a generic HTTP endpoint, Matrix,
+//! Discord and Slack webhooks, a Telegram bot, Pushover, or email through the server's SMTP
+//! settings). A channel's settings are stored as encrypted JSON ([`ChannelConfig`]), and its title
+//! and message are templates rendered per episode ([`render`]). A podcast with notifications
+//! switched on notifies every enabled channel of its owner, unless it has been routed to a
+//! subset of them ([`set_podcast_channels`]).
+//!
+//! The per-platform `/user/notification_settings` endpoints predate channels; they are kept as a
+//! view over each user's first channel of the ntfy, Gotify and HTTP platforms.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::scrobble::http_client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Row;
+use tracing::{debug, warn};
+
+pub const DEFAULT_TITLE_TEMPLATE: &str = "New episode of {podcast}";
+pub const DEFAULT_MESSAGE_TEMPLATE: &str = "{title}\n{duration}";
+
+/// Placeholders a title or message template can use.
+pub const TEMPLATE_VARIABLES: [&str; 9] = [
+    "podcast",
+    "title",
+    "description",
+    "duration",
+    "pub_date",
+    "artwork",
+    "stream_url",
+    "link",
+    "episode_id",
+];
+
+/// Telegram rejects photo captions longer than this.
+const TELEGRAM_CAPTION_LIMIT: usize = 1024;
+/// Descriptions are cut to this many characters for `{description}`.
+const DESCRIPTION_LIMIT: usize = 300;
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+const PUSHOVER_API: &str = "https://api.pushover.net/1/messages.json";
+
+/// Where a channel delivers and how to authenticate. Serialized with a `platform` tag; secret
+/// fields are blanked when channels are listed, and a blank secret on update keeps the stored one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "platform", rename_all = "lowercase")]
+pub enum ChannelConfig {
+    Ntfy {
+        /// Defaults to https://ntfy.sh.
+        server_url: Option<String>,
+        topic: String,
+        username: Option<String>,
+        password: Option<String>,
+        access_token: Option<String>,
+    },
+    Gotify {
+        url: String,
+        token: String,
+    },
+    /// A JSON POST (or a GET with `?message=`) to any URL, with an optional bearer token.
+    Http {
+        url: String,
+        token: Option<String>,
+        /// `POST` (default) or `GET`.
+        method: Option<String>,
+    },
+    Matrix {
+        /// e.g. https://matrix.org
+        homeserver: String,
+        room_id: String,
+        access_token: String,
+    },
+    Discord {
+        webhook_url: String,
+    },
+    Slack {
+        webhook_url: String,
+    },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+    },
+    Pushover {
+        user_key: String,
+        app_token: String,
+        device: Option<String>,
+    },
+    /// Sent with the server's email settings; defaults to the user's own address.
+    Email {
+        to_email: Option<String>,
+    },
+}
+
+impl ChannelConfig {
+    pub const PLATFORMS: [&'static str; 9] =
+        ["ntfy", "gotify", "http", "matrix", "discord", "slack", "telegram", "pushover", "email"];
+
+    pub fn platform(&self) -> &'static str {
+        match self {
+            ChannelConfig::Ntfy { .. } => "ntfy",
+            ChannelConfig::Gotify { .. } => "gotify",
+            ChannelConfig::Http { .. } => "http",
+            ChannelConfig::Matrix { .. } => "matrix",
+            ChannelConfig::Discord { .. } => "discord",
+            ChannelConfig::Slack { .. } => "slack",
+            ChannelConfig::Telegram { .. } => "telegram",
+            ChannelConfig::Pushover { .. } => "pushover",
+            ChannelConfig::Email { .. } => "email",
+        }
+    }
+
+    /// User-supplied URLs the server will call, for the SSRF guard.
+    pub fn urls(&self) -> Vec<&str> {
+        match self {
+            ChannelConfig::Ntfy { server_url, .. } => server_url.as_deref().into_iter().collect(),
+            ChannelConfig::Gotify { url, .. } | ChannelConfig::Http { url, .. } => vec![url],
+            ChannelConfig::Matrix { homeserver, .. } => vec![homeserver],
+            ChannelConfig::Discord { webhook_url } | ChannelConfig::Slack { webhook_url } => vec![webhook_url],
+            ChannelConfig::Telegram { .. } | ChannelConfig::Pushover { .. } | ChannelConfig::Email { .. } => Vec::new(),
+        }
+    }
+
+    /// Check the fields a platform can't work without.
+    pub fn validate(&self) -> Result<(), String> {
+        let required: Vec<(&str, &str)> = match self {
+            ChannelConfig::Ntfy { topic, .. } => vec![("topic", topic)],
+            ChannelConfig::Gotify { url, token } => vec![("url", url), ("token", token)],
+            ChannelConfig::Http { url, .. } => vec![("url", url)],
+            ChannelConfig::Matrix { homeserver, room_id, access_token } => {
+                vec![("homeserver", homeserver), ("room_id", room_id), ("access_token", access_token)]
+            }
+            ChannelConfig::Discord { webhook_url } | ChannelConfig::Slack { webhook_url } => {
+                vec![("webhook_url", webhook_url)]
+            }
+            ChannelConfig::Telegram { bot_token, chat_id } => vec![("bot_token", bot_token), ("chat_id", chat_id)],
+            ChannelConfig::Pushover { user_key, app_token, .. } => vec![("user_key", user_key), ("app_token", app_token)],
+            ChannelConfig::Email { .. } => Vec::new(),
+        };
+        match required.into_iter().find(|(_, value)| value.trim().is_empty()) {
+            Some((field, _)) => Err(format!("{} channels need a {}", self.platform(), field)),
+            None => Ok(()),
+        }
+    }
+
+    /// A copy safe to show: tokens, passwords and webhook URLs are blanked.
+    pub fn redacted(&self) -> ChannelConfig {
+        let mut config = self.clone();
+        for secret in config.secrets_mut() {
+            secret.clear();
+        }
+        config
+    }
+
+    /// Fill blank secrets from the stored config, so an edit needn't resend them.
+    /// Secrets are matched by field, and a missing optional secret counts as blank.
+    pub fn keep_secrets_from(&mut self, stored: &ChannelConfig) {
+        fn keep(secret: &mut String, old: &str) {
+            if secret.trim().is_empty() {
+                *secret = old.to_string();
+            }
+        }
+        fn keep_opt(secret: &mut Option<String>, old: &Option<String>) {
+            if secret.as_deref().is_none_or(|value| value.trim().is_empty()) {
+                *secret = old.clone();
+            }
+        }
+        match (self, stored) {
+            (
+                ChannelConfig::Ntfy { password, access_token, .. },
+                ChannelConfig::Ntfy { password: old_password, access_token: old_token, .. },
+            ) => {
+                keep_opt(password, old_password);
+                keep_opt(access_token, old_token);
+            }
+            (ChannelConfig::Gotify { token, .. }, ChannelConfig::Gotify { token: old, .. })
+            | (ChannelConfig::Matrix { access_token: token, .. }, ChannelConfig::Matrix { access_token: old, .. })
+            | (ChannelConfig::Discord { webhook_url: token }, ChannelConfig::Discord { webhook_url: old })
+            | (ChannelConfig::Slack { webhook_url: token }, ChannelConfig::Slack { webhook_url: old })
+            | (ChannelConfig::Telegram { bot_token: token, .. }, ChannelConfig::Telegram { bot_token: old, .. }) => {
+                keep(token, old)
+            }
+            (ChannelConfig::Http { token, .. }, ChannelConfig::Http { token: old, .. }) => keep_opt(token, old),
+            (
+                ChannelConfig::Pushover { user_key, app_token, .. },
+                ChannelConfig::Pushover { user_key: old_key, app_token: old_token, .. },
+            ) => {
+                keep(user_key, old_key);
+                keep(app_token, old_token);
+            }
+            _ => {}
+        }
+    }
+
+    fn secrets_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            ChannelConfig::Ntfy { password, access_token, .. } => {
+                password.iter_mut().chain(access_token.iter_mut()).collect()
+            }
+            ChannelConfig::Gotify { token, .. } => vec![token],
+            ChannelConfig::Http { token, .. } => token.iter_mut().collect(),
+            ChannelConfig::Matrix { access_token, .. } => vec![access_token],
+            ChannelConfig::Discord { webhook_url } | ChannelConfig::Slack { webhook_url } => vec![webhook_url],
+            ChannelConfig::Telegram { bot_token, .. } => vec![bot_token],
+            ChannelConfig::Pushover { user_key, app_token, .. } => vec![user_key, app_token],
+            ChannelConfig::Email { .. } => Vec::new(),
+        }
+    }
+}
+
+/// A channel as shown to its owner.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct NotificationChannel {
+    pub channel_id: i32,
+    pub name: String,
+    pub platform: String,
+    pub enabled: bool,
+    /// Settings with secrets blanked.
+    pub config: ChannelConfig,
+    /// None when the default template is used.
+    pub title_template: Option<String>,
+    pub message_template: Option<String>,
+    pub last_sent_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// The episode a notification is about; fills the template placeholders.
+#[derive(Debug, Clone, Default)]
+pub struct EpisodeNotice {
+    pub podcast_name: String,
+    pub episode_id: i32,
+    pub episode_title: String,
+    pub description: String,
+    pub duration_seconds: Option<i32>,
+    pub pub_date: Option<String>,
+    pub artwork_url: Option<String>,
+    /// The episode's audio/video URL.
+    pub stream_url: Option<String>,
+    /// The episode page in the web app, when SERVER_URL is configured.
+    pub link: Option<String>,
+}
+
+impl EpisodeNotice {
+    fn variable(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "podcast" => self.podcast_name.clone(),
+            "title" => self.episode_title.clone(),
+            "description" => self.description.clone(),
+            "duration" => self.duration_seconds.filter(|d| *d > 0).map(format_duration).unwrap_or_default(),
+            "pub_date" => self.pub_date.clone().unwrap_or_default(),
+            "artwork" => self.artwork_url.clone().unwrap_or_default(),
+            "stream_url" => self.stream_url.clone().unwrap_or_default(),
+            "link" => self.link.clone().unwrap_or_default(),
+            "episode_id" => self.episode_id.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Where "open" on a notification should go: the web app if we know its URL, else the audio.
+    fn open_url(&self) -> Option<&str> {
+        self.link.as_deref().or(self.stream_url.as_deref()).filter(|u| !u.is_empty())
+    }
+}
+
+/// `1:02:03` or `42:05`.
+fn format_duration(seconds: i32) -> String {
+    let (h, m, s) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// Replace `{name}` placeholders with the episode's values. Unknown placeholders are left as
+/// they are, and lines that end up empty are dropped.
+pub fn render(template: &str, notice: &EpisodeNotice) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}').and_then(|close| notice.variable(&after[..close]).map(|v| (close, v))) {
+            Some((close, value)) => {
+                out.push_str(&value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.lines().map(str::trim_end).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n")
+}
+
+/// Strip tags and collapse whitespace in a feed description, and cut it to a notification's size.
+fn plain_summary(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate(&text, DESCRIPTION_LIMIT)
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(limit.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The body of a notification email. Kept apart from the server's other emails, whose sender
+/// picks a template from the subject; a notification's subject comes from the feed.
+fn email_content(subject: &str, message_html: &str) -> String {
+    format!(
+        r#"<h2>{}</h2>
+<div style="background-color: #f8f9fa; padding: 16px; border-radius: 6px; border-left: 4px solid #539e8a;">
+{}
+</div>"#,
+        escape_html(subject),
+        message_html.replace('\n', "<br>")
+    )
+}
+
+fn format_time(time: Option<chrono::NaiveDateTime>) -> Option<String> {
+    time.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+/// The web app's episode page, if the public server URL is configured.
+fn episode_link(episode_id: i32) -> Option<String> {
+    std::env::var("SERVER_URL")
+        .ok()
+        .map(|base| base.trim().trim_end_matches('/').to_string())
+        .filter(|base| !base.is_empty())
+        .map(|base| format!("{}/episode?episode_id={}", base, episode_id))
+}
+
+/// A rendered notification ready to send.
+struct Rendered<'a> {
+    title: String,
+    message: String,
+    notice: &'a EpisodeNotice,
+}
+
+// ---- Sending ----
+
+async fn check(response: Result<reqwest::Response, reqwest::Error>) -> Result<(), String> {
+    let response = response.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(format!("HTTP {}: {}", status.as_u16(), truncate(body.trim(), 200)))
+}
+
+/// Deliver one notification. `user_email` is the fallback address for email channels.
+async fn send(db_pool: &DatabasePool, config: &ChannelConfig, rendered: &Rendered<'_>, user_email: Option<&str>) -> Result<(), String> {
+    let client = http_client().map_err(|e| e.message().to_string())?;
+    let notice = rendered.notice;
+    let artwork = notice.artwork_url.as_deref().filter(|u| !u.is_empty());
+
+    match config {
+        ChannelConfig::Ntfy { server_url, topic, username, password, access_token } => {
+            // JSON publishing, so titles aren't limited to what fits in a header
+            let server = server_url.as_deref().filter(|u| !u.is_empty()).unwrap_or("https://ntfy.sh");
+            let mut body = serde_json::json!({
+                "topic": topic,
+                "title": rendered.title,
+                "message": rendered.message,
+            });
+            if let Some(url) = notice.open_url() {
+                body["click"] = Value::String(url.to_string());
+            }
+            if let Some(url) = artwork {
+                body["icon"] = Value::String(url.to_string());
+            }
+            let mut request = client.post(server.trim_end_matches('/')).json(&body);
+            if let Some(token) = access_token.as_deref().filter(|t| !t.is_empty()) {
+                request = request.bearer_auth(token);
+            } else if let (Some(user), Some(pass)) =
+                (username.as_deref().filter(|u| !u.is_empty()), password.as_deref().filter(|p| !p.is_empty()))
+            {
+                request = request.basic_auth(user, Some(pass));
+            }
+            check(request.send().await).await
+        }
+        ChannelConfig::Gotify { url, token } => {
+            let mut extras = serde_json::Map::new();
+            if let Some(url) = notice.open_url() {
+                extras.insert("click".into(), serde_json::json!({ "url": url }));
+            }
+            if let Some(url) = artwork {
+                extras.insert("bigImageUrl".into(), Value::String(url.to_string()));
+            }
+            let body = serde_json::json!({
+                "title": rendered.title,
+                "message": rendered.message,
+                "priority": 5,
+                "extras": { "client::notification": extras },
+            });
+            let request = client
+                .post(format!("{}/message", url.trim_end_matches('/')))
+                .header("X-Gotify-Key", token)
+                .json(&body);
+            check(request.send().await).await
+        }
+        ChannelConfig::Http { url, token, method } => {
+            let request = if method.as_deref().is_some_and(|m| m.eq_ignore_ascii_case("GET")) {
+                client.get(url).query(&[("title", &rendered.title), ("message", &rendered.message)])
+            } else {
+                client.post(url).json(&serde_json::json!({
+                    "title": rendered.title,
+                    "message": rendered.message,
+                    // Older receivers read `text`
+                    "text": rendered.message,
+                    "podcast": notice.podcast_name,
+                    "episode_id": notice.episode_id,
+                    "episode_title": notice.episode_title,
+                    "duration_seconds": notice.duration_seconds,
+                    "pub_date": notice.pub_date,
+                    "artwork_url": notice.artwork_url,
+                    "stream_url": notice.stream_url,
+                    "link": notice.link,
+                }))
+            };
+            let request = match token.as_deref().filter(|t| !t.is_empty()) {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            };
+            check(request.send().await).await
+        }
+        ChannelConfig::Matrix { homeserver, room_id, access_token } => {
+            let url = format!(
+                "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+                homeserver.trim_end_matches('/'),
+                urlencoding::encode(room_id),
+                uuid::Uuid::new_v4()
+            );
+            let mut formatted = format!(
+                "<strong>{}</strong><br>{}",
+                escape_html(&rendered.title),
+                escape_html(&rendered.message).replace('\n', "<br>")
+            );
+            if let Some(link) = notice.open_url() {
+                formatted.push_str(&format!("<br><a href=\"{}\">{}</a>", escape_html(link), escape_html(link)));
+            }
+            let body = serde_json::json!({
+                "msgtype": "m.text",
+                "body": format!("{}\n{}", rendered.title, rendered.message),
+                "format": "org.matrix.custom.html",
+                "formatted_body": formatted,
+            });
+            check(client.put(url).bearer_auth(access_token).json(&body).send().await).await
+        }
+        ChannelConfig::Discord { webhook_url } => {
+            let mut embed = serde_json::json!({
+                "title": truncate(&rendered.title, 256),
+                "description": truncate(&rendered.message, 4096),
+            });
+            if let Some(url) = notice.open_url() {
+                embed["url"] = Value::String(url.to_string());
+            }
+            if let Some(url) = artwork {
+                embed["thumbnail"] = serde_json::json!({ "url": url });
+            }
+            let body = serde_json::json!({ "username": "PinePods", "embeds": [embed] });
+            check(client.post(webhook_url).json(&body).send().await).await
+        }
+        ChannelConfig::Slack { webhook_url } => {
+            let mut text = format!("*{}*\n{}", rendered.title, rendered.message);
+            if let Some(url) = notice.open_url() {
+                text.push_str(&format!("\n<{}|Open episode>", url));
+            }
+            let mut section = serde_json::json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": text },
+            });
+            if let Some(url) = artwork {
+                section["accessory"] = serde_json::json!({
+                    "type": "image",
+                    "image_url": url,
+                    "alt_text": notice.podcast_name,
+                });
+            }
+            let body = serde_json::json!({
+                "text": format!("{}: {}", rendered.title, rendered.message),
+                "blocks": [section],
+            });
+            check(client.post(webhook_url).json(&body).send().await).await
+        }
+        ChannelConfig::Telegram { bot_token, chat_id } => {
+            let mut text = format!("{}\n\n{}", rendered.title, rendered.message);
+            if let Some(url) = notice.open_url() {
+                text.push_str(&format!("\n{}", url));
+            }
+            let request = match artwork {
+                Some(photo) => client.post(format!("{}/bot{}/sendPhoto", TELEGRAM_API, bot_token)).json(&serde_json::json!({
+                    "chat_id": chat_id,
+                    "photo": photo,
+                    "caption": truncate(&text, TELEGRAM_CAPTION_LIMIT),
+                })),
+                None => client.post(format!("{}/bot{}/sendMessage", TELEGRAM_API, bot_token)).json(&serde_json::json!({
+                    "chat_id": chat_id,
+                    "text": text,
+                })),
+            };
+            check(request.send().await).await
+        }
+        ChannelConfig::Pushover { user_key, app_token, device } => {
+            let mut body = serde_json::json!({
+                "token": app_token,
+                "user": user_key,
+                "title": truncate(&rendered.title, 250),
+                "message": truncate(&rendered.message, 1024),
+            });
+            if let Some(url) = notice.open_url() {
+                body["url"] = Value::String(url.to_string());
+                body["url_title"] = Value::String("Open episode".to_string());
+            }
+            if let Some(device) = device.as_deref().filter(|d| !d.is_empty()) {
+                body["device"] = Value::String(device.to_string());
+            }
+            check(client.post(PUSHOVER_API).json(&body).send().await).await
+        }
+        ChannelConfig::Email { to_email } => {
+            let to_email = to_email
+                .as_deref()
+                .filter(|e| !e.trim().is_empty())
+                .or(user_email)
+                .ok_or("No email address for this channel")?;
+            let settings = db_pool
+                .get_email_settings()
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("The server's email settings are not configured")?;
+            let mut message = escape_html(&rendered.message);
+            if let Some(link) = notice.open_url() {
+                message.push_str(&format!("\n<a href=\"{}\">Open episode</a>", escape_html(link)));
+            }
+            if let Some(url) = artwork {
+                message = format!("<img src=\"{}\" alt=\"\" width=\"120\" style=\"float: right; margin-left: 12px;\">{}", escape_html(url), message);
+            }
+            let content = email_content(&rendered.title, &message);
+            crate::handlers::settings::send_html_email(&settings, to_email.trim(), &rendered.title, &content)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+// ---- Storage ----
+
+struct StoredChannel {
+    channel_id: i32,
+    user_id: i32,
+    name: String,
+    enabled: bool,
+    config: String,
+    title_template: Option<String>,
+    message_template: Option<String>,
+    last_sent_at: Option<chrono::NaiveDateTime>,
+    last_error: Option<String>,
+}
+
+async fn load_channels(db_pool: &DatabasePool, user_id: i32, channel_id: Option<i32>) -> AppResult<Vec<StoredChannel>> {
+    let channels = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT channelid, userid, name, enabled, config, titletemplate, messagetemplate, lastsentat, lasterror
+               FROM "NotificationChannels"
+               WHERE userid = $1 AND ($2::INT IS NULL OR channelid = $2)
+               ORDER BY channelid"#,
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<StoredChannel> {
+            Ok(StoredChannel {
+                channel_id: r.try_get("channelid")?,
+                user_id: r.try_get("userid")?,
+                name: r.try_get("name")?,
+                enabled: r.try_get("enabled")?,
+                config: r.try_get("config")?,
+                title_template: r.try_get("titletemplate")?,
+                message_template: r.try_get("messagetemplate")?,
+                last_sent_at: r.try_get("lastsentat")?,
+                last_error: r.try_get("lasterror")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT ChannelID, UserID, Name, CAST(Enabled AS SIGNED) AS enabled, Config, TitleTemplate,
+                    MessageTemplate, LastSentAt, LastError
+             FROM NotificationChannels
+             WHERE UserID = ? AND (? IS NULL OR ChannelID = ?)
+             ORDER BY ChannelID",
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<StoredChannel> {
+            Ok(StoredChannel {
+                channel_id: r.try_get("ChannelID")?,
+                user_id: r.try_get("UserID")?,
+                name: r.try_get("Name")?,
+                enabled: r.try_get::<i64, _>("enabled")? != 0,
+                config: r.try_get("Config")?,
+                title_template: r.try_get("TitleTemplate")?,
+                message_template: r.try_get("MessageTemplate")?,
+                last_sent_at: r.try_get("LastSentAt")?,
+                last_error: r.try_get("LastError")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(channels)
+}
+
+async fn decrypt_config(db_pool: &DatabasePool, encrypted: &str) -> AppResult<ChannelConfig> {
+    let json = db_pool.decrypt_password(encrypted).await?;
+    serde_json::from_str(&json).map_err(|e| AppError::internal(format!("Unreadable notification channel settings: {}", e)))
+}
+
+async fn encrypt_config(db_pool: &DatabasePool, config: &ChannelConfig) -> AppResult<String> {
+    let json = serde_json::to_string(config).map_err(|e| AppError::internal(e.to_string()))?;
+    db_pool.encrypt_password(&json).await
+}
+
+/// The user's channels, secrets blanked.
+pub async fn list_channels(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<NotificationChannel>> {
+    let mut channels = Vec::new();
+    for stored in load_channels(db_pool, user_id, None).await? {
+        let config = decrypt_config(db_pool, &stored.config).await?;
+        channels.push(NotificationChannel {
+            channel_id: stored.channel_id,
+            name: stored.name,
+            platform: config.platform().to_string(),
+            enabled: stored.enabled,
+            config: config.redacted(),
+            title_template: stored.title_template,
+            message_template: stored.message_template,
+            last_sent_at: format_time(stored.last_sent_at),
+            last_error: stored.last_error,
+        });
+    }
+    Ok(channels)
+}
+
+/// The stored (unredacted) settings of one of the user's channels.
+pub async fn channel_config(db_pool: &DatabasePool, user_id: i32, channel_id: i32) -> AppResult<Option<ChannelConfig>> {
+    match load_channels(db_pool, user_id, Some(channel_id)).await?.into_iter().next() {
+        Some(stored) => Ok(Some(decrypt_config(db_pool, &stored.config).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Empty templates mean "use the default".
+fn template(value: Option<&str>) -> Option<&str> {
+    value.filter(|t| !t.trim().is_empty())
+}
+
+/// The editable parts of a channel.
+pub struct ChannelSettings<'a> {
+    pub name: &'a str,
+    pub enabled: bool,
+    pub config: &'a ChannelConfig,
+    /// Empty or None for the default.
+    pub title_template: Option<&'a str>,
+    pub message_template: Option<&'a str>,
+}
+
+/// Create a channel. The config must already be validated. Returns the channel id.
+pub async fn add_channel(db_pool: &DatabasePool, user_id: i32, settings: &ChannelSettings<'_>) -> AppResult<i32> {
+    let encrypted = encrypt_config(db_pool, settings.config).await?;
+    let channel_id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"INSERT INTO "NotificationChannels" (userid, name, platform, enabled, config, titletemplate, messagetemplate)
+               VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING channelid"#,
+        )
+        .bind(user_id)
+        .bind(settings.name)
+        .bind(settings.config.platform())
+        .bind(settings.enabled)
+        .bind(&encrypted)
+        .bind(template(settings.title_template))
+        .bind(template(settings.message_template))
+        .fetch_one(pool)
+        .await?
+        .try_get("channelid")?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "INSERT INTO NotificationChannels (UserID, Name, Platform, Enabled, Config, TitleTemplate, MessageTemplate)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(settings.name)
+        .bind(settings.config.platform())
+        .bind(settings.enabled)
+        .bind(&encrypted)
+        .bind(template(settings.title_template))
+        .bind(template(settings.message_template))
+        .execute(pool)
+        .await?
+        .last_insert_id() as i32,
+    };
+    Ok(channel_id)
+}
+
+/// Replace a channel's settings. Returns false if the user has no such channel.
+pub async fn update_channel(db_pool: &DatabasePool, user_id: i32, channel_id: i32, settings: &ChannelSettings<'_>) -> AppResult<bool> {
+    let encrypted = encrypt_config(db_pool, settings.config).await?;
+    let updated = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"UPDATE "NotificationChannels"
+               SET name = $3, platform = $4, enabled = $5, config = $6, titletemplate = $7, messagetemplate = $8
+               WHERE channelid = $1 AND userid = $2"#,
+        )
+        .bind(channel_id)
+        .bind(user_id)
+        .bind(settings.name)
+        .bind(settings.config.platform())
+        .bind(settings.enabled)
+        .bind(&encrypted)
+        .bind(template(settings.title_template))
+        .bind(template(settings.message_template))
+        .execute(pool)
+        .await?
+        .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "UPDATE NotificationChannels
+             SET Name = ?, Platform = ?, Enabled = ?, Config = ?, TitleTemplate = ?, MessageTemplate = ?
+             WHERE ChannelID = ? AND UserID = ?",
+        )
+        .bind(settings.name)
+        .bind(settings.config.platform())
+        .bind(settings.enabled)
+        .bind(&encrypted)
+        .bind(template(settings.title_template))
+        .bind(template(settings.message_template))
+        .bind(channel_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+    };
+    // MySQL reports 0 rows for an update that changes nothing, so confirm the channel exists
+    Ok(updated > 0 || !load_channels(db_pool, user_id, Some(channel_id)).await?.is_empty())
+}
+
+/// Delete a channel and its podcast routes. Returns whether a row was removed.
+pub async fn remove_channel(db_pool: &DatabasePool, user_id: i32, channel_id: i32) -> AppResult<bool> {
+    let removed = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"DELETE FROM "NotificationChannels" WHERE channelid = $1 AND userid = $2"#)
+            .bind(channel_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query("DELETE FROM NotificationChannels WHERE ChannelID = ? AND UserID = ?")
+            .bind(channel_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+    };
+    Ok(removed > 0)
+}
+
+async fn record_result(db_pool: &DatabasePool, channel_id: i32, result: &Result<(), String>) -> AppResult<()> {
+    let error = result.as_ref().err();
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"UPDATE "NotificationChannels"
+                   SET lasterror = $2, lastsentat = CASE WHEN $2 IS NULL THEN NOW() ELSE lastsentat END
+                   WHERE channelid = $1"#,
+            )
+            .bind(channel_id)
+            .bind(error)
+            .execute(pool)
+            .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "UPDATE NotificationChannels
+                 SET LastSentAt = IF(? IS NULL, NOW(), LastSentAt), LastError = ?
+                 WHERE ChannelID = ?",
+            )
+            .bind(error)
+            .bind(error)
+            .bind(channel_id)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// ---- Per-podcast routing ----
+
+/// The channels a podcast is routed to; empty means all of its owner's channels.
+pub async fn podcast_channels(db_pool: &DatabasePool, user_id: i32, podcast_id: i32) -> AppResult<Vec<i32>> {
+    let ids = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT r.channelid FROM "PodcastNotificationChannels" r
+               JOIN "Podcasts" p ON p.podcastid = r.podcastid
+               WHERE r.podcastid = $1 AND p.userid = $2
+               ORDER BY r.channelid"#,
+        )
+        .bind(podcast_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<i32, _>("channelid"))
+        .collect::<Result<Vec<_>, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT r.ChannelID FROM PodcastNotificationChannels r
+             JOIN Podcasts p ON p.PodcastID = r.PodcastID
+             WHERE r.PodcastID = ? AND p.UserID = ?
+             ORDER BY r.ChannelID",
+        )
+        .bind(podcast_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<i32, _>("ChannelID"))
+        .collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(ids)
+}
+
+/// Route a podcast's notifications to the given channels (empty: all channels). Channels and the
+/// podcast must belong to the user; returns false if the podcast doesn't.
+pub async fn set_podcast_channels(db_pool: &DatabasePool, user_id: i32, podcast_id: i32, channel_ids: &[i32]) -> AppResult<bool> {
+    let owned: Vec<i32> = load_channels(db_pool, user_id, None).await?.iter().map(|c| c.channel_id).collect();
+    if let Some(unknown) = channel_ids.iter().find(|id| !owned.contains(id)) {
+        return Err(AppError::bad_request(format!("Notification channel {} not found", unknown)));
+    }
+
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let mut tx = pool.begin().await?;
+            let owns_podcast = sqlx::query(r#"SELECT 1 AS one FROM "Podcasts" WHERE podcastid = $1 AND userid = $2"#)
+                .bind(podcast_id)
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .is_some();
+            if !owns_podcast {
+                return Ok(false);
+            }
+            sqlx::query(r#"DELETE FROM "PodcastNotificationChannels" WHERE podcastid = $1"#)
+                .bind(podcast_id)
+                .execute(&mut *tx)
+                .await?;
+            for channel_id in channel_ids {
+                sqlx::query(
+                    r#"INSERT INTO "PodcastNotificationChannels" (podcastid, channelid) VALUES ($1, $2)
+                       ON CONFLICT DO NOTHING"#,
+                )
+                .bind(podcast_id)
+                .bind(channel_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            let mut tx = pool.begin().await?;
+            let owns_podcast = sqlx::query("SELECT 1 AS one FROM Podcasts WHERE PodcastID = ? AND UserID = ?")
+                .bind(podcast_id)
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .is_some();
+            if !owns_podcast {
+                return Ok(false);
+            }
+            sqlx::query("DELETE FROM PodcastNotificationChannels WHERE PodcastID = ?")
+                .bind(podcast_id)
+                .execute(&mut *tx)
+                .await?;
+            for channel_id in channel_ids {
+                sqlx::query("INSERT IGNORE INTO PodcastNotificationChannels (PodcastID, ChannelID) VALUES (?, ?)")
+                    .bind(podcast_id)
+                    .bind(channel_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+        }
+    }
+    Ok(true)
+}
+
+// ---- New-episode notifications ----
+
+/// Enabled channels that should hear about new episodes of a podcast: none if the podcast has
+/// notifications off, its routed channels if it has any, otherwise all of the owner's.
+async fn routed_channels(db_pool: &DatabasePool, podcast_id: i32) -> AppResult<Vec<StoredChannel>> {
+    let channel_ids: Vec<(i32, i32)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT c.channelid, c.userid
+               FROM "Podcasts" p JOIN "NotificationChannels" c ON c.userid = p.userid
+               WHERE p.podcastid = $1 AND p.notificationsenabled = TRUE AND c.enabled = TRUE
+                 AND (NOT EXISTS (SELECT 1 FROM "PodcastNotificationChannels" r WHERE r.podcastid = p.podcastid)
+                      OR EXISTS (SELECT 1 FROM "PodcastNotificationChannels" r
+                                 WHERE r.podcastid = p.podcastid AND r.channelid = c.channelid))"#,
+        )
+        .bind(podcast_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, i32)> { Ok((r.try_get("channelid")?, r.try_get("userid")?)) })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT c.ChannelID, c.UserID
+             FROM Podcasts p JOIN NotificationChannels c ON c.UserID = p.UserID
+             WHERE p.PodcastID = ? AND p.NotificationsEnabled = 1 AND c.Enabled = 1
+               AND (NOT EXISTS (SELECT 1 FROM PodcastNotificationChannels r WHERE r.PodcastID = p.PodcastID)
+                    OR EXISTS (SELECT 1 FROM PodcastNotificationChannels r
+                               WHERE r.PodcastID = p.PodcastID AND r.ChannelID = c.ChannelID))",
+        )
+        .bind(podcast_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, i32)> { Ok((r.try_get("ChannelID")?, r.try_get("UserID")?)) })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+
+    let mut channels = Vec::new();
+    for (channel_id, user_id) in channel_ids {
+        channels.extend(load_channels(db_pool, user_id, Some(channel_id)).await?);
+    }
+    Ok(channels)
+}
+
+async fn load_notice(db_pool: &DatabasePool, episode_id: i32) -> AppResult<Option<EpisodeNotice>> {
+    let row = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT p.podcastname, e.episodetitle, e.episodedescription, e.episodeduration, e.episodepubdate,
+                      COALESCE(NULLIF(e.episodeartwork, ''), p.artworkurl) AS artwork, e.episodeurl
+               FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               WHERE e.episodeid = $1"#,
+        )
+        .bind(episode_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| -> AppResult<EpisodeNotice> {
+            Ok(EpisodeNotice {
+                podcast_name: r.try_get::<Option<String>, _>("podcastname")?.unwrap_or_default(),
+                episode_id,
+                episode_title: r.try_get::<Option<String>, _>("episodetitle")?.unwrap_or_default(),
+                description: plain_summary(&r.try_get::<Option<String>, _>("episodedescription")?.unwrap_or_default()),
+                duration_seconds: r.try_get("episodeduration")?,
+                pub_date: format_time(r.try_get("episodepubdate")?),
+                artwork_url: r.try_get("artwork")?,
+                stream_url: r.try_get("episodeurl")?,
+                link: episode_link(episode_id),
+            })
+        })
+        .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT p.PodcastName, e.EpisodeTitle, e.EpisodeDescription, e.EpisodeDuration, e.EpisodePubDate,
+                    COALESCE(NULLIF(e.EpisodeArtwork, ''), p.ArtworkURL) AS artwork, e.EpisodeURL
+             FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             WHERE e.EpisodeID = ?",
+        )
+        .bind(episode_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| -> AppResult<EpisodeNotice> {
+            Ok(EpisodeNotice {
+                podcast_name: r.try_get::<Option<String>, _>("PodcastName")?.unwrap_or_default(),
+                episode_id,
+                episode_title: r.try_get::<Option<String>, _>("EpisodeTitle")?.unwrap_or_default(),
+                description: plain_summary(&r.try_get::<Option<String>, _>("EpisodeDescription")?.unwrap_or_default()),
+                duration_seconds: r.try_get("EpisodeDuration")?,
+                pub_date: format_time(r.try_get("EpisodePubDate")?),
+                artwork_url: r.try_get("artwork")?,
+                stream_url: r.try_get("EpisodeURL")?,
+                link: episode_link(episode_id),
+            })
+        })
+        .transpose()?,
+    };
+    Ok(row)
+}
+
+async fn user_email(db_pool: &DatabasePool, user_id: i32) -> AppResult<Option<String>> {
+    let email = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT email FROM "Users" WHERE userid = $1"#)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| r.try_get::<Option<String>, _>("email"))
+            .transpose()?
+            .flatten(),
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT Email FROM Users WHERE UserID = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| r.try_get::<Option<String>, _>("Email"))
+            .transpose()?
+            .flatten(),
+    };
+    Ok(email.filter(|e| !e.is_empty()))
+}
+
+/// Render a channel's templates for an episode and send it, recording the outcome on the channel.
+async fn deliver(db_pool: &DatabasePool, channel: &StoredChannel, notice: &EpisodeNotice) -> AppResult<Result<(), String>> {
+    let config = decrypt_config(db_pool, &channel.config).await?;
+    let rendered = Rendered {
+        title: render(channel.title_template.as_deref().unwrap_or(DEFAULT_TITLE_TEMPLATE), notice),
+        message: render(channel.message_template.as_deref().unwrap_or(DEFAULT_MESSAGE_TEMPLATE), notice),
+        notice,
+    };
+    let email = match config {
+        ChannelConfig::Email { .. } => user_email(db_pool, channel.user_id).await?,
+        _ => None,
+    };
+    let result = send(db_pool, &config, &rendered, email.as_deref()).await;
+    if let Err(e) = &result {
+        warn!("Notification channel {} ({}) failed: {}", channel.channel_id, config.platform(), e);
+    }
+    record_result(db_pool, channel.channel_id, &result).await?;
+    Ok(result)
+}
+
+/// Hook for newly found episodes: notify the podcast's routed channels. Detached, so a slow or
+/// unreachable channel never holds up a feed refresh.
+pub fn notify_new_episode(db_pool: &DatabasePool, podcast_id: i32, episode_id: i32) {
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        let result = async {
+            let channels = routed_channels(&db_pool, podcast_id).await?;
+            if channels.is_empty() {
+                return Ok(());
+            }
+            let Some(notice) = load_notice(&db_pool, episode_id).await? else {
+                return Ok(());
+            };
+            for channel in &channels {
+                // A failing channel is logged and recorded on the channel; the others still send
+                if let Err(e) = deliver(&db_pool, channel, &notice).await {
+                    warn!("Notification channel {} could not be delivered for episode {}: {}", channel.channel_id, episode_id, e);
+                }
+            }
+            debug!("Sent new-episode notification for episode {} to {} channel(s)", episode_id, channels.len());
+            AppResult::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("New-episode notifications for episode {} failed: {}", episode_id, e);
+        }
+    });
+}
+
+/// A sample episode for test notifications: the user's newest episode if they have one.
+async fn sample_notice(db_pool: &DatabasePool, user_id: i32) -> AppResult<EpisodeNotice> {
+    let newest: Option<i32> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT e.episodeid FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               WHERE p.userid = $1 ORDER BY e.episodepubdate DESC LIMIT 1"#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| r.try_get("episodeid"))
+        .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT e.EpisodeID FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             WHERE p.UserID = ? ORDER BY e.EpisodePubDate DESC LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| r.try_get("EpisodeID"))
+        .transpose()?,
+    };
+    let notice = match newest {
+        Some(episode_id) => load_notice(db_pool, episode_id).await?,
+        None => None,
+    };
+    Ok(notice.unwrap_or_else(|| EpisodeNotice {
+        podcast_name: "PinePods".to_string(),
+        episode_title: "Test notification from PinePods".to_string(),
+        ..Default::default()
+    }))
+}
+
+/// Send a test notification through one of the user's channels, rendered against their newest
+/// episode. Returns the channel's error if it failed; None if there is no such channel.
+pub async fn test_channel(db_pool: &DatabasePool, user_id: i32, channel_id: i32) -> AppResult<Option<Result<(), String>>> {
+    let Some(channel) = load_channels(db_pool, user_id, Some(channel_id)).await?.into_iter().next() else {
+        return Ok(None);
+    };
+    let notice = sample_notice(db_pool, user_id).await?;
+    deliver(db_pool, &channel, &notice).await.map(Some)
+}
+
+// ---- Per-platform settings (pre-channel API) ----
+
+const LEGACY_PLATFORMS: [&str; 3] = ["ntfy", "gotify", "http"];
+
+/// The user's first channel of a platform, for the per-platform settings endpoints.
+async fn first_channel_of(db_pool: &DatabasePool, user_id: i32, platform: &str) -> AppResult<Option<(StoredChannel, ChannelConfig)>> {
+    for stored in load_channels(db_pool, user_id, None).await? {
+        let config = decrypt_config(db_pool, &stored.config).await?;
+        if config.platform() == platform {
+            return Ok(Some((stored, config)));
+        }
+    }
+    Ok(None)
+}
+
+/// The old `notification_settings` rows: one per ntfy/Gotify/HTTP platform the user has a
+/// channel for, with the first such channel's settings. Secrets are blanked as in [`list_channels`].
+pub async fn legacy_settings(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<Value>> {
+    let mut settings = Vec::new();
+    for platform in LEGACY_PLATFORMS {
+        let Some((stored, config)) = first_channel_of(db_pool, user_id, platform).await? else {
+            continue;
+        };
+        let config = config.redacted();
+        let mut setting = serde_json::json!({
+            "platform": platform,
+            "enabled": stored.enabled,
+            "ntfy_topic": null, "ntfy_server_url": null, "ntfy_username": null, "ntfy_password": null,
+            "ntfy_access_token": null, "gotify_url": null, "gotify_token": null,
+            "http_url": null, "http_token": null, "http_method": null,
+        });
+        match config {
+            ChannelConfig::Ntfy { server_url, topic, username, password, access_token } => {
+                setting["ntfy_topic"] = topic.into();
+                setting["ntfy_server_url"] = server_url.into();
+                setting["ntfy_username"] = username.into();
+                setting["ntfy_password"] = password.into();
+                setting["ntfy_access_token"] = access_token.into();
+            }
+            ChannelConfig::Gotify { url, token } => {
+                setting["gotify_url"] = url.into();
+                setting["gotify_token"] = token.into();
+            }
+            ChannelConfig::Http { url, token, method } => {
+                setting["http_url"] = url.into();
+                setting["http_token"] = token.into();
+                setting["http_method"] = method.into();
+            }
+            _ => {}
+        }
+        settings.push(setting);
+    }
+    Ok(settings)
+}
+
+/// Save per-platform settings into the user's first channel of that platform, creating it if needed.
+/// Blank secrets keep the stored ones, as when a channel is edited.
+pub async fn save_legacy_settings(db_pool: &DatabasePool, user_id: i32, enabled: bool, config: &ChannelConfig) -> AppResult<()> {
+    match first_channel_of(db_pool, user_id, config.platform()).await? {
+        Some((stored, stored_config)) => {
+            let mut config = config.clone();
+            config.keep_secrets_from(&stored_config);
+            config.validate().map_err(AppError::bad_request)?;
+            let settings = ChannelSettings {
+                name: &stored.name,
+                enabled,
+                config: &config,
+                title_template: stored.title_template.as_deref(),
+                message_template: stored.message_template.as_deref(),
+            };
+            update_channel(db_pool, user_id, stored.channel_id, &settings).await?;
+        }
+        None => {
+            config.validate().map_err(AppError::bad_request)?;
+            let name = match config {
+                ChannelConfig::Gotify { .. } => "Gotify",
+                ChannelConfig::Http { .. } => "HTTP",
+                _ => config.platform(),
+            };
+            let settings = ChannelSettings { name, enabled, config, title_template: None, message_template: None };
+            add_channel(db_pool, user_id, &settings).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Test the user's first channel of a platform. None if they have no such channel.
+pub async fn test_legacy_platform(db_pool: &DatabasePool, user_id: i32, platform: &str) -> AppResult<Option<Result<(), String>>> {
+    match first_channel_of(db_pool, user_id, platform).await? {
+        Some((stored, _)) => test_channel(db_pool, user_id, stored.channel_id).await,
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_server;
+    use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, Value)>>>;
+
+    /// A stand-in receiver that records each JSON body.
+    async fn receiver() -> (String, Received) {
+        let received: Received = Arc::default();
+        let app = Router::new()
+            .route(
+                "/{*path}",
+                post(|State(received): State<Received>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                    received.lock().unwrap().push((headers, body));
+                    "ok"
+                }),
+            )
+            .with_state(received.clone());
+        (test_server::serve(app).await, received)
+    }
+
+    fn notice() -> EpisodeNotice {
+        EpisodeNotice {
+            podcast_name: "Pine Talk".to_string(),
+            episode_id: 7,
+            episode_title: "Episode 7: Needles".to_string(),
+            description: "All about needles".to_string(),
+            duration_seconds: Some(3723),
+            pub_date: Some("2026-01-02T03:04:05".to_string()),
+            artwork_url: Some("https://example.com/art.jpg".to_string()),
+            stream_url: Some("https://example.com/ep7.mp3".to_string()),
+            link: Some("https://pods.example.com/episode?episode_id=7".to_string()),
+        }
+    }
+
+    #[test]
+    fn templates_fill_known_placeholders_and_drop_empty_lines() {
+        let notice = notice();
+        assert_eq!(render(DEFAULT_TITLE_TEMPLATE, &notice), "New episode of Pine Talk");
+        assert_eq!(render(DEFAULT_MESSAGE_TEMPLATE, &notice), "Episode 7: Needles\n1:02:03");
+        assert_eq!(render("{title} {unknown} {", &notice), "Episode 7: Needles {unknown} {");
+        assert_eq!(render("{stream_url} | {link}", &notice), "https://example.com/ep7.mp3 | https://pods.example.com/episode?episode_id=7");
+
+        let bare = EpisodeNotice { episode_title: "Short".to_string(), ..Default::default() };
+        assert_eq!(render(DEFAULT_MESSAGE_TEMPLATE, &bare), "Short");
+        assert_eq!(format_duration(125), "2:05");
+        assert_eq!(plain_summary("<p>Hello&nbsp;<b>there</b></p>\n\n  world"), "Hello there world");
+    }
+
+    #[test]
+    fn email_subjects_are_escaped() {
+        let content = email_content("Password Reset <script>", "line one\nline two");
+        assert!(content.contains("<h2>Password Reset &lt;script&gt;</h2>"));
+        assert!(content.contains("line one<br>line two"));
+    }
+
+    #[test]
+    fn secrets_are_blanked_and_kept_on_edit() {
+        let stored = ChannelConfig::Pushover {
+            user_key: "u-key".to_string(),
+            app_token: "a-token".to_string(),
+            device: Some("phone".to_string()),
+        };
+        let shown = stored.redacted();
+        assert_eq!(shown, ChannelConfig::Pushover { user_key: String::new(), app_token: String::new(), device: Some("phone".to_string()) });
+
+        let mut edited = ChannelConfig::Pushover { user_key: "new-key".to_string(), app_token: String::new(), device: None };
+        edited.keep_secrets_from(&stored);
+        assert_eq!(edited, ChannelConfig::Pushover { user_key: "new-key".to_string(), app_token: "a-token".to_string(), device: None });
+
+        // Only the access token was stored; a new password must not take its place.
+        let stored = ChannelConfig::Ntfy {
+            server_url: None,
+            topic: "pods".to_string(),
+            username: None,
+            password: None,
+            access_token: Some("tk".to_string()),
+        };
+        let mut edited = ChannelConfig::Ntfy {
+            server_url: None,
+            topic: "pods".to_string(),
+            username: Some("me".to_string()),
+            password: Some("pw".to_string()),
+            access_token: None,
+        };
+        edited.keep_secrets_from(&stored);
+        assert!(matches!(
+            edited,
+            ChannelConfig::Ntfy { password: Some(ref p), access_token: Some(ref t), .. } if p == "pw" && t == "tk"
+        ));
+
+        assert!(ChannelConfig::Telegram { bot_token: "t".to_string(), chat_id: " ".to_string() }.validate().is_err());
+        let parsed: ChannelConfig = serde_json::from_str(r#"{"platform":"discord","webhook_url":"https://d"}"#).unwrap();
+        assert_eq!(parsed.platform(), "discord");
+    }
+
+    #[tokio::test]
+    async fn platforms_send_their_own_payload_shapes() {
+        let db_pool = DatabasePool::Postgres(sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap());
+        let (base, received) = receiver().await;
+        let notice = notice();
+        let rendered = Rendered { title: "T".to_string(), message: "M".to_string(), notice: &notice };
+
+        let ntfy = ChannelConfig::Ntfy {
+            server_url: Some(base.clone() + "/ntfy"),
+            topic: "pods".to_string(),
+            username: None,
+            password: None,
+            access_token: Some("tk".to_string()),
+        };
+        send(&db_pool, &ntfy, &rendered, None).await.unwrap();
+        let discord = ChannelConfig::Discord { webhook_url: base.clone() + "/discord" };
+        send(&db_pool, &discord, &rendered, None).await.unwrap();
+
+        let received = received.lock().unwrap();
+        let (headers, body) = &received[0];
+        assert_eq!(headers["authorization"], "Bearer tk");
+        assert_eq!(body["topic"], "pods");
+        assert_eq!(body["click"], "https://pods.example.com/episode?episode_id=7");
+        assert_eq!(body["icon"], "https://example.com/art.jpg");
+        let (_, body) = &received[1];
+        assert_eq!(body["embeds"][0]["title"], "T");
+        assert_eq!(body["embeds"][0]["thumbnail"]["url"], "https://example.com/art.jpg");
+    }
+}
@@ -0,0 +1,184 @@
+//! Claim and retry bookkeeping shared by the delivery queues: `ScrobbleQueue` (see `scrobble`)
+//! and `WebhookDeliveries` (see `webhooks`).
+//!
+//! A queued row stays `pending` until an attempt goes through or it gives up as `failed`. A
+//! delivery run first [claims](Outbox::claim) the row, so when an immediate send races the
+//! scheduler's retry sweep only one of them sends it. Each attempt is then
+//! [recorded](Outbox::record_attempt) on the row, pushing a retryable failure's next attempt out
+//! exponentially, and on the endpoint the row was sent to.
+
+use crate::database::DatabasePool;
+use crate::error::AppResult;
+use crate::services::scrobble::SubmitError;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_FAILED: &str = "failed";
+
+/// How long a delivery run holds a claimed row before another run may retry it.
+const CLAIM_SECONDS: i64 = 5 * 60;
+
+/// Delay before retry number `attempts` (1-based): `first` seconds, doubling, capped at `max`.
+pub fn backoff_seconds(first: i64, max: i64, attempts: i32) -> i64 {
+    let exponent = attempts.clamp(1, 20) as u32 - 1;
+    first.saturating_mul(1i64 << exponent).min(max)
+}
+
+/// A queue table and the endpoint table its rows are sent to. Names are given as
+/// `(Postgres, MySQL)`; both tables use the same columns apart from case: `status`, `attempts`,
+/// `lasterror` and `nextattemptat` on the queue, `lastsuccessat` and `lasterror` on the endpoint.
+pub struct Outbox {
+    pub table: (&'static str, &'static str),
+    pub id_column: (&'static str, &'static str),
+    /// Stamped when an attempt goes through.
+    pub done_at_column: (&'static str, &'static str),
+    /// Status of a row that went through.
+    pub done_status: &'static str,
+    pub endpoint_table: (&'static str, &'static str),
+    pub endpoint_id_column: (&'static str, &'static str),
+    /// Attempts before a row is marked failed.
+    pub max_attempts: i32,
+    pub first_retry_seconds: i64,
+    pub max_retry_seconds: i64,
+}
+
+impl Outbox {
+    pub fn retry_delay_seconds(&self, attempts: i32) -> i64 {
+        backoff_seconds(self.first_retry_seconds, self.max_retry_seconds, attempts)
+    }
+
+    /// Push a pending row's next attempt past the claim window. Only one delivery run wins the
+    /// claim, so a row is never sent twice by runs racing for it.
+    pub async fn claim(&self, db_pool: &DatabasePool, id: i32) -> AppResult<bool> {
+        let claimed = match db_pool {
+            DatabasePool::Postgres(pool) => {
+                let sql = format!(
+                    r#"UPDATE {} SET nextattemptat = NOW() + (INTERVAL '1 second' * $2)
+                       WHERE {} = $1 AND status = $3 AND nextattemptat <= NOW()"#,
+                    self.table.0, self.id_column.0
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(id)
+                    .bind(CLAIM_SECONDS as f64)
+                    .bind(STATUS_PENDING)
+                    .execute(pool)
+                    .await?
+                    .rows_affected()
+            }
+            DatabasePool::MySQL(pool) => {
+                let sql = format!(
+                    "UPDATE {} SET NextAttemptAt = NOW() + INTERVAL ? SECOND
+                     WHERE {} = ? AND Status = ? AND NextAttemptAt <= NOW()",
+                    self.table.1, self.id_column.1
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(CLAIM_SECONDS)
+                    .bind(id)
+                    .bind(STATUS_PENDING)
+                    .execute(pool)
+                    .await?
+                    .rows_affected()
+            }
+        };
+        Ok(claimed == 1)
+    }
+
+    /// Record the outcome of one attempt on row `id` (after `attempts` earlier ones) and on its
+    /// endpoint, returning the row's new status. Without `retry` a retryable failure is final too.
+    pub async fn record_attempt(
+        &self,
+        db_pool: &DatabasePool,
+        id: i32,
+        endpoint_id: i32,
+        attempts: i32,
+        result: &Result<(), SubmitError>,
+        retry: bool,
+    ) -> AppResult<&'static str> {
+        let attempts = attempts + 1;
+        let (status, error) = match result {
+            Ok(()) => (self.done_status, None),
+            Err(SubmitError::Retryable(e)) if retry && attempts < self.max_attempts => (STATUS_PENDING, Some(e.as_str())),
+            Err(e) => (STATUS_FAILED, Some(e.message())),
+        };
+        let done = status == self.done_status;
+        let delay = self.retry_delay_seconds(attempts);
+
+        match db_pool {
+            DatabasePool::Postgres(pool) => {
+                let sql = format!(
+                    r#"UPDATE {table}
+                       SET status = $2, attempts = $3, lasterror = $4,
+                           nextattemptat = NOW() + (INTERVAL '1 second' * $5),
+                           {done_at} = CASE WHEN $6 THEN NOW() ELSE {done_at} END
+                       WHERE {id} = $1"#,
+                    table = self.table.0,
+                    done_at = self.done_at_column.0,
+                    id = self.id_column.0
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(id)
+                    .bind(status)
+                    .bind(attempts)
+                    .bind(error)
+                    .bind(delay as f64)
+                    .bind(done)
+                    .execute(pool)
+                    .await?;
+                let sql = format!(
+                    "UPDATE {} SET lastsuccessat = CASE WHEN $2 THEN NOW() ELSE lastsuccessat END, lasterror = $3 WHERE {} = $1",
+                    self.endpoint_table.0, self.endpoint_id_column.0
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(endpoint_id)
+                    .bind(done)
+                    .bind(error)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySQL(pool) => {
+                let sql = format!(
+                    "UPDATE {table}
+                     SET Status = ?, Attempts = ?, LastError = ?,
+                         NextAttemptAt = NOW() + INTERVAL ? SECOND,
+                         {done_at} = IF(?, NOW(), {done_at})
+                     WHERE {id} = ?",
+                    table = self.table.1,
+                    done_at = self.done_at_column.1,
+                    id = self.id_column.1
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(status)
+                    .bind(attempts)
+                    .bind(error)
+                    .bind(delay)
+                    .bind(done)
+                    .bind(id)
+                    .execute(pool)
+                    .await?;
+                let sql = format!(
+                    "UPDATE {} SET LastSuccessAt = IF(?, NOW(), LastSuccessAt), LastError = ? WHERE {} = ?",
+                    self.endpoint_table.1, self.endpoint_id_column.1
+                );
+                sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                    .bind(done)
+                    .bind(error)
+                    .bind(endpoint_id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_its_cap() {
+        assert_eq!(backoff_seconds(60, 3600, 1), 60);
+        assert_eq!(backoff_seconds(60, 3600, 3), 240);
+        assert_eq!(backoff_seconds(30, 3600, 20), 3600);
+        assert_eq!(backoff_seconds(30, 3600, 0), 30);
+    }
+}
@@ -0,0 +1,404 @@
+//! Realtime playback handoff between a user's devices.
+//!
+//! Every client (web app, desktop, phone) holds a websocket on `/ws/api/playback/{user_id}` and
+//! reports what it is playing whenever that changes: the episode, the position and whether it is
+//! playing. The latest report is the user's "now playing" state; the hub fans it out to the
+//! user's other devices so they can offer "continue on this device", and relays remote-control
+//! commands (play, pause, seek, stop) from one device to another. Queue edits are announced too so
+//! open queues refresh.
+//!
+//! State lives in Redis so it survives reconnects and is shared by every replica:
+//! - `playback:now:{user_id}` holds the latest [`PlaybackState`];
+//! - `playback:seq:{user_id}` numbers the states, so a client can drop one that arrives late;
+//! - `playback:devices:{user_id}` is a hash of connected devices, refreshed while connected.
+//!
+//! A (re)connecting client gets a [`ServerMessage::Snapshot`] of the state and devices, so it
+//! never needs to replay missed messages.
+
+use crate::error::AppResult;
+use crate::redis_client::RedisClient;
+use crate::services::cluster;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Seconds a device stays listed after its last presence refresh.
+const DEVICE_TTL_SECONDS: i64 = 90;
+/// How often a connection refreshes its device's presence.
+pub const PRESENCE_REFRESH_SECONDS: u64 = 30;
+/// The last state is kept for a week, so "continue listening" works across days.
+const STATE_TTL_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// A connected client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeviceInfo {
+    pub device_id: String,
+    pub device_name: String,
+    /// Unix time of the device's last presence refresh.
+    pub last_seen: i64,
+}
+
+/// What a user's device is playing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PlaybackState {
+    pub device_id: String,
+    pub device_name: String,
+    /// None when the device stopped playback altogether.
+    pub episode_id: Option<i32>,
+    #[serde(default)]
+    pub is_youtube: bool,
+    /// Playback position in seconds.
+    #[serde(default)]
+    pub position: f64,
+    #[serde(default)]
+    pub playing: bool,
+    /// Episode details supplied by the reporting client, passed through untouched so another
+    /// client can start the same episode without looking it up.
+    #[serde(default)]
+    pub episode: Option<Value>,
+    /// Increases with every state reported for the user.
+    pub seq: i64,
+    /// Unix time of the report.
+    pub updated_at: i64,
+}
+
+/// A remote-control action sent from one device to another.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteAction {
+    Play,
+    Pause,
+    Seek,
+    Stop,
+}
+
+/// A device's report of its own playback.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaybackReport {
+    pub episode_id: Option<i32>,
+    #[serde(default)]
+    pub is_youtube: bool,
+    #[serde(default)]
+    pub position: f64,
+    #[serde(default)]
+    pub playing: bool,
+    #[serde(default)]
+    pub episode: Option<Value>,
+}
+
+/// Messages a client sends on the playback websocket.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// The sender's own playback changed.
+    State(PlaybackReport),
+    /// Control another of the user's devices. `position` is required for `seek`.
+    Command {
+        target: String,
+        action: RemoteAction,
+        #[serde(default)]
+        position: Option<f64>,
+    },
+    /// Ask for a fresh snapshot, e.g. after the client noticed it missed an update.
+    Resync,
+    Ping,
+}
+
+/// Messages the server sends on the playback websocket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Sent on connect and on request: the current state and connected devices.
+    Snapshot {
+        now_playing: Option<PlaybackState>,
+        devices: Vec<DeviceInfo>,
+    },
+    State(PlaybackState),
+    Command {
+        source: String,
+        source_name: String,
+        action: RemoteAction,
+        position: Option<f64>,
+    },
+    Devices {
+        devices: Vec<DeviceInfo>,
+    },
+    QueueChanged,
+}
+
+/// A message addressed to some of a user's connections, as published between replicas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub user_id: i32,
+    /// Only this device receives it.
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Every device but this one (the sender) receives it.
+    #[serde(default)]
+    pub except: Option<String>,
+    pub message: ServerMessage,
+}
+
+impl Envelope {
+    pub fn is_for(&self, user_id: i32, device_id: &str) -> bool {
+        self.user_id == user_id
+            && self.target.as_deref().is_none_or(|target| target == device_id)
+            && self.except.as_deref() != Some(device_id)
+    }
+}
+
+/// Whether a report from `device_id` replaces `current`. Reporting "nothing playing" only
+/// clears the state the same device set, so a client opening with an idle player doesn't hide
+/// another device's episode.
+fn supersedes(current: Option<&PlaybackState>, device_id: &str, episode_id: Option<i32>) -> bool {
+    episode_id.is_some() || current.is_none_or(|state| state.device_id == device_id)
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn state_key(user_id: i32) -> String {
+    format!("playback:now:{}", user_id)
+}
+
+fn devices_key(user_id: i32) -> String {
+    format!("playback:devices:{}", user_id)
+}
+
+pub struct PlaybackHub {
+    redis: RedisClient,
+    local: broadcast::Sender<Arc<Envelope>>,
+}
+
+impl PlaybackHub {
+    pub fn new(redis: RedisClient) -> Self {
+        let (local, _) = broadcast::channel(1000);
+        Self { redis, local }
+    }
+
+    /// Deliver messages published by any replica to this replica's connections. Called once at
+    /// startup.
+    pub fn spawn_fanout(&self) {
+        let local = self.local.clone();
+        cluster::spawn_subscriber(self.redis.clone(), cluster::PLAYBACK_CHANNEL, move |payload| {
+            match serde_json::from_str::<Envelope>(&payload) {
+                Ok(envelope) => {
+                    let _ = local.send(Arc::new(envelope));
+                }
+                Err(e) => warn!("Ignoring malformed playback message: {}", e),
+            }
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<Envelope>> {
+        self.local.subscribe()
+    }
+
+    async fn publish(&self, envelope: Envelope) {
+        let published = match serde_json::to_string(&envelope) {
+            Ok(payload) => self.redis.publish(cluster::PLAYBACK_CHANNEL, &payload).await.is_ok(),
+            Err(_) => false,
+        };
+        if !published {
+            let _ = self.local.send(Arc::new(envelope));
+        }
+    }
+
+    pub async fn now_playing(&self, user_id: i32) -> AppResult<Option<PlaybackState>> {
+        let stored: Option<String> = self.redis.get(&state_key(user_id)).await?;
+        Ok(stored.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// The user's connected devices. Devices whose connection vanished without saying goodbye
+    /// (a crashed replica) drop out once their presence goes stale.
+    pub async fn devices(&self, user_id: i32) -> AppResult<Vec<DeviceInfo>> {
+        let mut conn = self.redis.get_connection().await?;
+        let entries: std::collections::HashMap<String, String> = conn.hgetall(devices_key(user_id)).await?;
+        let cutoff = now() - DEVICE_TTL_SECONDS;
+        let mut devices: Vec<DeviceInfo> = entries
+            .values()
+            .filter_map(|json| serde_json::from_str::<DeviceInfo>(json).ok())
+            .filter(|device| device.last_seen >= cutoff)
+            .collect();
+        devices.sort_by(|a, b| a.device_name.cmp(&b.device_name).then(a.device_id.cmp(&b.device_id)));
+        Ok(devices)
+    }
+
+    pub async fn snapshot(&self, user_id: i32) -> AppResult<ServerMessage> {
+        Ok(ServerMessage::Snapshot {
+            now_playing: self.now_playing(user_id).await?,
+            devices: self.devices(user_id).await?,
+        })
+    }
+
+    /// Refresh a device's presence without announcing it.
+    pub async fn touch(&self, user_id: i32, device: &mut DeviceInfo) -> AppResult<()> {
+        device.last_seen = now();
+        let mut conn = self.redis.get_connection().await?;
+        let _: () = conn
+            .hset(devices_key(user_id), &device.device_id, serde_json::to_string(device)?)
+            .await?;
+        let _: () = conn.expire(devices_key(user_id), DEVICE_TTL_SECONDS).await?;
+        Ok(())
+    }
+
+    /// List a newly connected device and tell the user's other devices.
+    pub async fn join(&self, user_id: i32, device: &mut DeviceInfo) -> AppResult<()> {
+        self.touch(user_id, device).await?;
+        self.announce_devices(user_id).await
+    }
+
+    /// Unlist a disconnected device. If it was the one playing, the user's state becomes paused
+    /// where it stopped, ready to be continued elsewhere.
+    pub async fn leave(&self, user_id: i32, device_id: &str) -> AppResult<()> {
+        let mut conn = self.redis.get_connection().await?;
+        let _: () = conn.hdel(devices_key(user_id), device_id).await?;
+
+        if let Some(state) = self.now_playing(user_id).await? {
+            if state.device_id == device_id && state.playing {
+                self.store_state(user_id, PlaybackState { playing: false, ..state }).await?;
+            }
+        }
+        self.announce_devices(user_id).await
+    }
+
+    async fn announce_devices(&self, user_id: i32) -> AppResult<()> {
+        let devices = self.devices(user_id).await?;
+        self.publish(Envelope { user_id, target: None, except: None, message: ServerMessage::Devices { devices } })
+            .await;
+        Ok(())
+    }
+
+    /// Record a device's playback report and pass it to the user's other devices. Returns the
+    /// stored state, or None if the report didn't replace the current one.
+    pub async fn report_state(
+        &self,
+        user_id: i32,
+        device: &DeviceInfo,
+        report: PlaybackReport,
+    ) -> AppResult<Option<PlaybackState>> {
+        let current = self.now_playing(user_id).await?;
+        if !supersedes(current.as_ref(), &device.device_id, report.episode_id) {
+            return Ok(None);
+        }
+        let active = report.episode_id.is_some();
+        let state = PlaybackState {
+            device_id: device.device_id.clone(),
+            device_name: device.device_name.clone(),
+            episode_id: report.episode_id,
+            is_youtube: report.is_youtube,
+            position: report.position.max(0.0),
+            playing: report.playing && active,
+            episode: if active { report.episode } else { None },
+            seq: 0,
+            updated_at: 0,
+        };
+        self.store_state(user_id, state).await.map(Some)
+    }
+
+    async fn store_state(&self, user_id: i32, mut state: PlaybackState) -> AppResult<PlaybackState> {
+        state.seq = self.redis.incr(&format!("playback:seq:{}", user_id)).await?;
+        state.updated_at = now();
+        self.redis
+            .set_ex(&state_key(user_id), serde_json::to_string(&state)?, STATE_TTL_SECONDS)
+            .await?;
+        self.publish(Envelope {
+            user_id,
+            target: None,
+            except: Some(state.device_id.clone()),
+            message: ServerMessage::State(state.clone()),
+        })
+        .await;
+        Ok(state)
+    }
+
+    /// Relay a remote-control command to one of the user's devices.
+    pub async fn send_command(
+        &self,
+        user_id: i32,
+        source: &DeviceInfo,
+        target: String,
+        action: RemoteAction,
+        position: Option<f64>,
+    ) {
+        self.publish(Envelope {
+            user_id,
+            target: Some(target),
+            except: None,
+            message: ServerMessage::Command {
+                source: source.device_id.clone(),
+                source_name: source.device_name.clone(),
+                action,
+                position,
+            },
+        })
+        .await;
+    }
+
+    /// Tell the user's devices their queue changed.
+    pub async fn queue_changed(&self, user_id: i32) {
+        self.publish(Envelope { user_id, target: None, except: None, message: ServerMessage::QueueChanged })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(device_id: &str, episode_id: Option<i32>) -> PlaybackState {
+        PlaybackState {
+            device_id: device_id.to_string(),
+            device_name: device_id.to_string(),
+            episode_id,
+            is_youtube: false,
+            position: 12.5,
+            playing: true,
+            episode: None,
+            seq: 1,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn an_idle_device_does_not_hide_another_devices_episode() {
+        let phone = state("phone", Some(7));
+        assert!(!supersedes(Some(&phone), "web", None));
+        assert!(supersedes(Some(&phone), "phone", None));
+        assert!(supersedes(Some(&phone), "web", Some(9)));
+        assert!(supersedes(None, "web", None));
+    }
+
+    #[test]
+    fn envelopes_reach_only_their_recipients() {
+        let broadcast = Envelope { user_id: 1, target: None, except: Some("web".into()), message: ServerMessage::QueueChanged };
+        assert!(broadcast.is_for(1, "phone"));
+        assert!(!broadcast.is_for(1, "web"));
+        assert!(!broadcast.is_for(2, "phone"));
+
+        let command = Envelope { user_id: 1, target: Some("phone".into()), except: None, message: ServerMessage::QueueChanged };
+        assert!(command.is_for(1, "phone"));
+        assert!(!command.is_for(1, "desktop"));
+    }
+
+    #[test]
+    fn messages_use_the_documented_wire_format() {
+        let command: ClientMessage = serde_json::from_str(
+            r#"{"type":"command","target":"phone","action":"seek","position":90.0}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            command,
+            ClientMessage::Command { ref target, action: RemoteAction::Seek, position: Some(p) } if target == "phone" && p == 90.0
+        ));
+
+        let json = serde_json::to_value(ServerMessage::State(state("phone", Some(7)))).unwrap();
+        assert_eq!(json["type"], "state");
+        assert_eq!(json["episode_id"], 7);
+        assert_eq!(serde_json::to_value(ServerMessage::QueueChanged).unwrap(), serde_json::json!({ "type": "queue_changed" }));
+    }
+}
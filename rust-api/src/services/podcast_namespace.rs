@@ -0,0 +1,515 @@
+//! Parser for the Podcasting 2.0 `podcast:` namespace.
+//!
+//! Runs once per feed body at refresh time (next to the feed-rs episode parse) and produces the
+//! channel-level tags plus the per-item tags, which `DatabasePool::apply_parsed_episodes`
+//! persists. Episode and podcast pages then read these from the database instead of re-fetching
+//! and scanning the feed on every view.
+//!
+//! Parsing is best effort: a malformed document stops the parse at the error and keeps whatever
+//! was read up to that point, since the episode list itself comes from feed-rs.
+
+use std::collections::HashMap;
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub role: Option<String>,
+    pub group: Option<String>,
+    pub img: Option<String>,
+    pub href: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub language: Option<String>,
+    pub rel: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Soundbite {
+    pub start_time: f64,
+    pub duration: f64,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Funding {
+    pub url: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValueRecipient {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub recipient_type: Option<String>,
+    pub address: Option<String>,
+    pub split: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValueBlock {
+    #[serde(rename = "type")]
+    pub value_type: Option<String>,
+    pub method: Option<String>,
+    pub suggested: Option<String>,
+    pub recipients: Vec<ValueRecipient>,
+}
+
+/// Tags carried by a single `<item>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemTags {
+    pub chapters_url: Option<String>,
+    pub chapters_type: Option<String>,
+    #[serde(default)]
+    pub transcripts: Vec<Transcript>,
+    #[serde(default)]
+    pub persons: Vec<Person>,
+    #[serde(default)]
+    pub soundbites: Vec<Soundbite>,
+    /// `podcast:season`, falling back to `itunes:season`.
+    pub season: Option<i32>,
+    pub season_name: Option<String>,
+    /// `podcast:episode` (may be fractional), falling back to `itunes:episode`.
+    pub episode: Option<f64>,
+    pub episode_display: Option<String>,
+    #[serde(default)]
+    pub value: Vec<ValueBlock>,
+}
+
+impl ItemTags {
+    pub fn is_empty(&self) -> bool {
+        *self == ItemTags::default()
+    }
+}
+
+/// Tags carried by the `<channel>` itself.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChannelTags {
+    pub guid: Option<String>,
+    #[serde(default)]
+    pub funding: Vec<Funding>,
+    #[serde(default)]
+    pub value: Vec<ValueBlock>,
+    #[serde(default)]
+    pub persons: Vec<Person>,
+    /// feedGuid of each `podcast:remoteItem` in `podcast:podroll`.
+    #[serde(default)]
+    pub podroll: Vec<String>,
+}
+
+/// Everything parsed from one feed body, with the items indexed by guid and enclosure URL so
+/// they can be paired with the episodes feed-rs produced.
+#[derive(Debug, Default)]
+pub struct FeedTags {
+    pub channel: ChannelTags,
+    items: Vec<ItemTags>,
+    by_guid: HashMap<String, usize>,
+    by_url: HashMap<String, usize>,
+}
+
+impl FeedTags {
+    /// Find the tags for an episode by guid, then by enclosure URL. Items without any tags
+    /// return None so callers don't store empty rows.
+    pub fn item(&self, guid: Option<&str>, url: &str) -> Option<&ItemTags> {
+        guid.and_then(|g| self.by_guid.get(g))
+            .or_else(|| if url.is_empty() { None } else { self.by_url.get(url) })
+            .map(|&idx| &self.items[idx])
+            .filter(|tags| !tags.is_empty())
+    }
+}
+
+/// Elements whose text content we collect until the matching end tag.
+enum Pending {
+    Person(Person),
+    Funding(Funding),
+    Soundbite(Soundbite),
+    ChannelGuid,
+    ItemGuid,
+    Season { name: Option<String>, itunes: bool },
+    Episode { display: Option<String>, itunes: bool },
+}
+
+#[derive(Default)]
+struct ItemState {
+    guid: Option<String>,
+    enclosure_url: Option<String>,
+    tags: ItemTags,
+    /// Whether season/episode came from the podcast: tag (which wins over itunes:).
+    podcast_season: bool,
+    podcast_episode: bool,
+}
+
+fn attr(e: &BytesStart, key: &str) -> Option<String> {
+    e.attributes()
+        .with_checks(false)
+        .flatten()
+        .find(|a| a.key.as_ref() == key.as_bytes())
+        .map(|a| match a.normalized_value(XmlVersion::Implicit1_0) {
+            Ok(v) => v.into_owned(),
+            Err(_) => String::from_utf8_lossy(&a.value).into_owned(),
+        })
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn value_block(e: &BytesStart) -> ValueBlock {
+    ValueBlock {
+        value_type: attr(e, "type"),
+        method: attr(e, "method"),
+        suggested: attr(e, "suggested"),
+        recipients: Vec::new(),
+    }
+}
+
+fn parse_seconds(raw: Option<String>) -> Option<f64> {
+    raw.and_then(|v| v.parse::<f64>().ok()).filter(|v| v.is_finite() && *v >= 0.0)
+}
+
+/// Parse the `podcast:` namespace tags out of a feed body.
+pub fn parse_feed(content: &str) -> FeedTags {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().check_end_names = false;
+
+    let mut feed = FeedTags::default();
+    let mut item: Option<ItemState> = None;
+    let mut value: Option<ValueBlock> = None;
+    let mut in_podroll = false;
+    let mut pending: Option<(Vec<u8>, Pending)> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::debug!("Podcasting 2.0 parse stopped at byte {}: {}", reader.buffer_position(), e);
+                break;
+            }
+        };
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                let name = e.name().as_ref().to_vec();
+                let mut opened: Option<Pending> = None;
+
+                match name.as_slice() {
+                    b"item" if !is_empty => item = Some(ItemState::default()),
+                    b"enclosure" => {
+                        if let Some(state) = item.as_mut() {
+                            if state.enclosure_url.is_none() {
+                                state.enclosure_url = attr(e, "url");
+                            }
+                        }
+                    }
+                    b"guid" if item.is_some() => opened = Some(Pending::ItemGuid),
+                    b"podcast:guid" if item.is_none() => opened = Some(Pending::ChannelGuid),
+                    b"podcast:chapters" => {
+                        if let Some(state) = item.as_mut() {
+                            state.tags.chapters_url = attr(e, "url");
+                            state.tags.chapters_type = attr(e, "type");
+                        }
+                    }
+                    b"podcast:transcript" => {
+                        if let (Some(state), Some(url)) = (item.as_mut(), attr(e, "url")) {
+                            state.tags.transcripts.push(Transcript {
+                                url,
+                                mime_type: attr(e, "type"),
+                                language: attr(e, "language"),
+                                rel: attr(e, "rel"),
+                            });
+                        }
+                    }
+                    b"podcast:person" => {
+                        opened = Some(Pending::Person(Person {
+                            name: String::new(),
+                            role: attr(e, "role"),
+                            group: attr(e, "group"),
+                            img: attr(e, "img"),
+                            href: attr(e, "href"),
+                        }))
+                    }
+                    b"podcast:soundbite" if item.is_some() => {
+                        if let (Some(start_time), Some(duration)) =
+                            (parse_seconds(attr(e, "startTime")), parse_seconds(attr(e, "duration")))
+                        {
+                            opened = Some(Pending::Soundbite(Soundbite { start_time, duration, title: None }));
+                        }
+                    }
+                    b"podcast:season" if item.is_some() => {
+                        opened = Some(Pending::Season { name: attr(e, "name"), itunes: false })
+                    }
+                    b"itunes:season" if item.is_some() => {
+                        opened = Some(Pending::Season { name: None, itunes: true })
+                    }
+                    b"podcast:episode" if item.is_some() => {
+                        opened = Some(Pending::Episode { display: attr(e, "display"), itunes: false })
+                    }
+                    b"itunes:episode" if item.is_some() => {
+                        opened = Some(Pending::Episode { display: None, itunes: true })
+                    }
+                    b"podcast:funding" if item.is_none() => {
+                        if let Some(url) = attr(e, "url") {
+                            opened = Some(Pending::Funding(Funding { url, description: String::new() }));
+                        }
+                    }
+                    b"podcast:value" => {
+                        let block = value_block(e);
+                        if is_empty {
+                            push_value(&mut feed.channel, item.as_mut(), block);
+                        } else {
+                            value = Some(block);
+                        }
+                    }
+                    b"podcast:valueRecipient" => {
+                        if let Some(block) = value.as_mut() {
+                            block.recipients.push(ValueRecipient {
+                                name: attr(e, "name"),
+                                recipient_type: attr(e, "type"),
+                                address: attr(e, "address"),
+                                split: attr(e, "split"),
+                            });
+                        }
+                    }
+                    b"podcast:podroll" if item.is_none() && !is_empty => in_podroll = true,
+                    b"podcast:remoteItem" if in_podroll => {
+                        if let Some(feed_guid) = attr(e, "feedGuid") {
+                            feed.channel.podroll.push(feed_guid);
+                        }
+                    }
+                    _ => {}
+                }
+
+                if let Some(p) = opened {
+                    text.clear();
+                    if is_empty {
+                        finish(&mut feed, item.as_mut(), p, "");
+                    } else {
+                        pending = Some((name, p));
+                    }
+                }
+            }
+            Event::Text(ref e) if pending.is_some() => {
+                if let Ok(t) = e.xml10_content() {
+                    text.push_str(&t);
+                }
+            }
+            Event::CData(ref e) if pending.is_some() => {
+                if let Ok(t) = e.decode() {
+                    text.push_str(&t);
+                }
+            }
+            Event::GeneralRef(ref e) if pending.is_some() => match e.resolve_char_ref() {
+                Ok(Some(ch)) => text.push(ch),
+                _ => {
+                    if let Ok(name) = e.decode() {
+                        match resolve_predefined_entity(&name) {
+                            Some(resolved) => text.push_str(resolved),
+                            None => {
+                                text.push('&');
+                                text.push_str(&name);
+                                text.push(';');
+                            }
+                        }
+                    }
+                }
+            },
+            Event::End(ref e) => {
+                let name = e.name();
+                if pending.as_ref().is_some_and(|(open, _)| open.as_slice() == name.as_ref()) {
+                    if let Some((_, p)) = pending.take() {
+                        finish(&mut feed, item.as_mut(), p, text.trim());
+                    }
+                    text.clear();
+                    continue;
+                }
+                match name.as_ref() {
+                    b"item" => {
+                        if let Some(state) = item.take() {
+                            let idx = feed.items.len();
+                            if let Some(guid) = state.guid {
+                                feed.by_guid.entry(guid).or_insert(idx);
+                            }
+                            if let Some(url) = state.enclosure_url {
+                                feed.by_url.entry(url).or_insert(idx);
+                            }
+                            feed.items.push(state.tags);
+                        }
+                    }
+                    b"podcast:value" => {
+                        if let Some(block) = value.take() {
+                            push_value(&mut feed.channel, item.as_mut(), block);
+                        }
+                    }
+                    b"podcast:podroll" => in_podroll = false,
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    feed
+}
+
+fn push_value(channel: &mut ChannelTags, item: Option<&mut ItemState>, block: ValueBlock) {
+    match item {
+        Some(state) => state.tags.value.push(block),
+        None => channel.value.push(block),
+    }
+}
+
+fn finish(feed: &mut FeedTags, item: Option<&mut ItemState>, pending: Pending, text: &str) {
+    match pending {
+        Pending::Person(mut person) => {
+            if text.is_empty() {
+                return;
+            }
+            person.name = text.to_string();
+            let persons = match item {
+                Some(state) => &mut state.tags.persons,
+                None => &mut feed.channel.persons,
+            };
+            if !persons.contains(&person) {
+                persons.push(person);
+            }
+        }
+        Pending::Funding(mut funding) => {
+            funding.description = text.to_string();
+            feed.channel.funding.push(funding);
+        }
+        Pending::Soundbite(mut soundbite) => {
+            if let Some(state) = item {
+                soundbite.title = Some(text.to_string()).filter(|t| !t.is_empty());
+                state.tags.soundbites.push(soundbite);
+            }
+        }
+        Pending::ChannelGuid => {
+            if !text.is_empty() {
+                feed.channel.guid = Some(text.to_string());
+            }
+        }
+        Pending::ItemGuid => {
+            if let Some(state) = item {
+                if !text.is_empty() {
+                    state.guid = Some(text.to_string());
+                }
+            }
+        }
+        Pending::Season { name, itunes } => {
+            if let (Some(state), Ok(season)) = (item, text.parse::<i32>()) {
+                if !itunes || !state.podcast_season {
+                    state.tags.season = Some(season);
+                    if !itunes {
+                        state.tags.season_name = name;
+                        state.podcast_season = true;
+                    }
+                }
+            }
+        }
+        Pending::Episode { display, itunes } => {
+            if let (Some(state), Ok(number)) = (item, text.parse::<f64>()) {
+                if number.is_finite() && (!itunes || !state.podcast_episode) {
+                    state.tags.episode = Some(number);
+                    if !itunes {
+                        state.tags.episode_display = display;
+                        state.podcast_episode = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:podcast="https://podcastindex.org/namespace/1.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+<channel>
+  <title>Show</title>
+  <podcast:guid>917393e3-1b1e-5cef-ace4-edaa54e1f810</podcast:guid>
+  <podcast:person role="host" img="https://example.com/a.jpg">Alice &amp; Co</podcast:person>
+  <podcast:funding url="https://example.com/donate">Support the show</podcast:funding>
+  <podcast:value type="lightning" method="keysend" suggested="0.00000005000">
+    <podcast:valueRecipient name="Alice" type="node" address="02abc" split="90"/>
+    <podcast:valueRecipient name="App" type="node" address="03def" split="10"/>
+  </podcast:value>
+  <podcast:podroll><podcast:remoteItem feedGuid="abc-123"/></podcast:podroll>
+  <item>
+    <title>Episode 1</title>
+    <guid isPermaLink="false">ep-1</guid>
+    <enclosure url="https://example.com/1.mp3" type="audio/mpeg" length="1"/>
+    <podcast:chapters url="https://example.com/1.json" type="application/json+chapters"/>
+    <podcast:transcript url="https://example.com/1.vtt" type="text/vtt" language="en"/>
+    <podcast:person role="guest">Bob</podcast:person>
+    <podcast:soundbite startTime="73.0" duration="60.0">Best <![CDATA[bit]]></podcast:soundbite>
+    <itunes:season>3</itunes:season>
+    <podcast:season name="Third">2</podcast:season>
+    <podcast:episode display="Ch. 4">4.5</podcast:episode>
+  </item>
+  <item>
+    <title>Episode 2</title>
+    <enclosure url="https://example.com/2.mp3" type="audio/mpeg" length="1"/>
+    <itunes:episode>7</itunes:episode>
+  </item>
+  <item><title>Bare</title><guid>ep-3</guid></item>
+</channel>
+</rss>"#;
+
+    #[test]
+    fn parses_channel_tags() {
+        let feed = parse_feed(FEED);
+        let channel = &feed.channel;
+        assert_eq!(channel.guid.as_deref(), Some("917393e3-1b1e-5cef-ace4-edaa54e1f810"));
+        assert_eq!(channel.persons.len(), 1);
+        assert_eq!(channel.persons[0].name, "Alice & Co");
+        assert_eq!(channel.persons[0].role.as_deref(), Some("host"));
+        assert_eq!(channel.funding, vec![Funding {
+            url: "https://example.com/donate".into(),
+            description: "Support the show".into(),
+        }]);
+        assert_eq!(channel.value.len(), 1);
+        assert_eq!(channel.value[0].recipients.len(), 2);
+        assert_eq!(channel.value[0].recipients[1].split.as_deref(), Some("10"));
+        assert_eq!(channel.podroll, vec!["abc-123".to_string()]);
+    }
+
+    #[test]
+    fn parses_item_tags_and_matches_by_guid_or_url() {
+        let feed = parse_feed(FEED);
+        let first = feed.item(Some("ep-1"), "").expect("item by guid");
+        assert_eq!(first.chapters_url.as_deref(), Some("https://example.com/1.json"));
+        assert_eq!(first.transcripts[0].mime_type.as_deref(), Some("text/vtt"));
+        assert_eq!(first.persons[0].name, "Bob");
+        assert_eq!(first.soundbites, vec![Soundbite { start_time: 73.0, duration: 60.0, title: Some("Best bit".into()) }]);
+        // podcast:season wins over itunes:season regardless of order.
+        assert_eq!(first.season, Some(2));
+        assert_eq!(first.season_name.as_deref(), Some("Third"));
+        assert_eq!(first.episode, Some(4.5));
+        assert_eq!(first.episode_display.as_deref(), Some("Ch. 4"));
+
+        let second = feed.item(None, "https://example.com/2.mp3").expect("item by url");
+        assert_eq!(second.episode, Some(7.0));
+        assert!(second.chapters_url.is_none());
+
+        // Items with no namespace tags yield nothing to store.
+        assert!(feed.item(Some("ep-3"), "").is_none());
+        assert!(feed.item(Some("missing"), "https://example.com/none.mp3").is_none());
+    }
+
+    #[test]
+    fn malformed_feed_keeps_what_was_parsed() {
+        let feed = parse_feed("<rss><channel><podcast:guid>g-1</podcast:guid><item><guid>x</guid></channel");
+        assert_eq!(feed.channel.guid.as_deref(), Some("g-1"));
+    }
+}
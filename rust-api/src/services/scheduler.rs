@@ -1,14 +1,19 @@
 use crate::{
     error::AppResult,
     handlers::{refresh, tasks},
+    services::{cluster, job_queue::Job as QueueJob},
     AppState,
 };
+use std::future::Future;
 use std::sync::Arc;
 use chrono::Utc;
 use croner::parser::{CronParser, Seconds};
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tracing::{info, error, warn};
 
+/// Lease on a running scheduled job; renewed while it runs, so it only matters if the replica dies.
+const JOB_LOCK_TTL_SECONDS: u64 = 120;
+
 pub struct BackgroundScheduler {
     scheduler: JobScheduler,
 }
@@ -32,42 +37,41 @@ impl BackgroundScheduler {
         let refresh_state = app_state.clone();
         let refresh_job = Job::new_async(refresh_cron.as_str(), move |_uuid, _l| {
             let state = refresh_state.clone();
-            Box::pin(async move {
-                info!("🔄 Running scheduled podcast refresh");
-                if let Err(e) = Self::run_refresh_pods(state.clone()).await {
-                    error!("❌ Scheduled podcast refresh failed: {}", e);
-                } else {
-                    info!("✅ Scheduled podcast refresh completed");
+            Box::pin(Self::run_as_leader("refresh", async move {
+                // Runs on the feed refresh worker pool, which never overlaps two refreshes
+                info!("🔄 Queueing scheduled podcast refresh");
+                if let Err(e) = state.task_spawner.jobs().enqueue(0, QueueJob::RefreshFeeds).await {
+                    error!("❌ Queueing scheduled podcast refresh failed: {}", e);
                 }
-            })
+            }))
         })?;
 
         // Schedule nightly tasks at midnight
         let nightly_state = app_state.clone();
         let nightly_job = Job::new_async("0 0 0 * * *", move |_uuid, _l| {
             let state = nightly_state.clone();
-            Box::pin(async move {
+            Box::pin(Self::run_as_leader("nightly", async move {
                 info!("🌙 Running scheduled nightly tasks");
                 if let Err(e) = Self::run_nightly_tasks(state.clone()).await {
                     error!("❌ Scheduled nightly tasks failed: {}", e);
                 } else {
                     info!("✅ Scheduled nightly tasks completed");
                 }
-            })
+            }))
         })?;
 
         // Schedule cleanup tasks every 6 hours
         let cleanup_state = app_state.clone();
         let cleanup_job = Job::new_async("0 0 */6 * * *", move |_uuid, _l| {
             let state = cleanup_state.clone();
-            Box::pin(async move {
+            Box::pin(Self::run_as_leader("cleanup", async move {
                 info!("🧹 Running scheduled cleanup tasks");
                 if let Err(e) = Self::run_cleanup_tasks(state.clone()).await {
                     error!("❌ Scheduled cleanup tasks failed: {}", e);
                 } else {
                     info!("✅ Scheduled cleanup tasks completed");
                 }
-            })
+            }))
         })?;
 
         // Evaluate user-configured scheduled backups every minute. A poll-and-evaluate job is
@@ -76,11 +80,44 @@ impl BackgroundScheduler {
         let backup_state = app_state.clone();
         let backup_job = Job::new_async("0 * * * * *", move |_uuid, _l| {
             let state = backup_state.clone();
-            Box::pin(async move {
+            Box::pin(Self::run_as_leader("backups", async move {
                 if let Err(e) = Self::run_scheduled_backups(state.clone()).await {
                     error!("❌ Scheduled backup run failed: {}", e);
                 }
-            })
+            }))
+        })?;
+
+        // Renew WebSub leases before they lapse and retire subscriptions whose hub went quiet
+        let websub_state = app_state.clone();
+        let websub_job = Job::new_async("0 20 * * * *", move |_uuid, _l| {
+            let state = websub_state.clone();
+            Box::pin(Self::run_as_leader("websub_renewal", async move {
+                if let Err(e) = crate::services::websub::renew_leases(&state.db_pool).await {
+                    error!("❌ WebSub lease renewal failed: {}", e);
+                }
+            }))
+        })?;
+
+        // Retry scrobble deliveries whose backoff has elapsed
+        let scrobble_state = app_state.clone();
+        let scrobble_job = Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+            let state = scrobble_state.clone();
+            Box::pin(Self::run_as_leader("scrobble_retry", async move {
+                if let Err(e) = crate::services::scrobble::retry_due(&state).await {
+                    error!("❌ Scrobble retry sweep failed: {}", e);
+                }
+            }))
+        })?;
+
+        // Retry webhook deliveries whose backoff has elapsed
+        let webhook_state = app_state.clone();
+        let webhook_job = Job::new_async("30 */5 * * * *", move |_uuid, _l| {
+            let state = webhook_state.clone();
+            Box::pin(Self::run_as_leader("webhook_retry", async move {
+                if let Err(e) = crate::services::webhooks::retry_due(&state.db_pool).await {
+                    error!("❌ Webhook retry sweep failed: {}", e);
+                }
+            }))
         })?;
 
         // Add jobs to scheduler
@@ -88,6 +125,9 @@ impl BackgroundScheduler {
         self.scheduler.add(nightly_job).await?;
         self.scheduler.add(cleanup_job).await?;
         self.scheduler.add(backup_job).await?;
+        self.scheduler.add(websub_job).await?;
+        self.scheduler.add(scrobble_job).await?;
+        self.scheduler.add(webhook_job).await?;
 
         // Start the scheduler
         self.scheduler.start().await?;
@@ -96,8 +136,29 @@ impl BackgroundScheduler {
         Ok(())
     }
 
-    // Direct function calls instead of HTTP requests
-    async fn run_refresh_pods(state: Arc<AppState>) -> AppResult<()> {
+    /// Run a scheduled job on the scheduler leader only. The per-job lock also covers a leadership
+    /// handover while a long run (nightly tasks, a backup) is still going on the previous leader.
+    async fn run_as_leader(name: &str, job: impl Future<Output = ()>) {
+        if !cluster::is_leader() {
+            return;
+        }
+        let _lock = match cluster::try_lock(&format!("scheduler:{}", name), JOB_LOCK_TTL_SECONDS).await {
+            Ok(Some(lock)) => lock,
+            Ok(None) => {
+                info!("⏭️ Skipping scheduled {} job: a previous run is still going", name);
+                return;
+            }
+            Err(e) => {
+                warn!("⚠️ Skipping scheduled {} job: {}", name, e);
+                return;
+            }
+        };
+        job.await;
+    }
+
+    // Direct function calls instead of HTTP requests. The refresh itself runs as a
+    // `RefreshFeeds` job on the job queue.
+    pub(crate) async fn run_refresh_pods(state: Arc<AppState>) -> AppResult<()> {
         // Call refresh_pods function directly
         match refresh::refresh_pods_admin_internal(&state).await {
             Ok(_) => {
@@ -291,6 +352,15 @@ impl BackgroundScheduler {
 
     // Run initial startup tasks immediately
     pub async fn run_startup_tasks(state: Arc<AppState>) -> AppResult<()> {
+        // Replicas starting together run these once; a replica that starts later sees the
+        // lock released and repeats them, which they tolerate.
+        let _lock = match cluster::try_lock("startup_tasks", JOB_LOCK_TTL_SECONDS).await? {
+            Some(lock) => lock,
+            None => {
+                info!("⏭️ Startup tasks are already running on another instance");
+                return Ok(());
+            }
+        };
         info!("🚀 Running initial startup tasks...");
         
         // Initialize OIDC provider from environment variables if configured
@@ -309,8 +379,8 @@ impl BackgroundScheduler {
         }
 
         // Run an immediate refresh to ensure data is current on startup
-        if let Err(e) = Self::run_refresh_pods(state.clone()).await {
-            warn!("⚠️ Initial startup refresh failed: {}", e);
+        if let Err(e) = state.task_spawner.jobs().enqueue(0, QueueJob::RefreshFeeds).await {
+            warn!("⚠️ Queueing initial startup refresh failed: {}", e);
         }
         
         info!("✅ Startup tasks completed");
@@ -0,0 +1,749 @@
+//! Scrobbling of podcast listens to external services.
+//!
+//! Each user can configure any number of [`ScrobbleTarget`]s. A listen qualifies once the episode
+//! is completed, or once the recorded listen time reaches ListenBrainz's own rule for a listen:
+//! half the episode or four minutes, whichever is shorter. A qualifying listen is queued once
+//! per target in `ScrobbleQueue` (a re-listen of the same episode is not re-sent) and delivered
+//! by a `deliver_scrobbles` background task. Failed deliveries back off exponentially; the
+//! scheduler re-spawns the task for rows that are due again, and a row gives up after
+//! [`MAX_ATTEMPTS`] or on a permanent error (bad token, rejected payload).
+//!
+//! Targets are pluggable through the [`Scrobbler`] trait. [`ListenBrainz`] speaks the
+//! `/1/submit-listens` API (also served by self-hosted instances and compatible services such as
+//! Maloja); [`Webhook`] POSTs the [`Listen`] as JSON for anything else. Tokens are stored
+//! Fernet-encrypted with the same key as the gpodder passwords.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::outbox::{Outbox, STATUS_PENDING};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+pub const KIND_LISTENBRAINZ: &str = "listenbrainz";
+pub const KIND_WEBHOOK: &str = "webhook";
+
+pub const DEFAULT_LISTENBRAINZ_URL: &str = "https://api.listenbrainz.org";
+
+const STATUS_SENT: &str = "sent";
+
+/// Deliveries attempted before a queued listen is marked failed. With the backoff below (one
+/// minute, doubling, capped at six hours) that spans roughly two days.
+pub const MAX_ATTEMPTS: i32 = 12;
+
+const QUEUE: Outbox = Outbox {
+    table: (r#""ScrobbleQueue""#, "ScrobbleQueue"),
+    id_column: ("scrobbleid", "ScrobbleID"),
+    done_at_column: ("sentat", "SentAt"),
+    done_status: STATUS_SENT,
+    endpoint_table: (r#""ScrobbleTargets""#, "ScrobbleTargets"),
+    endpoint_id_column: ("targetid", "TargetID"),
+    max_attempts: MAX_ATTEMPTS,
+    first_retry_seconds: 60,
+    max_retry_seconds: 6 * 60 * 60,
+};
+/// ListenBrainz counts a listen after four minutes, or half the track if that is shorter.
+const LISTEN_THRESHOLD_SECONDS: i64 = 240;
+
+/// A configured scrobble destination as shown to its owner. The token is never returned.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ScrobbleTarget {
+    pub target_id: i32,
+    pub kind: String,
+    pub endpoint: Option<String>,
+    pub has_token: bool,
+    pub enabled: bool,
+    pub last_success_at: Option<String>,
+    pub last_error: Option<String>,
+    pub pending: i64,
+    pub failed: i64,
+}
+
+/// One qualifying listen, as sent to webhooks and mapped onto ListenBrainz track metadata.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Listen {
+    pub episode_id: i32,
+    /// Unix seconds when the listen qualified.
+    pub listened_at: i64,
+    pub episode_title: String,
+    pub podcast_name: String,
+    pub author: Option<String>,
+    pub episode_url: String,
+    pub episode_guid: Option<String>,
+    pub feed_url: String,
+    pub duration_seconds: Option<i64>,
+    pub listened_seconds: i64,
+    pub completed: bool,
+}
+
+/// Outcome of a failed submission.
+#[derive(Debug, PartialEq)]
+pub enum SubmitError {
+    /// Network errors, rate limits and server errors: try again later.
+    Retryable(String),
+    /// The service rejected the token or payload; retrying won't help.
+    Permanent(String),
+}
+
+impl SubmitError {
+    pub(crate) fn from_status(status: reqwest::StatusCode, body: &str) -> Self {
+        let message = format!("HTTP {}: {}", status, body.chars().take(200).collect::<String>());
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::REQUEST_TIMEOUT {
+            SubmitError::Retryable(message)
+        } else {
+            SubmitError::Permanent(message)
+        }
+    }
+
+    pub(crate) fn message(&self) -> &str {
+        match self {
+            SubmitError::Retryable(m) | SubmitError::Permanent(m) => m,
+        }
+    }
+}
+
+/// A destination listens can be submitted to.
+#[async_trait::async_trait]
+pub trait Scrobbler: Send + Sync {
+    async fn submit(&self, listen: &Listen) -> Result<(), SubmitError>;
+}
+
+pub(crate) fn http_client() -> Result<reqwest::Client, SubmitError> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        // Targets are validated when they are added; a redirect could point anywhere.
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent("PinePods/1.0")
+        .build()
+        .map_err(|e| SubmitError::Retryable(e.to_string()))
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<(), SubmitError> {
+    let response = request.send().await.map_err(|e| SubmitError::Retryable(e.to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(SubmitError::from_status(status, &body))
+}
+
+/// ListenBrainz `submit-listens` client.
+pub struct ListenBrainz {
+    pub base_url: String,
+    pub token: String,
+}
+
+impl ListenBrainz {
+    fn payload(listen: &Listen) -> serde_json::Value {
+        let mut additional_info = serde_json::json!({
+            "media_player": "PinePods",
+            "submission_client": "PinePods",
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+            "music_service_name": "podcast",
+            "origin_url": listen.episode_url,
+        });
+        if let Some(duration) = listen.duration_seconds.filter(|d| *d > 0) {
+            additional_info["duration_ms"] = serde_json::json!(duration * 1000);
+        }
+        serde_json::json!({
+            "listen_type": "single",
+            "payload": [{
+                "listened_at": listen.listened_at,
+                "track_metadata": {
+                    "artist_name": listen.author.as_deref().filter(|a| !a.is_empty()).unwrap_or(&listen.podcast_name),
+                    "track_name": listen.episode_title,
+                    "release_name": listen.podcast_name,
+                    "additional_info": additional_info,
+                },
+            }],
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Scrobbler for ListenBrainz {
+    async fn submit(&self, listen: &Listen) -> Result<(), SubmitError> {
+        let url = format!("{}/1/submit-listens", self.base_url.trim_end_matches('/'));
+        let request = http_client()?
+            .post(url)
+            .header("Authorization", format!("Token {}", self.token))
+            .json(&Self::payload(listen));
+        send(request).await
+    }
+}
+
+/// Generic JSON webhook: `{"event": "listen", "listen": {...}}`, with an optional bearer token.
+pub struct Webhook {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl Scrobbler for Webhook {
+    async fn submit(&self, listen: &Listen) -> Result<(), SubmitError> {
+        let mut request = http_client()?
+            .post(&self.url)
+            .json(&serde_json::json!({ "event": "listen", "listen": listen }));
+        if let Some(token) = self.token.as_deref().filter(|t| !t.is_empty()) {
+            request = request.bearer_auth(token);
+        }
+        send(request).await
+    }
+}
+
+/// Build the scrobbler for a stored target.
+fn scrobbler_for(kind: &str, endpoint: Option<String>, token: Option<String>) -> Result<Box<dyn Scrobbler>, String> {
+    match kind {
+        KIND_LISTENBRAINZ => Ok(Box::new(ListenBrainz {
+            base_url: endpoint.filter(|e| !e.is_empty()).unwrap_or_else(|| DEFAULT_LISTENBRAINZ_URL.to_string()),
+            token: token.ok_or_else(|| "ListenBrainz target has no token".to_string())?,
+        })),
+        KIND_WEBHOOK => Ok(Box::new(Webhook {
+            url: endpoint.ok_or_else(|| "Webhook target has no URL".to_string())?,
+            token,
+        })),
+        other => Err(format!("Unknown scrobble target kind '{}'", other)),
+    }
+}
+
+/// Whether a listen counts: completed, or heard for half the episode or four minutes,
+/// whichever is shorter. Without a known duration only the four-minute rule applies.
+pub fn listen_qualifies(listened_seconds: i64, duration_seconds: Option<i64>, completed: bool) -> bool {
+    if completed {
+        return true;
+    }
+    let threshold = match duration_seconds.filter(|d| *d > 0) {
+        Some(duration) => (duration / 2).min(LISTEN_THRESHOLD_SECONDS),
+        None => LISTEN_THRESHOLD_SECONDS,
+    };
+    listened_seconds > 0 && listened_seconds >= threshold
+}
+
+// ---- Target management ----
+
+pub async fn list_targets(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<ScrobbleTarget>> {
+    let targets = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT t.targetid, t.kind, t.endpoint, t.token IS NOT NULL AS has_token, t.enabled,
+                      t.lastsuccessat, t.lasterror,
+                      COUNT(q.scrobbleid) FILTER (WHERE q.status = 'pending') AS pending,
+                      COUNT(q.scrobbleid) FILTER (WHERE q.status = 'failed') AS failed
+               FROM "ScrobbleTargets" t LEFT JOIN "ScrobbleQueue" q ON q.targetid = t.targetid
+               WHERE t.userid = $1
+               GROUP BY t.targetid
+               ORDER BY t.targetid"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<ScrobbleTarget> {
+            Ok(ScrobbleTarget {
+                target_id: r.try_get("targetid")?,
+                kind: r.try_get("kind")?,
+                endpoint: r.try_get("endpoint")?,
+                has_token: r.try_get("has_token")?,
+                enabled: r.try_get("enabled")?,
+                last_success_at: r
+                    .try_get::<Option<chrono::NaiveDateTime>, _>("lastsuccessat")?
+                    .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+                last_error: r.try_get("lasterror")?,
+                pending: r.try_get("pending")?,
+                failed: r.try_get("failed")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT t.TargetID, t.Kind, t.Endpoint, CAST(t.Token IS NOT NULL AS SIGNED) AS has_token,
+                    CAST(t.Enabled AS SIGNED) AS enabled, t.LastSuccessAt, t.LastError,
+                    CAST(COALESCE(SUM(q.Status = 'pending'), 0) AS SIGNED) AS pending,
+                    CAST(COALESCE(SUM(q.Status = 'failed'), 0) AS SIGNED) AS failed
+             FROM ScrobbleTargets t LEFT JOIN ScrobbleQueue q ON q.TargetID = t.TargetID
+             WHERE t.UserID = ?
+             GROUP BY t.TargetID, t.Kind, t.Endpoint, t.Token, t.Enabled, t.LastSuccessAt, t.LastError
+             ORDER BY t.TargetID",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<ScrobbleTarget> {
+            Ok(ScrobbleTarget {
+                target_id: r.try_get("TargetID")?,
+                kind: r.try_get("Kind")?,
+                endpoint: r.try_get("Endpoint")?,
+                has_token: r.try_get::<i64, _>("has_token")? != 0,
+                enabled: r.try_get::<i64, _>("enabled")? != 0,
+                last_success_at: r
+                    .try_get::<Option<chrono::NaiveDateTime>, _>("LastSuccessAt")?
+                    .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+                last_error: r.try_get("LastError")?,
+                pending: r.try_get("pending")?,
+                failed: r.try_get("failed")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(targets)
+}
+
+/// Add a target. The token is encrypted before it is stored. Returns the new target id.
+pub async fn add_target(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    kind: &str,
+    endpoint: Option<&str>,
+    token: Option<&str>,
+) -> AppResult<i32> {
+    let token = match token.filter(|t| !t.is_empty()) {
+        Some(token) => Some(db_pool.encrypt_password(token).await?),
+        None => None,
+    };
+    let target_id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"INSERT INTO "ScrobbleTargets" (userid, kind, endpoint, token)
+               VALUES ($1, $2, $3, $4) RETURNING targetid"#,
+        )
+        .bind(user_id)
+        .bind(kind)
+        .bind(endpoint)
+        .bind(token)
+        .fetch_one(pool)
+        .await?
+        .try_get("targetid")?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "INSERT INTO ScrobbleTargets (UserID, Kind, Endpoint, Token) VALUES (?, ?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(kind)
+        .bind(endpoint)
+        .bind(token)
+        .execute(pool)
+        .await?
+        .last_insert_id() as i32,
+    };
+    Ok(target_id)
+}
+
+/// Delete one of the user's targets along with its queue. Returns whether a row was removed.
+pub async fn remove_target(db_pool: &DatabasePool, user_id: i32, target_id: i32) -> AppResult<bool> {
+    let removed = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"DELETE FROM "ScrobbleTargets" WHERE targetid = $1 AND userid = $2"#)
+            .bind(target_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query("DELETE FROM ScrobbleTargets WHERE TargetID = ? AND UserID = ?")
+            .bind(target_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+    };
+    Ok(removed > 0)
+}
+
+// ---- Queueing ----
+
+/// Load the listen for `episode_id` as of now, from the user's history and the episode row.
+async fn load_listen(db_pool: &DatabasePool, user_id: i32, episode_id: i32, completed: bool) -> AppResult<Option<Listen>> {
+    let listened_at = chrono::Utc::now().timestamp();
+    let listen = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT e.episodetitle, p.podcastname, p.author, e.episodeurl, e.episodeguid, p.feedurl,
+                      e.episodeduration, COALESCE(h.listenduration, 0) AS listened,
+                      COALESCE(e.completed, FALSE) AS completed
+               FROM "Episodes" e
+               JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               LEFT JOIN "UserEpisodeHistory" h ON h.episodeid = e.episodeid AND h.userid = $2
+               WHERE e.episodeid = $1 AND p.userid = $2"#,
+        )
+        .bind(episode_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| -> AppResult<Listen> {
+            Ok(Listen {
+                episode_id,
+                listened_at,
+                episode_title: r.try_get::<Option<String>, _>("episodetitle")?.unwrap_or_default(),
+                podcast_name: r.try_get::<Option<String>, _>("podcastname")?.unwrap_or_default(),
+                author: r.try_get("author")?,
+                episode_url: r.try_get::<Option<String>, _>("episodeurl")?.unwrap_or_default(),
+                episode_guid: r.try_get("episodeguid")?,
+                feed_url: r.try_get::<Option<String>, _>("feedurl")?.unwrap_or_default(),
+                duration_seconds: r.try_get::<Option<i32>, _>("episodeduration")?.map(i64::from),
+                listened_seconds: r.try_get::<i32, _>("listened")? as i64,
+                completed: completed || r.try_get::<bool, _>("completed")?,
+            })
+        })
+        .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT e.EpisodeTitle, p.PodcastName, p.Author, e.EpisodeURL, e.EpisodeGUID, p.FeedURL,
+                    e.EpisodeDuration, CAST(COALESCE(h.ListenDuration, 0) AS SIGNED) AS listened,
+                    CAST(COALESCE(e.Completed, 0) AS SIGNED) AS completed
+             FROM Episodes e
+             JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             LEFT JOIN UserEpisodeHistory h ON h.EpisodeID = e.EpisodeID AND h.UserID = ?
+             WHERE e.EpisodeID = ? AND p.UserID = ?",
+        )
+        .bind(user_id)
+        .bind(episode_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| -> AppResult<Listen> {
+            Ok(Listen {
+                episode_id,
+                listened_at,
+                episode_title: r.try_get::<Option<String>, _>("EpisodeTitle")?.unwrap_or_default(),
+                podcast_name: r.try_get::<Option<String>, _>("PodcastName")?.unwrap_or_default(),
+                author: r.try_get("Author")?,
+                episode_url: r.try_get::<Option<String>, _>("EpisodeURL")?.unwrap_or_default(),
+                episode_guid: r.try_get("EpisodeGUID")?,
+                feed_url: r.try_get::<Option<String>, _>("FeedURL")?.unwrap_or_default(),
+                duration_seconds: r.try_get::<Option<i32>, _>("EpisodeDuration")?.map(i64::from),
+                listened_seconds: r.try_get::<i64, _>("listened")?,
+                completed: completed || r.try_get::<i64, _>("completed")? != 0,
+            })
+        })
+        .transpose()?,
+    };
+    Ok(listen)
+}
+
+/// Queue the listen for every enabled target of the user that hasn't received this episode yet.
+/// Returns how many rows were queued.
+async fn enqueue(db_pool: &DatabasePool, user_id: i32, episode_id: i32, completed: bool) -> AppResult<u64> {
+    let has_targets = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT EXISTS(SELECT 1 FROM "ScrobbleTargets" WHERE userid = $1 AND enabled = TRUE) AS any_on"#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?
+        .try_get::<bool, _>("any_on")?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT CAST(EXISTS(SELECT 1 FROM ScrobbleTargets WHERE UserID = ? AND Enabled = 1) AS SIGNED) AS any_on",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?
+        .try_get::<i64, _>("any_on")?
+            != 0,
+    };
+    if !has_targets {
+        return Ok(0);
+    }
+
+    let Some(listen) = load_listen(db_pool, user_id, episode_id, completed).await? else {
+        return Ok(0);
+    };
+    if !listen_qualifies(listen.listened_seconds, listen.duration_seconds, listen.completed) {
+        return Ok(0);
+    }
+    let payload = serde_json::to_string(&listen).map_err(|e| AppError::internal(e.to_string()))?;
+
+    let queued = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"INSERT INTO "ScrobbleQueue" (targetid, episodeid, payload)
+               SELECT targetid, $2, $3 FROM "ScrobbleTargets" WHERE userid = $1 AND enabled = TRUE
+               ON CONFLICT (targetid, episodeid) DO NOTHING"#,
+        )
+        .bind(user_id)
+        .bind(episode_id)
+        .bind(&payload)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "INSERT IGNORE INTO ScrobbleQueue (TargetID, EpisodeID, Payload)
+             SELECT TargetID, ?, ? FROM ScrobbleTargets WHERE UserID = ? AND Enabled = 1",
+        )
+        .bind(episode_id)
+        .bind(&payload)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+    };
+    Ok(queued)
+}
+
+/// Hook for listen progress and completion: queue the listen if it now qualifies and start a
+/// delivery task. Detached, so recording playback never waits on (or fails because of) it.
+pub fn record_listen(state: &crate::AppState, user_id: i32, episode_id: i32, completed: bool) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        match enqueue(&state.db_pool, user_id, episode_id, completed).await {
+            Ok(0) => {}
+            Ok(queued) => {
+                debug!("Queued episode {} for {} scrobble target(s) of user {}", episode_id, queued, user_id);
+                if let Err(e) = state.task_spawner.spawn_deliver_scrobbles(user_id).await {
+                    warn!("Failed to start scrobble delivery for user {}: {}", user_id, e);
+                }
+            }
+            Err(e) => warn!("Failed to queue scrobble of episode {} for user {}: {}", episode_id, user_id, e),
+        }
+    });
+}
+
+// ---- Delivery ----
+
+struct QueuedScrobble {
+    scrobble_id: i32,
+    target_id: i32,
+    kind: String,
+    endpoint: Option<String>,
+    token: Option<String>,
+    payload: String,
+    attempts: i32,
+}
+
+/// Per-run delivery counts, returned as the task result.
+#[derive(Debug, Default, Serialize)]
+pub struct DeliveryReport {
+    pub sent: usize,
+    pub retrying: usize,
+    pub failed: usize,
+}
+
+async fn due_scrobbles(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<QueuedScrobble>> {
+    let rows = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT q.scrobbleid, q.targetid, t.kind, t.endpoint, t.token, q.payload, q.attempts
+               FROM "ScrobbleQueue" q JOIN "ScrobbleTargets" t ON t.targetid = q.targetid
+               WHERE t.userid = $1 AND t.enabled = TRUE AND q.status = $2 AND q.nextattemptat <= NOW()
+               ORDER BY q.scrobbleid
+               LIMIT 200"#,
+        )
+        .bind(user_id)
+        .bind(STATUS_PENDING)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<QueuedScrobble> {
+            Ok(QueuedScrobble {
+                scrobble_id: r.try_get("scrobbleid")?,
+                target_id: r.try_get("targetid")?,
+                kind: r.try_get("kind")?,
+                endpoint: r.try_get("endpoint")?,
+                token: r.try_get("token")?,
+                payload: r.try_get("payload")?,
+                attempts: r.try_get("attempts")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT q.ScrobbleID, q.TargetID, t.Kind, t.Endpoint, t.Token, q.Payload, q.Attempts
+             FROM ScrobbleQueue q JOIN ScrobbleTargets t ON t.TargetID = q.TargetID
+             WHERE t.UserID = ? AND t.Enabled = 1 AND q.Status = ? AND q.NextAttemptAt <= NOW()
+             ORDER BY q.ScrobbleID
+             LIMIT 200",
+        )
+        .bind(user_id)
+        .bind(STATUS_PENDING)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<QueuedScrobble> {
+            Ok(QueuedScrobble {
+                scrobble_id: r.try_get("ScrobbleID")?,
+                target_id: r.try_get("TargetID")?,
+                kind: r.try_get("Kind")?,
+                endpoint: r.try_get("Endpoint")?,
+                token: r.try_get("Token")?,
+                payload: r.try_get("Payload")?,
+                attempts: r.try_get("Attempts")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(rows)
+}
+
+/// Deliver every due queued listen of one user. Runs inside a `deliver_scrobbles` task.
+pub async fn deliver_due(db_pool: &DatabasePool, user_id: i32) -> AppResult<DeliveryReport> {
+    let mut report = DeliveryReport::default();
+    for item in due_scrobbles(db_pool, user_id).await? {
+        if !QUEUE.claim(db_pool, item.scrobble_id).await? {
+            continue;
+        }
+
+        let token = match item.token.as_deref() {
+            Some(encrypted) => match db_pool.decrypt_password(encrypted).await {
+                Ok(token) => Some(token),
+                Err(e) => {
+                    let result = Err(SubmitError::Permanent(format!("Could not decrypt token: {}", e)));
+                    QUEUE.record_attempt(db_pool, item.scrobble_id, item.target_id, item.attempts, &result, true).await?;
+                    report.failed += 1;
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let result = match (
+            scrobbler_for(&item.kind, item.endpoint.clone(), token),
+            serde_json::from_str::<Listen>(&item.payload),
+        ) {
+            (Ok(scrobbler), Ok(listen)) => scrobbler.submit(&listen).await,
+            (Err(e), _) => Err(SubmitError::Permanent(e)),
+            (_, Err(e)) => Err(SubmitError::Permanent(format!("Corrupt queued listen: {}", e))),
+        };
+
+        match QUEUE.record_attempt(db_pool, item.scrobble_id, item.target_id, item.attempts, &result, true).await? {
+            STATUS_SENT => report.sent += 1,
+            STATUS_PENDING => report.retrying += 1,
+            _ => report.failed += 1,
+        }
+        if let Err(e) = &result {
+            warn!("Scrobble {} to target {} failed: {}", item.scrobble_id, item.target_id, e.message());
+        }
+    }
+    Ok(report)
+}
+
+/// Users with queued listens due for delivery; the scheduler spawns a delivery task for each.
+pub async fn users_with_due_scrobbles(db_pool: &DatabasePool) -> AppResult<Vec<i32>> {
+    let users = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT DISTINCT t.userid
+               FROM "ScrobbleQueue" q JOIN "ScrobbleTargets" t ON t.targetid = q.targetid
+               WHERE t.enabled = TRUE AND q.status = $1 AND q.nextattemptat <= NOW()"#,
+        )
+        .bind(STATUS_PENDING)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<i32, _>("userid"))
+        .collect::<Result<Vec<_>, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT DISTINCT t.UserID
+             FROM ScrobbleQueue q JOIN ScrobbleTargets t ON t.TargetID = q.TargetID
+             WHERE t.Enabled = 1 AND q.Status = ? AND q.NextAttemptAt <= NOW()",
+        )
+        .bind(STATUS_PENDING)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<i32, _>("UserID"))
+        .collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(users)
+}
+
+/// Scheduler entry point: start a delivery task for every user with retries that are due.
+pub async fn retry_due(state: &crate::AppState) -> AppResult<()> {
+    let users = users_with_due_scrobbles(&state.db_pool).await?;
+    if !users.is_empty() {
+        info!("Retrying due scrobbles for {} user(s)", users.len());
+    }
+    for user_id in users {
+        state.task_spawner.spawn_deliver_scrobbles(user_id).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_server;
+    use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    /// A stand-in for ListenBrainz / a webhook receiver: records each request's Authorization
+    /// header and JSON body, and answers with a fixed status.
+    type Received = Vec<(Option<String>, serde_json::Value)>;
+
+    #[derive(Clone)]
+    struct StandIn {
+        status: StatusCode,
+        received: Arc<Mutex<Received>>,
+    }
+
+    async fn receive(State(s): State<StandIn>, headers: HeaderMap, Json(body): Json<serde_json::Value>) -> StatusCode {
+        let auth = headers.get("authorization").and_then(|v| v.to_str().ok()).map(str::to_string);
+        s.received.lock().unwrap().push((auth, body));
+        s.status
+    }
+
+    async fn stand_in(status: StatusCode) -> (String, StandIn) {
+        let state = StandIn { status, received: Arc::default() };
+        let app = Router::new()
+            .route("/1/submit-listens", post(receive))
+            .route("/hook", post(receive))
+            .with_state(state.clone());
+        (test_server::serve(app).await, state)
+    }
+
+    fn listen() -> Listen {
+        Listen {
+            episode_id: 7,
+            listened_at: 1_700_000_000,
+            episode_title: "Episode 7".into(),
+            podcast_name: "The Show".into(),
+            author: Some("Host".into()),
+            episode_url: "https://example.com/7.mp3".into(),
+            episode_guid: Some("guid-7".into()),
+            feed_url: "https://example.com/feed.xml".into(),
+            duration_seconds: Some(1800),
+            listened_seconds: 1800,
+            completed: true,
+        }
+    }
+
+    #[test]
+    fn qualifying_listens() {
+        assert!(listen_qualifies(0, Some(3600), true));
+        assert!(listen_qualifies(240, Some(3600), false));
+        assert!(!listen_qualifies(239, Some(3600), false));
+        assert!(listen_qualifies(60, Some(120), false));
+        assert!(!listen_qualifies(0, None, false));
+        assert_eq!(QUEUE.retry_delay_seconds(1), 60);
+        assert_eq!(QUEUE.retry_delay_seconds(MAX_ATTEMPTS), 6 * 60 * 60);
+    }
+
+    #[tokio::test]
+    async fn listenbrainz_submits_token_and_track_metadata() {
+        let (base, server) = stand_in(StatusCode::OK).await;
+        let scrobbler = ListenBrainz { base_url: base, token: "secret".into() };
+        scrobbler.submit(&listen()).await.unwrap();
+
+        let received = server.received.lock().unwrap();
+        let (auth, body) = &received[0];
+        assert_eq!(auth.as_deref(), Some("Token secret"));
+        assert_eq!(body["listen_type"], "single");
+        let track = &body["payload"][0]["track_metadata"];
+        assert_eq!(track["artist_name"], "Host");
+        assert_eq!(track["track_name"], "Episode 7");
+        assert_eq!(track["release_name"], "The Show");
+        assert_eq!(track["additional_info"]["duration_ms"], 1_800_000);
+    }
+
+    #[tokio::test]
+    async fn classifies_rate_limits_as_retryable_and_auth_errors_as_permanent() {
+        let (base, _) = stand_in(StatusCode::TOO_MANY_REQUESTS).await;
+        let scrobbler = ListenBrainz { base_url: base, token: "t".into() };
+        assert!(matches!(scrobbler.submit(&listen()).await, Err(SubmitError::Retryable(_))));
+
+        let (base, _) = stand_in(StatusCode::UNAUTHORIZED).await;
+        let scrobbler = ListenBrainz { base_url: base, token: "t".into() };
+        assert!(matches!(scrobbler.submit(&listen()).await, Err(SubmitError::Permanent(_))));
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_listen_with_bearer_token() {
+        let (base, server) = stand_in(StatusCode::NO_CONTENT).await;
+        let scrobbler = Webhook { url: format!("{}/hook", base), token: Some("abc".into()) };
+        scrobbler.submit(&listen()).await.unwrap();
+
+        let received = server.received.lock().unwrap();
+        let (auth, body) = &received[0];
+        assert_eq!(auth.as_deref(), Some("Bearer abc"));
+        assert_eq!(body["event"], "listen");
+        assert_eq!(serde_json::from_value::<Listen>(body["listen"].clone()).unwrap(), listen());
+    }
+}
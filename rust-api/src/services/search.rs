@@ -0,0 +1,460 @@
+//! Native full-text search over a user's episodes, the podcasts they belong to and their
+//! generated transcripts.
+//!
+//! Backed by the indexes from migrations 060 (episodes, transcripts) and 076 (podcast names): a
+//! weighted `tsvector` + GIN index on PostgreSQL, FULLTEXT indexes on MySQL/MariaDB. The database does matching and relevance ranking; snippet
+//! highlighting and transcript timestamps are computed here so both engines return identical
+//! shapes. Titles, descriptions and transcripts come from feeds, so highlighted text is
+//! HTML-escaped before `<mark>` is added; the only markup in a result is ours. Transcripts of
+//! linked episodes are keyed by `FeedEpisodeID` and the rest by `EpisodeID`; each is reached with
+//! its own indexed lookup rather than an OR across both. Candidate episodes are collected as a
+//! UNION of one indexed match per source, since MySQL can't use a FULLTEXT index for a MATCH()
+//! inside an OR.
+
+use crate::database::DatabasePool;
+use crate::error::AppResult;
+use serde::Serialize;
+use sqlx::Row;
+
+/// Maximum number of transcript moments returned per hit.
+const MAX_TRANSCRIPT_MATCHES: usize = 5;
+/// Target length (in characters) of a highlighted snippet.
+const SNIPPET_CHARS: usize = 200;
+
+/// A point in an episode's transcript where the query terms are spoken.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct TranscriptMatch {
+    /// Seconds from the start of the episode; clients seek playback here.
+    pub start: f64,
+    pub end: f64,
+    /// Segment text with matches wrapped in `<mark>`.
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SearchHit {
+    pub episodeid: i32,
+    pub episodetitle: String,
+    pub episodeurl: String,
+    pub episodeartwork: String,
+    pub episodepubdate: Option<String>,
+    pub episodeduration: i32,
+    pub podcastid: i32,
+    pub podcastname: String,
+    /// Relevance score from the database engine; only meaningful for ordering.
+    pub relevance: f64,
+    /// Title with matches wrapped in `<mark>`.
+    pub title_highlight: String,
+    /// Best-matching excerpt of the description (tags stripped) with matches wrapped in `<mark>`.
+    pub snippet: String,
+    pub transcript_matches: Vec<TranscriptMatch>,
+}
+
+/// Lower-cased query terms used for highlighting. Quotes and a leading '-' (negation in
+/// websearch syntax) are stripped; negated terms are dropped since they can't appear in a hit.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for raw in query.split_whitespace() {
+        if raw.starts_with('-') || raw.eq_ignore_ascii_case("or") {
+            continue;
+        }
+        let term: String = raw
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if !term.is_empty() && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Remove HTML tags, decode the common entities and collapse whitespace so descriptions can be
+/// excerpted. The result is plain text; `highlight` escapes it again.
+pub fn strip_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        _ => out.push(c),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    text.chars().for_each(|c| push_escaped(&mut out, c));
+    out
+}
+
+/// HTML-escape plain `text` and wrap every case-insensitive occurrence of any term in
+/// `<mark>…</mark>`.
+pub fn highlight(text: &str, terms: &[String]) -> String {
+    if terms.is_empty() {
+        return escape_html(text);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.to_lowercase().chars().collect();
+    // to_lowercase can change length for a few code points; fall back to no highlighting
+    // rather than risk misaligned markup.
+    if lower.len() != chars.len() {
+        return escape_html(text);
+    }
+    let term_chars: Vec<Vec<char>> = terms.iter().map(|t| t.chars().collect()).collect();
+
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut i = 0;
+    while i < chars.len() {
+        let matched = term_chars
+            .iter()
+            .filter(|t| !t.is_empty() && lower[i..].starts_with(t))
+            .map(|t| t.len())
+            .max();
+        match matched {
+            Some(len) => {
+                out.push_str("<mark>");
+                chars[i..i + len].iter().for_each(|&c| push_escaped(&mut out, c));
+                out.push_str("</mark>");
+                i += len;
+            }
+            None => {
+                push_escaped(&mut out, chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Excerpt of `text` (HTML stripped) around the first term occurrence, highlighted.
+pub fn snippet(text: &str, terms: &[String]) -> String {
+    let plain = strip_html(text);
+    let chars: Vec<char> = plain.chars().collect();
+    if chars.len() <= SNIPPET_CHARS {
+        return highlight(&plain, terms);
+    }
+
+    let lower = plain.to_lowercase();
+    let first_byte = terms.iter().filter_map(|t| lower.find(t.as_str())).min();
+    // Byte offset -> char offset (lower-casing can shift bytes but the prefix is close enough
+    // for choosing a window; clamp below keeps us in range).
+    let first_char = first_byte
+        .map(|b| lower[..b.min(lower.len())].chars().count())
+        .unwrap_or(0)
+        .min(chars.len());
+
+    let start = first_char.saturating_sub(SNIPPET_CHARS / 4);
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+    let mut excerpt: String = chars[start..end].iter().collect();
+    if start > 0 {
+        excerpt.insert_str(0, "… ");
+    }
+    if end < chars.len() {
+        excerpt.push_str(" …");
+    }
+    highlight(&excerpt, terms)
+}
+
+/// Transcript segments (`[{start,end,text}]` JSON) containing any of the terms.
+pub fn transcript_matches(segments_json: &str, terms: &[String]) -> Vec<TranscriptMatch> {
+    let segments: Vec<serde_json::Value> = match serde_json::from_str(segments_json) {
+        Ok(s) => s,
+        Err(_) => return Vec::new(),
+    };
+    segments
+        .iter()
+        .filter_map(|seg| {
+            let text = seg.get("text").and_then(|v| v.as_str())?.trim();
+            let lower = text.to_lowercase();
+            if !terms.iter().any(|t| lower.contains(t.as_str())) {
+                return None;
+            }
+            let start = seg.get("start").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            let end = seg.get("end").and_then(|v| v.as_f64()).unwrap_or(start);
+            Some(TranscriptMatch { start, end, snippet: highlight(text, terms) })
+        })
+        .take(MAX_TRANSCRIPT_MATCHES)
+        .collect()
+}
+
+/// Relevance-ranked full-text search across the user's episodes, podcast names and generated
+/// transcripts.
+/// Returns the page of hits plus the total hit count.
+pub async fn search_episodes(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> AppResult<(Vec<SearchHit>, i64)> {
+    let terms = query_terms(query);
+    let mut hits = Vec::new();
+    let mut total = 0i64;
+
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let rows = sqlx::query(
+                r#"
+                WITH q AS (SELECT websearch_to_tsquery('simple', $1) AS query),
+                matched AS (
+                    SELECT e.episodeid FROM "Episodes" e, q WHERE e.searchvector @@ q.query
+                    UNION
+                    SELECT e.episodeid FROM "EpisodeTranscripts" t
+                    JOIN "Episodes" e ON e.feedepisodeid = t.feedepisodeid, q
+                    WHERE t.status = 'complete' AND t.searchvector @@ q.query
+                    UNION
+                    SELECT t.episodeid FROM "EpisodeTranscripts" t, q
+                    WHERE t.episodeid IS NOT NULL AND t.status = 'complete' AND t.searchvector @@ q.query
+                    UNION
+                    SELECT e.episodeid FROM "Podcasts" p
+                    JOIN "Episodes" e ON e.podcastid = p.podcastid, q
+                    WHERE p.userid = $2 AND p.searchvector @@ q.query
+                )
+                SELECT e.episodeid, e.episodetitle, e.episodedescription, e.episodeurl,
+                       e.episodeartwork, e.episodepubdate, e.episodeduration,
+                       p.podcastid, p.podcastname,
+                       (ts_rank(e.searchvector, q.query)
+                        + 0.5 * COALESCE(ts_rank(t.searchvector, q.query), 0)
+                        + 0.3 * ts_rank(p.searchvector, q.query))::float8 AS relevance,
+                       CASE WHEN t.searchvector @@ q.query THEN t.segments::text END AS segments,
+                       COUNT(*) OVER() AS total_count
+                FROM matched m
+                JOIN "Episodes" e ON e.episodeid = m.episodeid
+                JOIN "Podcasts" p ON p.podcastid = e.podcastid
+                CROSS JOIN q
+                LEFT JOIN LATERAL (
+                    SELECT searchvector, segments FROM (
+                        SELECT searchvector, segments, createdat FROM "EpisodeTranscripts"
+                        WHERE feedepisodeid = e.feedepisodeid AND status = 'complete'
+                        UNION ALL
+                        SELECT searchvector, segments, createdat FROM "EpisodeTranscripts"
+                        WHERE episodeid = e.episodeid AND status = 'complete'
+                    ) own
+                    ORDER BY createdat DESC LIMIT 1
+                ) t ON TRUE
+                WHERE p.userid = $2
+                ORDER BY relevance DESC, e.episodepubdate DESC
+                LIMIT $3 OFFSET $4
+                "#,
+            )
+            .bind(query)
+            .bind(user_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await?;
+
+            for row in rows {
+                total = row.try_get("total_count")?;
+                let pub_date = row
+                    .try_get::<Option<chrono::NaiveDateTime>, _>("episodepubdate")?
+                    .map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string());
+                hits.push(build_hit(
+                    &terms,
+                    row.try_get("episodeid")?,
+                    row.try_get("episodetitle")?,
+                    row.try_get::<Option<String>, _>("episodedescription")?.unwrap_or_default(),
+                    row.try_get("episodeurl")?,
+                    row.try_get::<Option<String>, _>("episodeartwork")?.unwrap_or_default(),
+                    pub_date,
+                    row.try_get("episodeduration")?,
+                    row.try_get("podcastid")?,
+                    row.try_get("podcastname")?,
+                    row.try_get("relevance")?,
+                    row.try_get("segments")?,
+                ));
+            }
+        }
+        DatabasePool::MySQL(pool) => {
+            // MATCH() column lists must be identical to the FULLTEXT indexes from migrations 060
+            // and 076. Each branch of `m` matches one index on its own; the outer query only
+            // scores the episodes they found.
+            let rows = sqlx::query(
+                "SELECT e.EpisodeID, e.EpisodeTitle, e.EpisodeDescription, e.EpisodeURL,
+                        e.EpisodeArtwork, e.EpisodePubDate, e.EpisodeDuration,
+                        p.PodcastID, p.PodcastName,
+                        MATCH(e.EpisodeTitle, e.EpisodeDescription) AGAINST (? IN NATURAL LANGUAGE MODE)
+                            + 0.5 * COALESCE(MATCH(t.TranscriptText) AGAINST (? IN NATURAL LANGUAGE MODE), 0)
+                            + 0.3 * MATCH(p.PodcastName) AGAINST (? IN NATURAL LANGUAGE MODE)
+                            AS Relevance,
+                        CASE WHEN MATCH(t.TranscriptText) AGAINST (? IN NATURAL LANGUAGE MODE) > 0
+                             THEN CAST(t.Segments AS CHAR) END AS SegmentsText,
+                        COUNT(*) OVER() AS TotalCount
+                 FROM (
+                     SELECT e.EpisodeID FROM Episodes e
+                     WHERE MATCH(e.EpisodeTitle, e.EpisodeDescription) AGAINST (? IN NATURAL LANGUAGE MODE)
+                     UNION
+                     SELECT e.EpisodeID FROM EpisodeTranscripts t
+                     JOIN Episodes e ON e.FeedEpisodeID = t.FeedEpisodeID
+                     WHERE t.Status = 'complete' AND MATCH(t.TranscriptText) AGAINST (? IN NATURAL LANGUAGE MODE)
+                     UNION
+                     SELECT t.EpisodeID FROM EpisodeTranscripts t
+                     WHERE t.EpisodeID IS NOT NULL AND t.Status = 'complete'
+                       AND MATCH(t.TranscriptText) AGAINST (? IN NATURAL LANGUAGE MODE)
+                     UNION
+                     SELECT e.EpisodeID FROM Podcasts p
+                     JOIN Episodes e ON e.PodcastID = p.PodcastID
+                     WHERE p.UserID = ? AND MATCH(p.PodcastName) AGAINST (? IN NATURAL LANGUAGE MODE)
+                 ) m
+                 JOIN Episodes e ON e.EpisodeID = m.EpisodeID
+                 JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                 LEFT JOIN EpisodeTranscripts t ON t.TranscriptID = GREATEST(
+                     COALESCE((SELECT MAX(t2.TranscriptID) FROM EpisodeTranscripts t2
+                               WHERE t2.FeedEpisodeID = e.FeedEpisodeID AND t2.Status = 'complete'), 0),
+                     COALESCE((SELECT MAX(t3.TranscriptID) FROM EpisodeTranscripts t3
+                               WHERE t3.EpisodeID = e.EpisodeID AND t3.Status = 'complete'), 0)
+                 )
+                 WHERE p.UserID = ?
+                 ORDER BY Relevance DESC, e.EpisodePubDate DESC
+                 LIMIT ? OFFSET ?",
+            )
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(query)
+            .bind(user_id)
+            .bind(query)
+            .bind(user_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await?;
+
+            for row in rows {
+                total = row.try_get("TotalCount")?;
+                let pub_date = row
+                    .try_get::<Option<chrono::NaiveDateTime>, _>("EpisodePubDate")?
+                    .map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string());
+                hits.push(build_hit(
+                    &terms,
+                    row.try_get("EpisodeID")?,
+                    row.try_get("EpisodeTitle")?,
+                    row.try_get::<Option<String>, _>("EpisodeDescription")?.unwrap_or_default(),
+                    row.try_get("EpisodeURL")?,
+                    row.try_get::<Option<String>, _>("EpisodeArtwork")?.unwrap_or_default(),
+                    pub_date,
+                    row.try_get("EpisodeDuration")?,
+                    row.try_get("PodcastID")?,
+                    row.try_get("PodcastName")?,
+                    row.try_get("Relevance")?,
+                    row.try_get("SegmentsText")?,
+                ));
+            }
+        }
+    }
+
+    Ok((hits, total))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_hit(
+    terms: &[String],
+    episodeid: i32,
+    episodetitle: String,
+    description: String,
+    episodeurl: String,
+    episodeartwork: String,
+    episodepubdate: Option<String>,
+    episodeduration: i32,
+    podcastid: i32,
+    podcastname: String,
+    relevance: f64,
+    segments: Option<String>,
+) -> SearchHit {
+    SearchHit {
+        title_highlight: highlight(&episodetitle, terms),
+        snippet: snippet(&description, terms),
+        transcript_matches: segments
+            .map(|s| transcript_matches(&s, terms))
+            .unwrap_or_default(),
+        episodeid,
+        episodetitle,
+        episodeurl,
+        episodeartwork,
+        episodepubdate,
+        episodeduration,
+        podcastid,
+        podcastname,
+        relevance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_terms_drop_negations_and_quotes() {
+        assert_eq!(query_terms(r#""Rust async" -python or Tokio"#), vec!["rust", "async", "tokio"]);
+    }
+
+    #[test]
+    fn highlight_is_case_insensitive_and_prefers_longest_term() {
+        let terms = vec!["rust".to_string(), "rustacean".to_string()];
+        assert_eq!(
+            highlight("Rustaceans love RUST", &terms),
+            "<mark>Rustacean</mark>s love <mark>RUST</mark>"
+        );
+    }
+
+    #[test]
+    fn highlight_escapes_feed_markup() {
+        let terms = vec!["episode".to_string()];
+        let title = r#"Episode <img src=x onerror="alert(1)">"#;
+        assert_eq!(
+            highlight(title, &terms),
+            "<mark>Episode</mark> &lt;img src=x onerror=&quot;alert(1)&quot;&gt;"
+        );
+        // Entity-encoded markup in a description stays text after strip_html decodes it
+        let s = snippet("<p>An episode &lt;script&gt;alert(1)&lt;/script&gt;</p>", &terms);
+        assert!(!s.contains("<script"));
+        assert!(s.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn snippet_strips_html_and_windows_around_match() {
+        let long = format!("<p>{}needle{}</p>", "a ".repeat(200), " b".repeat(200));
+        let s = snippet(&long, &["needle".to_string()]);
+        assert!(s.contains("<mark>needle</mark>"));
+        assert!(!s.contains("<p>"));
+        assert!(s.starts_with("… ") && s.ends_with(" …"));
+    }
+
+    #[test]
+    fn transcript_matches_carry_timestamps() {
+        let segs = r#"[{"start":0.0,"end":4.5,"text":"Welcome back"},
+                      {"start":61.2,"end":65.0,"text":"today we talk about Kubernetes"}]"#;
+        let m = transcript_matches(segs, &["kubernetes".to_string()]);
+        assert_eq!(m.len(), 1);
+        assert_eq!(m[0].start, 61.2);
+        assert!(m[0].snippet.contains("<mark>Kubernetes</mark>"));
+    }
+}
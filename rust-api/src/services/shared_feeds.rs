@@ -0,0 +1,309 @@
+//! Shared canonical feed layer: one `Feeds` row per distinct feed (URL + credentials) and one
+//! `FeedEpisodes` row per distinct episode within it, regardless of how many users subscribe.
+//!
+//! `Podcasts` and `Episodes` stay per-user and act as the subscription/state layer on top:
+//! `Podcasts.FeedID` and `Episodes.FeedEpisodeID` link each user's rows to the shared identity.
+//! Content-level work (transcripts, ad and silence detection) is stored against the
+//! `FeedEpisodeID` itself (migration 075), so it runs once per feed episode, every subscriber
+//! reads the same result, and it outlives any one subscriber's `Episodes` row.
+//!
+//! Keys are computed in SQL (MD5 is available on both engines) so the migration 063 backfill and
+//! the runtime linker produce identical values. Keep [`PG_EPISODE_KEY`]/[`MYSQL_EPISODE_KEY`] in
+//! step with that migration.
+
+use crate::database::DatabasePool;
+use crate::error::AppResult;
+use sqlx::Row;
+
+/// Episode identity within a feed: the GUID when present, else the enclosure URL without its
+/// query string, else the title. Mirrors the dedup order used when refreshing a feed.
+const PG_EPISODE_KEY: &str = "md5(CASE WHEN COALESCE(e.episodeguid, '') <> '' THEN 'g:' || e.episodeguid \
+     WHEN COALESCE(e.episodeurl, '') <> '' THEN 'u:' || split_part(e.episodeurl, '?', 1) \
+     ELSE 't:' || COALESCE(e.episodetitle, '') END)";
+const MYSQL_EPISODE_KEY: &str = "MD5(CASE WHEN COALESCE(e.EpisodeGUID, '') <> '' THEN CONCAT('g:', e.EpisodeGUID) \
+     WHEN COALESCE(e.EpisodeURL, '') <> '' THEN CONCAT('u:', SUBSTRING_INDEX(e.EpisodeURL, '?', 1)) \
+     ELSE CONCAT('t:', COALESCE(e.EpisodeTitle, '')) END)";
+
+/// Link a podcast and any of its episodes not yet attached to the shared layer, creating the
+/// `Feeds`/`FeedEpisodes` rows they need. Set-based and idempotent; a podcast whose episodes are
+/// all linked costs three no-op statements.
+pub async fn link_podcast_episodes(db_pool: &DatabasePool, podcast_id: i32) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"INSERT INTO "Feeds" (feedkey, feedurl, feedusername)
+                   SELECT md5(feedurl || chr(10) || COALESCE(username, '')), feedurl, username
+                   FROM "Podcasts" WHERE podcastid = $1 AND feedid IS NULL
+                   ON CONFLICT (feedkey) DO NOTHING"#,
+            )
+            .bind(podcast_id)
+            .execute(pool)
+            .await?;
+            sqlx::query(
+                r#"UPDATE "Podcasts" p SET feedid = f.feedid
+                   FROM "Feeds" f
+                   WHERE p.podcastid = $1 AND p.feedid IS NULL
+                     AND f.feedkey = md5(p.feedurl || chr(10) || COALESCE(p.username, ''))"#,
+            )
+            .bind(podcast_id)
+            .execute(pool)
+            .await?;
+
+            let insert = format!(
+                r#"INSERT INTO "FeedEpisodes" (feedid, episodekey)
+                   SELECT DISTINCT p.feedid, {key}
+                   FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+                   WHERE e.podcastid = $1 AND e.feedepisodeid IS NULL AND p.feedid IS NOT NULL
+                   ON CONFLICT (feedid, episodekey) DO NOTHING"#,
+                key = PG_EPISODE_KEY
+            );
+            sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                .bind(podcast_id)
+                .execute(pool)
+                .await?;
+            let link = format!(
+                r#"UPDATE "Episodes" e SET feedepisodeid = fe.feedepisodeid
+                   FROM "Podcasts" p, "FeedEpisodes" fe
+                   WHERE e.podcastid = $1 AND e.feedepisodeid IS NULL
+                     AND p.podcastid = e.podcastid AND fe.feedid = p.feedid
+                     AND fe.episodekey = {key}"#,
+                key = PG_EPISODE_KEY
+            );
+            sqlx::query(sqlx::AssertSqlSafe(link.as_str()))
+                .bind(podcast_id)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "INSERT IGNORE INTO Feeds (FeedKey, FeedURL, FeedUsername)
+                 SELECT MD5(CONCAT(FeedURL, CHAR(10 USING utf8mb4), COALESCE(Username, ''))), FeedURL, Username
+                 FROM Podcasts WHERE PodcastID = ? AND FeedID IS NULL",
+            )
+            .bind(podcast_id)
+            .execute(pool)
+            .await?;
+            sqlx::query(
+                "UPDATE Podcasts p
+                 JOIN Feeds f ON f.FeedKey = MD5(CONCAT(p.FeedURL, CHAR(10 USING utf8mb4), COALESCE(p.Username, '')))
+                 SET p.FeedID = f.FeedID
+                 WHERE p.PodcastID = ? AND p.FeedID IS NULL",
+            )
+            .bind(podcast_id)
+            .execute(pool)
+            .await?;
+
+            let insert = format!(
+                "INSERT IGNORE INTO FeedEpisodes (FeedID, EpisodeKey)
+                 SELECT DISTINCT p.FeedID, {key}
+                 FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                 WHERE e.PodcastID = ? AND e.FeedEpisodeID IS NULL AND p.FeedID IS NOT NULL",
+                key = MYSQL_EPISODE_KEY
+            );
+            sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                .bind(podcast_id)
+                .execute(pool)
+                .await?;
+            let link = format!(
+                "UPDATE Episodes e
+                 JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                 JOIN FeedEpisodes fe ON fe.FeedID = p.FeedID AND fe.EpisodeKey = {key}
+                 SET e.FeedEpisodeID = fe.FeedEpisodeID
+                 WHERE e.PodcastID = ? AND e.FeedEpisodeID IS NULL",
+                key = MYSQL_EPISODE_KEY
+            );
+            sqlx::query(sqlx::AssertSqlSafe(link.as_str()))
+                .bind(podcast_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Where content-level results for an episode are stored: the `FeedEpisodeID` column of
+/// `EpisodeTranscripts`/`EpisodeSkipSegments` when the episode is linked, else its own `EpisodeID`
+/// (YouTube and local-folder episodes, or rows inserted before the next refresh links them).
+/// User reviews in `EpisodeAdSkipReview` hang off the segments and follow them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKey {
+    FeedEpisode(i32),
+    Episode(i32),
+}
+
+impl ContentKey {
+    pub fn id(self) -> i32 {
+        match self {
+            ContentKey::FeedEpisode(id) | ContentKey::Episode(id) => id,
+        }
+    }
+
+    /// Key column on the content tables (PostgreSQL spelling).
+    pub fn pg_column(self) -> &'static str {
+        match self {
+            ContentKey::FeedEpisode(_) => "feedepisodeid",
+            ContentKey::Episode(_) => "episodeid",
+        }
+    }
+
+    /// Key column on the content tables (MySQL spelling).
+    pub fn mysql_column(self) -> &'static str {
+        match self {
+            ContentKey::FeedEpisode(_) => "FeedEpisodeID",
+            ContentKey::Episode(_) => "EpisodeID",
+        }
+    }
+
+    fn pg_flag_table(self) -> &'static str {
+        match self {
+            ContentKey::FeedEpisode(_) => r#""FeedEpisodes""#,
+            ContentKey::Episode(_) => r#""Episodes""#,
+        }
+    }
+
+    fn mysql_flag_table(self) -> &'static str {
+        match self {
+            ContentKey::FeedEpisode(_) => "FeedEpisodes",
+            ContentKey::Episode(_) => "Episodes",
+        }
+    }
+}
+
+/// Used in lock names, so two subscribers of one feed episode contend for the same lock.
+impl std::fmt::Display for ContentKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentKey::FeedEpisode(id) => write!(f, "fe{}", id),
+            ContentKey::Episode(id) => write!(f, "e{}", id),
+        }
+    }
+}
+
+/// Resolve where content-level results for `episode_id` live. Falls back to the episode itself
+/// when it is unlinked or the lookup fails.
+pub async fn content_key(db_pool: &DatabasePool, episode_id: i32) -> ContentKey {
+    let feed_episode_id = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"SELECT feedepisodeid FROM "Episodes" WHERE episodeid = $1"#)
+                .bind(episode_id)
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|r| r.try_get::<Option<i32>, _>("feedepisodeid").ok().flatten())
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("SELECT FeedEpisodeID FROM Episodes WHERE EpisodeID = ?")
+                .bind(episode_id)
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|r| r.try_get::<Option<i32>, _>("FeedEpisodeID").ok().flatten())
+        }
+    };
+    feed_episode_id.map_or(ContentKey::Episode(episode_id), ContentKey::FeedEpisode)
+}
+
+/// "Already analyzed" guards, kept next to the content key so re-analysis is skipped for every
+/// subscriber.
+#[derive(Debug, Clone, Copy)]
+pub enum ContentFlag {
+    AdsDetected,
+    SilenceDetected,
+}
+
+impl ContentFlag {
+    fn pg_column(self) -> &'static str {
+        match self {
+            ContentFlag::AdsDetected => "adsdetected",
+            ContentFlag::SilenceDetected => "silencedetected",
+        }
+    }
+
+    fn mysql_column(self) -> &'static str {
+        match self {
+            ContentFlag::AdsDetected => "AdsDetected",
+            ContentFlag::SilenceDetected => "SilenceDetected",
+        }
+    }
+}
+
+/// Whether `flag` is set for the content key. Errors read as unset, so the work is redone.
+pub async fn content_flag(db_pool: &DatabasePool, key: ContentKey, flag: ContentFlag) -> bool {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let query = format!(
+                "SELECT COALESCE({flag}, FALSE) AS d FROM {table} WHERE {column} = $1",
+                flag = flag.pg_column(),
+                table = key.pg_flag_table(),
+                column = key.pg_column()
+            );
+            sqlx::query(sqlx::AssertSqlSafe(query.as_str()))
+                .bind(key.id())
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|r| r.try_get::<bool, _>("d").ok())
+                .unwrap_or(false)
+        }
+        DatabasePool::MySQL(pool) => {
+            let query = format!(
+                "SELECT CAST(COALESCE({flag}, 0) AS SIGNED) AS d FROM {table} WHERE {column} = ?",
+                flag = flag.mysql_column(),
+                table = key.mysql_flag_table(),
+                column = key.mysql_column()
+            );
+            sqlx::query(sqlx::AssertSqlSafe(query.as_str()))
+                .bind(key.id())
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|r| r.try_get::<i64, _>("d").ok())
+                .map(|d| d != 0)
+                .unwrap_or(false)
+        }
+    }
+}
+
+/// Set `flag` for the content key.
+pub async fn set_content_flag(db_pool: &DatabasePool, key: ContentKey, flag: ContentFlag) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let query = format!(
+                "UPDATE {table} SET {flag} = TRUE WHERE {column} = $1",
+                flag = flag.pg_column(),
+                table = key.pg_flag_table(),
+                column = key.pg_column()
+            );
+            sqlx::query(sqlx::AssertSqlSafe(query.as_str())).bind(key.id()).execute(pool).await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            let query = format!(
+                "UPDATE {table} SET {flag} = TRUE WHERE {column} = ?",
+                flag = flag.mysql_column(),
+                table = key.mysql_flag_table(),
+                column = key.mysql_column()
+            );
+            sqlx::query(sqlx::AssertSqlSafe(query.as_str())).bind(key.id()).execute(pool).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_keys_pick_their_column() {
+        let linked = ContentKey::FeedEpisode(7);
+        let unlinked = ContentKey::Episode(7);
+        assert_eq!((linked.pg_column(), linked.mysql_column()), ("feedepisodeid", "FeedEpisodeID"));
+        assert_eq!((unlinked.pg_column(), unlinked.mysql_column()), ("episodeid", "EpisodeID"));
+        // Lock names must not collide between an episode and a feed episode with the same id
+        assert_ne!(linked.to_string(), unlinked.to_string());
+    }
+}
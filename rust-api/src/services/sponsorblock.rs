@@ -0,0 +1,486 @@
+//! SponsorBlock segments for YouTube subscriptions.
+//!
+//! Community-submitted ranges (sponsor reads, self-promotion, intros, outros, interaction
+//! reminders) are fetched from the SponsorBlock API when a channel is processed and stored in
+//! `EpisodeSkipSegments` against the video, with `Kind` set to the category and
+//! `Source='sponsorblock'`. Lookups use the hash-prefix endpoint, so the API only learns the first
+//! four hex digits of the video id's SHA-256.
+//!
+//! Like detected ads, whether a segment is skipped is per-user: each user picks an action per
+//! category in `UserSponsorBlockCategories`, and per-segment confirm/deny goes through
+//! `ad_detection::set_ad_segment_review`. Videos are re-queried while young, since most segments
+//! are submitted in the first days after upload.
+
+use crate::database::DatabasePool;
+use crate::services::ad_detection::SkipSegmentView;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Source tag written to `EpisodeSkipSegments.Source` for SponsorBlock segments.
+pub const SOURCE_SPONSORBLOCK: &str = "sponsorblock";
+
+/// Categories imported from SponsorBlock; each is stored as the segment's `Kind`.
+pub const CATEGORIES: &[&str] = &["sponsor", "selfpromo", "intro", "outro", "interaction"];
+
+/// Per-category actions: skip immediately, hold pending review, or hide the segment.
+pub const ACTION_SKIP: &str = "skip";
+pub const ACTION_REVIEW: &str = "review";
+pub const ACTION_OFF: &str = "off";
+
+pub const DEFAULT_API_URL: &str = "https://sponsor.ajay.app";
+
+/// SponsorBlock API base URL. `PINEPODS_SPONSORBLOCK_URL` points at a mirror or mock; setting it
+/// to an empty string turns lookups off.
+pub fn api_base_url() -> Option<String> {
+    match std::env::var("PINEPODS_SPONSORBLOCK_URL") {
+        Ok(v) if v.trim().is_empty() => None,
+        Ok(v) => Some(v.trim().trim_end_matches('/').to_string()),
+        Err(_) => Some(DEFAULT_API_URL.to_string()),
+    }
+}
+
+/// The action a user gets for a category they haven't configured.
+pub fn default_action(category: &str) -> &'static str {
+    if category == "sponsor" {
+        ACTION_SKIP
+    } else {
+        ACTION_REVIEW
+    }
+}
+
+/// One skip range for a video.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub category: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Deserialize)]
+struct ApiVideo {
+    #[serde(rename = "videoID")]
+    video_id: String,
+    #[serde(default)]
+    segments: Vec<ApiSegment>,
+}
+
+#[derive(Deserialize)]
+struct ApiSegment {
+    category: String,
+    segment: [f64; 2],
+    #[serde(rename = "actionType", default)]
+    action_type: Option<String>,
+}
+
+/// Fetch a video's skip segments from the SponsorBlock API at `base_url`. A video nobody has
+/// submitted segments for yields an empty list.
+pub async fn fetch_segments(base_url: &str, video_id: &str) -> Result<Vec<Segment>, String> {
+    let prefix = &hex::encode(Sha256::digest(video_id.as_bytes()))[..4];
+    let categories = serde_json::to_string(CATEGORIES).map_err(|e| e.to_string())?;
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(format!("{}/api/skipSegments/{}", base_url, prefix))
+        .query(&[("categories", categories.as_str()), ("actionType", "skip")])
+        .send()
+        .await
+        .map_err(|e| format!("SponsorBlock request failed: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    if !response.status().is_success() {
+        return Err(format!("SponsorBlock returned {}", response.status()));
+    }
+    let videos: Vec<ApiVideo> = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid SponsorBlock response: {}", e))?;
+
+    Ok(videos
+        .into_iter()
+        .filter(|v| v.video_id == video_id)
+        .flat_map(|v| v.segments)
+        .filter(|s| s.action_type.as_deref().is_none_or(|a| a == "skip"))
+        .filter(|s| CATEGORIES.contains(&s.category.as_str()) && s.segment[1] > s.segment[0])
+        .map(|s| Segment { category: s.category, start: s.segment[0], end: s.segment[1] })
+        .collect())
+}
+
+fn same_range(a: &Segment, b: &Segment) -> bool {
+    a.category == b.category && (a.start - b.start).abs() < 0.01 && (a.end - b.end).abs() < 0.01
+}
+
+/// Replace one video row's SponsorBlock segments. Unchanged segments keep their ids so users'
+/// reviews of them survive a re-query.
+async fn store_video_segments(db_pool: &DatabasePool, video_id: i32, segments: &[Segment]) -> Result<(), String> {
+    let existing: Vec<(i32, Segment)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT segmentid, kind, starttime, endtime FROM "EpisodeSkipSegments" WHERE videoid = $1 AND source = $2"#,
+        )
+        .bind(video_id).bind(SOURCE_SPONSORBLOCK)
+        .fetch_all(pool).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (
+            r.try_get::<i32, _>("segmentid").unwrap_or(0),
+            Segment {
+                category: r.try_get::<String, _>("kind").unwrap_or_default(),
+                start: r.try_get::<f64, _>("starttime").unwrap_or(0.0),
+                end: r.try_get::<f64, _>("endtime").unwrap_or(0.0),
+            },
+        ))
+        .collect(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT SegmentID AS segmentid, Kind AS kind, StartTime AS starttime, EndTime AS endtime
+             FROM EpisodeSkipSegments WHERE VideoID = ? AND Source = ?",
+        )
+        .bind(video_id).bind(SOURCE_SPONSORBLOCK)
+        .fetch_all(pool).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (
+            r.try_get::<i32, _>("segmentid").unwrap_or(0),
+            Segment {
+                category: r.try_get::<String, _>("kind").unwrap_or_default(),
+                start: r.try_get::<f64, _>("starttime").unwrap_or(0.0),
+                end: r.try_get::<f64, _>("endtime").unwrap_or(0.0),
+            },
+        ))
+        .collect(),
+    };
+
+    let stale = existing.iter().filter(|(_, old)| !segments.iter().any(|s| same_range(old, s)));
+    for (segment_id, _) in stale {
+        match db_pool {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"DELETE FROM "EpisodeSkipSegments" WHERE segmentid = $1"#)
+                    .bind(segment_id).execute(pool).await.map_err(|e| e.to_string())?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("DELETE FROM EpisodeSkipSegments WHERE SegmentID = ?")
+                    .bind(segment_id).execute(pool).await.map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    let added = segments.iter().filter(|s| !existing.iter().any(|(_, old)| same_range(old, s)));
+    for segment in added {
+        match db_pool {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"
+                    INSERT INTO "EpisodeSkipSegments" (videoid, kind, starttime, endtime, source)
+                    VALUES ($1, $2, $3, $4, $5)
+                "#)
+                .bind(video_id).bind(&segment.category).bind(segment.start).bind(segment.end).bind(SOURCE_SPONSORBLOCK)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("
+                    INSERT INTO EpisodeSkipSegments (VideoID, Kind, StartTime, EndTime, Source)
+                    VALUES (?, ?, ?, ?, ?)
+                ")
+                .bind(video_id).bind(&segment.category).bind(segment.start).bind(segment.end).bind(SOURCE_SPONSORBLOCK)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Look up a YouTube video's segments and store them on every subscriber's copy of the video.
+/// Returns the number of segments found.
+pub async fn refresh_video(db_pool: &DatabasePool, youtube_video_id: &str) -> Result<usize, String> {
+    let Some(base_url) = api_base_url() else {
+        return Ok(0);
+    };
+    let segments = fetch_segments(&base_url, youtube_video_id).await?;
+
+    let video_ids: Vec<i32> = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"UPDATE "YouTubeVideos" SET sponsorblockcheckedat = NOW() WHERE youtubevideoid = $1 RETURNING videoid"#)
+                .bind(youtube_video_id)
+                .fetch_all(pool).await.map_err(|e| e.to_string())?
+                .into_iter()
+                .filter_map(|r| r.try_get::<i32, _>("videoid").ok())
+                .collect()
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE YouTubeVideos SET SponsorBlockCheckedAt = NOW() WHERE YouTubeVideoID = ?")
+                .bind(youtube_video_id)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+            sqlx::query("SELECT VideoID FROM YouTubeVideos WHERE YouTubeVideoID = ?")
+                .bind(youtube_video_id)
+                .fetch_all(pool).await.map_err(|e| e.to_string())?
+                .into_iter()
+                .filter_map(|r| r.try_get::<i32, _>("VideoID").ok())
+                .collect()
+        }
+    };
+    for video_id in video_ids {
+        store_video_segments(db_pool, video_id, &segments).await?;
+    }
+    Ok(segments.len())
+}
+
+/// Query SponsorBlock for a channel's videos that were never checked, or that are under a week
+/// old and were last checked over six hours ago. Failures are logged and skipped.
+pub async fn refresh_podcast_videos(db_pool: &DatabasePool, podcast_id: i32) {
+    if api_base_url().is_none() {
+        return;
+    }
+    let due: Result<Vec<String>, sqlx::Error> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"
+            SELECT youtubevideoid FROM "YouTubeVideos"
+            WHERE podcastid = $1
+              AND (sponsorblockcheckedat IS NULL
+                   OR (publishedat > NOW() - INTERVAL '7 days' AND sponsorblockcheckedat < NOW() - INTERVAL '6 hours'))
+        "#)
+        .bind(podcast_id)
+        .fetch_all(pool)
+        .await
+        .map(|rows| rows.into_iter().filter_map(|r| r.try_get::<String, _>("youtubevideoid").ok()).collect()),
+        DatabasePool::MySQL(pool) => sqlx::query("
+            SELECT YouTubeVideoID FROM YouTubeVideos
+            WHERE PodcastID = ?
+              AND (SponsorBlockCheckedAt IS NULL
+                   OR (PublishedAt > NOW() - INTERVAL 7 DAY AND SponsorBlockCheckedAt < NOW() - INTERVAL 6 HOUR))
+        ")
+        .bind(podcast_id)
+        .fetch_all(pool)
+        .await
+        .map(|rows| rows.into_iter().filter_map(|r| r.try_get::<String, _>("YouTubeVideoID").ok()).collect()),
+    };
+    let due = match due {
+        Ok(due) => due,
+        Err(e) => {
+            warn!("Could not list videos due a SponsorBlock check for podcast {}: {}", podcast_id, e);
+            return;
+        }
+    };
+
+    for youtube_video_id in due {
+        match refresh_video(db_pool, &youtube_video_id).await {
+            Ok(n) => debug!("SponsorBlock: {} segment(s) for video {}", n, youtube_video_id),
+            Err(e) => warn!("SponsorBlock lookup failed for video {}: {}", youtube_video_id, e),
+        }
+    }
+}
+
+/// A user's action for every category, with defaults filled in.
+pub async fn get_category_actions(db_pool: &DatabasePool, user_id: i32) -> Result<BTreeMap<String, String>, String> {
+    let rows: Vec<(String, String)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT category, action FROM "UserSponsorBlockCategories" WHERE userid = $1"#,
+        )
+        .bind(user_id).fetch_all(pool).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (r.try_get("category").unwrap_or_default(), r.try_get("action").unwrap_or_default()))
+        .collect(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT Category AS category, Action AS action FROM UserSponsorBlockCategories WHERE UserID = ?",
+        )
+        .bind(user_id).fetch_all(pool).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (r.try_get("category").unwrap_or_default(), r.try_get("action").unwrap_or_default()))
+        .collect(),
+    };
+    let mut actions: BTreeMap<String, String> = CATEGORIES
+        .iter()
+        .map(|c| (c.to_string(), default_action(c).to_string()))
+        .collect();
+    for (category, action) in rows {
+        if let Some(current) = actions.get_mut(&category) {
+            *current = action;
+        }
+    }
+    Ok(actions)
+}
+
+/// Set a user's action for the given categories; unlisted categories are left alone.
+pub async fn set_category_actions(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    actions: &HashMap<String, String>,
+) -> Result<(), String> {
+    for (category, action) in actions {
+        if !CATEGORIES.contains(&category.as_str()) {
+            return Err(format!("unknown SponsorBlock category '{}'", category));
+        }
+        if ![ACTION_SKIP, ACTION_REVIEW, ACTION_OFF].contains(&action.as_str()) {
+            return Err("action must be 'skip', 'review' or 'off'".to_string());
+        }
+    }
+    for (category, action) in actions {
+        match db_pool {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query(r#"
+                    INSERT INTO "UserSponsorBlockCategories" (userid, category, action)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (userid, category) DO UPDATE SET action = EXCLUDED.action
+                "#)
+                .bind(user_id).bind(category).bind(action)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+            }
+            DatabasePool::MySQL(pool) => {
+                sqlx::query("
+                    INSERT INTO UserSponsorBlockCategories (UserID, Category, Action)
+                    VALUES (?, ?, ?)
+                    ON DUPLICATE KEY UPDATE Action = VALUES(Action)
+                ")
+                .bind(user_id).bind(category).bind(action)
+                .execute(pool).await.map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Effective status of a SponsorBlock segment for a user: a review wins, otherwise the category
+/// action decides. `None` means the category is off and the segment is hidden.
+fn segment_status(review_status: Option<String>, action: &str) -> Option<String> {
+    match (review_status, action) {
+        (_, ACTION_OFF) => None,
+        (Some(review), _) => Some(review),
+        (None, ACTION_SKIP) => Some("active".to_string()),
+        (None, _) => Some("pending".to_string()),
+    }
+}
+
+/// Read a YouTube video's skip segments for a user, in the same shape as
+/// `ad_detection::get_episode_skip_segments_for_user`.
+pub async fn get_video_skip_segments_for_user(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    video_id: i32,
+) -> Result<Vec<SkipSegmentView>, String> {
+    let rows: Vec<(SkipSegmentView, Option<String>)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"
+            SELECT s.segmentid, s.kind, s.starttime, s.endtime, s.source, r.status AS review_status
+            FROM "EpisodeSkipSegments" s
+            LEFT JOIN "EpisodeAdSkipReview" r ON r.segmentid = s.segmentid AND r.userid = $2
+            WHERE s.videoid = $1
+            ORDER BY s.starttime
+        "#)
+        .bind(video_id).bind(user_id)
+        .fetch_all(pool).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (
+            SkipSegmentView {
+                segment_id: r.try_get::<i32, _>("segmentid").unwrap_or(0),
+                kind: r.try_get::<String, _>("kind").unwrap_or_default(),
+                start_time: r.try_get::<f64, _>("starttime").unwrap_or(0.0),
+                end_time: r.try_get::<f64, _>("endtime").unwrap_or(0.0),
+                source: r.try_get::<String, _>("source").unwrap_or_default(),
+                status: None,
+            },
+            r.try_get::<Option<String>, _>("review_status").ok().flatten(),
+        ))
+        .collect(),
+        DatabasePool::MySQL(pool) => sqlx::query("
+            SELECT s.SegmentID AS segmentid, s.Kind AS kind, s.StartTime AS starttime,
+                   s.EndTime AS endtime, s.Source AS source, r.Status AS review_status
+            FROM EpisodeSkipSegments s
+            LEFT JOIN EpisodeAdSkipReview r ON r.SegmentID = s.SegmentID AND r.UserID = ?
+            WHERE s.VideoID = ?
+            ORDER BY s.StartTime
+        ")
+        .bind(user_id).bind(video_id)
+        .fetch_all(pool).await.map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|r| (
+            SkipSegmentView {
+                segment_id: r.try_get::<i32, _>("segmentid").unwrap_or(0),
+                kind: r.try_get::<String, _>("kind").unwrap_or_default(),
+                start_time: r.try_get::<f64, _>("starttime").unwrap_or(0.0),
+                end_time: r.try_get::<f64, _>("endtime").unwrap_or(0.0),
+                source: r.try_get::<String, _>("source").unwrap_or_default(),
+                status: None,
+            },
+            r.try_get::<Option<String>, _>("review_status").ok().flatten(),
+        ))
+        .collect(),
+    };
+    let actions = get_category_actions(db_pool, user_id).await?;
+
+    let mut segments = Vec::new();
+    for (mut segment, review_status) in rows {
+        if segment.source == SOURCE_SPONSORBLOCK {
+            let action = actions.get(&segment.kind).map(String::as_str).unwrap_or(ACTION_REVIEW);
+            match segment_status(review_status, action) {
+                Some(status) => segment.status = Some(status),
+                None => continue,
+            }
+        }
+        segments.push(segment);
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_server;
+    use axum::extract::{Path, Query};
+    use axum::routing::get;
+    use axum::{Json, Router};
+
+    /// Stands in for the SponsorBlock API: one known video under its hash prefix.
+    async fn mock_api() -> String {
+        async fn skip_segments(
+            Path(prefix): Path<String>,
+            Query(query): Query<HashMap<String, String>>,
+        ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+            let expected = &hex::encode(Sha256::digest(b"dQw4w9WgXcQ"))[..4];
+            if prefix != expected {
+                return Err(axum::http::StatusCode::NOT_FOUND);
+            }
+            assert!(query["categories"].contains("selfpromo"));
+            Ok(Json(serde_json::json!([
+                {
+                    "videoID": "dQw4w9WgXcQ",
+                    "segments": [
+                        {"category": "sponsor", "segment": [30.5, 95.0], "actionType": "skip", "UUID": "a"},
+                        {"category": "intro", "segment": [0.0, 12.0], "actionType": "skip", "UUID": "b"},
+                        {"category": "sponsor", "segment": [200.0, 230.0], "actionType": "mute", "UUID": "c"},
+                        {"category": "music_offtopic", "segment": [300.0, 320.0], "actionType": "skip", "UUID": "d"}
+                    ]
+                },
+                {
+                    "videoID": "someOtherId",
+                    "segments": [{"category": "sponsor", "segment": [1.0, 2.0], "actionType": "skip", "UUID": "e"}]
+                }
+            ])))
+        }
+
+        let app = Router::new().route("/api/skipSegments/{prefix}", get(skip_segments));
+        test_server::serve(app).await
+    }
+
+    #[tokio::test]
+    async fn fetches_skip_segments_for_the_requested_video_only() {
+        let base = mock_api().await;
+        let segments = fetch_segments(&base, "dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                Segment { category: "sponsor".into(), start: 30.5, end: 95.0 },
+                Segment { category: "intro".into(), start: 0.0, end: 12.0 },
+            ]
+        );
+        assert!(fetch_segments(&base, "unknownVideo").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn reviews_override_category_actions() {
+        assert_eq!(segment_status(None, ACTION_SKIP).as_deref(), Some("active"));
+        assert_eq!(segment_status(None, ACTION_REVIEW).as_deref(), Some("pending"));
+        assert_eq!(segment_status(Some("rejected".into()), ACTION_SKIP).as_deref(), Some("rejected"));
+        assert_eq!(segment_status(Some("confirmed".into()), ACTION_OFF), None);
+        assert_eq!(default_action("sponsor"), ACTION_SKIP);
+        assert_eq!(default_action("outro"), ACTION_REVIEW);
+    }
+}
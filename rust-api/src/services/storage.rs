@@ -0,0 +1,586 @@
+//! Per-user download quotas and per-podcast retention rules for server downloads.
+//!
+//! `Users.DownloadQuotaBytes` caps what a user may hold under `/opt/pinepods/downloads` (NULL is
+//! unlimited) and `Users.QuotaPolicy` decides what a download that would exceed it does: `refuse`
+//! fails it, `evict` deletes the user's played downloads first and then the oldest until the new
+//! file fits. Per podcast, `KeepLastEpisodes` keeps only the newest N downloaded episodes and
+//! `DeleteAfterPlayed` drops a download once the episode is completed; both are applied by the
+//! scheduled cleanup next to the day-based `auto_delete_old_downloads`.
+//!
+//! Usage is the sum of `DownloadedSize` over the user's episode and video downloads, plus the
+//! bytes this replica's in-progress downloads have [reserved](Reservation). Local (`local://`)
+//! podcasts are user-managed media and are never evicted or pruned.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+
+/// A download's reservation grows in steps of this many bytes when its size wasn't known up front.
+const RESERVE_STEP_BYTES: i64 = 8 * 1024 * 1024;
+
+lazy_static::lazy_static! {
+    /// Bytes held by downloads in progress, by user.
+    static ref RESERVED: std::sync::Mutex<HashMap<i32, i64>> = std::sync::Mutex::new(HashMap::new());
+    /// Serializes checking the quota with taking a reservation, so two downloads can't both fit
+    /// into the same free space.
+    static ref RESERVE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+fn reserved_bytes(user_id: i32) -> i64 {
+    RESERVED.lock().unwrap().get(&user_id).copied().unwrap_or(0)
+}
+
+fn adjust_reserved(user_id: i32, delta: i64) {
+    let mut reserved = RESERVED.lock().unwrap();
+    let bytes = reserved.entry(user_id).or_insert(0);
+    *bytes += delta;
+    if *bytes <= 0 {
+        reserved.remove(&user_id);
+    }
+}
+
+pub const POLICY_REFUSE: &str = "refuse";
+pub const POLICY_EVICT: &str = "evict";
+
+/// A user's download quota.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct UserQuota {
+    /// Maximum bytes of server downloads; `None` is unlimited.
+    pub quota_bytes: Option<i64>,
+    /// `refuse` or `evict`.
+    pub policy: String,
+}
+
+/// Download usage for one podcast.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PodcastStorage {
+    pub podcast_id: i32,
+    pub podcast_name: String,
+    pub downloads: i64,
+    pub bytes: i64,
+}
+
+/// A user's download usage against their quota, broken down by podcast (largest first).
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct StorageReport {
+    pub user_id: i32,
+    pub username: String,
+    pub used_bytes: i64,
+    pub quota_bytes: Option<i64>,
+    pub policy: String,
+    pub podcasts: Vec<PodcastStorage>,
+}
+
+/// Per-podcast retention rules.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RetentionRules {
+    /// Keep only the newest N downloaded episodes; 0 keeps all.
+    pub keep_last_episodes: i32,
+    /// Delete a download once its episode is marked completed.
+    pub delete_after_played: bool,
+}
+
+/// One downloaded item that may be removed to honor a quota or retention rule.
+struct DownloadedItem {
+    id: i32,
+    is_video: bool,
+    location: Option<String>,
+    size: i64,
+}
+
+pub async fn get_quota(db_pool: &DatabasePool, user_id: i32) -> AppResult<UserQuota> {
+    let row = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT downloadquotabytes AS quota, COALESCE(quotapolicy, 'refuse') AS policy FROM "Users" WHERE userid = $1"#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| (r.try_get::<Option<i64>, _>("quota").ok().flatten(), r.try_get::<String, _>("policy").ok())),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT DownloadQuotaBytes AS quota, COALESCE(QuotaPolicy, 'refuse') AS policy FROM Users WHERE UserID = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| (r.try_get::<Option<i64>, _>("quota").ok().flatten(), r.try_get::<String, _>("policy").ok())),
+    };
+    let (quota_bytes, policy) = row.ok_or_else(|| AppError::not_found("User not found"))?;
+    Ok(UserQuota {
+        quota_bytes: quota_bytes.filter(|q| *q > 0),
+        policy: policy.unwrap_or_else(|| POLICY_REFUSE.to_string()),
+    })
+}
+
+/// Set (or with `None`, remove) a user's quota. Admin-only at the handler.
+pub async fn set_quota(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    quota_bytes: Option<i64>,
+    policy: &str,
+) -> AppResult<()> {
+    if policy != POLICY_REFUSE && policy != POLICY_EVICT {
+        return Err(AppError::bad_request("policy must be 'refuse' or 'evict'"));
+    }
+    let quota_bytes = quota_bytes.filter(|q| *q > 0);
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"UPDATE "Users" SET downloadquotabytes = $1, quotapolicy = $2 WHERE userid = $3"#)
+                .bind(quota_bytes)
+                .bind(policy)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE Users SET DownloadQuotaBytes = ?, QuotaPolicy = ? WHERE UserID = ?")
+                .bind(quota_bytes)
+                .bind(policy)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Bytes of server downloads (episodes and videos) currently held by a user.
+pub async fn used_bytes(db_pool: &DatabasePool, user_id: i32) -> AppResult<i64> {
+    let used = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT (COALESCE((SELECT SUM(downloadedsize) FROM "DownloadedEpisodes" WHERE userid = $1), 0)
+                     + COALESCE((SELECT SUM(downloadedsize) FROM "DownloadedVideos" WHERE userid = $1), 0))::BIGINT AS used"#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?
+        .try_get::<i64, _>("used")?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT CAST(COALESCE((SELECT SUM(DownloadedSize) FROM DownloadedEpisodes WHERE UserID = ?), 0)
+                       + COALESCE((SELECT SUM(DownloadedSize) FROM DownloadedVideos WHERE UserID = ?), 0) AS SIGNED) AS used",
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?
+        .try_get::<i64, _>("used")?,
+    };
+    Ok(used)
+}
+
+/// Usage report for one user.
+pub async fn storage_report(db_pool: &DatabasePool, user_id: i32) -> AppResult<StorageReport> {
+    let quota = get_quota(db_pool, user_id).await?;
+    let (username, podcasts) = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let username = sqlx::query(r#"SELECT username FROM "Users" WHERE userid = $1"#)
+                .bind(user_id)
+                .fetch_one(pool)
+                .await?
+                .try_get::<Option<String>, _>("username")?
+                .unwrap_or_default();
+            let rows = sqlx::query(
+                r#"SELECT p.podcastid, p.podcastname, COUNT(*) AS downloads, SUM(d.size)::BIGINT AS bytes
+                   FROM (
+                       SELECT e.podcastid, COALESCE(de.downloadedsize, 0)::BIGINT AS size
+                       FROM "DownloadedEpisodes" de JOIN "Episodes" e ON e.episodeid = de.episodeid
+                       WHERE de.userid = $1
+                       UNION ALL
+                       SELECT v.podcastid, COALESCE(dv.downloadedsize, 0)::BIGINT
+                       FROM "DownloadedVideos" dv JOIN "YouTubeVideos" v ON v.videoid = dv.videoid
+                       WHERE dv.userid = $1
+                   ) d
+                   JOIN "Podcasts" p ON p.podcastid = d.podcastid
+                   GROUP BY p.podcastid, p.podcastname
+                   ORDER BY bytes DESC"#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+            let mut podcasts = Vec::with_capacity(rows.len());
+            for row in rows {
+                podcasts.push(PodcastStorage {
+                    podcast_id: row.try_get("podcastid")?,
+                    podcast_name: row.try_get::<Option<String>, _>("podcastname")?.unwrap_or_default(),
+                    downloads: row.try_get("downloads")?,
+                    bytes: row.try_get::<Option<i64>, _>("bytes")?.unwrap_or(0),
+                });
+            }
+            (username, podcasts)
+        }
+        DatabasePool::MySQL(pool) => {
+            let username = sqlx::query("SELECT Username FROM Users WHERE UserID = ?")
+                .bind(user_id)
+                .fetch_one(pool)
+                .await?
+                .try_get::<Option<String>, _>("Username")?
+                .unwrap_or_default();
+            let rows = sqlx::query(
+                "SELECT p.PodcastID, p.PodcastName, COUNT(*) AS downloads, CAST(SUM(d.size) AS SIGNED) AS bytes
+                 FROM (
+                     SELECT e.PodcastID AS podcastid, COALESCE(de.DownloadedSize, 0) AS size
+                     FROM DownloadedEpisodes de JOIN Episodes e ON e.EpisodeID = de.EpisodeID
+                     WHERE de.UserID = ?
+                     UNION ALL
+                     SELECT v.PodcastID, COALESCE(dv.DownloadedSize, 0)
+                     FROM DownloadedVideos dv JOIN YouTubeVideos v ON v.VideoID = dv.VideoID
+                     WHERE dv.UserID = ?
+                 ) d
+                 JOIN Podcasts p ON p.PodcastID = d.podcastid
+                 GROUP BY p.PodcastID, p.PodcastName
+                 ORDER BY bytes DESC",
+            )
+            .bind(user_id)
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+            let mut podcasts = Vec::with_capacity(rows.len());
+            for row in rows {
+                podcasts.push(PodcastStorage {
+                    podcast_id: row.try_get("PodcastID")?,
+                    podcast_name: row.try_get::<Option<String>, _>("PodcastName")?.unwrap_or_default(),
+                    downloads: row.try_get("downloads")?,
+                    bytes: row.try_get::<Option<i64>, _>("bytes")?.unwrap_or(0),
+                });
+            }
+            (username, podcasts)
+        }
+    };
+
+    Ok(StorageReport {
+        user_id,
+        username,
+        used_bytes: podcasts.iter().map(|p| p.bytes).sum(),
+        quota_bytes: quota.quota_bytes,
+        policy: quota.policy,
+        podcasts,
+    })
+}
+
+/// Usage reports for every user, for the admin view.
+pub async fn storage_reports(db_pool: &DatabasePool) -> AppResult<Vec<StorageReport>> {
+    let user_ids: Vec<i32> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT userid FROM "Users" ORDER BY userid"#)
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .filter_map(|r| r.try_get("userid").ok())
+            .collect(),
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT UserID FROM Users ORDER BY UserID")
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .filter_map(|r| r.try_get("UserID").ok())
+            .collect(),
+    };
+    let mut reports = Vec::with_capacity(user_ids.len());
+    for user_id in user_ids {
+        reports.push(storage_report(db_pool, user_id).await?);
+    }
+    Ok(reports)
+}
+
+pub async fn get_retention(db_pool: &DatabasePool, podcast_id: i32) -> AppResult<RetentionRules> {
+    let rules = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT COALESCE(keeplastepisodes, 0) AS keep, COALESCE(deleteafterplayed, FALSE) AS played
+               FROM "Podcasts" WHERE podcastid = $1"#,
+        )
+        .bind(podcast_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| RetentionRules {
+            keep_last_episodes: r.try_get("keep").unwrap_or(0),
+            delete_after_played: r.try_get("played").unwrap_or(false),
+        }),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT COALESCE(KeepLastEpisodes, 0) AS keep, CAST(COALESCE(DeleteAfterPlayed, 0) AS SIGNED) AS played
+             FROM Podcasts WHERE PodcastID = ?",
+        )
+        .bind(podcast_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| RetentionRules {
+            keep_last_episodes: r.try_get("keep").unwrap_or(0),
+            delete_after_played: r.try_get::<i64, _>("played").unwrap_or(0) != 0,
+        }),
+    };
+    rules.ok_or_else(|| AppError::not_found("Podcast not found"))
+}
+
+/// Update a podcast's retention rules (owner-scoped by user_id, like the other per-podcast setters).
+pub async fn set_retention(
+    db_pool: &DatabasePool,
+    podcast_id: i32,
+    user_id: i32,
+    rules: &RetentionRules,
+) -> AppResult<()> {
+    let keep = rules.keep_last_episodes.max(0);
+    let affected = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"UPDATE "Podcasts" SET keeplastepisodes = $1, deleteafterplayed = $2 WHERE podcastid = $3 AND userid = $4"#,
+        )
+        .bind(keep)
+        .bind(rules.delete_after_played)
+        .bind(podcast_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "UPDATE Podcasts SET KeepLastEpisodes = ?, DeleteAfterPlayed = ? WHERE PodcastID = ? AND UserID = ?",
+        )
+        .bind(keep)
+        .bind(rules.delete_after_played)
+        .bind(podcast_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+    };
+    if affected == 0 {
+        return Err(AppError::not_found("Podcast not found"));
+    }
+    Ok(())
+}
+
+/// Remove one download's file and row.
+async fn remove(db_pool: &DatabasePool, user_id: i32, item: &DownloadedItem) -> AppResult<()> {
+    DatabasePool::remove_download_file(item.location.as_deref()).await;
+    db_pool.delete_episode(user_id, item.id, item.is_video).await
+}
+
+/// Make room for a download of `incoming_bytes` (0 when the size is not known yet) under the
+/// user's quota. Succeeds immediately without a quota. Over quota, the `evict` policy deletes
+/// played downloads first, then the oldest, until the new file fits; `refuse` (or running out of
+/// evictable downloads) fails with the usage in the message.
+pub async fn ensure_capacity(db_pool: &DatabasePool, user_id: i32, incoming_bytes: i64) -> AppResult<()> {
+    let quota = get_quota(db_pool, user_id).await?;
+    let Some(limit) = quota.quota_bytes else {
+        return Ok(());
+    };
+    let mut used = used_bytes(db_pool, user_id).await? + reserved_bytes(user_id);
+    // Unknown size: only refuse when the user is already at or past the limit.
+    let fits = |used: i64| if incoming_bytes > 0 { used + incoming_bytes <= limit } else { used < limit };
+    if fits(used) {
+        return Ok(());
+    }
+
+    if quota.policy == POLICY_EVICT {
+        let mut evicted = 0usize;
+        for item in eviction_candidates(db_pool, user_id).await? {
+            remove(db_pool, user_id, &item).await?;
+            used -= item.size;
+            evicted += 1;
+            if fits(used) {
+                tracing::info!("Quota: evicted {} download(s) for user {} to make room", evicted, user_id);
+                return Ok(());
+            }
+        }
+    }
+
+    Err(AppError::Conflict(format!(
+        "Download quota exceeded: {} of {} bytes used",
+        used, limit
+    )))
+}
+
+/// Bytes a download in progress holds against its user's quota, released when dropped (by then
+/// the finished download is recorded, or its partial file removed). Reservations are per replica.
+pub struct Reservation {
+    user_id: i32,
+    bytes: i64,
+}
+
+impl Reservation {
+    /// Make room for `incoming_bytes` (0 when unknown) as [`ensure_capacity`] does, and hold it.
+    pub async fn take(db_pool: &DatabasePool, user_id: i32, incoming_bytes: i64) -> AppResult<Self> {
+        let mut reservation = Reservation { user_id, bytes: 0 };
+        reservation.grow(db_pool, incoming_bytes).await?;
+        Ok(reservation)
+    }
+
+    /// Call as bytes arrive with the total written so far. Grows the reservation when the
+    /// download outruns it, failing once the quota can't cover it.
+    pub async fn cover(&mut self, db_pool: &DatabasePool, written: i64) -> AppResult<()> {
+        if written <= self.bytes {
+            return Ok(());
+        }
+        self.grow(db_pool, written.max(self.bytes + RESERVE_STEP_BYTES)).await
+    }
+
+    async fn grow(&mut self, db_pool: &DatabasePool, bytes: i64) -> AppResult<()> {
+        let extra = bytes - self.bytes;
+        let _guard = RESERVE_LOCK.lock().await;
+        ensure_capacity(db_pool, self.user_id, extra).await?;
+        adjust_reserved(self.user_id, extra.max(0));
+        self.bytes += extra.max(0);
+        Ok(())
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        adjust_reserved(self.user_id, -self.bytes);
+    }
+}
+
+/// A user's downloads in eviction order: completed episodes first, then oldest download first.
+async fn eviction_candidates(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<DownloadedItem>> {
+    let rows = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT id, is_video, location, size FROM (
+                   SELECT de.episodeid AS id, FALSE AS is_video, de.downloadedlocation AS location,
+                          COALESCE(de.downloadedsize, 0)::BIGINT AS size, COALESCE(e.completed, FALSE) AS completed,
+                          de.downloadeddate AS downloaded, p.feedurl
+                   FROM "DownloadedEpisodes" de
+                   JOIN "Episodes" e ON e.episodeid = de.episodeid
+                   JOIN "Podcasts" p ON p.podcastid = e.podcastid
+                   WHERE de.userid = $1
+                   UNION ALL
+                   SELECT dv.videoid, TRUE, dv.downloadedlocation,
+                          COALESCE(dv.downloadedsize, 0)::BIGINT, COALESCE(v.completed, FALSE),
+                          dv.downloadeddate, p.feedurl
+                   FROM "DownloadedVideos" dv
+                   JOIN "YouTubeVideos" v ON v.videoid = dv.videoid
+                   JOIN "Podcasts" p ON p.podcastid = v.podcastid
+                   WHERE dv.userid = $1
+               ) d
+               WHERE feedurl NOT LIKE 'local://%'
+               ORDER BY completed DESC, downloaded ASC"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|r| DownloadedItem {
+            id: r.try_get("id").unwrap_or(0),
+            is_video: r.try_get("is_video").unwrap_or(false),
+            location: r.try_get("location").ok().flatten(),
+            size: r.try_get("size").unwrap_or(0),
+        })
+        .collect(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT id, is_video, location, size FROM (
+                 SELECT de.EpisodeID AS id, 0 AS is_video, de.DownloadedLocation AS location,
+                        CAST(COALESCE(de.DownloadedSize, 0) AS SIGNED) AS size, COALESCE(e.Completed, 0) AS completed,
+                        de.DownloadedDate AS downloaded, p.FeedURL AS feedurl
+                 FROM DownloadedEpisodes de
+                 JOIN Episodes e ON e.EpisodeID = de.EpisodeID
+                 JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                 WHERE de.UserID = ?
+                 UNION ALL
+                 SELECT dv.VideoID, 1, dv.DownloadedLocation,
+                        CAST(COALESCE(dv.DownloadedSize, 0) AS SIGNED), COALESCE(v.Completed, 0),
+                        dv.DownloadedDate, p.FeedURL
+                 FROM DownloadedVideos dv
+                 JOIN YouTubeVideos v ON v.VideoID = dv.VideoID
+                 JOIN Podcasts p ON p.PodcastID = v.PodcastID
+                 WHERE dv.UserID = ?
+             ) d
+             WHERE feedurl NOT LIKE 'local://%'
+             ORDER BY completed DESC, downloaded ASC",
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|r| DownloadedItem {
+            id: r.try_get("id").unwrap_or(0),
+            is_video: r.try_get::<i64, _>("is_video").unwrap_or(0) != 0,
+            location: r.try_get("location").ok().flatten(),
+            size: r.try_get("size").unwrap_or(0),
+        })
+        .collect(),
+    };
+    Ok(rows)
+}
+
+/// Apply every podcast's keep-last-N and delete-after-played rules to its downloaded episodes.
+/// Run by the scheduled cleanup.
+pub async fn apply_retention_rules(db_pool: &DatabasePool) -> AppResult<()> {
+    // (user_id, podcast_id, item, completed, keep_last, delete_after_played), newest first per podcast.
+    let rows: Vec<(i32, i32, DownloadedItem, bool, i32, bool)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT de.userid, p.podcastid, de.episodeid, de.downloadedlocation,
+                      COALESCE(de.downloadedsize, 0)::BIGINT AS size, COALESCE(e.completed, FALSE) AS completed,
+                      COALESCE(p.keeplastepisodes, 0) AS keep, COALESCE(p.deleteafterplayed, FALSE) AS played
+               FROM "DownloadedEpisodes" de
+               JOIN "Episodes" e ON e.episodeid = de.episodeid
+               JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               WHERE (p.keeplastepisodes > 0 OR p.deleteafterplayed = TRUE)
+                 AND p.feedurl NOT LIKE 'local://%'
+               ORDER BY de.userid, p.podcastid, e.episodepubdate DESC"#,
+        )
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|r| (
+            r.try_get("userid").unwrap_or(0),
+            r.try_get("podcastid").unwrap_or(0),
+            DownloadedItem {
+                id: r.try_get("episodeid").unwrap_or(0),
+                is_video: false,
+                location: r.try_get("downloadedlocation").ok().flatten(),
+                size: r.try_get("size").unwrap_or(0),
+            },
+            r.try_get("completed").unwrap_or(false),
+            r.try_get("keep").unwrap_or(0),
+            r.try_get("played").unwrap_or(false),
+        ))
+        .collect(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT de.UserID, p.PodcastID, de.EpisodeID, de.DownloadedLocation,
+                    CAST(COALESCE(de.DownloadedSize, 0) AS SIGNED) AS size,
+                    CAST(COALESCE(e.Completed, 0) AS SIGNED) AS completed,
+                    COALESCE(p.KeepLastEpisodes, 0) AS keep,
+                    CAST(COALESCE(p.DeleteAfterPlayed, 0) AS SIGNED) AS played
+             FROM DownloadedEpisodes de
+             JOIN Episodes e ON e.EpisodeID = de.EpisodeID
+             JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             WHERE (p.KeepLastEpisodes > 0 OR p.DeleteAfterPlayed = 1)
+               AND p.FeedURL NOT LIKE 'local://%'
+             ORDER BY de.UserID, p.PodcastID, e.EpisodePubDate DESC",
+        )
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|r| (
+            r.try_get("UserID").unwrap_or(0),
+            r.try_get("PodcastID").unwrap_or(0),
+            DownloadedItem {
+                id: r.try_get("EpisodeID").unwrap_or(0),
+                is_video: false,
+                location: r.try_get("DownloadedLocation").ok().flatten(),
+                size: r.try_get("size").unwrap_or(0),
+            },
+            r.try_get::<i64, _>("completed").unwrap_or(0) != 0,
+            r.try_get("keep").unwrap_or(0),
+            r.try_get::<i64, _>("played").unwrap_or(0) != 0,
+        ))
+        .collect(),
+    };
+
+    let mut removed = 0usize;
+    let mut current: Option<(i32, i32)> = None;
+    let mut rank = 0;
+    for (user_id, podcast_id, item, completed, keep_last, delete_after_played) in rows {
+        if current != Some((user_id, podcast_id)) {
+            current = Some((user_id, podcast_id));
+            rank = 0;
+        }
+        rank += 1;
+        let expired = (delete_after_played && completed) || (keep_last > 0 && rank > keep_last);
+        if expired {
+            remove(db_pool, user_id, &item).await?;
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        tracing::info!("Retention: removed {} download(s) per keep-last/delete-after-played rules", removed);
+    }
+    Ok(())
+}
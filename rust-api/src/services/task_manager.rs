@@ -14,6 +14,27 @@ pub enum TaskStatus {
     Completed,
     #[serde(rename = "FAILED")]
     Failed,
+    #[serde(rename = "CANCELLED")]
+    Cancelled,
+}
+
+/// Task keys expire a week after their last update.
+const TASK_TTL_SECONDS: u64 = 86400 * 7;
+/// Status names used as `/api/metrics` labels and in the status index keys.
+const TASK_STATUSES: [&str; 5] = ["pending", "running", "completed", "failed", "cancelled"];
+
+fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "pending",
+        TaskStatus::Running => "running",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Failed => "failed",
+        TaskStatus::Cancelled => "cancelled",
+    }
+}
+
+fn status_index_key(status: &str) -> String {
+    format!("task_status:{}", status)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -33,7 +54,7 @@ pub struct TaskInfo {
     pub podcast_name: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskUpdate {
     pub task_id: String,
     pub user_id: i32,
@@ -59,6 +80,16 @@ pub struct WebSocketMessage {
 pub type TaskProgressSender = broadcast::Sender<TaskUpdate>;
 pub type TaskProgressReceiver = broadcast::Receiver<TaskUpdate>;
 
+/// Hand an update published on the task updates channel to this replica's subscribers.
+fn relay_update(sender: &TaskProgressSender, payload: &str) {
+    match serde_json::from_str::<TaskUpdate>(payload) {
+        Ok(update) => {
+            let _ = sender.send(update);
+        }
+        Err(e) => tracing::warn!("Ignoring malformed task update: {}", e),
+    }
+}
+
 #[derive(Clone)]
 pub struct TaskManager {
     redis: RedisClient,
@@ -79,6 +110,33 @@ impl TaskManager {
         self.progress_sender.subscribe()
     }
 
+    /// Re-broadcast updates published by any replica (including this one) to local websocket
+    /// subscribers. Called once at startup.
+    pub fn spawn_fanout(&self) {
+        let sender = self.progress_sender.clone();
+        crate::services::cluster::spawn_subscriber(
+            self.redis.clone(),
+            crate::services::cluster::TASK_UPDATES_CHANNEL,
+            move |payload| relay_update(&sender, &payload),
+        );
+    }
+
+    /// Send an update to every replica's websocket subscribers, or only to this replica's if
+    /// Redis can't take it.
+    async fn broadcast(&self, update: TaskUpdate) {
+        let published = match serde_json::to_string(&update) {
+            Ok(payload) => self
+                .redis
+                .publish(crate::services::cluster::TASK_UPDATES_CHANNEL, &payload)
+                .await
+                .is_ok(),
+            Err(_) => false,
+        };
+        if !published {
+            let _ = self.progress_sender.send(update);
+        }
+    }
+
     pub async fn create_task(
         &self,
         task_type: String,
@@ -122,7 +180,7 @@ impl TaskManager {
             started_at: chrono::Utc::now().to_rfc3339(),
             completed_at: None,
         };
-        let _ = self.progress_sender.send(update);
+        self.broadcast(update).await;
 
         Ok(task_id)
     }
@@ -163,7 +221,7 @@ impl TaskManager {
             started_at: chrono::Utc::now().to_rfc3339(),
             completed_at: None,
         };
-        let _ = self.progress_sender.send(update);
+        self.broadcast(update).await;
 
         Ok(task_id)
     }
@@ -245,7 +303,7 @@ impl TaskManager {
             completed_at: None,
         };
 
-        let _ = self.progress_sender.send(update);
+        self.broadcast(update).await;
         Ok(())
     }
 
@@ -279,7 +337,7 @@ impl TaskManager {
             completed_at: Some(chrono::Utc::now().to_rfc3339()),
         };
 
-        let _ = self.progress_sender.send(update);
+        self.broadcast(update).await;
         Ok(())
     }
 
@@ -310,10 +368,99 @@ impl TaskManager {
             completed_at: Some(chrono::Utc::now().to_rfc3339()),
         };
 
-        let _ = self.progress_sender.send(update);
+        self.broadcast(update).await;
+        Ok(())
+    }
+
+    /// Put a task back to pending after a failed attempt that will be retried.
+    pub async fn requeue_task(
+        &self,
+        task_id: &str,
+        message: String,
+    ) -> AppResult<()> {
+        let mut task = self.get_task(task_id).await?;
+        task.status = TaskStatus::Pending;
+        task.progress = 0.0;
+        task.message = Some(message.clone());
+        task.updated_at = chrono::Utc::now();
+
+        self.save_task(&task).await?;
+
+        let update = TaskUpdate {
+            task_id: task_id.to_string(),
+            user_id: task.user_id,
+            task_type: task.task_type.clone(),
+            item_id: None,
+            progress: 0.0,
+            status: TaskStatus::Pending,
+            details: serde_json::json!({
+                "status_text": message
+            }),
+            started_at: task.created_at.to_rfc3339(),
+            completed_at: None,
+        };
+
+        self.broadcast(update).await;
         Ok(())
     }
 
+    pub async fn cancel_task(&self, task_id: &str) -> AppResult<()> {
+        let mut task = self.get_task(task_id).await?;
+        task.status = TaskStatus::Cancelled;
+        task.message = Some("Cancelled".to_string());
+        task.updated_at = chrono::Utc::now();
+
+        self.save_task(&task).await?;
+
+        let update = TaskUpdate {
+            task_id: task_id.to_string(),
+            user_id: task.user_id,
+            task_type: task.task_type.clone(),
+            item_id: None,
+            progress: task.progress,
+            status: TaskStatus::Cancelled,
+            details: serde_json::json!({
+                "status_text": "Cancelled"
+            }),
+            started_at: task.created_at.to_rfc3339(),
+            completed_at: Some(chrono::Utc::now().to_rfc3339()),
+        };
+
+        self.broadcast(update).await;
+        Ok(())
+    }
+
+    /// Recreate a pending task entry that has expired or was lost (e.g. Redis restarted without
+    /// persistence), so a job resumed after a restart can still report progress under its ID.
+    pub async fn ensure_task(
+        &self,
+        task_id: &str,
+        task_type: &str,
+        user_id: i32,
+    ) -> AppResult<()> {
+        let key = format!("task:{}", task_id);
+        let mut conn = self.redis.get_connection().await?;
+        let exists: bool = conn.exists(&key).await?;
+        if exists {
+            return Ok(());
+        }
+
+        let task = TaskInfo {
+            id: task_id.to_string(),
+            task_type: task_type.to_string(),
+            user_id,
+            status: TaskStatus::Pending,
+            progress: 0.0,
+            message: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            result: None,
+            episode_title: None,
+            podcast_name: None,
+        };
+        self.save_task(&task).await
+    }
+
     pub async fn get_task(&self, task_id: &str) -> AppResult<TaskInfo> {
         let key = format!("task:{}", task_id);
         let mut conn = self.redis.get_connection().await?;
@@ -342,12 +489,42 @@ impl TaskManager {
         Ok(user_tasks)
     }
 
+    /// Count stored tasks by status, for the task queue depth gauge on `/api/metrics`.
+    ///
+    /// Reads the per-status sorted sets `save_task` maintains instead of walking `task:*`, so a
+    /// scrape costs a few commands however long the task history is. Members are scored by the
+    /// task's expiry time and expired ones are trimmed here, matching the task keys' TTL.
+    pub async fn status_counts(&self) -> AppResult<std::collections::BTreeMap<String, u64>> {
+        let mut conn = self.redis.get_connection().await?;
+        let now = chrono::Utc::now().timestamp();
+        let mut pipe = redis::pipe();
+        for status in TASK_STATUSES {
+            let index = status_index_key(status);
+            pipe.zrembyscore(&index, "-inf", now).ignore().zcard(&index);
+        }
+        let cards: Vec<u64> = pipe.query_async(&mut conn).await?;
+        Ok(TASK_STATUSES.iter().map(|s| s.to_string()).zip(cards).collect())
+    }
+
     async fn save_task(&self, task: &TaskInfo) -> AppResult<()> {
         let key = format!("task:{}", task.id);
         let task_json = serde_json::to_string(task)?;
         let mut conn = self.redis.get_connection().await?;
-        
-        conn.set_ex::<_, _, ()>(&key, &task_json, 86400 * 7).await?; // 7 days TTL
+
+        conn.set_ex::<_, _, ()>(&key, &task_json, TASK_TTL_SECONDS).await?;
+
+        // Move the task into its status's index (see status_counts)
+        let current = status_name(&task.status);
+        let expires_at = chrono::Utc::now().timestamp() + TASK_TTL_SECONDS as i64;
+        let mut pipe = redis::pipe();
+        for status in TASK_STATUSES {
+            if status == current {
+                pipe.zadd(status_index_key(status), &task.id, expires_at).ignore();
+            } else {
+                pipe.zrem(status_index_key(status), &task.id).ignore();
+            }
+        }
+        pipe.query_async::<()>(&mut conn).await?;
         Ok(())
     }
 
@@ -362,6 +539,7 @@ impl TaskManager {
                 if let Ok(task) = serde_json::from_str::<TaskInfo>(&task_json) {
                     if task.created_at < cutoff {
                         let _: () = conn.del(&key).await?;
+                        let _: () = conn.zrem(status_index_key(status_name(&task.status)), &task.id).await?;
                     }
                 }
             }
@@ -369,4 +547,45 @@ impl TaskManager {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_replica_relays_a_published_update_to_its_subscribers() {
+        // Two replicas, each with a websocket subscribed to its own progress channel. What one
+        // replica publishes reaches every replica's subscription, itself included.
+        let replicas: Vec<TaskProgressSender> = (0..2).map(|_| broadcast::channel(8).0).collect();
+        let mut sockets: Vec<TaskProgressReceiver> = replicas.iter().map(|r| r.subscribe()).collect();
+        let update = TaskUpdate {
+            task_id: "t1".to_string(),
+            user_id: 2,
+            task_type: "download_episode".to_string(),
+            item_id: Some(9),
+            progress: 40.0,
+            status: TaskStatus::Running,
+            details: serde_json::json!({ "status_text": "Downloading" }),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            completed_at: None,
+        };
+        let payload = serde_json::to_string(&update).unwrap();
+        for replica in &replicas {
+            relay_update(replica, "not a task update");
+            relay_update(replica, &payload);
+        }
+
+        for socket in &mut sockets {
+            let received = socket.try_recv().expect("update relayed to every replica");
+            assert_eq!(received.task_id, "t1");
+            assert_eq!(received.user_id, 2);
+            assert_eq!(received.item_id, Some(9));
+            assert_eq!(received.progress, 40.0);
+            assert!(matches!(received.status, TaskStatus::Running));
+            assert_eq!(received.details["status_text"], "Downloading");
+            // The malformed message was dropped rather than relayed.
+            assert!(socket.try_recv().is_err());
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Stand-in HTTP servers for tests of services that call out to other hosts (notification
+//! channels, webhooks, scrobble targets, SponsorBlock, gpodder/Nextcloud).
+
+use axum::Router;
+
+/// Serve `app` on an ephemeral localhost port for the rest of the test and return its base URL,
+/// e.g. `http://127.0.0.1:41234`.
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
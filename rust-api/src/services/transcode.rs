@@ -0,0 +1,290 @@
+//! Server-side audio transcoding for streaming and downloads over slow or metered links.
+//!
+//! A client asks for a [`Profile`] (`?profile=opus48` / `?profile=aac64`) and gets the episode
+//! re-encoded by the same `ffmpeg` binary `audio_processing` uses for silence detection. Outputs
+//! are written as complete files into [`CACHE_DIR`] and served from there with `ServeFile`, so
+//! range requests (seeking) work exactly as they do for the original download, and a second
+//! listener or a resumed stream reuses the same file.
+//!
+//! Rendering a long episode takes minutes, so requests never wait for it: [`cached_or_queue`]
+//! returns a cached output or queues a render job on the job queue's render pool and lets the
+//! caller serve the original meanwhile. Cache entries are keyed by source path, size, mtime and
+//! profile, so a re-downloaded episode gets a fresh transcode rather than a stale one. Identical
+//! render jobs are merged when queued, and [`render`] holds a per-entry lock, local and across
+//! replicas, so one ffmpeg runs per entry. [`prune_cache`] runs with the scheduled cleanup tasks
+//! and trims the cache back under `PINEPODS_TRANSCODE_CACHE_MB` (default 2048), least recently
+//! used first.
+
+use crate::error::{AppError, AppResult};
+use crate::services::cluster;
+use crate::services::job_queue::{Job, JobQueue};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::{debug, info, warn};
+
+/// Under the downloads mount so cached outputs live on the same volume as their sources.
+pub const CACHE_DIR: &str = "/opt/pinepods/downloads/.transcode-cache";
+
+const DEFAULT_CACHE_MB: u64 = 2048;
+/// Lease on a cache entry's cross-replica render lock, renewed while ffmpeg runs.
+pub(crate) const RENDER_LOCK_TTL_SECONDS: u64 = 120;
+
+/// A transcode target a client can request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Opus in Ogg at 48 kbps: the smallest output, for clients that can play Opus.
+    Opus48,
+    /// AAC-LC in MP4 at 64 kbps: plays everywhere, including iOS and older Android.
+    Aac64,
+}
+
+impl Profile {
+    /// Parse the `profile` query parameter. `original` (or no parameter) means no transcode.
+    pub fn parse(value: &str) -> Result<Option<Self>, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "original" => Ok(None),
+            "opus48" => Ok(Some(Self::Opus48)),
+            "aac64" => Ok(Some(Self::Aac64)),
+            other => Err(format!("Unknown transcode profile '{}' (expected opus48, aac64 or original)", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Opus48 => "opus48",
+            Self::Aac64 => "aac64",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Opus48 => "opus",
+            Self::Aac64 => "m4a",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Opus48 => "audio/ogg",
+            Self::Aac64 => "audio/mp4",
+        }
+    }
+
+    fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            Self::Opus48 => &["-c:a", "libopus", "-b:a", "48k", "-vbr", "on", "-application", "audio"],
+            // faststart moves the moov atom to the front so players can seek before the whole
+            // file has arrived.
+            Self::Aac64 => &["-c:a", "aac", "-b:a", "64k", "-movflags", "+faststart"],
+        }
+    }
+}
+
+/// Re-encode `source` into `dest` with ffmpeg. Tags from the source are carried over; embedded
+/// artwork and video streams are dropped.
+pub async fn transcode_file(source: &Path, dest: &Path, profile: Profile) -> Result<(), String> {
+    debug!("Transcoding {} to {} ({})", source.display(), dest.display(), profile.name());
+    let output = tokio::process::Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-nostats")
+        .arg("-y")
+        .arg("-i")
+        .arg(source)
+        .args(["-map", "0:a:0", "-map_metadata", "0", "-vn"])
+        .args(profile.codec_args())
+        .arg(dest)
+        .output()
+        .await
+        .map_err(|e| format!("failed to spawn ffmpeg: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or("")
+        ));
+    }
+    Ok(())
+}
+
+fn cache_key(source: &Path, meta: &std::fs::Metadata, profile: Profile) -> String {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = Sha256::new();
+    hasher.update(source.to_string_lossy().as_bytes());
+    hasher.update(meta.len().to_le_bytes());
+    hasher.update(mtime.to_le_bytes());
+    hasher.update(profile.name().as_bytes());
+    hex::encode(&hasher.finalize()[..16])
+}
+
+/// One lock per cache key, so concurrent renders of the same output on this replica share one
+/// ffmpeg run.
+pub(crate) fn key_lock(key: &str) -> Arc<tokio::sync::Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> = OnceLock::new();
+    let mut locks = LOCKS.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap_or_else(|e| e.into_inner());
+    // Drop entries nobody is waiting on so the map doesn't grow with every episode ever streamed.
+    locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+    locks.entry(key.to_string()).or_default().clone()
+}
+
+/// Cache key and path of the `profile` rendition of `source`.
+async fn cache_entry(source: &Path, profile: Profile) -> Result<(String, PathBuf), String> {
+    let meta = tokio::fs::metadata(source)
+        .await
+        .map_err(|e| format!("cannot read {}: {}", source.display(), e))?;
+    let key = cache_key(source, &meta, profile);
+    let dest = Path::new(CACHE_DIR).join(format!("{}.{}", key, profile.extension()));
+    Ok((key, dest))
+}
+
+/// The cached `profile` rendition of `source`, or None after queueing a render of it for
+/// `user_id`. Callers serve the original until the rendition exists.
+pub async fn cached_or_queue(jobs: &JobQueue, user_id: i32, source: &Path, profile: Profile) -> AppResult<Option<PathBuf>> {
+    let (_, dest) = cache_entry(source, profile).await.map_err(AppError::internal)?;
+    if tokio::fs::metadata(&dest).await.is_ok() {
+        touch(&dest);
+        return Ok(Some(dest));
+    }
+    let job = Job::Transcode { source: source.to_string_lossy().into_owned(), profile: profile.name().to_string() };
+    jobs.enqueue(user_id, job).await?;
+    Ok(None)
+}
+
+/// Render the `profile` rendition of `source` into the cache unless it is already there. Run by
+/// the job queue; fails while another replica is rendering the same entry, so the job's retry
+/// finds the finished file.
+pub async fn render(source: &Path, profile: Profile) -> Result<PathBuf, String> {
+    let (key, dest) = cache_entry(source, profile).await?;
+
+    let lock = key_lock(&key);
+    let _guard = lock.lock().await;
+
+    if tokio::fs::metadata(&dest).await.is_ok() {
+        touch(&dest);
+        return Ok(dest);
+    }
+    let Some(_render_lock) = cluster::try_lock(&format!("render:{}", key), RENDER_LOCK_TTL_SECONDS)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err(format!("{} is being rendered by another instance", dest.display()));
+    };
+
+    tokio::fs::create_dir_all(CACHE_DIR)
+        .await
+        .map_err(|e| format!("cannot create {}: {}", CACHE_DIR, e))?;
+    // Write under a temporary name and rename into place, so a crash mid-transcode never leaves a
+    // truncated file that later requests would serve.
+    let partial = Path::new(CACHE_DIR).join(format!("{}.partial.{}", key, profile.extension()));
+    let started = std::time::Instant::now();
+    if let Err(e) = transcode_file(source, &partial, profile).await {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&partial, &dest)
+        .await
+        .map_err(|e| format!("cannot move transcode into cache: {}", e))?;
+    info!(
+        "Transcoded {} to {} in {:.1}s",
+        source.display(),
+        profile.name(),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(dest)
+}
+
+/// Bump the mtime of a cache hit so [`prune_cache`] evicts least recently *used* entries.
+pub(crate) fn touch(path: &Path) {
+    if let Ok(file) = std::fs::File::options().append(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+fn cache_limit_bytes() -> u64 {
+    std::env::var("PINEPODS_TRANSCODE_CACHE_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CACHE_MB)
+        * 1024
+        * 1024
+}
+
+/// Delete the least recently used cache entries until the cache fits its size limit.
+pub async fn prune_cache() -> Result<(), String> {
+    prune_dir(CACHE_DIR, cache_limit_bytes()).await
+}
+
+/// Trim a render cache directory to `limit` bytes, oldest mtime first. Leftover partial files
+/// older than a day (from a crash mid-render) are removed regardless. Shared with the cut-audio
+/// cache, which uses the same naming and touch-on-hit scheme.
+pub(crate) async fn prune_dir(dir: &'static str, limit: u64) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("cannot read {}: {}", dir, e)),
+        };
+
+        let stale = SystemTime::now() - std::time::Duration::from_secs(24 * 60 * 60);
+        let mut files = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if !meta.is_file() {
+                continue;
+            }
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            if entry.file_name().to_string_lossy().contains(".partial.") {
+                if modified < stale {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+                continue;
+            }
+            files.push((modified, meta.len(), entry.path()));
+        }
+
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        if total <= limit {
+            return Ok(());
+        }
+        files.sort_by_key(|(modified, _, _)| *modified);
+        let mut removed = 0;
+        for (_, len, path) in files {
+            if total <= limit {
+                break;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    total = total.saturating_sub(len);
+                    removed += 1;
+                }
+                Err(e) => warn!("Failed to evict cache entry {}: {}", path.display(), e),
+            }
+        }
+        info!("Evicted {} cache entries from {}", removed, dir);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("cache prune task for {} failed: {}", dir, e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profiles() {
+        assert_eq!(Profile::parse("opus48"), Ok(Some(Profile::Opus48)));
+        assert_eq!(Profile::parse(" AAC64 "), Ok(Some(Profile::Aac64)));
+        assert_eq!(Profile::parse("original"), Ok(None));
+        assert!(Profile::parse("flac").is_err());
+    }
+}
@@ -2,10 +2,12 @@
 //! by calling the optional `pinepods-ai` sidecar, then persisting the result into
 //! `EpisodeTranscripts`.
 //!
-//! Transcripts are content-level (per episode, deduped across users). The row's `Status` tracks
-//! the async lifecycle (`running` → `complete`/`failed`) so a queue view can surface progress.
+//! Transcripts are content-level (per episode, deduped across users): rows are stored against the
+//! episode's shared content key (see `shared_feeds::ContentKey`), so every subscriber to a feed
+//! reads the same transcript and it is generated once. The row's `Status` tracks the async lifecycle (`running` → `complete`/`failed`) so a queue view can surface progress.
 
 use crate::database::DatabasePool;
+use crate::services::shared_feeds::{self, ContentKey};
 use crate::services::{ai_client, audio_processing};
 use serde::Serialize;
 use sqlx::Row;
@@ -27,21 +29,23 @@ pub struct StoredTranscript {
 
 /// Whether a generated transcript already exists OR is in progress for the episode. Used to skip
 /// redundant work (and to avoid two triggers racing to transcribe the same episode).
-async fn has_complete_transcript(db_pool: &DatabasePool, episode_id: i32) -> bool {
+async fn has_complete_transcript(db_pool: &DatabasePool, key: ContentKey) -> bool {
     match db_pool {
-        DatabasePool::Postgres(pool) => sqlx::query(
-            r#"SELECT 1 FROM "EpisodeTranscripts" WHERE episodeid = $1 AND source = $2 AND status IN ('complete','running','pending') LIMIT 1"#,
-        )
-        .bind(episode_id)
+        DatabasePool::Postgres(pool) => sqlx::query(sqlx::AssertSqlSafe(format!(
+            r#"SELECT 1 FROM "EpisodeTranscripts" WHERE {} = $1 AND source = $2 AND status IN ('complete','running','pending') LIMIT 1"#,
+            key.pg_column()
+        )))
+        .bind(key.id())
         .bind(SOURCE_GENERATED)
         .fetch_optional(pool)
         .await
         .map(|r| r.is_some())
         .unwrap_or(false),
-        DatabasePool::MySQL(pool) => sqlx::query(
-            "SELECT 1 FROM EpisodeTranscripts WHERE EpisodeID = ? AND Source = ? AND Status IN ('complete','running','pending') LIMIT 1",
-        )
-        .bind(episode_id)
+        DatabasePool::MySQL(pool) => sqlx::query(sqlx::AssertSqlSafe(format!(
+            "SELECT 1 FROM EpisodeTranscripts WHERE {} = ? AND Source = ? AND Status IN ('complete','running','pending') LIMIT 1",
+            key.mysql_column()
+        )))
+        .bind(key.id())
         .bind(SOURCE_GENERATED)
         .fetch_optional(pool)
         .await
@@ -51,19 +55,21 @@ async fn has_complete_transcript(db_pool: &DatabasePool, episode_id: i32) -> boo
 }
 
 /// Delete any prior generated transcript rows for an episode (so re-runs don't accumulate).
-async fn clear_generated(db_pool: &DatabasePool, episode_id: i32) -> Result<(), String> {
+async fn clear_generated(db_pool: &DatabasePool, key: ContentKey) -> Result<(), String> {
     match db_pool {
         DatabasePool::Postgres(pool) => {
-            sqlx::query(r#"DELETE FROM "EpisodeTranscripts" WHERE episodeid = $1 AND source = $2"#)
-                .bind(episode_id)
+            let query = format!(r#"DELETE FROM "EpisodeTranscripts" WHERE {} = $1 AND source = $2"#, key.pg_column());
+            sqlx::query(sqlx::AssertSqlSafe(query.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_GENERATED)
                 .execute(pool)
                 .await
                 .map_err(|e| e.to_string())?;
         }
         DatabasePool::MySQL(pool) => {
-            sqlx::query("DELETE FROM EpisodeTranscripts WHERE EpisodeID = ? AND Source = ?")
-                .bind(episode_id)
+            let query = format!("DELETE FROM EpisodeTranscripts WHERE {} = ? AND Source = ?", key.mysql_column());
+            sqlx::query(sqlx::AssertSqlSafe(query.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_GENERATED)
                 .execute(pool)
                 .await
@@ -74,23 +80,25 @@ async fn clear_generated(db_pool: &DatabasePool, episode_id: i32) -> Result<(),
 }
 
 /// Insert a `running` placeholder row and return its id, so a queue view can show in-progress work.
-async fn insert_running(db_pool: &DatabasePool, episode_id: i32) -> Result<i64, String> {
+async fn insert_running(db_pool: &DatabasePool, key: ContentKey) -> Result<i64, String> {
     let id = match db_pool {
         DatabasePool::Postgres(pool) => {
-            let row = sqlx::query(r#"
-                INSERT INTO "EpisodeTranscripts" (episodeid, source, status)
-                VALUES ($1, $2, 'running') RETURNING transcriptid
-            "#)
-            .bind(episode_id)
-            .bind(SOURCE_GENERATED)
-            .fetch_one(pool)
-            .await
-            .map_err(|e| e.to_string())?;
+            let query = format!(
+                r#"INSERT INTO "EpisodeTranscripts" ({}, source, status) VALUES ($1, $2, 'running') RETURNING transcriptid"#,
+                key.pg_column()
+            );
+            let row = sqlx::query(sqlx::AssertSqlSafe(query.as_str()))
+                .bind(key.id())
+                .bind(SOURCE_GENERATED)
+                .fetch_one(pool)
+                .await
+                .map_err(|e| e.to_string())?;
             row.try_get::<i32, _>("transcriptid").map_err(|e| e.to_string())? as i64
         }
         DatabasePool::MySQL(pool) => {
-            let res = sqlx::query("INSERT INTO EpisodeTranscripts (EpisodeID, Source, Status) VALUES (?, ?, 'running')")
-                .bind(episode_id)
+            let query = format!("INSERT INTO EpisodeTranscripts ({}, Source, Status) VALUES (?, ?, 'running')", key.mysql_column());
+            let res = sqlx::query(sqlx::AssertSqlSafe(query.as_str()))
+                .bind(key.id())
                 .bind(SOURCE_GENERATED)
                 .execute(pool)
                 .await
@@ -257,19 +265,21 @@ pub async fn transcribe_episode(
     if ai_client::ai_base_url().is_none() {
         return Err("AI service not configured".to_string());
     }
-    if !force && has_complete_transcript(db_pool, episode_id).await {
+    let content = shared_feeds::content_key(db_pool, episode_id).await;
+    if !force && has_complete_transcript(db_pool, content).await {
         debug!("Episode {} already transcribed; skipping", episode_id);
         return Ok(());
     }
 
-    // Prefer an existing download; otherwise fetch a temp copy we clean up afterward.
+    // Prefer an existing download; otherwise fetch a temp copy we clean up afterward. Audio comes
+    // from the requesting user's row (their download, their feed credentials).
     let (file_path, is_temp) = match audio_processing::downloaded_location(db_pool, episode_id).await? {
         Some(p) if std::path::Path::new(&p).exists() => (p, false),
         _ => (fetch_episode_audio_temp(db_pool, episode_id).await?, true),
     };
 
-    clear_generated(db_pool, episode_id).await?;
-    let transcript_id = insert_running(db_pool, episode_id).await?;
+    clear_generated(db_pool, content).await?;
+    let transcript_id = insert_running(db_pool, content).await?;
 
     // Use the admin-configured whisper model (AISettings), falling back to the sidecar default.
     let model = crate::services::ai_settings::transcription_model(db_pool).await;
@@ -289,6 +299,12 @@ pub async fn transcribe_episode(
             .unwrap_or_else(|_| "[]".to_string());
             complete_row(db_pool, transcript_id, &result.language, &result.model, &result.text, &segments_json).await?;
             debug!("Stored transcript for episode {} ({} segments)", episode_id, result.segments.len());
+            crate::services::webhooks::emit_episode_event(
+                db_pool,
+                episode_id,
+                crate::services::webhooks::WebhookEvent::TranscriptReady,
+                serde_json::json!({ "language": result.language, "segments": result.segments.len() }),
+            );
             // Chain ad detection if any subscriber to this feed opted in (safe against the
             // ad-path's own transcription trigger via an in-flight guard).
             crate::services::ad_detection::maybe_detect_ads_after_transcript(db_pool.clone(), episode_id);
@@ -306,14 +322,18 @@ pub async fn get_episode_transcript(
     db_pool: &DatabasePool,
     episode_id: i32,
 ) -> Result<Option<StoredTranscript>, String> {
+    let content = shared_feeds::content_key(db_pool, episode_id).await;
     let transcript = match db_pool {
-        DatabasePool::Postgres(pool) => sqlx::query(r#"
+        DatabasePool::Postgres(pool) => sqlx::query(sqlx::AssertSqlSafe(format!(
+            r#"
             SELECT source, language, model, status, transcripttext, segments::text AS segments_text
             FROM "EpisodeTranscripts"
-            WHERE episodeid = $1 AND source = $2
+            WHERE {} = $1 AND source = $2
             ORDER BY createdat DESC LIMIT 1
-        "#)
-        .bind(episode_id)
+            "#,
+            content.pg_column()
+        )))
+        .bind(content.id())
         .bind(SOURCE_GENERATED)
         .fetch_optional(pool)
         .await
@@ -326,13 +346,16 @@ pub async fn get_episode_transcript(
             full_text: r.try_get("transcripttext").ok(),
             segments: r.try_get("segments_text").ok(),
         }),
-        DatabasePool::MySQL(pool) => sqlx::query(r#"
+        DatabasePool::MySQL(pool) => sqlx::query(sqlx::AssertSqlSafe(format!(
+            r#"
             SELECT Source, Language, Model, Status, TranscriptText, Segments
             FROM EpisodeTranscripts
-            WHERE EpisodeID = ? AND Source = ?
+            WHERE {} = ? AND Source = ?
             ORDER BY CreatedAt DESC LIMIT 1
-        "#)
-        .bind(episode_id)
+            "#,
+            content.mysql_column()
+        )))
+        .bind(content.id())
         .bind(SOURCE_GENERATED)
         .fetch_optional(pool)
         .await
@@ -486,6 +509,8 @@ pub fn maybe_transcribe_episode(db_pool: DatabasePool, episode_id: i32) {
         };
 
         if podcast_enabled {
+            // Shares the transcription pool's limit with queued transcription jobs.
+            let _permit = crate::services::job_queue::acquire(crate::services::job_queue::WorkerPool::Transcription).await;
             if let Err(e) = transcribe_episode(&db_pool, episode_id, false, |_| {}).await {
                 warn!("Auto-transcription failed for episode {}: {}", episode_id, e);
             }
@@ -0,0 +1,1013 @@
+//! Portable per-user data export and import.
+//!
+//! [`export_user`] gathers everything a user owns into a versioned [`UserExport`] document:
+//! podcast subscriptions (including feed credentials and per-podcast settings such as skip
+//! times, playback speed and download rules), listen history and positions, completion, saved
+//! episodes, the queue, smart playlist definitions and collections. [`import_user`] replays such
+//! a document onto this server for a user, subscribing to any missing feeds and mapping each
+//! episode onto the target's copy by GUID, then by enclosure URL; episodes the target feed no
+//! longer carries are counted as unmatched rather than failing the import.
+//!
+//! Importing is additive: existing subscriptions, history, playlists and collections are kept,
+//! listen positions only ever move forward, and names that already exist are reused. YouTube
+//! channels are not part of the export; their videos are re-fetched from YouTube instead.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::tasks::ProgressReporter;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+pub const EXPORT_FORMAT: &str = "pinepods-user-export";
+pub const EXPORT_VERSION: u32 = 1;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+#[derive(Clone, Copy)]
+enum SettingKind {
+    Bool,
+    Int,
+    /// NUMERIC(2,1) playback speed, read as tenths so both backends return an integer.
+    Tenths,
+}
+
+/// Per-podcast settings carried in the export: (export key, MySQL column, kind). The Postgres
+/// column is the lowercased MySQL name.
+const PODCAST_SETTINGS: &[(&str, &str, SettingKind)] = &[
+    ("auto_download", "AutoDownload", SettingKind::Bool),
+    ("auto_queue", "AutoQueue", SettingKind::Bool),
+    ("start_skip", "StartSkip", SettingKind::Int),
+    ("end_skip", "EndSkip", SettingKind::Int),
+    ("notifications_enabled", "NotificationsEnabled", SettingKind::Bool),
+    ("feed_cutoff_days", "FeedCutoffDays", SettingKind::Int),
+    ("playback_speed", "PlaybackSpeed", SettingKind::Tenths),
+    ("playback_speed_customized", "PlaybackSpeedCustomized", SettingKind::Bool),
+    ("auto_download_delete_days", "AutoDownloadDeleteDays", SettingKind::Int),
+    ("auto_download_delete_customized", "AutoDownloadDeleteCustomized", SettingKind::Bool),
+    ("ignore_podcast_index", "IgnorePodcastIndex", SettingKind::Bool),
+    ("display_podcast", "DisplayPodcast", SettingKind::Bool),
+    ("refresh_podcast", "RefreshPodcast", SettingKind::Bool),
+    ("use_podcast_covers", "UsePodcastCovers", SettingKind::Bool),
+    ("use_podcast_covers_customized", "UsePodcastCoversCustomized", SettingKind::Bool),
+    ("auto_play_next", "AutoPlayNext", SettingKind::Bool),
+    ("is_favorite", "IsFavorite", SettingKind::Bool),
+    ("trim_silence", "TrimSilence", SettingKind::Bool),
+    ("silence_threshold", "SilenceThreshold", SettingKind::Int),
+    ("auto_transcribe", "AutoTranscribe", SettingKind::Bool),
+    ("auto_ad_detect", "AutoAdDetect", SettingKind::Bool),
+    ("ad_skip_auto_activate", "AdSkipAutoActivate", SettingKind::Bool),
+    ("keep_last_episodes", "KeepLastEpisodes", SettingKind::Int),
+    ("delete_after_played", "DeleteAfterPlayed", SettingKind::Bool),
+    ("serve_cut_audio", "ServeCutAudio", SettingKind::Bool),
+];
+
+/// The export document.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UserExport {
+    /// Always `pinepods-user-export`.
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub podcasts: Vec<ExportPodcast>,
+    #[serde(default)]
+    pub playlists: Vec<ExportPlaylist>,
+    #[serde(default)]
+    pub collections: Vec<ExportCollection>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportPodcast {
+    pub feed_url: String,
+    pub title: Option<String>,
+    /// Credentials for authenticated feeds, as stored on the source server.
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Per-podcast settings keyed by name (`start_skip`, `playback_speed`, ...). Unknown keys
+    /// are ignored on import so newer exports still load.
+    #[schema(value_type = Object)]
+    #[serde(default)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    /// Episodes the user has any state for.
+    #[serde(default)]
+    pub episodes: Vec<ExportEpisode>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportEpisode {
+    pub guid: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub pub_date: Option<String>,
+    /// Listen position in seconds.
+    pub listen_duration: Option<i32>,
+    pub listened_at: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub saved: bool,
+    pub queue_position: Option<i32>,
+}
+
+/// Smart playlist definition. Podcast filters refer to feeds by URL.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportPlaylist {
+    pub name: String,
+    pub description: Option<String>,
+    pub icon_name: String,
+    pub podcast_feed_urls: Vec<String>,
+    pub include_unplayed: bool,
+    pub include_partially_played: bool,
+    pub include_played: bool,
+    pub play_progress_min: Option<f64>,
+    pub play_progress_max: Option<f64>,
+    pub time_filter_hours: Option<i32>,
+    pub min_duration_minutes: Option<i32>,
+    pub max_duration_minutes: Option<i32>,
+    pub sort_order: String,
+    pub group_by_podcast: bool,
+    pub max_episodes: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportCollection {
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: String,
+    pub auto_add_categories: Option<Vec<String>>,
+    pub episodes: Vec<ExportEpisodeRef>,
+}
+
+/// Reference to an episode of one of the exported podcasts.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportEpisodeRef {
+    pub feed_url: String,
+    pub guid: Option<String>,
+    pub url: Option<String>,
+}
+
+/// What an import did, returned as the task result.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub podcasts_added: usize,
+    pub podcasts_existing: usize,
+    pub podcasts_failed: Vec<String>,
+    pub episodes_matched: usize,
+    pub episodes_unmatched: usize,
+    pub playlists_created: usize,
+    pub playlists_skipped: usize,
+    pub collections_imported: usize,
+}
+
+fn format_ts(ts: Option<chrono::NaiveDateTime>) -> Option<String> {
+    ts.map(|t| t.format(TIMESTAMP_FORMAT).to_string())
+}
+
+fn parse_ts(ts: Option<&str>) -> Option<chrono::NaiveDateTime> {
+    let ts = ts?;
+    chrono::NaiveDateTime::parse_from_str(ts, TIMESTAMP_FORMAT)
+        .ok()
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(ts).ok().map(|t| t.naive_utc()))
+}
+
+// ---- Export ----
+
+struct EpisodeRow {
+    feed_url: String,
+    episode: ExportEpisode,
+}
+
+async fn export_podcasts(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<ExportPodcast>> {
+    let postgres = matches!(db_pool, DatabasePool::Postgres(_));
+    let columns: Vec<String> = PODCAST_SETTINGS
+        .iter()
+        .map(|(key, column, kind)| {
+            let col = if postgres { column.to_lowercase() } else { column.to_string() };
+            match (kind, postgres) {
+                (SettingKind::Bool, true) => format!("COALESCE({}, FALSE)::INT::BIGINT AS {}", col, key),
+                (SettingKind::Bool, false) => format!("CAST(COALESCE({}, 0) AS SIGNED) AS {}", col, key),
+                (SettingKind::Int, true) => format!("COALESCE({}, 0)::BIGINT AS {}", col, key),
+                (SettingKind::Int, false) => format!("CAST(COALESCE({}, 0) AS SIGNED) AS {}", col, key),
+                (SettingKind::Tenths, true) => format!("ROUND(COALESCE({}, 1.0) * 10)::BIGINT AS {}", col, key),
+                (SettingKind::Tenths, false) => format!("CAST(ROUND(COALESCE({}, 1.0) * 10) AS SIGNED) AS {}", col, key),
+            }
+        })
+        .collect();
+
+    let sql = if postgres {
+        format!(
+            r#"SELECT feedurl AS feed_url, podcastname AS title, username, password, {}
+               FROM "Podcasts"
+               WHERE userid = $1 AND COALESCE(isyoutubechannel, FALSE) = FALSE
+               ORDER BY podcastname"#,
+            columns.join(", ")
+        )
+    } else {
+        format!(
+            "SELECT FeedURL AS feed_url, PodcastName AS title, Username AS username, Password AS password, {}
+             FROM Podcasts
+             WHERE UserID = ? AND COALESCE(IsYouTubeChannel, 0) = 0
+             ORDER BY PodcastName",
+            columns.join(", ")
+        )
+    };
+
+    macro_rules! read_podcasts {
+        ($rows:expr) => {{
+            let mut podcasts = Vec::new();
+            for row in $rows {
+                let mut settings = serde_json::Map::new();
+                for (key, _, kind) in PODCAST_SETTINGS {
+                    let value: i64 = row.try_get(*key)?;
+                    settings.insert(
+                        key.to_string(),
+                        match kind {
+                            SettingKind::Bool => serde_json::json!(value != 0),
+                            SettingKind::Int => serde_json::json!(value),
+                            SettingKind::Tenths => serde_json::json!(value as f64 / 10.0),
+                        },
+                    );
+                }
+                podcasts.push(ExportPodcast {
+                    feed_url: row.try_get("feed_url")?,
+                    title: row.try_get("title")?,
+                    username: row.try_get::<Option<String>, _>("username")?.filter(|u| !u.is_empty()),
+                    password: row.try_get::<Option<String>, _>("password")?.filter(|p| !p.is_empty()),
+                    settings,
+                    episodes: Vec::new(),
+                });
+            }
+            podcasts
+        }};
+    }
+
+    let podcasts = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let rows = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).bind(user_id).fetch_all(pool).await?;
+            read_podcasts!(rows)
+        }
+        DatabasePool::MySQL(pool) => {
+            let rows = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).bind(user_id).fetch_all(pool).await?;
+            read_podcasts!(rows)
+        }
+    };
+    Ok(podcasts)
+}
+
+/// Every non-YouTube episode the user has history, completion, a save or a queue entry for.
+async fn export_episode_state(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<EpisodeRow>> {
+    let rows = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT p.feedurl, e.episodeguid, e.episodeurl, e.episodetitle, e.episodepubdate,
+                      h.listenduration, h.listendate, COALESCE(e.completed, FALSE) AS completed,
+                      EXISTS(SELECT 1 FROM "SavedEpisodes" s WHERE s.episodeid = e.episodeid AND s.userid = $1) AS saved,
+                      (SELECT MIN(q.queueposition) FROM "EpisodeQueue" q
+                        WHERE q.episodeid = e.episodeid AND q.userid = $1 AND COALESCE(q.is_youtube, FALSE) = FALSE) AS queue_position
+               FROM "Episodes" e
+               JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               LEFT JOIN (SELECT episodeid, MAX(listenduration) AS listenduration, MAX(listendate) AS listendate
+                          FROM "UserEpisodeHistory" WHERE userid = $1 GROUP BY episodeid) h
+                      ON h.episodeid = e.episodeid
+               WHERE p.userid = $1 AND COALESCE(p.isyoutubechannel, FALSE) = FALSE
+                 AND (h.episodeid IS NOT NULL OR e.completed = TRUE
+                      OR EXISTS(SELECT 1 FROM "SavedEpisodes" s WHERE s.episodeid = e.episodeid AND s.userid = $1)
+                      OR EXISTS(SELECT 1 FROM "EpisodeQueue" q WHERE q.episodeid = e.episodeid AND q.userid = $1
+                                AND COALESCE(q.is_youtube, FALSE) = FALSE))
+               ORDER BY e.episodepubdate"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<EpisodeRow> {
+            Ok(EpisodeRow {
+                feed_url: r.try_get("feedurl")?,
+                episode: ExportEpisode {
+                    guid: r.try_get("episodeguid")?,
+                    url: r.try_get("episodeurl")?,
+                    title: r.try_get("episodetitle")?,
+                    pub_date: format_ts(r.try_get("episodepubdate")?),
+                    listen_duration: r.try_get("listenduration")?,
+                    listened_at: format_ts(r.try_get("listendate")?),
+                    completed: r.try_get("completed")?,
+                    saved: r.try_get("saved")?,
+                    queue_position: r.try_get("queue_position")?,
+                },
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT p.FeedURL, e.EpisodeGUID, e.EpisodeURL, e.EpisodeTitle, e.EpisodePubDate,
+                    h.ListenDuration, h.ListenDate, CAST(COALESCE(e.Completed, 0) AS SIGNED) AS completed,
+                    CAST(EXISTS(SELECT 1 FROM SavedEpisodes s WHERE s.EpisodeID = e.EpisodeID AND s.UserID = ?) AS SIGNED) AS saved,
+                    (SELECT MIN(q.QueuePosition) FROM EpisodeQueue q
+                      WHERE q.EpisodeID = e.EpisodeID AND q.UserID = ? AND COALESCE(q.is_youtube, 0) = 0) AS queue_position
+             FROM Episodes e
+             JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             LEFT JOIN (SELECT EpisodeID, MAX(ListenDuration) AS ListenDuration, MAX(ListenDate) AS ListenDate
+                        FROM UserEpisodeHistory WHERE UserID = ? GROUP BY EpisodeID) h
+                    ON h.EpisodeID = e.EpisodeID
+             WHERE p.UserID = ? AND COALESCE(p.IsYouTubeChannel, 0) = 0
+               AND (h.EpisodeID IS NOT NULL OR e.Completed = 1
+                    OR EXISTS(SELECT 1 FROM SavedEpisodes s WHERE s.EpisodeID = e.EpisodeID AND s.UserID = ?)
+                    OR EXISTS(SELECT 1 FROM EpisodeQueue q WHERE q.EpisodeID = e.EpisodeID AND q.UserID = ?
+                              AND COALESCE(q.is_youtube, 0) = 0))
+             ORDER BY e.EpisodePubDate",
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<EpisodeRow> {
+            Ok(EpisodeRow {
+                feed_url: r.try_get("FeedURL")?,
+                episode: ExportEpisode {
+                    guid: r.try_get("EpisodeGUID")?,
+                    url: r.try_get("EpisodeURL")?,
+                    title: r.try_get("EpisodeTitle")?,
+                    pub_date: format_ts(r.try_get("EpisodePubDate")?),
+                    listen_duration: r.try_get("ListenDuration")?,
+                    listened_at: format_ts(r.try_get("ListenDate")?),
+                    completed: r.try_get::<i64, _>("completed")? != 0,
+                    saved: r.try_get::<i64, _>("saved")? != 0,
+                    queue_position: r.try_get("queue_position")?,
+                },
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(rows)
+}
+
+async fn export_playlists(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<ExportPlaylist>> {
+    // Resolve podcast ids to feed URLs so the playlist survives the move.
+    let feed_urls: HashMap<i32, String> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT podcastid, feedurl FROM "Podcasts" WHERE userid = $1"#)
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|r| Ok((r.try_get("podcastid")?, r.try_get("feedurl")?)))
+            .collect::<Result<_, sqlx::Error>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT PodcastID, FeedURL FROM Podcasts WHERE UserID = ?")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|r| Ok((r.try_get("PodcastID")?, r.try_get("FeedURL")?)))
+            .collect::<Result<_, sqlx::Error>>()?,
+    };
+    let urls_for = |ids: Vec<i32>| -> Vec<String> { ids.iter().filter_map(|id| feed_urls.get(id).cloned()).collect() };
+
+    let playlists = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT name, description, iconname, podcastids, includeunplayed, includepartiallyplayed,
+                      includeplayed, playprogressmin, playprogressmax, timefilterhours, minduration,
+                      maxduration, sortorder, groupbypodcast, maxepisodes
+               FROM "Playlists" WHERE userid = $1 AND issystemplaylist = FALSE ORDER BY playlistid"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<ExportPlaylist> {
+            Ok(ExportPlaylist {
+                name: r.try_get("name")?,
+                description: r.try_get("description")?,
+                icon_name: r.try_get("iconname")?,
+                podcast_feed_urls: urls_for(r.try_get::<Option<Vec<i32>>, _>("podcastids")?.unwrap_or_default()),
+                include_unplayed: r.try_get("includeunplayed")?,
+                include_partially_played: r.try_get("includepartiallyplayed")?,
+                include_played: r.try_get("includeplayed")?,
+                play_progress_min: r.try_get("playprogressmin")?,
+                play_progress_max: r.try_get("playprogressmax")?,
+                time_filter_hours: r.try_get("timefilterhours")?,
+                min_duration_minutes: r.try_get::<Option<i32>, _>("minduration")?.map(|s| s / 60),
+                max_duration_minutes: r.try_get::<Option<i32>, _>("maxduration")?.map(|s| s / 60),
+                sort_order: r.try_get("sortorder")?,
+                group_by_podcast: r.try_get("groupbypodcast")?,
+                max_episodes: r.try_get("maxepisodes")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT Name, Description, IconName, CAST(PodcastIDs AS CHAR) AS podcast_ids,
+                    CAST(IncludeUnplayed AS SIGNED) AS include_unplayed,
+                    CAST(IncludePartiallyPlayed AS SIGNED) AS include_partially_played,
+                    CAST(IncludePlayed AS SIGNED) AS include_played,
+                    PlayProgressMin, PlayProgressMax, TimeFilterHours, MinDuration, MaxDuration,
+                    SortOrder, CAST(GroupByPodcast AS SIGNED) AS group_by_podcast, MaxEpisodes
+             FROM Playlists WHERE UserID = ? AND IsSystemPlaylist = 0 ORDER BY PlaylistID",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<ExportPlaylist> {
+            let ids = r
+                .try_get::<Option<String>, _>("podcast_ids")?
+                .and_then(|s| serde_json::from_str::<Vec<i32>>(&s).ok())
+                .unwrap_or_default();
+            Ok(ExportPlaylist {
+                name: r.try_get("Name")?,
+                description: r.try_get("Description")?,
+                icon_name: r.try_get("IconName")?,
+                podcast_feed_urls: urls_for(ids),
+                include_unplayed: r.try_get::<i64, _>("include_unplayed")? != 0,
+                include_partially_played: r.try_get::<i64, _>("include_partially_played")? != 0,
+                include_played: r.try_get::<i64, _>("include_played")? != 0,
+                play_progress_min: r.try_get::<Option<f32>, _>("PlayProgressMin")?.map(f64::from),
+                play_progress_max: r.try_get::<Option<f32>, _>("PlayProgressMax")?.map(f64::from),
+                time_filter_hours: r.try_get("TimeFilterHours")?,
+                min_duration_minutes: r.try_get::<Option<i32>, _>("MinDuration")?.map(|s| s / 60),
+                max_duration_minutes: r.try_get::<Option<i32>, _>("MaxDuration")?.map(|s| s / 60),
+                sort_order: r.try_get("SortOrder")?,
+                group_by_podcast: r.try_get::<i64, _>("group_by_podcast")? != 0,
+                max_episodes: r.try_get("MaxEpisodes")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(playlists)
+}
+
+/// Non-default collections. The default collection is the saved-episodes list, which travels
+/// as the `saved` flag on each episode.
+async fn export_collections(db_pool: &DatabasePool, user_id: i32) -> AppResult<Vec<ExportCollection>> {
+    let mut collections: BTreeMap<i32, ExportCollection> = BTreeMap::new();
+    let mut refs: Vec<(i32, ExportEpisodeRef)> = Vec::new();
+    let parse_categories = |stored: Option<String>| {
+        stored
+            .and_then(|s| serde_json::from_str::<Vec<String>>(&s).ok())
+            .filter(|c| !c.is_empty())
+    };
+
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            for r in sqlx::query(
+                r#"SELECT collectionid, name, description, icon, autoaddcategories
+                   FROM "Collections" WHERE userid = $1 AND isdefault = FALSE"#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            {
+                collections.insert(
+                    r.try_get("collectionid")?,
+                    ExportCollection {
+                        name: r.try_get("name")?,
+                        description: r.try_get("description")?,
+                        icon: r.try_get("icon")?,
+                        auto_add_categories: parse_categories(r.try_get("autoaddcategories")?),
+                        episodes: Vec::new(),
+                    },
+                );
+            }
+            for r in sqlx::query(
+                r#"SELECT ce.collectionid, p.feedurl, e.episodeguid, e.episodeurl
+                   FROM "CollectionEpisodes" ce
+                   JOIN "Collections" c ON c.collectionid = ce.collectionid
+                   JOIN "Episodes" e ON e.episodeid = ce.episodeid
+                   JOIN "Podcasts" p ON p.podcastid = e.podcastid
+                   WHERE c.userid = $1 AND c.isdefault = FALSE
+                   ORDER BY ce.addedat"#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            {
+                refs.push((
+                    r.try_get("collectionid")?,
+                    ExportEpisodeRef { feed_url: r.try_get("feedurl")?, guid: r.try_get("episodeguid")?, url: r.try_get("episodeurl")? },
+                ));
+            }
+        }
+        DatabasePool::MySQL(pool) => {
+            for r in sqlx::query(
+                "SELECT CollectionID, Name, Description, Icon, AutoAddCategories
+                 FROM Collections WHERE UserID = ? AND IsDefault = 0",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            {
+                collections.insert(
+                    r.try_get("CollectionID")?,
+                    ExportCollection {
+                        name: r.try_get("Name")?,
+                        description: r.try_get("Description")?,
+                        icon: r.try_get("Icon")?,
+                        auto_add_categories: parse_categories(r.try_get("AutoAddCategories")?),
+                        episodes: Vec::new(),
+                    },
+                );
+            }
+            for r in sqlx::query(
+                "SELECT ce.CollectionID, p.FeedURL, e.EpisodeGUID, e.EpisodeURL
+                 FROM CollectionEpisodes ce
+                 JOIN Collections c ON c.CollectionID = ce.CollectionID
+                 JOIN Episodes e ON e.EpisodeID = ce.EpisodeID
+                 JOIN Podcasts p ON p.PodcastID = e.PodcastID
+                 WHERE c.UserID = ? AND c.IsDefault = 0
+                 ORDER BY ce.AddedAt",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            {
+                refs.push((
+                    r.try_get("CollectionID")?,
+                    ExportEpisodeRef { feed_url: r.try_get("FeedURL")?, guid: r.try_get("EpisodeGUID")?, url: r.try_get("EpisodeURL")? },
+                ));
+            }
+        }
+    }
+
+    for (collection_id, episode) in refs {
+        if let Some(collection) = collections.get_mut(&collection_id) {
+            collection.episodes.push(episode);
+        }
+    }
+    Ok(collections.into_values().collect())
+}
+
+/// Build the export document for `user_id`.
+pub async fn export_user(db_pool: &DatabasePool, user_id: i32) -> AppResult<UserExport> {
+    let mut podcasts = export_podcasts(db_pool, user_id).await?;
+    let mut by_feed: HashMap<String, Vec<ExportEpisode>> = HashMap::new();
+    for row in export_episode_state(db_pool, user_id).await? {
+        by_feed.entry(row.feed_url).or_default().push(row.episode);
+    }
+    for podcast in &mut podcasts {
+        podcast.episodes = by_feed.remove(&podcast.feed_url).unwrap_or_default();
+    }
+
+    Ok(UserExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().format(TIMESTAMP_FORMAT).to_string(),
+        podcasts,
+        playlists: export_playlists(db_pool, user_id).await?,
+        collections: export_collections(db_pool, user_id).await?,
+    })
+}
+
+// ---- Import ----
+
+/// Reject documents this server can't read before any work is queued.
+pub fn check_compatible(export: &UserExport) -> Result<(), String> {
+    if export.format != EXPORT_FORMAT {
+        return Err(format!("Not a PinePods user export (format '{}')", export.format));
+    }
+    if export.version == 0 || export.version > EXPORT_VERSION {
+        return Err(format!(
+            "Unsupported export version {} (this server reads up to {})",
+            export.version, EXPORT_VERSION
+        ));
+    }
+    Ok(())
+}
+
+/// A podcast's episodes on the target server, for matching exported episodes.
+#[derive(Default)]
+pub(crate) struct EpisodeIndex {
+    by_guid: HashMap<String, i32>,
+    by_url: HashMap<String, i32>,
+}
+
+impl EpisodeIndex {
+    pub(crate) fn insert(&mut self, episode_id: i32, guid: Option<String>, url: Option<String>) {
+        if let Some(guid) = guid.filter(|g| !g.is_empty()) {
+            self.by_guid.entry(guid).or_insert(episode_id);
+        }
+        if let Some(url) = url.filter(|u| !u.is_empty()) {
+            self.by_url.entry(url).or_insert(episode_id);
+        }
+    }
+
+    /// GUID first (stable across enclosure changes), then enclosure URL.
+    pub(crate) fn find(&self, guid: Option<&str>, url: Option<&str>) -> Option<i32> {
+        guid.and_then(|g| self.by_guid.get(g))
+            .or_else(|| url.and_then(|u| self.by_url.get(u)))
+            .copied()
+    }
+}
+
+pub(crate) async fn find_podcast(db_pool: &DatabasePool, user_id: i32, feed_url: &str) -> AppResult<Option<i32>> {
+    let id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT podcastid FROM "Podcasts" WHERE userid = $1 AND feedurl = $2"#)
+            .bind(user_id)
+            .bind(feed_url)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| r.try_get("podcastid"))
+            .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT PodcastID FROM Podcasts WHERE UserID = ? AND FeedURL = ?")
+            .bind(user_id)
+            .bind(feed_url)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| r.try_get("PodcastID"))
+            .transpose()?,
+    };
+    Ok(id)
+}
+
+pub(crate) async fn episode_index(db_pool: &DatabasePool, podcast_id: i32) -> AppResult<EpisodeIndex> {
+    let mut index = EpisodeIndex::default();
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            for r in sqlx::query(r#"SELECT episodeid, episodeguid, episodeurl FROM "Episodes" WHERE podcastid = $1"#)
+                .bind(podcast_id)
+                .fetch_all(pool)
+                .await?
+            {
+                index.insert(r.try_get("episodeid")?, r.try_get("episodeguid")?, r.try_get("episodeurl")?);
+            }
+        }
+        DatabasePool::MySQL(pool) => {
+            for r in sqlx::query("SELECT EpisodeID, EpisodeGUID, EpisodeURL FROM Episodes WHERE PodcastID = ?")
+                .bind(podcast_id)
+                .fetch_all(pool)
+                .await?
+            {
+                index.insert(r.try_get("EpisodeID")?, r.try_get("EpisodeGUID")?, r.try_get("EpisodeURL")?);
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Apply the exported settings that this server knows about.
+async fn apply_settings(
+    db_pool: &DatabasePool,
+    podcast_id: i32,
+    settings: &serde_json::Map<String, serde_json::Value>,
+) -> AppResult<()> {
+    enum Bind {
+        Bool(bool),
+        Int(i32),
+        Speed(f64),
+    }
+    let postgres = matches!(db_pool, DatabasePool::Postgres(_));
+    let mut assignments = Vec::new();
+    let mut binds = Vec::new();
+    for (key, column, kind) in PODCAST_SETTINGS {
+        let Some(value) = settings.get(*key) else { continue };
+        let bind = match kind {
+            SettingKind::Bool => value.as_bool().map(Bind::Bool),
+            SettingKind::Int => value.as_i64().map(|v| Bind::Int(v.clamp(i32::MIN as i64, i32::MAX as i64) as i32)),
+            SettingKind::Tenths => value.as_f64().map(|v| Bind::Speed((v * 10.0).round() / 10.0)),
+        };
+        let Some(bind) = bind else { continue };
+        assignments.push(if postgres {
+            let cast = if matches!(kind, SettingKind::Tenths) { "::NUMERIC" } else { "" };
+            format!("{} = ${}{}", column.to_lowercase(), binds.len() + 2, cast)
+        } else {
+            format!("{} = ?", column)
+        });
+        binds.push(bind);
+    }
+    if assignments.is_empty() {
+        return Ok(());
+    }
+
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let sql = format!(r#"UPDATE "Podcasts" SET {} WHERE podcastid = $1"#, assignments.join(", "));
+            let mut query = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).bind(podcast_id);
+            for bind in binds {
+                query = match bind {
+                    Bind::Bool(v) => query.bind(v),
+                    Bind::Int(v) => query.bind(v),
+                    Bind::Speed(v) => query.bind(v),
+                };
+            }
+            query.execute(pool).await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            let sql = format!("UPDATE Podcasts SET {} WHERE PodcastID = ?", assignments.join(", "));
+            let mut query = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()));
+            for bind in binds {
+                query = match bind {
+                    Bind::Bool(v) => query.bind(v),
+                    Bind::Int(v) => query.bind(v),
+                    Bind::Speed(v) => query.bind(v),
+                };
+            }
+            query.bind(podcast_id).execute(pool).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Merge an exported listen position into the target's history: positions only move forward,
+/// and a new row keeps the original listen date.
+async fn merge_history(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    episode_id: i32,
+    listen_duration: i32,
+    listened_at: Option<chrono::NaiveDateTime>,
+) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let updated = sqlx::query(
+                r#"UPDATE "UserEpisodeHistory" SET listenduration = GREATEST(COALESCE(listenduration, 0), $3)
+                   WHERE userid = $1 AND episodeid = $2"#,
+            )
+            .bind(user_id)
+            .bind(episode_id)
+            .bind(listen_duration)
+            .execute(pool)
+            .await?
+            .rows_affected();
+            if updated == 0 {
+                sqlx::query(
+                    r#"INSERT INTO "UserEpisodeHistory" (userid, episodeid, listendate, listenduration)
+                       VALUES ($1, $2, COALESCE($3, NOW()), $4)"#,
+                )
+                .bind(user_id)
+                .bind(episode_id)
+                .bind(listened_at)
+                .bind(listen_duration)
+                .execute(pool)
+                .await?;
+            }
+        }
+        DatabasePool::MySQL(pool) => {
+            let existing = sqlx::query("SELECT 1 FROM UserEpisodeHistory WHERE UserID = ? AND EpisodeID = ?")
+                .bind(user_id)
+                .bind(episode_id)
+                .fetch_optional(pool)
+                .await?;
+            if existing.is_some() {
+                sqlx::query(
+                    "UPDATE UserEpisodeHistory SET ListenDuration = GREATEST(COALESCE(ListenDuration, 0), ?)
+                     WHERE UserID = ? AND EpisodeID = ?",
+                )
+                .bind(listen_duration)
+                .bind(user_id)
+                .bind(episode_id)
+                .execute(pool)
+                .await?;
+            } else {
+                sqlx::query(
+                    "INSERT INTO UserEpisodeHistory (UserID, EpisodeID, ListenDate, ListenDuration)
+                     VALUES (?, ?, COALESCE(?, NOW()), ?)",
+                )
+                .bind(user_id)
+                .bind(episode_id)
+                .bind(listened_at)
+                .bind(listen_duration)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn playlist_exists(db_pool: &DatabasePool, user_id: i32, name: &str) -> AppResult<bool> {
+    let found = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT 1 FROM "Playlists" WHERE userid = $1 AND name = $2"#)
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .is_some(),
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT 1 FROM Playlists WHERE UserID = ? AND Name = ?")
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .is_some(),
+    };
+    Ok(found)
+}
+
+async fn find_collection(db_pool: &DatabasePool, user_id: i32, name: &str) -> AppResult<Option<i32>> {
+    let id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT collectionid FROM "Collections" WHERE userid = $1 AND name = $2"#)
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| r.try_get("collectionid"))
+            .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT CollectionID FROM Collections WHERE UserID = ? AND Name = ?")
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| r.try_get("CollectionID"))
+            .transpose()?,
+    };
+    Ok(id)
+}
+
+/// Subscribe to the feed unless the user already has it. Returns the podcast id and whether it
+/// was newly added.
+async fn ensure_subscribed(db_pool: &DatabasePool, user_id: i32, podcast: &ExportPodcast) -> AppResult<(i32, bool)> {
+    if let Some(podcast_id) = find_podcast(db_pool, user_id, &podcast.feed_url).await? {
+        return Ok((podcast_id, false));
+    }
+    let username = podcast.username.as_deref();
+    let password = podcast.password.as_deref();
+    let values = db_pool.get_podcast_values(&podcast.feed_url, user_id, username, password).await?;
+    let (podcast_id, _) = db_pool.add_podcast_from_values(&values, user_id, 30, username, password).await?;
+    Ok((podcast_id, true))
+}
+
+/// Replay `export` onto this server for `user_id`, reporting progress per podcast.
+pub async fn import_user(
+    state: &crate::AppState,
+    user_id: i32,
+    export: UserExport,
+    reporter: Arc<dyn ProgressReporter>,
+) -> AppResult<ImportReport> {
+    check_compatible(&export).map_err(AppError::bad_request)?;
+    let db_pool = &state.db_pool;
+    let mut report = ImportReport::default();
+    let mut feeds: HashMap<String, (i32, EpisodeIndex)> = HashMap::new();
+    let mut queue: Vec<(i32, i32)> = Vec::new();
+    // Podcasts are the slow part (each new one fetches its feed); the rest shares the last 10%.
+    let total = export.podcasts.len().max(1) as f64;
+
+    for (i, podcast) in export.podcasts.iter().enumerate() {
+        reporter
+            .update_progress(i as f64 / total * 90.0, Some(format!("Importing {}", podcast.title.as_deref().unwrap_or(&podcast.feed_url))))
+            .await?;
+
+        let podcast_id = match ensure_subscribed(db_pool, user_id, podcast).await {
+            Ok((podcast_id, added)) => {
+                if added {
+                    report.podcasts_added += 1;
+                } else {
+                    report.podcasts_existing += 1;
+                }
+                podcast_id
+            }
+            Err(e) => {
+                warn!("User import: could not subscribe user {} to {}: {}", user_id, podcast.feed_url, e);
+                report.podcasts_failed.push(podcast.feed_url.clone());
+                report.episodes_unmatched += podcast.episodes.len();
+                continue;
+            }
+        };
+        apply_settings(db_pool, podcast_id, &podcast.settings).await?;
+
+        let index = episode_index(db_pool, podcast_id).await?;
+        for episode in &podcast.episodes {
+            let Some(episode_id) = index.find(episode.guid.as_deref(), episode.url.as_deref()) else {
+                report.episodes_unmatched += 1;
+                continue;
+            };
+            report.episodes_matched += 1;
+            if let Some(duration) = episode.listen_duration.filter(|d| *d > 0) {
+                merge_history(db_pool, user_id, episode_id, duration, parse_ts(episode.listened_at.as_deref())).await?;
+            }
+            if episode.completed {
+                db_pool.mark_episode_completed(episode_id, user_id, false).await?;
+            }
+            if episode.saved {
+                db_pool.save_episode(episode_id, user_id, false).await?;
+            }
+            if let Some(position) = episode.queue_position {
+                queue.push((position, episode_id));
+            }
+        }
+        feeds.insert(podcast.feed_url.clone(), (podcast_id, index));
+    }
+
+    reporter.update_progress(90.0, Some("Restoring queue".to_string())).await?;
+    queue.sort();
+    for (_, episode_id) in queue {
+        db_pool.queue_episode(episode_id, user_id, false).await?;
+    }
+
+    reporter.update_progress(94.0, Some("Restoring playlists".to_string())).await?;
+    for playlist in &export.playlists {
+        if playlist_exists(db_pool, user_id, &playlist.name).await? {
+            report.playlists_skipped += 1;
+            continue;
+        }
+        let podcast_ids: Vec<i32> = playlist
+            .podcast_feed_urls
+            .iter()
+            .filter_map(|url| feeds.get(url).map(|(id, _)| *id))
+            .collect();
+        // An empty filter means "all podcasts"; don't widen a playlist whose feeds all failed.
+        if podcast_ids.is_empty() && !playlist.podcast_feed_urls.is_empty() {
+            report.playlists_skipped += 1;
+            continue;
+        }
+        let request = crate::models::CreatePlaylistRequest {
+            user_id,
+            name: playlist.name.clone(),
+            description: playlist.description.clone(),
+            podcast_ids: (!podcast_ids.is_empty()).then_some(podcast_ids),
+            include_unplayed: playlist.include_unplayed,
+            include_partially_played: playlist.include_partially_played,
+            include_played: playlist.include_played,
+            play_progress_min: playlist.play_progress_min,
+            play_progress_max: playlist.play_progress_max,
+            time_filter_hours: playlist.time_filter_hours,
+            min_duration: playlist.min_duration_minutes,
+            max_duration: playlist.max_duration_minutes,
+            sort_order: playlist.sort_order.clone(),
+            group_by_podcast: playlist.group_by_podcast,
+            max_episodes: playlist.max_episodes,
+            icon_name: playlist.icon_name.clone(),
+        };
+        match db_pool.create_playlist(&state.config, &request).await {
+            Ok(_) => report.playlists_created += 1,
+            Err(e) => {
+                warn!("User import: could not create playlist '{}' for user {}: {}", playlist.name, user_id, e);
+                report.playlists_skipped += 1;
+            }
+        }
+    }
+
+    reporter.update_progress(97.0, Some("Restoring collections".to_string())).await?;
+    for collection in &export.collections {
+        let collection_id = match find_collection(db_pool, user_id, &collection.name).await? {
+            Some(id) => id,
+            None => {
+                db_pool
+                    .create_collection(&crate::models::CreateCollectionRequest {
+                        user_id,
+                        name: collection.name.clone(),
+                        description: collection.description.clone(),
+                        icon: Some(collection.icon.clone()),
+                        auto_add_categories: collection.auto_add_categories.clone(),
+                        backfill: Some(false),
+                    })
+                    .await?
+            }
+        };
+        for episode in &collection.episodes {
+            let found = feeds
+                .get(&episode.feed_url)
+                .and_then(|(_, index)| index.find(episode.guid.as_deref(), episode.url.as_deref()));
+            if let Some(episode_id) = found {
+                db_pool.add_episode_to_collection(user_id, collection_id, episode_id, false).await?;
+            }
+        }
+        report.collections_imported += 1;
+    }
+
+    info!(
+        "User import for {}: {} podcasts added, {} existing, {} failed; {} episodes matched, {} unmatched",
+        user_id,
+        report.podcasts_added,
+        report.podcasts_existing,
+        report.podcasts_failed.len(),
+        report.episodes_matched,
+        report.episodes_unmatched
+    );
+    reporter.update_progress(100.0, Some("Import complete".to_string())).await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_by_guid_then_enclosure_url() {
+        let mut index = EpisodeIndex::default();
+        index.insert(1, Some("guid-1".into()), Some("https://cdn.example/1.mp3".into()));
+        index.insert(2, None, Some("https://cdn.example/2.mp3".into()));
+
+        // A re-hosted enclosure still matches by GUID.
+        assert_eq!(index.find(Some("guid-1"), Some("https://new-cdn.example/1.mp3")), Some(1));
+        assert_eq!(index.find(Some("unknown"), Some("https://cdn.example/2.mp3")), Some(2));
+        assert_eq!(index.find(None, Some("https://cdn.example/3.mp3")), None);
+    }
+
+    #[test]
+    fn rejects_foreign_and_future_documents() {
+        let mut export = UserExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: String::new(),
+            podcasts: Vec::new(),
+            playlists: Vec::new(),
+            collections: Vec::new(),
+        };
+        assert!(check_compatible(&export).is_ok());
+        export.version = EXPORT_VERSION + 1;
+        assert!(check_compatible(&export).is_err());
+        export.version = EXPORT_VERSION;
+        export.format = "opml".to_string();
+        assert!(check_compatible(&export).is_err());
+    }
+}
@@ -0,0 +1,1064 @@
+//! Outbound webhooks for server events.
+//!
+//! Users register endpoints for the [`WebhookEvent`]s they care about; admins can also register
+//! admin-wide endpoints that see every user's events plus admin-only ones (`user.created`). When
+//! an event fires, [`emit`] queues one `WebhookDeliveries` row per matching endpoint and delivers
+//! them straight away in the background. Claims and retries go through the same `outbox` as
+//! scrobbles: the scheduler's retry sweep picks up rows that are due again, and a row gives up
+//! after [`MAX_ATTEMPTS`] or on a permanent error (a 4xx other than 408/429). The rows double as
+//! the delivery log shown to the endpoint's owner and are pruned after [`DELIVERY_LOG_DAYS`].
+//!
+//! Each delivery is a JSON POST of an [`Envelope`], signed with the endpoint's secret:
+//! `X-PinePods-Signature: sha256=<hex HMAC-SHA256 of "{timestamp}.{body}">`, with the timestamp
+//! in `X-PinePods-Timestamp`. Receivers should recompute the HMAC and reject stale timestamps.
+//! `X-PinePods-Delivery` carries the event id, which stays the same across retries.
+
+use crate::database::DatabasePool;
+use crate::error::{AppError, AppResult};
+use crate::services::outbox::{Outbox, STATUS_PENDING};
+use crate::services::scrobble::{http_client, SubmitError};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::Row;
+use tracing::{info, warn};
+
+pub const SCOPE_USER: &str = "user";
+pub const SCOPE_ADMIN: &str = "admin";
+/// Subscribes an endpoint to every event its scope can see.
+pub const ALL_EVENTS: &str = "*";
+
+const STATUS_DELIVERED: &str = "delivered";
+
+/// Attempts before a delivery is marked failed. With the backoff below (30 seconds, doubling,
+/// capped at six hours) that spans about four hours.
+pub const MAX_ATTEMPTS: i32 = 10;
+
+const DELIVERIES: Outbox = Outbox {
+    table: (r#""WebhookDeliveries""#, "WebhookDeliveries"),
+    id_column: ("deliveryid", "DeliveryID"),
+    done_at_column: ("deliveredat", "DeliveredAt"),
+    done_status: STATUS_DELIVERED,
+    endpoint_table: (r#""Webhooks""#, "Webhooks"),
+    endpoint_id_column: ("webhookid", "WebhookID"),
+    max_attempts: MAX_ATTEMPTS,
+    first_retry_seconds: 30,
+    max_retry_seconds: 6 * 60 * 60,
+};
+/// Finished deliveries are kept this long for the delivery log.
+pub const DELIVERY_LOG_DAYS: i64 = 30;
+
+/// Something that happened on the server that webhooks can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    EpisodeNew,
+    EpisodeDownloaded,
+    EpisodeCompleted,
+    TranscriptReady,
+    AdsDetected,
+    RefreshFailed,
+    UserCreated,
+    /// Sent only by the test-fire endpoint; endpoints can't subscribe to it.
+    Test,
+}
+
+impl WebhookEvent {
+    /// Every event an endpoint can subscribe to.
+    pub const SUBSCRIBABLE: [WebhookEvent; 7] = [
+        WebhookEvent::EpisodeNew,
+        WebhookEvent::EpisodeDownloaded,
+        WebhookEvent::EpisodeCompleted,
+        WebhookEvent::TranscriptReady,
+        WebhookEvent::AdsDetected,
+        WebhookEvent::RefreshFailed,
+        WebhookEvent::UserCreated,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WebhookEvent::EpisodeNew => "episode.new",
+            WebhookEvent::EpisodeDownloaded => "episode.downloaded",
+            WebhookEvent::EpisodeCompleted => "episode.completed",
+            WebhookEvent::TranscriptReady => "transcript.ready",
+            WebhookEvent::AdsDetected => "ads.detected",
+            WebhookEvent::RefreshFailed => "refresh.failed",
+            WebhookEvent::UserCreated => "user.created",
+            WebhookEvent::Test => "webhook.test",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::SUBSCRIBABLE.into_iter().find(|event| event.name() == name)
+    }
+
+    /// Events about the server rather than one user, delivered only to admin-wide endpoints.
+    pub fn admin_only(self) -> bool {
+        matches!(self, WebhookEvent::UserCreated)
+    }
+
+    /// Content-level work (transcripts, ad detection) runs once per feed episode and is
+    /// reported to every subscriber of it.
+    fn shared_by_subscribers(self) -> bool {
+        matches!(self, WebhookEvent::TranscriptReady | WebhookEvent::AdsDetected)
+    }
+}
+
+/// The body of every delivery.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Envelope {
+    /// Unique per event and endpoint; unchanged across retries so receivers can deduplicate.
+    pub id: String,
+    pub event: String,
+    /// Unix seconds when the event fired.
+    pub created_at: i64,
+    /// The user the event concerns, if any.
+    pub user_id: Option<i32>,
+    pub data: Value,
+}
+
+/// A registered endpoint as shown to its owner. The secret is only returned when it is created.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Webhook {
+    pub webhook_id: i32,
+    /// The user who created it.
+    pub user_id: i32,
+    /// `user` or `admin`.
+    pub scope: String,
+    pub url: String,
+    /// Subscribed event names, or `["*"]` for all.
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub last_success_at: Option<String>,
+    pub last_error: Option<String>,
+    pub pending: i64,
+    pub failed: i64,
+}
+
+/// One entry of an endpoint's delivery log.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WebhookDelivery {
+    pub delivery_id: i32,
+    pub webhook_id: i32,
+    pub event_id: String,
+    pub event: String,
+    /// `pending`, `delivered` or `failed`.
+    pub status: String,
+    pub attempts: i32,
+    /// HTTP status of the last attempt, if the endpoint answered.
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+    /// When a pending delivery is next tried.
+    pub next_attempt_at: Option<String>,
+    pub payload: Value,
+}
+
+/// Normalise a requested event list for storage: `*`, or known event names joined by commas.
+/// Admin-only events need an admin-wide endpoint.
+pub fn parse_events(events: &[String], scope: &str) -> Result<String, String> {
+    let mut names: Vec<&'static str> = Vec::new();
+    for requested in events.iter().map(|e| e.trim()).filter(|e| !e.is_empty()) {
+        if requested == ALL_EVENTS {
+            return Ok(ALL_EVENTS.to_string());
+        }
+        let event = WebhookEvent::from_name(requested).ok_or_else(|| {
+            format!(
+                "Unknown event '{}' (expected one of: {})",
+                requested,
+                WebhookEvent::SUBSCRIBABLE.map(WebhookEvent::name).join(", ")
+            )
+        })?;
+        if event.admin_only() && scope != SCOPE_ADMIN {
+            return Err(format!("'{}' is only available to admin webhooks", requested));
+        }
+        if !names.contains(&event.name()) {
+            names.push(event.name());
+        }
+    }
+    if names.is_empty() {
+        return Err("At least one event is required".to_string());
+    }
+    Ok(names.join(","))
+}
+
+fn subscribes(events: &str, event: WebhookEvent) -> bool {
+    events == ALL_EVENTS || events.split(',').any(|name| name == event.name())
+}
+
+/// `sha256=<hex>` signature of `{timestamp}.{body}` under `secret`.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// A random signing secret for a new endpoint.
+pub fn new_secret() -> String {
+    use rand::distr::Alphanumeric;
+    use rand::RngExt;
+    rand::rng().sample_iter(&Alphanumeric).take(40).map(char::from).collect()
+}
+
+fn format_time(time: Option<chrono::NaiveDateTime>) -> Option<String> {
+    time.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+// ---- Endpoint management ----
+
+/// The user's own endpoints, and every admin-wide endpoint when `include_admin`.
+pub async fn list_webhooks(db_pool: &DatabasePool, user_id: i32, include_admin: bool) -> AppResult<Vec<Webhook>> {
+    let webhooks = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT w.webhookid, w.userid, w.scope, w.url, w.events, w.enabled, w.lastsuccessat, w.lasterror,
+                      COUNT(d.deliveryid) FILTER (WHERE d.status = 'pending') AS pending,
+                      COUNT(d.deliveryid) FILTER (WHERE d.status = 'failed') AS failed
+               FROM "Webhooks" w LEFT JOIN "WebhookDeliveries" d ON d.webhookid = w.webhookid
+               WHERE (w.scope = 'user' AND w.userid = $1) OR (w.scope = 'admin' AND $2)
+               GROUP BY w.webhookid
+               ORDER BY w.webhookid"#,
+        )
+        .bind(user_id)
+        .bind(include_admin)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<Webhook> {
+            Ok(Webhook {
+                webhook_id: r.try_get("webhookid")?,
+                user_id: r.try_get("userid")?,
+                scope: r.try_get("scope")?,
+                url: r.try_get("url")?,
+                events: r.try_get::<String, _>("events")?.split(',').map(str::to_string).collect(),
+                enabled: r.try_get("enabled")?,
+                last_success_at: format_time(r.try_get("lastsuccessat")?),
+                last_error: r.try_get("lasterror")?,
+                pending: r.try_get("pending")?,
+                failed: r.try_get("failed")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT w.WebhookID, w.UserID, w.Scope, w.Url, w.Events, CAST(w.Enabled AS SIGNED) AS enabled,
+                    w.LastSuccessAt, w.LastError,
+                    CAST(COALESCE(SUM(d.Status = 'pending'), 0) AS SIGNED) AS pending,
+                    CAST(COALESCE(SUM(d.Status = 'failed'), 0) AS SIGNED) AS failed
+             FROM Webhooks w LEFT JOIN WebhookDeliveries d ON d.WebhookID = w.WebhookID
+             WHERE (w.Scope = 'user' AND w.UserID = ?) OR (w.Scope = 'admin' AND ?)
+             GROUP BY w.WebhookID, w.UserID, w.Scope, w.Url, w.Events, w.Enabled, w.LastSuccessAt, w.LastError
+             ORDER BY w.WebhookID",
+        )
+        .bind(user_id)
+        .bind(include_admin)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<Webhook> {
+            Ok(Webhook {
+                webhook_id: r.try_get("WebhookID")?,
+                user_id: r.try_get("UserID")?,
+                scope: r.try_get("Scope")?,
+                url: r.try_get("Url")?,
+                events: r.try_get::<String, _>("Events")?.split(',').map(str::to_string).collect(),
+                enabled: r.try_get::<i64, _>("enabled")? != 0,
+                last_success_at: format_time(r.try_get("LastSuccessAt")?),
+                last_error: r.try_get("LastError")?,
+                pending: r.try_get("pending")?,
+                failed: r.try_get("failed")?,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(webhooks)
+}
+
+/// The creator and scope of an endpoint, for access checks.
+pub async fn webhook_owner(db_pool: &DatabasePool, webhook_id: i32) -> AppResult<Option<(i32, String)>> {
+    let owner = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT userid, scope FROM "Webhooks" WHERE webhookid = $1"#)
+            .bind(webhook_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| -> AppResult<(i32, String)> { Ok((r.try_get("userid")?, r.try_get("scope")?)) })
+            .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query("SELECT UserID, Scope FROM Webhooks WHERE WebhookID = ?")
+            .bind(webhook_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| -> AppResult<(i32, String)> { Ok((r.try_get("UserID")?, r.try_get("Scope")?)) })
+            .transpose()?,
+    };
+    Ok(owner)
+}
+
+/// Register an endpoint. `events` must already be normalised by [`parse_events`]; the secret is
+/// encrypted before it is stored. Returns the new webhook id.
+pub async fn add_webhook(
+    db_pool: &DatabasePool,
+    user_id: i32,
+    scope: &str,
+    url: &str,
+    events: &str,
+    secret: &str,
+) -> AppResult<i32> {
+    let secret = db_pool.encrypt_password(secret).await?;
+    let webhook_id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"INSERT INTO "Webhooks" (userid, scope, url, secret, events)
+               VALUES ($1, $2, $3, $4, $5) RETURNING webhookid"#,
+        )
+        .bind(user_id)
+        .bind(scope)
+        .bind(url)
+        .bind(&secret)
+        .bind(events)
+        .fetch_one(pool)
+        .await?
+        .try_get("webhookid")?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "INSERT INTO Webhooks (UserID, Scope, Url, Secret, Events) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(scope)
+        .bind(url)
+        .bind(&secret)
+        .bind(events)
+        .execute(pool)
+        .await?
+        .last_insert_id() as i32,
+    };
+    Ok(webhook_id)
+}
+
+/// Delete an endpoint along with its delivery log. Returns whether a row was removed.
+pub async fn remove_webhook(db_pool: &DatabasePool, webhook_id: i32) -> AppResult<bool> {
+    let removed = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"DELETE FROM "Webhooks" WHERE webhookid = $1"#)
+            .bind(webhook_id)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query("DELETE FROM Webhooks WHERE WebhookID = ?")
+            .bind(webhook_id)
+            .execute(pool)
+            .await?
+            .rows_affected(),
+    };
+    Ok(removed > 0)
+}
+
+/// The most recent deliveries to an endpoint, newest first.
+pub async fn list_deliveries(db_pool: &DatabasePool, webhook_id: i32, limit: i64) -> AppResult<Vec<WebhookDelivery>> {
+    let deliveries = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT deliveryid, webhookid, eventid, event, status, attempts, responsestatus, lasterror,
+                      createdat, deliveredat, nextattemptat, payload
+               FROM "WebhookDeliveries" WHERE webhookid = $1
+               ORDER BY deliveryid DESC LIMIT $2"#,
+        )
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(pg_delivery)
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT DeliveryID, WebhookID, EventID, Event, Status, Attempts, ResponseStatus, LastError,
+                    CreatedAt, DeliveredAt, NextAttemptAt, Payload
+             FROM WebhookDeliveries WHERE WebhookID = ?
+             ORDER BY DeliveryID DESC LIMIT ?",
+        )
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(mysql_delivery)
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(deliveries)
+}
+
+async fn get_delivery(db_pool: &DatabasePool, delivery_id: i32) -> AppResult<Option<WebhookDelivery>> {
+    let delivery = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT deliveryid, webhookid, eventid, event, status, attempts, responsestatus, lasterror,
+                      createdat, deliveredat, nextattemptat, payload
+               FROM "WebhookDeliveries" WHERE deliveryid = $1"#,
+        )
+        .bind(delivery_id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(pg_delivery)
+        .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT DeliveryID, WebhookID, EventID, Event, Status, Attempts, ResponseStatus, LastError,
+                    CreatedAt, DeliveredAt, NextAttemptAt, Payload
+             FROM WebhookDeliveries WHERE DeliveryID = ?",
+        )
+        .bind(delivery_id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(mysql_delivery)
+        .transpose()?,
+    };
+    Ok(delivery)
+}
+
+fn pg_delivery(r: &sqlx::postgres::PgRow) -> AppResult<WebhookDelivery> {
+    let status: String = r.try_get("status")?;
+    Ok(WebhookDelivery {
+        delivery_id: r.try_get("deliveryid")?,
+        webhook_id: r.try_get("webhookid")?,
+        event_id: r.try_get("eventid")?,
+        event: r.try_get("event")?,
+        attempts: r.try_get("attempts")?,
+        response_status: r.try_get("responsestatus")?,
+        last_error: r.try_get("lasterror")?,
+        created_at: format_time(r.try_get("createdat")?).unwrap_or_default(),
+        delivered_at: format_time(r.try_get("deliveredat")?),
+        next_attempt_at: format_time(r.try_get("nextattemptat")?).filter(|_| status == STATUS_PENDING),
+        payload: serde_json::from_str(&r.try_get::<String, _>("payload")?).unwrap_or(Value::Null),
+        status,
+    })
+}
+
+fn mysql_delivery(r: &sqlx::mysql::MySqlRow) -> AppResult<WebhookDelivery> {
+    let status: String = r.try_get("Status")?;
+    Ok(WebhookDelivery {
+        delivery_id: r.try_get("DeliveryID")?,
+        webhook_id: r.try_get("WebhookID")?,
+        event_id: r.try_get("EventID")?,
+        event: r.try_get("Event")?,
+        attempts: r.try_get("Attempts")?,
+        response_status: r.try_get("ResponseStatus")?,
+        last_error: r.try_get("LastError")?,
+        created_at: format_time(r.try_get("CreatedAt")?).unwrap_or_default(),
+        delivered_at: format_time(r.try_get("DeliveredAt")?),
+        next_attempt_at: format_time(r.try_get("NextAttemptAt")?).filter(|_| status == STATUS_PENDING),
+        payload: serde_json::from_str(&r.try_get::<String, _>("Payload")?).unwrap_or(Value::Null),
+        status,
+    })
+}
+
+// ---- Queueing ----
+
+/// Whether any endpoint is enabled at all, so hooks on hot paths can skip loading event data.
+async fn any_enabled(db_pool: &DatabasePool) -> AppResult<bool> {
+    let any = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(r#"SELECT EXISTS(SELECT 1 FROM "Webhooks" WHERE enabled = TRUE) AS any_on"#)
+            .fetch_one(pool)
+            .await?
+            .try_get::<bool, _>("any_on")?,
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("SELECT CAST(EXISTS(SELECT 1 FROM Webhooks WHERE Enabled = 1) AS SIGNED) AS any_on")
+                .fetch_one(pool)
+                .await?
+                .try_get::<i64, _>("any_on")?
+                != 0
+        }
+    };
+    Ok(any)
+}
+
+/// Enabled endpoints that should receive `event` about `user_id`: the user's own endpoints and
+/// every admin-wide one, filtered by their subscriptions.
+async fn matching_webhooks(db_pool: &DatabasePool, user_id: Option<i32>, event: WebhookEvent) -> AppResult<Vec<i32>> {
+    let rows: Vec<(i32, String, String)> = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT webhookid, scope, events FROM "Webhooks"
+               WHERE enabled = TRUE AND (scope = 'admin' OR userid = $1)"#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, String, String)> {
+            Ok((r.try_get("webhookid")?, r.try_get("scope")?, r.try_get("events")?))
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT WebhookID, Scope, Events FROM Webhooks
+             WHERE Enabled = 1 AND (Scope = 'admin' OR UserID = ?)",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, String, String)> {
+            Ok((r.try_get("WebhookID")?, r.try_get("Scope")?, r.try_get("Events")?))
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(rows
+        .into_iter()
+        .filter(|(_, scope, events)| (scope == SCOPE_ADMIN || !event.admin_only()) && subscribes(events, event))
+        .map(|(webhook_id, _, _)| webhook_id)
+        .collect())
+}
+
+/// Queue one delivery of `event` for an endpoint. Returns the delivery id.
+async fn insert_delivery(
+    db_pool: &DatabasePool,
+    webhook_id: i32,
+    user_id: Option<i32>,
+    event: WebhookEvent,
+    data: &Value,
+) -> AppResult<i32> {
+    let envelope = Envelope {
+        id: uuid::Uuid::new_v4().to_string(),
+        event: event.name().to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+        user_id,
+        data: data.clone(),
+    };
+    let payload = serde_json::to_string(&envelope).map_err(|e| AppError::internal(e.to_string()))?;
+
+    let delivery_id = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"INSERT INTO "WebhookDeliveries" (webhookid, eventid, event, payload)
+               VALUES ($1, $2, $3, $4) RETURNING deliveryid"#,
+        )
+        .bind(webhook_id)
+        .bind(&envelope.id)
+        .bind(&envelope.event)
+        .bind(&payload)
+        .fetch_one(pool)
+        .await?
+        .try_get("deliveryid")?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "INSERT INTO WebhookDeliveries (WebhookID, EventID, Event, Payload) VALUES (?, ?, ?, ?)",
+        )
+        .bind(webhook_id)
+        .bind(&envelope.id)
+        .bind(&envelope.event)
+        .bind(&payload)
+        .execute(pool)
+        .await?
+        .last_insert_id() as i32,
+    };
+    Ok(delivery_id)
+}
+
+/// Queue `event` for every matching endpoint. Returns the new delivery ids.
+pub async fn enqueue(db_pool: &DatabasePool, user_id: Option<i32>, event: WebhookEvent, data: &Value) -> AppResult<Vec<i32>> {
+    let mut delivery_ids = Vec::new();
+    for webhook_id in matching_webhooks(db_pool, user_id, event).await? {
+        delivery_ids.push(insert_delivery(db_pool, webhook_id, user_id, event, data).await?);
+    }
+    Ok(delivery_ids)
+}
+
+/// Hook for server events: queue `event` for the matching endpoints and deliver it. Detached,
+/// so the code raising the event never waits on (or fails because of) a receiver.
+pub fn emit(db_pool: &DatabasePool, user_id: Option<i32>, event: WebhookEvent, data: Value) {
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        match enqueue(&db_pool, user_id, event, &data).await {
+            Ok(delivery_ids) => deliver(&db_pool, &delivery_ids).await,
+            Err(e) => warn!("Failed to queue {} webhooks: {}", event.name(), e),
+        }
+    });
+}
+
+/// The episode fields every episode event carries, for each user it concerns. Content-level
+/// events cover every subscriber's copy of the feed episode.
+async fn episode_subjects(db_pool: &DatabasePool, episode_id: i32, shared: bool) -> AppResult<Vec<(i32, Value)>> {
+    let subjects = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT e.episodeid, e.episodetitle, e.episodeurl, e.episodepubdate, e.episodeduration,
+                      p.podcastid, p.podcastname, p.feedurl, p.userid
+               FROM "Episodes" e JOIN "Podcasts" p ON p.podcastid = e.podcastid
+               WHERE e.episodeid = $1
+                  OR ($2 AND e.feedepisodeid = (SELECT feedepisodeid FROM "Episodes" WHERE episodeid = $1))"#,
+        )
+        .bind(episode_id)
+        .bind(shared)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, Value)> {
+            Ok((
+                r.try_get("userid")?,
+                serde_json::json!({
+                    "episode_id": r.try_get::<i32, _>("episodeid")?,
+                    "episode_title": r.try_get::<Option<String>, _>("episodetitle")?,
+                    "episode_url": r.try_get::<Option<String>, _>("episodeurl")?,
+                    "pub_date": format_time(r.try_get("episodepubdate")?),
+                    "duration_seconds": r.try_get::<Option<i32>, _>("episodeduration")?,
+                    "podcast_id": r.try_get::<i32, _>("podcastid")?,
+                    "podcast_name": r.try_get::<Option<String>, _>("podcastname")?,
+                    "feed_url": r.try_get::<Option<String>, _>("feedurl")?,
+                }),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT e.EpisodeID, e.EpisodeTitle, e.EpisodeURL, e.EpisodePubDate, e.EpisodeDuration,
+                    p.PodcastID, p.PodcastName, p.FeedURL, p.UserID
+             FROM Episodes e JOIN Podcasts p ON p.PodcastID = e.PodcastID
+             WHERE e.EpisodeID = ?
+                OR (? AND e.FeedEpisodeID = (SELECT FeedEpisodeID FROM Episodes WHERE EpisodeID = ?))",
+        )
+        .bind(episode_id)
+        .bind(shared)
+        .bind(episode_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, Value)> {
+            Ok((
+                r.try_get("UserID")?,
+                serde_json::json!({
+                    "episode_id": r.try_get::<i32, _>("EpisodeID")?,
+                    "episode_title": r.try_get::<Option<String>, _>("EpisodeTitle")?,
+                    "episode_url": r.try_get::<Option<String>, _>("EpisodeURL")?,
+                    "pub_date": format_time(r.try_get("EpisodePubDate")?),
+                    "duration_seconds": r.try_get::<Option<i32>, _>("EpisodeDuration")?,
+                    "podcast_id": r.try_get::<i32, _>("PodcastID")?,
+                    "podcast_name": r.try_get::<Option<String>, _>("PodcastName")?,
+                    "feed_url": r.try_get::<Option<String>, _>("FeedURL")?,
+                }),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+    };
+    Ok(subjects)
+}
+
+/// [`emit`] for an event about an episode: the payload is the episode's details plus `extra`.
+pub fn emit_episode_event(db_pool: &DatabasePool, episode_id: i32, event: WebhookEvent, extra: Value) {
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        let result = async {
+            if !any_enabled(&db_pool).await? {
+                return Ok(Vec::new());
+            }
+            let mut delivery_ids = Vec::new();
+            for (user_id, mut data) in episode_subjects(&db_pool, episode_id, event.shared_by_subscribers()).await? {
+                if let (Some(data), Value::Object(extra)) = (data.as_object_mut(), &extra) {
+                    data.extend(extra.clone());
+                }
+                delivery_ids.extend(enqueue(&db_pool, Some(user_id), event, &data).await?);
+            }
+            AppResult::Ok(delivery_ids)
+        }
+        .await;
+        match result {
+            Ok(delivery_ids) => deliver(&db_pool, &delivery_ids).await,
+            Err(e) => warn!("Failed to queue {} webhooks for episode {}: {}", event.name(), episode_id, e),
+        }
+    });
+}
+
+/// `user.created` for the admin-wide endpoints. `source` says how the account came about:
+/// `setup`, `admin`, `self_service` or `oidc`.
+pub fn emit_user_created(db_pool: &DatabasePool, user_id: i32, username: &str, email: &str, source: &str) {
+    emit(
+        db_pool,
+        Some(user_id),
+        WebhookEvent::UserCreated,
+        serde_json::json!({
+            "user_id": user_id,
+            "username": username,
+            "email": email,
+            "source": source,
+        }),
+    );
+}
+
+/// `refresh.failed` for each of the podcasts whose refresh just failed for the first time since
+/// it last succeeded, so a feed that stays broken doesn't fire on every refresh cycle.
+pub fn emit_refresh_failures(db_pool: &DatabasePool, podcast_ids: &[i32], error: &str) {
+    let db_pool = db_pool.clone();
+    let podcast_ids = podcast_ids.to_vec();
+    let error = error.to_string();
+    tokio::spawn(async move {
+        let result = async {
+            if !any_enabled(&db_pool).await? {
+                return Ok(Vec::new());
+            }
+            let mut delivery_ids = Vec::new();
+            for (user_id, mut data) in newly_failing_podcasts(&db_pool, &podcast_ids).await? {
+                data["error"] = Value::String(error.clone());
+                delivery_ids.extend(enqueue(&db_pool, Some(user_id), WebhookEvent::RefreshFailed, &data).await?);
+            }
+            AppResult::Ok(delivery_ids)
+        }
+        .await;
+        match result {
+            Ok(delivery_ids) => deliver(&db_pool, &delivery_ids).await,
+            Err(e) => warn!("Failed to queue refresh.failed webhooks: {}", e),
+        }
+    });
+}
+
+async fn newly_failing_podcasts(db_pool: &DatabasePool, podcast_ids: &[i32]) -> AppResult<Vec<(i32, Value)>> {
+    let podcasts = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT podcastid, podcastname, feedurl, userid FROM "Podcasts"
+               WHERE podcastid = ANY($1) AND consecutivefailures = 1"#,
+        )
+        .bind(podcast_ids)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| -> AppResult<(i32, Value)> {
+            Ok((
+                r.try_get("userid")?,
+                serde_json::json!({
+                    "podcast_id": r.try_get::<i32, _>("podcastid")?,
+                    "podcast_name": r.try_get::<Option<String>, _>("podcastname")?,
+                    "feed_url": r.try_get::<Option<String>, _>("feedurl")?,
+                }),
+            ))
+        })
+        .collect::<AppResult<Vec<_>>>()?,
+        DatabasePool::MySQL(pool) => {
+            let placeholders = podcast_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+            let sql = format!(
+                "SELECT PodcastID, PodcastName, FeedURL, UserID FROM Podcasts
+                 WHERE PodcastID IN ({}) AND ConsecutiveFailures = 1",
+                placeholders
+            );
+            let mut query = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()));
+            for id in podcast_ids {
+                query = query.bind(id);
+            }
+            query
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|r| -> AppResult<(i32, Value)> {
+                    Ok((
+                        r.try_get("UserID")?,
+                        serde_json::json!({
+                            "podcast_id": r.try_get::<i32, _>("PodcastID")?,
+                            "podcast_name": r.try_get::<Option<String>, _>("PodcastName")?,
+                            "feed_url": r.try_get::<Option<String>, _>("FeedURL")?,
+                        }),
+                    ))
+                })
+                .collect::<AppResult<Vec<_>>>()?
+        }
+    };
+    Ok(podcasts)
+}
+
+// ---- Delivery ----
+
+struct QueuedDelivery {
+    delivery_id: i32,
+    webhook_id: i32,
+    url: String,
+    secret: String,
+    event: String,
+    event_id: String,
+    payload: String,
+    attempts: i32,
+}
+
+/// The result of one POST: the status code if the endpoint answered, and whether it succeeded.
+struct Attempt {
+    response_status: Option<i32>,
+    result: Result<(), SubmitError>,
+}
+
+async fn load_delivery(db_pool: &DatabasePool, delivery_id: i32) -> AppResult<Option<QueuedDelivery>> {
+    let delivery = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT d.deliveryid, d.webhookid, w.url, w.secret, d.event, d.eventid, d.payload, d.attempts
+               FROM "WebhookDeliveries" d JOIN "Webhooks" w ON w.webhookid = d.webhookid
+               WHERE d.deliveryid = $1"#,
+        )
+        .bind(delivery_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| -> AppResult<QueuedDelivery> {
+            Ok(QueuedDelivery {
+                delivery_id: r.try_get("deliveryid")?,
+                webhook_id: r.try_get("webhookid")?,
+                url: r.try_get("url")?,
+                secret: r.try_get("secret")?,
+                event: r.try_get("event")?,
+                event_id: r.try_get("eventid")?,
+                payload: r.try_get("payload")?,
+                attempts: r.try_get("attempts")?,
+            })
+        })
+        .transpose()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT d.DeliveryID, d.WebhookID, w.Url, w.Secret, d.Event, d.EventID, d.Payload, d.Attempts
+             FROM WebhookDeliveries d JOIN Webhooks w ON w.WebhookID = d.WebhookID
+             WHERE d.DeliveryID = ?",
+        )
+        .bind(delivery_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| -> AppResult<QueuedDelivery> {
+            Ok(QueuedDelivery {
+                delivery_id: r.try_get("DeliveryID")?,
+                webhook_id: r.try_get("WebhookID")?,
+                url: r.try_get("Url")?,
+                secret: r.try_get("Secret")?,
+                event: r.try_get("Event")?,
+                event_id: r.try_get("EventID")?,
+                payload: r.try_get("Payload")?,
+                attempts: r.try_get("Attempts")?,
+            })
+        })
+        .transpose()?,
+    };
+    Ok(delivery)
+}
+
+/// POST a stored payload to an endpoint, signed with its (decrypted) secret.
+async fn post(url: &str, secret: &str, event: &str, event_id: &str, body: &str) -> Attempt {
+    let client = match http_client() {
+        Ok(client) => client,
+        Err(e) => return Attempt { response_status: None, result: Err(e) },
+    };
+    let timestamp = chrono::Utc::now().timestamp();
+    let request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-PinePods-Event", event)
+        .header("X-PinePods-Delivery", event_id)
+        .header("X-PinePods-Timestamp", timestamp.to_string())
+        .header("X-PinePods-Signature", sign(secret, timestamp, body))
+        .body(body.to_string());
+
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            let result = if status.is_success() {
+                Ok(())
+            } else {
+                let text = response.text().await.unwrap_or_default();
+                Err(SubmitError::from_status(status, &text))
+            };
+            Attempt { response_status: Some(status.as_u16() as i32), result }
+        }
+        Err(e) => Attempt { response_status: None, result: Err(SubmitError::Retryable(e.to_string())) },
+    }
+}
+
+/// Record the outcome of one attempt, with the endpoint's response status for the delivery log.
+/// Without `retry` a retryable failure is final too (test deliveries).
+async fn record_attempt(
+    db_pool: &DatabasePool,
+    item: &QueuedDelivery,
+    attempt: &Attempt,
+    retry: bool,
+) -> AppResult<&'static str> {
+    let status = DELIVERIES
+        .record_attempt(db_pool, item.delivery_id, item.webhook_id, item.attempts, &attempt.result, retry)
+        .await?;
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"UPDATE "WebhookDeliveries" SET responsestatus = $2 WHERE deliveryid = $1"#)
+                .bind(item.delivery_id)
+                .bind(attempt.response_status)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE WebhookDeliveries SET ResponseStatus = ? WHERE DeliveryID = ?")
+                .bind(attempt.response_status)
+                .bind(item.delivery_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(status)
+}
+
+/// Claim and attempt one delivery. Returns its new status, or None if another run had it.
+async fn deliver_one(db_pool: &DatabasePool, delivery_id: i32, retry: bool) -> AppResult<Option<&'static str>> {
+    if !DELIVERIES.claim(db_pool, delivery_id).await? {
+        return Ok(None);
+    }
+    let Some(item) = load_delivery(db_pool, delivery_id).await? else {
+        return Ok(None);
+    };
+
+    let attempt = match db_pool.decrypt_password(&item.secret).await {
+        Ok(secret) => post(&item.url, &secret, &item.event, &item.event_id, &item.payload).await,
+        Err(e) => Attempt {
+            response_status: None,
+            result: Err(SubmitError::Permanent(format!("Could not decrypt webhook secret: {}", e))),
+        },
+    };
+    if let Err(e) = &attempt.result {
+        warn!("Webhook delivery {} ({}) to endpoint {} failed: {}", item.delivery_id, item.event, item.webhook_id, e.message());
+    }
+    record_attempt(db_pool, &item, &attempt, retry).await.map(Some)
+}
+
+/// Attempt the given deliveries once each; failures are left to the retry sweep.
+pub async fn deliver(db_pool: &DatabasePool, delivery_ids: &[i32]) {
+    for &delivery_id in delivery_ids {
+        if let Err(e) = deliver_one(db_pool, delivery_id, true).await {
+            warn!("Webhook delivery {} could not be recorded: {}", delivery_id, e);
+        }
+    }
+}
+
+/// Send a `webhook.test` event to one endpoint right away, without retries, and return the
+/// resulting delivery log entry.
+pub async fn test_fire(db_pool: &DatabasePool, webhook_id: i32, user_id: i32) -> AppResult<WebhookDelivery> {
+    let data = serde_json::json!({
+        "webhook_id": webhook_id,
+        "message": "Test delivery from PinePods",
+    });
+    let delivery_id = insert_delivery(db_pool, webhook_id, Some(user_id), WebhookEvent::Test, &data).await?;
+    deliver_one(db_pool, delivery_id, false).await?;
+    get_delivery(db_pool, delivery_id)
+        .await?
+        .ok_or_else(|| AppError::internal("Test delivery disappeared"))
+}
+
+async fn due_delivery_ids(db_pool: &DatabasePool) -> AppResult<Vec<i32>> {
+    let ids = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"SELECT d.deliveryid
+               FROM "WebhookDeliveries" d JOIN "Webhooks" w ON w.webhookid = d.webhookid
+               WHERE w.enabled = TRUE AND d.status = $1 AND d.nextattemptat <= NOW()
+               ORDER BY d.deliveryid
+               LIMIT 500"#,
+        )
+        .bind(STATUS_PENDING)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<i32, _>("deliveryid"))
+        .collect::<Result<Vec<_>, _>>()?,
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "SELECT d.DeliveryID
+             FROM WebhookDeliveries d JOIN Webhooks w ON w.WebhookID = d.WebhookID
+             WHERE w.Enabled = 1 AND d.Status = ? AND d.NextAttemptAt <= NOW()
+             ORDER BY d.DeliveryID
+             LIMIT 500",
+        )
+        .bind(STATUS_PENDING)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.try_get::<i32, _>("DeliveryID"))
+        .collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(ids)
+}
+
+/// Scheduler entry point: retry every delivery whose backoff has elapsed.
+pub async fn retry_due(db_pool: &DatabasePool) -> AppResult<()> {
+    let delivery_ids = due_delivery_ids(db_pool).await?;
+    if !delivery_ids.is_empty() {
+        info!("Retrying {} due webhook deliveries", delivery_ids.len());
+    }
+    deliver(db_pool, &delivery_ids).await;
+    Ok(())
+}
+
+/// Drop finished deliveries older than [`DELIVERY_LOG_DAYS`]. Returns how many were removed.
+pub async fn prune_deliveries(db_pool: &DatabasePool) -> AppResult<u64> {
+    let removed = match db_pool {
+        DatabasePool::Postgres(pool) => sqlx::query(
+            r#"DELETE FROM "WebhookDeliveries"
+               WHERE status <> $1 AND createdat < NOW() - (INTERVAL '1 day' * $2)"#,
+        )
+        .bind(STATUS_PENDING)
+        .bind(DELIVERY_LOG_DAYS as f64)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+        DatabasePool::MySQL(pool) => sqlx::query(
+            "DELETE FROM WebhookDeliveries WHERE Status <> ? AND CreatedAt < NOW() - INTERVAL ? DAY",
+        )
+        .bind(STATUS_PENDING)
+        .bind(DELIVERY_LOG_DAYS)
+        .execute(pool)
+        .await?
+        .rows_affected(),
+    };
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_server;
+    use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post as post_route, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Vec<(HeaderMap, String)>;
+
+    /// A stand-in receiver: records each request's headers and raw body, and answers with a
+    /// fixed status.
+    async fn receiver(status: StatusCode) -> (String, Arc<Mutex<Received>>) {
+        let received: Arc<Mutex<Received>> = Arc::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post_route(|State((status, received)): State<(StatusCode, Arc<Mutex<Received>>)>, headers: HeaderMap, body: String| async move {
+                    received.lock().unwrap().push((headers, body));
+                    status
+                }),
+            )
+            .with_state((status, received.clone()));
+        (format!("{}/hook", test_server::serve(app).await), received)
+    }
+
+    #[test]
+    fn event_lists_are_validated_per_scope() {
+        let events = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(parse_events(&events(&["episode.new", "ads.detected", "episode.new"]), SCOPE_USER).unwrap(), "episode.new,ads.detected");
+        assert_eq!(parse_events(&events(&["episode.new", "*"]), SCOPE_USER).unwrap(), ALL_EVENTS);
+        assert!(parse_events(&events(&["user.created"]), SCOPE_USER).is_err());
+        assert!(parse_events(&events(&["user.created"]), SCOPE_ADMIN).is_ok());
+        assert!(parse_events(&events(&["webhook.test"]), SCOPE_ADMIN).is_err());
+        assert!(parse_events(&events(&[" "]), SCOPE_USER).is_err());
+
+        assert!(subscribes("episode.new,ads.detected", WebhookEvent::AdsDetected));
+        assert!(!subscribes("episode.new", WebhookEvent::EpisodeCompleted));
+        assert!(subscribes(ALL_EVENTS, WebhookEvent::RefreshFailed));
+        assert_eq!(DELIVERIES.retry_delay_seconds(1), 30);
+        assert_eq!(DELIVERIES.retry_delay_seconds(4), 240);
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_signed_over_timestamp_and_body() {
+        let (url, received) = receiver(StatusCode::NO_CONTENT).await;
+        let body = r#"{"id":"abc","event":"episode.new"}"#;
+        let attempt = post(&url, "s3cret", "episode.new", "abc", body).await;
+        assert!(attempt.result.is_ok());
+        assert_eq!(attempt.response_status, Some(204));
+
+        let received = received.lock().unwrap();
+        let (headers, got) = &received[0];
+        assert_eq!(got, body);
+        assert_eq!(headers["x-pinepods-event"], "episode.new");
+        assert_eq!(headers["x-pinepods-delivery"], "abc");
+        let timestamp: i64 = headers["x-pinepods-timestamp"].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers["x-pinepods-signature"].to_str().unwrap(), sign("s3cret", timestamp, body));
+        assert_ne!(sign("other", timestamp, body), sign("s3cret", timestamp, body));
+    }
+
+    #[tokio::test]
+    async fn server_errors_retry_and_client_errors_do_not() {
+        let (url, _) = receiver(StatusCode::BAD_GATEWAY).await;
+        let attempt = post(&url, "k", "episode.new", "1", "{}").await;
+        assert_eq!(attempt.response_status, Some(502));
+        assert!(matches!(attempt.result, Err(SubmitError::Retryable(_))));
+
+        let (url, _) = receiver(StatusCode::GONE).await;
+        let attempt = post(&url, "k", "episode.new", "1", "{}").await;
+        assert!(matches!(attempt.result, Err(SubmitError::Permanent(_))));
+    }
+}
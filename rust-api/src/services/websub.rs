@@ -0,0 +1,662 @@
+//! WebSub (PubSubHubbub) push subscriptions.
+//!
+//! Feeds that advertise `<atom:link rel="hub">` are subscribed at their hub whenever a refresh
+//! parses them. The hub then calls `/api/websub/callback/{token}`: a GET to verify intent (we echo
+//! `hub.challenge`) and a signed POST whenever the feed changes, which refreshes that one feed
+//! through the normal `refresh_feed_group` path.
+//!
+//! Feeds with a verified, unexpired lease are left out of the scheduled poll apart from a daily
+//! safety poll. Every other state — the feed no longer lists a hub, the hub denied or never
+//! verified the subscription, the hub is unreachable, the lease lapsed — leaves the feed on
+//! normal polling. Leases are renewed by the scheduler a day before they expire.
+//!
+//! Push needs a callback URL the hub can reach, so it is only used when SERVER_URL is set to a
+//! non-local address. `PINEPODS_WEBSUB=false` turns it off entirely.
+
+use crate::database::DatabasePool;
+use crate::error::AppResult;
+use hmac::{Hmac, Mac};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use sqlx::Row;
+use tracing::{debug, info, warn};
+
+pub const CALLBACK_PATH: &str = "/api/websub/callback";
+
+/// Lease we ask hubs for. Hubs may grant less; the granted value comes back on verification.
+pub const REQUESTED_LEASE_SECONDS: i64 = 10 * 24 * 60 * 60;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_VERIFIED: &str = "verified";
+pub const STATUS_DENIED: &str = "denied";
+pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_UNSUBSCRIBING: &str = "unsubscribing";
+
+/// The hub and self (topic) links advertised by a feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HubLinks {
+    pub hub: String,
+    /// The feed's rel="self" URL. Hubs key subscriptions by topic, so this is what we subscribe
+    /// to when present; otherwise the feed URL we fetched is used.
+    pub topic: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub id: i32,
+    pub feed_url: String,
+    pub hub_url: String,
+    pub topic_url: String,
+    pub token: String,
+    pub secret: String,
+    pub status: String,
+}
+
+/// Find the hub/self links in a feed's channel header. Stops at the first item or entry since
+/// both links belong to the channel.
+pub fn discover(content: &str) -> Option<HubLinks> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().check_end_names = false;
+    let mut hub = None;
+    let mut topic = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                let name = e.name();
+                let name = name.as_ref();
+                if name == b"item" || name == b"entry" {
+                    break;
+                }
+                if name != b"link" && !name.ends_with(b":link") {
+                    continue;
+                }
+                let mut rel = None;
+                let mut href = None;
+                for attr in e.attributes().with_checks(false).flatten() {
+                    let value = String::from_utf8_lossy(&attr.value).trim().replace("&amp;", "&");
+                    match attr.key.as_ref() {
+                        b"rel" => rel = Some(value.to_ascii_lowercase()),
+                        b"href" => href = Some(value),
+                        _ => {}
+                    }
+                }
+                match (rel.as_deref(), href) {
+                    (Some("hub"), Some(h)) if hub.is_none() && !h.is_empty() => hub = Some(h),
+                    (Some("self"), Some(h)) if topic.is_none() && !h.is_empty() => topic = Some(h),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    hub.map(|hub| HubLinks { hub, topic })
+}
+
+/// Public base URL for callbacks, or None when push can't (or shouldn't) be used.
+pub fn callback_base() -> Option<String> {
+    if matches!(
+        std::env::var("PINEPODS_WEBSUB").ok().map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("false" | "0" | "off" | "no")
+    ) {
+        return None;
+    }
+    let base = std::env::var("SERVER_URL").ok()?;
+    let base = base.trim().trim_end_matches('/');
+    let parsed = url::Url::parse(base).ok()?;
+    let host = parsed.host_str()?;
+    let local = host == "localhost"
+        || host.ends_with(".localhost")
+        || host.parse::<std::net::IpAddr>().map(|ip| ip.is_loopback() || ip.is_unspecified()).unwrap_or(false);
+    if local || !matches!(parsed.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(base.to_string())
+}
+
+/// Check an `X-Hub-Signature` header (`<algo>=<hex hmac>`) against the pushed body.
+pub fn verify_signature(secret: &str, header: Option<&str>, body: &[u8]) -> bool {
+    let Some((algo, signature)) = header.and_then(|h| h.trim().split_once('=')) else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(signature.trim()) else {
+        return false;
+    };
+    match algo.trim().to_ascii_lowercase().as_str() {
+        "sha1" => mac_matches::<Hmac<sha1::Sha1>>(secret, body, &expected),
+        "sha256" => mac_matches::<Hmac<sha2::Sha256>>(secret, body, &expected),
+        "sha384" => mac_matches::<Hmac<sha2::Sha384>>(secret, body, &expected),
+        "sha512" => mac_matches::<Hmac<sha2::Sha512>>(secret, body, &expected),
+        _ => false,
+    }
+}
+
+fn mac_matches<M: Mac + hmac::digest::KeyInit>(secret: &str, body: &[u8], expected: &[u8]) -> bool {
+    let Ok(mut mac) = <M as Mac>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(expected).is_ok()
+}
+
+fn new_secret() -> String {
+    use rand::distr::Alphanumeric;
+    use rand::RngExt;
+    rand::rng().sample_iter(&Alphanumeric).take(40).map(char::from).collect()
+}
+
+fn subscription_from_row(row: &sqlx::postgres::PgRow) -> Result<Subscription, sqlx::Error> {
+    Ok(Subscription {
+        id: row.try_get("subscriptionid")?,
+        feed_url: row.try_get("feedurl")?,
+        hub_url: row.try_get("huburl")?,
+        topic_url: row.try_get("topicurl")?,
+        token: row.try_get("callbacktoken")?,
+        secret: row.try_get("secret")?,
+        status: row.try_get("status")?,
+    })
+}
+
+fn subscription_from_mysql_row(row: &sqlx::mysql::MySqlRow) -> Result<Subscription, sqlx::Error> {
+    Ok(Subscription {
+        id: row.try_get("SubscriptionID")?,
+        feed_url: row.try_get("FeedURL")?,
+        hub_url: row.try_get("HubURL")?,
+        topic_url: row.try_get("TopicURL")?,
+        token: row.try_get("CallbackToken")?,
+        secret: row.try_get("Secret")?,
+        status: row.try_get("Status")?,
+    })
+}
+
+const PG_COLUMNS: &str = "subscriptionid, feedurl, huburl, topicurl, callbacktoken, secret, status";
+const MYSQL_COLUMNS: &str = "SubscriptionID, FeedURL, HubURL, TopicURL, CallbackToken, Secret, Status";
+
+async fn load_where(db_pool: &DatabasePool, column: &str, value: &str) -> AppResult<Option<Subscription>> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            let sql = format!(r#"SELECT {} FROM "WebSubSubscriptions" WHERE {} = $1 LIMIT 1"#, PG_COLUMNS, column.to_lowercase());
+            let row = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).bind(value).fetch_optional(pool).await?;
+            Ok(row.as_ref().map(subscription_from_row).transpose()?)
+        }
+        DatabasePool::MySQL(pool) => {
+            let sql = format!("SELECT {} FROM WebSubSubscriptions WHERE {} = ? LIMIT 1", MYSQL_COLUMNS, column);
+            let row = sqlx::query(sqlx::AssertSqlSafe(sql.as_str())).bind(value).fetch_optional(pool).await?;
+            Ok(row.as_ref().map(subscription_from_mysql_row).transpose()?)
+        }
+    }
+}
+
+pub async fn load_by_token(db_pool: &DatabasePool, token: &str) -> AppResult<Option<Subscription>> {
+    load_where(db_pool, "CallbackToken", token).await
+}
+
+async fn load_by_feed(db_pool: &DatabasePool, feed_url: &str) -> AppResult<Option<Subscription>> {
+    load_where(db_pool, "FeedURL", feed_url).await
+}
+
+/// Update a subscription's status. `requested` also stamps RequestedAt (a request to the hub was
+/// just made), which drives the pending timeout and retry back-off.
+async fn set_status(db_pool: &DatabasePool, id: i32, status: &str, error: Option<&str>, requested: bool) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"UPDATE "WebSubSubscriptions"
+                   SET status = $1, lasterror = $2,
+                       requestedat = CASE WHEN $3 THEN CURRENT_TIMESTAMP ELSE requestedat END
+                   WHERE subscriptionid = $4"#,
+            )
+            .bind(status)
+            .bind(error)
+            .bind(requested)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "UPDATE WebSubSubscriptions
+                 SET Status = ?, LastError = ?,
+                     RequestedAt = CASE WHEN ? THEN CURRENT_TIMESTAMP ELSE RequestedAt END
+                 WHERE SubscriptionID = ?",
+            )
+            .bind(status)
+            .bind(error)
+            .bind(requested)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn delete_subscription(db_pool: &DatabasePool, id: i32) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"DELETE FROM "WebSubSubscriptions" WHERE subscriptionid = $1"#)
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("DELETE FROM WebSubSubscriptions WHERE SubscriptionID = ?")
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Send a subscribe/unsubscribe request to the hub. Hubs answer 202 and verify asynchronously
+/// (some verify before answering, which is why the row is written before calling this).
+async fn request_hub(base: &str, sub: &Subscription, mode: &str) -> Result<(), String> {
+    crate::services::url_guard::ensure_safe_public_url_async(&sub.hub_url).await?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .redirect(crate::services::url_guard::guarded_redirect_policy())
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let callback = format!("{}{}/{}", base, CALLBACK_PATH, sub.token);
+    let lease = REQUESTED_LEASE_SECONDS.to_string();
+    let mut form = vec![
+        ("hub.callback", callback.as_str()),
+        ("hub.mode", mode),
+        ("hub.topic", sub.topic_url.as_str()),
+    ];
+    if mode == "subscribe" {
+        form.push(("hub.secret", sub.secret.as_str()));
+        form.push(("hub.lease_seconds", lease.as_str()));
+    }
+
+    let response = client.post(&sub.hub_url).form(&form).send().await.map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Hub responded with HTTP {}", response.status()))
+    }
+}
+
+async fn subscribe(db_pool: &DatabasePool, base: &str, sub: &Subscription) -> AppResult<()> {
+    match request_hub(base, sub, "subscribe").await {
+        Ok(()) => debug!("WebSub subscribe requested for {} at {}", sub.topic_url, sub.hub_url),
+        Err(e) => {
+            warn!("WebSub subscribe for {} at {} failed: {}", sub.topic_url, sub.hub_url, e);
+            set_status(db_pool, sub.id, STATUS_FAILED, Some(&e), false).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Reconcile a feed's subscription with the hub it currently advertises. Called after every
+/// successful fetch+parse of the feed; errors are logged, never propagated into the refresh.
+pub async fn sync_subscription(db_pool: &DatabasePool, feed_url: &str, links: Option<HubLinks>) {
+    let Some(base) = callback_base() else {
+        return;
+    };
+    if let Err(e) = sync_subscription_inner(db_pool, &base, feed_url, links).await {
+        warn!("WebSub sync for {} failed: {}", feed_url, e);
+    }
+}
+
+async fn sync_subscription_inner(
+    db_pool: &DatabasePool,
+    base: &str,
+    feed_url: &str,
+    links: Option<HubLinks>,
+) -> AppResult<()> {
+    let existing = load_by_feed(db_pool, feed_url).await?;
+
+    match (existing, links) {
+        (None, None) => {}
+        (None, Some(links)) => {
+            let token = uuid::Uuid::new_v4().simple().to_string();
+            let topic = links.topic.unwrap_or_else(|| feed_url.to_string());
+            let secret = new_secret();
+            match db_pool {
+                DatabasePool::Postgres(pool) => {
+                    sqlx::query(
+                        r#"INSERT INTO "WebSubSubscriptions" (feedurl, huburl, topicurl, callbacktoken, secret, status)
+                           VALUES ($1, $2, $3, $4, $5, $6)"#,
+                    )
+                    .bind(feed_url)
+                    .bind(&links.hub)
+                    .bind(&topic)
+                    .bind(&token)
+                    .bind(&secret)
+                    .bind(STATUS_PENDING)
+                    .execute(pool)
+                    .await?;
+                }
+                DatabasePool::MySQL(pool) => {
+                    sqlx::query(
+                        "INSERT INTO WebSubSubscriptions (FeedURL, HubURL, TopicURL, CallbackToken, Secret, Status)
+                         VALUES (?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(feed_url)
+                    .bind(&links.hub)
+                    .bind(&topic)
+                    .bind(&token)
+                    .bind(&secret)
+                    .bind(STATUS_PENDING)
+                    .execute(pool)
+                    .await?;
+                }
+            }
+            if let Some(sub) = load_by_token(db_pool, &token).await? {
+                info!("Feed {} advertises WebSub hub {}; subscribing", feed_url, sub.hub_url);
+                subscribe(db_pool, base, &sub).await?;
+            }
+        }
+        (Some(sub), None) => {
+            // The hub went away: drop back to polling and tell the old hub, if it ever accepted us.
+            if sub.status == STATUS_VERIFIED || sub.status == STATUS_PENDING {
+                info!("Feed {} no longer advertises a WebSub hub; unsubscribing", feed_url);
+                set_status(db_pool, sub.id, STATUS_UNSUBSCRIBING, None, true).await?;
+                if let Err(e) = request_hub(base, &sub, "unsubscribe").await {
+                    debug!("WebSub unsubscribe for {} failed: {}", feed_url, e);
+                    delete_subscription(db_pool, sub.id).await?;
+                }
+            } else if sub.status != STATUS_UNSUBSCRIBING {
+                delete_subscription(db_pool, sub.id).await?;
+            }
+        }
+        (Some(sub), Some(links)) => {
+            let topic = links.topic.unwrap_or_else(|| feed_url.to_string());
+            if sub.hub_url != links.hub || sub.topic_url != topic {
+                info!("WebSub hub/topic changed for {}; resubscribing at {}", feed_url, links.hub);
+                if sub.status == STATUS_VERIFIED {
+                    let _ = request_hub(base, &sub, "unsubscribe").await;
+                }
+                update_target(db_pool, sub.id, &links.hub, &topic).await?;
+                if let Some(sub) = load_by_token(db_pool, &sub.token).await? {
+                    subscribe(db_pool, base, &sub).await?;
+                }
+            } else if (sub.status == STATUS_FAILED || sub.status == STATUS_DENIED)
+                && requested_before(db_pool, sub.id, 24 * 60).await?
+            {
+                // Retry a refused or unreachable hub at most once a day.
+                set_status(db_pool, sub.id, STATUS_PENDING, None, true).await?;
+                subscribe(db_pool, base, &sub).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn update_target(db_pool: &DatabasePool, id: i32, hub: &str, topic: &str) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"UPDATE "WebSubSubscriptions"
+                   SET huburl = $1, topicurl = $2, status = $3, expiresat = NULL, lasterror = NULL,
+                       requestedat = CURRENT_TIMESTAMP
+                   WHERE subscriptionid = $4"#,
+            )
+            .bind(hub)
+            .bind(topic)
+            .bind(STATUS_PENDING)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "UPDATE WebSubSubscriptions
+                 SET HubURL = ?, TopicURL = ?, Status = ?, ExpiresAt = NULL, LastError = NULL,
+                     RequestedAt = CURRENT_TIMESTAMP
+                 WHERE SubscriptionID = ?",
+            )
+            .bind(hub)
+            .bind(topic)
+            .bind(STATUS_PENDING)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn requested_before(db_pool: &DatabasePool, id: i32, minutes: i64) -> AppResult<bool> {
+    let row = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"SELECT 1 FROM "WebSubSubscriptions"
+                   WHERE subscriptionid = $1 AND requestedat < NOW() - (INTERVAL '1 minute' * $2)"#,
+            )
+            .bind(id)
+            .bind(minutes as f64)
+            .fetch_optional(pool)
+            .await?
+            .is_some()
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "SELECT 1 FROM WebSubSubscriptions
+                 WHERE SubscriptionID = ? AND RequestedAt < NOW() - INTERVAL ? MINUTE",
+            )
+            .bind(id)
+            .bind(minutes)
+            .fetch_optional(pool)
+            .await?
+            .is_some()
+        }
+    };
+    Ok(row)
+}
+
+/// Record a hub's verification of our subscribe request and start the lease.
+pub async fn mark_verified(db_pool: &DatabasePool, id: i32, lease_seconds: i64) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"UPDATE "WebSubSubscriptions"
+                   SET status = $1, leaseseconds = $2, lasterror = NULL,
+                       expiresat = NOW() + (INTERVAL '1 second' * $2)
+                   WHERE subscriptionid = $3"#,
+            )
+            .bind(STATUS_VERIFIED)
+            .bind(lease_seconds as i32)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "UPDATE WebSubSubscriptions
+                 SET Status = ?, LeaseSeconds = ?, LastError = NULL,
+                     ExpiresAt = NOW() + INTERVAL ? SECOND
+                 WHERE SubscriptionID = ?",
+            )
+            .bind(STATUS_VERIFIED)
+            .bind(lease_seconds as i32)
+            .bind(lease_seconds)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The hub refused the subscription (or later cancelled it): fall back to polling.
+pub async fn mark_denied(db_pool: &DatabasePool, id: i32, reason: Option<&str>) -> AppResult<()> {
+    set_status(db_pool, id, STATUS_DENIED, Some(reason.unwrap_or("Denied by hub")), false).await
+}
+
+/// The hub confirmed our unsubscribe request.
+pub async fn confirm_unsubscribed(db_pool: &DatabasePool, id: i32) -> AppResult<()> {
+    delete_subscription(db_pool, id).await
+}
+
+pub async fn record_push(db_pool: &DatabasePool, id: i32) -> AppResult<()> {
+    match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(r#"UPDATE "WebSubSubscriptions" SET lastpushat = CURRENT_TIMESTAMP WHERE subscriptionid = $1"#)
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query("UPDATE WebSubSubscriptions SET LastPushAt = CURRENT_TIMESTAMP WHERE SubscriptionID = ?")
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Scheduler job: renew leases that expire within a day, give up on subscriptions the hub never
+/// verified, retry unreachable hubs daily, and clear unsubscribes the hub never confirmed.
+pub async fn renew_leases(db_pool: &DatabasePool) -> AppResult<()> {
+    let Some(base) = callback_base() else {
+        return Ok(());
+    };
+
+    // Pending for over an hour: the hub is not going to verify; fall back to polling.
+    // Unsubscribing for over a day: the hub never confirmed; forget the row.
+    let (renew, retry) = match db_pool {
+        DatabasePool::Postgres(pool) => {
+            sqlx::query(
+                r#"UPDATE "WebSubSubscriptions" SET status = $1, lasterror = 'Hub did not verify the subscription'
+                   WHERE status = $2 AND requestedat < NOW() - INTERVAL '1 hour'"#,
+            )
+            .bind(STATUS_FAILED)
+            .bind(STATUS_PENDING)
+            .execute(pool)
+            .await?;
+            sqlx::query(r#"DELETE FROM "WebSubSubscriptions" WHERE status = $1 AND requestedat < NOW() - INTERVAL '1 day'"#)
+                .bind(STATUS_UNSUBSCRIBING)
+                .execute(pool)
+                .await?;
+
+            let sql = format!(
+                r#"SELECT {} FROM "WebSubSubscriptions"
+                   WHERE status = $1 AND (expiresat IS NULL OR expiresat < NOW() + INTERVAL '1 day')
+                     AND requestedat < NOW() - INTERVAL '1 hour'"#,
+                PG_COLUMNS
+            );
+            let renew = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                .bind(STATUS_VERIFIED)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(subscription_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+            let sql = format!(
+                r#"SELECT {} FROM "WebSubSubscriptions" WHERE status = $1 AND requestedat < NOW() - INTERVAL '1 day'"#,
+                PG_COLUMNS
+            );
+            let retry = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                .bind(STATUS_FAILED)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(subscription_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+            (renew, retry)
+        }
+        DatabasePool::MySQL(pool) => {
+            sqlx::query(
+                "UPDATE WebSubSubscriptions SET Status = ?, LastError = 'Hub did not verify the subscription'
+                 WHERE Status = ? AND RequestedAt < NOW() - INTERVAL 1 HOUR",
+            )
+            .bind(STATUS_FAILED)
+            .bind(STATUS_PENDING)
+            .execute(pool)
+            .await?;
+            sqlx::query("DELETE FROM WebSubSubscriptions WHERE Status = ? AND RequestedAt < NOW() - INTERVAL 1 DAY")
+                .bind(STATUS_UNSUBSCRIBING)
+                .execute(pool)
+                .await?;
+
+            let sql = format!(
+                "SELECT {} FROM WebSubSubscriptions
+                 WHERE Status = ? AND (ExpiresAt IS NULL OR ExpiresAt < NOW() + INTERVAL 1 DAY)
+                   AND RequestedAt < NOW() - INTERVAL 1 HOUR",
+                MYSQL_COLUMNS
+            );
+            let renew = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                .bind(STATUS_VERIFIED)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(subscription_from_mysql_row)
+                .collect::<Result<Vec<_>, _>>()?;
+            let sql = format!(
+                "SELECT {} FROM WebSubSubscriptions WHERE Status = ? AND RequestedAt < NOW() - INTERVAL 1 DAY",
+                MYSQL_COLUMNS
+            );
+            let retry = sqlx::query(sqlx::AssertSqlSafe(sql.as_str()))
+                .bind(STATUS_FAILED)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(subscription_from_mysql_row)
+                .collect::<Result<Vec<_>, _>>()?;
+            (renew, retry)
+        }
+    };
+
+    if !renew.is_empty() || !retry.is_empty() {
+        info!("WebSub: renewing {} leases, retrying {} failed subscriptions", renew.len(), retry.len());
+    }
+    for sub in renew {
+        // Stays verified (and keeps its current lease) until the hub re-verifies.
+        set_status(db_pool, sub.id, STATUS_VERIFIED, None, true).await?;
+        subscribe(db_pool, &base, &sub).await?;
+    }
+    for sub in retry {
+        set_status(db_pool, sub.id, STATUS_PENDING, None, true).await?;
+        subscribe(db_pool, &base, &sub).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discovers_hub_and_self_links_in_channel() {
+        let rss = r#"<rss xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+            <title>Show</title><link>https://example.com</link>
+            <atom:link rel="self" type="application/rss+xml" href="https://example.com/feed.xml?a=1&amp;b=2"/>
+            <atom:link rel="hub" href="https://pubsubhubbub.appspot.com/"/>
+            <item><atom:link rel="hub" href="https://other.example/"/></item>
+        </channel></rss>"#;
+        assert_eq!(
+            discover(rss),
+            Some(HubLinks {
+                hub: "https://pubsubhubbub.appspot.com/".into(),
+                topic: Some("https://example.com/feed.xml?a=1&b=2".into()),
+            })
+        );
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><link rel="hub" href="https://hub.example/"/></feed>"#;
+        assert_eq!(discover(atom).map(|l| l.hub), Some("https://hub.example/".into()));
+
+        let hub_only_in_item = r#"<rss><channel><item><atom:link rel="hub" href="https://x/"/></item></channel></rss>"#;
+        assert_eq!(discover(hub_only_in_item), None);
+    }
+
+    #[test]
+    fn verifies_hub_signatures() {
+        let body = b"<rss>new episode</rss>";
+        let mut mac = <Hmac<sha2::Sha256> as Mac>::new_from_slice(b"s3cret").unwrap();
+        mac.update(body);
+        let header = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(verify_signature("s3cret", Some(&header), body));
+        assert!(!verify_signature("wrong", Some(&header), body));
+        assert!(!verify_signature("s3cret", Some(&header), b"tampered"));
+        assert!(!verify_signature("s3cret", None, body));
+        assert!(!verify_signature("s3cret", Some("md5=abcd"), body));
+    }
+}
@@ -0,0 +1,571 @@
+//! YouTube channels and playlists read directly, without the search `Backend` service.
+//!
+//! Channel/playlist details (name, description, artwork) and the newest 50 entries, with titles,
+//! thumbnails, durations and approximate publish dates, come from one `yt-dlp --flat-playlist`
+//! call, which needs no API key. YouTube's public Atom feeds (`/feeds/videos.xml?channel_id=` or
+//! `?playlist_id=`) add exact publish dates and descriptions for the newest 15, and are the
+//! fallback when yt-dlp can't list the source.
+//!
+//! Audio is downloaded in the format YouTube serves it (usually Opus or AAC) and only remuxed, so
+//! files are `<id>.opus`/`<id>.m4a`; `<id>.mp3` files from earlier versions are still found by
+//! [`audio_path`]. A video download is optional and per user, recorded in `DownloadedVideos`.
+
+use chrono::{DateTime, Utc};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// Shared audio downloads, one file per video.
+pub const AUDIO_DIR: &str = "/opt/pinepods/downloads/youtube";
+
+/// Per-user video downloads live under `<VIDEO_DIR>/<user_id>/`.
+pub const VIDEO_DIR: &str = "/opt/pinepods/downloads/youtube/video";
+
+/// Extensions an audio download may have, in lookup order. `mp3.mp3` is the double extension
+/// older yt-dlp invocations produced.
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "opus", "ogg", "webm", "aac", "mp3.mp3"];
+
+/// What a YouTube "podcast" follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YouTubeSource {
+    Channel(String),
+    Playlist(String),
+}
+
+fn is_id(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let query = url.split_once('?')?.1;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.split('#').next().unwrap_or(value))
+}
+
+impl YouTubeSource {
+    /// Recognise a channel or playlist ID or URL. `@handle` URLs need [`resolve`].
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(list) = query_param(input, "list").filter(|l| is_id(l)) {
+            return Some(Self::Playlist(list.to_string()));
+        }
+        if let Some(rest) = input.split("/channel/").nth(1) {
+            let id = rest.split(['/', '?', '&', '#']).next().unwrap_or("");
+            return is_id(id).then(|| Self::Channel(id.to_string()));
+        }
+        if input.contains('/') || input.contains('.') || !is_id(input) {
+            return None;
+        }
+        // Channel IDs start with UC; playlist IDs with PL (user playlists), OL (albums), UU
+        // (a channel's uploads) and so on.
+        if input.starts_with("UC") {
+            Some(Self::Channel(input.to_string()))
+        } else if input.len() > 12 {
+            Some(Self::Playlist(input.to_string()))
+        } else {
+            None
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Channel(id) | Self::Playlist(id) => id,
+        }
+    }
+
+    /// The URL stored as the podcast's feed URL, which is also what [`parse`](Self::parse) reads
+    /// back on refresh.
+    pub fn feed_url(&self) -> String {
+        match self {
+            Self::Channel(id) => format!("https://www.youtube.com/channel/{}", id),
+            Self::Playlist(id) => format!("https://www.youtube.com/playlist?list={}", id),
+        }
+    }
+
+    fn atom_url(&self) -> String {
+        match self {
+            Self::Channel(id) => format!("https://www.youtube.com/feeds/videos.xml?channel_id={}", id),
+            Self::Playlist(id) => format!("https://www.youtube.com/feeds/videos.xml?playlist_id={}", id),
+        }
+    }
+
+    /// The page yt-dlp lists: a channel's Videos tab (so Shorts and streams stay out) or the
+    /// playlist itself.
+    fn listing_url(&self) -> String {
+        match self {
+            Self::Channel(id) => format!("https://www.youtube.com/channel/{}/videos", id),
+            Self::Playlist(_) => self.feed_url(),
+        }
+    }
+}
+
+/// One upload, from the Atom feed or the yt-dlp listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upload {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub published: DateTime<Utc>,
+}
+
+/// A channel's or playlist's details from yt-dlp.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceInfo {
+    pub name: String,
+    pub description: String,
+    pub thumbnail_url: String,
+    /// Video ID to duration in seconds, for the newest entries.
+    pub durations: HashMap<String, i64>,
+    /// The newest entries that came with a publish date, in listing order.
+    pub entries: Vec<Upload>,
+}
+
+/// Parse a YouTube Atom feed into its title and uploads.
+pub fn parse_atom(xml: &str) -> (String, Vec<Upload>) {
+    let mut reader = Reader::from_str(xml);
+    let mut feed_title = String::new();
+    let mut uploads = Vec::new();
+    let mut entry: Option<Upload> = None;
+    let mut field: Option<Vec<u8>> = None;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                b"entry" => {
+                    entry = Some(Upload {
+                        id: String::new(),
+                        title: String::new(),
+                        description: String::new(),
+                        thumbnail: String::new(),
+                        published: Utc::now(),
+                    })
+                }
+                name @ (b"yt:videoId" | b"title" | b"published" | b"media:description") => {
+                    field = Some(name.to_vec());
+                    text.clear();
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if e.name().as_ref() == b"media:thumbnail" => {
+                if let Some(entry) = entry.as_mut() {
+                    if let Some(url) = e.attributes().flatten().find(|a| a.key.as_ref() == b"url") {
+                        entry.thumbnail = String::from_utf8_lossy(&url.value).replace("&amp;", "&");
+                    }
+                }
+            }
+            Ok(Event::Text(ref e)) if field.is_some() => {
+                if let Ok(t) = e.xml10_content() {
+                    text.push_str(&t);
+                }
+            }
+            Ok(Event::GeneralRef(ref e)) if field.is_some() => match e.resolve_char_ref() {
+                Ok(Some(ch)) => text.push(ch),
+                _ => {
+                    if let Some(resolved) = e.decode().ok().and_then(|name| resolve_predefined_entity(&name)) {
+                        text.push_str(resolved);
+                    }
+                }
+            },
+            Ok(Event::End(ref e)) => {
+                let name = e.name();
+                if field.as_deref() == Some(name.as_ref()) {
+                    field = None;
+                    let value = text.trim().to_string();
+                    match (entry.as_mut(), name.as_ref()) {
+                        (None, b"title") => feed_title = value,
+                        (Some(entry), b"yt:videoId") => entry.id = value,
+                        (Some(entry), b"title") => entry.title = value,
+                        (Some(entry), b"media:description") => entry.description = value,
+                        (Some(entry), b"published") => {
+                            if let Ok(published) = DateTime::parse_from_rfc3339(&value) {
+                                entry.published = published.with_timezone(&Utc);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if name.as_ref() == b"entry" {
+                    if let Some(entry) = entry.take().filter(|e| !e.id.is_empty()) {
+                        uploads.push(entry);
+                    }
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    (feed_title, uploads)
+}
+
+/// Map `yt-dlp --flat-playlist --dump-single-json` output onto [`SourceInfo`].
+pub fn parse_listing(listing: &serde_json::Value) -> SourceInfo {
+    let text = |key: &str| listing.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    // Thumbnails are listed smallest first; prefer the square avatar over the banner.
+    let thumbnail_url = listing
+        .get("thumbnails")
+        .and_then(|t| t.as_array())
+        .and_then(|thumbnails| {
+            let url = |t: &serde_json::Value| t.get("url").and_then(|u| u.as_str()).map(str::to_string);
+            thumbnails
+                .iter()
+                .rev()
+                .find(|t| t.get("id").and_then(|i| i.as_str()) == Some("avatar_uncropped"))
+                .and_then(url)
+                .or_else(|| thumbnails.last().and_then(url))
+        })
+        .unwrap_or_default();
+    let durations = listing
+        .get("entries")
+        .and_then(|e| e.as_array())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| {
+                    let id = e.get("id")?.as_str()?.to_string();
+                    let duration = e.get("duration")?.as_f64()?;
+                    Some((id, duration.round() as i64))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let entries = listing
+        .get("entries")
+        .and_then(|e| e.as_array())
+        .map(|entries| entries.iter().filter_map(listing_upload).collect())
+        .unwrap_or_default();
+
+    // A channel's Videos tab is titled "<name> - Videos"; a playlist keeps its own title.
+    let is_channel = !text("channel_id").is_empty() && text("id") == text("channel_id");
+    let name = if is_channel && !text("channel").is_empty() { text("channel") } else { text("title") };
+
+    SourceInfo {
+        name,
+        description: text("description").chars().take(500).collect(),
+        thumbnail_url,
+        durations,
+        entries,
+    }
+}
+
+/// An upload from a flat listing entry. Entries without any publish date are dropped, since the
+/// feed cutoff can't be applied to them.
+fn listing_upload(entry: &serde_json::Value) -> Option<Upload> {
+    let id = entry.get("id")?.as_str().filter(|id| is_id(id))?.to_string();
+    let published = ["timestamp", "release_timestamp"]
+        .iter()
+        .find_map(|key| entry.get(*key)?.as_i64())
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .or_else(|| {
+            let date = entry.get("upload_date")?.as_str()?;
+            let date = chrono::NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+            Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+        })?;
+    let text = |key: &str| entry.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let thumbnail = entry
+        .get("thumbnails")
+        .and_then(|t| t.as_array())
+        .and_then(|t| t.last())
+        .and_then(|t| t.get("url"))
+        .and_then(|u| u.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id));
+    Some(Upload { title: text("title"), description: text("description"), thumbnail, published, id })
+}
+
+/// Combine the Atom feed's uploads with the listing's, newest first. The Atom entry wins for a
+/// video in both, since its date is exact and it carries the description.
+pub fn merge_uploads(atom: Vec<Upload>, listing: &[Upload]) -> Vec<Upload> {
+    let mut uploads = atom;
+    for entry in listing {
+        if !uploads.iter().any(|u| u.id == entry.id) {
+            uploads.push(entry.clone());
+        }
+    }
+    uploads.sort_by_key(|u| std::cmp::Reverse(u.published));
+    uploads
+}
+
+async fn fetch_atom(source: &YouTubeSource) -> Result<(String, Vec<Upload>), String> {
+    let response = reqwest::Client::new()
+        .get(source.atom_url())
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("YouTube feed request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("YouTube feed returned {} for {}", response.status(), source.id()));
+    }
+    let xml = response.text().await.map_err(|e| format!("YouTube feed read failed: {}", e))?;
+    Ok(parse_atom(&xml))
+}
+
+/// The newest uploads, newest first: the entries of `info` (from [`fetch_info`]) merged with the
+/// public Atom feed. Fails only when neither has anything.
+pub async fn fetch_uploads(source: &YouTubeSource, info: &SourceInfo) -> Result<Vec<Upload>, String> {
+    let atom = match fetch_atom(source).await {
+        Ok((_, uploads)) => uploads,
+        Err(e) if !info.entries.is_empty() => {
+            warn!("{}; using the yt-dlp listing only", e);
+            Vec::new()
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(merge_uploads(atom, &info.entries))
+}
+
+/// The channel or playlist title from the Atom feed.
+pub async fn fetch_title(source: &YouTubeSource) -> Result<String, String> {
+    let (title, _) = fetch_atom(source).await?;
+    Ok(title)
+}
+
+/// Details and recent entries for a channel or playlist from yt-dlp.
+pub async fn fetch_info(source: &YouTubeSource) -> Result<SourceInfo, String> {
+    let output = Command::new("yt-dlp")
+        .args(["--flat-playlist", "--dump-single-json", "--playlist-items", "1-50", "--socket-timeout", "30"])
+        // Flat channel listings only carry dates ("3 weeks ago") when asked to approximate them.
+        .args(["--extractor-args", "youtubetab:approximate_date"])
+        .arg(source.listing_url())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("yt-dlp listing failed: {}", stderr.lines().last().unwrap_or("")));
+    }
+    let listing: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Unreadable yt-dlp listing: {}", e))?;
+    Ok(parse_listing(&listing))
+}
+
+/// Turn user input into a source, asking yt-dlp for the channel behind an `@handle` or custom URL.
+pub async fn resolve(input: &str) -> Result<YouTubeSource, String> {
+    if let Some(source) = YouTubeSource::parse(input) {
+        return Ok(source);
+    }
+    let input = input.trim();
+    let url = if input.starts_with('@') {
+        format!("https://www.youtube.com/{}", input)
+    } else if input.contains("youtube.com/") {
+        input.to_string()
+    } else {
+        return Err(format!("Not a YouTube channel or playlist: {}", input));
+    };
+    let output = Command::new("yt-dlp")
+        .args(["--flat-playlist", "--dump-single-json", "--playlist-items", "0", "--socket-timeout", "30"])
+        .arg(&url)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    let listing: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    listing
+        .get("channel_id")
+        .and_then(|c| c.as_str())
+        .filter(|c| is_id(c))
+        .map(|c| YouTubeSource::Channel(c.to_string()))
+        .ok_or_else(|| format!("Could not find a YouTube channel at {}", url))
+}
+
+/// Every downloaded audio file for a video (normally one).
+pub fn audio_files(video_id: &str) -> Vec<PathBuf> {
+    AUDIO_EXTENSIONS
+        .iter()
+        .map(|ext| Path::new(AUDIO_DIR).join(format!("{}.{}", video_id, ext)))
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+/// The downloaded audio for a video, if any.
+pub fn audio_path(video_id: &str) -> Option<PathBuf> {
+    audio_files(video_id).into_iter().next()
+}
+
+/// Where a user's video download of `video_id` is written.
+pub fn video_path(user_id: i32, video_id: &str) -> PathBuf {
+    Path::new(VIDEO_DIR).join(user_id.to_string()).join(format!("{}.mp4", video_id))
+}
+
+/// The watch page of a YouTube video.
+pub fn watch_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", video_id)
+}
+
+async fn run_yt_dlp(args: &[&str], page_url: &str) -> Result<Option<PathBuf>, String> {
+    let output = Command::new("yt-dlp")
+        .args(args)
+        .args(["--no-playlist", "--socket-timeout", "30", "--print", "after_move:filepath"])
+        .arg(page_url)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("yt-dlp download failed: {}", stderr));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().last().map(|l| PathBuf::from(l.trim())).filter(|p| p.is_file()))
+}
+
+/// Download the audio of the video at `page_url` without re-encoding and return the file, named
+/// after `video_id`. Entries from other sites ([`media_source`](super::media_source)) pass their
+/// own page and namespaced ID.
+pub async fn download_audio(video_id: &str, page_url: &str) -> Result<PathBuf, String> {
+    tokio::fs::create_dir_all(AUDIO_DIR)
+        .await
+        .map_err(|e| format!("cannot create {}: {}", AUDIO_DIR, e))?;
+    let template = format!("{}/{}.%(ext)s", AUDIO_DIR, video_id);
+    // -x without --audio-format keeps the source codec and only drops the video stream.
+    let printed = run_yt_dlp(&["--format", "bestaudio/best", "--extract-audio", "--output", &template], page_url).await?;
+    debug!("yt-dlp wrote audio for {} to {:?}", video_id, printed);
+    printed
+        .or_else(|| audio_path(video_id))
+        .ok_or_else(|| format!("yt-dlp finished but no audio file was found for {}", video_id))
+}
+
+/// Download a video (MP4 where the site offers it) for one user and return the file.
+pub async fn download_video(user_id: i32, video_id: &str, page_url: &str) -> Result<PathBuf, String> {
+    let dest = video_path(user_id, video_id);
+    if let Some(dir) = dest.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    let template = dest.with_extension("%(ext)s").to_string_lossy().to_string();
+    let printed = run_yt_dlp(
+        &[
+            "--format",
+            "bv*[ext=mp4]+ba[ext=m4a]/b[ext=mp4]/bv*+ba/b",
+            "--merge-output-format",
+            "mp4",
+            "--output",
+            &template,
+        ],
+        page_url,
+    )
+    .await?;
+    match printed {
+        Some(path) => Ok(path),
+        None if dest.is_file() => Ok(dest),
+        None => Err(format!("yt-dlp finished but no video file was found for {}", video_id)),
+    }
+}
+
+/// Duration of a downloaded file: mp3 frames when it's MP3, otherwise ffprobe. Both block, so
+/// they run on the blocking pool.
+pub async fn file_duration(path: &Path) -> Option<i32> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let is_mp3 = path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("mp3"));
+        if is_mp3 {
+            if let Some(duration) = crate::handlers::youtube::get_mp3_duration(&path.to_string_lossy()).filter(|d| *d > 0) {
+                return Some(duration);
+            }
+        }
+        let duration = crate::services::media_tags::probe(&path).and_then(|t| t.duration);
+        if duration.is_none() {
+            warn!("Could not read duration from {}", path.display());
+        }
+        duration
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_channel_and_playlist_inputs() {
+        let channel = YouTubeSource::Channel("UCabcdefghijklmnopqrstuv".into());
+        assert_eq!(YouTubeSource::parse("UCabcdefghijklmnopqrstuv"), Some(channel.clone()));
+        assert_eq!(YouTubeSource::parse("https://www.youtube.com/channel/UCabcdefghijklmnopqrstuv/videos"), Some(channel.clone()));
+        assert_eq!(YouTubeSource::parse(&channel.feed_url()), Some(channel));
+
+        let playlist = YouTubeSource::Playlist("PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG".into());
+        assert_eq!(
+            YouTubeSource::parse("https://www.youtube.com/watch?v=abc&list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG&index=2"),
+            Some(playlist.clone())
+        );
+        assert_eq!(YouTubeSource::parse(&playlist.feed_url()), Some(playlist));
+        assert_eq!(YouTubeSource::parse("https://www.youtube.com/@somehandle"), None);
+    }
+
+    #[test]
+    fn reads_channel_listing() {
+        let listing = serde_json::json!({
+            "id": "UCabc", "channel_id": "UCabc", "channel": "Tom", "title": "Tom - Videos",
+            "description": "About",
+            "thumbnails": [
+                {"url": "https://yt3/banner", "id": "banner_uncropped"},
+                {"url": "https://yt3/avatar", "id": "avatar_uncropped"}
+            ],
+            "entries": [
+                {"id": "v1", "title": "One", "duration": 61.6, "timestamp": 1714557600},
+                {"id": "v2", "title": "Two", "duration": null, "upload_date": "20240420"},
+                {"id": "v3", "title": "Three", "duration": 30}
+            ]
+        });
+        let info = parse_listing(&listing);
+        assert_eq!(info.name, "Tom");
+        assert_eq!(info.thumbnail_url, "https://yt3/avatar");
+        assert_eq!(info.durations, HashMap::from([("v1".to_string(), 62), ("v3".to_string(), 30)]));
+        let ids: Vec<_> = info.entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["v1", "v2"]);
+        assert_eq!(info.entries[1].published.to_rfc3339(), "2024-04-20T00:00:00+00:00");
+        assert_eq!(info.entries[1].thumbnail, "https://i.ytimg.com/vi/v2/hqdefault.jpg");
+    }
+
+    #[test]
+    fn merges_listing_beyond_the_atom_feed() {
+        let upload = |id: &str, day: u32, title: &str| Upload {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+            thumbnail: String::new(),
+            published: chrono::NaiveDate::from_ymd_opt(2024, 5, day).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc(),
+        };
+        let atom = vec![upload("a", 3, "from atom")];
+        let listing = vec![upload("a", 2, "from listing"), upload("b", 4, "newer"), upload("c", 1, "older")];
+        let merged = merge_uploads(atom, &listing);
+        let ids: Vec<_> = merged.iter().map(|u| u.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a", "c"]);
+        assert_eq!(merged[1].title, "from atom");
+    }
+
+    #[test]
+    fn reads_atom_entries() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+ <title>Tom &amp; Friends</title>
+ <entry>
+  <yt:videoId>dQw4w9WgXcQ</yt:videoId>
+  <title>Episode &#8220;1&#8221;</title>
+  <published>2024-05-01T10:00:00+00:00</published>
+  <media:group>
+   <media:title>Episode 1</media:title>
+   <media:thumbnail url="https://i1.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg" width="480" height="360"/>
+   <media:description>Notes &lt;here&gt;</media:description>
+  </media:group>
+ </entry>
+</feed>"#;
+        let (title, uploads) = parse_atom(xml);
+        assert_eq!(title, "Tom & Friends");
+        assert_eq!(uploads.len(), 1);
+        assert_eq!(uploads[0].id, "dQw4w9WgXcQ");
+        assert_eq!(uploads[0].title, "Episode \u{201c}1\u{201d}");
+        assert_eq!(uploads[0].description, "Notes <here>");
+        assert_eq!(uploads[0].thumbnail, "https://i1.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg");
+        assert_eq!(uploads[0].published.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+    }
+}
@@ -6,6 +6,7 @@ use crate::requests::episode::Episode;
 use crate::requests::login_requests::GetUserDetails;
 use crate::requests::login_requests::LoginServerRequest;
 use crate::requests::login_requests::GetApiDetails;
+use crate::requests::playback_sync_reqs::{PlaybackDevice, RemotePlayback};
 use crate::requests::pod_req::PodcastResponseExtra;
 
 use crate::requests::pod_req::{
@@ -208,6 +209,17 @@ impl EpisodeStatusState {
     }
 }
 
+/// The user's other devices as seen over the playback websocket (see `playback_sync_reqs`).
+/// `remote` is the latest state reported by a device other than this one; it is cleared when
+/// this device becomes the most recent player.
+#[derive(Default, Clone, PartialEq, Store, Debug)]
+pub struct PlaybackSyncState {
+    pub connected: bool,
+    pub devices: Vec<PlaybackDevice>,
+    pub remote: Option<RemotePlayback>,
+    pub last_seq: i64,
+}
+
 /// Loading state kept separate from AppState so that the ~50+ components
 /// subscribing to AppState do NOT re-render on every page navigation or fetch.
 #[derive(Default, Deserialize, Clone, PartialEq, Store, Debug)]
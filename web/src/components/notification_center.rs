@@ -67,6 +67,23 @@ impl TaskProgress {
     }
 }
 
+fn is_finished(task: &TaskProgress) -> bool {
+    matches!(task.status.as_str(), "SUCCESS" | "FAILED" | "CANCELLED")
+}
+
+// Task types that run on the server's job queue and can be cancelled while queued or running
+fn is_cancellable(task: &TaskProgress) -> bool {
+    !is_finished(task)
+        && matches!(
+            task.r#type.as_str(),
+            "download_episode"
+                | "podcast_download"
+                | "download_video"
+                | "transcribe_episode"
+                | "detect_ads"
+        )
+}
+
 #[function_component(NotificationCenter)]
 pub fn notification_center() -> Html {
     let (i18n, _) = use_translation();
@@ -99,6 +116,8 @@ pub fn notification_center() -> Html {
     let i18n_finalizing = i18n.t("notification_center.status_finalizing").to_string();
     let i18n_completed = i18n.t("notification_center.status_completed").to_string();
     let i18n_failed = i18n.t("notification_center.status_failed").to_string();
+    let i18n_cancelled = i18n.t("notification_center.status_cancelled").to_string();
+    let i18n_cancel_task = i18n.t("notification_center.cancel_task").to_string();
 
     // Task type translations
     let i18n_download = i18n.t("notification_center.task_download").to_string();
@@ -212,7 +231,7 @@ pub fn notification_center() -> Html {
     } else {
         active_tasks
             .iter()
-            .filter(|task| !(task.status == "SUCCESS" || task.status == "FAILED"))
+            .filter(|task| !is_finished(task))
             .cloned()
             .collect::<Vec<_>>()
     };
@@ -220,7 +239,7 @@ pub fn notification_center() -> Html {
     // Count active (non-completed) tasks for badge
     let active_count = active_tasks
         .iter()
-        .filter(|task| !(task.status == "SUCCESS" || task.status == "FAILED"))
+        .filter(|task| !is_finished(task))
         .count();
 
     // Count notifications - active tasks plus any error or info messages
@@ -261,12 +280,35 @@ pub fn notification_center() -> Html {
         Callback::from(move |_| {
             dispatch.reduce_mut(|state| {
                 if let Some(ref mut tasks) = state.active_tasks {
-                    tasks.retain(|task| !(task.status == "SUCCESS" || task.status == "FAILED"));
+                    tasks.retain(|task| !is_finished(task));
                 }
             });
         })
     };
 
+    // Handle cancel of a queued or running job
+    let cancel_task = {
+        let dispatch = dispatch.clone();
+        Callback::from(move |task_id: String| {
+            let dispatch = dispatch.clone();
+            let app_state = Dispatch::<AppState>::global().get();
+            let auth = app_state.auth_details.as_ref().and_then(|auth| {
+                auth.api_key
+                    .clone()
+                    .map(|api_key| (auth.server_name.clone(), api_key))
+            });
+            if let Some((server_name, api_key)) = auth {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = crate::requests::task_reqs::cancel_task(&server_name, &api_key, &task_id).await {
+                        dispatch.reduce_mut(|state| {
+                            state.error_message = Some(e.to_string());
+                        });
+                    }
+                });
+            }
+        })
+    };
+
     // Handle dismiss single task
     let dismiss_task = {
         let dispatch = dispatch.clone();
@@ -402,7 +444,7 @@ pub fn notification_center() -> Html {
                                                 <div class="flex justify-between items-center">
                                                     <h4 class="text-sm font-medium px-2 py-1">{if *show_completed { &i18n_all_tasks } else { &i18n_active_tasks }}</h4>
                                                     {
-                                                        if filtered_tasks.iter().any(|t| t.status == "SUCCESS" || t.status == "FAILED") {
+                                                        if filtered_tasks.iter().any(is_finished) {
                                                             html! {
                                                                 <button
                                                                     class="text-xs px-2 py-1 rounded hover:bg-opacity-20"
@@ -423,6 +465,12 @@ pub fn notification_center() -> Html {
                                                         let task_id = task.task_id.clone();
                                                         let task_dismiss = dismiss_task.clone();
                                                         let on_dismiss = Callback::from(move |_| task_dismiss.emit(task_id.clone()));
+                                                        let cancellable = is_cancellable(task);
+                                                        let on_cancel = {
+                                                            let task_id = task.task_id.clone();
+                                                            let cancel_task = cancel_task.clone();
+                                                            Callback::from(move |_: MouseEvent| cancel_task.emit(task_id.clone()))
+                                                        };
 
                                                         // Determine status styling
                                                         let status_str = task.status.as_str();
@@ -435,6 +483,7 @@ pub fn notification_center() -> Html {
                                                             "FINALIZING" => ("status-started", i18n_finalizing.as_str()),
                                                             "SUCCESS" => ("status-success", i18n_completed.as_str()),
                                                             "FAILED" => ("status-failed", i18n_failed.as_str()),
+                                                            "CANCELLED" => ("status-failed", i18n_cancelled.as_str()),
                                                             _ => ("status-started", status_str),
                                                         };
 
@@ -492,13 +541,30 @@ pub fn notification_center() -> Html {
                                                                             {status_text}
                                                                         </span>
                                                                     </div>
-                                                                    <button
-                                                                        class="dismiss-button text-xs hover:opacity-70"
-                                                                        onclick={on_dismiss}
-                                                                        title={i18n_dismiss_notification.clone()}
-                                                                    >
-                                                                        <i class="ph ph-x"></i>
-                                                                    </button>
+                                                                    <div class="flex items-center">
+                                                                        {
+                                                                            if cancellable {
+                                                                                html! {
+                                                                                    <button
+                                                                                        class="dismiss-button text-xs hover:opacity-70 mr-2"
+                                                                                        onclick={on_cancel}
+                                                                                        title={i18n_cancel_task.clone()}
+                                                                                    >
+                                                                                        <i class="ph ph-stop-circle"></i>
+                                                                                    </button>
+                                                                                }
+                                                                            } else {
+                                                                                html! {}
+                                                                            }
+                                                                        }
+                                                                        <button
+                                                                            class="dismiss-button text-xs hover:opacity-70"
+                                                                            onclick={on_dismiss}
+                                                                            title={i18n_dismiss_notification.clone()}
+                                                                        >
+                                                                            <i class="ph ph-x"></i>
+                                                                        </button>
+                                                                    </div>
                                                                 </div>
                                                                 {
                                                                     if !status_detail.is_empty() {
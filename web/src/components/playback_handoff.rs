@@ -0,0 +1,192 @@
+use crate::components::audio::on_play_click;
+use crate::components::context::{AppState, PlaybackSyncState, UIState};
+use crate::components::gen_funcs::format_time;
+use crate::requests::episode::Episode;
+use crate::requests::playback_sync_reqs::{
+    device_id, report_local_state, send_remote_command, start_playback_sync, RemoteAction,
+};
+use i18nrs::yew::use_translation;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+/// Paused sessions older than this are history, not something to hand off.
+const HANDOFF_WINDOW_SECONDS: i64 = 12 * 3600;
+/// How often a playing device reports its position, so a handoff resumes close to where it was.
+const POSITION_REPORT_MS: u32 = 15_000;
+
+/// Global "continue on this device" banner, and the component that keeps this client on the
+/// per-user playback channel (see `playback_sync_reqs`).
+///
+/// Reports local playback whenever the episode or play/pause state changes, and every 15s
+/// while playing. When another device is playing (or recently paused) a different episode it
+/// offers to continue it here from the same position, or to play/pause it remotely while that
+/// device is still connected.
+#[function_component(PlaybackHandoff)]
+pub fn playback_handoff() -> Html {
+    let (i18n, _) = use_translation();
+    let credentials = use_selector(|state: &AppState| {
+        match (
+            state.auth_details.as_ref().map(|auth| auth.server_name.clone()),
+            state.auth_details.as_ref().and_then(|auth| auth.api_key.clone()),
+            state.user_details.as_ref().map(|user| user.UserID),
+        ) {
+            (Some(server_name), Some(api_key), Some(user_id)) => Some((server_name, api_key, user_id)),
+            _ => None,
+        }
+    });
+    // Only what the reports depend on, so position ticks don't re-render this component
+    let local = use_selector(|state: &UIState| {
+        (
+            state.currently_playing.as_ref().map(|playing| playing.episode_id),
+            state.audio_playing.unwrap_or(false),
+        )
+    });
+    let (sync_state, _) = use_store::<PlaybackSyncState>();
+    let ui_dispatch = Dispatch::<UIState>::global();
+    let dismissed_seq = use_state(|| None::<i64>);
+
+    use_effect_with((*credentials).clone(), |credentials| {
+        if let Some((server_name, api_key, user_id)) = credentials.clone() {
+            start_playback_sync(server_name, user_id, api_key);
+        }
+        || ()
+    });
+
+    {
+        let connected = sync_state.connected;
+        use_effect_with((*local, connected), move |((_, playing), connected)| {
+            if *connected {
+                report_local_state(&Dispatch::<UIState>::global().get());
+            }
+            let interval = playing.then(|| {
+                gloo_timers::callback::Interval::new(POSITION_REPORT_MS, || {
+                    report_local_state(&Dispatch::<UIState>::global().get());
+                })
+            });
+            move || drop(interval)
+        });
+    }
+
+    let Some((server_name, api_key, user_id)) = (*credentials).clone() else {
+        return Html::default();
+    };
+    let Some(remote) = sync_state.remote.clone() else {
+        return Html::default();
+    };
+    let (local_episode_id, _) = *local;
+    let age_seconds = (js_sys::Date::now() / 1000.0) as i64 - remote.updated_at;
+    let recent = remote.playing || age_seconds < HANDOFF_WINDOW_SECONDS;
+    if remote.device_id == device_id()
+        || remote.episode_id.is_none()
+        || remote.episode_id == local_episode_id
+        || !recent
+        || *dismissed_seq == Some(remote.seq)
+    {
+        return Html::default();
+    }
+    let Some(episode) = remote
+        .episode
+        .clone()
+        .and_then(|value| serde_json::from_value::<Episode>(value).ok())
+    else {
+        return Html::default();
+    };
+    let remote_connected = sync_state
+        .devices
+        .iter()
+        .any(|device| device.device_id == remote.device_id);
+
+    let on_continue = {
+        let remote = remote.clone();
+        let episode = episode.clone();
+        let ui_dispatch = ui_dispatch.clone();
+        let dismissed_seq = dismissed_seq.clone();
+        Callback::from(move |e: MouseEvent| {
+            let mut episode = episode.clone();
+            episode.listenduration = remote.position as i32;
+            // Hand off rather than play on both
+            if remote.playing {
+                send_remote_command(&remote.device_id, RemoteAction::Pause, None);
+            }
+            dismissed_seq.set(Some(remote.seq));
+            on_play_click(
+                episode,
+                api_key.clone(),
+                user_id,
+                server_name.clone(),
+                ui_dispatch.clone(),
+                ui_dispatch.get(),
+                false,
+                false,
+                None,
+            )
+            .emit(e);
+        })
+    };
+
+    let on_remote_toggle = {
+        let target = remote.device_id.clone();
+        let action = if remote.playing {
+            RemoteAction::Pause
+        } else {
+            RemoteAction::Play
+        };
+        Callback::from(move |_: MouseEvent| send_remote_command(&target, action, None))
+    };
+
+    let on_dismiss = {
+        let dismissed_seq = dismissed_seq.clone();
+        let seq = remote.seq;
+        Callback::from(move |_: MouseEvent| dismissed_seq.set(Some(seq)))
+    };
+
+    let status = if remote.playing {
+        i18n.t("playback_handoff.playing_on")
+    } else {
+        i18n.t("playback_handoff.paused_on")
+    }
+    .replace("{device}", &remote.device_name);
+
+    html! {
+        <div class="fixed bottom-24 left-1/2 transform -translate-x-1/2 z-50 w-11/12 max-w-md">
+            <div class="item-container p-4 shadow-lg">
+                <div class="flex items-start justify-between">
+                    <div class="min-w-0 mr-2">
+                        <p class="item_container-text text-xs opacity-75">{ status }</p>
+                        <p class="item_container-text font-semibold truncate">{ &episode.episodetitle }</p>
+                        <p class="item_container-text text-xs">
+                            { format!("{} · {}", episode.podcastname, format_time(remote.position as i32)) }
+                        </p>
+                    </div>
+                    <button
+                        onclick={on_dismiss}
+                        class="dismiss-button text-xs hover:opacity-70"
+                        title={i18n.t("playback_handoff.dismiss")}
+                    >
+                        <i class="ph ph-x text-lg"></i>
+                    </button>
+                </div>
+                <div class="flex justify-end space-x-2 mt-3">
+                    if remote_connected {
+                        <button
+                            onclick={on_remote_toggle}
+                            class="px-3 py-1 text-sm rounded-md border border-solid item_container-text hover:opacity-70"
+                        >
+                            { if remote.playing {
+                                i18n.t("playback_handoff.pause_there")
+                            } else {
+                                i18n.t("playback_handoff.play_there")
+                            } }
+                        </button>
+                    }
+                    <button
+                        onclick={on_continue}
+                        class="px-3 py-1 text-sm bg-button-color text-button-text-color rounded-md hover:bg-hover-color transition-colors"
+                    >
+                        { i18n.t("playback_handoff.continue_here") }
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -4,6 +4,7 @@ mod requests;
 
 use crate::components::navigation::NavigationHandler;
 use crate::components::oauth_callback::OAuthCallback;
+use crate::components::playback_handoff::PlaybackHandoff;
 use crate::components::restore_overlay::RestoreOverlay;
 use crate::components::collection_picker_modal::CollectionPickerModal;
 use crate::pages::downloads::Downloads;
@@ -251,6 +252,7 @@ fn language_manager() -> Html {
             <NavigationHandler>
                 <Switch<Route> render={switch} />
             </NavigationHandler>
+            <PlaybackHandoff />
             <RestoreOverlay />
             <CollectionPickerModal />
         </BrowserRouter>
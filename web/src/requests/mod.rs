@@ -4,6 +4,7 @@ pub(crate) mod episode;
 pub(crate) mod login_requests;
 pub(crate) mod models;
 pub(crate) mod people_req;
+pub(crate) mod playback_sync_reqs;
 pub(crate) mod pod_req;
 pub(crate) mod search_pods;
 pub(crate) mod setting_reqs;
@@ -0,0 +1,395 @@
+// playback_sync_reqs.rs
+//
+// Client for the per-user playback websocket (/ws/api/playback/{user_id}). Every signed-in
+// client reports what it is playing, so the others can offer "continue on this device" and
+// send play/pause/seek commands to it. The connection is kept open for the life of the app
+// and re-established with backoff; each (re)connect starts from the server's snapshot.
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::{SinkExt, StreamExt};
+use gloo::net::websocket::{futures::WebSocket, Message};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::console;
+use yewdux::prelude::*;
+
+use crate::components::context::{AppState, EpisodeStatusState, PlaybackSyncState, UIState};
+use crate::requests::pod_req::{call_get_queued_episodes, QueuedEpisodesResponse};
+
+const DEVICE_ID_KEY: &str = "pinepods_device_id";
+const MIN_RECONNECT_MS: u32 = 1_000;
+const MAX_RECONNECT_MS: u32 = 30_000;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PlaybackDevice {
+    pub device_id: String,
+    pub device_name: String,
+    #[serde(default)]
+    pub last_seen: i64,
+}
+
+/// What another device last reported playing.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RemotePlayback {
+    pub device_id: String,
+    pub device_name: String,
+    pub episode_id: Option<i32>,
+    #[serde(default)]
+    pub is_youtube: bool,
+    #[serde(default)]
+    pub position: f64,
+    #[serde(default)]
+    pub playing: bool,
+    #[serde(default)]
+    pub episode: Option<Value>,
+    pub seq: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteAction {
+    Play,
+    Pause,
+    Seek,
+    Stop,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PlaybackReport {
+    pub episode_id: Option<i32>,
+    pub is_youtube: bool,
+    pub position: f64,
+    pub playing: bool,
+    pub episode: Option<Value>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    State(PlaybackReport),
+    Command {
+        target: String,
+        action: RemoteAction,
+        position: Option<f64>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Snapshot {
+        now_playing: Option<RemotePlayback>,
+        devices: Vec<PlaybackDevice>,
+    },
+    State(RemotePlayback),
+    Command {
+        source_name: String,
+        action: RemoteAction,
+        position: Option<f64>,
+    },
+    Devices {
+        devices: Vec<PlaybackDevice>,
+    },
+    QueueChanged,
+}
+
+thread_local! {
+    // Sender for the open connection's writer; None while disconnected
+    static OUTBOX: RefCell<Option<UnboundedSender<String>>> = const { RefCell::new(None) };
+    static STARTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// This install's ID on the playback channel, generated once and kept in localStorage so a
+/// reconnect or page reload is recognised as the same device.
+pub fn device_id() -> String {
+    let storage = web_sys::window().and_then(|w| w.local_storage().ok().flatten());
+    if let Some(id) = storage
+        .as_ref()
+        .and_then(|s| s.get_item(DEVICE_ID_KEY).ok().flatten())
+        .filter(|id| !id.is_empty())
+    {
+        return id;
+    }
+
+    let id = format!(
+        "{:08x}{:08x}",
+        (js_sys::Math::random() * u32::MAX as f64) as u32,
+        (js_sys::Math::random() * u32::MAX as f64) as u32
+    );
+    if let Some(storage) = storage {
+        let _ = storage.set_item(DEVICE_ID_KEY, &id);
+    }
+    id
+}
+
+#[cfg(not(feature = "server_build"))]
+fn device_name() -> String {
+    "PinePods Desktop".to_string()
+}
+
+#[cfg(feature = "server_build")]
+fn device_name() -> String {
+    let navigator = match web_sys::window() {
+        Some(window) => window.navigator(),
+        None => return "Web browser".to_string(),
+    };
+    let agent = navigator.user_agent().unwrap_or_default();
+    // Order matters: Edge and Opera also claim Chrome, and Chrome claims Safari
+    let browser = if agent.contains("Firefox/") {
+        "Firefox"
+    } else if agent.contains("Edg/") {
+        "Edge"
+    } else if agent.contains("OPR/") {
+        "Opera"
+    } else if agent.contains("Chrome/") {
+        "Chrome"
+    } else if agent.contains("Safari/") {
+        "Safari"
+    } else {
+        "Web browser"
+    };
+    match navigator.platform().ok().filter(|p| !p.is_empty()) {
+        Some(platform) => format!("{} on {}", browser, platform),
+        None => browser.to_string(),
+    }
+}
+
+fn send_message(message: &ClientMessage) {
+    let Ok(json) = serde_json::to_string(message) else {
+        return;
+    };
+    OUTBOX.with(|outbox| {
+        if let Some(sender) = outbox.borrow().as_ref() {
+            let _ = sender.unbounded_send(json);
+        }
+    });
+}
+
+/// The local player's state in the shape the server expects.
+pub fn current_report(ui_state: &UIState) -> PlaybackReport {
+    match &ui_state.currently_playing {
+        Some(playing) => PlaybackReport {
+            episode_id: Some(playing.episode_id),
+            is_youtube: playing.is_youtube,
+            position: ui_state
+                .media_element
+                .as_ref()
+                .map(|media| media.current_time())
+                .unwrap_or(ui_state.current_time_seconds),
+            playing: ui_state.audio_playing.unwrap_or(false),
+            episode: serde_json::to_value(&playing.episode).ok(),
+        },
+        None => PlaybackReport {
+            episode_id: None,
+            is_youtube: false,
+            position: 0.0,
+            playing: false,
+            episode: None,
+        },
+    }
+}
+
+/// Tell the other devices what this one is playing now.
+pub fn report_local_state(ui_state: &UIState) {
+    send_message(&ClientMessage::State(current_report(ui_state)));
+}
+
+/// Ask another of the user's devices to play, pause, seek or stop.
+pub fn send_remote_command(target: &str, action: RemoteAction, position: Option<f64>) {
+    send_message(&ClientMessage::Command {
+        target: target.to_string(),
+        action,
+        position,
+    });
+}
+
+/// Open the playback channel for the signed-in user. Safe to call on every render: only the
+/// first call connects, and the connection loop ends by itself once the user signs out.
+pub fn start_playback_sync(server_name: String, user_id: i32, api_key: String) {
+    if STARTED.with(|started| started.replace(true)) {
+        return;
+    }
+
+    spawn_local(async move {
+        let mut backoff = MIN_RECONNECT_MS;
+        loop {
+            let signed_in = Dispatch::<AppState>::global()
+                .get()
+                .auth_details
+                .as_ref()
+                .and_then(|auth| auth.api_key.clone())
+                .is_some_and(|key| key == api_key);
+            if !signed_in {
+                break;
+            }
+
+            if run_connection(&server_name, user_id, &api_key).await {
+                backoff = MIN_RECONNECT_MS;
+            }
+            Dispatch::<PlaybackSyncState>::global().reduce_mut(|state| state.connected = false);
+
+            gloo_timers::future::TimeoutFuture::new(backoff).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_MS);
+        }
+
+        Dispatch::<PlaybackSyncState>::global().set(PlaybackSyncState::default());
+        STARTED.with(|started| started.set(false));
+    });
+}
+
+/// One connection's lifetime. Returns whether it got as far as the server's snapshot, which
+/// resets the reconnect backoff.
+async fn run_connection(server_name: &str, user_id: i32, api_key: &str) -> bool {
+    let clean_server_name = server_name
+        .trim_start_matches("http://")
+        .trim_start_matches("https://");
+    let ws_protocol = if server_name.starts_with("https://") {
+        "wss://"
+    } else {
+        "ws://"
+    };
+    let url = format!(
+        "{}{}/ws/api/playback/{}?api_key={}&device_id={}&device_name={}",
+        ws_protocol,
+        clean_server_name,
+        user_id,
+        api_key,
+        device_id(),
+        utf8_percent_encode(&device_name(), NON_ALPHANUMERIC)
+    );
+
+    let websocket = match WebSocket::open(&url) {
+        Ok(websocket) => websocket,
+        Err(e) => {
+            console::warn_1(&format!("Failed to open playback WebSocket: {:?}", e).into());
+            return false;
+        }
+    };
+    let (mut write, mut read) = websocket.split();
+
+    let (sender, mut receiver) = unbounded::<String>();
+    OUTBOX.with(|outbox| *outbox.borrow_mut() = Some(sender));
+    spawn_local(async move {
+        while let Some(json) = receiver.next().await {
+            if write.send(Message::Text(json)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut synced = false;
+    while let Some(message) = read.next().await {
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Bytes(_)) => continue,
+            Err(e) => {
+                console::warn_1(&format!("Playback WebSocket error: {:?}", e).into());
+                break;
+            }
+        };
+        let message = match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(message) => message,
+            Err(e) => {
+                console::warn_1(&format!("Unrecognised playback message: {}", e).into());
+                continue;
+            }
+        };
+        if matches!(message, ServerMessage::Snapshot { .. }) && !synced {
+            synced = true;
+            Dispatch::<PlaybackSyncState>::global().reduce_mut(|state| state.connected = true);
+            // Whatever changed here while disconnected wasn't seen by anyone
+            report_local_state(&Dispatch::<UIState>::global().get());
+        }
+        handle_server_message(message, server_name, user_id, api_key);
+    }
+
+    // Dropping the sender ends the writer task
+    OUTBOX.with(|outbox| *outbox.borrow_mut() = None);
+    synced
+}
+
+fn handle_server_message(message: ServerMessage, server_name: &str, user_id: i32, api_key: &str) {
+    let own_device = device_id();
+    let sync = Dispatch::<PlaybackSyncState>::global();
+    match message {
+        ServerMessage::Snapshot {
+            now_playing,
+            devices,
+        } => sync.reduce_mut(|state| {
+            state.last_seq = now_playing.as_ref().map(|p| p.seq).unwrap_or(0);
+            state.remote = now_playing.filter(|p| p.device_id != own_device);
+            state.devices = devices;
+        }),
+        ServerMessage::State(playback) => {
+            if playback.seq <= sync.get().last_seq {
+                // Stale or duplicated, e.g. delivered twice across a reconnect
+                return;
+            }
+            sync.reduce_mut(|state| {
+                state.last_seq = playback.seq;
+                // Our own report coming back means we are now the most recent player
+                state.remote = Some(playback).filter(|p| p.device_id != own_device);
+            });
+        }
+        ServerMessage::Command {
+            source_name,
+            action,
+            position,
+        } => {
+            console::log_1(&format!("Playback command {:?} from {}", action, source_name).into());
+            apply_command(action, position);
+        }
+        ServerMessage::Devices { devices } => sync.reduce_mut(|state| state.devices = devices),
+        ServerMessage::QueueChanged => {
+            let server_name = server_name.to_string();
+            let api_key = Some(api_key.to_string());
+            spawn_local(async move {
+                match call_get_queued_episodes(&server_name, &api_key, &user_id).await {
+                    Ok(mut episodes) => {
+                        episodes.sort_by_key(|ep| ep.queueposition.unwrap_or(i32::MAX));
+                        let ids = episodes.iter().map(|ep| ep.episodeid).collect();
+                        Dispatch::<EpisodeStatusState>::global().reduce_mut(move |state| {
+                            state.queued_episodes = Some(QueuedEpisodesResponse { episodes });
+                            state.queued_episode_ids = Some(ids);
+                        });
+                    }
+                    Err(e) => console::warn_1(
+                        &format!("Failed to refresh queue after remote change: {:?}", e).into(),
+                    ),
+                }
+            });
+        }
+    }
+}
+
+/// Carry out a command another device sent to this one, then report the result.
+fn apply_command(action: RemoteAction, position: Option<f64>) {
+    let dispatch = Dispatch::<UIState>::global();
+    match action {
+        RemoteAction::Play => dispatch.reduce_mut(|state| {
+            if state.currently_playing.is_some() && !state.audio_playing.unwrap_or(false) {
+                state.toggle_playback();
+            }
+        }),
+        RemoteAction::Pause | RemoteAction::Stop => dispatch.reduce_mut(|state| {
+            if state.audio_playing.unwrap_or(false) {
+                state.toggle_playback();
+            }
+        }),
+        RemoteAction::Seek => {
+            let Some(position) = position else {
+                return;
+            };
+            dispatch.reduce_mut(|state| {
+                if let Some(media) = &state.media_element {
+                    media.set_current_time(position);
+                }
+                state.update_current_time(position);
+            });
+        }
+    }
+    report_local_state(&dispatch.get());
+}
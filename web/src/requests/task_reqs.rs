@@ -91,7 +91,7 @@ impl From<BackendTaskInfo> for TaskProgress {
         }
 
         // Check if task is completed before moving values
-        let is_completed = backend_task.status == "SUCCESS" || backend_task.status == "FAILED";
+        let is_completed = matches!(backend_task.status.as_str(), "SUCCESS" | "FAILED" | "CANCELLED");
 
         TaskProgress {
             task_id: backend_task.id,
@@ -350,6 +350,7 @@ pub async fn connect_to_task_websocket(
                                             // Add a timestamp for auto-removal of completed tasks
                                             completion_time: if raw_task.status == "SUCCESS"
                                                 || raw_task.status == "FAILED"
+                                                || raw_task.status == "CANCELLED"
                                             {
                                                 Some(js_sys::Date::now())
                                             } else {
@@ -357,13 +358,13 @@ pub async fn connect_to_task_websocket(
                                             },
                                         };
 
-                                        // If a podcast download failed, revert the optimistic
-                                        // "downloaded" marker the UI set when the task was queued.
+                                        // If a podcast download failed or was cancelled, revert the
+                                        // optimistic "downloaded" marker the UI set when the task was queued.
                                         // The failure payload omits item_id and uses the stored
                                         // task type ("download_episode"), so recover the episode
                                         // id from the task entry recorded during earlier progress
                                         // updates (which carried item_id + "podcast_download").
-                                        if task.status == "FAILED"
+                                        if (task.status == "FAILED" || task.status == "CANCELLED")
                                             && (task.r#type == "download_episode"
                                                 || task.r#type == "podcast_download")
                                         {
@@ -543,6 +544,28 @@ pub async fn fetch_active_tasks(
     }
 }
 
+// Cancel a queued or running download, transcription, ad detection or refresh job. The
+// websocket delivers the resulting CANCELLED update.
+pub async fn cancel_task(server_name: &str, api_key: &str, task_id: &str) -> Result<(), Error> {
+    let url = format!("{}/api/tasks/{}/cancel", server_name, task_id);
+
+    let response = Request::post(&url)
+        .header("Content-Type", "application/json")
+        .header("X-Api-Key", api_key)
+        .send()
+        .await?;
+
+    if response.ok() {
+        Ok(())
+    } else {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Failed to read error message".to_string());
+        Err(Error::msg(format!("Failed to cancel task: {}", error_text)))
+    }
+}
+
 // Initialize WebSocket connection or fall back to REST API
 pub fn init_task_monitoring(state: &AppState, dispatch: Dispatch<NotificationState>) {
     if let (Some(user_id), Some(Some(api_key)), Some(server_name)) = (